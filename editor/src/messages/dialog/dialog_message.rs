@@ -20,6 +20,10 @@ pub enum DialogMessage {
 		title: String,
 		description: String,
 	},
+	DisplayExportProgress {
+		frame: usize,
+		frame_count: usize,
+	},
 	RequestAboutGraphiteDialog,
 	RequestAboutGraphiteDialogWithLocalizedCommitDate {
 		localized_commit_date: String,
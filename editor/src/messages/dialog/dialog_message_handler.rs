@@ -43,6 +43,10 @@ impl MessageHandler<DialogMessage, DialogMessageData<'_>> for DialogMessageHandl
 				let dialog = simple_dialogs::ErrorDialog { title, description };
 				dialog.send_dialog_to_frontend(responses);
 			}
+			DialogMessage::DisplayExportProgress { frame, frame_count } => {
+				let dialog = simple_dialogs::ExportProgressDialog { frame, frame_count };
+				dialog.send_dialog_to_frontend(responses);
+			}
 			DialogMessage::RequestAboutGraphiteDialog => {
 				responses.add(FrontendMessage::TriggerAboutGraphiteLocalizedCommitDate {
 					commit_date: env!("GRAPHITE_GIT_COMMIT_DATE").into(),
@@ -1,4 +1,5 @@
-use crate::messages::frontend::utility_types::{ExportBounds, FileType};
+use crate::messages::frontend::utility_types::{AnimationFormat, BitDepth, ColorProfile, ExportBounds, FileType};
+use crate::messages::portfolio::document::utility_types::document_metadata::LayerNodeIdentifier;
 use crate::messages::prelude::*;
 
 #[impl_message(Message, DialogMessage, ExportDialog)]
@@ -8,6 +9,26 @@ pub enum ExportDialogMessage {
 	ScaleFactor(f64),
 	TransparentBackground(bool),
 	ExportBounds(ExportBounds),
+	Quality(f64),
+	Lossless(bool),
+	BitDepth(BitDepth),
+	ColorProfile(ColorProfile),
+	RoundNumbers(bool),
+	Precision(u32),
+	FlattenTransforms(bool),
+	MergeStyles(bool),
+	MinifyIds(bool),
+	StripMetadata(bool),
+	Animated(bool),
+	AnimationFormat(AnimationFormat),
+	AnimationStartTime(f64),
+	AnimationDuration(f64),
+	AnimationFrameRate(f64),
+	BatchExport(bool),
+	BatchScaleFactors(Vec<f64>),
+	NameTemplate(String),
+	ArtboardIncluded { artboard: LayerNodeIdentifier, included: bool },
+	ArtboardFileType { artboard: LayerNodeIdentifier, file_type: FileType },
 
 	Submit,
 }
@@ -1,7 +1,9 @@
-use crate::messages::frontend::utility_types::{ExportBounds, FileType};
+use crate::consts::FILE_SAVE_SUFFIX;
+use crate::messages::frontend::utility_types::{AnimationExportSettings, AnimationFormat, BatchExportFile, BitDepth, ColorProfile, ExportBounds, FileType, RasterExportOptions};
 use crate::messages::layout::utility_types::widget_prelude::*;
 use crate::messages::portfolio::document::utility_types::document_metadata::LayerNodeIdentifier;
 use crate::messages::prelude::*;
+use graphene_core::renderer::SvgExportOptions;
 
 pub struct ExportDialogMessageData<'a> {
 	pub portfolio: &'a PortfolioMessageHandler,
@@ -14,8 +16,35 @@ pub struct ExportDialogMessageHandler {
 	pub scale_factor: f64,
 	pub bounds: ExportBounds,
 	pub transparent_background: bool,
+	pub raster_options: RasterExportOptions,
+	pub svg_options: SvgExportOptions,
+	/// The decimal places to round SVG numbers to, remembered while rounding is turned off.
+	pub svg_precision: u32,
 	pub artboards: HashMap<LayerNodeIdentifier, String>,
 	pub has_selection: bool,
+	/// Export each artboard as its own file (or one file per scale factor), bundled together into an archive.
+	pub batch: bool,
+	pub batch_scale_factors: Vec<f64>,
+	/// The file name of each batch exported file, where `{document}`, `{artboard}` and `{scale}` are replaced by the document name, artboard label and scale suffix (like `@2x`).
+	pub name_template: String,
+	pub artboard_settings: HashMap<LayerNodeIdentifier, BatchArtboardSettings>,
+	/// Export a range of frames of the animation instead of a single image.
+	pub animated: bool,
+	pub animation: AnimationExportSettings,
+}
+
+/// The choices made for one artboard in a batch export.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchArtboardSettings {
+	pub included: bool,
+	/// Overrides the dialog's file type for this artboard.
+	pub file_type: Option<FileType>,
+}
+
+impl Default for BatchArtboardSettings {
+	fn default() -> Self {
+		Self { included: true, file_type: None }
+	}
 }
 
 impl Default for ExportDialogMessageHandler {
@@ -25,8 +54,17 @@ impl Default for ExportDialogMessageHandler {
 			scale_factor: 1.,
 			bounds: Default::default(),
 			transparent_background: false,
+			raster_options: Default::default(),
+			svg_options: Default::default(),
+			svg_precision: 3,
 			artboards: Default::default(),
 			has_selection: false,
+			batch: false,
+			batch_scale_factors: vec![1., 2., 3.],
+			name_template: "{artboard}{scale}".to_string(),
+			artboard_settings: Default::default(),
+			animated: false,
+			animation: Default::default(),
 		}
 	}
 }
@@ -36,18 +74,74 @@ impl MessageHandler<ExportDialogMessage, ExportDialogMessageData<'_>> for Export
 		let ExportDialogMessageData { portfolio } = data;
 
 		match message {
-			ExportDialogMessage::FileType(export_type) => self.file_type = export_type,
+			ExportDialogMessage::FileType(export_type) => {
+				self.file_type = export_type;
+				// Choosing the file type for the whole batch replaces the per-artboard choices
+				self.artboard_settings.values_mut().for_each(|settings| settings.file_type = None);
+			}
 			ExportDialogMessage::ScaleFactor(factor) => self.scale_factor = factor,
 			ExportDialogMessage::TransparentBackground(transparent_background) => self.transparent_background = transparent_background,
 			ExportDialogMessage::ExportBounds(export_area) => self.bounds = export_area,
+			ExportDialogMessage::Quality(quality) => self.raster_options.quality = quality,
+			ExportDialogMessage::Lossless(lossless) => self.raster_options.lossless = lossless,
+			ExportDialogMessage::BitDepth(bit_depth) => self.raster_options.bit_depth = bit_depth,
+			ExportDialogMessage::ColorProfile(color_profile) => self.raster_options.color_profile = color_profile,
+			ExportDialogMessage::RoundNumbers(round) => self.svg_options.precision = round.then_some(self.svg_precision),
+			ExportDialogMessage::Precision(precision) => {
+				self.svg_precision = precision;
+				self.svg_options.precision = Some(precision);
+			}
+			ExportDialogMessage::FlattenTransforms(flatten_transforms) => self.svg_options.flatten_transforms = flatten_transforms,
+			ExportDialogMessage::MergeStyles(merge_styles) => self.svg_options.merge_styles = merge_styles,
+			ExportDialogMessage::MinifyIds(minify_ids) => self.svg_options.minify_ids = minify_ids,
+			ExportDialogMessage::StripMetadata(strip_metadata) => self.svg_options.strip_metadata = strip_metadata,
+			ExportDialogMessage::Animated(animated) => self.animated = animated,
+			ExportDialogMessage::AnimationFormat(format) => self.animation.format = format,
+			ExportDialogMessage::AnimationStartTime(start_time) => self.animation.start_time = start_time,
+			ExportDialogMessage::AnimationDuration(duration) => self.animation.duration = duration,
+			ExportDialogMessage::AnimationFrameRate(frame_rate) => self.animation.frame_rate = frame_rate,
+			ExportDialogMessage::BatchExport(batch) => self.batch = batch,
+			ExportDialogMessage::BatchScaleFactors(scale_factors) => {
+				if !scale_factors.is_empty() {
+					self.batch_scale_factors = scale_factors;
+				}
+			}
+			ExportDialogMessage::NameTemplate(name_template) => self.name_template = name_template,
+			ExportDialogMessage::ArtboardIncluded { artboard, included } => self.artboard_settings.entry(artboard).or_default().included = included,
+			ExportDialogMessage::ArtboardFileType { artboard, file_type } => self.artboard_settings.entry(artboard).or_default().file_type = Some(file_type),
 
-			ExportDialogMessage::Submit => responses.add_front(PortfolioMessage::SubmitDocumentExport {
-				file_name: portfolio.active_document().map(|document| document.name.clone()).unwrap_or_default(),
-				file_type: self.file_type,
-				scale_factor: self.scale_factor,
-				bounds: self.bounds,
-				transparent_background: self.file_type != FileType::Jpg && self.transparent_background,
-			}),
+			ExportDialogMessage::Submit => {
+				let file_name = portfolio.active_document().map(|document| document.name.clone()).unwrap_or_default();
+
+				if self.animated {
+					responses.add_front(PortfolioMessage::SubmitAnimationExport {
+						file_name,
+						scale_factor: self.scale_factor,
+						bounds: self.bounds,
+						transparent_background: self.transparent_background,
+						settings: self.animation,
+					});
+				} else if self.batch && !self.artboards.is_empty() {
+					let document_name = file_name.strip_suffix(FILE_SAVE_SUFFIX).unwrap_or(&file_name).to_string();
+					responses.add_front(PortfolioMessage::SubmitBatchExport {
+						files: self.batch_files(&document_name),
+						archive_name: document_name,
+						transparent_background: self.transparent_background,
+						raster_options: self.raster_options,
+						svg_options: self.svg_options,
+					});
+				} else {
+					responses.add_front(PortfolioMessage::SubmitDocumentExport {
+						file_name,
+						file_type: self.file_type,
+						scale_factor: self.scale_factor,
+						bounds: self.bounds,
+						transparent_background: self.file_type != FileType::Jpg && self.transparent_background,
+						raster_options: self.raster_options,
+						svg_options: self.svg_options,
+					});
+				}
+			}
 		}
 
 		self.send_dialog_to_frontend(responses);
@@ -56,6 +150,93 @@ impl MessageHandler<ExportDialogMessage, ExportDialogMessageData<'_>> for Export
 	advertise_actions! {ExportDialogUpdate;}
 }
 
+impl ExportDialogMessageHandler {
+	fn artboard_settings(&self, artboard: LayerNodeIdentifier) -> BatchArtboardSettings {
+		self.artboard_settings.get(&artboard).copied().unwrap_or_default()
+	}
+
+	fn artboard_file_type(&self, artboard: LayerNodeIdentifier) -> FileType {
+		self.artboard_settings(artboard).file_type.unwrap_or(self.file_type)
+	}
+
+	/// The file types that will be written by the export, which decide the encoding options to show.
+	fn file_types(&self) -> Vec<FileType> {
+		if self.animated {
+			return Vec::new();
+		}
+		if !self.batch || self.artboards.is_empty() {
+			return vec![self.file_type];
+		}
+
+		self.artboards
+			.keys()
+			.filter(|&&artboard| self.artboard_settings(artboard).included)
+			.map(|&artboard| self.artboard_file_type(artboard))
+			.collect()
+	}
+
+	/// The artboards sorted by their labels, which is the order they are listed and exported in.
+	fn sorted_artboards(&self) -> Vec<(LayerNodeIdentifier, &str)> {
+		let mut artboards = self.artboards.iter().map(|(&layer, name)| (layer, name.as_str())).collect::<Vec<_>>();
+		artboards.sort_by(|(a_layer, a_name), (b_layer, b_name)| a_name.cmp(b_name).then_with(|| a_layer.to_node().cmp(&b_layer.to_node())));
+		artboards
+	}
+
+	/// Lists a file for every scale factor of every included artboard, or a single file for artboards exported to a vector format.
+	fn batch_files(&self, document_name: &str) -> Vec<BatchExportFile> {
+		let mut files = Vec::new();
+		let mut used_names = HashSet::new();
+
+		for (artboard, artboard_name) in self.sorted_artboards() {
+			if !self.artboard_settings(artboard).included {
+				continue;
+			}
+
+			let file_type = self.artboard_file_type(artboard);
+			let scale_factors = match file_type {
+				FileType::Svg | FileType::Pdf => vec![None],
+				_ => self.batch_scale_factors.iter().copied().map(Some).collect(),
+			};
+
+			for scale_factor in scale_factors {
+				let scale_suffix = scale_factor.map(|scale_factor| format!("@{scale_factor}x")).unwrap_or_default();
+				let base_name = self
+					.name_template
+					.replace("{document}", document_name)
+					.replace("{artboard}", artboard_name)
+					.replace("{scale}", &scale_suffix);
+				let base_name = if base_name.trim().is_empty() { artboard_name.to_string() } else { base_name };
+
+				// Keep file names unique within the archive by numbering any repeats
+				let mut file_name = base_name.clone();
+				let mut count = 1;
+				while !used_names.insert(format!("{file_name}.{file_type:?}").to_lowercase()) {
+					count += 1;
+					file_name = format!("{base_name} {count}");
+				}
+
+				files.push(BatchExportFile {
+					artboard,
+					file_name,
+					file_type,
+					scale_factor: scale_factor.unwrap_or(1.),
+				});
+			}
+		}
+
+		files
+	}
+}
+
+/// Reads a list of scale factors like "1, 2, 3" or "@1x @2x @3x", ignoring any entries which aren't positive numbers.
+fn parse_scale_factors(text: &str) -> Vec<f64> {
+	text.split(|c: char| c == ',' || c.is_whitespace())
+		.map(|entry| entry.trim().trim_start_matches('@').trim_end_matches(['x', 'X']))
+		.filter_map(|entry| entry.parse::<f64>().ok())
+		.filter(|&scale_factor| scale_factor.is_finite() && scale_factor > 0.)
+		.collect()
+}
+
 impl DialogLayoutHolder for ExportDialogMessageHandler {
 	const ICON: &'static str = "File";
 	const TITLE: &'static str = "Export";
@@ -80,10 +261,7 @@ impl DialogLayoutHolder for ExportDialogMessageHandler {
 
 impl LayoutHolder for ExportDialogMessageHandler {
 	fn layout(&self) -> Layout {
-		let entries = [(FileType::Png, "PNG"), (FileType::Jpg, "JPG"), (FileType::Svg, "SVG")]
-			.into_iter()
-			.map(|(val, name)| RadioEntryData::new(format!("{val:?}")).label(name).on_update(move |_| ExportDialogMessage::FileType(val).into()))
-			.collect();
+		let entries = file_type_entries(ExportDialogMessage::FileType);
 
 		let export_type = vec![
 			TextLabel::new("File Type").table_align(true).min_width(100).widget_holder(),
@@ -91,24 +269,47 @@ impl LayoutHolder for ExportDialogMessageHandler {
 			RadioInput::new(entries).selected_index(Some(self.file_type as u32)).widget_holder(),
 		];
 
-		let resolution = vec![
-			TextLabel::new("Scale Factor").table_align(true).min_width(100).widget_holder(),
-			Separator::new(SeparatorType::Unrelated).widget_holder(),
-			NumberInput::new(Some(self.scale_factor))
-				.unit("")
-				.min(0.)
-				.max((1_u64 << f64::MANTISSA_DIGITS) as f64)
-				.disabled(self.file_type == FileType::Svg)
-				.on_update(|number_input: &NumberInput| ExportDialogMessage::ScaleFactor(number_input.value.unwrap()).into())
-				.min_width(200)
-				.widget_holder(),
-		];
+		let batch = self.batch && !self.artboards.is_empty() && !self.animated;
+
+		let resolution = if batch {
+			let scale_factors = self.batch_scale_factors.iter().map(|scale_factor| scale_factor.to_string()).collect::<Vec<_>>().join(", ");
+			vec![
+				TextLabel::new("Scale Factors").table_align(true).min_width(100).widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				TextInput::new(scale_factors)
+					.tooltip("Comma-separated scale factors, each exported with its own suffix (like @2x) to raster formats")
+					.on_update(|text_input: &TextInput| ExportDialogMessage::BatchScaleFactors(parse_scale_factors(&text_input.value)).into())
+					.min_width(200)
+					.widget_holder(),
+			]
+		} else {
+			vec![
+				TextLabel::new("Scale Factor").table_align(true).min_width(100).widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				NumberInput::new(Some(self.scale_factor))
+					.unit("")
+					.min(0.)
+					.max((1_u64 << f64::MANTISSA_DIGITS) as f64)
+					.disabled(if self.animated {
+						matches!(self.animation.format, AnimationFormat::AnimatedSvg | AnimationFormat::AnimatedSvgCss | AnimationFormat::SvgSequence)
+					} else {
+						matches!(self.file_type, FileType::Svg | FileType::Pdf)
+					})
+					.on_update(|number_input: &NumberInput| ExportDialogMessage::ScaleFactor(number_input.value.unwrap()).into())
+					.min_width(200)
+					.widget_holder(),
+			]
+		};
 
 		let standard_bounds = vec![
 			(ExportBounds::AllArtwork, "All Artwork".to_string(), false),
 			(ExportBounds::Selection, "Selection".to_string(), !self.has_selection),
 		];
-		let artboards = self.artboards.iter().map(|(&layer, name)| (ExportBounds::Artboard(layer), name.to_string(), false)).collect();
+		let artboards = self
+			.sorted_artboards()
+			.into_iter()
+			.map(|(layer, name)| (ExportBounds::Artboard(layer), name.to_string(), false))
+			.collect();
 		let groups = [standard_bounds, artboards];
 
 		let current_bounds = if !self.has_selection && self.bounds == ExportBounds::Selection {
@@ -143,20 +344,396 @@ impl LayoutHolder for ExportDialogMessageHandler {
 			DropdownInput::new(entries).selected_index(Some(index as u32)).widget_holder(),
 		];
 
+		let batch_export = vec![
+			TextLabel::new("Artboards").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			CheckboxInput::new(batch)
+				.disabled(self.artboards.is_empty())
+				.tooltip("Export each artboard as its own file, bundled together into a ZIP archive")
+				.on_update(move |value: &CheckboxInput| ExportDialogMessage::BatchExport(value.checked).into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			TextLabel::new("Export Each Artboard").widget_holder(),
+		];
+
+		let name_template = vec![
+			TextLabel::new("File Names").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			TextInput::new(&self.name_template)
+				.tooltip("The name of each file, where {document}, {artboard} and {scale} are replaced by the document name, artboard name and scale suffix")
+				.on_update(|text_input: &TextInput| ExportDialogMessage::NameTemplate(text_input.value.clone()).into())
+				.min_width(200)
+				.widget_holder(),
+		];
+
+		let artboard_rows = self.sorted_artboards().into_iter().map(|(artboard, name)| {
+			let included = self.artboard_settings(artboard).included;
+			let entries = file_type_entries(move |file_type| ExportDialogMessage::ArtboardFileType { artboard, file_type });
+
+			let widgets = vec![
+				TextLabel::new(name).table_align(true).min_width(100).widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				CheckboxInput::new(included)
+					.on_update(move |value: &CheckboxInput| ExportDialogMessage::ArtboardIncluded { artboard, included: value.checked }.into())
+					.widget_holder(),
+				Separator::new(SeparatorType::Related).widget_holder(),
+				RadioInput::new(entries)
+					.selected_index(Some(self.artboard_file_type(artboard) as u32))
+					.disabled(!included)
+					.widget_holder(),
+			];
+			LayoutGroup::Row { widgets }
+		});
+
+		let file_types = self.file_types();
+		let lossless_webp = file_types.contains(&FileType::Webp) && self.raster_options.lossless;
+		let lossy_webp = file_types.contains(&FileType::Webp) && !self.raster_options.lossless;
+
+		let quality = vec![
+			TextLabel::new("Quality").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			NumberInput::new(Some(self.raster_options.quality))
+				.unit("%")
+				.min(1.)
+				.max(100.)
+				.tooltip("Higher quality keeps more detail, while lower quality makes smaller JPG, WebP and AVIF files")
+				.on_update(|number_input: &NumberInput| ExportDialogMessage::Quality(number_input.value.unwrap()).into())
+				.min_width(200)
+				.widget_holder(),
+		];
+
+		let lossless = vec![
+			TextLabel::new("Lossless").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			CheckboxInput::new(self.raster_options.lossless)
+				.tooltip("Encode WebP files without any loss of detail, instead of using the quality setting")
+				.on_update(move |value: &CheckboxInput| ExportDialogMessage::Lossless(value.checked).into())
+				.widget_holder(),
+		];
+
+		let bit_depth_entries = [(BitDepth::Eight, "8-bit"), (BitDepth::Sixteen, "16-bit")]
+			.into_iter()
+			.map(|(val, name)| RadioEntryData::new(format!("{val:?}")).label(name).on_update(move |_| ExportDialogMessage::BitDepth(val).into()))
+			.collect();
+		let bit_depth = vec![
+			TextLabel::new("Bit Depth")
+				.table_align(true)
+				.min_width(100)
+				.tooltip("The width of the samples in sRGB PNG and TIFF files. The artwork is still drawn with 8 bits per channel, so 16-bit files don't hold any more detail or remove banding, they only suit workflows that require them")
+				.widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			RadioInput::new(bit_depth_entries).selected_index(Some(self.raster_options.bit_depth as u32)).widget_holder(),
+		];
+
+		let color_profile_entries = [(ColorProfile::Srgb, "sRGB"), (ColorProfile::LinearSrgb, "Linear sRGB")]
+			.into_iter()
+			.map(|(val, name)| RadioEntryData::new(format!("{val:?}")).label(name).on_update(move |_| ExportDialogMessage::ColorProfile(val).into()))
+			.collect();
+		let color_profile = vec![
+			TextLabel::new("Color Profile")
+				.table_align(true)
+				.min_width(100)
+				.tooltip("The color space of the pixels in PNG, lossless WebP and TIFF files, which is embedded as an ICC profile")
+				.widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			RadioInput::new(color_profile_entries).selected_index(Some(self.raster_options.color_profile as u32)).widget_holder(),
+		];
+
+		let precision = vec![
+			TextLabel::new("Precision")
+				.table_align(true)
+				.min_width(100)
+				.tooltip("Round the coordinates and sizes in SVG files to this many decimal places, making them smaller")
+				.widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			CheckboxInput::new(self.svg_options.precision.is_some())
+				.on_update(move |value: &CheckboxInput| ExportDialogMessage::RoundNumbers(value.checked).into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(self.svg_precision as f64))
+				.int()
+				.min(0.)
+				.max(9.)
+				.unit(" decimals")
+				.disabled(self.svg_options.precision.is_none())
+				.on_update(|number_input: &NumberInput| ExportDialogMessage::Precision(number_input.value.unwrap() as u32).into())
+				.min_width(200)
+				.widget_holder(),
+		];
+
+		let svg_optimizations = [
+			(
+				"Flatten Transforms",
+				"Bake the transforms of shapes into their coordinates where it doesn't change how they look",
+				self.svg_options.flatten_transforms,
+				ExportDialogMessage::FlattenTransforms as fn(bool) -> ExportDialogMessage,
+			),
+			(
+				"Merge Styles",
+				"Move the fills and strokes shared by several shapes into a style sheet",
+				self.svg_options.merge_styles,
+				ExportDialogMessage::MergeStyles,
+			),
+			(
+				"Minify IDs",
+				"Shorten the IDs of gradients and clipping paths, and remove those which aren't used",
+				self.svg_options.minify_ids,
+				ExportDialogMessage::MinifyIds,
+			),
+			(
+				"Strip Metadata",
+				"Remove the markup only used by the editor, along with empty definitions and groups",
+				self.svg_options.strip_metadata,
+				ExportDialogMessage::StripMetadata,
+			),
+		]
+		.into_iter()
+		.map(|(label, tooltip, checked, message)| {
+			let widgets = vec![
+				TextLabel::new(label).table_align(true).min_width(100).tooltip(tooltip).widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				CheckboxInput::new(checked)
+					.tooltip(tooltip)
+					.on_update(move |value: &CheckboxInput| message(value.checked).into())
+					.widget_holder(),
+			];
+			LayoutGroup::Row { widgets }
+		});
+
 		let transparent_background = vec![
 			TextLabel::new("Transparency").table_align(true).min_width(100).widget_holder(),
 			Separator::new(SeparatorType::Unrelated).widget_holder(),
 			CheckboxInput::new(self.transparent_background)
-				.disabled(self.file_type == FileType::Jpg)
+				.disabled(!batch && !self.animated && self.file_type == FileType::Jpg)
 				.on_update(move |value: &CheckboxInput| ExportDialogMessage::TransparentBackground(value.checked).into())
 				.widget_holder(),
 		];
 
-		Layout::WidgetLayout(WidgetLayout::new(vec![
-			LayoutGroup::Row { widgets: export_type },
+		let animation_entries = [
+			(AnimationFormat::Gif, "GIF"),
+			(AnimationFormat::Apng, "APNG"),
+			(AnimationFormat::AnimatedSvg, "SVG"),
+			(AnimationFormat::AnimatedSvgCss, "SVG (CSS)"),
+			(AnimationFormat::PngSequence, "PNG Sequence"),
+			(AnimationFormat::SvgSequence, "SVG Sequence"),
+		]
+		.into_iter()
+		.map(|(val, name)| RadioEntryData::new(format!("{val:?}")).label(name).on_update(move |_| ExportDialogMessage::AnimationFormat(val).into()))
+		.collect();
+		let animation_format = vec![
+			TextLabel::new("File Type").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			RadioInput::new(animation_entries).selected_index(Some(self.animation.format as u32)).widget_holder(),
+		];
+
+		let animated = vec![
+			TextLabel::new("Animation").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			CheckboxInput::new(self.animated)
+				.tooltip("Export a range of frames from the animation, stepping its time forward by one frame at a time")
+				.on_update(move |value: &CheckboxInput| ExportDialogMessage::Animated(value.checked).into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			TextLabel::new("Export Frames").widget_holder(),
+		];
+
+		let animation_times = [
+			(
+				"Start Time",
+				self.animation.start_time,
+				0.,
+				"s",
+				ExportDialogMessage::AnimationStartTime as fn(f64) -> ExportDialogMessage,
+			),
+			("Duration", self.animation.duration, 0., "s", ExportDialogMessage::AnimationDuration),
+			("Frame Rate", self.animation.frame_rate, 1., " fps", ExportDialogMessage::AnimationFrameRate),
+		]
+		.into_iter()
+		.map(|(label, value, min, unit, message)| {
+			let widgets = vec![
+				TextLabel::new(label).table_align(true).min_width(100).widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				NumberInput::new(Some(value))
+					.unit(unit)
+					.min(min)
+					.on_update(move |number_input: &NumberInput| message(number_input.value.unwrap()).into())
+					.min_width(200)
+					.widget_holder(),
+			];
+			LayoutGroup::Row { widgets }
+		});
+
+		let mut rows = vec![
+			LayoutGroup::Row {
+				widgets: if self.animated { animation_format } else { export_type },
+			},
 			LayoutGroup::Row { widgets: resolution },
-			LayoutGroup::Row { widgets: export_area },
-			LayoutGroup::Row { widgets: transparent_background },
-		]))
+		];
+		if batch {
+			rows.push(LayoutGroup::Row { widgets: batch_export });
+			rows.push(LayoutGroup::Row { widgets: name_template });
+			rows.extend(artboard_rows);
+		} else {
+			rows.push(LayoutGroup::Row { widgets: export_area });
+			if !self.artboards.is_empty() && !self.animated {
+				rows.push(LayoutGroup::Row { widgets: batch_export });
+			}
+		}
+		rows.push(LayoutGroup::Row { widgets: animated });
+		if self.animated {
+			rows.extend(animation_times);
+		}
+		if file_types.iter().any(|file_type| matches!(file_type, FileType::Jpg | FileType::Avif)) || lossy_webp {
+			rows.push(LayoutGroup::Row { widgets: quality });
+		}
+		if file_types.contains(&FileType::Webp) {
+			rows.push(LayoutGroup::Row { widgets: lossless });
+		}
+		if file_types.iter().any(|file_type| matches!(file_type, FileType::Png | FileType::Tiff)) || lossless_webp {
+			rows.push(LayoutGroup::Row { widgets: color_profile });
+		}
+		if file_types.iter().any(|file_type| matches!(file_type, FileType::Png | FileType::Tiff)) && self.raster_options.color_profile == ColorProfile::Srgb {
+			rows.push(LayoutGroup::Row { widgets: bit_depth });
+		}
+		if file_types.contains(&FileType::Svg) {
+			rows.push(LayoutGroup::Row { widgets: precision });
+			rows.extend(svg_optimizations);
+		}
+		rows.push(LayoutGroup::Row { widgets: transparent_background });
+
+		Layout::WidgetLayout(WidgetLayout::new(rows))
+	}
+}
+
+fn file_type_entries(message: impl Fn(FileType) -> ExportDialogMessage + Copy + Send + Sync + 'static) -> Vec<RadioEntryData> {
+	[
+		(FileType::Png, "PNG"),
+		(FileType::Jpg, "JPG"),
+		(FileType::Webp, "WebP"),
+		(FileType::Avif, "AVIF"),
+		(FileType::Tiff, "TIFF"),
+		(FileType::Svg, "SVG"),
+		(FileType::Pdf, "PDF"),
+	]
+	.into_iter()
+	.map(|(val, name)| RadioEntryData::new(format!("{val:?}")).label(name).on_update(move |_| message(val).into()))
+	.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graph_craft::document::NodeId;
+
+	const FRONT: LayerNodeIdentifier = LayerNodeIdentifier::new_unchecked(NodeId(1));
+	const BACK: LayerNodeIdentifier = LayerNodeIdentifier::new_unchecked(NodeId(2));
+	const ICON: LayerNodeIdentifier = LayerNodeIdentifier::new_unchecked(NodeId(3));
+
+	fn batch_export(artboards: &[(LayerNodeIdentifier, &str)], name_template: &str, scale_factors: &[f64]) -> ExportDialogMessageHandler {
+		ExportDialogMessageHandler {
+			artboards: artboards.iter().map(|&(artboard, name)| (artboard, name.to_string())).collect(),
+			batch: true,
+			batch_scale_factors: scale_factors.to_vec(),
+			name_template: name_template.to_string(),
+			..Default::default()
+		}
+	}
+
+	fn file_list(files: &[BatchExportFile]) -> Vec<(LayerNodeIdentifier, &str, FileType, f64)> {
+		files.iter().map(|file| (file.artboard, file.file_name.as_str(), file.file_type, file.scale_factor)).collect()
+	}
+
+	#[test]
+	fn scale_factor_list() {
+		assert_eq!(parse_scale_factors("1, @2x 3X"), [1., 2., 3.]);
+		assert_eq!(parse_scale_factors("@0.5x,,1.5"), [0.5, 1.5]);
+		assert_eq!(parse_scale_factors("0, -1, two, @x, inf, NaN, 2"), [2.]);
+		assert!(parse_scale_factors("").is_empty());
+	}
+
+	#[test]
+	fn batch_file_names() {
+		let export = batch_export(&[(FRONT, "Front"), (BACK, "Back")], "{document} - {artboard}{scale}", &[1., 2.5]);
+
+		// Artboards are exported in the order of their labels
+		assert_eq!(
+			file_list(&export.batch_files("Poster")),
+			[
+				(BACK, "Poster - Back@1x", FileType::Png, 1.),
+				(BACK, "Poster - Back@2.5x", FileType::Png, 2.5),
+				(FRONT, "Poster - Front@1x", FileType::Png, 1.),
+				(FRONT, "Poster - Front@2.5x", FileType::Png, 2.5),
+			]
+		);
+
+		// A template which leaves nothing but spaces falls back to the artboard label
+		let export = batch_export(&[(FRONT, "Front")], " ", &[1.]);
+		assert_eq!(file_list(&export.batch_files("Poster")), [(FRONT, "Front", FileType::Png, 1.)]);
+	}
+
+	#[test]
+	fn batch_file_names_are_unique() {
+		// Without the scale in the template, every scale factor of both artboards ends up with the same name
+		let export = batch_export(&[(FRONT, "Cover"), (BACK, "Cover")], "{artboard}", &[1., 2.]);
+		assert_eq!(
+			file_list(&export.batch_files("Book")),
+			[
+				(FRONT, "Cover", FileType::Png, 1.),
+				(FRONT, "Cover 2", FileType::Png, 2.),
+				(BACK, "Cover 3", FileType::Png, 1.),
+				(BACK, "Cover 4", FileType::Png, 2.),
+			]
+		);
+
+		// Names only need to be unique together with their file extension
+		let mut export = batch_export(&[(FRONT, "Cover"), (BACK, "Cover")], "{artboard}", &[1.]);
+		export.artboard_settings.insert(
+			BACK,
+			BatchArtboardSettings {
+				included: true,
+				file_type: Some(FileType::Svg),
+			},
+		);
+		assert_eq!(file_list(&export.batch_files("Book")), [(FRONT, "Cover", FileType::Png, 1.), (BACK, "Cover", FileType::Svg, 1.)]);
+	}
+
+	#[test]
+	fn batch_artboard_settings() {
+		let mut export = batch_export(&[(FRONT, "Front"), (BACK, "Back"), (ICON, "Icon")], "{artboard}{scale}", &[1., 2.]);
+		export.artboard_settings.insert(FRONT, BatchArtboardSettings { included: false, file_type: None });
+		export.artboard_settings.insert(
+			BACK,
+			BatchArtboardSettings {
+				included: true,
+				file_type: Some(FileType::Jpg),
+			},
+		);
+		export.artboard_settings.insert(
+			ICON,
+			BatchArtboardSettings {
+				included: true,
+				file_type: Some(FileType::Pdf),
+			},
+		);
+
+		// Vector formats are written once, without a scale suffix
+		assert_eq!(
+			file_list(&export.batch_files("Poster")),
+			[(BACK, "Back@1x", FileType::Jpg, 1.), (BACK, "Back@2x", FileType::Jpg, 2.), (ICON, "Icon", FileType::Pdf, 1.)]
+		);
+		assert_eq!(export.file_types().len(), 2);
+
+		// Choosing a file type for the whole batch replaces the overrides, but keeps the excluded artboard out
+		let portfolio = PortfolioMessageHandler::default();
+		export.process_message(ExportDialogMessage::FileType(FileType::Webp), &mut VecDeque::new(), ExportDialogMessageData { portfolio: &portfolio });
+		assert_eq!(
+			file_list(&export.batch_files("Poster")),
+			[
+				(BACK, "Back@1x", FileType::Webp, 1.),
+				(BACK, "Back@2x", FileType::Webp, 2.),
+				(ICON, "Icon@1x", FileType::Webp, 1.),
+				(ICON, "Icon@2x", FileType::Webp, 2.),
+			]
+		);
 	}
 }
@@ -0,0 +1,34 @@
+use crate::messages::layout::utility_types::widget_prelude::*;
+use crate::messages::prelude::*;
+
+/// A dialog showing how many frames of an animated export have been rendered, which lets users cancel the export.
+pub struct ExportProgressDialog {
+	pub frame: usize,
+	pub frame_count: usize,
+}
+
+impl DialogLayoutHolder for ExportProgressDialog {
+	const ICON: &'static str = "File";
+	const TITLE: &'static str = "Exporting Animation";
+
+	fn layout_buttons(&self) -> Layout {
+		let widgets = vec![TextButton::new("Cancel").on_update(|_| PortfolioMessage::CancelAnimationExport.into()).widget_holder()];
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row { widgets }]))
+	}
+}
+
+impl LayoutHolder for ExportProgressDialog {
+	fn layout(&self) -> Layout {
+		let percent = if self.frame_count == 0 { 0 } else { self.frame * 100 / self.frame_count };
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![
+			LayoutGroup::Row {
+				widgets: vec![TextLabel::new("Rendering frames").bold(true).widget_holder()],
+			},
+			LayoutGroup::Row {
+				widgets: vec![TextLabel::new(format!("Frame {} of {} ({percent}%)", self.frame, self.frame_count)).widget_holder()],
+			},
+		]))
+	}
+}
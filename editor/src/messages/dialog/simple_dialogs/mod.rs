@@ -4,6 +4,7 @@ mod close_document_dialog;
 mod coming_soon_dialog;
 mod demo_artwork_dialog;
 mod error_dialog;
+mod export_progress_dialog;
 mod licenses_dialog;
 
 pub use about_graphite_dialog::AboutGraphiteDialog;
@@ -13,4 +14,5 @@ pub use coming_soon_dialog::ComingSoonDialog;
 pub use demo_artwork_dialog::ARTWORK;
 pub use demo_artwork_dialog::DemoArtworkDialog;
 pub use error_dialog::ErrorDialog;
+pub use export_progress_dialog::ExportProgressDialog;
 pub use licenses_dialog::LicensesDialog;
@@ -1,4 +1,4 @@
-use super::utility_types::{FrontendDocumentDetails, MouseCursorIcon};
+use super::utility_types::{FrontendDocumentDetails, FrontendExportFile, MouseCursorIcon};
 use crate::messages::layout::utility_types::widget_prelude::*;
 use crate::messages::portfolio::document::node_graph::utility_types::{
 	BoxSelection, ContextMenuInformation, FrontendClickTargets, FrontendGraphInput, FrontendGraphOutput, FrontendNode, FrontendNodeType, FrontendNodeWire, Transform, WirePath,
@@ -58,11 +58,21 @@ pub enum FrontendMessage {
 		commit_date: String,
 	},
 	TriggerDelayedZoomCanvasToFitAll,
+	TriggerDownloadArchive {
+		name: String,
+		files: Vec<FrontendExportFile>,
+	},
+	TriggerDownloadBinaryFile {
+		data: Vec<u8>,
+		name: String,
+		mime: String,
+	},
 	TriggerDownloadImage {
 		svg: String,
 		name: String,
 		mime: String,
 		size: (f64, f64),
+		quality: Option<f64>,
 	},
 	TriggerDownloadTextFile {
 		document: String,
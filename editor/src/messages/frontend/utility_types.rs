@@ -34,7 +34,11 @@ pub enum FileType {
 	#[default]
 	Png,
 	Jpg,
+	Webp,
+	Avif,
+	Tiff,
 	Svg,
+	Pdf,
 }
 
 impl FileType {
@@ -42,11 +46,121 @@ impl FileType {
 		match self {
 			FileType::Png => "image/png",
 			FileType::Jpg => "image/jpeg",
+			FileType::Webp => "image/webp",
+			FileType::Avif => "image/avif",
+			FileType::Tiff => "image/tiff",
 			FileType::Svg => "image/svg+xml",
+			FileType::Pdf => "application/pdf",
 		}
 	}
 }
 
+/// The width of the samples in exported sRGB raster files.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum BitDepth {
+	#[default]
+	Eight,
+	Sixteen,
+}
+
+/// The color space of exported raster pixels, which is embedded in the file as an ICC profile.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum ColorProfile {
+	#[default]
+	Srgb,
+	LinearSrgb,
+}
+
+/// Encoding choices for the raster file types.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct RasterExportOptions {
+	/// From 1 to 100, used by JPG, lossy WebP and AVIF.
+	pub quality: f64,
+	/// Encode WebP without loss instead of using the quality setting.
+	pub lossless: bool,
+	/// Only sRGB PNG and TIFF files can be written with either depth, since linear ones always need 16-bit or float samples to keep the dark shades converted from sRGB.
+	/// The artwork is drawn with 8 bits per channel either way, so 16-bit files hold the same colors in wider samples for the workflows that require them.
+	pub bit_depth: BitDepth,
+	pub color_profile: ColorProfile,
+}
+
+impl Default for RasterExportOptions {
+	fn default() -> Self {
+		Self {
+			quality: 90.,
+			lossless: false,
+			bit_depth: BitDepth::Eight,
+			color_profile: ColorProfile::Srgb,
+		}
+	}
+}
+
+impl RasterExportOptions {
+	/// Whether the file can only be encoded by the editor, since the browser's canvas can't produce it.
+	pub fn needs_native_encoding(&self, file_type: FileType) -> bool {
+		match file_type {
+			FileType::Png => self.bit_depth == BitDepth::Sixteen || self.color_profile == ColorProfile::LinearSrgb,
+			FileType::Webp => self.lossless,
+			FileType::Avif | FileType::Tiff => true,
+			FileType::Jpg | FileType::Svg | FileType::Pdf => false,
+		}
+	}
+
+	/// The quality given to the browser's canvas encoder, for the lossy file types it supports.
+	pub fn canvas_quality(&self, file_type: FileType) -> Option<f64> {
+		matches!(file_type, FileType::Jpg | FileType::Webp).then_some(self.quality.clamp(1., 100.) / 100.)
+	}
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum AnimationFormat {
+	#[default]
+	Gif,
+	Apng,
+	/// A single SVG which animates the attributes that change between frames using SMIL.
+	AnimatedSvg,
+	/// A single SVG which animates the presentation properties that change between frames using CSS keyframes.
+	AnimatedSvgCss,
+	/// A ZIP archive of numbered PNG files, one for each frame.
+	PngSequence,
+	/// A ZIP archive of numbered SVG files, one for each frame.
+	SvgSequence,
+}
+
+/// The frames of an animated export, which are rendered by stepping the animation time through the node graph.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct AnimationExportSettings {
+	pub format: AnimationFormat,
+	/// The animation time of the first frame, in seconds.
+	pub start_time: f64,
+	/// In seconds.
+	pub duration: f64,
+	/// In frames per second.
+	pub frame_rate: f64,
+}
+
+impl Default for AnimationExportSettings {
+	fn default() -> Self {
+		Self {
+			format: AnimationFormat::Gif,
+			start_time: 0.,
+			duration: 2.,
+			frame_rate: 24.,
+		}
+	}
+}
+
+impl AnimationExportSettings {
+	pub fn frame_count(&self) -> usize {
+		((self.duration * self.frame_rate).round() as usize).max(1)
+	}
+
+	/// The animation time of the given frame, in seconds.
+	pub fn frame_time(&self, frame: usize) -> f64 {
+		self.start_time + frame as f64 / self.frame_rate
+	}
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
 pub enum ExportBounds {
 	#[default]
@@ -54,3 +168,30 @@ pub enum ExportBounds {
 	Selection,
 	Artboard(LayerNodeIdentifier),
 }
+
+/// One file of a batch export: an artboard rendered at one of the requested scale factors.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct BatchExportFile {
+	pub artboard: LayerNodeIdentifier,
+	pub file_name: String,
+	pub file_type: FileType,
+	pub scale_factor: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum FrontendExportFileContent {
+	Text(String),
+	/// An SVG which the frontend rasterizes to the given size.
+	Image {
+		svg: String,
+		size: (f64, f64),
+	},
+	Binary(Vec<u8>),
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct FrontendExportFile {
+	pub name: String,
+	pub mime: String,
+	pub content: FrontendExportFileContent,
+}
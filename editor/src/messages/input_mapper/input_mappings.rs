@@ -10,7 +10,10 @@ use crate::messages::portfolio::document::utility_types::clipboards::Clipboard;
 use crate::messages::portfolio::document::utility_types::misc::GroupFolderType;
 use crate::messages::portfolio::document::utility_types::transformation::TransformType;
 use crate::messages::prelude::*;
-use crate::messages::tool::tool_messages::brush_tool::BrushToolMessageOptionsUpdate;
+use crate::messages::tool::tool_messages::brush_tool::{BrushShapeOptionsUpdate, BrushToolMessageOptionsUpdate};
+use crate::messages::tool::tool_messages::clone_tool::CloneToolMessageOptionsUpdate;
+use crate::messages::tool::tool_messages::detail_tool::DetailToolMessageOptionsUpdate;
+use crate::messages::tool::tool_messages::relight_tool::RelightToolMessageOptionsUpdate;
 use crate::messages::tool::tool_messages::select_tool::SelectToolPointerKeys;
 use glam::DVec2;
 
@@ -292,11 +295,56 @@ pub fn input_mappings() -> Mapping {
 		entry!(PointerMove; action_dispatch=BrushToolMessage::PointerMove),
 		entry!(KeyDown(MouseLeft); action_dispatch=BrushToolMessage::DragStart),
 		entry!(KeyUp(MouseLeft); action_dispatch=BrushToolMessage::DragStop),
-		entry!(KeyDown(BracketLeft); action_dispatch=BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::ChangeDiameter(-BRUSH_SIZE_CHANGE_KEYBOARD))),
-		entry!(KeyDown(BracketRight); action_dispatch=BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::ChangeDiameter(BRUSH_SIZE_CHANGE_KEYBOARD))),
+		entry!(KeyDown(BracketLeft); action_dispatch=BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::Shape(BrushShapeOptionsUpdate::ChangeDiameter(-BRUSH_SIZE_CHANGE_KEYBOARD)))),
+		entry!(KeyDown(BracketRight); action_dispatch=BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::Shape(BrushShapeOptionsUpdate::ChangeDiameter(BRUSH_SIZE_CHANGE_KEYBOARD)))),
 		entry!(KeyDown(MouseRight); action_dispatch=BrushToolMessage::Abort),
 		entry!(KeyDown(Escape); action_dispatch=BrushToolMessage::Abort),
 		//
+		// CloneToolMessage
+		entry!(PointerMove; action_dispatch=CloneToolMessage::PointerMove),
+		entry!(KeyDown(MouseLeft); action_dispatch=CloneToolMessage::DragStart),
+		entry!(KeyDown(MouseLeft); modifiers=[Alt], action_dispatch=CloneToolMessage::SetSource),
+		entry!(KeyUp(MouseLeft); action_dispatch=CloneToolMessage::DragStop),
+		entry!(KeyDown(BracketLeft); action_dispatch=CloneToolMessage::UpdateOptions(CloneToolMessageOptionsUpdate::Brush(BrushShapeOptionsUpdate::ChangeDiameter(-BRUSH_SIZE_CHANGE_KEYBOARD)))),
+		entry!(KeyDown(BracketRight); action_dispatch=CloneToolMessage::UpdateOptions(CloneToolMessageOptionsUpdate::Brush(BrushShapeOptionsUpdate::ChangeDiameter(BRUSH_SIZE_CHANGE_KEYBOARD)))),
+		entry!(KeyDown(MouseRight); action_dispatch=CloneToolMessage::Abort),
+		entry!(KeyDown(Escape); action_dispatch=CloneToolMessage::Abort),
+		//
+		// HealToolMessage
+		entry!(PointerMove; action_dispatch=HealToolMessage::PointerMove),
+		entry!(KeyDown(MouseLeft); action_dispatch=HealToolMessage::DragStart),
+		entry!(KeyDown(MouseLeft); modifiers=[Alt], action_dispatch=HealToolMessage::SetSource),
+		entry!(KeyUp(MouseLeft); action_dispatch=HealToolMessage::DragStop),
+		entry!(KeyDown(BracketLeft); action_dispatch=HealToolMessage::UpdateOptions(CloneToolMessageOptionsUpdate::Brush(BrushShapeOptionsUpdate::ChangeDiameter(-BRUSH_SIZE_CHANGE_KEYBOARD)))),
+		entry!(KeyDown(BracketRight); action_dispatch=HealToolMessage::UpdateOptions(CloneToolMessageOptionsUpdate::Brush(BrushShapeOptionsUpdate::ChangeDiameter(BRUSH_SIZE_CHANGE_KEYBOARD)))),
+		entry!(KeyDown(MouseRight); action_dispatch=HealToolMessage::Abort),
+		entry!(KeyDown(Escape); action_dispatch=HealToolMessage::Abort),
+		//
+		// PatchToolMessage
+		entry!(PointerMove; action_dispatch=PatchToolMessage::PointerMove),
+		entry!(KeyDown(MouseLeft); action_dispatch=PatchToolMessage::DragStart),
+		entry!(KeyUp(MouseLeft); action_dispatch=PatchToolMessage::DragStop),
+		entry!(KeyDown(MouseRight); action_dispatch=PatchToolMessage::Abort),
+		entry!(KeyDown(Escape); action_dispatch=PatchToolMessage::Abort),
+		//
+		// DetailToolMessage
+		entry!(PointerMove; action_dispatch=DetailToolMessage::PointerMove),
+		entry!(KeyDown(MouseLeft); action_dispatch=DetailToolMessage::DragStart),
+		entry!(KeyUp(MouseLeft); action_dispatch=DetailToolMessage::DragStop),
+		entry!(KeyDown(BracketLeft); action_dispatch=DetailToolMessage::UpdateOptions(DetailToolMessageOptionsUpdate::Brush(BrushShapeOptionsUpdate::ChangeDiameter(-BRUSH_SIZE_CHANGE_KEYBOARD)))),
+		entry!(KeyDown(BracketRight); action_dispatch=DetailToolMessage::UpdateOptions(DetailToolMessageOptionsUpdate::Brush(BrushShapeOptionsUpdate::ChangeDiameter(BRUSH_SIZE_CHANGE_KEYBOARD)))),
+		entry!(KeyDown(MouseRight); action_dispatch=DetailToolMessage::Abort),
+		entry!(KeyDown(Escape); action_dispatch=DetailToolMessage::Abort),
+		//
+		// RelightToolMessage
+		entry!(PointerMove; action_dispatch=RelightToolMessage::PointerMove),
+		entry!(KeyDown(MouseLeft); action_dispatch=RelightToolMessage::DragStart),
+		entry!(KeyUp(MouseLeft); action_dispatch=RelightToolMessage::DragStop),
+		entry!(KeyDown(BracketLeft); action_dispatch=RelightToolMessage::UpdateOptions(RelightToolMessageOptionsUpdate::Brush(BrushShapeOptionsUpdate::ChangeDiameter(-BRUSH_SIZE_CHANGE_KEYBOARD)))),
+		entry!(KeyDown(BracketRight); action_dispatch=RelightToolMessage::UpdateOptions(RelightToolMessageOptionsUpdate::Brush(BrushShapeOptionsUpdate::ChangeDiameter(BRUSH_SIZE_CHANGE_KEYBOARD)))),
+		entry!(KeyDown(MouseRight); action_dispatch=RelightToolMessage::Abort),
+		entry!(KeyDown(Escape); action_dispatch=RelightToolMessage::Abort),
+		//
 		// ToolMessage
 		entry!(KeyDown(KeyV); action_dispatch=ToolMessage::ActivateToolSelect),
 		entry!(KeyDown(KeyZ); action_dispatch=ToolMessage::ActivateToolNavigate),
@@ -312,6 +360,10 @@ pub fn input_mappings() -> Mapping {
 		entry!(KeyDown(KeyE); action_dispatch=ToolMessage::ActivateToolEllipse),
 		entry!(KeyDown(KeyY); action_dispatch=ToolMessage::ActivateToolPolygon),
 		entry!(KeyDown(KeyB); action_dispatch=ToolMessage::ActivateToolBrush),
+		entry!(KeyDown(KeyJ); action_dispatch=ToolMessage::ActivateToolHeal),
+		entry!(KeyDown(KeyC); action_dispatch=ToolMessage::ActivateToolClone),
+		entry!(KeyDown(KeyD); action_dispatch=ToolMessage::ActivateToolDetail),
+		entry!(KeyDown(KeyO); action_dispatch=ToolMessage::ActivateToolRelight),
 		entry!(KeyDown(KeyX); modifiers=[Accel, Shift], action_dispatch=ToolMessage::ResetColors),
 		entry!(KeyDown(KeyX); modifiers=[Shift], action_dispatch=ToolMessage::SwapColors),
 		entry!(KeyDown(KeyC); modifiers=[Alt], action_dispatch=ToolMessage::SelectRandomPrimaryColor),
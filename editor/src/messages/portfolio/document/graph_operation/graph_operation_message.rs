@@ -2,6 +2,7 @@ use super::utility_types::TransformIn;
 use crate::messages::portfolio::document::utility_types::document_metadata::LayerNodeIdentifier;
 use crate::messages::portfolio::document::utility_types::network_interface::NodeTemplate;
 use crate::messages::prelude::*;
+use crate::messages::tool::tool_messages::detail_tool::DetailMode;
 use bezier_rs::Subpath;
 use glam::{DAffine2, DVec2, IVec2};
 use graph_craft::document::NodeId;
@@ -57,6 +58,31 @@ pub enum GraphOperationMessage {
 		layer: LayerNodeIdentifier,
 		strokes: Vec<BrushStroke>,
 	},
+	Patch {
+		layer: LayerNodeIdentifier,
+		region: Vec<DVec2>,
+		offset: DVec2,
+	},
+	FloodFill {
+		layer: LayerNodeIdentifier,
+		seed_point: DVec2,
+		color: Color,
+		tolerance: f64,
+		contiguous: bool,
+		anti_aliasing: bool,
+		sample_all_layers: bool,
+	},
+	Detail {
+		layer: LayerNodeIdentifier,
+		strokes: Vec<BrushStroke>,
+		mode: DetailMode,
+		strength: f64,
+	},
+	Relight {
+		layer: LayerNodeIdentifier,
+		strokes: Vec<BrushStroke>,
+		exposure: f64,
+	},
 	SetUpstreamToChain {
 		layer: LayerNodeIdentifier,
 	},
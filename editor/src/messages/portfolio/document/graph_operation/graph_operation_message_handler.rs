@@ -100,6 +100,34 @@ impl MessageHandler<GraphOperationMessage, GraphOperationMessageData<'_>> for Gr
 					modify_inputs.brush_modify(strokes);
 				}
 			}
+			GraphOperationMessage::Patch { layer, region, offset } => {
+				if let Some(mut modify_inputs) = ModifyInputsContext::new_with_layer(layer, network_interface, responses) {
+					modify_inputs.patch_add(region, offset);
+				}
+			}
+			GraphOperationMessage::FloodFill {
+				layer,
+				seed_point,
+				color,
+				tolerance,
+				contiguous,
+				anti_aliasing,
+				sample_all_layers,
+			} => {
+				if let Some(mut modify_inputs) = ModifyInputsContext::new_with_layer(layer, network_interface, responses) {
+					modify_inputs.flood_fill_add(seed_point, color, tolerance, contiguous, anti_aliasing, sample_all_layers);
+				}
+			}
+			GraphOperationMessage::Detail { layer, strokes, mode, strength } => {
+				if let Some(mut modify_inputs) = ModifyInputsContext::new_with_layer(layer, network_interface, responses) {
+					modify_inputs.detail_modify(strokes, mode, strength);
+				}
+			}
+			GraphOperationMessage::Relight { layer, strokes, exposure } => {
+				if let Some(mut modify_inputs) = ModifyInputsContext::new_with_layer(layer, network_interface, responses) {
+					modify_inputs.relight_modify(strokes, exposure);
+				}
+			}
 			GraphOperationMessage::SetUpstreamToChain { layer } => {
 				let Some(OutputConnector::Node { node_id: first_chain_node, .. }) = network_interface.upstream_output_connector(&InputConnector::node(layer.to_node(), 1), &[]) else {
 					return;
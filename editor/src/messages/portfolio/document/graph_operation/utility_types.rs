@@ -3,6 +3,7 @@ use crate::messages::portfolio::document::node_graph::document_node_definitions:
 use crate::messages::portfolio::document::utility_types::document_metadata::LayerNodeIdentifier;
 use crate::messages::portfolio::document::utility_types::network_interface::{self, InputConnector, NodeNetworkInterface, OutputConnector};
 use crate::messages::prelude::*;
+use crate::messages::tool::tool_messages::detail_tool::DetailMode;
 use bezier_rs::Subpath;
 use glam::{DAffine2, DVec2, IVec2};
 use graph_craft::concrete;
@@ -456,6 +457,71 @@ impl<'a> ModifyInputsContext<'a> {
 		self.set_input_with_refresh(InputConnector::node(brush_node_id, 2), NodeInput::value(TaggedValue::BrushStrokes(strokes), false), false);
 	}
 
+	/// Adds a new "Patch" node to the layer, so every patch stays editable on its own.
+	pub fn patch_add(&mut self, region: Vec<DVec2>, offset: DVec2) {
+		let Some(patch_node_id) = self.create_node("Patch") else { return };
+		self.set_input_with_refresh(InputConnector::node(patch_node_id, 1), NodeInput::value(TaggedValue::VecDVec2(region), false), true);
+		self.set_input_with_refresh(InputConnector::node(patch_node_id, 2), NodeInput::value(TaggedValue::DVec2(offset), false), false);
+	}
+
+	/// Adds a new "Flood Fill" node to the layer, which finds the filled area again whenever the image under it changes.
+	/// When sampling all layers, the layers beneath this one in its stack are wired into the node so their pixels also bound the fill.
+	pub fn flood_fill_add(&mut self, seed_point: DVec2, color: Color, tolerance: f64, contiguous: bool, anti_aliasing: bool, sample_all_layers: bool) {
+		let Some(layer) = self.get_output_layer() else { return };
+		let Some(fill_node_id) = self.create_node("Flood Fill") else { return };
+		self.set_input_with_refresh(InputConnector::node(fill_node_id, 1), NodeInput::value(TaggedValue::DVec2(seed_point), false), true);
+		self.set_input_with_refresh(InputConnector::node(fill_node_id, 2), NodeInput::value(TaggedValue::Color(color), false), true);
+		self.set_input_with_refresh(InputConnector::node(fill_node_id, 3), NodeInput::value(TaggedValue::F64(tolerance), false), true);
+		self.set_input_with_refresh(InputConnector::node(fill_node_id, 4), NodeInput::value(TaggedValue::Bool(contiguous), false), true);
+		self.set_input_with_refresh(InputConnector::node(fill_node_id, 5), NodeInput::value(TaggedValue::Bool(anti_aliasing), false), true);
+
+		if sample_all_layers {
+			if let Some(layers_below) = self.network_interface.upstream_output_connector(&InputConnector::node(layer.to_node(), 0), &[]) {
+				self.network_interface.create_wire(&layers_below, &InputConnector::node(fill_node_id, 6), &[]);
+			}
+		}
+		self.responses.add(NodeGraphMessage::RunDocumentGraph);
+	}
+
+	/// Gets the node with a specific reference that flows directly into the layer, if its inputs at the given indices hold the given values.
+	/// Used by the tools whose strokes mask the effect of a node, so strokes painted with other settings go into a node of their own.
+	pub fn locate_brush_mask_node(reference_name: &str, parameters: &[(usize, TaggedValue)], layer: LayerNodeIdentifier, network_interface: &NodeNetworkInterface) -> Option<NodeId> {
+		let Some(OutputConnector::Node { node_id, .. }) = network_interface.upstream_output_connector(&InputConnector::node(layer.to_node(), 1), &[]) else {
+			return None;
+		};
+		if network_interface.reference(&node_id, &[]).is_none_or(|reference| *reference != Some(reference_name.to_string())) {
+			return None;
+		}
+
+		let node = network_interface.document_node(&node_id, &[])?;
+		let matches = parameters.iter().all(|(index, value)| node.inputs.get(*index).and_then(|input| input.as_value()) == Some(value));
+		matches.then_some(node_id)
+	}
+
+	fn brush_mask_modify(&mut self, reference_name: &'static str, strokes: Vec<BrushStroke>, parameters: Vec<(usize, TaggedValue)>) {
+		let Some(layer) = self.get_output_layer() else { return };
+		let node_id = match Self::locate_brush_mask_node(reference_name, &parameters, layer, self.network_interface) {
+			Some(node_id) => node_id,
+			None => {
+				let Some(node_id) = self.create_node(reference_name) else { return };
+				for (index, value) in parameters {
+					self.set_input_with_refresh(InputConnector::node(node_id, index), NodeInput::value(value, false), true);
+				}
+				node_id
+			}
+		};
+		self.set_input_with_refresh(InputConnector::node(node_id, 1), NodeInput::value(TaggedValue::BrushStrokes(strokes), false), false);
+	}
+
+	pub fn detail_modify(&mut self, strokes: Vec<BrushStroke>, mode: DetailMode, strength: f64) {
+		let (reference, parameters) = mode.node(strength);
+		self.brush_mask_modify(reference, strokes, parameters);
+	}
+
+	pub fn relight_modify(&mut self, strokes: Vec<BrushStroke>, exposure: f64) {
+		self.brush_mask_modify("Relight", strokes, vec![(2, TaggedValue::F64(exposure))]);
+	}
+
 	pub fn resize_artboard(&mut self, location: IVec2, dimensions: IVec2) {
 		let Some(artboard_node_id) = self.existing_node_id("Artboard", true) else {
 			return;
@@ -13,8 +13,8 @@ use graph_craft::document::{DocumentNode, DocumentNodeImplementation, NodeId, No
 use graphene_core::raster::curve::Curve;
 use graphene_core::raster::image::ImageFrameTable;
 use graphene_core::raster::{
-	BlendMode, CellularDistanceFunction, CellularReturnType, Color, DomainWarpType, FractalType, LuminanceCalculation, NoiseType, RedGreenBlue, RedGreenBlueAlpha, RelativeAbsolute,
-	SelectiveColorChoice,
+	BlendMode, CellularDistanceFunction, CellularReturnType, Color, DomainWarpType, FractalType, LuminanceCalculation, NoiseType, RawColorSpace, RawDemosaicAlgorithm, RawHighlightRecovery,
+	RawWhiteBalance, RedGreenBlue, RedGreenBlueAlpha, RelativeAbsolute, SelectiveColorChoice,
 };
 use graphene_core::text::Font;
 use graphene_core::vector::misc::CentroidType;
@@ -173,6 +173,10 @@ pub(crate) fn property_from_type(
 						Some(x) if x == TypeId::of::<CellularDistanceFunction>() => cellular_distance_function(document_node, node_id, index, name, true, false),
 						Some(x) if x == TypeId::of::<CellularReturnType>() => cellular_return_type(document_node, node_id, index, name, true, false),
 						Some(x) if x == TypeId::of::<DomainWarpType>() => domain_warp_type(document_node, node_id, index, name, true, false),
+						Some(x) if x == TypeId::of::<RawDemosaicAlgorithm>() => raw_demosaic_algorithm(document_node, node_id, index, name, true),
+						Some(x) if x == TypeId::of::<RawWhiteBalance>() => raw_white_balance(document_node, node_id, index, name, true),
+						Some(x) if x == TypeId::of::<RawHighlightRecovery>() => raw_highlight_recovery(document_node, node_id, index, name, true),
+						Some(x) if x == TypeId::of::<RawColorSpace>() => raw_color_space(document_node, node_id, index, name, true),
 						Some(x) if x == TypeId::of::<RelativeAbsolute>() => vec![
 							DropdownInput::new(vec![vec![
 								MenuListEntry::new("Relative")
@@ -973,6 +977,110 @@ pub fn domain_warp_type(document_node: &DocumentNode, node_id: NodeId, index: us
 	LayoutGroup::Row { widgets }.with_tooltip("Type of domain warp")
 }
 
+// TODO: Generalize this instead of using a separate function per dropdown menu enum
+pub fn raw_demosaic_algorithm(document_node: &DocumentNode, node_id: NodeId, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	let Some(input) = document_node.inputs.get(index) else {
+		log::warn!("A widget failed to be built because its node's input index is invalid.");
+		return LayoutGroup::Row { widgets: vec![] };
+	};
+	if let Some(&TaggedValue::RawDemosaicAlgorithm(algorithm)) = input.as_non_exposed_value() {
+		let entries = RawDemosaicAlgorithm::list()
+			.iter()
+			.map(|algorithm| {
+				MenuListEntry::new(format!("{algorithm:?}"))
+					.label(algorithm.to_string())
+					.on_update(update_value(move |_| TaggedValue::RawDemosaicAlgorithm(*algorithm), node_id, index))
+					.on_commit(commit_value)
+			})
+			.collect();
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			DropdownInput::new(vec![entries]).selected_index(Some(algorithm as u32)).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }.with_tooltip("Algorithm used to interpolate the colors which the camera sensor doesn't record at each pixel")
+}
+
+// TODO: Generalize this instead of using a separate function per dropdown menu enum
+pub fn raw_white_balance(document_node: &DocumentNode, node_id: NodeId, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	let Some(input) = document_node.inputs.get(index) else {
+		log::warn!("A widget failed to be built because its node's input index is invalid.");
+		return LayoutGroup::Row { widgets: vec![] };
+	};
+	if let Some(&TaggedValue::RawWhiteBalance(white_balance)) = input.as_non_exposed_value() {
+		let entries = RawWhiteBalance::list()
+			.iter()
+			.map(|white_balance| {
+				MenuListEntry::new(format!("{white_balance:?}"))
+					.label(white_balance.to_string())
+					.on_update(update_value(move |_| TaggedValue::RawWhiteBalance(*white_balance), node_id, index))
+					.on_commit(commit_value)
+			})
+			.collect();
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			DropdownInput::new(vec![entries]).selected_index(Some(white_balance as u32)).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }.with_tooltip("How the color of the light in the scene is neutralized")
+}
+
+// TODO: Generalize this instead of using a separate function per dropdown menu enum
+pub fn raw_highlight_recovery(document_node: &DocumentNode, node_id: NodeId, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	let Some(input) = document_node.inputs.get(index) else {
+		log::warn!("A widget failed to be built because its node's input index is invalid.");
+		return LayoutGroup::Row { widgets: vec![] };
+	};
+	if let Some(&TaggedValue::RawHighlightRecovery(highlight_recovery)) = input.as_non_exposed_value() {
+		let entries = RawHighlightRecovery::list()
+			.iter()
+			.map(|highlight_recovery| {
+				MenuListEntry::new(format!("{highlight_recovery:?}"))
+					.label(highlight_recovery.to_string())
+					.on_update(update_value(move |_| TaggedValue::RawHighlightRecovery(*highlight_recovery), node_id, index))
+					.on_commit(commit_value)
+			})
+			.collect();
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			DropdownInput::new(vec![entries]).selected_index(Some(highlight_recovery as u32)).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }.with_tooltip("How parts of the image where the camera sensor is saturated are handled")
+}
+
+// TODO: Generalize this instead of using a separate function per dropdown menu enum
+pub fn raw_color_space(document_node: &DocumentNode, node_id: NodeId, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	let Some(input) = document_node.inputs.get(index) else {
+		log::warn!("A widget failed to be built because its node's input index is invalid.");
+		return LayoutGroup::Row { widgets: vec![] };
+	};
+	if let Some(&TaggedValue::RawColorSpace(color_space)) = input.as_non_exposed_value() {
+		let entries = RawColorSpace::list()
+			.iter()
+			.map(|color_space| {
+				MenuListEntry::new(format!("{color_space:?}"))
+					.label(color_space.to_string())
+					.on_update(update_value(move |_| TaggedValue::RawColorSpace(*color_space), node_id, index))
+					.on_commit(commit_value)
+			})
+			.collect();
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			DropdownInput::new(vec![entries]).selected_index(Some(color_space as u32)).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }.with_tooltip("Color space of the developed image")
+}
+
 // TODO: Generalize this instead of using a separate function per dropdown menu enum
 pub fn blend_mode(document_node: &DocumentNode, node_id: NodeId, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
 	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
@@ -2545,12 +2653,19 @@ pub fn math_properties(node_id: NodeId, context: &mut NodePropertiesContext) ->
 		}
 		widgets
 	})();
-	let operand_b = number_widget(document_node, node_id, operation_b_index, "Operand B", NumberInput::default(), true);
+	// Operand B may be a number, vector or color depending on which implementation of the node is used
+	let operand_b = match document_node.inputs.get(operation_b_index).and_then(|input| input.as_non_exposed_value()) {
+		Some(TaggedValue::DVec2(_)) => vec2_widget(document_node, node_id, operation_b_index, "Operand B", "X", "Y", "", None, add_blank_assist),
+		Some(TaggedValue::Color(_)) => color_widget(document_node, node_id, operation_b_index, "Operand B", ColorInput::default().allow_none(false), true),
+		_ => LayoutGroup::Row {
+			widgets: number_widget(document_node, node_id, operation_b_index, "Operand B", NumberInput::default(), true),
+		},
+	};
 	let operand_a_hint = vec![TextLabel::new("(Operand A is the primary input)").widget_holder()];
 
 	vec![
 		LayoutGroup::Row { widgets: expression }.with_tooltip(r#"A math expression that may incorporate "A" and/or "B", such as "sqrt(A + B) - B^2""#),
-		LayoutGroup::Row { widgets: operand_b }.with_tooltip(r#"The value of "B" when calculating the expression"#),
+		operand_b.with_tooltip(r#"The value of "B" when calculating the expression"#),
 		LayoutGroup::Row { widgets: operand_a_hint }.with_tooltip(r#""A" is fed by the value from the previous node in the primary data flow, or it is 0 if disconnected"#),
 	]
 }
@@ -1,10 +1,11 @@
 use super::document::utility_types::document_metadata::LayerNodeIdentifier;
 use super::utility_types::PanelType;
-use crate::messages::frontend::utility_types::{ExportBounds, FileType};
+use crate::messages::frontend::utility_types::{AnimationExportSettings, BatchExportFile, ExportBounds, FileType, RasterExportOptions};
 use crate::messages::portfolio::document::utility_types::clipboards::Clipboard;
 use crate::messages::prelude::*;
 use graphene_core::Color;
 use graphene_core::raster::Image;
+use graphene_core::renderer::SvgExportOptions;
 use graphene_core::text::Font;
 
 #[impl_message(Message, Portfolio)]
@@ -28,6 +29,7 @@ pub enum PortfolioMessage {
 	AutoSaveDocument {
 		document_id: DocumentId,
 	},
+	CancelAnimationExport,
 	CloseActiveDocumentWithConfirmation,
 	CloseAllDocuments,
 	CloseAllDocumentsWithConfirmation,
@@ -112,12 +114,28 @@ pub enum PortfolioMessage {
 	SelectDocument {
 		document_id: DocumentId,
 	},
+	SubmitAnimationExport {
+		file_name: String,
+		scale_factor: f64,
+		bounds: ExportBounds,
+		transparent_background: bool,
+		settings: AnimationExportSettings,
+	},
+	SubmitBatchExport {
+		archive_name: String,
+		files: Vec<BatchExportFile>,
+		transparent_background: bool,
+		raster_options: RasterExportOptions,
+		svg_options: SvgExportOptions,
+	},
 	SubmitDocumentExport {
 		file_name: String,
 		file_type: FileType,
 		scale_factor: f64,
 		bounds: ExportBounds,
 		transparent_background: bool,
+		raster_options: RasterExportOptions,
+		svg_options: SvgExportOptions,
 	},
 	SubmitActiveGraphRender,
 	SubmitGraphRender {
@@ -156,6 +156,10 @@ impl MessageHandler<PortfolioMessage, PortfolioMessageData<'_>> for PortfolioMes
 					},
 				})
 			}
+			PortfolioMessage::CancelAnimationExport => {
+				self.executor.cancel_animation_export();
+				responses.add(FrontendMessage::DisplayDialogDismiss);
+			}
 			PortfolioMessage::CloseActiveDocumentWithConfirmation => {
 				if let Some(document_id) = self.active_document_id {
 					responses.add(PortfolioMessage::CloseDocumentWithConfirmation { document_id });
@@ -1082,12 +1086,55 @@ impl MessageHandler<PortfolioMessage, PortfolioMessageData<'_>> for PortfolioMes
 					responses.add(PropertiesPanelMessage::Clear);
 				}
 			}
+			PortfolioMessage::SubmitAnimationExport {
+				file_name,
+				scale_factor,
+				bounds,
+				transparent_background,
+				settings,
+			} => {
+				let document = self.active_document_id.and_then(|id| self.documents.get_mut(&id)).expect("Tried to render non-existent document");
+				let export_config = ExportConfig {
+					file_name,
+					scale_factor,
+					bounds,
+					transparent_background,
+					..Default::default()
+				};
+				let result = self.executor.submit_animation_export(document, export_config, settings, responses);
+
+				if let Err(description) = result {
+					responses.add(DialogMessage::DisplayDialogError {
+						title: "Unable to export animation".to_string(),
+						description,
+					});
+				}
+			}
+			PortfolioMessage::SubmitBatchExport {
+				archive_name,
+				files,
+				transparent_background,
+				raster_options,
+				svg_options,
+			} => {
+				let document = self.active_document_id.and_then(|id| self.documents.get_mut(&id)).expect("Tried to render non-existent document");
+				let result = self.executor.submit_batch_export(document, archive_name, files, transparent_background, raster_options, svg_options);
+
+				if let Err(description) = result {
+					responses.add(DialogMessage::DisplayDialogError {
+						title: "Unable to export artboards".to_string(),
+						description,
+					});
+				}
+			}
 			PortfolioMessage::SubmitDocumentExport {
 				file_name,
 				file_type,
 				scale_factor,
 				bounds,
 				transparent_background,
+				raster_options,
+				svg_options,
 			} => {
 				let document = self.active_document_id.and_then(|id| self.documents.get_mut(&id)).expect("Tried to render non-existent document");
 				let export_config = ExportConfig {
@@ -1096,6 +1143,8 @@ impl MessageHandler<PortfolioMessage, PortfolioMessageData<'_>> for PortfolioMes
 					scale_factor,
 					bounds,
 					transparent_background,
+					raster_options,
+					svg_options,
 					..Default::default()
 				};
 				let result = self.executor.submit_document_export(document, export_config);
@@ -44,16 +44,16 @@ pub enum ToolMessage {
 
 	#[child]
 	Brush(BrushToolMessage),
-	// 	// #[child]
-	// Heal(HealToolMessage),
-	// 	// #[child]
-	// Clone(CloneToolMessage),
-	// 	// #[child]
-	// Patch(PatchToolMessage),
-	// 	// #[child]
-	// Relight(RelightToolMessage),
-	// 	// #[child]
-	// Detail(DetailToolMessage),
+	#[child]
+	Heal(HealToolMessage),
+	#[child]
+	Clone(CloneToolMessage),
+	#[child]
+	Patch(PatchToolMessage),
+	#[child]
+	Relight(RelightToolMessage),
+	#[child]
+	Detail(DetailToolMessage),
 	// #[child]
 	// Imaginate(ImaginateToolMessage),
 
@@ -76,6 +76,11 @@ pub enum ToolMessage {
 	ActivateToolPolygon,
 
 	ActivateToolBrush,
+	ActivateToolHeal,
+	ActivateToolClone,
+	ActivateToolPatch,
+	ActivateToolDetail,
+	ActivateToolRelight,
 	// ActivateToolImaginate,
 	//
 	ActivateTool {
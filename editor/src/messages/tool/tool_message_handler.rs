@@ -64,6 +64,11 @@ impl MessageHandler<ToolMessage, ToolMessageData<'_>> for ToolMessageHandler {
 			ToolMessage::ActivateToolPolygon => responses.add_front(ToolMessage::ActivateTool { tool_type: ToolType::Polygon }),
 
 			ToolMessage::ActivateToolBrush => responses.add_front(ToolMessage::ActivateTool { tool_type: ToolType::Brush }),
+			ToolMessage::ActivateToolHeal => responses.add_front(ToolMessage::ActivateTool { tool_type: ToolType::Heal }),
+			ToolMessage::ActivateToolClone => responses.add_front(ToolMessage::ActivateTool { tool_type: ToolType::Clone }),
+			ToolMessage::ActivateToolPatch => responses.add_front(ToolMessage::ActivateTool { tool_type: ToolType::Patch }),
+			ToolMessage::ActivateToolDetail => responses.add_front(ToolMessage::ActivateTool { tool_type: ToolType::Detail }),
+			ToolMessage::ActivateToolRelight => responses.add_front(ToolMessage::ActivateTool { tool_type: ToolType::Relight }),
 			// ToolMessage::ActivateToolImaginate => responses.add_front(ToolMessage::ActivateTool { tool_type: ToolType::Imaginate }),
 			ToolMessage::ActivateTool { tool_type } => {
 				let tool_data = &mut self.tool_state.tool_data;
@@ -313,6 +318,11 @@ impl MessageHandler<ToolMessage, ToolMessageData<'_>> for ToolMessageHandler {
 			ActivateToolPolygon,
 
 			ActivateToolBrush,
+			ActivateToolHeal,
+			ActivateToolClone,
+			ActivateToolPatch,
+			ActivateToolDetail,
+			ActivateToolRelight,
 			// ActivateToolImaginate,
 
 			SelectRandomPrimaryColor,
@@ -9,9 +9,10 @@ use graph_craft::document::NodeId;
 use graph_craft::document::value::TaggedValue;
 use graphene_core::Color;
 use graphene_core::raster::BlendMode;
-use graphene_core::vector::brush_stroke::{BrushInputSample, BrushStroke, BrushStyle};
+use graphene_core::vector::brush_stroke::{BrushInputSample, BrushSource, BrushStroke, BrushStyle};
 
-const BRUSH_MAX_SIZE: f64 = 5000.;
+/// Anything bigger would cause the application to be unresponsive and eventually die.
+pub const BRUSH_MAX_SIZE: f64 = 5000.;
 
 #[derive(PartialEq, Copy, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
 pub enum DrawMode {
@@ -27,12 +28,111 @@ pub struct BrushTool {
 	options: BrushOptions,
 }
 
+/// The size and softness of the brush along with how its dabs are placed, shared by the tools which paint with brush strokes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BrushShapeOptions {
+	pub diameter: f64,
+	pub hardness: f64,
+	pub flow: f64,
+	pub spacing: f64,
+}
+
+impl Default for BrushShapeOptions {
+	fn default() -> Self {
+		Self {
+			diameter: DEFAULT_BRUSH_SIZE,
+			hardness: 0.,
+			flow: 100.,
+			spacing: 20.,
+		}
+	}
+}
+
+impl BrushShapeOptions {
+	pub fn widgets(&self, update: impl Fn(BrushShapeOptionsUpdate) -> Message + Copy + Send + Sync + 'static) -> Vec<WidgetHolder> {
+		vec![
+			NumberInput::new(Some(self.diameter))
+				.label("Diameter")
+				.min(1.)
+				.max(BRUSH_MAX_SIZE)
+				.unit(" px")
+				.on_update(move |number_input: &NumberInput| update(BrushShapeOptionsUpdate::Diameter(number_input.value.unwrap())))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(self.hardness))
+				.label("Hardness")
+				.min(0.)
+				.max(100.)
+				.mode_range()
+				.unit("%")
+				.on_update(move |number_input: &NumberInput| update(BrushShapeOptionsUpdate::Hardness(number_input.value.unwrap())))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(self.flow))
+				.label("Flow")
+				.min(1.)
+				.max(100.)
+				.mode_range()
+				.unit("%")
+				.on_update(move |number_input: &NumberInput| update(BrushShapeOptionsUpdate::Flow(number_input.value.unwrap())))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(self.spacing))
+				.label("Spacing")
+				.min(1.)
+				.max(100.)
+				.mode_range()
+				.unit("%")
+				.on_update(move |number_input: &NumberInput| update(BrushShapeOptionsUpdate::Spacing(number_input.value.unwrap())))
+				.widget_holder(),
+		]
+	}
+
+	pub fn update(&mut self, action: BrushShapeOptionsUpdate) {
+		match action {
+			BrushShapeOptionsUpdate::ChangeDiameter(change) => {
+				let needs_rounding = ((self.diameter + change.abs() / 2.) % change.abs() - change.abs() / 2.).abs() > 0.5;
+				if needs_rounding && change > 0. {
+					self.diameter = (self.diameter / change.abs()).ceil() * change.abs();
+				} else if needs_rounding && change < 0. {
+					self.diameter = (self.diameter / change.abs()).floor() * change.abs();
+				} else {
+					self.diameter = (self.diameter / change.abs()).round() * change.abs() + change;
+				}
+				self.diameter = self.diameter.max(1.);
+			}
+			BrushShapeOptionsUpdate::Diameter(diameter) => self.diameter = diameter,
+			BrushShapeOptionsUpdate::Flow(flow) => self.flow = flow,
+			BrushShapeOptionsUpdate::Hardness(hardness) => self.hardness = hardness,
+			BrushShapeOptionsUpdate::Spacing(spacing) => self.spacing = spacing,
+		}
+	}
+
+	/// The style of a new stroke in a layer whose pixels are `layer_scale` times the size of viewport pixels, so the brush keeps its diameter on screen.
+	pub fn style(&self, color: Color, blend_mode: BlendMode, layer_scale: f64) -> BrushStyle {
+		BrushStyle {
+			color,
+			diameter: self.diameter / layer_scale,
+			hardness: self.hardness,
+			flow: self.flow,
+			spacing: self.spacing,
+			blend_mode,
+		}
+	}
+}
+
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum BrushShapeOptionsUpdate {
+	ChangeDiameter(f64),
+	Diameter(f64),
+	Flow(f64),
+	Hardness(f64),
+	Spacing(f64),
+}
+
 pub struct BrushOptions {
 	legacy_warning_was_shown: bool,
-	diameter: f64,
-	hardness: f64,
-	flow: f64,
-	spacing: f64,
+	shape: BrushShapeOptions,
 	color: ToolColorOptions,
 	blend_mode: BlendMode,
 	draw_mode: DrawMode,
@@ -42,10 +142,7 @@ impl Default for BrushOptions {
 	fn default() -> Self {
 		Self {
 			legacy_warning_was_shown: false,
-			diameter: DEFAULT_BRUSH_SIZE,
-			hardness: 0.,
-			flow: 100.,
-			spacing: 20.,
+			shape: BrushShapeOptions::default(),
 			color: ToolColorOptions::default(),
 			blend_mode: BlendMode::Normal,
 			draw_mode: DrawMode::Draw,
@@ -70,14 +167,10 @@ pub enum BrushToolMessage {
 #[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
 pub enum BrushToolMessageOptionsUpdate {
 	BlendMode(BlendMode),
-	ChangeDiameter(f64),
 	Color(Option<Color>),
 	ColorType(ToolColorType),
-	Diameter(f64),
 	DrawMode(DrawMode),
-	Flow(f64),
-	Hardness(f64),
-	Spacing(f64),
+	Shape(BrushShapeOptionsUpdate),
 	WorkingColors(Option<Color>, Option<Color>),
 	NoDisplayLegacyWarning,
 }
@@ -103,42 +196,10 @@ impl ToolMetadata for BrushTool {
 
 impl LayoutHolder for BrushTool {
 	fn layout(&self) -> Layout {
-		let mut widgets = vec![
-			NumberInput::new(Some(self.options.diameter))
-				.label("Diameter")
-				.min(1.)
-				.max(BRUSH_MAX_SIZE) /* Anything bigger would cause the application to be unresponsive and eventually die */
-				.unit(" px")
-				.on_update(|number_input: &NumberInput| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::Diameter(number_input.value.unwrap())).into())
-				.widget_holder(),
-			Separator::new(SeparatorType::Related).widget_holder(),
-			NumberInput::new(Some(self.options.hardness))
-				.label("Hardness")
-				.min(0.)
-				.max(100.)
-				.mode_range()
-				.unit("%")
-				.on_update(|number_input: &NumberInput| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::Hardness(number_input.value.unwrap())).into())
-				.widget_holder(),
-			Separator::new(SeparatorType::Related).widget_holder(),
-			NumberInput::new(Some(self.options.flow))
-				.label("Flow")
-				.min(1.)
-				.max(100.)
-				.mode_range()
-				.unit("%")
-				.on_update(|number_input: &NumberInput| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::Flow(number_input.value.unwrap())).into())
-				.widget_holder(),
-			Separator::new(SeparatorType::Related).widget_holder(),
-			NumberInput::new(Some(self.options.spacing))
-				.label("Spacing")
-				.min(1.)
-				.max(100.)
-				.mode_range()
-				.unit("%")
-				.on_update(|number_input: &NumberInput| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::Spacing(number_input.value.unwrap())).into())
-				.widget_holder(),
-		];
+		let mut widgets = self
+			.options
+			.shape
+			.widgets(|action| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::Shape(action)).into());
 
 		widgets.push(Separator::new(SeparatorType::Unrelated).widget_holder());
 
@@ -193,23 +254,8 @@ impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for BrushTo
 		};
 		match action {
 			BrushToolMessageOptionsUpdate::BlendMode(blend_mode) => self.options.blend_mode = blend_mode,
-			BrushToolMessageOptionsUpdate::ChangeDiameter(change) => {
-				let needs_rounding = ((self.options.diameter + change.abs() / 2.) % change.abs() - change.abs() / 2.).abs() > 0.5;
-				if needs_rounding && change > 0. {
-					self.options.diameter = (self.options.diameter / change.abs()).ceil() * change.abs();
-				} else if needs_rounding && change < 0. {
-					self.options.diameter = (self.options.diameter / change.abs()).floor() * change.abs();
-				} else {
-					self.options.diameter = (self.options.diameter / change.abs()).round() * change.abs() + change;
-				}
-				self.options.diameter = self.options.diameter.max(1.);
-				self.send_layout(responses, LayoutTarget::ToolOptions);
-			}
-			BrushToolMessageOptionsUpdate::Diameter(diameter) => self.options.diameter = diameter,
 			BrushToolMessageOptionsUpdate::DrawMode(draw_mode) => self.options.draw_mode = draw_mode,
-			BrushToolMessageOptionsUpdate::Hardness(hardness) => self.options.hardness = hardness,
-			BrushToolMessageOptionsUpdate::Flow(flow) => self.options.flow = flow,
-			BrushToolMessageOptionsUpdate::Spacing(spacing) => self.options.spacing = spacing,
+			BrushToolMessageOptionsUpdate::Shape(action) => self.options.shape.update(action),
 			BrushToolMessageOptionsUpdate::Color(color) => {
 				self.options.color.custom_color = color;
 				self.options.color.color_type = ToolColorType::Custom;
@@ -355,14 +401,8 @@ impl Fsm for BrushToolFsmState {
 					};
 					tool_data.strokes.push(BrushStroke {
 						trace: vec![BrushInputSample { position: layer_position }],
-						style: BrushStyle {
-							color: tool_options.color.active_color().unwrap_or_default(),
-							diameter: tool_options.diameter / layer_scale,
-							hardness: tool_options.hardness,
-							flow: tool_options.flow,
-							spacing: tool_options.spacing,
-							blend_mode,
-						},
+						style: tool_options.shape.style(tool_options.color.active_color().unwrap_or_default(), blend_mode, layer_scale),
+						source: BrushSource::Color,
 					});
 
 					tool_data.update_strokes(responses);
@@ -0,0 +1,434 @@
+use super::brush_tool::{BrushShapeOptions, BrushShapeOptionsUpdate};
+use super::tool_prelude::*;
+use crate::consts::COLOR_OVERLAY_BLUE;
+use crate::messages::portfolio::document::graph_operation::transform_utils::{get_current_normalized_pivot, get_current_transform};
+use crate::messages::portfolio::document::overlays::utility_types::OverlayContext;
+use crate::messages::portfolio::document::utility_types::document_metadata::LayerNodeIdentifier;
+use crate::messages::portfolio::document::utility_types::network_interface::FlowType;
+use crate::messages::tool::common_functionality::graph_modification_utils::NodeGraphLayer;
+use graph_craft::document::value::TaggedValue;
+use graphene_core::Color;
+use graphene_core::raster::BlendMode;
+use graphene_core::vector::brush_stroke::{BrushInputSample, BrushSource, BrushStroke};
+
+#[derive(Default)]
+pub struct CloneTool {
+	fsm_state: CloneToolFsmState,
+	data: CloneToolData,
+	options: CloneOptions,
+}
+
+/// The options of the tools which paint with pixels from a source point, shared by the Clone and Heal tools.
+pub struct CloneOptions {
+	brush: BrushShapeOptions,
+	aligned: bool,
+}
+
+impl Default for CloneOptions {
+	fn default() -> Self {
+		Self {
+			brush: BrushShapeOptions::default(),
+			aligned: true,
+		}
+	}
+}
+
+impl CloneOptions {
+	pub fn widgets(&self, update: fn(CloneToolMessageOptionsUpdate) -> Message) -> Vec<WidgetHolder> {
+		let mut widgets = self.brush.widgets(move |action| update(CloneToolMessageOptionsUpdate::Brush(action)));
+		widgets.extend([
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			CheckboxInput::new(self.aligned)
+				.tooltip("Keep the source moving along with each new stroke, instead of restarting every stroke from the source point")
+				.on_update(move |input: &CheckboxInput| update(CloneToolMessageOptionsUpdate::Aligned(input.checked)))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			TextLabel::new("Aligned").widget_holder(),
+		]);
+		widgets
+	}
+
+	pub fn update(&mut self, action: CloneToolMessageOptionsUpdate, data: &mut CloneToolData, responses: &mut VecDeque<Message>) {
+		match action {
+			CloneToolMessageOptionsUpdate::Aligned(aligned) => {
+				self.aligned = aligned;
+				data.aligned_offset = None;
+			}
+			CloneToolMessageOptionsUpdate::Brush(action) => {
+				self.brush.update(action);
+				responses.add(OverlaysMessage::Draw);
+			}
+		}
+	}
+}
+
+#[impl_message(Message, ToolMessage, Clone)]
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum CloneToolMessage {
+	// Standard messages
+	Abort,
+	Overlays(OverlayContext),
+
+	// Tool-specific messages
+	DragStart,
+	DragStop,
+	PointerMove,
+	SetSource,
+	UpdateOptions(CloneToolMessageOptionsUpdate),
+}
+
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum CloneToolMessageOptionsUpdate {
+	Aligned(bool),
+	Brush(BrushShapeOptionsUpdate),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum CloneToolFsmState {
+	#[default]
+	Ready,
+	Drawing,
+}
+
+impl ToolMetadata for CloneTool {
+	fn icon_name(&self) -> String {
+		"RasterCloneTool".into()
+	}
+	fn tooltip(&self) -> String {
+		"Clone Tool".into()
+	}
+	fn tool_type(&self) -> crate::messages::tool::utility_types::ToolType {
+		ToolType::Clone
+	}
+}
+
+impl LayoutHolder for CloneTool {
+	fn layout(&self) -> Layout {
+		let widgets = self.options.widgets(|action| CloneToolMessage::UpdateOptions(action).into());
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row { widgets }]))
+	}
+}
+
+impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for CloneTool {
+	fn process_message(&mut self, message: ToolMessage, responses: &mut VecDeque<Message>, tool_data: &mut ToolActionHandlerData<'a>) {
+		let ToolMessage::Clone(CloneToolMessage::UpdateOptions(action)) = message else {
+			self.fsm_state.process_event(message, &mut self.data, tool_data, &self.options, responses, true);
+			return;
+		};
+		self.options.update(action, &mut self.data, responses);
+
+		self.send_layout(responses, LayoutTarget::ToolOptions);
+	}
+
+	fn actions(&self) -> ActionList {
+		match self.fsm_state {
+			CloneToolFsmState::Ready => actions!(CloneToolMessageDiscriminant;
+				DragStart,
+				DragStop,
+				PointerMove,
+				SetSource,
+				UpdateOptions,
+			),
+			CloneToolFsmState::Drawing => actions!(CloneToolMessageDiscriminant;
+				DragStop,
+				PointerMove,
+				Abort,
+				UpdateOptions,
+			),
+		}
+	}
+}
+
+impl ToolTransition for CloneTool {
+	fn event_to_message_map(&self) -> EventToMessageMap {
+		EventToMessageMap {
+			tool_abort: Some(CloneToolMessage::Abort.into()),
+			overlay_provider: Some(|overlay_context: OverlayContext| CloneToolMessage::Overlays(overlay_context).into()),
+			..Default::default()
+		}
+	}
+}
+
+/// The state of the tools which paint with pixels from a source point, shared by the Clone and Heal tools.
+#[derive(Clone, Debug, Default)]
+pub struct CloneToolData {
+	strokes: Vec<BrushStroke>,
+	layer: Option<LayerNodeIdentifier>,
+	transform: DAffine2,
+	/// The point to clone from in document space, picked with Alt-click.
+	source: Option<DVec2>,
+	/// The document space offset from the first stroke since the source was picked to the source, reused by later strokes in aligned mode.
+	aligned_offset: Option<DVec2>,
+	/// The document space offset from the cursor to the pixels being cloned by the current stroke.
+	stroke_offset: DVec2,
+}
+
+impl CloneToolData {
+	/// Finds the selected raster layer to paint into along with its existing brush strokes, if any. The "Brush" node holding them is added on the first stroke otherwise.
+	fn load_existing_strokes(&mut self, document: &mut DocumentMessageHandler) -> Option<LayerNodeIdentifier> {
+		self.transform = DAffine2::IDENTITY;
+		self.strokes.clear();
+
+		if document.network_interface.selected_nodes().selected_layers(document.metadata()).count() != 1 {
+			return None;
+		}
+		let layer = document.network_interface.selected_nodes().selected_layers(document.metadata()).next()?;
+
+		for node_id in document.network_interface.upstream_flow_back_from_nodes(vec![layer.to_node()], &[], FlowType::HorizontalFlow) {
+			let Some(node) = document.network_interface.document_network().nodes.get(&node_id) else {
+				continue;
+			};
+			let Some(reference) = document.network_interface.reference(&node_id, &[]) else {
+				continue;
+			};
+			if *reference == Some("Brush".to_string()) && node_id != layer.to_node() {
+				let points_input = node.inputs.get(2)?;
+				let Some(TaggedValue::BrushStrokes(strokes)) = points_input.as_value() else {
+					continue;
+				};
+				self.strokes.clone_from(strokes);
+				self.layer = Some(layer);
+
+				return Some(layer);
+			} else if *reference == Some("Transform".to_string()) {
+				let upstream = document.metadata().upstream_transform(node_id);
+				let pivot = DAffine2::from_translation(upstream.transform_point2(get_current_normalized_pivot(&node.inputs)));
+				self.transform = pivot * get_current_transform(&node.inputs) * pivot.inverse() * self.transform;
+			}
+		}
+
+		// A new "Brush" node gets inserted directly upstream of the layer, where no transforms apply
+		self.transform = DAffine2::IDENTITY;
+		if !NodeGraphLayer::is_raster_layer(layer, &mut document.network_interface) {
+			return None;
+		}
+		self.layer = Some(layer);
+		Some(layer)
+	}
+
+	fn document_to_layer(&self, document: &DocumentMessageHandler, layer: LayerNodeIdentifier) -> DAffine2 {
+		let viewport_to_layer = document.metadata().downstream_transform_to_viewport(layer).inverse();
+		self.transform.inverse() * viewport_to_layer * document.metadata().document_to_viewport
+	}
+
+	fn update_strokes(&self, responses: &mut VecDeque<Message>) {
+		let Some(layer) = self.layer else { return };
+		let strokes = self.strokes.clone();
+		responses.add(GraphOperationMessage::Brush { layer, strokes });
+	}
+
+	pub fn set_source(&mut self, document: &DocumentMessageHandler, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		self.source = Some(document.metadata().document_to_viewport.inverse().transform_point2(input.mouse.position));
+		self.aligned_offset = None;
+		responses.add(OverlaysMessage::Draw);
+	}
+
+	/// Starts a stroke under the cursor painting with the pixels at the source offset, unless no source point was picked yet or no single raster layer is selected.
+	pub fn start_stroke(
+		&mut self,
+		document: &mut DocumentMessageHandler,
+		input: &InputPreprocessorMessageHandler,
+		options: &CloneOptions,
+		brush_source: fn(DVec2) -> BrushSource,
+		responses: &mut VecDeque<Message>,
+	) -> bool {
+		let Some(source) = self.source else { return false };
+		let Some(layer) = self.load_existing_strokes(document) else { return false };
+		responses.add(DocumentMessage::StartTransaction);
+
+		let start = document.metadata().document_to_viewport.inverse().transform_point2(input.mouse.position);
+		let offset = match self.aligned_offset {
+			Some(offset) if options.aligned => offset,
+			_ => source - start,
+		};
+		self.aligned_offset = Some(offset);
+		self.stroke_offset = offset;
+
+		// The offset is measured in document space, so it has to be brought into the layer space of the stroke
+		let document_to_layer = self.document_to_layer(document, layer);
+		let layer_position = document_to_layer.transform_point2(start);
+		let layer_offset = document_to_layer.transform_point2(start + offset) - layer_position;
+
+		let layer_document_scale = document.metadata().downstream_transform_to_viewport(layer) * self.transform;
+		let layer_scale = 0.0001_f64 // Safety against division by zero
+			.max((layer_document_scale.matrix2 * glam::DVec2::X).length())
+			.max((layer_document_scale.matrix2 * glam::DVec2::Y).length());
+
+		self.strokes.push(BrushStroke {
+			trace: vec![BrushInputSample { position: layer_position }],
+			style: options.brush.style(Color::WHITE, BlendMode::Normal, layer_scale),
+			source: brush_source(layer_offset),
+		});
+
+		self.update_strokes(responses);
+		responses.add(OverlaysMessage::Draw);
+		true
+	}
+
+	pub fn extend_stroke(&mut self, document: &DocumentMessageHandler, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		if let Some(layer) = self.layer {
+			if let Some(stroke) = self.strokes.last_mut() {
+				let layer_position = document
+					.network_interface
+					.document_metadata()
+					.downstream_transform_to_viewport(layer)
+					.inverse()
+					.transform_point2(input.mouse.position);
+				let layer_position = self.transform.inverse().transform_point2(layer_position);
+
+				stroke.trace.push(BrushInputSample { position: layer_position })
+			}
+		}
+		self.update_strokes(responses);
+		responses.add(OverlaysMessage::Draw);
+	}
+
+	pub fn end_stroke(&mut self) {
+		self.strokes.clear();
+	}
+
+	/// Marks where the pixels under the cursor get sampled from, or the source point before any stroke.
+	pub fn draw_source(&self, overlay_context: &mut OverlayContext, document: &DocumentMessageHandler, input: &InputPreprocessorMessageHandler, drawing: bool, options: &CloneOptions) {
+		let cursor = document.metadata().document_to_viewport.inverse().transform_point2(input.mouse.position);
+		let source = match self.aligned_offset {
+			_ if drawing => Some(cursor + self.stroke_offset),
+			Some(offset) if options.aligned => Some(cursor + offset),
+			_ => self.source,
+		};
+		let Some(source) = source else { return };
+
+		let source = document.metadata().document_to_viewport.transform_point2(source);
+		overlay_context.circle(source, options.brush.diameter / 2., None, Some(COLOR_OVERLAY_BLUE));
+		overlay_context.line(source - DVec2::X * 4., source + DVec2::X * 4., Some(COLOR_OVERLAY_BLUE), None);
+		overlay_context.line(source - DVec2::Y * 4., source + DVec2::Y * 4., Some(COLOR_OVERLAY_BLUE), None);
+	}
+}
+
+impl Fsm for CloneToolFsmState {
+	type ToolData = CloneToolData;
+	type ToolOptions = CloneOptions;
+
+	fn transition(self, event: ToolMessage, tool_data: &mut Self::ToolData, tool_action_data: &mut ToolActionHandlerData, tool_options: &Self::ToolOptions, responses: &mut VecDeque<Message>) -> Self {
+		let ToolActionHandlerData { document, input, .. } = tool_action_data;
+
+		let ToolMessage::Clone(event) = event else { return self };
+		match (self, event) {
+			(_, CloneToolMessage::Overlays(mut overlay_context)) => {
+				tool_data.draw_source(&mut overlay_context, document, input, self == CloneToolFsmState::Drawing, tool_options);
+
+				self
+			}
+			(CloneToolFsmState::Ready, CloneToolMessage::SetSource) => {
+				tool_data.set_source(document, input, responses);
+
+				CloneToolFsmState::Ready
+			}
+			(CloneToolFsmState::Ready, CloneToolMessage::DragStart) => {
+				if !tool_data.start_stroke(document, input, tool_options, |offset| BrushSource::Clone { offset }, responses) {
+					return self;
+				}
+
+				CloneToolFsmState::Drawing
+			}
+			(CloneToolFsmState::Drawing, CloneToolMessage::PointerMove) => {
+				tool_data.extend_stroke(document, input, responses);
+
+				CloneToolFsmState::Drawing
+			}
+			(CloneToolFsmState::Ready, CloneToolMessage::PointerMove) => {
+				responses.add(OverlaysMessage::Draw);
+
+				CloneToolFsmState::Ready
+			}
+			(CloneToolFsmState::Drawing, CloneToolMessage::DragStop) => {
+				responses.add(DocumentMessage::EndTransaction);
+				tool_data.end_stroke();
+
+				CloneToolFsmState::Ready
+			}
+			(CloneToolFsmState::Drawing, CloneToolMessage::Abort) => {
+				responses.add(DocumentMessage::AbortTransaction);
+				tool_data.end_stroke();
+
+				CloneToolFsmState::Ready
+			}
+			_ => self,
+		}
+	}
+
+	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+		let hint_data = match self {
+			CloneToolFsmState::Ready => HintData(vec![
+				HintGroup(vec![HintInfo::keys_and_mouse([Key::Alt], MouseMotion::Lmb, "Set Source")]),
+				HintGroup(vec![HintInfo::mouse(MouseMotion::LmbDrag, "Clone")]),
+				HintGroup(vec![HintInfo::multi_keys([[Key::BracketLeft], [Key::BracketRight]], "Shrink/Grow Brush")]),
+			]),
+			CloneToolFsmState::Drawing => HintData(vec![HintGroup(vec![HintInfo::mouse(MouseMotion::Rmb, ""), HintInfo::keys([Key::Escape], "Cancel").prepend_slash()])]),
+		};
+
+		responses.add(FrontendMessage::UpdateInputHints { hint_data });
+	}
+
+	fn update_cursor(&self, responses: &mut VecDeque<Message>) {
+		responses.add(FrontendMessage::UpdateMouseCursor { cursor: MouseCursorIcon::Default });
+	}
+}
+
+#[cfg(test)]
+mod test_clone {
+	pub use crate::test_utils::test_prelude::*;
+	use graphene_core::vector::brush_stroke::{BrushSource, BrushStroke};
+
+	async fn get_strokes(editor: &mut EditorTestUtils) -> Vec<Vec<BrushStroke>> {
+		let instrumented = editor.eval_graph().await;
+
+		instrumented.grab_all_input::<graphene_std::brush::brush::StrokesInput>(&editor.runtime).collect()
+	}
+
+	#[tokio::test]
+	async fn clone_needs_source() {
+		let mut editor = EditorTestUtils::create();
+		editor.new_document().await;
+		editor.create_raster_image(Image::new(100, 100, Color::WHITE), Some((50., 50.))).await;
+		editor.drag_tool(ToolType::Clone, 50., 50., 60., 50., ModifierKeys::empty()).await;
+		assert!(get_strokes(&mut editor).await.is_empty());
+	}
+
+	#[tokio::test]
+	async fn clone_from_source() {
+		let mut editor = EditorTestUtils::create();
+		editor.new_document().await;
+		editor.create_raster_image(Image::new(100, 100, Color::WHITE), Some((50., 50.))).await;
+		editor.click_tool(ToolType::Clone, MouseKeys::LEFT, DVec2::new(10., 10.), ModifierKeys::ALT).await;
+		editor.drag_tool(ToolType::Clone, 50., 50., 60., 50., ModifierKeys::empty()).await;
+
+		let strokes = get_strokes(&mut editor).await;
+		assert_eq!(strokes.len(), 1);
+		assert_eq!(strokes[0].len(), 1);
+		let BrushSource::Clone { offset } = strokes[0][0].source else {
+			panic!("The stroke should clone, but it paints with {:?}", strokes[0][0].source);
+		};
+		assert!(offset.abs_diff_eq(DVec2::new(-40., -40.), 1e-6), "{offset:?}");
+		assert!(strokes[0][0].trace[0].position.abs_diff_eq(DVec2::new(50., 50.), 1e-6), "{:?}", strokes[0][0].trace);
+	}
+
+	#[tokio::test]
+	async fn aligned_strokes_keep_offset() {
+		let mut editor = EditorTestUtils::create();
+		editor.new_document().await;
+		editor.create_raster_image(Image::new(100, 100, Color::WHITE), Some((50., 50.))).await;
+		editor.click_tool(ToolType::Clone, MouseKeys::LEFT, DVec2::new(10., 10.), ModifierKeys::ALT).await;
+		editor.drag_tool(ToolType::Clone, 50., 50., 60., 50., ModifierKeys::empty()).await;
+		editor.drag_tool(ToolType::Clone, 80., 80., 90., 80., ModifierKeys::empty()).await;
+
+		// Both strokes end up in the same "Brush" node, sampling from the same distance away
+		let strokes = get_strokes(&mut editor).await;
+		assert_eq!(strokes.len(), 1);
+		assert_eq!(strokes[0].len(), 2);
+		for stroke in &strokes[0] {
+			let BrushSource::Clone { offset } = stroke.source else { panic!("{:?}", stroke.source) };
+			assert!(offset.abs_diff_eq(DVec2::new(-40., -40.), 1e-6), "{offset:?}");
+		}
+	}
+}
@@ -0,0 +1,367 @@
+use super::brush_tool::{BrushShapeOptions, BrushShapeOptionsUpdate};
+use super::tool_prelude::*;
+use crate::messages::portfolio::document::graph_operation::utility_types::ModifyInputsContext;
+use crate::messages::portfolio::document::utility_types::document_metadata::LayerNodeIdentifier;
+use crate::messages::tool::common_functionality::graph_modification_utils::NodeGraphLayer;
+use graph_craft::document::value::TaggedValue;
+use graphene_core::Color;
+use graphene_core::raster::BlendMode;
+use graphene_core::vector::brush_stroke::{BrushInputSample, BrushSource, BrushStroke};
+
+#[derive(PartialEq, Copy, Clone, Debug, Default, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum DetailMode {
+	#[default]
+	Blur,
+	Sharpen,
+	Smudge,
+}
+
+impl DetailMode {
+	pub fn list() -> [DetailMode; 3] {
+		[DetailMode::Blur, DetailMode::Sharpen, DetailMode::Smudge]
+	}
+
+	/// The reference name of the node which paints this mode, along with its parameters that a stroke must match to be added to an existing node.
+	/// Sharpening is a blur blended in with a negative amount.
+	pub fn node(self, strength: f64) -> (&'static str, Vec<(usize, TaggedValue)>) {
+		match self {
+			DetailMode::Blur => ("Detail", vec![(2, TaggedValue::F64(strength))]),
+			DetailMode::Sharpen => ("Detail", vec![(2, TaggedValue::F64(-strength))]),
+			DetailMode::Smudge => ("Smudge", vec![(2, TaggedValue::F64(strength))]),
+		}
+	}
+}
+
+impl std::fmt::Display for DetailMode {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			DetailMode::Blur => write!(f, "Blur"),
+			DetailMode::Sharpen => write!(f, "Sharpen"),
+			DetailMode::Smudge => write!(f, "Smudge"),
+		}
+	}
+}
+
+#[derive(Default)]
+pub struct DetailTool {
+	fsm_state: DetailToolFsmState,
+	data: BrushMaskToolData,
+	options: DetailOptions,
+}
+
+pub struct DetailOptions {
+	brush: BrushShapeOptions,
+	mode: DetailMode,
+	strength: f64,
+}
+
+impl Default for DetailOptions {
+	fn default() -> Self {
+		Self {
+			brush: BrushShapeOptions::default(),
+			mode: DetailMode::Blur,
+			strength: 50.,
+		}
+	}
+}
+
+#[impl_message(Message, ToolMessage, Detail)]
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum DetailToolMessage {
+	// Standard messages
+	Abort,
+
+	// Tool-specific messages
+	DragStart,
+	DragStop,
+	PointerMove,
+	UpdateOptions(DetailToolMessageOptionsUpdate),
+}
+
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum DetailToolMessageOptionsUpdate {
+	Brush(BrushShapeOptionsUpdate),
+	Mode(DetailMode),
+	Strength(f64),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum DetailToolFsmState {
+	#[default]
+	Ready,
+	Drawing,
+}
+
+impl ToolMetadata for DetailTool {
+	fn icon_name(&self) -> String {
+		"RasterDetailTool".into()
+	}
+	fn tooltip(&self) -> String {
+		"Detail Tool".into()
+	}
+	fn tool_type(&self) -> crate::messages::tool::utility_types::ToolType {
+		ToolType::Detail
+	}
+}
+
+impl LayoutHolder for DetailTool {
+	fn layout(&self) -> Layout {
+		let mut widgets = self
+			.options
+			.brush
+			.widgets(|action| DetailToolMessage::UpdateOptions(DetailToolMessageOptionsUpdate::Brush(action)).into());
+
+		widgets.push(Separator::new(SeparatorType::Unrelated).widget_holder());
+
+		let mode_entries: Vec<_> = DetailMode::list()
+			.into_iter()
+			.map(|mode| {
+				RadioEntryData::new(format!("{mode:?}"))
+					.label(mode.to_string())
+					.on_update(move |_| DetailToolMessage::UpdateOptions(DetailToolMessageOptionsUpdate::Mode(mode)).into())
+			})
+			.collect();
+		widgets.push(RadioInput::new(mode_entries).selected_index(Some(self.options.mode as u32)).widget_holder());
+
+		widgets.push(Separator::new(SeparatorType::Related).widget_holder());
+
+		widgets.push(
+			NumberInput::new(Some(self.options.strength))
+				.label("Strength")
+				.min(1.)
+				.max(100.)
+				.mode_range()
+				.unit("%")
+				.on_update(|number_input: &NumberInput| DetailToolMessage::UpdateOptions(DetailToolMessageOptionsUpdate::Strength(number_input.value.unwrap())).into())
+				.widget_holder(),
+		);
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row { widgets }]))
+	}
+}
+
+impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for DetailTool {
+	fn process_message(&mut self, message: ToolMessage, responses: &mut VecDeque<Message>, tool_data: &mut ToolActionHandlerData<'a>) {
+		let ToolMessage::Detail(DetailToolMessage::UpdateOptions(action)) = message else {
+			self.fsm_state.process_event(message, &mut self.data, tool_data, &self.options, responses, true);
+			return;
+		};
+		match action {
+			DetailToolMessageOptionsUpdate::Brush(action) => self.options.brush.update(action),
+			DetailToolMessageOptionsUpdate::Mode(mode) => self.options.mode = mode,
+			DetailToolMessageOptionsUpdate::Strength(strength) => self.options.strength = strength,
+		}
+
+		self.send_layout(responses, LayoutTarget::ToolOptions);
+	}
+
+	fn actions(&self) -> ActionList {
+		match self.fsm_state {
+			DetailToolFsmState::Ready => actions!(DetailToolMessageDiscriminant;
+				DragStart,
+				DragStop,
+				UpdateOptions,
+			),
+			DetailToolFsmState::Drawing => actions!(DetailToolMessageDiscriminant;
+				DragStop,
+				PointerMove,
+				Abort,
+				UpdateOptions,
+			),
+		}
+	}
+}
+
+impl ToolTransition for DetailTool {
+	fn event_to_message_map(&self) -> EventToMessageMap {
+		EventToMessageMap {
+			tool_abort: Some(DetailToolMessage::Abort.into()),
+			..Default::default()
+		}
+	}
+}
+
+/// The state of the tools whose strokes mask the effect of a node directly upstream of the layer, shared by the Detail and Relight tools.
+#[derive(Clone, Debug, Default)]
+pub struct BrushMaskToolData {
+	strokes: Vec<BrushStroke>,
+	layer: Option<LayerNodeIdentifier>,
+}
+
+impl BrushMaskToolData {
+	/// Starts a stroke under the cursor, unless no single raster layer is selected. If the node directly upstream of the layer is the given node
+	/// with the same values at the given input indices, its strokes are continued, otherwise a new node with these values gets added on the first update.
+	pub fn start_stroke(
+		&mut self,
+		document: &mut DocumentMessageHandler,
+		input: &InputPreprocessorMessageHandler,
+		options: &BrushShapeOptions,
+		reference_name: &str,
+		parameters: &[(usize, TaggedValue)],
+		responses: &mut VecDeque<Message>,
+	) -> bool {
+		self.strokes.clear();
+		self.layer = None;
+
+		let mut selected_layers = document.network_interface.selected_nodes().selected_layers(document.metadata());
+		let Some(layer) = selected_layers.next() else { return false };
+		if selected_layers.next().is_some() || !NodeGraphLayer::is_raster_layer(layer, &mut document.network_interface) {
+			return false;
+		}
+
+		if let Some(node_id) = ModifyInputsContext::locate_brush_mask_node(reference_name, parameters, layer, &document.network_interface) {
+			let strokes_input = document.network_interface.document_node(&node_id, &[]).and_then(|node| node.inputs.get(1));
+			if let Some(TaggedValue::BrushStrokes(strokes)) = strokes_input.and_then(|input| input.as_value()) {
+				self.strokes.clone_from(strokes);
+			}
+		}
+		self.layer = Some(layer);
+		responses.add(DocumentMessage::StartTransaction);
+
+		// The node is directly upstream of the layer, where only the transforms downstream of the layer apply
+		let layer_to_viewport = document.metadata().downstream_transform_to_viewport(layer);
+		let layer_position = layer_to_viewport.inverse().transform_point2(input.mouse.position);
+		let layer_scale = 0.0001_f64 // Safety against division by zero
+			.max((layer_to_viewport.matrix2 * glam::DVec2::X).length())
+			.max((layer_to_viewport.matrix2 * glam::DVec2::Y).length());
+
+		self.strokes.push(BrushStroke {
+			trace: vec![BrushInputSample { position: layer_position }],
+			style: options.style(Color::WHITE, BlendMode::Normal, layer_scale),
+			source: BrushSource::Color,
+		});
+		true
+	}
+
+	pub fn extend_stroke(&mut self, document: &DocumentMessageHandler, input: &InputPreprocessorMessageHandler) {
+		let (Some(layer), Some(stroke)) = (self.layer, self.strokes.last_mut()) else { return };
+
+		let layer_position = document.metadata().downstream_transform_to_viewport(layer).inverse().transform_point2(input.mouse.position);
+		stroke.trace.push(BrushInputSample { position: layer_position });
+	}
+
+	/// Sends the strokes to the node they mask, using the message built by the given function.
+	pub fn update_strokes(&self, responses: &mut VecDeque<Message>, message: impl FnOnce(LayerNodeIdentifier, Vec<BrushStroke>) -> GraphOperationMessage) {
+		let Some(layer) = self.layer else { return };
+		responses.add(message(layer, self.strokes.clone()));
+	}
+
+	pub fn end_stroke(&mut self) {
+		self.strokes.clear();
+		self.layer = None;
+	}
+}
+
+impl Fsm for DetailToolFsmState {
+	type ToolData = BrushMaskToolData;
+	type ToolOptions = DetailOptions;
+
+	fn transition(self, event: ToolMessage, tool_data: &mut Self::ToolData, tool_action_data: &mut ToolActionHandlerData, tool_options: &Self::ToolOptions, responses: &mut VecDeque<Message>) -> Self {
+		let ToolActionHandlerData { document, input, .. } = tool_action_data;
+		let (mode, strength) = (tool_options.mode, tool_options.strength);
+		let update = |layer, strokes| GraphOperationMessage::Detail { layer, strokes, mode, strength };
+
+		let ToolMessage::Detail(event) = event else { return self };
+		match (self, event) {
+			(DetailToolFsmState::Ready, DetailToolMessage::DragStart) => {
+				let (reference, parameters) = mode.node(strength);
+				if !tool_data.start_stroke(document, input, &tool_options.brush, reference, &parameters, responses) {
+					return self;
+				}
+				tool_data.update_strokes(responses, update);
+
+				DetailToolFsmState::Drawing
+			}
+			(DetailToolFsmState::Drawing, DetailToolMessage::PointerMove) => {
+				tool_data.extend_stroke(document, input);
+				tool_data.update_strokes(responses, update);
+
+				DetailToolFsmState::Drawing
+			}
+			(DetailToolFsmState::Drawing, DetailToolMessage::DragStop) => {
+				responses.add(DocumentMessage::EndTransaction);
+				tool_data.end_stroke();
+
+				DetailToolFsmState::Ready
+			}
+			(DetailToolFsmState::Drawing, DetailToolMessage::Abort) => {
+				responses.add(DocumentMessage::AbortTransaction);
+				tool_data.end_stroke();
+
+				DetailToolFsmState::Ready
+			}
+			_ => self,
+		}
+	}
+
+	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+		let hint_data = match self {
+			DetailToolFsmState::Ready => HintData(vec![
+				HintGroup(vec![HintInfo::mouse(MouseMotion::LmbDrag, "Blur, Sharpen or Smudge")]),
+				HintGroup(vec![HintInfo::multi_keys([[Key::BracketLeft], [Key::BracketRight]], "Shrink/Grow Brush")]),
+			]),
+			DetailToolFsmState::Drawing => HintData(vec![HintGroup(vec![HintInfo::mouse(MouseMotion::Rmb, ""), HintInfo::keys([Key::Escape], "Cancel").prepend_slash()])]),
+		};
+
+		responses.add(FrontendMessage::UpdateInputHints { hint_data });
+	}
+
+	fn update_cursor(&self, responses: &mut VecDeque<Message>) {
+		responses.add(FrontendMessage::UpdateMouseCursor { cursor: MouseCursorIcon::Default });
+	}
+}
+
+#[cfg(test)]
+mod test_detail {
+	use super::{DetailMode, DetailToolMessage, DetailToolMessageOptionsUpdate};
+	pub use crate::test_utils::test_prelude::*;
+	use graphene_std::retouch::{masked_blend, smudge};
+
+	async fn set_mode(editor: &mut EditorTestUtils, mode: DetailMode) {
+		editor.select_tool(ToolType::Detail).await;
+		editor.handle_message(DetailToolMessage::UpdateOptions(DetailToolMessageOptionsUpdate::Mode(mode))).await;
+	}
+
+	#[tokio::test]
+	async fn blur_and_sharpen() {
+		let mut editor = EditorTestUtils::create();
+		editor.new_document().await;
+		editor.create_raster_image(Image::new(100, 100, Color::WHITE), Some((50., 50.))).await;
+		editor.drag_tool(ToolType::Detail, 20., 20., 40., 20., ModifierKeys::empty()).await;
+		editor.drag_tool(ToolType::Detail, 20., 60., 40., 60., ModifierKeys::empty()).await;
+
+		// Strokes with the same mode and strength are added to the same node
+		let instrumented = editor.eval_graph().await;
+		let strokes: Vec<_> = instrumented.grab_all_input::<masked_blend::StrokesInput>(&editor.runtime).collect();
+		assert_eq!(strokes.len(), 1);
+		assert_eq!(strokes[0].len(), 2);
+		let amounts: Vec<_> = instrumented.grab_all_input::<masked_blend::AmountInput>(&editor.runtime).collect();
+		assert_eq!(amounts, vec![50.]);
+
+		set_mode(&mut editor, DetailMode::Sharpen).await;
+		editor.drag_tool(ToolType::Detail, 60., 20., 80., 20., ModifierKeys::empty()).await;
+
+		// Sharpening blends away from the blurred image in a node of its own
+		let instrumented = editor.eval_graph().await;
+		let mut amounts: Vec<_> = instrumented.grab_all_input::<masked_blend::AmountInput>(&editor.runtime).collect();
+		amounts.sort_by(f64::total_cmp);
+		assert_eq!(amounts, vec![-50., 50.]);
+	}
+
+	#[tokio::test]
+	async fn smudge_strokes() {
+		let mut editor = EditorTestUtils::create();
+		editor.new_document().await;
+		editor.create_raster_image(Image::new(100, 100, Color::WHITE), Some((50., 50.))).await;
+		set_mode(&mut editor, DetailMode::Smudge).await;
+		editor.drag_tool(ToolType::Detail, 20., 20., 40., 20., ModifierKeys::empty()).await;
+
+		let instrumented = editor.eval_graph().await;
+		let strokes: Vec<_> = instrumented.grab_all_input::<smudge::StrokesInput>(&editor.runtime).collect();
+		assert_eq!(strokes.len(), 1);
+		assert_eq!(strokes[0].len(), 1);
+		let strengths: Vec<_> = instrumented.grab_all_input::<smudge::StrengthInput>(&editor.runtime).collect();
+		assert_eq!(strengths, vec![50.]);
+		assert_eq!(instrumented.grab_all_input::<masked_blend::StrokesInput>(&editor.runtime).count(), 0);
+	}
+}
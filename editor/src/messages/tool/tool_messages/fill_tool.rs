@@ -1,13 +1,40 @@
 use super::tool_prelude::*;
+use crate::messages::portfolio::document::utility_types::document_metadata::LayerNodeIdentifier;
 use crate::messages::tool::common_functionality::graph_modification_utils::NodeGraphLayer;
-use graphene_core::vector::style::Fill;
+use bezier_rs::Subpath;
+use graph_craft::document::NodeId;
+use graphene_core::Color;
+use graphene_core::vector::PointId;
+use graphene_core::vector::style::{Fill, Stroke};
 #[derive(Default)]
 pub struct FillTool {
 	fsm_state: FillToolFsmState,
+	options: FillOptions,
+}
+
+/// The options for bucket filling raster layers, and for filling the regions enclosed by vector artwork.
+pub struct FillOptions {
+	tolerance: f64,
+	contiguous: bool,
+	anti_aliasing: bool,
+	sample_all_layers: bool,
+	fill_regions: bool,
+}
+
+impl Default for FillOptions {
+	fn default() -> Self {
+		Self {
+			tolerance: 12.5,
+			contiguous: true,
+			anti_aliasing: true,
+			sample_all_layers: false,
+			fill_regions: false,
+		}
+	}
 }
 
 #[impl_message(Message, ToolMessage, Fill)]
-#[derive(PartialEq, Eq, Clone, Debug, Hash, serde::Serialize, serde::Deserialize, specta::Type)]
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
 pub enum FillToolMessage {
 	// Standard messages
 	Abort,
@@ -16,6 +43,16 @@ pub enum FillToolMessage {
 	PointerUp,
 	FillPrimaryColor,
 	FillSecondaryColor,
+	UpdateOptions(FillToolMessageOptionsUpdate),
+}
+
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum FillToolMessageOptionsUpdate {
+	AntiAliasing(bool),
+	Contiguous(bool),
+	FillRegions(bool),
+	SampleAllLayers(bool),
+	Tolerance(f64),
 }
 
 impl ToolMetadata for FillTool {
@@ -30,25 +67,90 @@ impl ToolMetadata for FillTool {
 	}
 }
 
+fn checkbox_widgets(label: &str, checked: bool, tooltip: &str, update: fn(bool) -> FillToolMessageOptionsUpdate) -> [WidgetHolder; 3] {
+	[
+		CheckboxInput::new(checked)
+			.tooltip(tooltip)
+			.on_update(move |input: &CheckboxInput| FillToolMessage::UpdateOptions(update(input.checked)).into())
+			.widget_holder(),
+		Separator::new(SeparatorType::Related).widget_holder(),
+		TextLabel::new(label).tooltip(tooltip).widget_holder(),
+	]
+}
+
 impl LayoutHolder for FillTool {
 	fn layout(&self) -> Layout {
-		Layout::WidgetLayout(WidgetLayout::default())
+		let mut widgets = vec![
+			NumberInput::new(Some(self.options.tolerance))
+				.label("Tolerance")
+				.min(0.)
+				.max(100.)
+				.mode_range()
+				.unit("%")
+				.tooltip("How different from the clicked color the colors filled on raster layers may be")
+				.on_update(|number_input: &NumberInput| FillToolMessage::UpdateOptions(FillToolMessageOptionsUpdate::Tolerance(number_input.value.unwrap())).into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+		];
+		widgets.extend(checkbox_widgets(
+			"Contiguous",
+			self.options.contiguous,
+			"Only fill the area connected to the clicked pixel, instead of every matching pixel of the raster layer",
+			FillToolMessageOptionsUpdate::Contiguous,
+		));
+		widgets.push(Separator::new(SeparatorType::Unrelated).widget_holder());
+		widgets.extend(checkbox_widgets(
+			"Anti-aliasing",
+			self.options.anti_aliasing,
+			"Smooth the edges of the filled area",
+			FillToolMessageOptionsUpdate::AntiAliasing,
+		));
+		widgets.push(Separator::new(SeparatorType::Unrelated).widget_holder());
+		widgets.extend(checkbox_widgets(
+			"Sample All Layers",
+			self.options.sample_all_layers,
+			"Find the area to fill from the raster layers beneath as well, not just the clicked layer",
+			FillToolMessageOptionsUpdate::SampleAllLayers,
+		));
+		widgets.push(Separator::new(SeparatorType::Section).widget_holder());
+		widgets.extend(checkbox_widgets(
+			"Fill Regions",
+			self.options.fill_regions,
+			"Fill the region enclosed by the surrounding vector paths under the cursor with a new shape, instead of changing the fill of the clicked shape.\nRegions are always filled when clicking outside of any shape.",
+			FillToolMessageOptionsUpdate::FillRegions,
+		));
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row { widgets }]))
 	}
 }
 
 impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for FillTool {
 	fn process_message(&mut self, message: ToolMessage, responses: &mut VecDeque<Message>, tool_data: &mut ToolActionHandlerData<'a>) {
-		self.fsm_state.process_event(message, &mut (), tool_data, &(), responses, true);
+		let ToolMessage::Fill(FillToolMessage::UpdateOptions(action)) = message else {
+			self.fsm_state.process_event(message, &mut (), tool_data, &self.options, responses, true);
+			return;
+		};
+		match action {
+			FillToolMessageOptionsUpdate::AntiAliasing(anti_aliasing) => self.options.anti_aliasing = anti_aliasing,
+			FillToolMessageOptionsUpdate::Contiguous(contiguous) => self.options.contiguous = contiguous,
+			FillToolMessageOptionsUpdate::FillRegions(fill_regions) => self.options.fill_regions = fill_regions,
+			FillToolMessageOptionsUpdate::SampleAllLayers(sample_all_layers) => self.options.sample_all_layers = sample_all_layers,
+			FillToolMessageOptionsUpdate::Tolerance(tolerance) => self.options.tolerance = tolerance,
+		}
+
+		self.send_layout(responses, LayoutTarget::ToolOptions);
 	}
 	fn actions(&self) -> ActionList {
 		match self.fsm_state {
 			FillToolFsmState::Ready => actions!(FillToolMessageDiscriminant;
 				FillPrimaryColor,
 				FillSecondaryColor,
+				UpdateOptions,
 			),
 			FillToolFsmState::Filling => actions!(FillToolMessageDiscriminant;
 				PointerUp,
 				Abort,
+				UpdateOptions,
 			),
 		}
 	}
@@ -71,11 +173,59 @@ enum FillToolFsmState {
 	Filling,
 }
 
+/// The region enclosed by the outlines of the visible vector layers which contains the cursor, in document space.
+fn enclosed_region(document: &mut DocumentMessageHandler, input: &InputPreprocessorMessageHandler) -> Option<Vec<Subpath<PointId>>> {
+	let network_interface = &document.network_interface;
+	let metadata = network_interface.document_metadata();
+	let point = metadata.document_to_viewport.inverse().transform_point2(input.mouse.position);
+
+	// Layers away from the point still matter, since lines can cross to enclose it and shapes inside the region cut holes into it
+	let mut layers: Vec<_> = metadata
+		.all_layers()
+		.filter(|&layer| !layer.has_children(metadata) && !network_interface.is_artboard(&layer.to_node(), &[]))
+		.filter(|&layer| network_interface.selected_nodes().layer_visible(layer, network_interface))
+		.collect();
+	layers.retain(|&layer| !NodeGraphLayer::is_raster_layer(layer, &mut document.network_interface));
+
+	let metadata = document.metadata();
+	let outlines = layers.iter().flat_map(move |&layer| {
+		let transform = metadata.transform_to_document(layer);
+		metadata.layer_outline(layer).map(move |subpath| (subpath, transform))
+	});
+
+	graphene_std::vector::enclosed_region(outlines, point)
+}
+
+/// Creates a layer filled with the color in the shape of the region, beneath the other layers so it doesn't cover the paths enclosing it.
+fn new_region_layer(mut region: Vec<Subpath<PointId>>, color: Color, document: &DocumentMessageHandler, input: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+	let parent = document.new_layer_bounding_artboard(input);
+	let document_to_parent = document.metadata().transform_to_document(parent).inverse();
+	for subpath in &mut region {
+		subpath.apply_transform(document_to_parent);
+	}
+
+	let id = NodeId::new();
+	let insert_index = parent.children(document.metadata()).count();
+	responses.add(GraphOperationMessage::NewVectorLayer {
+		id,
+		subpaths: region,
+		parent,
+		insert_index,
+	});
+
+	let layer = LayerNodeIdentifier::new_unchecked(id);
+	responses.add(GraphOperationMessage::FillSet {
+		layer,
+		fill: Fill::Solid(color.to_gamma_srgb()),
+	});
+	responses.add(GraphOperationMessage::StrokeSet { layer, stroke: Stroke::default() });
+}
+
 impl Fsm for FillToolFsmState {
 	type ToolData = ();
-	type ToolOptions = ();
+	type ToolOptions = FillOptions;
 
-	fn transition(self, event: ToolMessage, _tool_data: &mut Self::ToolData, handler_data: &mut ToolActionHandlerData, _tool_options: &Self::ToolOptions, responses: &mut VecDeque<Message>) -> Self {
+	fn transition(self, event: ToolMessage, _tool_data: &mut Self::ToolData, handler_data: &mut ToolActionHandlerData, tool_options: &Self::ToolOptions, responses: &mut VecDeque<Message>) -> Self {
 		let ToolActionHandlerData {
 			document, global_tool_data, input, ..
 		} = handler_data;
@@ -83,21 +233,39 @@ impl Fsm for FillToolFsmState {
 		let ToolMessage::Fill(event) = event else { return self };
 		match (self, event) {
 			(FillToolFsmState::Ready, color_event) => {
-				let Some(layer_identifier) = document.click(input) else {
-					return self;
-				};
-				// If the layer is a raster layer, don't fill it, wait till the flood fill tool is implemented
-				if NodeGraphLayer::is_raster_layer(layer_identifier, &mut document.network_interface) {
-					return self;
-				}
-				let fill = match color_event {
-					FillToolMessage::FillPrimaryColor => Fill::Solid(global_tool_data.primary_color.to_gamma_srgb()),
-					FillToolMessage::FillSecondaryColor => Fill::Solid(global_tool_data.secondary_color.to_gamma_srgb()),
+				let color = match color_event {
+					FillToolMessage::FillPrimaryColor => global_tool_data.primary_color,
+					FillToolMessage::FillSecondaryColor => global_tool_data.secondary_color,
 					_ => return self,
 				};
 
-				responses.add(DocumentMessage::AddTransaction);
-				responses.add(GraphOperationMessage::FillSet { layer: layer_identifier, fill });
+				let clicked_layer = document.click(input);
+				let raster_layer = clicked_layer.filter(|&layer| NodeGraphLayer::is_raster_layer(layer, &mut document.network_interface));
+				let vector_layer = clicked_layer.filter(|_| raster_layer.is_none() && !tool_options.fill_regions);
+
+				if let Some(layer_identifier) = raster_layer {
+					responses.add(DocumentMessage::AddTransaction);
+					// The "Flood Fill" node is inserted directly upstream of the layer, where only the transforms downstream of the layer apply
+					let seed_point = document.metadata().downstream_transform_to_viewport(layer_identifier).inverse().transform_point2(input.mouse.position);
+					responses.add(GraphOperationMessage::FloodFill {
+						layer: layer_identifier,
+						seed_point,
+						color,
+						tolerance: tool_options.tolerance,
+						contiguous: tool_options.contiguous,
+						anti_aliasing: tool_options.anti_aliasing,
+						sample_all_layers: tool_options.sample_all_layers,
+					});
+				} else if let Some(layer_identifier) = vector_layer {
+					let fill = Fill::Solid(color.to_gamma_srgb());
+					responses.add(DocumentMessage::AddTransaction);
+					responses.add(GraphOperationMessage::FillSet { layer: layer_identifier, fill });
+				} else {
+					// Like the "live paint" of illustration software, fill the region which the surrounding paths enclose with a new shape
+					let Some(region) = enclosed_region(document, input) else { return self };
+					responses.add(DocumentMessage::AddTransaction);
+					new_region_layer(region, color, document, input, responses);
+				}
 
 				FillToolFsmState::Filling
 			}
@@ -150,12 +318,18 @@ mod test_fill {
 	}
 
 	#[tokio::test]
-	async fn ignore_raster() {
+	async fn flood_fill_raster() {
 		let mut editor = EditorTestUtils::create();
 		editor.new_document().await;
 		editor.create_raster_image(Image::new(100, 100, Color::WHITE), Some((0., 0.))).await;
+		editor.select_primary_color(Color::GREEN).await;
 		editor.click_tool(ToolType::Fill, MouseKeys::LEFT, DVec2::new(2., 2.), ModifierKeys::empty()).await;
-		assert!(get_fills(&mut editor,).await.is_empty());
+
+		let instrumented = editor.eval_graph().await;
+		let colors: Vec<_> = instrumented.grab_all_input::<graphene_std::flood_fill::flood_fill::ColorInput>(&editor.runtime).collect();
+		assert_eq!(colors.len(), 1);
+		assert_eq!(colors[0].to_rgba8_srgb(), Color::GREEN.to_rgba8_srgb());
+		assert!(get_fills(&mut editor).await.is_empty());
 	}
 
 	#[tokio::test]
@@ -181,4 +355,74 @@ mod test_fill {
 		assert_eq!(fills.len(), 1);
 		assert_eq!(fills[0].as_solid().unwrap().to_rgba8_srgb(), Color::YELLOW.to_rgba8_srgb());
 	}
+
+	#[tokio::test]
+	async fn fill_region() {
+		let mut editor = EditorTestUtils::create();
+		editor.new_document().await;
+		editor.drag_tool(ToolType::Rectangle, 0., 0., 100., 100., ModifierKeys::empty()).await;
+		editor.drag_tool(ToolType::Rectangle, 50., 50., 150., 150., ModifierKeys::empty()).await;
+		editor.select_primary_color(Color::GREEN).await;
+		editor.select_tool(ToolType::Fill).await;
+		editor
+			.handle_message(super::FillToolMessage::UpdateOptions(super::FillToolMessageOptionsUpdate::FillRegions(true)))
+			.await;
+		editor.click_tool(ToolType::Fill, MouseKeys::LEFT, DVec2::new(75., 75.), ModifierKeys::empty()).await;
+
+		// The overlap of the rectangles gets a new shape with the fill, leaving the rectangles as they were
+		let fills = get_fills(&mut editor).await;
+		assert_eq!(fills.len(), 3);
+		let green_fills = fills.iter().filter(|fill| fill.as_solid().is_some_and(|color| color.to_rgba8_srgb() == Color::GREEN.to_rgba8_srgb()));
+		assert_eq!(green_fills.count(), 1);
+	}
+
+	#[tokio::test]
+	async fn fill_region_between_crossing_lines() {
+		let mut editor = EditorTestUtils::create();
+		editor.new_document().await;
+		editor.drag_tool(ToolType::Line, 0., 20., 100., 20., ModifierKeys::empty()).await;
+		editor.drag_tool(ToolType::Line, 0., 80., 100., 80., ModifierKeys::empty()).await;
+		editor.drag_tool(ToolType::Line, 20., 0., 20., 100., ModifierKeys::empty()).await;
+		editor.drag_tool(ToolType::Line, 80., 0., 80., 100., ModifierKeys::empty()).await;
+		editor.select_primary_color(Color::GREEN).await;
+		editor.select_tool(ToolType::Fill).await;
+		editor
+			.handle_message(super::FillToolMessage::UpdateOptions(super::FillToolMessageOptionsUpdate::FillRegions(true)))
+			.await;
+		editor.click_tool(ToolType::Fill, MouseKeys::LEFT, DVec2::new(50., 50.), ModifierKeys::empty()).await;
+
+		// None of the lines contain the point within their own bounds, but together they enclose it
+		let fills = get_fills(&mut editor).await;
+		let green_fills = fills.iter().filter(|fill| fill.as_solid().is_some_and(|color| color.to_rgba8_srgb() == Color::GREEN.to_rgba8_srgb()));
+		assert_eq!(green_fills.count(), 1);
+
+		let document = editor.active_document();
+		let region = document
+			.metadata()
+			.all_layers()
+			.find_map(|layer| document.metadata().bounding_box_document(layer).filter(|[min, max]| (*max - *min).min_element() > 1.));
+		let [min, max] = region.expect("The region between the lines should get a new layer");
+		assert!(min.abs_diff_eq(DVec2::splat(20.), 1e-3) && max.abs_diff_eq(DVec2::splat(80.), 1e-3), "{min:?} {max:?}");
+	}
+
+	#[tokio::test]
+	async fn fill_ring() {
+		let mut editor = EditorTestUtils::create();
+		editor.new_document().await;
+		editor.drag_tool(ToolType::Ellipse, 0., 0., 100., 100., ModifierKeys::empty()).await;
+		editor.drag_tool(ToolType::Ellipse, 30., 30., 70., 70., ModifierKeys::empty()).await;
+		editor.select_primary_color(Color::GREEN).await;
+		editor.select_tool(ToolType::Fill).await;
+		editor
+			.handle_message(super::FillToolMessage::UpdateOptions(super::FillToolMessageOptionsUpdate::FillRegions(true)))
+			.await;
+		editor.click_tool(ToolType::Fill, MouseKeys::LEFT, DVec2::new(90., 50.), ModifierKeys::empty()).await;
+
+		let fills = get_fills(&mut editor).await;
+		assert_eq!(fills.len(), 3);
+
+		// The inner circle is beside the point, but still cuts a hole into the filled ring
+		let metadata = editor.active_document().metadata();
+		assert!(metadata.all_layers().any(|layer| metadata.layer_outline(layer).count() == 2));
+	}
 }
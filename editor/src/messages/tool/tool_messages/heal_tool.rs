@@ -0,0 +1,187 @@
+use super::clone_tool::{CloneOptions, CloneToolData, CloneToolMessageOptionsUpdate};
+use super::tool_prelude::*;
+use crate::messages::portfolio::document::overlays::utility_types::OverlayContext;
+use graphene_core::vector::brush_stroke::BrushSource;
+
+#[derive(Default)]
+pub struct HealTool {
+	fsm_state: HealToolFsmState,
+	data: CloneToolData,
+	options: CloneOptions,
+}
+
+#[impl_message(Message, ToolMessage, Heal)]
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum HealToolMessage {
+	// Standard messages
+	Abort,
+	Overlays(OverlayContext),
+
+	// Tool-specific messages
+	DragStart,
+	DragStop,
+	PointerMove,
+	SetSource,
+	UpdateOptions(CloneToolMessageOptionsUpdate),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum HealToolFsmState {
+	#[default]
+	Ready,
+	Drawing,
+}
+
+impl ToolMetadata for HealTool {
+	fn icon_name(&self) -> String {
+		"RasterHealTool".into()
+	}
+	fn tooltip(&self) -> String {
+		"Heal Tool".into()
+	}
+	fn tool_type(&self) -> crate::messages::tool::utility_types::ToolType {
+		ToolType::Heal
+	}
+}
+
+impl LayoutHolder for HealTool {
+	fn layout(&self) -> Layout {
+		let widgets = self.options.widgets(|action| HealToolMessage::UpdateOptions(action).into());
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row { widgets }]))
+	}
+}
+
+impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for HealTool {
+	fn process_message(&mut self, message: ToolMessage, responses: &mut VecDeque<Message>, tool_data: &mut ToolActionHandlerData<'a>) {
+		let ToolMessage::Heal(HealToolMessage::UpdateOptions(action)) = message else {
+			self.fsm_state.process_event(message, &mut self.data, tool_data, &self.options, responses, true);
+			return;
+		};
+		self.options.update(action, &mut self.data, responses);
+
+		self.send_layout(responses, LayoutTarget::ToolOptions);
+	}
+
+	fn actions(&self) -> ActionList {
+		match self.fsm_state {
+			HealToolFsmState::Ready => actions!(HealToolMessageDiscriminant;
+				DragStart,
+				DragStop,
+				PointerMove,
+				SetSource,
+				UpdateOptions,
+			),
+			HealToolFsmState::Drawing => actions!(HealToolMessageDiscriminant;
+				DragStop,
+				PointerMove,
+				Abort,
+				UpdateOptions,
+			),
+		}
+	}
+}
+
+impl ToolTransition for HealTool {
+	fn event_to_message_map(&self) -> EventToMessageMap {
+		EventToMessageMap {
+			tool_abort: Some(HealToolMessage::Abort.into()),
+			overlay_provider: Some(|overlay_context: OverlayContext| HealToolMessage::Overlays(overlay_context).into()),
+			..Default::default()
+		}
+	}
+}
+
+impl Fsm for HealToolFsmState {
+	type ToolData = CloneToolData;
+	type ToolOptions = CloneOptions;
+
+	fn transition(self, event: ToolMessage, tool_data: &mut Self::ToolData, tool_action_data: &mut ToolActionHandlerData, tool_options: &Self::ToolOptions, responses: &mut VecDeque<Message>) -> Self {
+		let ToolActionHandlerData { document, input, .. } = tool_action_data;
+
+		let ToolMessage::Heal(event) = event else { return self };
+		match (self, event) {
+			(_, HealToolMessage::Overlays(mut overlay_context)) => {
+				tool_data.draw_source(&mut overlay_context, document, input, self == HealToolFsmState::Drawing, tool_options);
+
+				self
+			}
+			(HealToolFsmState::Ready, HealToolMessage::SetSource) => {
+				tool_data.set_source(document, input, responses);
+
+				HealToolFsmState::Ready
+			}
+			(HealToolFsmState::Ready, HealToolMessage::DragStart) => {
+				if !tool_data.start_stroke(document, input, tool_options, |offset| BrushSource::Heal { offset }, responses) {
+					return self;
+				}
+
+				HealToolFsmState::Drawing
+			}
+			(HealToolFsmState::Drawing, HealToolMessage::PointerMove) => {
+				tool_data.extend_stroke(document, input, responses);
+
+				HealToolFsmState::Drawing
+			}
+			(HealToolFsmState::Ready, HealToolMessage::PointerMove) => {
+				responses.add(OverlaysMessage::Draw);
+
+				HealToolFsmState::Ready
+			}
+			(HealToolFsmState::Drawing, HealToolMessage::DragStop) => {
+				responses.add(DocumentMessage::EndTransaction);
+				tool_data.end_stroke();
+
+				HealToolFsmState::Ready
+			}
+			(HealToolFsmState::Drawing, HealToolMessage::Abort) => {
+				responses.add(DocumentMessage::AbortTransaction);
+				tool_data.end_stroke();
+
+				HealToolFsmState::Ready
+			}
+			_ => self,
+		}
+	}
+
+	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+		let hint_data = match self {
+			HealToolFsmState::Ready => HintData(vec![
+				HintGroup(vec![HintInfo::keys_and_mouse([Key::Alt], MouseMotion::Lmb, "Set Source")]),
+				HintGroup(vec![HintInfo::mouse(MouseMotion::LmbDrag, "Heal")]),
+				HintGroup(vec![HintInfo::multi_keys([[Key::BracketLeft], [Key::BracketRight]], "Shrink/Grow Brush")]),
+			]),
+			HealToolFsmState::Drawing => HintData(vec![HintGroup(vec![HintInfo::mouse(MouseMotion::Rmb, ""), HintInfo::keys([Key::Escape], "Cancel").prepend_slash()])]),
+		};
+
+		responses.add(FrontendMessage::UpdateInputHints { hint_data });
+	}
+
+	fn update_cursor(&self, responses: &mut VecDeque<Message>) {
+		responses.add(FrontendMessage::UpdateMouseCursor { cursor: MouseCursorIcon::Default });
+	}
+}
+
+#[cfg(test)]
+mod test_heal {
+	pub use crate::test_utils::test_prelude::*;
+	use graphene_core::vector::brush_stroke::BrushSource;
+
+	#[tokio::test]
+	async fn heal_from_source() {
+		let mut editor = EditorTestUtils::create();
+		editor.new_document().await;
+		editor.create_raster_image(Image::new(100, 100, Color::WHITE), Some((50., 50.))).await;
+		editor.click_tool(ToolType::Heal, MouseKeys::LEFT, DVec2::new(20., 70.), ModifierKeys::ALT).await;
+		editor.drag_tool(ToolType::Heal, 50., 50., 50., 60., ModifierKeys::empty()).await;
+
+		let instrumented = editor.eval_graph().await;
+		let strokes: Vec<_> = instrumented.grab_all_input::<graphene_std::brush::brush::StrokesInput>(&editor.runtime).collect();
+		assert_eq!(strokes.len(), 1);
+		assert_eq!(strokes[0].len(), 1);
+		let BrushSource::Heal { offset } = strokes[0][0].source else {
+			panic!("The stroke should heal, but it paints with {:?}", strokes[0][0].source);
+		};
+		assert!(offset.abs_diff_eq(DVec2::new(-30., 20.), 1e-6), "{offset:?}");
+	}
+}
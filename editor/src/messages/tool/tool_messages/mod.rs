@@ -1,17 +1,22 @@
 pub mod artboard_tool;
 pub mod brush_tool;
+pub mod clone_tool;
+pub mod detail_tool;
 pub mod ellipse_tool;
 pub mod eyedropper_tool;
 pub mod fill_tool;
 pub mod freehand_tool;
 pub mod gradient_tool;
+pub mod heal_tool;
 // pub mod imaginate_tool;
 pub mod line_tool;
 pub mod navigate_tool;
+pub mod patch_tool;
 pub mod path_tool;
 pub mod pen_tool;
 pub mod polygon_tool;
 pub mod rectangle_tool;
+pub mod relight_tool;
 pub mod select_tool;
 pub mod spline_tool;
 pub mod text_tool;
@@ -0,0 +1,285 @@
+use super::select_tool::extend_lasso;
+use super::tool_prelude::*;
+use crate::consts::COLOR_OVERLAY_BLUE;
+use crate::messages::portfolio::document::overlays::utility_types::OverlayContext;
+use crate::messages::portfolio::document::utility_types::document_metadata::LayerNodeIdentifier;
+use crate::messages::tool::common_functionality::graph_modification_utils::NodeGraphLayer;
+use graphene_std::healing::polygon_contains;
+
+#[derive(Default)]
+pub struct PatchTool {
+	fsm_state: PatchToolFsmState,
+	data: PatchToolData,
+}
+
+#[impl_message(Message, ToolMessage, Patch)]
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum PatchToolMessage {
+	// Standard messages
+	Abort,
+	Overlays(OverlayContext),
+
+	// Tool-specific messages
+	DragStart,
+	DragStop,
+	PointerMove,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum PatchToolFsmState {
+	#[default]
+	Ready,
+	Selecting,
+	Dragging,
+}
+
+impl ToolMetadata for PatchTool {
+	fn icon_name(&self) -> String {
+		"RasterPatchTool".into()
+	}
+	fn tooltip(&self) -> String {
+		"Patch Tool".into()
+	}
+	fn tool_type(&self) -> crate::messages::tool::utility_types::ToolType {
+		ToolType::Patch
+	}
+}
+
+impl LayoutHolder for PatchTool {
+	fn layout(&self) -> Layout {
+		Layout::WidgetLayout(WidgetLayout::default())
+	}
+}
+
+impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for PatchTool {
+	fn process_message(&mut self, message: ToolMessage, responses: &mut VecDeque<Message>, tool_data: &mut ToolActionHandlerData<'a>) {
+		self.fsm_state.process_event(message, &mut self.data, tool_data, &(), responses, true);
+	}
+
+	fn actions(&self) -> ActionList {
+		match self.fsm_state {
+			PatchToolFsmState::Ready => actions!(PatchToolMessageDiscriminant;
+				DragStart,
+				Abort,
+			),
+			PatchToolFsmState::Selecting | PatchToolFsmState::Dragging => actions!(PatchToolMessageDiscriminant;
+				DragStop,
+				PointerMove,
+				Abort,
+			),
+		}
+	}
+}
+
+impl ToolTransition for PatchTool {
+	fn event_to_message_map(&self) -> EventToMessageMap {
+		EventToMessageMap {
+			tool_abort: Some(PatchToolMessage::Abort.into()),
+			overlay_provider: Some(|overlay_context: OverlayContext| PatchToolMessage::Overlays(overlay_context).into()),
+			..Default::default()
+		}
+	}
+}
+
+#[derive(Clone, Debug, Default)]
+struct PatchToolData {
+	/// The lasso selection of the area to patch, in document space.
+	region: Vec<DVec2>,
+	/// Where the selection started being dragged towards its source, in document space.
+	drag_start: DVec2,
+}
+
+impl PatchToolData {
+	fn cursor(document: &DocumentMessageHandler, input: &InputPreprocessorMessageHandler) -> DVec2 {
+		document.metadata().document_to_viewport.inverse().transform_point2(input.mouse.position)
+	}
+
+	fn selected_raster_layer(document: &mut DocumentMessageHandler) -> Option<LayerNodeIdentifier> {
+		let mut selected_layers = document.network_interface.selected_nodes().selected_layers(document.metadata());
+		let layer = selected_layers.next()?;
+		if selected_layers.next().is_some() {
+			return None;
+		}
+		NodeGraphLayer::is_raster_layer(layer, &mut document.network_interface).then_some(layer)
+	}
+}
+
+impl Fsm for PatchToolFsmState {
+	type ToolData = PatchToolData;
+	type ToolOptions = ();
+
+	fn transition(
+		self,
+		event: ToolMessage,
+		tool_data: &mut Self::ToolData,
+		tool_action_data: &mut ToolActionHandlerData,
+		_tool_options: &Self::ToolOptions,
+		responses: &mut VecDeque<Message>,
+	) -> Self {
+		let ToolActionHandlerData { document, input, .. } = tool_action_data;
+
+		let ToolMessage::Patch(event) = event else { return self };
+		match (self, event) {
+			(_, PatchToolMessage::Overlays(mut overlay_context)) => {
+				let document_to_viewport = document.metadata().document_to_viewport;
+				let region: Vec<_> = tool_data.region.iter().map(|&point| document_to_viewport.transform_point2(point)).collect();
+				if region.len() < 2 {
+					return self;
+				}
+
+				let mut fill_color = graphene_std::Color::from_rgb_str(COLOR_OVERLAY_BLUE.strip_prefix('#').unwrap())
+					.unwrap()
+					.with_alpha(0.05)
+					.to_rgba_hex_srgb();
+				fill_color.insert(0, '#');
+				overlay_context.dashed_polygon(&region, Some(fill_color.as_str()), Some(4.), Some(4.), Some(0.5));
+
+				// Preview the source area the selection is being dragged onto
+				if self == PatchToolFsmState::Dragging {
+					let delta = document_to_viewport.transform_vector2(PatchToolData::cursor(document, input) - tool_data.drag_start);
+					let source: Vec<_> = region.iter().map(|&point| point + delta).collect();
+					overlay_context.polygon(&source, None);
+				}
+
+				self
+			}
+			(PatchToolFsmState::Ready, PatchToolMessage::DragStart) => {
+				let cursor = PatchToolData::cursor(document, input);
+				responses.add(OverlaysMessage::Draw);
+
+				if tool_data.region.len() > 2 && polygon_contains(&tool_data.region, cursor) {
+					tool_data.drag_start = cursor;
+					return PatchToolFsmState::Dragging;
+				}
+
+				tool_data.region = vec![cursor];
+				PatchToolFsmState::Selecting
+			}
+			(PatchToolFsmState::Selecting, PatchToolMessage::PointerMove) => {
+				extend_lasso(&mut tool_data.region, PatchToolData::cursor(document, input));
+				responses.add(OverlaysMessage::Draw);
+
+				PatchToolFsmState::Selecting
+			}
+			(PatchToolFsmState::Dragging, PatchToolMessage::PointerMove) => {
+				responses.add(OverlaysMessage::Draw);
+
+				PatchToolFsmState::Dragging
+			}
+			(PatchToolFsmState::Selecting, PatchToolMessage::DragStop) => {
+				if tool_data.region.len() < 3 {
+					tool_data.region.clear();
+				}
+				responses.add(OverlaysMessage::Draw);
+
+				PatchToolFsmState::Ready
+			}
+			(PatchToolFsmState::Dragging, PatchToolMessage::DragStop) => {
+				let offset = PatchToolData::cursor(document, input) - tool_data.drag_start;
+				responses.add(OverlaysMessage::Draw);
+				let Some(layer) = PatchToolData::selected_raster_layer(document) else {
+					return PatchToolFsmState::Ready;
+				};
+				if offset.length_squared() == 0. {
+					return PatchToolFsmState::Ready;
+				}
+
+				// The "Patch" node is inserted directly upstream of the layer, where only the transforms downstream of the layer apply
+				let document_to_layer = document.metadata().downstream_transform_to_document(layer).inverse();
+				let region = tool_data.region.iter().map(|&point| document_to_layer.transform_point2(point)).collect();
+				let offset = document_to_layer.transform_vector2(offset);
+
+				responses.add(DocumentMessage::AddTransaction);
+				responses.add(GraphOperationMessage::Patch { layer, region, offset });
+
+				PatchToolFsmState::Ready
+			}
+			(_, PatchToolMessage::Abort) => {
+				tool_data.region.clear();
+				responses.add(OverlaysMessage::Draw);
+
+				PatchToolFsmState::Ready
+			}
+			_ => self,
+		}
+	}
+
+	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+		let hint_data = match self {
+			PatchToolFsmState::Ready => HintData(vec![
+				HintGroup(vec![HintInfo::mouse(MouseMotion::LmbDrag, "Select Area to Patch")]),
+				HintGroup(vec![HintInfo::mouse(MouseMotion::LmbDrag, "Drag Selection to Source")]),
+			]),
+			PatchToolFsmState::Selecting | PatchToolFsmState::Dragging => {
+				HintData(vec![HintGroup(vec![HintInfo::mouse(MouseMotion::Rmb, ""), HintInfo::keys([Key::Escape], "Cancel").prepend_slash()])])
+			}
+		};
+
+		responses.add(FrontendMessage::UpdateInputHints { hint_data });
+	}
+
+	fn update_cursor(&self, responses: &mut VecDeque<Message>) {
+		let cursor = match self {
+			PatchToolFsmState::Dragging => MouseCursorIcon::Move,
+			_ => MouseCursorIcon::Default,
+		};
+		responses.add(FrontendMessage::UpdateMouseCursor { cursor });
+	}
+}
+
+#[cfg(test)]
+mod test_patch {
+	use crate::messages::input_mapper::utility_types::input_mouse::EditorMouseState;
+	pub use crate::test_utils::test_prelude::*;
+
+	async fn lasso(editor: &mut EditorTestUtils, points: &[DVec2]) {
+		let (first, rest) = points.split_first().unwrap();
+		editor.move_mouse(first.x, first.y, ModifierKeys::empty(), MouseKeys::empty()).await;
+		editor.left_mousedown(first.x, first.y, ModifierKeys::empty()).await;
+		for point in rest {
+			editor.move_mouse(point.x, point.y, ModifierKeys::empty(), MouseKeys::LEFT).await;
+		}
+		let last = points.last().unwrap();
+		editor
+			.mouseup(
+				EditorMouseState {
+					editor_position: *last,
+					..Default::default()
+				},
+				ModifierKeys::empty(),
+			)
+			.await;
+	}
+
+	async fn get_patches(editor: &mut EditorTestUtils) -> Vec<(Vec<DVec2>, DVec2)> {
+		let instrumented = editor.eval_graph().await;
+
+		let regions = instrumented.grab_all_input::<graphene_std::healing::patch::RegionInput>(&editor.runtime);
+		let offsets = instrumented.grab_all_input::<graphene_std::healing::patch::OffsetInput>(&editor.runtime);
+		regions.zip(offsets).collect()
+	}
+
+	#[tokio::test]
+	async fn patch_from_dragged_selection() {
+		let mut editor = EditorTestUtils::create();
+		editor.new_document().await;
+		editor.create_raster_image(Image::new(100, 100, Color::WHITE), Some((50., 50.))).await;
+		editor.select_tool(ToolType::Patch).await;
+		let square = [DVec2::new(20., 20.), DVec2::new(40., 20.), DVec2::new(40., 40.), DVec2::new(20., 40.)];
+		lasso(&mut editor, &square).await;
+
+		// Only selecting the area doesn't change the image
+		assert!(get_patches(&mut editor).await.is_empty());
+
+		lasso(&mut editor, &[DVec2::new(30., 30.), DVec2::new(70., 30.)]).await;
+
+		let patches = get_patches(&mut editor).await;
+		assert_eq!(patches.len(), 1);
+		let (region, offset) = &patches[0];
+		assert_eq!(region.len(), square.len());
+		for (point, expected) in region.iter().zip(square) {
+			assert!(point.abs_diff_eq(expected, 1e-6), "{region:?}");
+		}
+		assert!(offset.abs_diff_eq(DVec2::new(40., 0.), 1e-6), "{offset:?}");
+	}
+}
@@ -0,0 +1,246 @@
+use super::brush_tool::{BrushShapeOptions, BrushShapeOptionsUpdate};
+use super::detail_tool::BrushMaskToolData;
+use super::tool_prelude::*;
+use graph_craft::document::value::TaggedValue;
+
+#[derive(PartialEq, Copy, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum RelightMode {
+	Dodge = 0,
+	Burn,
+}
+
+#[derive(Default)]
+pub struct RelightTool {
+	fsm_state: RelightToolFsmState,
+	data: BrushMaskToolData,
+	options: RelightOptions,
+}
+
+pub struct RelightOptions {
+	brush: BrushShapeOptions,
+	mode: RelightMode,
+	exposure: f64,
+}
+
+impl Default for RelightOptions {
+	fn default() -> Self {
+		Self {
+			brush: BrushShapeOptions::default(),
+			mode: RelightMode::Dodge,
+			exposure: 1.,
+		}
+	}
+}
+
+impl RelightOptions {
+	/// The exposure change in stops of the "Relight" node, which darkens when negative.
+	fn signed_exposure(&self) -> f64 {
+		match self.mode {
+			RelightMode::Dodge => self.exposure,
+			RelightMode::Burn => -self.exposure,
+		}
+	}
+}
+
+#[impl_message(Message, ToolMessage, Relight)]
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum RelightToolMessage {
+	// Standard messages
+	Abort,
+
+	// Tool-specific messages
+	DragStart,
+	DragStop,
+	PointerMove,
+	UpdateOptions(RelightToolMessageOptionsUpdate),
+}
+
+#[derive(PartialEq, Clone, Debug, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum RelightToolMessageOptionsUpdate {
+	Brush(BrushShapeOptionsUpdate),
+	Exposure(f64),
+	Mode(RelightMode),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum RelightToolFsmState {
+	#[default]
+	Ready,
+	Drawing,
+}
+
+impl ToolMetadata for RelightTool {
+	fn icon_name(&self) -> String {
+		"RasterRelightTool".into()
+	}
+	fn tooltip(&self) -> String {
+		"Relight Tool".into()
+	}
+	fn tool_type(&self) -> crate::messages::tool::utility_types::ToolType {
+		ToolType::Relight
+	}
+}
+
+impl LayoutHolder for RelightTool {
+	fn layout(&self) -> Layout {
+		let mut widgets = self
+			.options
+			.brush
+			.widgets(|action| RelightToolMessage::UpdateOptions(RelightToolMessageOptionsUpdate::Brush(action)).into());
+
+		widgets.push(Separator::new(SeparatorType::Unrelated).widget_holder());
+
+		let mode_entries: Vec<_> = [RelightMode::Dodge, RelightMode::Burn]
+			.into_iter()
+			.map(|mode| RadioEntryData::new(format!("{mode:?}")).on_update(move |_| RelightToolMessage::UpdateOptions(RelightToolMessageOptionsUpdate::Mode(mode)).into()))
+			.collect();
+		widgets.push(RadioInput::new(mode_entries).selected_index(Some(self.options.mode as u32)).widget_holder());
+
+		widgets.push(Separator::new(SeparatorType::Related).widget_holder());
+
+		widgets.push(
+			NumberInput::new(Some(self.options.exposure))
+				.label("Exposure")
+				.min(0.01)
+				.max(4.)
+				.mode_range()
+				.unit(" stops")
+				.tooltip("How much the strokes brighten (dodge) or darken (burn) at full flow")
+				.on_update(|number_input: &NumberInput| RelightToolMessage::UpdateOptions(RelightToolMessageOptionsUpdate::Exposure(number_input.value.unwrap())).into())
+				.widget_holder(),
+		);
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row { widgets }]))
+	}
+}
+
+impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for RelightTool {
+	fn process_message(&mut self, message: ToolMessage, responses: &mut VecDeque<Message>, tool_data: &mut ToolActionHandlerData<'a>) {
+		let ToolMessage::Relight(RelightToolMessage::UpdateOptions(action)) = message else {
+			self.fsm_state.process_event(message, &mut self.data, tool_data, &self.options, responses, true);
+			return;
+		};
+		match action {
+			RelightToolMessageOptionsUpdate::Brush(action) => self.options.brush.update(action),
+			RelightToolMessageOptionsUpdate::Exposure(exposure) => self.options.exposure = exposure,
+			RelightToolMessageOptionsUpdate::Mode(mode) => self.options.mode = mode,
+		}
+
+		self.send_layout(responses, LayoutTarget::ToolOptions);
+	}
+
+	fn actions(&self) -> ActionList {
+		match self.fsm_state {
+			RelightToolFsmState::Ready => actions!(RelightToolMessageDiscriminant;
+				DragStart,
+				DragStop,
+				UpdateOptions,
+			),
+			RelightToolFsmState::Drawing => actions!(RelightToolMessageDiscriminant;
+				DragStop,
+				PointerMove,
+				Abort,
+				UpdateOptions,
+			),
+		}
+	}
+}
+
+impl ToolTransition for RelightTool {
+	fn event_to_message_map(&self) -> EventToMessageMap {
+		EventToMessageMap {
+			tool_abort: Some(RelightToolMessage::Abort.into()),
+			..Default::default()
+		}
+	}
+}
+
+impl Fsm for RelightToolFsmState {
+	type ToolData = BrushMaskToolData;
+	type ToolOptions = RelightOptions;
+
+	fn transition(self, event: ToolMessage, tool_data: &mut Self::ToolData, tool_action_data: &mut ToolActionHandlerData, tool_options: &Self::ToolOptions, responses: &mut VecDeque<Message>) -> Self {
+		let ToolActionHandlerData { document, input, .. } = tool_action_data;
+		let exposure = tool_options.signed_exposure();
+		let update = |layer, strokes| GraphOperationMessage::Relight { layer, strokes, exposure };
+
+		let ToolMessage::Relight(event) = event else { return self };
+		match (self, event) {
+			(RelightToolFsmState::Ready, RelightToolMessage::DragStart) => {
+				if !tool_data.start_stroke(document, input, &tool_options.brush, "Relight", &[(2, TaggedValue::F64(exposure))], responses) {
+					return self;
+				}
+				tool_data.update_strokes(responses, update);
+
+				RelightToolFsmState::Drawing
+			}
+			(RelightToolFsmState::Drawing, RelightToolMessage::PointerMove) => {
+				tool_data.extend_stroke(document, input);
+				tool_data.update_strokes(responses, update);
+
+				RelightToolFsmState::Drawing
+			}
+			(RelightToolFsmState::Drawing, RelightToolMessage::DragStop) => {
+				responses.add(DocumentMessage::EndTransaction);
+				tool_data.end_stroke();
+
+				RelightToolFsmState::Ready
+			}
+			(RelightToolFsmState::Drawing, RelightToolMessage::Abort) => {
+				responses.add(DocumentMessage::AbortTransaction);
+				tool_data.end_stroke();
+
+				RelightToolFsmState::Ready
+			}
+			_ => self,
+		}
+	}
+
+	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+		let hint_data = match self {
+			RelightToolFsmState::Ready => HintData(vec![
+				HintGroup(vec![HintInfo::mouse(MouseMotion::LmbDrag, "Dodge or Burn")]),
+				HintGroup(vec![HintInfo::multi_keys([[Key::BracketLeft], [Key::BracketRight]], "Shrink/Grow Brush")]),
+			]),
+			RelightToolFsmState::Drawing => HintData(vec![HintGroup(vec![HintInfo::mouse(MouseMotion::Rmb, ""), HintInfo::keys([Key::Escape], "Cancel").prepend_slash()])]),
+		};
+
+		responses.add(FrontendMessage::UpdateInputHints { hint_data });
+	}
+
+	fn update_cursor(&self, responses: &mut VecDeque<Message>) {
+		responses.add(FrontendMessage::UpdateMouseCursor { cursor: MouseCursorIcon::Default });
+	}
+}
+
+#[cfg(test)]
+mod test_relight {
+	use super::{RelightMode, RelightToolMessage, RelightToolMessageOptionsUpdate};
+	pub use crate::test_utils::test_prelude::*;
+	use graphene_core::raster::adjustments::exposure;
+	use graphene_std::retouch::masked_blend;
+
+	#[tokio::test]
+	async fn dodge_and_burn() {
+		let mut editor = EditorTestUtils::create();
+		editor.new_document().await;
+		editor.create_raster_image(Image::new(100, 100, Color::WHITE), Some((50., 50.))).await;
+		editor.drag_tool(ToolType::Relight, 20., 20., 40., 20., ModifierKeys::empty()).await;
+
+		let instrumented = editor.eval_graph().await;
+		let strokes: Vec<_> = instrumented.grab_all_input::<masked_blend::StrokesInput>(&editor.runtime).collect();
+		assert_eq!(strokes.len(), 1);
+		assert_eq!(strokes[0].len(), 1);
+		let exposures: Vec<_> = instrumented.grab_all_input::<exposure::ExposureInput>(&editor.runtime).collect();
+		assert_eq!(exposures, vec![1.]);
+
+		editor.handle_message(RelightToolMessage::UpdateOptions(RelightToolMessageOptionsUpdate::Mode(RelightMode::Burn))).await;
+		editor.drag_tool(ToolType::Relight, 20., 60., 40., 60., ModifierKeys::empty()).await;
+
+		// Burning darkens with a negative exposure, in a node of its own
+		let instrumented = editor.eval_graph().await;
+		let mut exposures: Vec<_> = instrumented.grab_all_input::<exposure::ExposureInput>(&editor.runtime).collect();
+		exposures.sort_by(f64::total_cmp);
+		assert_eq!(exposures, vec![-1., 1.]);
+	}
+}
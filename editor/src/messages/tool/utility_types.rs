@@ -376,11 +376,11 @@ fn list_tools_in_groups() -> Vec<Vec<ToolAvailability>> {
 		vec![
 			// Raster tool group
 			ToolAvailability::Available(Box::<brush_tool::BrushTool>::default()),
-			ToolAvailability::ComingSoon(ToolEntry::new(ToolType::Heal, "RasterHealTool").tooltip("Coming Soon: Heal Tool (J)")),
-			ToolAvailability::ComingSoon(ToolEntry::new(ToolType::Clone, "RasterCloneTool").tooltip("Coming Soon: Clone Tool (C)")),
-			ToolAvailability::ComingSoon(ToolEntry::new(ToolType::Patch, "RasterPatchTool").tooltip("Coming Soon: Patch Tool")),
-			ToolAvailability::ComingSoon(ToolEntry::new(ToolType::Detail, "RasterDetailTool").tooltip("Coming Soon: Detail Tool (D)")),
-			ToolAvailability::ComingSoon(ToolEntry::new(ToolType::Relight, "RasterRelightTool").tooltip("Coming Soon: Relight Tool (O)")),
+			ToolAvailability::Available(Box::<heal_tool::HealTool>::default()),
+			ToolAvailability::Available(Box::<clone_tool::CloneTool>::default()),
+			ToolAvailability::Available(Box::<patch_tool::PatchTool>::default()),
+			ToolAvailability::Available(Box::<detail_tool::DetailTool>::default()),
+			ToolAvailability::Available(Box::<relight_tool::RelightTool>::default()),
 			// TODO: Fix and reenable Imaginate tool
 			// ToolAvailability::Available(Box::<imaginate_tool::ImaginateTool>::default()),
 			ToolAvailability::ComingSoon(ToolEntry::new(ToolType::Heal, "RasterImaginateTool").tooltip("Coming Soon: Imaginate Tool")),
@@ -411,11 +411,11 @@ pub fn tool_message_to_tool_type(tool_message: &ToolMessage) -> ToolType {
 
 		// Raster tool group
 		ToolMessage::Brush(_) => ToolType::Brush,
-		// ToolMessage::Heal(_) => ToolType::Heal,
-		// ToolMessage::Clone(_) => ToolType::Clone,
-		// ToolMessage::Patch(_) => ToolType::Patch,
-		// ToolMessage::Detail(_) => ToolType::Detail,
-		// ToolMessage::Relight(_) => ToolType::Relight,
+		ToolMessage::Heal(_) => ToolType::Heal,
+		ToolMessage::Clone(_) => ToolType::Clone,
+		ToolMessage::Patch(_) => ToolType::Patch,
+		ToolMessage::Detail(_) => ToolType::Detail,
+		ToolMessage::Relight(_) => ToolType::Relight,
 		// ToolMessage::Imaginate(_) => ToolType::Imaginate,
 		_ => panic!("Conversion from ToolMessage to ToolType impossible because the given ToolMessage does not have a matching ToolType. Got: {tool_message:?}"),
 	}
@@ -444,11 +444,11 @@ pub fn tool_type_to_activate_tool_message(tool_type: ToolType) -> ToolMessageDis
 
 		// Raster tool group
 		ToolType::Brush => ToolMessageDiscriminant::ActivateToolBrush,
-		// ToolType::Heal => ToolMessageDiscriminant::ActivateToolHeal,
-		// ToolType::Clone => ToolMessageDiscriminant::ActivateToolClone,
-		// ToolType::Patch => ToolMessageDiscriminant::ActivateToolPatch,
-		// ToolType::Detail => ToolMessageDiscriminant::ActivateToolDetail,
-		// ToolType::Relight => ToolMessageDiscriminant::ActivateToolRelight,
+		ToolType::Heal => ToolMessageDiscriminant::ActivateToolHeal,
+		ToolType::Clone => ToolMessageDiscriminant::ActivateToolClone,
+		ToolType::Patch => ToolMessageDiscriminant::ActivateToolPatch,
+		ToolType::Detail => ToolMessageDiscriminant::ActivateToolDetail,
+		ToolType::Relight => ToolMessageDiscriminant::ActivateToolRelight,
 		// ToolType::Imaginate => ToolMessageDiscriminant::ActivateToolImaginate,
 		_ => panic!("Conversion from ToolType to ToolMessage impossible because the given ToolType does not have a matching ToolMessage. Got: {tool_type:?}"),
 	}
@@ -1,14 +1,14 @@
 use crate::consts::FILE_SAVE_SUFFIX;
-use crate::messages::frontend::utility_types::{ExportBounds, FileType};
+use crate::messages::frontend::utility_types::{AnimationExportSettings, AnimationFormat, BatchExportFile, ExportBounds, FileType, FrontendExportFile, FrontendExportFileContent, RasterExportOptions};
 use crate::messages::prelude::*;
 use glam::{DAffine2, DVec2, UVec2};
-use graph_craft::document::value::{RenderOutput, TaggedValue};
+use graph_craft::document::value::{RenderOutput, RenderOutputType, TaggedValue};
 use graph_craft::document::{DocumentNode, DocumentNodeImplementation, NodeId, NodeInput, generate_uuid};
 use graph_craft::proto::GraphErrors;
 use graph_craft::wasm_application_io::EditorPreferences;
 use graphene_core::application_io::{NodeGraphUpdateMessage, RenderConfig};
 use graphene_core::renderer::RenderSvgSegmentList;
-use graphene_core::renderer::{GraphicElementRendered, RenderParams, SvgRender};
+use graphene_core::renderer::{GraphicElementRendered, RenderParams, SvgAnimationStyle, SvgExportOptions, SvgRender};
 use graphene_core::text::FontCache;
 use graphene_core::transform::Footprint;
 use graphene_core::vector::style::ViewMode;
@@ -16,6 +16,9 @@ use graphene_std::application_io::TimingInformation;
 use graphene_std::renderer::{RenderMetadata, format_transform_matrix};
 use graphene_std::vector::VectorData;
 use interpreted_executor::dynamic_executor::ResolvedDocumentNodeTypesDelta;
+use std::time::Duration;
+
+mod raster_export;
 
 mod runtime_io;
 pub use runtime_io::NodeRuntimeIO;
@@ -59,6 +62,8 @@ pub struct NodeGraphExecutor {
 	futures: HashMap<u64, ExecutionContext>,
 	node_graph_hash: u64,
 	old_inspect_node: Option<NodeId>,
+	batch_export: Option<BatchExport>,
+	animation_export: Option<AnimationExport>,
 }
 
 #[derive(Debug, Clone)]
@@ -66,6 +71,24 @@ struct ExecutionContext {
 	export_config: Option<ExportConfig>,
 }
 
+/// The files of an in-progress batch export, which are sent to the frontend together once all of them have been rendered.
+#[derive(Debug)]
+struct BatchExport {
+	id: u64,
+	archive_name: String,
+	files: Vec<Option<FrontendExportFile>>,
+}
+
+/// An in-progress animated export, whose frames are rendered one after another so it can report its progress and be cancelled.
+#[derive(Debug)]
+struct AnimationExport {
+	id: u64,
+	settings: AnimationExportSettings,
+	render_config: RenderConfig,
+	export_config: ExportConfig,
+	frames: Vec<String>,
+}
+
 impl Default for NodeGraphExecutor {
 	fn default() -> Self {
 		Self {
@@ -73,6 +96,8 @@ impl Default for NodeGraphExecutor {
 			runtime_io: NodeRuntimeIO::new(),
 			node_graph_hash: 0,
 			old_inspect_node: None,
+			batch_export: None,
+			animation_export: None,
 		}
 	}
 }
@@ -90,6 +115,8 @@ impl NodeGraphExecutor {
 			runtime_io: NodeRuntimeIO::with_channels(request_sender, response_receiver),
 			node_graph_hash: 0,
 			old_inspect_node: None,
+			batch_export: None,
+			animation_export: None,
 		};
 		(node_runtime, node_executor)
 	}
@@ -185,8 +212,196 @@ impl NodeGraphExecutor {
 
 	/// Evaluates a node graph for export
 	pub fn submit_document_export(&mut self, document: &mut DocumentMessageHandler, mut export_config: ExportConfig) -> Result<(), String> {
-		let network = document.network_interface.document_network().clone();
+		let render_config = Self::export_render_config(document, &mut export_config)?;
+
+		// Execute the node graph
+		self.send_export_graph_update(document)?;
+		self.queue_export(render_config, export_config);
+
+		Ok(())
+	}
+
+	/// Renders every file of a batch export, which are downloaded together once the last one has finished.
+	pub fn submit_batch_export(
+		&mut self,
+		document: &mut DocumentMessageHandler,
+		archive_name: String,
+		files: Vec<BatchExportFile>,
+		transparent_background: bool,
+		raster_options: RasterExportOptions,
+		svg_options: SvgExportOptions,
+	) -> Result<(), String> {
+		if files.is_empty() {
+			return Err("No artboards were chosen for export".to_string());
+		}
+
+		let id = generate_uuid();
+		let exports = files
+			.into_iter()
+			.enumerate()
+			.map(|(index, file)| {
+				let mut export_config = ExportConfig {
+					file_name: file.file_name,
+					file_type: file.file_type,
+					scale_factor: file.scale_factor,
+					bounds: ExportBounds::Artboard(file.artboard),
+					transparent_background: file.file_type != FileType::Jpg && transparent_background,
+					raster_options,
+					svg_options,
+					batch: Some((id, index)),
+					..Default::default()
+				};
+				let render_config = Self::export_render_config(document, &mut export_config)?;
+				Ok((render_config, export_config))
+			})
+			.collect::<Result<Vec<_>, String>>()?;
+
+		// Execute the node graph once for each file
+		self.send_export_graph_update(document)?;
+		self.batch_export = Some(BatchExport {
+			id,
+			archive_name,
+			files: vec![None; exports.len()],
+		});
+		for (render_config, export_config) in exports {
+			self.queue_export(render_config, export_config);
+		}
+
+		Ok(())
+	}
+
+	/// Renders the frames of an animation one at a time, reporting the progress after each one, and downloads them once they are all done.
+	pub fn submit_animation_export(
+		&mut self,
+		document: &mut DocumentMessageHandler,
+		mut export_config: ExportConfig,
+		settings: AnimationExportSettings,
+		responses: &mut VecDeque<Message>,
+	) -> Result<(), String> {
+		let render_config = Self::export_render_config(document, &mut export_config)?;
+
+		self.send_export_graph_update(document)?;
+		self.animation_export = Some(AnimationExport {
+			id: generate_uuid(),
+			settings,
+			render_config,
+			export_config,
+			frames: Vec::new(),
+		});
+		self.queue_animation_frame(0);
+
+		responses.add(DialogMessage::DisplayExportProgress {
+			frame: 0,
+			frame_count: settings.frame_count(),
+		});
+
+		Ok(())
+	}
+
+	/// Stops rendering the frames of the current animated export and discards those already rendered.
+	pub fn cancel_animation_export(&mut self) {
+		self.animation_export = None;
+	}
+
+	fn queue_animation_frame(&mut self, frame: usize) {
+		let Some(animation_export) = &self.animation_export else { return };
+
+		// Real time based nodes advance along with the animation time, so each frame is the same every time it's exported
+		let time = animation_export.settings.frame_time(frame).max(0.);
+		let render_config = RenderConfig {
+			time: TimingInformation {
+				time: time * 1000.,
+				animation_time: Duration::from_secs_f64(time),
+			},
+			..animation_export.render_config
+		};
+		let export_config = ExportConfig {
+			animation_frame: Some((animation_export.id, frame)),
+			..animation_export.export_config.clone()
+		};
+
+		self.queue_export(render_config, export_config);
+	}
+
+	fn export_animation_frame(&mut self, id: u64, frame: usize, svg: String, responses: &mut VecDeque<Message>) -> Result<(), String> {
+		// Ignore frames left over from an animated export that has since been cancelled or replaced by another
+		let Some(animation_export) = self.animation_export.as_mut().filter(|animation_export| animation_export.id == id) else {
+			return Ok(());
+		};
+		animation_export.frames.push(svg);
+
+		let frame_count = animation_export.settings.frame_count();
+		if animation_export.frames.len() < frame_count {
+			responses.add(DialogMessage::DisplayExportProgress {
+				frame: animation_export.frames.len(),
+				frame_count,
+			});
+			self.queue_animation_frame(frame + 1);
+			return Ok(());
+		}
+
+		let AnimationExport { settings, export_config, frames, .. } = self.animation_export.take().unwrap();
+		responses.add(FrontendMessage::DisplayDialogDismiss);
+
+		let ExportConfig { file_name, size, scale_factor, .. } = export_config;
+		let name = file_name.strip_suffix(FILE_SAVE_SUFFIX).unwrap_or(&file_name);
+		let size = size * scale_factor;
+
+		if matches!(settings.format, AnimationFormat::Gif | AnimationFormat::Apng) {
+			let data = raster_export::encode_animation(&frames, size.as_uvec2(), settings)?;
+			let (extension, mime) = match settings.format {
+				AnimationFormat::Gif => ("gif", "image/gif"),
+				_ => ("png", "image/apng"),
+			};
+			responses.add(FrontendMessage::TriggerDownloadBinaryFile {
+				data,
+				name: format!("{name}.{extension}"),
+				mime: mime.to_string(),
+			});
+			return Ok(());
+		}
+
+		if let Some(style) = match settings.format {
+			AnimationFormat::AnimatedSvg => Some(SvgAnimationStyle::Smil),
+			AnimationFormat::AnimatedSvgCss => Some(SvgAnimationStyle::Css),
+			_ => None,
+		} {
+			let document = SvgRender::animate_frames(&frames, settings.frame_rate, style)?;
+			responses.add(FrontendMessage::TriggerDownloadTextFile {
+				document,
+				name: format!("{name}.svg"),
+			});
+			return Ok(());
+		}
 
+		// Number the frames with enough digits for them to sort correctly
+		let digits = frame_count.to_string().len().max(4);
+		let files = frames
+			.into_iter()
+			.enumerate()
+			.map(|(index, svg)| match settings.format {
+				AnimationFormat::SvgSequence => FrontendExportFile {
+					name: format!("{name} {:0digits$}.svg", index + 1),
+					mime: FileType::Svg.to_mime().to_string(),
+					content: FrontendExportFileContent::Text(svg),
+				},
+				_ => FrontendExportFile {
+					name: format!("{name} {:0digits$}.png", index + 1),
+					mime: FileType::Png.to_mime().to_string(),
+					content: FrontendExportFileContent::Image {
+						svg,
+						size: size.into(),
+						quality: None,
+					},
+				},
+			})
+			.collect();
+		responses.add(FrontendMessage::TriggerDownloadArchive { name: format!("{name}.zip"), files });
+
+		Ok(())
+	}
+
+	fn export_render_config(document: &DocumentMessageHandler, export_config: &mut ExportConfig) -> Result<RenderConfig, String> {
 		// Calculate the bounding box of the region to be exported
 		let bounds = match export_config.bounds {
 			ExportBounds::AllArtwork => document.network_interface.document_bounds_document_space(!export_config.transparent_background),
@@ -204,38 +419,52 @@ impl NodeGraphExecutor {
 				..Default::default()
 			},
 			time: Default::default(),
-			export_format: graphene_core::application_io::ExportFormat::Svg,
+			export_format: match export_config.file_type {
+				FileType::Pdf => graphene_core::application_io::ExportFormat::Pdf,
+				_ => graphene_core::application_io::ExportFormat::Svg,
+			},
 			view_mode: document.view_mode,
 			hide_artboards: export_config.transparent_background,
 			for_export: true,
 		};
 		export_config.size = size;
 
-		// Execute the node graph
+		Ok(render_config)
+	}
+
+	fn send_export_graph_update(&self, document: &DocumentMessageHandler) -> Result<(), String> {
+		let network = document.network_interface.document_network().clone();
 		self.runtime_io
 			.send(GraphRuntimeRequest::GraphUpdate(GraphUpdate { network, inspect_node: None }))
-			.map_err(|e| e.to_string())?;
+			.map_err(|e| e.to_string())
+	}
+
+	fn queue_export(&mut self, render_config: RenderConfig, export_config: ExportConfig) {
 		let execution_id = self.queue_execution(render_config);
 		let execution_context = ExecutionContext { export_config: Some(export_config) };
 		self.futures.insert(execution_id, execution_context);
-
-		Ok(())
 	}
 
-	fn export(&self, node_graph_output: TaggedValue, export_config: ExportConfig, responses: &mut VecDeque<Message>) -> Result<(), String> {
-		let TaggedValue::RenderOutput(RenderOutput {
-			data: graphene_std::wasm_application_io::RenderOutputType::Svg(svg),
-			..
-		}) = node_graph_output
-		else {
-			return Err("Incorrect render type for exporting (expected RenderOutput::Svg)".to_string());
+	fn export(&mut self, node_graph_output: TaggedValue, export_config: ExportConfig, responses: &mut VecDeque<Message>) -> Result<(), String> {
+		let TaggedValue::RenderOutput(RenderOutput { data, .. }) = node_graph_output else {
+			return Err("Incorrect render type for exporting (expected RenderOutput)".to_string());
 		};
 
+		if let Some((id, frame)) = export_config.animation_frame {
+			let RenderOutputType::Svg(svg) = data else {
+				return Err("Incorrect render type for exporting an animation frame".to_string());
+			};
+			return self.export_animation_frame(id, frame, svg, responses);
+		}
+
 		let ExportConfig {
 			file_type,
 			file_name,
 			size,
 			scale_factor,
+			raster_options,
+			svg_options,
+			batch,
 			..
 		} = export_config;
 
@@ -245,13 +474,47 @@ impl NodeGraphExecutor {
 			false => file_name + file_suffix,
 		};
 
-		if file_type == FileType::Svg {
-			responses.add(FrontendMessage::TriggerDownloadTextFile { document: svg, name });
-		} else {
-			let mime = file_type.to_mime().to_string();
-			let size = (size * scale_factor).into();
-			responses.add(FrontendMessage::TriggerDownloadImage { svg, name, mime, size });
+		let mime = file_type.to_mime().to_string();
+		let content = match data {
+			RenderOutputType::Pdf(data) => FrontendExportFileContent::Binary(data),
+			RenderOutputType::Svg(svg) if file_type == FileType::Svg && svg_options != SvgExportOptions::default() => FrontendExportFileContent::Text(SvgRender::optimize(&svg, svg_options)?),
+			RenderOutputType::Svg(svg) if file_type == FileType::Svg => FrontendExportFileContent::Text(svg),
+			RenderOutputType::Svg(svg) if raster_options.needs_native_encoding(file_type) => {
+				FrontendExportFileContent::Binary(raster_export::encode_svg(&svg, (size * scale_factor).as_uvec2(), file_type, raster_options)?)
+			}
+			RenderOutputType::Svg(svg) => FrontendExportFileContent::Image {
+				svg,
+				size: (size * scale_factor).into(),
+				quality: raster_options.canvas_quality(file_type),
+			},
+			_ => return Err(format!("Incorrect render type for exporting to {file_type:?}")),
+		};
+
+		let Some((id, index)) = batch else {
+			match content {
+				FrontendExportFileContent::Binary(data) => responses.add(FrontendMessage::TriggerDownloadBinaryFile { data, name, mime }),
+				FrontendExportFileContent::Text(document) => responses.add(FrontendMessage::TriggerDownloadTextFile { document, name }),
+				FrontendExportFileContent::Image { svg, size, quality } => responses.add(FrontendMessage::TriggerDownloadImage { svg, name, mime, size, quality }),
+			}
+			return Ok(());
+		};
+
+		// Ignore files left over from a batch export that has since been replaced by another
+		let Some(batch_export) = self.batch_export.as_mut().filter(|batch_export| batch_export.id == id) else {
+			return Ok(());
+		};
+		if let Some(file) = batch_export.files.get_mut(index) {
+			*file = Some(FrontendExportFile { name, mime, content });
+		}
+
+		if batch_export.files.iter().all(Option::is_some) {
+			let BatchExport { archive_name, files, .. } = self.batch_export.take().unwrap();
+			responses.add(FrontendMessage::TriggerDownloadArchive {
+				name: format!("{archive_name}.zip"),
+				files: files.into_iter().flatten().collect(),
+			});
 		}
+
 		Ok(())
 	}
 
@@ -519,3 +782,112 @@ mod test {
 		}
 	}
 }
+
+#[cfg(test)]
+mod test_animation_export {
+	use super::*;
+	use crate::application::Editor;
+	use crate::test_utils::EditorTestUtils;
+	use std::sync::mpsc::{Receiver, Sender};
+
+	/// Passes the requests for the node runtime on one at a time, rendering after each execution request, until there are none left.
+	///
+	/// Returns the animation time of each exported frame that was rendered and the messages sent to the frontend.
+	async fn render_queued_frames(
+		editor: &mut Editor,
+		requests: &Receiver<GraphRuntimeRequest>,
+		runtime_sender: &Sender<GraphRuntimeRequest>,
+		runtime: &mut NodeRuntime,
+	) -> (Vec<Duration>, Vec<FrontendMessage>) {
+		let mut animation_times = Vec::new();
+		let mut frontend_messages = Vec::new();
+
+		while let Ok(request) = requests.try_recv() {
+			let GraphRuntimeRequest::ExecutionRequest(ExecutionRequest { render_config, .. }) = &request else {
+				runtime_sender.send(request).unwrap();
+				continue;
+			};
+			if render_config.for_export {
+				animation_times.push(render_config.time.animation_time);
+			}
+
+			// The runtime only executes the latest execution request it has received, so each one is run before the next is passed on
+			runtime_sender.send(request).unwrap();
+			runtime.run().await;
+
+			let mut messages = VecDeque::new();
+			editor.poll_node_graph_evaluation(&mut messages).expect("Graph should render");
+			frontend_messages.extend(messages.into_iter().flat_map(|message| editor.handle_message(message)));
+		}
+
+		(animation_times, frontend_messages)
+	}
+
+	fn submit(settings: AnimationExportSettings) -> PortfolioMessage {
+		PortfolioMessage::SubmitAnimationExport {
+			file_name: "Animation".to_string(),
+			scale_factor: 1.,
+			bounds: ExportBounds::AllArtwork,
+			transparent_background: false,
+			settings,
+		}
+	}
+
+	#[tokio::test]
+	async fn frames_and_cancellation() {
+		let mut editor = EditorTestUtils::create();
+		editor.new_document().await;
+		editor.draw_rect(0., 0., 100., 50.).await;
+
+		// Requests for the node runtime go through the test, so the render config of each frame can be checked
+		let (request_sender, requests) = std::sync::mpsc::channel();
+		let (runtime_sender, runtime_receiver) = std::sync::mpsc::channel();
+		let (response_sender, response_receiver) = std::sync::mpsc::channel();
+		let mut runtime = NodeRuntime::new(runtime_receiver, response_sender);
+		let editor = &mut editor.editor;
+		editor.dispatcher.message_handlers.portfolio_message_handler.executor.runtime_io = NodeRuntimeIO::with_channels(request_sender, response_receiver);
+
+		let settings = AnimationExportSettings {
+			format: AnimationFormat::PngSequence,
+			start_time: 0.5,
+			duration: 0.25,
+			frame_rate: 20.,
+		};
+
+		// The first frame is still being rendered when the export is cancelled, so it has to be dropped once it arrives
+		editor.handle_message(submit(settings));
+		editor.handle_message(PortfolioMessage::CancelAnimationExport);
+		let (animation_times, frontend_messages) = render_queued_frames(editor, &requests, &runtime_sender, &mut runtime).await;
+		assert_eq!(animation_times, [Duration::from_secs_f64(0.5)]);
+		assert!(!frontend_messages.iter().any(|message| matches!(message, FrontendMessage::TriggerDownloadArchive { .. })));
+		assert!(editor.dispatcher.message_handlers.portfolio_message_handler.executor.animation_export.is_none());
+
+		editor.handle_message(submit(settings));
+		let (animation_times, frontend_messages) = render_queued_frames(editor, &requests, &runtime_sender, &mut runtime).await;
+
+		let frame_count = settings.frame_count();
+		assert_eq!(frame_count, 5);
+		let expected_times: Vec<_> = (0..frame_count).map(|frame| Duration::from_secs_f64(0.5 + frame as f64 / 20.)).collect();
+		assert_eq!(animation_times, expected_times);
+
+		let archives: Vec<_> = frontend_messages
+			.iter()
+			.filter_map(|message| match message {
+				FrontendMessage::TriggerDownloadArchive { name, files } => Some((name, files)),
+				_ => None,
+			})
+			.collect();
+		let [(name, files)] = archives.as_slice() else {
+			panic!("Expected one archive, got {}", archives.len());
+		};
+		assert_eq!(name.as_str(), "Animation.zip");
+
+		// Frame numbers are padded to at least four digits so the files sort correctly
+		let file_names: Vec<_> = files.iter().map(|file| file.name.as_str()).collect();
+		assert_eq!(
+			file_names,
+			["Animation 0001.png", "Animation 0002.png", "Animation 0003.png", "Animation 0004.png", "Animation 0005.png"]
+		);
+		assert!(files.iter().all(|file| matches!(&file.content, FrontendExportFileContent::Image { svg, .. } if svg.contains("<svg"))));
+	}
+}
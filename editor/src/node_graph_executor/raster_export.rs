@@ -0,0 +1,376 @@
+// Without the SVG rasterizer, nothing uses the encoders outside of the tests
+#![cfg_attr(not(feature = "resvg"), allow(dead_code))]
+
+use crate::messages::frontend::utility_types::{AnimationExportSettings, AnimationFormat, BitDepth, ColorProfile, FileType, RasterExportOptions};
+use glam::UVec2;
+use graphene_core::raster::color::Color;
+use graphene_core::raster::image::Image;
+use image::{ExtendedColorType, ImageEncoder};
+
+/// Rasterizes the exported SVG at the given resolution and encodes it to one of the file types that the browser's canvas can't produce.
+#[cfg(feature = "resvg")]
+pub fn encode_svg(svg: &str, resolution: UVec2, file_type: FileType, options: RasterExportOptions) -> Result<Vec<u8>, String> {
+	let pixels = rasterize(svg, resolution)?;
+	encode_pixels(&pixels, resolution, file_type, options)
+}
+
+/// Rasterizes each exported frame and encodes them together as an animated GIF or PNG, which loops forever.
+#[cfg(feature = "resvg")]
+pub fn encode_animation(frames: &[String], resolution: UVec2, settings: AnimationExportSettings) -> Result<Vec<u8>, String> {
+	let frames = frames.iter().map(|svg| rasterize(svg, resolution)).collect::<Result<Vec<_>, _>>()?;
+	let delay = std::time::Duration::from_secs_f64(1. / settings.frame_rate);
+
+	let mut output = Vec::new();
+	match settings.format {
+		AnimationFormat::Gif => {
+			let mut encoder = image::codecs::gif::GifEncoder::new_with_speed(&mut output, 10);
+			encoder.set_repeat(image::codecs::gif::Repeat::Infinite).map_err(|e| e.to_string())?;
+
+			let frames = frames.into_iter().map(|pixels| {
+				let buffer = image::RgbaImage::from_raw(resolution.x, resolution.y, pixels.into_flattened()).expect("Frame should match the resolution");
+				image::Frame::from_parts(buffer, 0, 0, image::Delay::from_saturating_duration(delay))
+			});
+			encoder.encode_frames(frames).map_err(|e| format!("Failed to encode the GIF file: {e}"))?;
+		}
+		AnimationFormat::Apng => {
+			let mut encoder = png::Encoder::new(&mut output, resolution.x, resolution.y);
+			encoder.set_color(png::ColorType::Rgba);
+			encoder.set_depth(png::BitDepth::Eight);
+			encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+			encoder.set_animated(frames.len() as u32, 0).map_err(|e| e.to_string())?;
+			// The delay is a fraction of a second, given in hundredths of a frame to support fractional frame rates
+			encoder
+				.set_frame_delay(100, (settings.frame_rate * 100.).round().clamp(1., u16::MAX as f64) as u16)
+				.map_err(|e| e.to_string())?;
+			// Clear each frame before drawing the next so transparent areas don't show the ones before it
+			encoder.set_dispose_op(png::DisposeOp::Background).map_err(|e| e.to_string())?;
+			encoder.set_blend_op(png::BlendOp::Source).map_err(|e| e.to_string())?;
+
+			let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+			for pixels in frames {
+				writer.write_image_data(pixels.as_flattened()).map_err(|e| format!("Failed to encode the PNG file: {e}"))?;
+			}
+			writer.finish().map_err(|e| e.to_string())?;
+		}
+		_ => return Err(format!("{:?} is not an animated file type", settings.format)),
+	}
+
+	Ok(output)
+}
+
+#[cfg(not(feature = "resvg"))]
+pub fn encode_animation(_frames: &[String], _resolution: UVec2, settings: AnimationExportSettings) -> Result<Vec<u8>, String> {
+	Err(format!("Exporting to {:?} isn't supported in this build", settings.format))
+}
+
+/// Renders the SVG into straight alpha sRGB pixels.
+#[cfg(feature = "resvg")]
+fn rasterize(svg: &str, resolution: UVec2) -> Result<Vec<[u8; 4]>, String> {
+	use resvg::{tiny_skia, usvg};
+
+	let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).map_err(|e| format!("Failed to read the exported SVG: {e}"))?;
+	let mut pixmap = tiny_skia::Pixmap::new(resolution.x, resolution.y).ok_or_else(|| "The exported image has no area".to_string())?;
+	let scale = resolution.as_vec2() / glam::Vec2::new(tree.size().width(), tree.size().height());
+	resvg::render(&tree, tiny_skia::Transform::from_scale(scale.x, scale.y), &mut pixmap.as_mut());
+
+	let pixels = pixmap
+		.pixels()
+		.iter()
+		.map(|pixel| {
+			let color = pixel.demultiply();
+			[color.red(), color.green(), color.blue(), color.alpha()]
+		})
+		.collect();
+
+	Ok(pixels)
+}
+
+#[cfg(not(feature = "resvg"))]
+pub fn encode_svg(_svg: &str, _resolution: UVec2, file_type: FileType, _options: RasterExportOptions) -> Result<Vec<u8>, String> {
+	Err(format!("Exporting to {file_type:?} with these settings isn't supported in this build"))
+}
+
+/// Encodes straight alpha sRGB pixels after converting them to the bit depth and color profile of the options.
+fn encode_pixels(pixels: &[[u8; 4]], resolution: UVec2, file_type: FileType, options: RasterExportOptions) -> Result<Vec<u8>, String> {
+	// AVIF files describe their color space with their own tags instead of an ICC profile, so they are always sRGB
+	let color_profile = if file_type == FileType::Avif { ColorProfile::Srgb } else { options.color_profile };
+	if file_type == FileType::Tiff {
+		return encode_tiff(pixels, resolution, options);
+	}
+
+	let channel = |value: u8| {
+		let value = value as f32 / 255.;
+		match color_profile {
+			ColorProfile::Srgb => value,
+			ColorProfile::LinearSrgb => Color::srgb_to_linear(value),
+		}
+	};
+	let samples = pixels.iter().flat_map(|&[r, g, b, a]| [channel(r), channel(g), channel(b), a as f32 / 255.]);
+
+	// The rasterized pixels only have 8 bits per channel, but linear PNGs are written with 16 so the dark shades converted from sRGB keep their precision
+	let sixteen_bit = color_profile == ColorProfile::LinearSrgb || options.bit_depth == BitDepth::Sixteen;
+	let (data, color_type) = if file_type == FileType::Png && sixteen_bit {
+		// The image encoders expect samples wider than a byte in native endianness
+		let data = samples.flat_map(|sample| ((sample * 65535.).round() as u16).to_ne_bytes()).collect::<Vec<_>>();
+		(data, ExtendedColorType::Rgba16)
+	} else {
+		let data = samples.map(|sample| (sample * 255.).round() as u8).collect::<Vec<_>>();
+		(data, ExtendedColorType::Rgba8)
+	};
+
+	let mut output = Vec::new();
+	let result = match file_type {
+		FileType::Png => {
+			let mut encoder = image::codecs::png::PngEncoder::new(&mut output);
+			encoder.set_icc_profile(icc_profile(color_profile)).map_err(|e| e.to_string())?;
+			encoder.write_image(&data, resolution.x, resolution.y, color_type)
+		}
+		FileType::Webp => {
+			let mut encoder = image::codecs::webp::WebPEncoder::new_lossless(&mut output);
+			encoder.set_icc_profile(icc_profile(color_profile)).map_err(|e| e.to_string())?;
+			encoder.write_image(&data, resolution.x, resolution.y, color_type)
+		}
+		FileType::Avif => {
+			let quality = options.quality.clamp(1., 100.).round() as u8;
+			image::codecs::avif::AvifEncoder::new_with_speed_quality(&mut output, 8, quality).write_image(&data, resolution.x, resolution.y, color_type)
+		}
+		_ => return Err(format!("{file_type:?} is not a raster file type")),
+	};
+	result.map_err(|e| format!("Failed to encode the {file_type:?} file: {e}"))?;
+
+	Ok(output)
+}
+
+/// Encodes sRGB pixels with the chosen bit depth, or linear pixels as floats so their dark shades don't get rounded away in the conversion from sRGB.
+/// The rasterized pixels only have 8 bits per channel to begin with, so neither adds detail that the renderer didn't draw.
+fn encode_tiff(pixels: &[[u8; 4]], resolution: UVec2, options: RasterExportOptions) -> Result<Vec<u8>, String> {
+	use graphene_std::tiff::{TiffBitDepth, TiffCompression, TiffWriteOptions};
+
+	let image = Image::from_image_data(pixels.as_flattened(), resolution.x, resolution.y);
+	let color_profile = options.color_profile;
+	let bit_depth = match (color_profile, options.bit_depth) {
+		(ColorProfile::Srgb, BitDepth::Eight) => TiffBitDepth::Eight,
+		(ColorProfile::Srgb, BitDepth::Sixteen) => TiffBitDepth::Sixteen,
+		(ColorProfile::LinearSrgb, _) => TiffBitDepth::Float,
+	};
+	let tiff_options = TiffWriteOptions {
+		compression: TiffCompression::Deflate,
+		icc_profile: Some(icc_profile(color_profile)),
+		..Default::default()
+	};
+
+	graphene_std::tiff::encode_tiff(&image, bit_depth, &tiff_options).map_err(|e| format!("Failed to encode the TIFF file: {e}"))
+}
+
+/// Builds an ICC version 4 display profile with the sRGB primaries and either the sRGB or a linear tone curve.
+fn icc_profile(color_profile: ColorProfile) -> Vec<u8> {
+	// The profile connection space white point, and the sRGB primaries adapted to it from D65 with the Bradford transform
+	const D50: [f64; 3] = [0.9642, 1., 0.8249];
+	const RED: [f64; 3] = [0.436_074_7, 0.222_504_5, 0.013_932_2];
+	const GREEN: [f64; 3] = [0.385_064_9, 0.716_878_6, 0.097_104_5];
+	const BLUE: [f64; 3] = [0.143_080_4, 0.060_616_9, 0.714_173_3];
+	const D65_TO_D50: [f64; 9] = [1.047_811_2, 0.022_886_6, -0.050_127, 0.029_542_4, 0.990_484_4, -0.017_049_1, -0.009_234_5, 0.015_043_6, 0.752_131_6];
+
+	let (description, curve) = match color_profile {
+		ColorProfile::Srgb => ("sRGB", parametric_curve(3, &[2.4, 1. / 1.055, 0.055 / 1.055, 1. / 12.92, 0.04045])),
+		ColorProfile::LinearSrgb => ("Linear sRGB", parametric_curve(0, &[1.])),
+	};
+
+	let tags = [
+		(b"desc", multi_localized_text(description)),
+		(b"cprt", multi_localized_text("No copyright, use freely")),
+		(b"wtpt", tag_data(b"XYZ ", &D50)),
+		(b"rXYZ", tag_data(b"XYZ ", &RED)),
+		(b"gXYZ", tag_data(b"XYZ ", &GREEN)),
+		(b"bXYZ", tag_data(b"XYZ ", &BLUE)),
+		(b"rTRC", curve.clone()),
+		(b"gTRC", curve.clone()),
+		(b"bTRC", curve),
+		(b"chad", tag_data(b"sf32", &D65_TO_D50)),
+	];
+
+	let mut profile = vec![0; 128];
+	profile.extend((tags.len() as u32).to_be_bytes());
+	let mut offset = profile.len() + tags.len() * 12;
+	let mut data = Vec::new();
+	for (signature, tag) in &tags {
+		profile.extend(*signature);
+		profile.extend((offset as u32).to_be_bytes());
+		profile.extend((tag.len() as u32).to_be_bytes());
+
+		// Each tag's data starts on a four byte boundary
+		let padded_length = tag.len().next_multiple_of(4);
+		data.extend(tag);
+		data.resize(data.len() + padded_length - tag.len(), 0);
+		offset += padded_length;
+	}
+	profile.extend(data);
+
+	let header = [
+		(0, (profile.len() as u32).to_be_bytes()),
+		(8, 0x0430_0000_u32.to_be_bytes()),
+		(12, *b"mntr"),
+		(16, *b"RGB "),
+		(20, *b"XYZ "),
+		(36, *b"acsp"),
+	];
+	for (position, bytes) in header {
+		profile[position..position + 4].copy_from_slice(&bytes);
+	}
+	// Creation date of 2025-01-01, fixed so the same settings always produce the same file
+	for (index, value) in [2025_u16, 1, 1, 0, 0, 0].into_iter().enumerate() {
+		profile[24 + index * 2..26 + index * 2].copy_from_slice(&value.to_be_bytes());
+	}
+	for (index, value) in D50.into_iter().enumerate() {
+		profile[68 + index * 4..72 + index * 4].copy_from_slice(&s15_fixed16(value));
+	}
+
+	profile
+}
+
+fn s15_fixed16(value: f64) -> [u8; 4] {
+	((value * 65536.).round() as i32).to_be_bytes()
+}
+
+/// A tag made of its type signature, four reserved bytes and a list of fixed point numbers.
+fn tag_data(signature: &[u8; 4], values: &[f64]) -> Vec<u8> {
+	let mut data = signature.to_vec();
+	data.extend([0; 4]);
+	data.extend(values.iter().flat_map(|&value| s15_fixed16(value)));
+	data
+}
+
+fn parametric_curve(function_type: u16, parameters: &[f64]) -> Vec<u8> {
+	let mut data = b"para".to_vec();
+	data.extend([0; 4]);
+	data.extend(function_type.to_be_bytes());
+	data.extend([0; 2]);
+	data.extend(parameters.iter().flat_map(|&value| s15_fixed16(value)));
+	data
+}
+
+fn multi_localized_text(text: &str) -> Vec<u8> {
+	let text = text.encode_utf16().flat_map(u16::to_be_bytes).collect::<Vec<_>>();
+
+	let mut data = b"mluc".to_vec();
+	data.extend([0; 4]);
+	// A single record of 12 bytes for US English, followed by its UTF-16 text
+	data.extend(1_u32.to_be_bytes());
+	data.extend(12_u32.to_be_bytes());
+	data.extend(*b"enUS");
+	data.extend((text.len() as u32).to_be_bytes());
+	data.extend(28_u32.to_be_bytes());
+	data.extend(text);
+	data
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::ImageDecoder;
+
+	#[test]
+	fn icc_profile_layout() {
+		let profile = icc_profile(ColorProfile::LinearSrgb);
+		assert_eq!(u32::from_be_bytes(profile[0..4].try_into().unwrap()) as usize, profile.len());
+		assert_eq!(&profile[36..40], b"acsp");
+
+		let tag_count = u32::from_be_bytes(profile[128..132].try_into().unwrap()) as usize;
+		assert_eq!(tag_count, 10);
+		for tag in profile[132..132 + tag_count * 12].chunks(12) {
+			let offset = u32::from_be_bytes(tag[4..8].try_into().unwrap()) as usize;
+			let length = u32::from_be_bytes(tag[8..12].try_into().unwrap()) as usize;
+			assert!(offset.is_multiple_of(4) && offset + length <= profile.len());
+		}
+	}
+
+	#[test]
+	fn linear_png() {
+		let pixels = [[255, 128, 0, 255], [0, 0, 0, 0]];
+		let options = RasterExportOptions {
+			color_profile: ColorProfile::LinearSrgb,
+			..Default::default()
+		};
+		let png = encode_pixels(&pixels, UVec2::new(2, 1), FileType::Png, options).unwrap();
+
+		let mut decoder = image::codecs::png::PngDecoder::new(std::io::Cursor::new(&png)).unwrap();
+		assert_eq!(decoder.icc_profile().unwrap(), Some(icc_profile(ColorProfile::LinearSrgb)));
+		assert_eq!(decoder.color_type(), image::ColorType::Rgba16);
+
+		let mut data = vec![0; decoder.total_bytes() as usize];
+		decoder.read_image(&mut data).unwrap();
+		let samples = data.chunks(2).map(|sample| u16::from_ne_bytes([sample[0], sample[1]])).collect::<Vec<_>>();
+		let half = (Color::srgb_to_linear(128. / 255.) * 65535.).round() as u16;
+		assert_eq!(samples, [65535, half, 0, 65535, 0, 0, 0, 0]);
+	}
+
+	#[test]
+	fn sixteen_bit_png() {
+		let options = RasterExportOptions {
+			bit_depth: BitDepth::Sixteen,
+			..Default::default()
+		};
+		let png = encode_pixels(&[[255, 128, 0, 255]], UVec2::new(1, 1), FileType::Png, options).unwrap();
+
+		let mut decoder = image::codecs::png::PngDecoder::new(std::io::Cursor::new(&png)).unwrap();
+		assert_eq!(decoder.icc_profile().unwrap(), Some(icc_profile(ColorProfile::Srgb)));
+		assert_eq!(decoder.color_type(), image::ColorType::Rgba16);
+
+		// The 8-bit samples are widened so they decode to the same colors
+		let mut data = vec![0; decoder.total_bytes() as usize];
+		decoder.read_image(&mut data).unwrap();
+		let samples = data.chunks(2).map(|sample| u16::from_ne_bytes([sample[0], sample[1]])).collect::<Vec<_>>();
+		assert_eq!(samples, [65535, 128 * 257, 0, 65535]);
+	}
+
+	#[test]
+	fn sixteen_bit_tiff() {
+		let options = RasterExportOptions {
+			bit_depth: BitDepth::Sixteen,
+			..Default::default()
+		};
+		let tiff = encode_pixels(&[[255, 128, 0, 255]], UVec2::new(1, 1), FileType::Tiff, options).unwrap();
+
+		// The bits per sample of the four channels
+		assert!(tiff.windows(8).any(|window| window == [16, 0, 16, 0, 16, 0, 16, 0]));
+		let profile = icc_profile(ColorProfile::Srgb);
+		assert!(tiff.windows(profile.len()).any(|window| window == profile));
+	}
+
+	#[test]
+	fn linear_tiff() {
+		let options = RasterExportOptions {
+			color_profile: ColorProfile::LinearSrgb,
+			..Default::default()
+		};
+		let tiff = encode_pixels(&[[255, 128, 0, 255]], UVec2::new(1, 1), FileType::Tiff, options).unwrap();
+
+		assert_eq!(tiff[..4], *b"II*\0");
+		let profile = icc_profile(ColorProfile::LinearSrgb);
+		assert!(tiff.windows(profile.len()).any(|window| window == profile));
+	}
+
+	#[cfg(feature = "resvg")]
+	#[test]
+	fn animated_png_frames() {
+		let frames = ["red", "blue", "lime"].map(|fill| format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4"><rect width="4" height="4" fill="{fill}"/></svg>"#));
+		let settings = AnimationExportSettings {
+			format: AnimationFormat::Apng,
+			frame_rate: 12.5,
+			..Default::default()
+		};
+		let apng = encode_animation(&frames, UVec2::new(4, 4), settings).unwrap();
+
+		let mut reader = png::Decoder::new(std::io::Cursor::new(&apng)).read_info().unwrap();
+		let animation_control = reader.info().animation_control.unwrap();
+		assert_eq!((animation_control.num_frames, animation_control.num_plays), (3, 0));
+
+		let mut buffer = vec![0; reader.output_buffer_size()];
+		for expected in [[255, 0, 0, 255], [0, 0, 255, 255], [0, 255, 0, 255]] {
+			reader.next_frame(&mut buffer).unwrap();
+			let frame_control = reader.info().frame_control.unwrap();
+			assert_eq!((frame_control.delay_num, frame_control.delay_den), (100, 1250));
+			assert_eq!(buffer[..4], expected);
+		}
+	}
+}
@@ -1,5 +1,5 @@
 use super::*;
-use crate::messages::frontend::utility_types::{ExportBounds, FileType};
+use crate::messages::frontend::utility_types::{ExportBounds, FileType, RasterExportOptions};
 use glam::{DAffine2, DVec2};
 use graph_craft::concrete;
 use graph_craft::document::value::TaggedValue;
@@ -9,7 +9,7 @@ use graph_craft::proto::GraphErrors;
 use graph_craft::wasm_application_io::EditorPreferences;
 use graphene_core::application_io::{NodeGraphUpdateMessage, NodeGraphUpdateSender, RenderConfig};
 use graphene_core::memo::IORecord;
-use graphene_core::renderer::{GraphicElementRendered, RenderParams, SvgRender};
+use graphene_core::renderer::{GraphicElementRendered, RenderParams, SvgExportOptions, SvgRender};
 use graphene_core::renderer::{RenderSvgSegmentList, SvgSegment};
 use graphene_core::text::FontCache;
 use graphene_core::vector::style::ViewMode;
@@ -73,7 +73,13 @@ pub struct ExportConfig {
 	pub scale_factor: f64,
 	pub bounds: ExportBounds,
 	pub transparent_background: bool,
+	pub raster_options: RasterExportOptions,
+	pub svg_options: SvgExportOptions,
 	pub size: DVec2,
+	/// The ID of the batch export this file belongs to, and the file's index within it.
+	pub batch: Option<(u64, usize)>,
+	/// The ID of the animated export this frame belongs to, and the frame's index within it.
+	pub animation_frame: Option<(u64, usize)>,
 }
 
 #[derive(Clone)]
@@ -804,6 +804,28 @@ export class TriggerPaste extends JsMessage {}
 
 export class TriggerDelayedZoomCanvasToFitAll extends JsMessage {}
 
+export type FrontendExportFileContent = { Text: string } | { Image: { svg: string; size: [number, number]; quality: number | undefined } } | { Binary: number[] };
+
+export type FrontendExportFile = {
+	name: string;
+	mime: string;
+	content: FrontendExportFileContent;
+};
+
+export class TriggerDownloadArchive extends JsMessage {
+	readonly name!: string;
+
+	readonly files!: FrontendExportFile[];
+}
+
+export class TriggerDownloadBinaryFile extends JsMessage {
+	readonly data!: number[];
+
+	readonly name!: string;
+
+	readonly mime!: string;
+}
+
 export class TriggerDownloadImage extends JsMessage {
 	readonly svg!: string;
 
@@ -813,6 +835,8 @@ export class TriggerDownloadImage extends JsMessage {
 
 	@TupleToVec2
 	readonly size!: XY;
+
+	readonly quality!: number | undefined;
 }
 
 export class TriggerDownloadTextFile extends JsMessage {
@@ -1637,6 +1661,8 @@ export const messageMakers: Record<string, MessageMaker> = {
 	SendUIMetadata,
 	TriggerAboutGraphiteLocalizedCommitDate,
 	TriggerDelayedZoomCanvasToFitAll,
+	TriggerDownloadArchive,
+	TriggerDownloadBinaryFile,
 	TriggerDownloadImage,
 	TriggerDownloadTextFile,
 	TriggerFetchAndOpenDocument,
@@ -6,6 +6,8 @@ import { type Editor } from "@graphite/editor";
 import {
 	type FrontendDocumentDetails,
 	TriggerFetchAndOpenDocument,
+	TriggerDownloadArchive,
+	TriggerDownloadBinaryFile,
 	TriggerDownloadImage,
 	TriggerDownloadTextFile,
 	TriggerImport,
@@ -19,10 +21,15 @@ import {
 	UpdateSpreadsheetLayout,
 } from "@graphite/messages";
 import { downloadFileText, downloadFileBlob, upload } from "@graphite/utility-functions/files";
-import { extractPixelData, rasterizeSVG } from "@graphite/utility-functions/rasterization";
+import { UnsupportedImageTypeError, extractPixelData, rasterizeSVG } from "@graphite/utility-functions/rasterization";
+import { type ZipEntry, createZipArchive } from "@graphite/utility-functions/zip";
 
 // eslint-disable-next-line @typescript-eslint/explicit-function-return-type
 export function createPortfolioState(editor: Editor) {
+	const exportFailedDialog = (error: UnsupportedImageTypeError) => {
+		editor.handle.errorDialog("Export failed", `${error.message}. Choose another file type, or lossless WebP which Graphite encodes itself.`);
+	};
+
 	const { subscribe, update } = writable({
 		unsaved: false,
 		documents: [] as FrontendDocumentDetails[],
@@ -88,20 +95,59 @@ export function createPortfolioState(editor: Editor) {
 	editor.subscriptions.subscribeJsMessage(TriggerDownloadTextFile, (triggerFileDownload) => {
 		downloadFileText(triggerFileDownload.name, triggerFileDownload.document);
 	});
+	editor.subscriptions.subscribeJsMessage(TriggerDownloadArchive, async (triggerDownloadArchive) => {
+		const { name, files } = triggerDownloadArchive;
+
+		const entries = await Promise.all(
+			files.map(async ({ name, mime, content }): Promise<ZipEntry | undefined | UnsupportedImageTypeError> => {
+				if ("Text" in content) return { name, blob: new Blob([content.Text], { type: mime }) };
+				if ("Binary" in content) return { name, blob: new Blob([new Uint8Array(content.Binary)], { type: mime }) };
+
+				// Rasterize the SVG to an image file, filling the canvas with white if it'll be a JPEG
+				const { svg, size, quality } = content.Image;
+				const backgroundColor = mime.endsWith("jpeg") ? "white" : undefined;
+				try {
+					return { name, blob: await rasterizeSVG(svg, size[0], size[1], mime, backgroundColor, quality) };
+				} catch (error) {
+					// The whole export fails if the browser can't encode the file type, since every file of that type would be missing
+					if (error instanceof UnsupportedImageTypeError) return error;
+
+					// Leave out any file that can't be rasterized, such as a zero-sized image
+					return undefined;
+				}
+			}),
+		);
+		const unsupported = entries.find((entry): entry is UnsupportedImageTypeError => entry instanceof UnsupportedImageTypeError);
+		if (unsupported) {
+			exportFailedDialog(unsupported);
+			return;
+		}
+		const exported = entries.filter((entry): entry is ZipEntry => entry !== undefined && !(entry instanceof UnsupportedImageTypeError));
+
+		// A single file is downloaded directly, while several are bundled together into a ZIP archive
+		if (exported.length === 1) downloadFileBlob(exported[0].name, exported[0].blob);
+		else if (exported.length > 1) downloadFileBlob(name, await createZipArchive(exported));
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerDownloadBinaryFile, (triggerDownloadBinaryFile) => {
+		const { data, name, mime } = triggerDownloadBinaryFile;
+
+		downloadFileBlob(name, new Blob([new Uint8Array(data)], { type: mime }));
+	});
 	editor.subscriptions.subscribeJsMessage(TriggerDownloadImage, async (triggerDownloadImage) => {
-		const { svg, name, mime, size } = triggerDownloadImage;
+		const { svg, name, mime, size, quality } = triggerDownloadImage;
 
 		// Fill the canvas with white if it'll be a JPEG (which does not support transparency and defaults to black)
 		const backgroundColor = mime.endsWith("jpeg") ? "white" : undefined;
 
 		// Rasterize the SVG to an image file
 		try {
-			const blob = await rasterizeSVG(svg, size.x, size.y, mime, backgroundColor);
+			const blob = await rasterizeSVG(svg, size.x, size.y, mime, backgroundColor, quality);
 
 			// Have the browser download the file to the user's disk
 			downloadFileBlob(name, blob);
-		} catch {
-			// Fail silently if there's an error rasterizing the SVG, such as a zero-sized image
+		} catch (error) {
+			if (error instanceof UnsupportedImageTypeError) exportFailedDialog(error);
+			// Otherwise fail silently if there's an error rasterizing the SVG, such as a zero-sized image
 		}
 	});
 	editor.subscriptions.subscribeJsMessage(TriggerUpgradeDocumentToVectorManipulationFormat, async (triggerUpgradeDocumentToVectorManipulationFormat) => {
@@ -101,8 +101,10 @@ import Artboard from "@graphite-frontend/assets/icon-16px-solid/artboard.svg";
 import BooleanDifference from "@graphite-frontend/assets/icon-16px-solid/boolean-difference.svg";
 import BooleanDivide from "@graphite-frontend/assets/icon-16px-solid/boolean-divide.svg";
 import BooleanIntersect from "@graphite-frontend/assets/icon-16px-solid/boolean-intersect.svg";
+import BooleanMerge from "@graphite-frontend/assets/icon-16px-solid/boolean-merge.svg";
 import BooleanSubtractBack from "@graphite-frontend/assets/icon-16px-solid/boolean-subtract-back.svg";
 import BooleanSubtractFront from "@graphite-frontend/assets/icon-16px-solid/boolean-subtract-front.svg";
+import BooleanTrim from "@graphite-frontend/assets/icon-16px-solid/boolean-trim.svg";
 import BooleanUnion from "@graphite-frontend/assets/icon-16px-solid/boolean-union.svg";
 import Bug from "@graphite-frontend/assets/icon-16px-solid/bug.svg";
 import CheckboxChecked from "@graphite-frontend/assets/icon-16px-solid/checkbox-checked.svg";
@@ -221,8 +223,10 @@ const SOLID_16PX = {
 	BooleanDifference: { svg: BooleanDifference, size: 16 },
 	BooleanDivide: { svg: BooleanDivide, size: 16 },
 	BooleanIntersect: { svg: BooleanIntersect, size: 16 },
+	BooleanMerge: { svg: BooleanMerge, size: 16 },
 	BooleanSubtractBack: { svg: BooleanSubtractBack, size: 16 },
 	BooleanSubtractFront: { svg: BooleanSubtractFront, size: 16 },
+	BooleanTrim: { svg: BooleanTrim, size: 16 },
 	BooleanUnion: { svg: BooleanUnion, size: 16 },
 	Bug: { svg: Bug, size: 16 },
 	CheckboxChecked: { svg: CheckboxChecked, size: 16 },
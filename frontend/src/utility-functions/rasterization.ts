@@ -33,8 +33,16 @@ export async function rasterizeSVGCanvas(svg: string, width: number, height: num
 	return canvas;
 }
 
+// Thrown by `rasterizeSVG()` when the browser can't encode images of the requested MIME type
+export class UnsupportedImageTypeError extends Error {
+	constructor(mime: string) {
+		super(`This browser can't encode ${mime} images`);
+	}
+}
+
 // Rasterize the string of an SVG document at a given width and height and turn it into the blob data of an image file matching the given MIME type
-export async function rasterizeSVG(svg: string, width: number, height: number, mime: string, backgroundColor?: string): Promise<Blob> {
+// The quality, from 0 to 1, is used by lossy image formats such as JPEG and WebP
+export async function rasterizeSVG(svg: string, width: number, height: number, mime: string, backgroundColor?: string, quality?: number): Promise<Blob> {
 	if (!width || !height) throw new Error("Width and height must be nonzero when given to rasterizeSVG()");
 
 	const canvas = await rasterizeSVGCanvas(svg, width, height, backgroundColor);
@@ -43,11 +51,14 @@ export async function rasterizeSVG(svg: string, width: number, height: number, m
 	const blob = await new Promise<Blob | undefined>((resolve) => {
 		canvas.toBlob((blob) => {
 			resolve(blob || undefined);
-		}, mime);
+		}, mime, quality);
 	});
 
 	if (!blob) throw new Error("Converting canvas to blob data failed in rasterizeSVG()");
 
+	// Browsers which can't encode the requested type give a PNG instead, like Safari does for WebP
+	if (blob.type !== mime) throw new UnsupportedImageTypeError(mime);
+
 	return blob;
 }
 
@@ -0,0 +1,92 @@
+export type ZipEntry = {
+	name: string;
+	blob: Blob;
+};
+
+const CRC32_TABLE = (() => {
+	const table = new Uint32Array(256);
+	for (let n = 0; n < 256; n += 1) {
+		let c = n;
+		for (let k = 0; k < 8; k += 1) {
+			c = c & 1 ? 0xedb88320 ^ (c >>> 1) : c >>> 1;
+		}
+		table[n] = c >>> 0;
+	}
+	return table;
+})();
+
+function crc32(data: Uint8Array): number {
+	let crc = 0xffffffff;
+	data.forEach((byte) => {
+		crc = CRC32_TABLE[(crc ^ byte) & 0xff] ^ (crc >>> 8);
+	});
+	return (crc ^ 0xffffffff) >>> 0;
+}
+
+// Packs the date into the MS-DOS format used by ZIP file headers
+function dosDateTime(date: Date): { time: number; date: number } {
+	const time = (date.getHours() << 11) | (date.getMinutes() << 5) | Math.floor(date.getSeconds() / 2);
+	const day = ((Math.max(date.getFullYear(), 1980) - 1980) << 9) | ((date.getMonth() + 1) << 5) | date.getDate();
+	return { time, date: day };
+}
+
+// Bundles the files into a ZIP archive without compression, since the exported images are already compressed
+export async function createZipArchive(entries: ZipEntry[]): Promise<Blob> {
+	const encoder = new TextEncoder();
+	const { time, date } = dosDateTime(new Date());
+
+	const localParts: BlobPart[] = [];
+	const centralParts: BlobPart[] = [];
+	let offset = 0;
+	let centralSize = 0;
+
+	for (const entry of entries) {
+		const name = encoder.encode(entry.name);
+		const data = new Uint8Array(await entry.blob.arrayBuffer());
+		const crc = crc32(data);
+
+		// Local file header, with bit 11 of the flags marking the name as UTF-8
+		const local = new DataView(new ArrayBuffer(30));
+		local.setUint32(0, 0x04034b50, true);
+		local.setUint16(4, 20, true);
+		local.setUint16(6, 0x0800, true);
+		local.setUint16(8, 0, true);
+		local.setUint16(10, time, true);
+		local.setUint16(12, date, true);
+		local.setUint32(14, crc, true);
+		local.setUint32(18, data.length, true);
+		local.setUint32(22, data.length, true);
+		local.setUint16(26, name.length, true);
+		local.setUint16(28, 0, true);
+		localParts.push(local, name, data);
+
+		// Central directory header
+		const central = new DataView(new ArrayBuffer(46));
+		central.setUint32(0, 0x02014b50, true);
+		central.setUint16(4, 20, true);
+		central.setUint16(6, 20, true);
+		central.setUint16(8, 0x0800, true);
+		central.setUint16(10, 0, true);
+		central.setUint16(12, time, true);
+		central.setUint16(14, date, true);
+		central.setUint32(16, crc, true);
+		central.setUint32(20, data.length, true);
+		central.setUint32(24, data.length, true);
+		central.setUint16(28, name.length, true);
+		central.setUint32(42, offset, true);
+		centralParts.push(central, name);
+
+		offset += 30 + name.length + data.length;
+		centralSize += 46 + name.length;
+	}
+
+	// End of central directory record
+	const end = new DataView(new ArrayBuffer(22));
+	end.setUint32(0, 0x06054b50, true);
+	end.setUint16(8, entries.length, true);
+	end.setUint16(10, entries.length, true);
+	end.setUint32(12, centralSize, true);
+	end.setUint32(16, offset, true);
+
+	return new Blob([...localParts, ...centralParts, end], { type: "application/zip" });
+}
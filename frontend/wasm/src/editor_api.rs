@@ -950,10 +950,10 @@ pub fn evaluate_math_expression(expression: &str) -> Option<f64> {
 	let value = math_parser::evaluate(expression)
 		.inspect_err(|err| error!("Math parser error on \"{expression}\": {err}"))
 		.ok()?
-		.0
 		.inspect_err(|err| error!("Math evaluate error on \"{expression}\": {err} "))
 		.ok()?;
-	let Some(real) = value.as_real() else {
+	// Lengths such as "12mm + 3pt" are given in pixels and angles in degrees
+	let Some(real) = value.as_design_real() else {
 		error!("{value} was not a real; skipping.");
 		return None;
 	};
@@ -1,6 +1,50 @@
 use criterion::{Criterion, black_box, criterion_group, criterion_main};
 use math_parser::ast;
-use math_parser::context::EvalContext;
+use math_parser::context::{EvalContext, NothingMap, ValueProvider};
+use math_parser::value::Value;
+
+/// Supplies the variable `a`, like the math node does for every instance it runs on.
+struct PointContext {
+	a: f64,
+}
+
+impl ValueProvider for PointContext {
+	fn get_value(&self, name: &str) -> Option<Value> {
+		(name == "a").then(|| Value::from_f64(self.a))
+	}
+}
+
+/// Compares the tree-walking evaluator with compiled programs for expressions which depend on a per-point variable.
+fn per_point_bench(c: &mut Criterion) {
+	const POINTS: usize = 1000;
+
+	for input in [
+		"a * 2 + 1",
+		"sqrt(a^2 + 16) * cos(pi / 3) + e",
+		"let r = a * 2; let area = pi * r^2; area / (1 + sqrt(16))",
+		"let f(x) = x^2 + 2 * x + 1; f(a) + f(a + 1)",
+		"a > 500 ? sin(a) * 10 : cos(a) * 20",
+	] {
+		let expr = ast::Node::try_parse_from_str(input).unwrap();
+		let program = expr.compile().unwrap();
+
+		c.bench_function(&format!("tree-walking {POINTS} points: {input}"), |b| {
+			b.iter(|| {
+				for a in 0..POINTS {
+					let _ = black_box(expr.eval(&EvalContext::new(PointContext { a: a as f64 }, NothingMap)));
+				}
+			});
+		});
+
+		c.bench_function(&format!("compiled {POINTS} points: {input}"), |b| {
+			b.iter(|| {
+				for a in 0..POINTS {
+					let _ = black_box(program.eval(&EvalContext::new(PointContext { a: a as f64 }, NothingMap)));
+				}
+			});
+		});
+	}
+}
 
 macro_rules! generate_benchmarks {
     ($( $input:expr_2021 ),* $(,)?) => {
@@ -16,7 +60,7 @@ macro_rules! generate_benchmarks {
 
         fn evaluation_bench(c: &mut Criterion) {
             $(
-                let expr = ast::Node::try_parse_from_str($input).unwrap().0;
+                let expr = ast::Node::try_parse_from_str($input).unwrap();
                 let context = EvalContext::default();
 
                 c.bench_function(concat!("eval ", $input), |b| {
@@ -27,7 +71,32 @@ macro_rules! generate_benchmarks {
             )*
         }
 
-        criterion_group!(benches, parsing_bench, evaluation_bench);
+        fn compilation_bench(c: &mut Criterion) {
+            $(
+                let expr = ast::Node::try_parse_from_str($input).unwrap();
+
+                c.bench_function(concat!("compile ", $input), |b| {
+                    b.iter(|| {
+                        let _ = black_box(expr.compile()).unwrap();
+                    });
+                });
+            )*
+        }
+
+        fn compiled_evaluation_bench(c: &mut Criterion) {
+            $(
+                let program = ast::Node::try_parse_from_str($input).unwrap().compile().unwrap();
+                let context = EvalContext::default();
+
+                c.bench_function(concat!("compiled eval ", $input), |b| {
+                    b.iter(|| {
+                        let _ = black_box(program.eval(&context));
+                    });
+                });
+            )*
+        }
+
+        criterion_group!(benches, parsing_bench, evaluation_bench, compilation_bench, compiled_evaluation_bench, per_point_bench);
         criterion_main!(benches);
     };
 }
@@ -1,13 +1,24 @@
 use crate::value::Complex;
 
-#[derive(Debug, PartialEq, Eq)]
+/// The size of one inch in meters, which pixels and points are defined by.
+pub const INCH: f64 = 0.0254;
+
+/// The size of a pixel in meters, following the CSS definition of 96 pixels per inch.
+pub const PIXEL: f64 = INCH / 96.;
+
+/// The size of a degree in radians.
+pub const DEGREE: f64 = std::f64::consts::PI / 180.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Unit {
 	// Exponent of length unit (meters)
 	pub length: i32,
-	// Exponent of mass unit (kilograms)
+	// Exponent of mass unit (grams)
 	pub mass: i32,
 	// Exponent of time unit (seconds)
 	pub time: i32,
+	// Exponent of angle unit (radians)
+	pub angle: i32,
 }
 
 impl Default for Unit {
@@ -17,16 +28,57 @@ impl Default for Unit {
 }
 
 impl Unit {
-	pub const BASE_UNIT: Unit = Unit { length: 0, mass: 0, time: 0 };
+	pub const BASE_UNIT: Unit = Unit {
+		length: 0,
+		mass: 0,
+		time: 0,
+		angle: 0,
+	};
 
-	pub const LENGTH: Unit = Unit { length: 1, mass: 0, time: 0 };
-	pub const MASS: Unit = Unit { length: 0, mass: 1, time: 0 };
-	pub const TIME: Unit = Unit { length: 0, mass: 0, time: 1 };
+	pub const LENGTH: Unit = Unit {
+		length: 1,
+		mass: 0,
+		time: 0,
+		angle: 0,
+	};
+	pub const MASS: Unit = Unit {
+		length: 0,
+		mass: 1,
+		time: 0,
+		angle: 0,
+	};
+	pub const TIME: Unit = Unit {
+		length: 0,
+		mass: 0,
+		time: 1,
+		angle: 0,
+	};
+	pub const ANGLE: Unit = Unit {
+		length: 0,
+		mass: 0,
+		time: 0,
+		angle: 1,
+	};
 
-	pub const VELOCITY: Unit = Unit { length: 1, mass: 0, time: -1 };
-	pub const ACCELERATION: Unit = Unit { length: 1, mass: 0, time: -2 };
+	pub const VELOCITY: Unit = Unit {
+		length: 1,
+		mass: 0,
+		time: -1,
+		angle: 0,
+	};
+	pub const ACCELERATION: Unit = Unit {
+		length: 1,
+		mass: 0,
+		time: -2,
+		angle: 0,
+	};
 
-	pub const FORCE: Unit = Unit { length: 1, mass: 1, time: -2 };
+	pub const FORCE: Unit = Unit {
+		length: 1,
+		mass: 1,
+		time: -2,
+		angle: 0,
+	};
 
 	pub fn base_unit() -> Self {
 		Self::BASE_UNIT
@@ -35,12 +87,78 @@ impl Unit {
 	pub fn is_base(&self) -> bool {
 		*self == Self::BASE_UNIT
 	}
+
+	pub fn pow(self, exponent: i32) -> Unit {
+		Unit {
+			length: self.length * exponent,
+			mass: self.mass * exponent,
+			time: self.time * exponent,
+			angle: self.angle * exponent,
+		}
+	}
+
+	/// The unit of the square root of a value with this unit, if every exponent is even.
+	pub fn sqrt(self) -> Option<Unit> {
+		let exponents = [self.length, self.mass, self.time, self.angle];
+		exponents.iter().all(|exponent| exponent % 2 == 0).then_some(Unit {
+			length: self.length / 2,
+			mass: self.mass / 2,
+			time: self.time / 2,
+			angle: self.angle / 2,
+		})
+	}
+}
+
+impl std::ops::Mul for Unit {
+	type Output = Unit;
+
+	/// The unit of the product of two values with these units.
+	fn mul(self, other: Unit) -> Unit {
+		Unit {
+			length: self.length + other.length,
+			mass: self.mass + other.mass,
+			time: self.time + other.time,
+			angle: self.angle + other.angle,
+		}
+	}
+}
+
+impl std::ops::Div for Unit {
+	type Output = Unit;
+
+	/// The unit of the quotient of two values with these units.
+	fn div(self, other: Unit) -> Unit {
+		self * other.pow(-1)
+	}
+}
+
+impl std::fmt::Display for Unit {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if self.is_base() {
+			return write!(f, "dimensionless");
+		}
+
+		let dimensions = [("m", self.length), ("g", self.mass), ("s", self.time), ("rad", self.angle)];
+		let mut dimensions = dimensions.iter().filter(|(_, exponent)| *exponent != 0).map(|(symbol, exponent)| match exponent {
+			1 => symbol.to_string(),
+			exponent => format!("{symbol}^{exponent}"),
+		});
+
+		write!(f, "{}", dimensions.next().unwrap_or_default())?;
+		for dimension in dimensions {
+			write!(f, "·{dimension}")?;
+		}
+		Ok(())
+	}
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Literal {
 	Float(f64),
 	Complex(Complex),
+	Bool(bool),
+	/// A number with a unit, measured in the base unit of each dimension
+	Quantity(f64, Unit),
 }
 
 impl From<f64> for Literal {
@@ -55,7 +173,28 @@ pub enum BinaryOp {
 	Sub,
 	Mul,
 	Div,
+	Mod,
 	Pow,
+	Eq,
+	Ne,
+	Lt,
+	Le,
+	Gt,
+	Ge,
+	And,
+	Or,
+}
+
+impl BinaryOp {
+	/// Whether the operation compares its operands, producing a boolean.
+	pub fn is_comparison(self) -> bool {
+		matches!(self, Self::Eq | Self::Ne | Self::Lt | Self::Le | Self::Gt | Self::Ge)
+	}
+
+	/// Whether the operation combines two booleans.
+	pub fn is_logical(self) -> bool {
+		matches!(self, Self::And | Self::Or)
+	}
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -63,13 +202,61 @@ pub enum UnaryOp {
 	Neg,
 	Sqrt,
 	Fac,
+	Not,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Node {
 	Lit(Literal),
 	Var(String),
-	FnCall { name: String, expr: Vec<Node> },
-	BinOp { lhs: Box<Node>, op: BinaryOp, rhs: Box<Node> },
-	UnaryOp { expr: Box<Node>, op: UnaryOp },
+	FnCall {
+		name: String,
+		expr: Vec<Node>,
+	},
+	BinOp {
+		lhs: Box<Node>,
+		op: BinaryOp,
+		rhs: Box<Node>,
+	},
+	UnaryOp {
+		expr: Box<Node>,
+		op: UnaryOp,
+	},
+	/// A list of values, such as `[1, 2, 3]`
+	List(Vec<Node>),
+	/// Accesses the component called `name` of a vector or color, such as `v.x`
+	Member {
+		expr: Box<Node>,
+		name: String,
+	},
+	/// Accesses the element of a list at a zero-based index, such as `list[0]`
+	Index {
+		expr: Box<Node>,
+		index: Box<Node>,
+	},
+	/// Evaluates to `if_true` when the condition holds and to `if_false` otherwise, only evaluating the chosen branch
+	Conditional {
+		condition: Box<Node>,
+		if_true: Box<Node>,
+		if_false: Box<Node>,
+	},
+	/// Binds the value of `value` to a variable called `name` while evaluating `body`
+	Let {
+		name: String,
+		value: Box<Node>,
+		body: Box<Node>,
+	},
+	/// Converts the value of `expr` into the unit which is `scale` times `unit` in base units, giving the magnitude in that unit as a plain number
+	Convert {
+		expr: Box<Node>,
+		unit: Unit,
+		scale: f64,
+	},
+	/// Defines a function called `name` which can be called while evaluating `body`
+	FnDef {
+		name: String,
+		params: Vec<String>,
+		expr: Box<Node>,
+		body: Box<Node>,
+	},
 }
@@ -0,0 +1,532 @@
+use crate::ast::{BinaryOp, Node, UnaryOp, Unit};
+use crate::constants::{DEFAULT_FUNCTIONS, FunctionImplementation};
+use crate::context::{EvalContext, FunctionProvider, ValueProvider};
+use crate::executer::{EvalError, MAX_CALL_DEPTH};
+use crate::value::Value;
+use std::cell::RefCell;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CompileError {
+	#[error("Functions can only capture variables defined at the top level of the program, but {0} belongs to another function")]
+	UnsupportedCapture(String),
+}
+
+/// A single step of a compiled program, which operates on a stack of values.
+/// Jump offsets count the instructions skipped after the jump itself.
+enum Instruction {
+	/// Pushes a constant, which may have been folded from a constant subexpression
+	Push(Value),
+	/// Pushes a variable from the frame of the function being evaluated
+	Local(usize),
+	/// Pushes a variable defined at the top level of the program, which functions can capture
+	Global(usize),
+	/// Pushes a variable supplied by the evaluation context
+	Var(String),
+	Binary(BinaryOp),
+	Unary(UnaryOp),
+	/// Skips the right hand side of a logical operation if the value on top of the stack already decides its result
+	ShortCircuit {
+		op: BinaryOp,
+		offset: usize,
+	},
+	/// Pops a condition and skips the instructions of the true branch unless it holds
+	JumpUnless(usize),
+	Jump(usize),
+	/// Removes the value below the top of the stack, which is how `let` bindings go out of scope
+	Drop,
+	List(usize),
+	Member(String),
+	Index,
+	Convert {
+		unit: Unit,
+		scale: f64,
+	},
+	CallBuiltin {
+		function: &'static FunctionImplementation,
+		args: usize,
+	},
+	/// Calls a function supplied by the evaluation context, falling back to a value with the same name like the tree-walking evaluator
+	CallExternal {
+		name: String,
+		args: usize,
+	},
+	Call {
+		function: usize,
+		args: usize,
+	},
+	Return,
+}
+
+/// A user-defined function within a compiled program.
+struct Function {
+	name: String,
+	params: usize,
+	entry: usize,
+}
+
+struct Frame {
+	return_address: usize,
+	base: usize,
+}
+
+/// The value stack and call frames used while running a program, which are reused between evaluations to avoid allocating.
+#[derive(Default)]
+struct Stack {
+	values: Vec<Value>,
+	frames: Vec<Frame>,
+}
+
+thread_local! {
+	static STACK: RefCell<Stack> = RefCell::default();
+}
+
+/// An expression compiled into a flat list of instructions, with its constant subexpressions folded ahead of time.
+/// Compiling once and evaluating many times avoids walking the syntax tree for every evaluation.
+pub struct Program {
+	instructions: Vec<Instruction>,
+	functions: Vec<Function>,
+}
+
+impl Node {
+	pub fn compile(&self) -> Result<Program, CompileError> {
+		let mut compiler = Compiler::default();
+		let mut instructions = compiler.compile(self, &mut Vec::new(), Position { frame: 0, height: 0 })?.into_instructions();
+		instructions.push(Instruction::Return);
+
+		let mut functions = Vec::with_capacity(compiler.functions.len());
+		for (name, params, body) in compiler.functions {
+			let entry = instructions.len();
+			instructions.extend(body);
+			functions.push(Function { name, params, entry });
+		}
+
+		Ok(Program { instructions, functions })
+	}
+}
+
+impl Program {
+	pub fn eval<V: ValueProvider, F: FunctionProvider>(&self, context: &EvalContext<V, F>) -> Result<Value, EvalError> {
+		STACK.with(|stack| match stack.try_borrow_mut() {
+			Ok(mut stack) => self.run(context, &mut stack),
+			// The context may evaluate another program while this one is running, which then needs a stack of its own
+			Err(_) => self.run(context, &mut Stack::default()),
+		})
+	}
+
+	/// The number of instructions, which shows how much of the expression was folded into constants.
+	pub fn len(&self) -> usize {
+		self.instructions.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.instructions.is_empty()
+	}
+
+	fn run<V: ValueProvider, F: FunctionProvider>(&self, context: &EvalContext<V, F>, stack: &mut Stack) -> Result<Value, EvalError> {
+		let Stack { values, frames } = stack;
+		values.clear();
+		frames.clear();
+
+		let mut address = 0;
+		let mut base = 0;
+
+		loop {
+			let instruction = &self.instructions[address];
+			address += 1;
+
+			match instruction {
+				Instruction::Push(value) => values.push(value.clone()),
+				Instruction::Local(slot) => values.push(values[base + slot].clone()),
+				Instruction::Global(slot) => values.push(values[*slot].clone()),
+				Instruction::Var(name) => values.push(context.get_value(name).ok_or_else(|| EvalError::MissingValue(name.clone()))?),
+				Instruction::Binary(op) => {
+					let rhs = pop(values);
+					let lhs = pop(values);
+					values.push(lhs.binary_op(*op, rhs)?);
+				}
+				Instruction::Unary(op) => {
+					let value = pop(values);
+					values.push(value.unary_op(*op)?);
+				}
+				Instruction::ShortCircuit { op, offset } => {
+					if matches!((op, values.last()), (BinaryOp::And, Some(Value::Bool(false))) | (BinaryOp::Or, Some(Value::Bool(true)))) {
+						address += offset;
+					}
+				}
+				Instruction::JumpUnless(offset) => match pop(values) {
+					Value::Bool(true) => {}
+					Value::Bool(false) => address += offset,
+					_ => return Err(EvalError::NonBooleanCondition),
+				},
+				Instruction::Jump(offset) => address += offset,
+				Instruction::Drop => {
+					let below_top = values.len() - 2;
+					values.swap_remove(below_top);
+				}
+				Instruction::List(length) => {
+					let list = values.split_off(values.len() - length);
+					values.push(Value::List(list));
+				}
+				Instruction::Member(name) => {
+					let value = pop(values);
+					values.push(value.component(name)?);
+				}
+				Instruction::Index => {
+					let index = pop(values);
+					let list = pop(values);
+					values.push(list.index(&index)?);
+				}
+				Instruction::Convert { unit, scale } => {
+					let value = pop(values);
+					values.push(value.convert(*unit, *scale)?);
+				}
+				Instruction::CallBuiltin { function, args } => {
+					let start = values.len() - args;
+					let result = function(&values[start..]).ok_or(EvalError::TypeError)?;
+					values.truncate(start);
+					values.push(result);
+				}
+				Instruction::CallExternal { name, args } => {
+					let start = values.len() - args;
+					let result = context
+						.run_function(name, &values[start..])
+						.or_else(|| context.get_value(name))
+						.ok_or_else(|| EvalError::MissingFunction(name.clone()))?;
+					values.truncate(start);
+					values.push(result);
+				}
+				Instruction::Call { function, args } => {
+					let function = &self.functions[*function];
+					if function.params != *args {
+						return Err(EvalError::WrongArgumentCount {
+							name: function.name.clone(),
+							expected: function.params,
+							found: *args,
+						});
+					}
+					if frames.len() >= MAX_CALL_DEPTH {
+						return Err(EvalError::CallDepthExceeded);
+					}
+
+					frames.push(Frame { return_address: address, base });
+					base = values.len() - args;
+					address = function.entry;
+				}
+				Instruction::Return => {
+					let result = pop(values);
+					let Some(frame) = frames.pop() else {
+						return Ok(result);
+					};
+
+					values.truncate(base);
+					values.push(result);
+					address = frame.return_address;
+					base = frame.base;
+				}
+			}
+		}
+	}
+}
+
+fn pop(values: &mut Vec<Value>) -> Value {
+	values.pop().expect("compiled programs never pop more values than they push")
+}
+
+/// The result of compiling a node, which is either a constant known ahead of time or the instructions computing it.
+enum Code {
+	Constant(Value),
+	Instructions(Vec<Instruction>),
+}
+
+impl Code {
+	fn into_instructions(self) -> Vec<Instruction> {
+		match self {
+			Code::Constant(value) => vec![Instruction::Push(value)],
+			Code::Instructions(instructions) => instructions,
+		}
+	}
+
+	fn len(&self) -> usize {
+		match self {
+			Code::Constant(_) => 1,
+			Code::Instructions(instructions) => instructions.len(),
+		}
+	}
+
+	fn constant(&self) -> Option<&Value> {
+		match self {
+			Code::Constant(value) => Some(value),
+			Code::Instructions(_) => None,
+		}
+	}
+}
+
+/// Applies an operation to a constant ahead of time, or adds the instruction performing it if the value is only known later or the operation fails.
+fn fold(code: Code, operation: impl FnOnce(&Value) -> Result<Value, EvalError>, instruction: Instruction) -> Code {
+	if let Some(Ok(value)) = code.constant().map(operation) {
+		return Code::Constant(value);
+	}
+	concat([code], [instruction])
+}
+
+/// Concatenates the instructions of several pieces of code.
+fn concat(code: impl IntoIterator<Item = Code>, extra: impl IntoIterator<Item = Instruction>) -> Code {
+	let mut instructions: Vec<Instruction> = code.into_iter().flat_map(Code::into_instructions).collect();
+	instructions.extend(extra);
+	Code::Instructions(instructions)
+}
+
+/// Where the value of the node being compiled ends up: in which function's frame and at which height of its stack.
+#[derive(Clone, Copy)]
+struct Position {
+	frame: usize,
+	height: usize,
+}
+
+impl Position {
+	fn above(self, count: usize) -> Self {
+		Self { height: self.height + count, ..self }
+	}
+}
+
+enum Binding<'a> {
+	Constant { name: &'a str, value: Value },
+	Variable { name: &'a str, frame: usize, slot: usize },
+	Function { name: &'a str, index: usize },
+}
+
+#[derive(Default)]
+struct Compiler {
+	/// The name, parameter count and body of every user-defined function
+	functions: Vec<(String, usize, Vec<Instruction>)>,
+	frames: usize,
+}
+
+impl Compiler {
+	fn compile<'a>(&mut self, node: &'a Node, scope: &mut Vec<Binding<'a>>, position: Position) -> Result<Code, CompileError> {
+		Ok(match node {
+			Node::Lit(lit) => Code::Constant(lit.into()),
+			Node::Var(name) => {
+				let binding = scope
+					.iter()
+					.rev()
+					.find(|binding| matches!(binding, Binding::Constant { name: other, .. } | Binding::Variable { name: other, .. } if *other == *name));
+				match binding {
+					Some(Binding::Constant { value, .. }) => Code::Constant(value.clone()),
+					Some(Binding::Variable { frame, slot, .. }) if *frame == position.frame => Code::Instructions(vec![Instruction::Local(*slot)]),
+					Some(Binding::Variable { frame: 0, slot, .. }) => Code::Instructions(vec![Instruction::Global(*slot)]),
+					Some(_) => return Err(CompileError::UnsupportedCapture(name.clone())),
+					None => Code::Instructions(vec![Instruction::Var(name.clone())]),
+				}
+			}
+			Node::BinOp { lhs, op, rhs } => {
+				let lhs = self.compile(lhs, scope, position)?;
+
+				// The right hand side can be skipped entirely when the left one decides the result of a logical operation
+				match (op, &lhs) {
+					(BinaryOp::And, Code::Constant(Value::Bool(false))) | (BinaryOp::Or, Code::Constant(Value::Bool(true))) => return Ok(lhs),
+					_ => {}
+				}
+
+				let rhs = self.compile(rhs, scope, position.above(1))?;
+				if let (Code::Constant(lhs), Code::Constant(rhs)) = (&lhs, &rhs) {
+					if let Ok(value) = lhs.clone().binary_op(*op, rhs.clone()) {
+						return Ok(Code::Constant(value));
+					}
+				}
+
+				let short_circuit = (op.is_logical() && matches!(lhs, Code::Instructions(_))).then(|| Instruction::ShortCircuit { op: *op, offset: rhs.len() + 1 });
+				let lhs = concat([lhs], short_circuit);
+				concat([lhs, rhs], [Instruction::Binary(*op)])
+			}
+			Node::UnaryOp { expr, op } => fold(self.compile(expr, scope, position)?, |value| value.clone().unary_op(*op), Instruction::Unary(*op)),
+			Node::List(elements) => {
+				let elements = self.compile_all(elements, scope, position)?;
+				match elements.iter().map(|element| element.constant().cloned()).collect::<Option<Vec<_>>>() {
+					Some(list) => Code::Constant(Value::List(list)),
+					None => {
+						let length = elements.len();
+						concat(elements, [Instruction::List(length)])
+					}
+				}
+			}
+			Node::Member { expr, name } => fold(self.compile(expr, scope, position)?, |value| value.component(name), Instruction::Member(name.clone())),
+			Node::Index { expr, index } => {
+				let expr = self.compile(expr, scope, position)?;
+				let index = self.compile(index, scope, position.above(1))?;
+				if let (Code::Constant(expr), Code::Constant(index)) = (&expr, &index) {
+					if let Ok(value) = expr.index(index) {
+						return Ok(Code::Constant(value));
+					}
+				}
+				concat([expr, index], [Instruction::Index])
+			}
+			Node::Convert { expr, unit, scale } => fold(
+				self.compile(expr, scope, position)?,
+				|value| value.convert(*unit, *scale),
+				Instruction::Convert { unit: *unit, scale: *scale },
+			),
+			Node::FnCall { name, expr } => {
+				let args = self.compile_all(expr, scope, position)?;
+				let count = args.len();
+
+				// Functions are looked up in the same order as the tree-walking evaluator: user-defined, then built-in, then from the context
+				let function = scope.iter().rev().find_map(|binding| match binding {
+					Binding::Function { name: other, index } if *other == *name => Some(*index),
+					_ => None,
+				});
+				if let Some(function) = function {
+					return Ok(concat(args, [Instruction::Call { function, args: count }]));
+				}
+
+				let Some(function) = DEFAULT_FUNCTIONS.get(name.as_str()) else {
+					return Ok(concat(args, [Instruction::CallExternal { name: name.clone(), args: count }]));
+				};
+
+				let constants = args.iter().map(|arg| arg.constant().cloned()).collect::<Option<Vec<_>>>();
+				match constants.and_then(|constants| function(&constants)) {
+					Some(value) => Code::Constant(value),
+					None => concat(args, [Instruction::CallBuiltin { function, args: count }]),
+				}
+			}
+			Node::Conditional { condition, if_true, if_false } => match self.compile(condition, scope, position)? {
+				// Only the branch which is taken needs to be compiled when the condition is constant
+				Code::Constant(Value::Bool(true)) => self.compile(if_true, scope, position)?,
+				Code::Constant(Value::Bool(false)) => self.compile(if_false, scope, position)?,
+				condition => {
+					let if_true = self.compile(if_true, scope, position)?;
+					let if_false = self.compile(if_false, scope, position)?;
+
+					let if_true = concat([if_true], [Instruction::Jump(if_false.len())]);
+					let condition = concat([condition], [Instruction::JumpUnless(if_true.len())]);
+					concat([condition, if_true, if_false], [])
+				}
+			},
+			Node::Let { name, value, body } => match self.compile(value, scope, position)? {
+				Code::Constant(value) => {
+					scope.push(Binding::Constant { name, value });
+					let body = self.compile(body, scope, position);
+					scope.pop();
+					body?
+				}
+				value => {
+					scope.push(Binding::Variable {
+						name,
+						frame: position.frame,
+						slot: position.height,
+					});
+					let body = self.compile(body, scope, position.above(1));
+					scope.pop();
+					concat([value, body?], [Instruction::Drop])
+				}
+			},
+			Node::FnDef { name, params, expr, body } => {
+				let index = self.functions.len();
+				self.functions.push((name.clone(), params.len(), Vec::new()));
+				scope.push(Binding::Function { name, index });
+
+				// The function's body runs in a frame of its own, starting with its parameters
+				self.frames += 1;
+				let frame = self.frames;
+				let parameter_count = scope.len();
+				scope.extend(params.iter().enumerate().map(|(slot, name)| Binding::Variable { name, frame, slot }));
+				let function_body = self.compile(expr, scope, Position { frame, height: params.len() });
+				scope.truncate(parameter_count);
+				self.functions[index].2 = concat([function_body?], [Instruction::Return]).into_instructions();
+
+				let body = self.compile(body, scope, position);
+				scope.pop();
+				body?
+			}
+		})
+	}
+
+	/// Compiles a sequence of nodes whose values are pushed onto the stack one after another.
+	fn compile_all<'a>(&mut self, nodes: &'a [Node], scope: &mut Vec<Binding<'a>>, position: Position) -> Result<Vec<Code>, CompileError> {
+		nodes.iter().enumerate().map(|(i, node)| self.compile(node, scope, position.above(i))).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::context::NothingMap;
+	use std::collections::HashMap;
+
+	struct Values(HashMap<&'static str, Value>);
+
+	impl ValueProvider for Values {
+		fn get_value(&self, name: &str) -> Option<Value> {
+			self.0.get(name).cloned()
+		}
+	}
+
+	/// Checks that the compiled program gives the same result as the tree-walking evaluator.
+	fn assert_same_result(expression: &str, context: &EvalContext<impl ValueProvider, impl FunctionProvider>) {
+		let node = Node::try_parse_from_str(expression).unwrap();
+		let expected = node.eval(context);
+		let actual = node.compile().unwrap().eval(context);
+		assert_eq!(format!("{actual:?}"), format!("{expected:?}"), "Compiled result differs for `{expression}`");
+	}
+
+	#[test]
+	fn matches_tree_walking_evaluator() {
+		let values = Values(HashMap::from([("a", Value::from_f64(3.)), ("v", Value::Vec2(glam::DVec2::new(1., 2.)))]));
+		let context = EvalContext::new(values, NothingMap);
+
+		let expressions = [
+			"a * 2 + 1",
+			"sqrt(a^2 + 16)",
+			"let x = a * 2; let y = x + 1; x * y",
+			"let k = a; let scale(x) = k * x; scale(2) + scale(3)",
+			"let fact(n) = n <= 1 ? 1 : n * fact(n - 1); fact(a + 2)",
+			"let fib(n) = n < 2 ? n : fib(n - 1) + fib(n - 2); fib(10)",
+			"let f(x, y) = x - y; f(a, 1) * f(1, a)",
+			"a > 2 && a < 4",
+			"a > 5 && missing",
+			"a < 5 || missing",
+			"a ? 1 : 2",
+			"if(a == 3, v.x, v.y)",
+			"[a, 2, 3] * 2",
+			"[a, 2, 3][2]",
+			"sum([a, 2, 3])",
+			"max(a, 1, 5)",
+			"v + vec2(a, a)",
+			"length(v * a)",
+			"a in to mm",
+			"a m + 1s",
+			"missing + 1",
+			"unknown(a)",
+			"let f(x) = x; f(a, a)",
+			"let f(x) = f(x + 1); f(a)",
+		];
+		for expression in expressions {
+			assert_same_result(expression, &context);
+		}
+	}
+
+	#[test]
+	fn folds_constants() {
+		let program = Node::try_parse_from_str("(2 * pi * sqrt(16) + max(1, 2)) rad to deg").unwrap().compile().unwrap();
+		// A single instruction pushing the result, followed by returning it
+		assert_eq!(program.len(), 2);
+
+		let program = Node::try_parse_from_str("let r = 2; let area = pi * r^2; area / 2").unwrap().compile().unwrap();
+		assert_eq!(program.len(), 2);
+
+		let program = Node::try_parse_from_str("1 > 2 ? missing : 3").unwrap().compile().unwrap();
+		assert_eq!(program.len(), 2);
+	}
+
+	#[test]
+	fn reuses_program() {
+		let program = Node::try_parse_from_str("let f(x) = x * x + 1; f(a)").unwrap().compile().unwrap();
+
+		for a in 0..10 {
+			let values = Values(HashMap::from([("a", Value::from_f64(a as f64))]));
+			let result = program.eval(&EvalContext::new(values, NothingMap)).unwrap();
+			assert_eq!(result, Value::from_f64((a * a + 1) as f64));
+		}
+	}
+}
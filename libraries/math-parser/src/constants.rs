@@ -1,10 +1,37 @@
-use crate::value::{Number, Value};
+use crate::ast::{BinaryOp, Unit};
+use crate::value::{Number, Quantity, Value};
+use glam::{DVec2, DVec4};
 use lazy_static::lazy_static;
 use num_complex::{Complex, ComplexFloat};
 use std::collections::HashMap;
 use std::f64::consts::PI;
 
-type FunctionImplementation = Box<dyn Fn(&[Value]) -> Option<Value> + Send + Sync>;
+pub(crate) type FunctionImplementation = Box<dyn Fn(&[Value]) -> Option<Value> + Send + Sync>;
+
+/// Wraps a function of one real number, which is the shape of most of the standard library.
+fn real_function(function: fn(f64) -> f64) -> FunctionImplementation {
+	Box::new(move |values| match values {
+		[Value::Number(Number::Real(real))] => Some(Value::from_f64(function(*real))),
+		_ => None,
+	})
+}
+
+/// The elements of a list if it's the only argument, so functions such as `max` accept either a list or several arguments.
+fn elements(values: &[Value]) -> &[Value] {
+	match values {
+		[Value::List(list)] => list,
+		values => values,
+	}
+}
+
+/// Collects the arguments as real numbers in base units, failing unless all of them share the same unit.
+fn quantities(values: &[Value]) -> Option<(Vec<f64>, Unit)> {
+	let values = elements(values);
+	let unit = values.first()?.unit();
+	let values = values.iter().map(|value| value.as_quantity().filter(|(_, other)| *other == unit).map(|(value, _)| value));
+	Some((values.collect::<Option<_>>()?, unit))
+}
+
 lazy_static! {
 	pub static ref DEFAULT_FUNCTIONS: HashMap<&'static str, FunctionImplementation> = {
 		let mut map: HashMap<&'static str, FunctionImplementation> = HashMap::new();
@@ -12,7 +39,7 @@ lazy_static! {
 		map.insert(
 			"sin",
 			Box::new(|values| match values {
-				[Value::Number(Number::Real(real))] => Some(Value::Number(Number::Real(real.sin()))),
+				[Value::Number(Number::Real(real)) | Value::Quantity(Quantity { value: real, unit: Unit::ANGLE })] => Some(Value::Number(Number::Real(real.sin()))),
 				[Value::Number(Number::Complex(complex))] => Some(Value::Number(Number::Complex(complex.sin()))),
 				_ => None,
 			}),
@@ -21,7 +48,7 @@ lazy_static! {
 		map.insert(
 			"cos",
 			Box::new(|values| match values {
-				[Value::Number(Number::Real(real))] => Some(Value::Number(Number::Real(real.cos()))),
+				[Value::Number(Number::Real(real)) | Value::Quantity(Quantity { value: real, unit: Unit::ANGLE })] => Some(Value::Number(Number::Real(real.cos()))),
 				[Value::Number(Number::Complex(complex))] => Some(Value::Number(Number::Complex(complex.cos()))),
 				_ => None,
 			}),
@@ -30,7 +57,7 @@ lazy_static! {
 		map.insert(
 			"tan",
 			Box::new(|values| match values {
-				[Value::Number(Number::Real(real))] => Some(Value::Number(Number::Real(real.tan()))),
+				[Value::Number(Number::Real(real)) | Value::Quantity(Quantity { value: real, unit: Unit::ANGLE })] => Some(Value::Number(Number::Real(real.tan()))),
 				[Value::Number(Number::Complex(complex))] => Some(Value::Number(Number::Complex(complex.tan()))),
 				_ => None,
 			}),
@@ -39,7 +66,7 @@ lazy_static! {
 		map.insert(
 			"csc",
 			Box::new(|values| match values {
-				[Value::Number(Number::Real(real))] => Some(Value::Number(Number::Real(real.sin().recip()))),
+				[Value::Number(Number::Real(real)) | Value::Quantity(Quantity { value: real, unit: Unit::ANGLE })] => Some(Value::Number(Number::Real(real.sin().recip()))),
 				[Value::Number(Number::Complex(complex))] => Some(Value::Number(Number::Complex(complex.sin().recip()))),
 				_ => None,
 			}),
@@ -48,7 +75,7 @@ lazy_static! {
 		map.insert(
 			"sec",
 			Box::new(|values| match values {
-				[Value::Number(Number::Real(real))] => Some(Value::Number(Number::Real(real.cos().recip()))),
+				[Value::Number(Number::Real(real)) | Value::Quantity(Quantity { value: real, unit: Unit::ANGLE })] => Some(Value::Number(Number::Real(real.cos().recip()))),
 				[Value::Number(Number::Complex(complex))] => Some(Value::Number(Number::Complex(complex.cos().recip()))),
 				_ => None,
 			}),
@@ -57,7 +84,7 @@ lazy_static! {
 		map.insert(
 			"cot",
 			Box::new(|values| match values {
-				[Value::Number(Number::Real(real))] => Some(Value::Number(Number::Real(real.tan().recip()))),
+				[Value::Number(Number::Real(real)) | Value::Quantity(Quantity { value: real, unit: Unit::ANGLE })] => Some(Value::Number(Number::Real(real.tan().recip()))),
 				[Value::Number(Number::Complex(complex))] => Some(Value::Number(Number::Complex(complex.tan().recip()))),
 				_ => None,
 			}),
@@ -117,6 +144,197 @@ lazy_static! {
 			}),
 		);
 
+		map.insert("asin", Box::new(|values| DEFAULT_FUNCTIONS["invsin"](values)));
+		map.insert("acos", Box::new(|values| DEFAULT_FUNCTIONS["invcos"](values)));
+		map.insert("atan", Box::new(|values| DEFAULT_FUNCTIONS["invtan"](values)));
+
+		map.insert(
+			"atan2",
+			Box::new(|values| match values {
+				[Value::Number(Number::Real(y)), Value::Number(Number::Real(x))] => Some(Value::from_f64(y.atan2(*x))),
+				_ => None,
+			}),
+		);
+
+		map.insert("floor", real_function(f64::floor));
+		map.insert("ceil", real_function(f64::ceil));
+		map.insert("round", real_function(f64::round));
+		map.insert("trunc", real_function(f64::trunc));
+		map.insert("fract", real_function(f64::fract));
+		map.insert("sign", real_function(f64::signum));
+
+		map.insert(
+			"abs",
+			Box::new(|values| match values {
+				[Value::Number(Number::Real(real))] => Some(Value::from_f64(real.abs())),
+				[Value::Number(Number::Complex(complex))] => Some(Value::from_f64(complex.norm())),
+				[Value::Quantity(quantity)] => Some(Value::from_quantity(quantity.value.abs(), quantity.unit)),
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"exp",
+			Box::new(|values| match values {
+				[Value::Number(Number::Real(real))] => Some(Value::Number(Number::Real(real.exp()))),
+				[Value::Number(Number::Complex(complex))] => Some(Value::Number(Number::Complex(complex.exp()))),
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"ln",
+			Box::new(|values| match values {
+				[Value::Number(Number::Real(real))] => Some(Value::Number(Number::Real(real.ln()))),
+				[Value::Number(Number::Complex(complex))] => Some(Value::Number(Number::Complex(complex.ln()))),
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"log",
+			Box::new(|values| match values {
+				[Value::Number(Number::Real(real))] => Some(Value::from_f64(real.log10())),
+				[Value::Number(Number::Real(real)), Value::Number(Number::Real(base))] => Some(Value::from_f64(real.log(*base))),
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"min",
+			Box::new(|values| {
+				let (values, unit) = quantities(values)?;
+				values.into_iter().reduce(f64::min).map(|min| Value::from_quantity(min, unit))
+			}),
+		);
+
+		map.insert(
+			"max",
+			Box::new(|values| {
+				let (values, unit) = quantities(values)?;
+				values.into_iter().reduce(f64::max).map(|max| Value::from_quantity(max, unit))
+			}),
+		);
+
+		map.insert(
+			"clamp",
+			Box::new(|values| {
+				let (values, unit) = quantities(values)?;
+				match values.as_slice() {
+					[value, min, max] if min <= max => Some(Value::from_quantity(value.clamp(*min, *max), unit)),
+					_ => None,
+				}
+			}),
+		);
+
+		map.insert(
+			"lerp",
+			Box::new(|values| match values {
+				[a, b, t] => {
+					let difference = b.clone().binary_op(BinaryOp::Sub, a.clone()).ok()?;
+					a.clone().binary_op(BinaryOp::Add, difference.binary_op(BinaryOp::Mul, t.clone()).ok()?).ok()
+				}
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"sum",
+			Box::new(|values| {
+				let mut values = elements(values).iter().cloned();
+				let first = values.next().unwrap_or(Value::from_f64(0.));
+				values.try_fold(first, |sum, value| sum.binary_op(BinaryOp::Add, value).ok())
+			}),
+		);
+
+		map.insert(
+			"avg",
+			Box::new(|values| {
+				let count = elements(values).len();
+				if count == 0 {
+					return None;
+				}
+				DEFAULT_FUNCTIONS["sum"](values)?.binary_op(BinaryOp::Div, Value::from_f64(count as f64)).ok()
+			}),
+		);
+
+		map.insert(
+			"len",
+			Box::new(|values| match values {
+				[Value::List(list)] => Some(Value::from_f64(list.len() as f64)),
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"vec2",
+			Box::new(|values| match values {
+				[Value::Number(Number::Real(x)), Value::Number(Number::Real(y))] => Some(Value::Vec2(DVec2::new(*x, *y))),
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"rgba",
+			Box::new(|values| match values {
+				[
+					Value::Number(Number::Real(r)),
+					Value::Number(Number::Real(g)),
+					Value::Number(Number::Real(b)),
+					Value::Number(Number::Real(a)),
+				] => Some(Value::Color(DVec4::new(*r, *g, *b, *a))),
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"rgb",
+			Box::new(|values| match values {
+				[r, g, b] => DEFAULT_FUNCTIONS["rgba"](&[r.clone(), g.clone(), b.clone(), Value::from_f64(1.)]),
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"dot",
+			Box::new(|values| match values {
+				[Value::Vec2(a), Value::Vec2(b)] => Some(Value::from_f64(a.dot(*b))),
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"cross",
+			Box::new(|values| match values {
+				[Value::Vec2(a), Value::Vec2(b)] => Some(Value::from_f64(a.perp_dot(*b))),
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"length",
+			Box::new(|values| match values {
+				[Value::Vec2(vector)] => Some(Value::from_f64(vector.length())),
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"normalize",
+			Box::new(|values| match values {
+				[Value::Vec2(vector)] => Some(Value::Vec2(vector.normalize_or_zero())),
+				_ => None,
+			}),
+		);
+
+		map.insert(
+			"distance",
+			Box::new(|values| match values {
+				[Value::Vec2(a), Value::Vec2(b)] => Some(Value::from_f64(a.distance(*b))),
+				_ => None,
+			}),
+		);
+
 		map
 	};
 }
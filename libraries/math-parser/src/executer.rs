@@ -1,9 +1,12 @@
-use crate::ast::{Literal, Node};
+use crate::ast::{BinaryOp, Node, UnaryOp, Unit};
 use crate::constants::DEFAULT_FUNCTIONS;
 use crate::context::{EvalContext, FunctionProvider, ValueProvider};
-use crate::value::{Number, Value};
+use crate::value::Value;
 use thiserror::Error;
 
+/// Limits how deeply user-defined functions may call each other, so runaway recursion fails instead of overflowing the stack.
+pub(crate) const MAX_CALL_DEPTH: usize = 128;
+
 #[derive(Debug, Error)]
 pub enum EvalError {
 	#[error("Missing value: {0}")]
@@ -13,26 +16,114 @@ pub enum EvalError {
 	MissingFunction(String),
 	#[error("Wrong type for function call")]
 	TypeError,
+	#[error("Unsupported operand types for {0:?}")]
+	UnsupportedBinaryOp(BinaryOp),
+	#[error("Unsupported operand type for {0:?}")]
+	UnsupportedUnaryOp(UnaryOp),
+	#[error("The condition must evaluate to a boolean")]
+	NonBooleanCondition,
+
+	#[error("Incompatible units for {1:?}: {0} and {2}")]
+	IncompatibleUnits(Unit, BinaryOp, Unit),
+	#[error("Incompatible unit for {0:?}: {1}")]
+	IncompatibleUnit(UnaryOp, Unit),
+	#[error("Cannot convert a value in {0} to {1}")]
+	IncompatibleConversion(Unit, Unit),
+	#[error("Only real numbers can be converted between units")]
+	UnsupportedConversion,
+
+	#[error("No component named {0}")]
+	MissingComponent(String),
+	#[error("Invalid index {0} for a list of length {1}")]
+	InvalidIndex(f64, usize),
+	#[error("Lists of lengths {0} and {1} can't be combined")]
+	MismatchedLengths(usize, usize),
+
+	#[error("Function {name} expects {expected} arguments but was given {found}")]
+	WrongArgumentCount { name: String, expected: usize, found: usize },
+	#[error("Function calls are nested too deeply")]
+	CallDepthExceeded,
+}
+
+/// The variables and functions defined by `let` statements enclosing the node being evaluated.
+/// Each binding lives on the call stack and points to the bindings defined before it.
+#[derive(Clone)]
+enum Scope<'a> {
+	Empty,
+	Value {
+		name: &'a str,
+		value: Value,
+		parent: &'a Scope<'a>,
+	},
+	Function {
+		name: &'a str,
+		params: &'a [String],
+		expr: &'a Node,
+		parent: &'a Scope<'a>,
+	},
+}
+
+impl<'a> Scope<'a> {
+	fn get_value(&self, target: &str) -> Option<Value> {
+		match self {
+			Scope::Empty => None,
+			Scope::Value { name, value, .. } if *name == target => Some(value.clone()),
+			Scope::Value { parent, .. } | Scope::Function { parent, .. } => parent.get_value(target),
+		}
+	}
+
+	/// Finds a function along with the scope it was defined in, which includes the function itself so it can recurse.
+	fn get_function(&'a self, target: &str) -> Option<(&'a [String], &'a Node, &'a Scope<'a>)> {
+		match self {
+			Scope::Empty => None,
+			Scope::Function { name, params, expr, .. } if *name == target => Some((params, expr, self)),
+			Scope::Value { parent, .. } | Scope::Function { parent, .. } => parent.get_function(target),
+		}
+	}
 }
 
 impl Node {
 	pub fn eval<V: ValueProvider, F: FunctionProvider>(&self, context: &EvalContext<V, F>) -> Result<Value, EvalError> {
+		self.eval_scoped(context, &Scope::Empty, 0)
+	}
+
+	fn eval_scoped<V: ValueProvider, F: FunctionProvider>(&self, context: &EvalContext<V, F>, scope: &Scope, depth: usize) -> Result<Value, EvalError> {
 		match self {
-			Node::Lit(lit) => match lit {
-				Literal::Float(num) => Ok(Value::from_f64(*num)),
-				Literal::Complex(num) => Ok(Value::Number(Number::Complex(*num))),
-			},
+			Node::Lit(lit) => Ok(lit.into()),
 
-			Node::BinOp { lhs, op, rhs } => match (lhs.eval(context)?, rhs.eval(context)?) {
-				(Value::Number(lhs), Value::Number(rhs)) => Ok(Value::Number(lhs.binary_op(*op, rhs))),
-			},
-			Node::UnaryOp { expr, op } => match expr.eval(context)? {
-				Value::Number(num) => Ok(Value::Number(num.unary_op(*op))),
-			},
-			Node::Var(name) => context.get_value(name).ok_or_else(|| EvalError::MissingValue(name.clone())),
+			Node::BinOp { lhs, op, rhs } => {
+				let lhs = lhs.eval_scoped(context, scope, depth)?;
+
+				// Logical operations short-circuit, skipping the right hand side when the left one decides the result
+				match (op, &lhs) {
+					(BinaryOp::And, Value::Bool(false)) => return Ok(Value::Bool(false)),
+					(BinaryOp::Or, Value::Bool(true)) => return Ok(Value::Bool(true)),
+					_ => {}
+				}
+
+				let rhs = rhs.eval_scoped(context, scope, depth)?;
+				lhs.binary_op(*op, rhs)
+			}
+			Node::UnaryOp { expr, op } => expr.eval_scoped(context, scope, depth)?.unary_op(*op),
+			Node::List(elements) => elements.iter().map(|element| element.eval_scoped(context, scope, depth)).collect::<Result<_, _>>().map(Value::List),
+			Node::Member { expr, name } => expr.eval_scoped(context, scope, depth)?.component(name),
+			Node::Index { expr, index } => expr.eval_scoped(context, scope, depth)?.index(&index.eval_scoped(context, scope, depth)?),
+			Node::Var(name) => scope.get_value(name).or_else(|| context.get_value(name)).ok_or_else(|| EvalError::MissingValue(name.clone())),
 			Node::FnCall { name, expr } => {
-				let values = expr.iter().map(|expr| expr.eval(context)).collect::<Result<Vec<Value>, EvalError>>()?;
-				if let Some(function) = DEFAULT_FUNCTIONS.get(&name.as_str()) {
+				let values = expr.iter().map(|expr| expr.eval_scoped(context, scope, depth)).collect::<Result<Vec<Value>, EvalError>>()?;
+				if let Some((params, expr, definition_scope)) = scope.get_function(name) {
+					if params.len() != values.len() {
+						return Err(EvalError::WrongArgumentCount {
+							name: name.clone(),
+							expected: params.len(),
+							found: values.len(),
+						});
+					}
+					if depth >= MAX_CALL_DEPTH {
+						return Err(EvalError::CallDepthExceeded);
+					}
+					expr.eval_call(context, params, &values, definition_scope, depth + 1)
+				} else if let Some(function) = DEFAULT_FUNCTIONS.get(&name.as_str()) {
 					function(&values).ok_or(EvalError::TypeError)
 				} else if let Some(val) = context.run_function(name, &values) {
 					Ok(val)
@@ -40,6 +131,32 @@ impl Node {
 					context.get_value(name).ok_or_else(|| EvalError::MissingFunction(name.to_string()))
 				}
 			}
+			Node::Conditional { condition, if_true, if_false } => match condition.eval_scoped(context, scope, depth)? {
+				Value::Bool(true) => if_true.eval_scoped(context, scope, depth),
+				Value::Bool(false) => if_false.eval_scoped(context, scope, depth),
+				_ => Err(EvalError::NonBooleanCondition),
+			},
+			Node::Convert { expr, unit, scale } => expr.eval_scoped(context, scope, depth)?.convert(*unit, *scale),
+			Node::Let { name, value, body } => {
+				let value = value.eval_scoped(context, scope, depth)?;
+				body.eval_scoped(context, &Scope::Value { name, value, parent: scope }, depth)
+			}
+			Node::FnDef { name, params, expr, body } => body.eval_scoped(context, &Scope::Function { name, params, expr, parent: scope }, depth),
+		}
+	}
+
+	/// Evaluates the body of a user-defined function after binding its parameters to the arguments one at a time.
+	fn eval_call<V: ValueProvider, F: FunctionProvider>(&self, context: &EvalContext<V, F>, params: &[String], args: &[Value], scope: &Scope, depth: usize) -> Result<Value, EvalError> {
+		match (params.split_first(), args.split_first()) {
+			(Some((name, params)), Some((value, args))) => {
+				let scope = Scope::Value {
+					name,
+					value: value.clone(),
+					parent: scope,
+				};
+				self.eval_call(context, params, args, &scope, depth)
+			}
+			_ => self.eval_scoped(context, scope, depth),
 		}
 	}
 }
@@ -1,6 +1,7 @@
 #![allow(unused)]
 
 pub mod ast;
+pub mod compiler;
 mod constants;
 pub mod context;
 pub mod executer;
@@ -13,16 +14,17 @@ use executer::EvalError;
 use parser::ParseError;
 use value::Value;
 
-pub fn evaluate(expression: &str) -> Result<(Result<Value, EvalError>, Unit), ParseError> {
+pub fn evaluate(expression: &str) -> Result<Result<Value, EvalError>, ParseError> {
 	let expr = ast::Node::try_parse_from_str(expression);
 	let context = EvalContext::default();
-	expr.map(|(node, unit)| (node.eval(&context), unit))
+	expr.map(|node| node.eval(&context))
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use ast::Unit;
+	use ast::{BinaryOp, UnaryOp, Unit};
+	use glam::{DVec2, DVec4};
 	use value::Number;
 
 	const EPSILON: f64 = 1e-10_f64;
@@ -35,17 +37,27 @@ mod tests {
 					let expected_value = $expected_value;
 					let expected_unit = $expected_unit;
 
-					let expr = ast::Node::try_parse_from_str($input);
+					let node = ast::Node::try_parse_from_str($input).unwrap();
 					let context = EvalContext::default();
 
-					let (actual_value, actual_unit) = expr.map(|(node, unit)| (node.eval(&context), unit)).unwrap();
-					let actual_value = actual_value.unwrap();
+					let actual_value = node.eval(&context).unwrap();
+					let actual_unit = actual_value.unit();
+
+					// The compiled program must agree with the tree-walking evaluator
+					let compiled_value = node.compile().unwrap().eval(&context).unwrap();
+					assert_eq!(format!("{compiled_value:?}"), format!("{actual_value:?}"), "Compiled program gave a different result");
 
 
 					assert!(actual_unit == expected_unit, "Expected unit {:?} but found unit {:?}", expected_unit, actual_unit);
 
 					let expected_value = expected_value.into();
 
+					// Quantities are compared by their magnitude in base units, since the units were already checked
+					let actual_value = match actual_value.as_quantity() {
+						Some((value, _)) => Value::from_f64(value),
+						None => actual_value,
+					};
+
 					match (actual_value, expected_value) {
 						(Value::Number(Number::Complex(actual_c)), Value::Number(Number::Complex(expected_c))) => {
 							assert!(
@@ -75,8 +87,14 @@ mod tests {
 								assert!((actual_f - expected_f).abs() < EPSILON, "Expected {}, but got {}", expected_f, actual_f);
 							}
 						}
+						(Value::Bool(actual_b), Value::Bool(expected_b)) => {
+							assert_eq!(actual_b, expected_b, "Expected {}, but got {}", expected_b, actual_b);
+						}
+						(actual, expected @ (Value::Vec2(_) | Value::Color(_) | Value::List(_))) => {
+							assert_eq!(actual, expected, "Expected {}, but got {}", expected, actual);
+						}
 						// Handle mismatched types
-						_ => panic!("Mismatched types: expected {:?}, got {:?}", expected_value, actual_value),
+						(actual, expected) => panic!("Mismatched types: expected {:?}, got {:?}", expected, actual),
 					}
 
 				}
@@ -88,7 +106,7 @@ mod tests {
 		// Basic arithmetic and units
 		infix_addition: "5 + 5" => (10., Unit::BASE_UNIT),
 		infix_subtraction_units: "5m - 3m" => (2., Unit::LENGTH),
-		infix_multiplication_units: "4s * 4s" => (16., Unit { length: 0, mass: 0, time: 2 }),
+		infix_multiplication_units: "4s * 4s" => (16., Unit::TIME.pow(2)),
 		infix_division_units: "8m/2s" => (4., Unit::VELOCITY),
 
 		// Order of operations
@@ -145,5 +163,167 @@ mod tests {
 		trig_tan_pi_div_four: "tan(pi/4)" => (1.0, Unit::BASE_UNIT),
 		trig_sin_tau: "sin(tau)" => (0.0, Unit::BASE_UNIT),
 		trig_cos_tau_div_two: "cos(tau/2)" => (-1.0, Unit::BASE_UNIT),
+
+		// Modulo
+		modulo_integers: "7 % 3" => (1., Unit::BASE_UNIT),
+		modulo_negative: "-1 % 3" => (2., Unit::BASE_UNIT),
+		modulo_precedence: "1 + 7 % 3 * 2" => (3., Unit::BASE_UNIT),
+
+		// Factorial
+		factorial_integer: "5!" => (120., Unit::BASE_UNIT),
+		factorial_zero: "0!" => (1., Unit::BASE_UNIT),
+		factorial_precedence: "2 * 3! + 1" => (13., Unit::BASE_UNIT),
+		factorial_fraction: "0.5!" => (std::f64::consts::PI.sqrt() / 2., Unit::BASE_UNIT),
+		factorial_negative_integer: "(-1)!" => (f64::NAN, Unit::BASE_UNIT),
+
+		// Comparisons and logic
+		comparison_less_than: "3 < 5" => (true, Unit::BASE_UNIT),
+		comparison_greater_equal: "3 >= 5" => (false, Unit::BASE_UNIT),
+		comparison_equal: "2 * 2 == 4" => (true, Unit::BASE_UNIT),
+		comparison_not_equal: "1 != 1" => (false, Unit::BASE_UNIT),
+		comparison_units: "5m > 3m" => (true, Unit::BASE_UNIT),
+		logic_and: "1 < 2 && 3 > 4" => (false, Unit::BASE_UNIT),
+		logic_or_literals: "true || false" => (true, Unit::BASE_UNIT),
+		logic_not: "!(1 > 2)" => (true, Unit::BASE_UNIT),
+
+		// Conditionals
+		conditional_ternary: "2 > 1 ? 10 : 20" => (10., Unit::BASE_UNIT),
+		conditional_nested: "1 > 2 ? 1 : 2 > 3 ? 2 : 3" => (3., Unit::BASE_UNIT),
+		conditional_if_function: "if(1 > 2, 10, 20)" => (20., Unit::BASE_UNIT),
+		conditional_units: "true ? 2m : 3m" => (2., Unit::LENGTH),
+		conditional_branch_units: "false ? 2m : 3s" => (3., Unit::TIME),
+
+		// Variables and functions
+		variable_let: "let x = 5; x * 2" => (10., Unit::BASE_UNIT),
+		variable_multiline: "let x = 2m\nlet y = x + 3m\ny * 2" => (10., Unit::LENGTH),
+		variable_shadowing: "let x = 1; let x = x + 1; x" => (2., Unit::BASE_UNIT),
+		function_single_param: "let f(x) = x^2 + 1; f(3)" => (10., Unit::BASE_UNIT),
+		function_multiple_params: "let hyp(a, b) = sqrt(a^2 + b^2); hyp(3, 4)" => (5., Unit::BASE_UNIT),
+		function_captures_variable: "let k = 3; let scale(x) = k * x; scale(2)" => (6., Unit::BASE_UNIT),
+		function_recursive: "let fact(n) = n <= 1 ? 1 : n * fact(n - 1); fact(5)" => (120., Unit::BASE_UNIT),
+
+		// Standard library
+		stdlib_min: "min(3, 1, 2)" => (1., Unit::BASE_UNIT),
+		stdlib_max: "max(3, 1, 2)" => (3., Unit::BASE_UNIT),
+		stdlib_clamp: "clamp(5, 0, 2)" => (2., Unit::BASE_UNIT),
+		stdlib_lerp: "lerp(0, 10, 0.25)" => (2.5, Unit::BASE_UNIT),
+		stdlib_floor: "floor(2.7)" => (2., Unit::BASE_UNIT),
+		stdlib_round: "round(2.5)" => (3., Unit::BASE_UNIT),
+		stdlib_atan2: "atan2(1, 1)" => (std::f64::consts::FRAC_PI_4, Unit::BASE_UNIT),
+		stdlib_exp: "exp(1)" => (std::f64::consts::E, Unit::BASE_UNIT),
+		stdlib_log_base: "log(100, 10)" => (2., Unit::BASE_UNIT),
+
+		// Units
+		units_scaled: "3km + 500m" => (3500., Unit::LENGTH),
+		units_design_lengths: "1in - 72pt" => (0., Unit::LENGTH),
+		units_mixed_lengths: "12mm + 3pt" => (0.012 + 0.0254 / 24., Unit::LENGTH),
+		units_area: "2m * 3m" => (6., Unit::LENGTH.pow(2)),
+		units_power: "(2m)^3" => (8., Unit::LENGTH.pow(3)),
+		units_sqrt: "sqrt(16m^2)" => (16., Unit::LENGTH),
+		units_cancel: "1in / 1mm" => (25.4, Unit::BASE_UNIT),
+		units_angle: "180deg - pi rad" => (0., Unit::ANGLE),
+		units_degree_symbol: "90°" => (std::f64::consts::FRAC_PI_2, Unit::ANGLE),
+		units_trig: "sin(30deg)" => (0.5, Unit::BASE_UNIT),
+		units_stdlib: "max(1in, 2cm, 10px)" => (0.0254, Unit::LENGTH),
+		units_variable: "let width = 2in; width * 3" => (6. * 0.0254, Unit::LENGTH),
+		units_function_param: "let double(x) = x * 2; double(5mm)" => (0.01, Unit::LENGTH),
+
+		// Unit conversion
+		conversion_length: "5in to mm" => (127., Unit::BASE_UNIT),
+		conversion_sum: "12mm + 3pt to px" => (12. / 25.4 * 96. + 4., Unit::BASE_UNIT),
+		conversion_angle: "pi rad to deg" => (180., Unit::BASE_UNIT),
+		conversion_compound: "36km / 10s to m/s" => (3600., Unit::BASE_UNIT),
+		conversion_power: "1m^2 to cm^2" => (10000., Unit::BASE_UNIT),
+		conversion_in_let: "let x = 1in to pt; x / 2" => (36., Unit::BASE_UNIT),
+
+		// Vectors
+		vector_construction: "vec2(1, 2)" => (Value::Vec2(DVec2::new(1., 2.)), Unit::BASE_UNIT),
+		vector_addition: "vec2(1, 2) + vec2(3, 4)" => (Value::Vec2(DVec2::new(4., 6.)), Unit::BASE_UNIT),
+		vector_elementwise_multiplication: "vec2(1, 2) * vec2(3, 4)" => (Value::Vec2(DVec2::new(3., 8.)), Unit::BASE_UNIT),
+		vector_scaling: "2 * vec2(1, 2) / 4" => (Value::Vec2(DVec2::new(0.5, 1.)), Unit::BASE_UNIT),
+		vector_negation: "-vec2(1, -2)" => (Value::Vec2(DVec2::new(-1., 2.)), Unit::BASE_UNIT),
+		vector_component: "let v = vec2(3, 4); v.x + v.y" => (7., Unit::BASE_UNIT),
+		vector_component_chain: "[vec2(1, 2), vec2(3, 4)][1].y" => (4., Unit::BASE_UNIT),
+		vector_dot: "dot(vec2(1, 2), vec2(3, 4))" => (11., Unit::BASE_UNIT),
+		vector_cross: "cross(vec2(1, 0), vec2(0, 1))" => (1., Unit::BASE_UNIT),
+		vector_length: "length(vec2(3, 4))" => (5., Unit::BASE_UNIT),
+		vector_distance: "distance(vec2(1, 1), vec2(4, 5))" => (5., Unit::BASE_UNIT),
+		vector_normalize: "normalize(vec2(0, 2))" => (Value::Vec2(DVec2::new(0., 1.)), Unit::BASE_UNIT),
+		vector_equality: "vec2(1, 2) == vec2(1, 2)" => (true, Unit::BASE_UNIT),
+		vector_lerp: "lerp(vec2(0, 0), vec2(10, 20), 0.5)" => (Value::Vec2(DVec2::new(5., 10.)), Unit::BASE_UNIT),
+
+		// Colors
+		color_construction: "rgb(1, 0.5, 0)" => (Value::Color(DVec4::new(1., 0.5, 0., 1.)), Unit::BASE_UNIT),
+		color_scaling: "rgba(1, 0.5, 0, 1) * 0.5" => (Value::Color(DVec4::new(0.5, 0.25, 0., 0.5)), Unit::BASE_UNIT),
+		color_component: "rgba(0.1, 0.2, 0.3, 0.4).b" => (0.3, Unit::BASE_UNIT),
+		color_mix: "lerp(rgb(0, 0, 0), rgb(1, 1, 1), 0.25).r" => (0.25, Unit::BASE_UNIT),
+
+		// Lists
+		list_literal: "[1, 2, 3]" => (Value::List(vec![1.0.into(), 2.0.into(), 3.0.into()]), Unit::BASE_UNIT),
+		list_elementwise: "[1, 2, 3] + [10, 20, 30]" => (Value::List(vec![11.0.into(), 22.0.into(), 33.0.into()]), Unit::BASE_UNIT),
+		list_broadcast: "[1, 2, 3] * 2" => (Value::List(vec![2.0.into(), 4.0.into(), 6.0.into()]), Unit::BASE_UNIT),
+		list_index: "let l = [5, 6, 7]; l[1] + l[2]" => (13., Unit::BASE_UNIT),
+		list_length: "[5, 6, 7].len + len([])" => (3., Unit::BASE_UNIT),
+		list_sum: "sum([1, 2, 3, 4])" => (10., Unit::BASE_UNIT),
+		list_sum_arguments: "sum(1, 2, 3)" => (6., Unit::BASE_UNIT),
+		list_avg: "avg([1, 2, 3, 4])" => (2.5, Unit::BASE_UNIT),
+		list_max: "max([3, 9, 4])" => (9., Unit::BASE_UNIT),
+		list_sum_vectors: "sum([vec2(1, 2), vec2(3, 4)])" => (Value::Vec2(DVec2::new(4., 6.)), Unit::BASE_UNIT),
+		list_sum_units: "sum([1in, 2in]) to in" => (3., Unit::BASE_UNIT),
+	}
+
+	#[test]
+	fn function_wrong_argument_count() {
+		let node = ast::Node::try_parse_from_str("let f(x, y) = x + y; f(1)").unwrap();
+		assert!(matches!(node.eval(&EvalContext::default()), Err(EvalError::WrongArgumentCount { expected: 2, found: 1, .. })));
+	}
+
+	#[test]
+	fn function_unbounded_recursion() {
+		let node = ast::Node::try_parse_from_str("let f(x) = f(x + 1); f(0)").unwrap();
+		assert!(matches!(node.eval(&EvalContext::default()), Err(EvalError::CallDepthExceeded)));
+	}
+
+	#[test]
+	fn program_must_end_with_expression() {
+		assert!(matches!(ast::Node::try_parse_from_str("let x = 1"), Err(ParseError::MissingResult)));
+		assert!(matches!(ast::Node::try_parse_from_str("1; 2"), Err(ParseError::UnusedExpression)));
+	}
+
+	#[test]
+	fn comparison_mismatched_units() {
+		assert!(matches!(evaluate("5m < 3s").unwrap(), Err(EvalError::IncompatibleUnits(Unit::LENGTH, BinaryOp::Lt, Unit::TIME))));
+	}
+
+	#[test]
+	fn incompatible_units() {
+		assert!(matches!(evaluate("1m + 1s").unwrap(), Err(EvalError::IncompatibleUnits(Unit::LENGTH, BinaryOp::Add, Unit::TIME))));
+		assert!(matches!(evaluate("1m + 1").unwrap(), Err(EvalError::IncompatibleUnits(Unit::LENGTH, BinaryOp::Add, Unit::BASE_UNIT))));
+		assert!(matches!(evaluate("2^(1m)").unwrap(), Err(EvalError::IncompatibleUnits(..))));
+		assert!(matches!(evaluate("sqrt(2m)").unwrap(), Err(EvalError::IncompatibleUnit(UnaryOp::Sqrt, Unit::LENGTH))));
+		assert!(matches!(evaluate("let f(x) = x + 1mm; f(2s)").unwrap(), Err(EvalError::IncompatibleUnits(..))));
+	}
+
+	#[test]
+	fn invalid_collections() {
+		assert!(matches!(evaluate("[1, 2] + [1, 2, 3]").unwrap(), Err(EvalError::MismatchedLengths(2, 3))));
+		assert!(matches!(evaluate("[1, 2][2]").unwrap(), Err(EvalError::InvalidIndex(2., 2))));
+		assert!(matches!(evaluate("vec2(1, 2).z").unwrap(), Err(EvalError::MissingComponent(_))));
+		assert!(matches!(evaluate("vec2(1, 2) + rgb(1, 1, 1)").unwrap(), Err(EvalError::UnsupportedBinaryOp(BinaryOp::Add))));
+	}
+
+	#[test]
+	fn incompatible_conversion() {
+		assert!(matches!(evaluate("5in to s").unwrap(), Err(EvalError::IncompatibleConversion(Unit::LENGTH, Unit::TIME))));
+		assert!(matches!(evaluate("5 to mm").unwrap(), Err(EvalError::IncompatibleConversion(Unit::BASE_UNIT, Unit::LENGTH))));
+	}
+
+	#[test]
+	fn design_units() {
+		let value = evaluate("12mm + 3pt").unwrap().unwrap();
+		assert!((value.as_design_real().unwrap() - (12. / 25.4 * 96. + 4.)).abs() < EPSILON);
+
+		let value = evaluate("pi rad / 2").unwrap().unwrap();
+		assert!((value.as_design_real().unwrap() - 90.).abs() < EPSILON);
 	}
 }
@@ -1,8 +1,6 @@
-use crate::ast::{BinaryOp, Literal, Node, UnaryOp, Unit};
-use crate::context::EvalContext;
-use crate::value::{Complex, Number, Value};
+use crate::ast::{BinaryOp, DEGREE, INCH, Literal, Node, PIXEL, UnaryOp, Unit};
+use crate::value::Complex;
 use lazy_static::lazy_static;
-use num_complex::ComplexFloat;
 use pest::Parser;
 use pest::iterators::{Pair, Pairs};
 use pest::pratt_parser::{Assoc, Op, PrattParser};
@@ -17,24 +15,20 @@ struct ExprParser;
 lazy_static! {
 	static ref PRATT_PARSER: PrattParser<Rule> = {
 		PrattParser::new()
+			.op(Op::infix(Rule::or, Assoc::Left))
+			.op(Op::infix(Rule::and, Assoc::Left))
+			.op(Op::infix(Rule::eq, Assoc::Left) | Op::infix(Rule::ne, Assoc::Left))
+			.op(Op::infix(Rule::lt, Assoc::Left) | Op::infix(Rule::le, Assoc::Left) | Op::infix(Rule::gt, Assoc::Left) | Op::infix(Rule::ge, Assoc::Left))
 			.op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::sub, Assoc::Left))
-			.op(Op::infix(Rule::mul, Assoc::Left) | Op::infix(Rule::div, Assoc::Left) | Op::infix(Rule::paren, Assoc::Left))
+			.op(Op::infix(Rule::mul, Assoc::Left) | Op::infix(Rule::div, Assoc::Left) | Op::infix(Rule::modulo, Assoc::Left) | Op::infix(Rule::paren, Assoc::Left))
 			.op(Op::infix(Rule::pow, Assoc::Right))
-			.op(Op::postfix(Rule::fac) | Op::postfix(Rule::EOI))
+			.op(Op::postfix(Rule::fac))
 			.op(Op::prefix(Rule::sqrt))
-			.op(Op::prefix(Rule::neg))
+			.op(Op::prefix(Rule::neg) | Op::prefix(Rule::not))
+			.op(Op::postfix(Rule::member) | Op::postfix(Rule::index))
 	};
 }
 
-#[derive(Error, Debug)]
-pub enum TypeError {
-	#[error("Invalid BinOp: {0:?} {1:?} {2:?}")]
-	InvalidBinaryOp(Unit, BinaryOp, Unit),
-
-	#[error("Invalid UnaryOp: {0:?}")]
-	InvalidUnaryOp(Unit, UnaryOp),
-}
-
 #[derive(Error, Debug)]
 pub enum ParseError {
 	#[error("ParseIntError: {0}")]
@@ -42,39 +36,28 @@ pub enum ParseError {
 	#[error("ParseFloatError: {0}")]
 	ParseFloat(#[from] ParseFloatError),
 
-	#[error("TypeError: {0}")]
-	Type(#[from] TypeError),
-
 	#[error("PestError: {0}")]
 	Pest(#[from] Box<pest::error::Error<Rule>>),
+
+	#[error("The last statement must be an expression giving the result")]
+	MissingResult,
+	#[error("Only the last statement may be an expression, the others must be `let` bindings")]
+	UnusedExpression,
 }
 
 impl Node {
-	pub fn try_parse_from_str(s: &str) -> Result<(Node, Unit), ParseError> {
+	pub fn try_parse_from_str(s: &str) -> Result<Node, ParseError> {
 		let pairs = ExprParser::parse(Rule::program, s).map_err(Box::new)?;
-		let (node, metadata) = parse_expr(pairs)?;
-		Ok((node, metadata.unit))
-	}
-}
-
-struct NodeMetadata {
-	pub unit: Unit,
-}
-
-impl NodeMetadata {
-	pub fn new(unit: Unit) -> Self {
-		Self { unit }
+		let statements = pairs.filter(|pair| pair.as_rule() != Rule::EOI).collect::<Vec<_>>();
+		parse_statements(&statements)
 	}
 }
 
 fn parse_unit(pairs: Pairs<Rule>) -> Result<(Unit, f64), ParseError> {
 	let mut scale = 1.0;
-	let mut length = 0;
-	let mut mass = 0;
-	let mut time = 0;
+	let mut unit = Unit::BASE_UNIT;
 
 	for pair in pairs {
-		println!("found rule: {:?}", pair.as_rule());
 		match pair.as_rule() {
 			Rule::nano => scale *= 1e-9,
 			Rule::micro => scale *= 1e-6,
@@ -88,15 +71,45 @@ fn parse_unit(pairs: Pairs<Rule>) -> Result<(Unit, f64), ParseError> {
 			Rule::giga => scale *= 1e9,
 			Rule::tera => scale *= 1e12,
 
-			Rule::meter => length = 1,
-			Rule::gram => mass = 1,
-			Rule::second => time = 1,
+			Rule::meter => unit = Unit::LENGTH,
+			Rule::gram => unit = Unit::MASS,
+			Rule::second => unit = Unit::TIME,
+			Rule::radian => unit = Unit::ANGLE,
+
+			Rule::pixel => (unit, scale) = (Unit::LENGTH, scale * PIXEL),
+			Rule::point => (unit, scale) = (Unit::LENGTH, scale * INCH / 72.),
+			Rule::inch => (unit, scale) = (Unit::LENGTH, scale * INCH),
+			Rule::degree => (unit, scale) = (Unit::ANGLE, scale * DEGREE),
 
 			_ => unreachable!(), // All possible rules should be covered
 		}
 	}
 
-	Ok((Unit { length, mass, time }, scale))
+	Ok((unit, scale))
+}
+
+/// Parses the target of a conversion, which is a product of units raised to integer powers.
+fn parse_conversion(pairs: Pairs<Rule>) -> Result<(Unit, f64), ParseError> {
+	let mut target = (Unit::BASE_UNIT, 1.);
+	let mut divide = false;
+
+	for pair in pairs {
+		match pair.as_rule() {
+			Rule::to_kw | Rule::mul => divide = false,
+			Rule::div => divide = true,
+			Rule::unit_power => {
+				let mut pairs = pair.into_inner();
+				let (unit, scale) = parse_unit(pairs.next().expect("unit_power always has a unit").into_inner())?;
+				let exponent = pairs.next().map(|exponent| exponent.as_str().parse::<i32>()).transpose()?.unwrap_or(1);
+				let exponent = if divide { -exponent } else { exponent };
+
+				target = (target.0 * unit.pow(exponent), target.1 * scale.powi(exponent));
+			}
+			rule => unreachable!("unexpected rule: {:?}", rule),
+		}
+	}
+
+	Ok(target)
 }
 
 fn parse_const(pair: Pair<Rule>) -> Literal {
@@ -111,20 +124,14 @@ fn parse_const(pair: Pair<Rule>) -> Literal {
 	}
 }
 
-fn parse_lit(mut pairs: Pairs<Rule>) -> Result<(Literal, Unit), ParseError> {
-	let literal = match pairs.next() {
+fn parse_lit(mut pairs: Pairs<Rule>) -> Result<Literal, ParseError> {
+	let value = match pairs.next() {
 		Some(lit) => match lit.as_rule() {
-			Rule::int => {
-				let value = lit.as_str().parse::<i32>()? as f64;
-				Literal::Float(value)
-			}
-			Rule::float => {
-				let value = lit.as_str().parse::<f64>()?;
-				Literal::Float(value)
-			}
+			Rule::int => lit.as_str().parse::<i32>()? as f64,
+			Rule::float => lit.as_str().parse::<f64>()?,
 			Rule::unit => {
 				let (unit, scale) = parse_unit(lit.into_inner())?;
-				return Ok((Literal::Float(scale), unit));
+				return Ok(Literal::Quantity(scale, unit));
 			}
 			rule => unreachable!("unexpected rule: {:?}", rule),
 		},
@@ -135,182 +142,167 @@ fn parse_lit(mut pairs: Pairs<Rule>) -> Result<(Literal, Unit), ParseError> {
 		let unit_pairs = unit_pair.into_inner(); // Get the inner pairs for the unit
 		let (unit, scale) = parse_unit(unit_pairs)?;
 
-		println!("found unit: {:?}", unit);
-
-		Ok((
-			match literal {
-				Literal::Float(num) => Literal::Float(num * scale),
-				Literal::Complex(num) => Literal::Complex(num * scale),
-			},
-			unit,
-		))
+		Ok(Literal::Quantity(value * scale, unit))
 	} else {
-		Ok((literal, Unit::BASE_UNIT))
+		Ok(Literal::Float(value))
 	}
 }
 
-fn parse_expr(pairs: Pairs<Rule>) -> Result<(Node, NodeMetadata), ParseError> {
+/// Parses a program's statements, where every `let` binding wraps the statements following it.
+fn parse_statements(statements: &[Pair<Rule>]) -> Result<Node, ParseError> {
+	let Some((statement, rest)) = statements.split_first() else {
+		return Err(ParseError::MissingResult);
+	};
+
+	match statement.as_rule() {
+		Rule::expr if rest.is_empty() => parse_expr(statement.clone()),
+		Rule::expr => Err(ParseError::UnusedExpression),
+		Rule::let_stmt => {
+			let mut pairs = statement.clone().into_inner().skip(1);
+			let name = pairs.next().expect("let_stmt always has a name").as_str().to_string();
+			let value = parse_expr(pairs.next().expect("let_stmt always has a value"))?;
+
+			Ok(Node::Let {
+				name,
+				value: Box::new(value),
+				body: Box::new(parse_statements(rest)?),
+			})
+		}
+		Rule::fn_def => {
+			let mut pairs = statement.clone().into_inner().skip(1);
+			let name = pairs.next().expect("fn_def always has a name").as_str().to_string();
+			let params = pairs
+				.next()
+				.expect("fn_def always has parameters")
+				.into_inner()
+				.map(|param| param.as_str().to_string())
+				.collect::<Vec<_>>();
+			let expr = parse_expr(pairs.next().expect("fn_def always has an expression"))?;
+
+			Ok(Node::FnDef {
+				name,
+				params,
+				expr: Box::new(expr),
+				body: Box::new(parse_statements(rest)?),
+			})
+		}
+		rule => unreachable!("unexpected rule: {:?}", rule),
+	}
+}
+
+/// Parses an `expr` rule, which is an operation optionally followed by the branches of a conditional or a unit conversion.
+fn parse_expr(pair: Pair<Rule>) -> Result<Node, ParseError> {
+	let mut pairs = pair.into_inner();
+	let operation = parse_operation(pairs.next().expect("expr always has an operation").into_inner())?;
+
+	match (pairs.next(), pairs.next()) {
+		(Some(if_true), Some(if_false)) => Ok(Node::Conditional {
+			condition: Box::new(operation),
+			if_true: Box::new(parse_expr(if_true)?),
+			if_false: Box::new(parse_expr(if_false)?),
+		}),
+		(Some(conversion), None) => {
+			let (unit, scale) = parse_conversion(conversion.into_inner())?;
+			Ok(Node::Convert {
+				expr: Box::new(operation),
+				unit,
+				scale,
+			})
+		}
+		_ => Ok(operation),
+	}
+}
+
+fn parse_operation(pairs: Pairs<Rule>) -> Result<Node, ParseError> {
 	PRATT_PARSER
 		.map_primary(|primary| {
 			Ok(match primary.as_rule() {
-				Rule::lit => {
-					let (lit, unit) = parse_lit(primary.into_inner())?;
+				Rule::lit => Node::Lit(parse_lit(primary.into_inner())?),
+				Rule::list => Node::List(primary.into_inner().map(parse_expr).collect::<Result<_, _>>()?),
+				Rule::bool_lit => {
+					let value = primary.into_inner().next().expect("bool_lit should have 1 child").as_rule() == Rule::true_lit;
 
-					(Node::Lit(lit), NodeMetadata { unit })
+					Node::Lit(Literal::Bool(value))
 				}
 				Rule::fn_call => {
 					let mut pairs = primary.into_inner();
-					let name = pairs.next().expect("fn_call always has 2 children").as_str().to_string();
-
-					(
-						Node::FnCall {
-							name,
-							expr: pairs.map(|p| parse_expr(p.into_inner()).map(|expr| expr.0)).collect::<Result<Vec<Node>, ParseError>>()?,
-						},
-						NodeMetadata::new(Unit::BASE_UNIT),
-					)
-				}
-				Rule::constant => {
-					let lit = parse_const(primary.into_inner().next().expect("constant should have atleast 1 child"));
-
-					(Node::Lit(lit), NodeMetadata::new(Unit::BASE_UNIT))
-				}
-				Rule::ident => {
-					let name = primary.as_str().to_string();
+					let name = pairs.next().expect("fn_call always has a name").as_str().to_string();
+					let mut args = pairs.map(parse_expr).collect::<Result<Vec<_>, ParseError>>()?;
+
+					// `if(condition, if_true, if_false)` is an alternative syntax for the conditional operator, which only evaluates the chosen branch
+					if name == "if" && args.len() == 3 {
+						let (if_false, if_true, condition) = (args.pop().unwrap(), args.pop().unwrap(), args.pop().unwrap());
+						return Ok(Node::Conditional {
+							condition: Box::new(condition),
+							if_true: Box::new(if_true),
+							if_false: Box::new(if_false),
+						});
+					}
 
-					(Node::Var(name), NodeMetadata::new(Unit::BASE_UNIT))
-				}
-				Rule::expr => parse_expr(primary.into_inner())?,
-				Rule::float => {
-					let value = primary.as_str().parse::<f64>()?;
-					(Node::Lit(Literal::Float(value)), NodeMetadata::new(Unit::BASE_UNIT))
+					Node::FnCall { name, expr: args }
 				}
+				Rule::constant => Node::Lit(parse_const(primary.into_inner().next().expect("constant should have atleast 1 child"))),
+				Rule::ident => Node::Var(primary.as_str().to_string()),
+				Rule::expr => parse_expr(primary)?,
+				Rule::float => Node::Lit(Literal::Float(primary.as_str().parse::<f64>()?)),
 				rule => unreachable!("unexpected rule: {:?}", rule),
 			})
 		})
 		.map_prefix(|op, rhs| {
-			let (rhs, rhs_metadata) = rhs?;
 			let op = match op.as_rule() {
 				Rule::neg => UnaryOp::Neg,
+				Rule::not => UnaryOp::Not,
 				Rule::sqrt => UnaryOp::Sqrt,
 
 				rule => unreachable!("unexpected rule: {:?}", rule),
 			};
 
-			let node = Node::UnaryOp { expr: Box::new(rhs), op };
-			let unit = rhs_metadata.unit;
-
-			let unit = if !unit.is_base() {
-				match op {
-					UnaryOp::Sqrt if unit.length % 2 == 0 && unit.mass % 2 == 0 && unit.time % 2 == 0 => Unit {
-						length: unit.length / 2,
-						mass: unit.mass / 2,
-						time: unit.time / 2,
-					},
-					UnaryOp::Neg => unit,
-					op => return Err(ParseError::Type(TypeError::InvalidUnaryOp(unit, op))),
-				}
-			} else {
-				Unit::BASE_UNIT
-			};
-
-			Ok((node, NodeMetadata::new(unit)))
+			Ok(Node::UnaryOp { expr: Box::new(rhs?), op })
 		})
 		.map_postfix(|lhs, op| {
-			let (lhs_node, lhs_metadata) = lhs?;
-
-			let op = match op.as_rule() {
-				Rule::EOI => return Ok((lhs_node, lhs_metadata)),
-				Rule::fac => UnaryOp::Fac,
+			let expr = Box::new(lhs?);
+			match op.as_rule() {
+				Rule::fac => Ok(Node::UnaryOp { expr, op: UnaryOp::Fac }),
+				Rule::member => {
+					let name = op.into_inner().next().expect("member always has a name").as_str().to_string();
+					Ok(Node::Member { expr, name })
+				}
+				Rule::index => {
+					let index = parse_expr(op.into_inner().next().expect("index always has an expression"))?;
+					Ok(Node::Index { expr, index: Box::new(index) })
+				}
 				rule => unreachable!("unexpected rule: {:?}", rule),
-			};
-
-			if !lhs_metadata.unit.is_base() {
-				return Err(ParseError::Type(TypeError::InvalidUnaryOp(lhs_metadata.unit, op)));
 			}
-
-			Ok((Node::UnaryOp { expr: Box::new(lhs_node), op }, lhs_metadata))
 		})
 		.map_infix(|lhs, op, rhs| {
-			let (lhs, lhs_metadata) = lhs?;
-			let (rhs, rhs_metadata) = rhs?;
-
 			let op = match op.as_rule() {
 				Rule::add => BinaryOp::Add,
 				Rule::sub => BinaryOp::Sub,
 				Rule::mul => BinaryOp::Mul,
 				Rule::div => BinaryOp::Div,
+				Rule::modulo => BinaryOp::Mod,
 				Rule::pow => BinaryOp::Pow,
 				Rule::paren => BinaryOp::Mul,
+				Rule::eq => BinaryOp::Eq,
+				Rule::ne => BinaryOp::Ne,
+				Rule::lt => BinaryOp::Lt,
+				Rule::le => BinaryOp::Le,
+				Rule::gt => BinaryOp::Gt,
+				Rule::ge => BinaryOp::Ge,
+				Rule::and => BinaryOp::And,
+				Rule::or => BinaryOp::Or,
 				rule => unreachable!("unexpected rule: {:?}", rule),
 			};
 
-			let (lhs_unit, rhs_unit) = (lhs_metadata.unit, rhs_metadata.unit);
-
-			let unit = match (!lhs_unit.is_base(), !rhs_unit.is_base()) {
-				(true, true) => match op {
-					BinaryOp::Mul => Unit {
-						length: lhs_unit.length + rhs_unit.length,
-						mass: lhs_unit.mass + rhs_unit.mass,
-						time: lhs_unit.time + rhs_unit.time,
-					},
-					BinaryOp::Div => Unit {
-						length: lhs_unit.length - rhs_unit.length,
-						mass: lhs_unit.mass - rhs_unit.mass,
-						time: lhs_unit.time - rhs_unit.time,
-					},
-					BinaryOp::Add | BinaryOp::Sub => {
-						if lhs_unit == rhs_unit {
-							lhs_unit
-						} else {
-							return Err(ParseError::Type(TypeError::InvalidBinaryOp(lhs_unit, op, rhs_unit)));
-						}
-					}
-					BinaryOp::Pow => {
-						return Err(ParseError::Type(TypeError::InvalidBinaryOp(lhs_unit, op, rhs_unit)));
-					}
-				},
-
-				(true, false) => match op {
-					BinaryOp::Add | BinaryOp::Sub => return Err(ParseError::Type(TypeError::InvalidBinaryOp(lhs_unit, op, Unit::BASE_UNIT))),
-					BinaryOp::Pow => {
-						//TODO: improve error type
-						//TODO: support 1 / int
-						if let Ok(Value::Number(Number::Real(val))) = rhs.eval(&EvalContext::default()) {
-							if (val - val as i32 as f64).abs() <= f64::EPSILON {
-								Unit {
-									length: lhs_unit.length * val as i32,
-									mass: lhs_unit.mass * val as i32,
-									time: lhs_unit.time * val as i32,
-								}
-							} else {
-								return Err(ParseError::Type(TypeError::InvalidBinaryOp(lhs_unit, op, Unit::BASE_UNIT)));
-							}
-						} else {
-							return Err(ParseError::Type(TypeError::InvalidBinaryOp(lhs_unit, op, Unit::BASE_UNIT)));
-						}
-					}
-					_ => lhs_unit,
-				},
-				(false, true) => match op {
-					BinaryOp::Add | BinaryOp::Sub | BinaryOp::Pow => return Err(ParseError::Type(TypeError::InvalidBinaryOp(Unit::BASE_UNIT, op, rhs_unit))),
-					_ => rhs_unit,
-				},
-				(false, false) => Unit::BASE_UNIT,
-			};
-
-			let node = Node::BinOp {
-				lhs: Box::new(lhs),
+			Ok(Node::BinOp {
+				lhs: Box::new(lhs?),
 				op,
-				rhs: Box::new(rhs),
-			};
-
-			Ok((node, NodeMetadata::new(unit)))
+				rhs: Box::new(rhs?),
+			})
 		})
 		.parse(pairs)
 }
 
-//TODO: set up Unit test for Units
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -320,7 +312,7 @@ mod tests {
 				#[test]
 				fn $name() {
 					let result = Node::try_parse_from_str($input).unwrap();
-					assert_eq!(result.0, $expected);
+					assert_eq!(result, $expected);
 				}
 			)*
 		};
@@ -1,12 +1,20 @@
-use crate::ast::{BinaryOp, UnaryOp};
+use crate::ast::{BinaryOp, DEGREE, Literal, PIXEL, UnaryOp, Unit};
+use crate::executer::EvalError;
+use glam::{DVec2, DVec4};
 use num_complex::ComplexFloat;
 use std::f64::consts::PI;
 
 pub type Complex = num_complex::Complex<f64>;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Value {
 	Number(Number),
+	Bool(bool),
+	Quantity(Quantity),
+	Vec2(DVec2),
+	/// The red, green, blue and alpha channels of a color
+	Color(DVec4),
+	List(Vec<Value>),
 }
 
 impl Value {
@@ -14,12 +22,195 @@ impl Value {
 		Self::Number(Number::Real(x))
 	}
 
+	/// Creates a value with the given unit, which is a plain number if the unit is dimensionless.
+	pub fn from_quantity(value: f64, unit: Unit) -> Self {
+		if unit.is_base() { Self::from_f64(value) } else { Self::Quantity(Quantity { value, unit }) }
+	}
+
 	pub fn as_real(&self) -> Option<f64> {
 		match self {
 			Self::Number(Number::Real(val)) => Some(*val),
 			_ => None,
 		}
 	}
+
+	pub fn as_bool(&self) -> Option<bool> {
+		match self {
+			Self::Bool(val) => Some(*val),
+			_ => None,
+		}
+	}
+
+	/// The real magnitude in base units along with its unit, where plain numbers are dimensionless.
+	pub fn as_quantity(&self) -> Option<(f64, Unit)> {
+		match self {
+			Self::Number(Number::Real(val)) => Some((*val, Unit::BASE_UNIT)),
+			Self::Quantity(quantity) => Some((quantity.value, quantity.unit)),
+			_ => None,
+		}
+	}
+
+	/// The real magnitude measured in the units used by the editor, which are pixels for lengths and degrees for angles.
+	pub fn as_design_real(&self) -> Option<f64> {
+		let (value, unit) = self.as_quantity()?;
+		Some(value / PIXEL.powi(unit.length) / DEGREE.powi(unit.angle))
+	}
+
+	pub fn unit(&self) -> Unit {
+		match self {
+			Self::Quantity(quantity) => quantity.unit,
+			_ => Unit::BASE_UNIT,
+		}
+	}
+
+	/// Converts a real value into the unit which is `scale` times `unit` in base units, giving its magnitude in that unit.
+	pub fn convert(&self, unit: Unit, scale: f64) -> Result<Value, EvalError> {
+		let (value, found) = self.as_quantity().ok_or(EvalError::UnsupportedConversion)?;
+		if found != unit {
+			return Err(EvalError::IncompatibleConversion(found, unit));
+		}
+		Ok(Value::from_f64(value / scale))
+	}
+
+	/// Looks up a named component, such as `x` of a vector or `r` of a color.
+	pub fn component(&self, name: &str) -> Result<Value, EvalError> {
+		let component = match (self, name) {
+			(Value::Vec2(vector), "x") => vector.x,
+			(Value::Vec2(vector), "y") => vector.y,
+			(Value::Color(color), "r") => color.x,
+			(Value::Color(color), "g") => color.y,
+			(Value::Color(color), "b") => color.z,
+			(Value::Color(color), "a") => color.w,
+			(Value::List(list), "len") => list.len() as f64,
+			_ => return Err(EvalError::MissingComponent(name.to_string())),
+		};
+		Ok(Value::from_f64(component))
+	}
+
+	/// Looks up an element of a list by its zero-based index.
+	pub fn index(&self, index: &Value) -> Result<Value, EvalError> {
+		let (Value::List(list), Some(index)) = (self, index.as_real()) else {
+			return Err(EvalError::TypeError);
+		};
+
+		let valid = index >= 0. && index.fract() == 0. && (index as usize) < list.len();
+		if !valid {
+			return Err(EvalError::InvalidIndex(index, list.len()));
+		}
+		Ok(list[index as usize].clone())
+	}
+
+	/// Applies a binary operation, failing if it isn't defined for the operand types or units.
+	pub fn binary_op(self, op: BinaryOp, other: Value) -> Result<Value, EvalError> {
+		match (self, other) {
+			// Operations on lists apply to each pair of elements, or to each element along with a single value
+			(Value::List(lhs), Value::List(rhs)) => {
+				if lhs.len() != rhs.len() {
+					return Err(EvalError::MismatchedLengths(lhs.len(), rhs.len()));
+				}
+				lhs.into_iter().zip(rhs).map(|(lhs, rhs)| lhs.binary_op(op, rhs)).collect::<Result<_, _>>().map(Value::List)
+			}
+			(Value::List(list), other) => list.into_iter().map(|lhs| lhs.binary_op(op, other.clone())).collect::<Result<_, _>>().map(Value::List),
+			(other, Value::List(list)) => list.into_iter().map(|rhs| other.clone().binary_op(op, rhs)).collect::<Result<_, _>>().map(Value::List),
+
+			(Value::Vec2(lhs), Value::Vec2(rhs)) => vector_op(lhs.to_array(), op, rhs.to_array(), |vector| Value::Vec2(DVec2::from_array(vector))),
+			(Value::Vec2(lhs), Value::Number(Number::Real(rhs))) => vector_op(lhs.to_array(), op, [rhs; 2], |vector| Value::Vec2(DVec2::from_array(vector))),
+			(Value::Number(Number::Real(lhs)), Value::Vec2(rhs)) => vector_op([lhs; 2], op, rhs.to_array(), |vector| Value::Vec2(DVec2::from_array(vector))),
+			(Value::Color(lhs), Value::Color(rhs)) => vector_op(lhs.to_array(), op, rhs.to_array(), |color| Value::Color(DVec4::from_array(color))),
+			(Value::Color(lhs), Value::Number(Number::Real(rhs))) => vector_op(lhs.to_array(), op, [rhs; 4], |color| Value::Color(DVec4::from_array(color))),
+			(Value::Number(Number::Real(lhs)), Value::Color(rhs)) => vector_op([lhs; 4], op, rhs.to_array(), |color| Value::Color(DVec4::from_array(color))),
+
+			(Value::Number(lhs), Value::Number(rhs)) if op.is_comparison() => lhs.compare(op, rhs).map(Value::Bool).ok_or(EvalError::UnsupportedBinaryOp(op)),
+			(Value::Number(lhs), Value::Number(rhs)) if !op.is_logical() => Ok(Value::Number(lhs.binary_op(op, rhs))),
+			(Value::Bool(lhs), Value::Bool(rhs)) => match op {
+				BinaryOp::And => Ok(Value::Bool(lhs && rhs)),
+				BinaryOp::Or => Ok(Value::Bool(lhs || rhs)),
+				BinaryOp::Eq => Ok(Value::Bool(lhs == rhs)),
+				BinaryOp::Ne => Ok(Value::Bool(lhs != rhs)),
+				_ => Err(EvalError::UnsupportedBinaryOp(op)),
+			},
+			(lhs @ Value::Quantity(_), rhs) | (lhs, rhs @ Value::Quantity(_)) => Quantity::binary_op(lhs, op, rhs),
+			_ => Err(EvalError::UnsupportedBinaryOp(op)),
+		}
+	}
+
+	/// Applies a unary operation, failing if it isn't defined for the operand type or unit.
+	pub fn unary_op(self, op: UnaryOp) -> Result<Value, EvalError> {
+		match (self, op) {
+			(Value::Bool(val), UnaryOp::Not) => Ok(Value::Bool(!val)),
+			(Value::Number(_), UnaryOp::Not) | (Value::Bool(_), _) => Err(EvalError::UnsupportedUnaryOp(op)),
+			(Value::Number(num), op) => Ok(Value::Number(num.unary_op(op))),
+			(Value::Quantity(quantity), op) => quantity.unary_op(op),
+			(Value::List(list), op) => list.into_iter().map(|value| value.unary_op(op)).collect::<Result<_, _>>().map(Value::List),
+			(Value::Vec2(vector), UnaryOp::Neg) => Ok(Value::Vec2(-vector)),
+			(Value::Vec2(vector), UnaryOp::Sqrt) => Ok(Value::Vec2(vector.map(f64::sqrt))),
+			(Value::Color(color), UnaryOp::Neg) => Ok(Value::Color(-color)),
+			(Value::Color(color), UnaryOp::Sqrt) => Ok(Value::Color(color.map(f64::sqrt))),
+			(Value::Vec2(_) | Value::Color(_), _) => Err(EvalError::UnsupportedUnaryOp(op)),
+		}
+	}
+}
+
+/// Applies an arithmetic operation to each pair of components of two vectors, while equality compares the vectors as a whole.
+fn vector_op<const N: usize>(lhs: [f64; N], op: BinaryOp, rhs: [f64; N], to_value: impl Fn([f64; N]) -> Value) -> Result<Value, EvalError> {
+	match op {
+		BinaryOp::Eq => Ok(Value::Bool(lhs == rhs)),
+		BinaryOp::Ne => Ok(Value::Bool(lhs != rhs)),
+		BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod | BinaryOp::Pow => {
+			Ok(to_value(std::array::from_fn(|i| match Number::Real(lhs[i]).binary_op(op, Number::Real(rhs[i])) {
+				Number::Real(component) => component,
+				Number::Complex(_) => unreachable!("operations on real numbers give real numbers"),
+			})))
+		}
+		_ => Err(EvalError::UnsupportedBinaryOp(op)),
+	}
+}
+
+/// A real number with a unit, measured in the base unit of each dimension (meters, grams, seconds and radians).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Quantity {
+	pub value: f64,
+	pub unit: Unit,
+}
+
+impl Quantity {
+	/// Applies a binary operation where at least one side has a unit, checking that the units are compatible.
+	fn binary_op(lhs: Value, op: BinaryOp, rhs: Value) -> Result<Value, EvalError> {
+		let ((lhs, lhs_unit), (rhs, rhs_unit)) = lhs.as_quantity().zip(rhs.as_quantity()).ok_or(EvalError::UnsupportedBinaryOp(op))?;
+		let incompatible = EvalError::IncompatibleUnits(lhs_unit, op, rhs_unit);
+
+		match op {
+			BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mod | BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge if lhs_unit != rhs_unit => Err(incompatible),
+			BinaryOp::Add => Ok(Value::from_quantity(lhs + rhs, lhs_unit)),
+			BinaryOp::Sub => Ok(Value::from_quantity(lhs - rhs, lhs_unit)),
+			BinaryOp::Mod => Ok(Value::from_quantity(lhs.rem_euclid(rhs), lhs_unit)),
+			BinaryOp::Mul => Ok(Value::from_quantity(lhs * rhs, lhs_unit * rhs_unit)),
+			BinaryOp::Div => Ok(Value::from_quantity(lhs / rhs, lhs_unit / rhs_unit)),
+			// Units can only be raised to dimensionless integer powers
+			BinaryOp::Pow if rhs_unit.is_base() && rhs.fract() == 0. => Ok(Value::from_quantity(lhs.powf(rhs), lhs_unit.pow(rhs as i32))),
+			BinaryOp::Pow => Err(incompatible),
+			op if op.is_comparison() => Number::Real(lhs).compare(op, Number::Real(rhs)).map(Value::Bool).ok_or(EvalError::UnsupportedBinaryOp(op)),
+			_ => Err(EvalError::UnsupportedBinaryOp(op)),
+		}
+	}
+
+	fn unary_op(self, op: UnaryOp) -> Result<Value, EvalError> {
+		match op {
+			UnaryOp::Neg => Ok(Value::Quantity(Quantity { value: -self.value, ..self })),
+			UnaryOp::Sqrt => match self.unit.sqrt() {
+				Some(unit) => Ok(Value::from_quantity(self.value.sqrt(), unit)),
+				None => Err(EvalError::IncompatibleUnit(op, self.unit)),
+			},
+			UnaryOp::Fac => Err(EvalError::IncompatibleUnit(op, self.unit)),
+			UnaryOp::Not => Err(EvalError::UnsupportedUnaryOp(op)),
+		}
+	}
+}
+
+impl std::fmt::Display for Quantity {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} {}", self.value, self.unit)
+	}
 }
 
 impl From<f64> for Value {
@@ -28,10 +219,41 @@ impl From<f64> for Value {
 	}
 }
 
+impl From<&Literal> for Value {
+	fn from(literal: &Literal) -> Self {
+		match literal {
+			Literal::Float(num) => Self::from_f64(*num),
+			Literal::Complex(num) => Self::Number(Number::Complex(*num)),
+			Literal::Bool(val) => Self::Bool(*val),
+			Literal::Quantity(value, unit) => Self::from_quantity(*value, *unit),
+		}
+	}
+}
+
+impl From<bool> for Value {
+	fn from(x: bool) -> Self {
+		Self::Bool(x)
+	}
+}
+
 impl core::fmt::Display for Value {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
 			Value::Number(num) => num.fmt(f),
+			Value::Bool(val) => val.fmt(f),
+			Value::Quantity(quantity) => quantity.fmt(f),
+			Value::Vec2(vector) => write!(f, "vec2({}, {})", vector.x, vector.y),
+			Value::Color(color) => write!(f, "rgba({}, {}, {}, {})", color.x, color.y, color.z, color.w),
+			Value::List(list) => {
+				write!(f, "[")?;
+				for (i, value) in list.iter().enumerate() {
+					if i > 0 {
+						write!(f, ", ")?;
+					}
+					value.fmt(f)?;
+				}
+				write!(f, "]")
+			}
 		}
 	}
 }
@@ -60,7 +282,9 @@ impl Number {
 					BinaryOp::Sub => lhs - rhs,
 					BinaryOp::Mul => lhs * rhs,
 					BinaryOp::Div => lhs / rhs,
+					BinaryOp::Mod => lhs.rem_euclid(rhs),
 					BinaryOp::Pow => lhs.powf(rhs),
+					_ => f64::NAN,
 				};
 				Number::Real(result)
 			}
@@ -71,7 +295,9 @@ impl Number {
 					BinaryOp::Sub => lhs - rhs,
 					BinaryOp::Mul => lhs * rhs,
 					BinaryOp::Div => lhs / rhs,
+					BinaryOp::Mod => lhs % rhs,
 					BinaryOp::Pow => lhs.powc(rhs),
+					_ => Complex::new(f64::NAN, f64::NAN),
 				};
 				Number::Complex(result)
 			}
@@ -83,7 +309,9 @@ impl Number {
 					BinaryOp::Sub => lhs_complex - rhs,
 					BinaryOp::Mul => lhs_complex * rhs,
 					BinaryOp::Div => lhs_complex / rhs,
+					BinaryOp::Mod => lhs_complex % rhs,
 					BinaryOp::Pow => lhs_complex.powc(rhs),
+					_ => Complex::new(f64::NAN, f64::NAN),
 				};
 				Number::Complex(result)
 			}
@@ -95,7 +323,9 @@ impl Number {
 					BinaryOp::Sub => lhs - rhs_complex,
 					BinaryOp::Mul => lhs * rhs_complex,
 					BinaryOp::Div => lhs / rhs_complex,
+					BinaryOp::Mod => lhs % rhs_complex,
 					BinaryOp::Pow => lhs.powf(rhs),
+					_ => Complex::new(f64::NAN, f64::NAN),
 				};
 				Number::Complex(result)
 			}
@@ -108,19 +338,95 @@ impl Number {
 				UnaryOp::Neg => Number::Real(-real),
 				UnaryOp::Sqrt => Number::Real(real.sqrt()),
 
-				UnaryOp::Fac => todo!("Implement factorial"),
+				UnaryOp::Fac => Number::Real(factorial(Complex::new(real, 0.)).re),
+				UnaryOp::Not => Number::Real(f64::NAN),
 			},
 
 			Number::Complex(complex) => match op {
 				UnaryOp::Neg => Number::Complex(-complex),
 				UnaryOp::Sqrt => Number::Complex(complex.sqrt()),
 
-				UnaryOp::Fac => todo!("Implement factorial"),
+				UnaryOp::Fac => Number::Complex(factorial(complex)),
+				UnaryOp::Not => Number::Complex(Complex::new(f64::NAN, f64::NAN)),
 			},
 		}
 	}
 
+	/// Compares two numbers, where complex numbers only support equality.
+	pub fn compare(self, op: BinaryOp, other: Number) -> Option<bool> {
+		let (lhs, rhs) = match (self, other) {
+			(Number::Real(lhs), Number::Real(rhs)) => (lhs, rhs),
+			(lhs, rhs) => {
+				let (lhs, rhs) = (lhs.to_complex(), rhs.to_complex());
+				return match op {
+					BinaryOp::Eq => Some(lhs == rhs),
+					BinaryOp::Ne => Some(lhs != rhs),
+					_ => None,
+				};
+			}
+		};
+
+		match op {
+			BinaryOp::Eq => Some(lhs == rhs),
+			BinaryOp::Ne => Some(lhs != rhs),
+			BinaryOp::Lt => Some(lhs < rhs),
+			BinaryOp::Le => Some(lhs <= rhs),
+			BinaryOp::Gt => Some(lhs > rhs),
+			BinaryOp::Ge => Some(lhs >= rhs),
+			_ => None,
+		}
+	}
+
+	pub fn to_complex(self) -> Complex {
+		match self {
+			Number::Real(real) => Complex::new(real, 0.),
+			Number::Complex(complex) => complex,
+		}
+	}
+
 	pub fn from_f64(x: f64) -> Self {
 		Self::Real(x)
 	}
 }
+
+/// The factorial `n!`, extended to non-integer and complex numbers as `Γ(n + 1)`. It is undefined (NaN) for negative integers.
+fn factorial(n: Complex) -> Complex {
+	if n.im != 0. || n.re.fract() != 0. {
+		return gamma(n + 1.);
+	}
+	if n.re < 0. {
+		return Complex::new(f64::NAN, 0.);
+	}
+
+	// Anything above 170! overflows, so the loop stops there rather than counting up to huge numbers
+	let product = (2..=n.re.min(171.) as u32).fold(1., |product, factor| product * factor as f64);
+	Complex::new(product, 0.)
+}
+
+/// The gamma function using the Lanczos approximation, with the reflection formula for the left half of the complex plane.
+fn gamma(z: Complex) -> Complex {
+	const G: f64 = 7.;
+	const COEFFICIENTS: [f64; 9] = [
+		0.999_999_999_999_809_9,
+		676.520_368_121_885_1,
+		-1_259.139_216_722_402_8,
+		771.323_428_777_653_1,
+		-176.615_029_162_140_6,
+		12.507_343_278_686_905,
+		-0.138_571_095_265_720_12,
+		9.984_369_578_019_572e-6,
+		1.505_632_735_149_311_6e-7,
+	];
+
+	if z.re < 0.5 {
+		return PI / ((PI * z).sin() * gamma(1. - z));
+	}
+
+	let z = z - 1.;
+	let series = COEFFICIENTS[1..]
+		.iter()
+		.enumerate()
+		.fold(Complex::new(COEFFICIENTS[0], 0.), |sum, (index, &coefficient)| sum + coefficient / (z + (index + 1) as f64));
+	let t = z + G + 0.5;
+	(2. * PI).sqrt() * t.powc(z + 0.5) * (-t).exp() * series
+}
@@ -0,0 +1,54 @@
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use path_bool::*;
+
+/// Creates a grid of overlapping circles, similar to a group of many layers being combined.
+fn circles(count: usize) -> Vec<Vec<PathSegment>> {
+	let columns = (count as f64).sqrt().ceil() as usize;
+	(0..count)
+		.map(|index| {
+			let (x, y) = ((index % columns) as f64 * 15., (index / columns) as f64 * 15.);
+			let radius = 10. + (index % 7) as f64;
+			// Four cubic segments approximating a circle, which is how circles reach path-bool from vector data
+			let k = radius * 0.552284749831;
+			let (left, right, top, bottom) = (x - radius, x + radius, y - radius, y + radius);
+			path_from_path_data(&format!(
+				"M {right} {y} C {right} {} {} {bottom} {x} {bottom} C {} {bottom} {left} {} {left} {y} C {left} {} {} {top} {x} {top} C {} {top} {right} {} {right} {y} Z",
+				y + k,
+				x + k,
+				x - k,
+				y + k,
+				y - k,
+				x - k,
+				x + k,
+				y - k,
+			))
+			.unwrap()
+		})
+		.collect()
+}
+
+fn fold(paths: &[Vec<PathSegment>], op: PathBooleanOperation) -> Vec<PathSegment> {
+	let mut paths = paths.iter();
+	let first = paths.next().cloned().unwrap_or_default();
+	paths.fold(first, |result, path| path_boolean(&result, FillRule::NonZero, path, FillRule::NonZero, op).unwrap().concat())
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+	let mut group = c.benchmark_group("many_shapes_union");
+	group.sample_size(10);
+
+	for count in [10, 100, 300] {
+		let paths = circles(count);
+		let inputs: Vec<_> = paths.iter().map(|path| (path, FillRule::NonZero)).collect();
+
+		group.bench_with_input(BenchmarkId::new("multiple", count), &inputs, |b, inputs| {
+			b.iter(|| path_boolean_multiple(black_box(inputs), PathBooleanOperation::Union))
+		});
+		group.bench_with_input(BenchmarkId::new("pairwise", count), &paths, |b, paths| b.iter(|| fold(black_box(paths), PathBooleanOperation::Union)));
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
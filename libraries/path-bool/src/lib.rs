@@ -9,10 +9,12 @@ mod parsing {
 mod util {
 	pub(crate) mod aabb;
 	pub(crate) mod epsilons;
+	pub(crate) mod input_set;
 	pub(crate) mod math;
 	pub(crate) mod quad_tree;
 }
 mod path;
+mod robust;
 #[cfg(test)]
 mod visual_tests;
 
@@ -21,11 +23,13 @@ pub(crate) use parsing::*;
 pub(crate) use path::*;
 pub(crate) use util::*;
 
+pub use input_set::InputSet;
 pub use intersection_path_segment::path_segment_intersection;
 #[cfg(feature = "parsing")]
 pub use parsing::path_data::{path_from_path_data, path_to_path_data};
-pub use path_boolean::{BooleanError, EPS, FillRule, PathBooleanOperation, path_boolean};
+pub use path_boolean::{BooleanError, EPS, FillRule, PathBooleanOperation, PathFragment, enclosing_face, path_boolean, path_boolean_fragments, path_boolean_multiple, path_boolean_multiple_fragments};
 pub use path_segment::PathSegment;
+pub use robust::{Repair, RepairReport, RobustOptions, path_boolean_robust};
 
 #[cfg(test)]
 mod test {
@@ -311,4 +315,217 @@ mod test {
 		assert_eq!(result.len(), 1);
 		assert!(!result[0].is_empty());
 	}
+	#[test]
+	fn fracture_fragments() {
+		let a = path_from_path_data("M 0 0 L 20 0 L 20 20 L 0 20 Z").unwrap();
+		let b = path_from_path_data("M 10 0 L 30 0 L 30 20 L 10 20 Z").unwrap();
+
+		let result = crate::path_boolean_fragments(&a, FillRule::NonZero, &b, FillRule::NonZero, PathBooleanOperation::Fracture).unwrap();
+
+		assert_eq!(result.len(), 3, "Expected 3 fragments for Fracture operation");
+		let mut sources: Vec<Vec<usize>> = result.iter().map(|fragment| fragment.sources.iter().collect()).collect();
+		sources.sort();
+		assert_eq!(sources, [vec![0], vec![0, 1], vec![1]]);
+		assert!(result.iter().all(|fragment| !fragment.path.is_empty()));
+	}
+	#[test]
+	fn trim() {
+		let a = path_from_path_data("M 0 0 L 20 0 L 20 20 L 0 20 Z").unwrap();
+		let b = path_from_path_data("M 10 5 L 30 5 L 30 15 L 10 15 Z").unwrap();
+
+		let result = crate::path_boolean_fragments(&a, FillRule::NonZero, &b, FillRule::NonZero, PathBooleanOperation::Trim).unwrap();
+
+		assert_eq!(result.len(), 2, "Expected the visible part of A and B for Trim operation");
+		assert!(result[0].from_a() && !result[0].from_b());
+		assert!(result[1].from_b() && !result[1].from_a());
+
+		let visible = path_to_path_data(&result[0].path, 0.001);
+		assert!(!visible.contains("30.000"), "The visible part of A should not extend into B: {visible}");
+		let difference = path_boolean(&a, FillRule::NonZero, &b, FillRule::NonZero, PathBooleanOperation::Difference).unwrap();
+		assert_eq!(result[0].path, difference[0]);
+		assert!(!result[1].path.is_empty());
+	}
+
+	fn circle(x: f64, y: f64, radius: f64) -> crate::path::Path {
+		path_from_path_data(&format!(
+			"M {} {y} A {radius} {radius} 0 0 1 {x} {} A {radius} {radius} 0 0 1 {} {y} A {radius} {radius} 0 0 1 {x} {} A {radius} {radius} 0 0 1 {} {y} Z",
+			x + radius,
+			y + radius,
+			x - radius,
+			y - radius,
+			x + radius,
+		))
+		.unwrap()
+	}
+
+	/// A circle made of four cubic segments, which is how circles reach path-bool from vector data.
+	fn cubic_circle(x: f64, y: f64, radius: f64) -> crate::path::Path {
+		let k = radius * 0.552284749831;
+		let (left, right, top, bottom) = (x - radius, x + radius, y - radius, y + radius);
+		path_from_path_data(&format!(
+			"M {right} {y} C {right} {} {} {bottom} {x} {bottom} C {} {bottom} {left} {} {left} {y} C {left} {} {} {top} {x} {top} C {} {top} {right} {} {right} {y} Z",
+			y + k,
+			x + k,
+			x - k,
+			y + k,
+			y - k,
+			x - k,
+			x + k,
+			y - k,
+		))
+		.unwrap()
+	}
+
+	/// A mix of overlapping, nested and disjoint shapes, including a self-intersecting star which is only partially filled with the even-odd fill rule.
+	fn shapes() -> Vec<(crate::path::Path, FillRule)> {
+		vec![
+			(path_from_path_data("M 0 0 L 60 0 L 60 40 L 0 40 Z").unwrap(), FillRule::NonZero),
+			(circle(50., 30., 20.), FillRule::NonZero),
+			(path_from_path_data("M 30 -10 L 42 30 L 10 5 L 50 5 L 18 30 Z").unwrap(), FillRule::EvenOdd),
+			(circle(20., 20., 8.), FillRule::NonZero),
+			(path_from_path_data("M 80 80 L 90 80 L 90 90 L 80 90 Z").unwrap(), FillRule::NonZero),
+			(circle(15., 35., 12.), FillRule::EvenOdd),
+		]
+	}
+
+	/// Folds the operation over the shapes pairwise, which is how multiple shapes had to be combined before `path_boolean_multiple`.
+	fn fold(shapes: &[(crate::path::Path, FillRule)], op: PathBooleanOperation) -> crate::path::Path {
+		let (first, first_fill_rule) = shapes[0].clone();
+		let mut result = path_boolean(&first, first_fill_rule, &Vec::new(), FillRule::NonZero, PathBooleanOperation::Union).unwrap().concat();
+		for (path, fill_rule) in &shapes[1..] {
+			result = path_boolean(&result, FillRule::NonZero, path, *fill_rule, op).unwrap().concat();
+		}
+		result
+	}
+
+	/// A square grid of points for [`coverage`] to sample.
+	#[derive(Clone, Copy)]
+	pub(crate) struct SampleGrid {
+		/// The first point, offset so no point lies exactly on an edge of the shapes being sampled.
+		pub origin: glam::DVec2,
+		pub spacing: f64,
+		/// The number of points along each axis.
+		pub size: usize,
+	}
+
+	/// The grid covering [`shapes`].
+	const GRID: SampleGrid = SampleGrid {
+		origin: glam::DVec2::new(-10.37, -15.61),
+		spacing: 0.85,
+		size: 120,
+	};
+
+	/// Samples which points of the grid lie inside the path.
+	///
+	/// This relies on the even-odd fill rule, so it is only valid for paths which either use that fill rule or don't intersect themselves.
+	pub(crate) fn coverage(path: &crate::path::Path, grid: SampleGrid) -> Vec<bool> {
+		let points = (0..grid.size).flat_map(|y| (0..grid.size).map(move |x| grid.origin + glam::DVec2::new(x as f64, y as f64) * grid.spacing));
+		points
+			.map(|point| path.iter().map(|segment| path_boolean::path_segment_horizontal_ray_intersection_count(segment, point)).sum::<usize>() % 2 == 1)
+			.collect()
+	}
+
+	#[test]
+	fn multiple_matches_pairwise_folding() {
+		let shapes = shapes();
+		let inputs: Vec<_> = shapes.iter().map(|(path, fill_rule)| (path, *fill_rule)).collect();
+
+		for op in [
+			PathBooleanOperation::Union,
+			PathBooleanOperation::Intersection,
+			PathBooleanOperation::Difference,
+			PathBooleanOperation::Exclusion,
+		] {
+			let result = crate::path_boolean_multiple(&inputs, op).unwrap();
+			assert_eq!(result.len(), 1, "Expected 1 resulting path for {op:?} operation");
+
+			let expected = coverage(&fold(&shapes, op), GRID);
+			let actual = coverage(&result[0], GRID);
+			let mismatches = expected.iter().zip(&actual).filter(|(expected, actual)| expected != actual).count();
+			assert_eq!(mismatches, 0, "{op:?} of multiple paths differs from pairwise folding");
+			assert!(op == PathBooleanOperation::Intersection || actual.contains(&true), "Expected a non-empty result for {op:?} operation");
+		}
+	}
+
+	#[test]
+	fn multiple_trim() {
+		let shapes = shapes();
+		let inputs: Vec<_> = shapes.iter().map(|(path, fill_rule)| (path, *fill_rule)).collect();
+
+		let result = crate::path_boolean_multiple_fragments(&inputs, PathBooleanOperation::Trim).unwrap();
+		assert_eq!(result.len(), shapes.len(), "Expected a fragment for each path for Trim operation");
+
+		let union = coverage(&crate::path_boolean_multiple(&inputs, PathBooleanOperation::Union).unwrap()[0], GRID);
+		let fragments: Vec<_> = result.iter().map(|fragment| coverage(&fragment.path, GRID)).collect();
+		for (index, fragment) in result.iter().enumerate() {
+			assert_eq!(fragment.sources.iter().collect::<Vec<_>>(), [index]);
+		}
+		for (point, &inside_union) in union.iter().enumerate() {
+			let covering = fragments.iter().filter(|fragment| fragment[point]).count();
+			assert_eq!(covering, inside_union as usize, "Every visible point should be covered by exactly one fragment");
+		}
+
+		// The topmost path is never trimmed
+		let (top, top_fill_rule) = shapes.last().unwrap();
+		let top_alone = path_boolean(top, *top_fill_rule, &Vec::new(), FillRule::NonZero, PathBooleanOperation::Union).unwrap().concat();
+		assert_eq!(fragments.last().unwrap(), &coverage(&top_alone, GRID));
+	}
+
+	#[test]
+	fn robust_removes_collapsed_subpath() {
+		// The first path has collapsed into a line, which makes it impossible to tell its inside from its outside
+		let a = path_from_path_data("M 100 30 L 100 40 L 100 60 Z").unwrap();
+		let b = path_from_path_data("M 90 30 L 30 10 L 80 40 Z").unwrap();
+		let inputs = [(&a, FillRule::EvenOdd), (&b, FillRule::NonZero)];
+		assert!(crate::path_boolean_multiple(&inputs, PathBooleanOperation::Union).is_err());
+
+		let (fragments, report) = crate::path_boolean_robust(&inputs, PathBooleanOperation::Union, &crate::RobustOptions::default()).unwrap();
+
+		assert_eq!(fragments.len(), 1);
+		assert_eq!(coverage(&fragments[0].path, GRID), coverage(&b, GRID));
+		assert_eq!(report.repairs, [crate::Repair::RemovedDegenerateSubpaths { input: 0, count: 1 }]);
+		assert_eq!(report.errors.len(), 0);
+	}
+
+	#[test]
+	fn robust_splits_almost_overlapping_edges() {
+		// The top edges of the rectangles almost, but not exactly, overlap
+		let a = path_from_path_data("M 0 0 L 60 0 L 60 10 L 0 10 Z").unwrap();
+		let b = path_from_path_data("M 10 -0.000005 L 100 -0.000005 L 100 40 L 10 40 Z").unwrap();
+		let inputs = [(&a, FillRule::NonZero), (&b, FillRule::NonZero)];
+
+		let (fragments, report) = crate::path_boolean_robust(&inputs, PathBooleanOperation::Union, &crate::RobustOptions::default()).unwrap();
+
+		let expected = path_from_path_data("M 0 0 L 10 0 L 100 0 L 100 40 L 10 40 L 10 10 L 0 10 Z").unwrap();
+		assert_eq!(fragments.len(), 1);
+		assert_eq!(coverage(&fragments[0].path, GRID), coverage(&expected, GRID));
+		assert_eq!(report.repaired_inputs().iter().collect::<Vec<_>>(), [0, 1]);
+	}
+
+	#[test]
+	fn robust_follows_face_touching_itself() {
+		// The square minus the triangle is a face whose boundary passes through the shared corner twice
+		let a = path_from_path_data("M 60 60 L 80 20 L 70 50 Z").unwrap();
+		let b = path_from_path_data("M 60 0 L 90 0 L 90 60 L 60 60 Z").unwrap();
+		let inputs = [(&a, FillRule::NonZero), (&b, FillRule::NonZero)];
+
+		let (fragments, report) = crate::path_boolean_robust(&inputs, PathBooleanOperation::Union, &crate::RobustOptions::default()).unwrap();
+
+		assert_eq!(fragments.len(), 1);
+		assert_eq!(coverage(&fragments[0].path, GRID), coverage(&b, GRID));
+		assert!(report.is_empty());
+	}
+
+	#[test]
+	fn robust_picks_largest_outer_face() {
+		// The inner circle almost touches the outer one at the bottom, so once they are snapped together the face between them touches itself
+		let inner = cubic_circle(0.00000895069, 80.000005187635, 30.);
+		let outer = cubic_circle(0., 70., 40.);
+		let inputs = [(&inner, FillRule::NonZero), (&outer, FillRule::NonZero)];
+
+		let (fragments, _) = crate::path_boolean_robust(&inputs, PathBooleanOperation::Union, &crate::RobustOptions::default()).unwrap();
+
+		assert_eq!(fragments.len(), 1);
+		assert_eq!(coverage(&fragments[0].path, GRID), coverage(&outer, GRID));
+	}
 }
@@ -65,6 +65,7 @@ new_key_type! {
 
 use crate::aabb::{Aabb, bounding_box_around_point, bounding_box_max_extent, merge_bounding_boxes};
 use crate::epsilons::Epsilons;
+use crate::input_set::InputSet;
 use crate::intersection_path_segment::{path_segment_intersection, segments_equal};
 use crate::path::Path;
 use crate::path_cubic_segment_self_intersection::path_cubic_segment_self_intersection;
@@ -79,7 +80,11 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Display;
 
 /// Represents the types of boolean operations that can be performed on paths.
-#[derive(Debug, Clone, Copy)]
+///
+/// The operations are described for two paths A and B. When operating on more than two paths with
+/// [`path_boolean_multiple`], path A is the first path and path B stands for all of the other paths,
+/// with the result matching what folding the operation over the paths pairwise would produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PathBooleanOperation {
 	/// Computes the union of two paths.
 	///
@@ -102,6 +107,7 @@ pub enum PathBooleanOperation {
 	/// Computes the symmetric difference (exclusive or) of two paths.
 	///
 	/// The result contains areas that are inside either path A or path B, but not in both.
+	/// For more than two paths, these are the areas inside an odd number of paths.
 	/// This operation is useful for creating non-overlapping regions or finding boundaries.
 	Exclusion,
 
@@ -115,8 +121,36 @@ pub enum PathBooleanOperation {
 	///
 	/// This operation splits both path A and path B at their intersection points,
 	/// resulting in all possible non-overlapping segments from both paths.
-	/// It's useful for creating detailed breakdowns of overlapping shapes.
+	/// It's useful for creating detailed breakdowns of overlapping shapes, like the "divide" operation of a pathfinder panel.
 	Fracture,
+
+	/// Removes the parts of path A which are hidden behind path B.
+	///
+	/// The result contains two paths: the visible part of path A (A minus B) followed by path B itself.
+	/// For more than two paths, which are ordered from bottom to top, the result contains the visible part of each path.
+	/// Unlike [`PathBooleanOperation::Difference`], path B is kept, so this is useful for flattening overlapping artwork
+	/// into shapes which no longer overlap. A "merge" operation is a trim followed by a union of the resulting paths which share a fill.
+	Trim,
+}
+
+/// A path produced by a boolean operation along with the inputs it originates from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathFragment {
+	pub path: Path,
+	/// Indices of the inputs the fragment was produced from, with 0 for path A and 1 for path B.
+	pub sources: InputSet,
+}
+
+impl PathFragment {
+	/// Whether the fragment was produced from path A.
+	pub fn from_a(&self) -> bool {
+		self.sources.contains(0)
+	}
+
+	/// Whether the fragment was produced from path B.
+	pub fn from_b(&self) -> bool {
+		self.sources.contains(1)
+	}
 }
 
 /// Specifies how to determine the "inside" of a path for filling.
@@ -137,13 +171,13 @@ pub const EPS: Epsilons = Epsilons {
 	param: 1e-8,
 };
 
-type MajorGraphEdgeStage1 = (PathSegment, u8);
-type MajorGraphEdgeStage2 = (PathSegment, u8, Aabb);
+type MajorGraphEdgeStage1 = (PathSegment, usize);
+type MajorGraphEdgeStage2 = (PathSegment, usize, Aabb);
 
 #[derive(Debug, Clone)]
 pub struct MajorGraphEdge {
 	seg: PathSegment,
-	parent: u8,
+	parent: InputSet,
 	incident_vertices: [MajorVertexKey; 2],
 	direction_flag: Direction,
 	twin: Option<MajorEdgeKey>,
@@ -168,7 +202,7 @@ struct MajorGraph {
 #[derive(Debug, Clone, PartialEq)]
 struct MinorGraphEdge {
 	segments: Vec<PathSegment>,
-	parent: u8,
+	parent: InputSet,
 	incident_vertices: [MinorVertexKey; 2],
 	direction_flag: Direction,
 	twin: Option<MinorEdgeKey>,
@@ -221,7 +255,7 @@ struct MinorGraphVertex {
 #[derive(Debug, Clone)]
 struct MinorGraphCycle {
 	segments: Vec<PathSegment>,
-	parent: u8,
+	parent: InputSet,
 	direction_flag: Direction,
 }
 
@@ -238,7 +272,7 @@ struct MinorGraph {
 #[derive(Debug, Clone, PartialEq)]
 struct DualGraphHalfEdge {
 	segments: Vec<PathSegment>,
-	parent: u8,
+	parent: InputSet,
 	incident_vertex: DualVertexKey,
 	direction_flag: Direction,
 	twin: Option<DualEdgeKey>,
@@ -290,7 +324,7 @@ fn major_graph_to_dot(graph: &MajorGraph) -> String {
 	}
 	for (_, edge) in &graph.edges {
 		dot.push_str(&format!(
-			"  {:?} -> {:?}: {:0b}\n",
+			"  {:?} -> {:?}: {:?}\n",
 			(edge.incident_vertices[0].0.as_ffi() & 0xFF),
 			(edge.incident_vertices[1].0.as_ffi() & 0xFF),
 			edge.parent
@@ -305,7 +339,7 @@ fn minor_graph_to_dot(edges: &SlotMap<MinorEdgeKey, MinorGraphEdge>) -> String {
 	let mut dot = String::from("digraph {\n");
 	for edge in edges.values() {
 		dot.push_str(&format!(
-			"  {:?} -> {:?}: {:0b}\n",
+			"  {:?} -> {:?}: {:?}\n",
 			(edge.incident_vertices[0].0.as_ffi() & 0xFF),
 			(edge.incident_vertices[1].0.as_ffi() & 0xFF),
 			edge.parent
@@ -332,7 +366,7 @@ fn dual_graph_to_dot(components: &[DualGraphComponent], edges: &SlotMap<DualEdge
 	dot
 }
 
-fn segment_to_edge(parent: u8) -> impl Fn(&PathSegment) -> Option<MajorGraphEdgeStage1> {
+fn segment_to_edge(parent: usize) -> impl Fn(&PathSegment) -> Option<MajorGraphEdgeStage1> {
 	move |seg| {
 		if bounding_box_max_extent(&seg.bounding_box()) < EPS.point {
 			return None;
@@ -429,7 +463,7 @@ fn split_at_intersections(edges: &[MajorGraphEdgeStage1]) -> (Vec<MajorGraphEdge
 	for (i, edge) in with_bounding_box.iter().enumerate() {
 		let candidates = edge_tree.find(&edge.2);
 		for &j in &candidates {
-			let candidate: &(PathSegment, u8) = &edges[j];
+			let candidate: &(PathSegment, usize) = &edges[j];
 			let include_endpoints = edge.1 != candidate.1 || !(candidate.0.end().abs_diff_eq(edge.0.start(), EPS.point) || candidate.0.start().abs_diff_eq(edge.0.end(), EPS.point));
 			let intersection = path_segment_intersection(&edge.0, &candidate.0, include_endpoints, &EPS);
 			for [t0, t1] in intersection {
@@ -515,7 +549,7 @@ fn find_vertices(edges: &[MajorGraphEdgeStage2], bounding_box: Aabb) -> MajorGra
 		vertices: SlotMap::with_key(),
 	};
 
-	let mut parents: HashMap<MajorEdgeKey, u8> = HashMap::new();
+	let mut parents: HashMap<MajorEdgeKey, InputSet> = HashMap::new();
 
 	let mut vertex_pair_id_to_edges: HashMap<_, Vec<(MajorGraphEdgeStage2, MajorEdgeKey, MajorEdgeKey)>> = HashMap::new();
 
@@ -558,15 +592,15 @@ fn find_vertices(edges: &[MajorGraphEdgeStage2], bounding_box: Aabb) -> MajorGra
 				.iter()
 				.find(|(other_seg, ..)| segments_equal(seg, &other_seg.0, EPS.point) || segments_equal(&seg.reverse(), &other_seg.0, EPS.point))
 			{
-				*parents.entry(existing_edge.1).or_default() |= parent;
-				*parents.entry(existing_edge.2).or_default() |= parent;
+				parents.entry(existing_edge.1).or_default().insert(*parent);
+				parents.entry(existing_edge.2).or_default().insert(*parent);
 				continue;
 			}
 		}
 
 		let fwd_edge_key = graph.edges.insert(MajorGraphEdge {
 			seg: *seg,
-			parent: *parent,
+			parent: InputSet::single(*parent),
 			incident_vertices: [start_vertex, end_vertex],
 			direction_flag: Direction::Forward,
 			twin: None,
@@ -574,7 +608,7 @@ fn find_vertices(edges: &[MajorGraphEdgeStage2], bounding_box: Aabb) -> MajorGra
 
 		let bwd_edge_key = graph.edges.insert(MajorGraphEdge {
 			seg: *seg,
-			parent: *parent,
+			parent: InputSet::single(*parent),
 			incident_vertices: [end_vertex, start_vertex],
 			direction_flag: Direction::Backwards,
 			twin: Some(fwd_edge_key),
@@ -591,7 +625,7 @@ fn find_vertices(edges: &[MajorGraphEdgeStage2], bounding_box: Aabb) -> MajorGra
 			.push(((*seg, *parent, *bounding_box), fwd_edge_key, bwd_edge_key));
 	}
 	for (edge_key, parent) in parents {
-		graph.edges[edge_key].parent |= parent;
+		graph.edges[edge_key].parent.union_with(&parent);
 	}
 
 	graph
@@ -671,7 +705,7 @@ fn compute_minor(major_graph: &MajorGraph) -> MinorGraph {
 
 			let new_edge_key = new_edges.insert(MinorGraphEdge {
 				segments,
-				parent: major_graph.edges[start_edge_key].parent,
+				parent: major_graph.edges[start_edge_key].parent.clone(),
 				incident_vertices: [start_vertex, end_vertex],
 				direction_flag: major_graph.edges[start_edge_key].direction_flag,
 				twin: twin_key.copied(),
@@ -694,7 +728,7 @@ fn compute_minor(major_graph: &MajorGraph) -> MinorGraph {
 		let mut edge = &major_graph.edges[edge_key];
 		let mut cycle = MinorGraphCycle {
 			segments: Vec::new(),
-			parent: edge.parent,
+			parent: edge.parent.clone(),
 			direction_flag: edge.direction_flag,
 		};
 		loop {
@@ -720,7 +754,7 @@ fn compute_minor(major_graph: &MajorGraph) -> MinorGraph {
 
 fn remove_dangling_edges(graph: &mut MinorGraph) {
 	// Basically DFS for each parent with BFS number
-	fn walk(parent: u8, graph: &MinorGraph) -> HashSet<MinorVertexKey> {
+	fn walk(parent: usize, start_edges: &[MinorEdgeKey], graph: &MinorGraph) -> HashSet<MinorVertexKey> {
 		let mut kept_vertices = HashSet::new();
 		let mut vertex_to_level = HashMap::new();
 
@@ -731,7 +765,7 @@ fn remove_dangling_edges(graph: &mut MinorGraph) {
 			graph: &MinorGraph,
 			vertex_to_level: &mut HashMap<MinorVertexKey, usize>,
 			kept_vertices: &mut HashSet<MinorVertexKey>,
-			parent: u8,
+			parent: usize,
 		) -> usize {
 			if let Some(&existing_level) = vertex_to_level.get(&vertex) {
 				return existing_level;
@@ -741,7 +775,7 @@ fn remove_dangling_edges(graph: &mut MinorGraph) {
 			let mut min_level = usize::MAX;
 			for &edge_key in &graph.vertices[vertex].outgoing_edges {
 				let edge = &graph.edges[edge_key];
-				if edge.parent & parent != 0 && Some(edge_key) != incoming_edge {
+				if edge.parent.contains(parent) && Some(edge_key) != incoming_edge {
 					min_level = min_level.min(visit(edge.incident_vertices[1], edge.twin, level + 1, graph, vertex_to_level, kept_vertices, parent));
 				}
 			}
@@ -753,32 +787,38 @@ fn remove_dangling_edges(graph: &mut MinorGraph) {
 			min_level
 		}
 
-		for edge in graph.edges.values() {
-			if edge.parent & parent != 0 {
-				visit(edge.incident_vertices[0], None, 0, graph, &mut vertex_to_level, &mut kept_vertices, parent);
-			}
+		for &edge_key in start_edges {
+			visit(graph.edges[edge_key].incident_vertices[0], None, 0, graph, &mut vertex_to_level, &mut kept_vertices, parent);
 		}
 
 		kept_vertices
 	}
 
-	let kept_vertices_a = walk(1, graph);
-	let kept_vertices_b = walk(2, graph);
+	// Group the edges by input so each walk only has to look at the edges of its own input
+	let mut edges_by_parent: HashMap<usize, Vec<MinorEdgeKey>> = HashMap::new();
+	for (edge_key, edge) in &graph.edges {
+		for parent in edge.parent.iter() {
+			edges_by_parent.entry(parent).or_default().push(edge_key);
+		}
+	}
+
+	let kept_vertices: HashMap<usize, HashSet<MinorVertexKey>> = edges_by_parent.iter().map(|(&parent, start_edges)| (parent, walk(parent, start_edges, graph))).collect();
+
+	let is_kept_edge = |edge: &MinorGraphEdge| {
+		edge.parent.iter().any(|parent| {
+			let kept_vertices = &kept_vertices[&parent];
+			kept_vertices.contains(&edge.incident_vertices[0]) && kept_vertices.contains(&edge.incident_vertices[1])
+		})
+	};
 
-	graph.vertices.retain(|k, _| kept_vertices_a.contains(&k) || kept_vertices_b.contains(&k));
+	let any_kept_vertices: HashSet<MinorVertexKey> = kept_vertices.values().flatten().copied().collect();
+	graph.vertices.retain(|k, _| any_kept_vertices.contains(&k));
 
 	for vertex in graph.vertices.values_mut() {
-		vertex.outgoing_edges.retain(|&edge_key| {
-			let edge = &graph.edges[edge_key];
-			(edge.parent & 1 == 1 && kept_vertices_a.contains(&edge.incident_vertices[0]) && kept_vertices_a.contains(&edge.incident_vertices[1]))
-				|| (edge.parent & 2 == 2 && kept_vertices_b.contains(&edge.incident_vertices[0]) && kept_vertices_b.contains(&edge.incident_vertices[1]))
-		});
+		vertex.outgoing_edges.retain(|&edge_key| is_kept_edge(&graph.edges[edge_key]));
 	}
 	// TODO(@TrueDoctor): merge
-	graph.edges.retain(|_, edge| {
-		(edge.parent & 1 == 1 && kept_vertices_a.contains(&edge.incident_vertices[0]) && kept_vertices_a.contains(&edge.incident_vertices[1]))
-			|| (edge.parent & 2 == 2 && kept_vertices_b.contains(&edge.incident_vertices[0]) && kept_vertices_b.contains(&edge.incident_vertices[1]))
-	});
+	graph.edges.retain(|_, edge| is_kept_edge(edge));
 }
 
 fn sort_outgoing_edges_by_angle(graph: &mut MinorGraph) {
@@ -905,12 +945,13 @@ fn compute_signed_area(face: &DualGraphVertex, edges: &SlotMap<DualEdgeKey, Dual
 /// # Arguments
 ///
 /// * `minor_graph` - A reference to the MinorGraph.
+/// * `robust` - Whether to handle faces whose boundary touches itself, as done by [`crate::path_boolean_robust`].
 ///
 /// # Returns
 ///
 /// A Result containing either the computed DualGraph or a BooleanError if the
 /// operation cannot be completed successfully.
-fn compute_dual(minor_graph: &MinorGraph) -> Result<DualGraph, BooleanError> {
+fn compute_dual(minor_graph: &MinorGraph, robust: bool) -> Result<DualGraph, BooleanError> {
 	let mut new_vertices: Vec<DualVertexKey> = Vec::new();
 	let mut minor_to_dual_edge: HashMap<MinorEdgeKey, DualEdgeKey> = HashMap::new();
 	let mut dual_edges = SlotMap::with_key();
@@ -936,7 +977,7 @@ fn compute_dual(minor_graph: &MinorGraph) -> Result<DualGraph, BooleanError> {
 
 			let new_edge_key = dual_edges.insert(DualGraphHalfEdge {
 				segments: edge.segments.clone(),
-				parent: edge.parent,
+				parent: edge.parent.clone(),
 				incident_vertex: face_key,
 				direction_flag: edge.direction_flag,
 				twin: twin_dual_key,
@@ -955,7 +996,13 @@ fn compute_dual(minor_graph: &MinorGraph) -> Result<DualGraph, BooleanError> {
 			eprintln!("Next edge: {}", (edge_key.0.as_ffi() & 0xFF));
 			edge = &minor_graph.edges[edge_key];
 
-			if edge.incident_vertices[0] == start_edge.incident_vertices[0] {
+			// In robust mode, compare edges rather than vertices, since the boundary of a face can pass through the same vertex more than once where it touches itself
+			let closed = if robust {
+				edge_key == start_edge_key
+			} else {
+				edge.incident_vertices[0] == start_edge.incident_vertices[0]
+			};
+			if closed {
 				break;
 			}
 		}
@@ -969,7 +1016,7 @@ fn compute_dual(minor_graph: &MinorGraph) -> Result<DualGraph, BooleanError> {
 
 		let inner_half_edge_key = dual_edges.insert(DualGraphHalfEdge {
 			segments: cycle.segments.clone(),
-			parent: cycle.parent,
+			parent: cycle.parent.clone(),
 			incident_vertex: inner_face_key,
 			direction_flag: cycle.direction_flag,
 			twin: None,
@@ -977,7 +1024,7 @@ fn compute_dual(minor_graph: &MinorGraph) -> Result<DualGraph, BooleanError> {
 
 		let outer_half_edge_key = dual_edges.insert(DualGraphHalfEdge {
 			segments: cycle.segments.iter().cloned().rev().collect(),
-			parent: cycle.parent,
+			parent: cycle.parent.clone(),
 			incident_vertex: outer_face_key,
 			direction_flag: !cycle.direction_flag,
 			twin: Some(inner_half_edge_key),
@@ -1095,6 +1142,15 @@ fn compute_dual(minor_graph: &MinorGraph) -> Result<DualGraph, BooleanError> {
 				.expect("No outer face of a component found.")
 				.0
 		};
+
+		// In robust mode, don't trust the winding of a face whose boundary touches itself, since the outer face always encloses the largest area
+		let outer_face_key = if robust {
+			let (largest_face_key, largest_area) = areas.iter().map(|&(key, area)| (key, area.abs())).max_by(|(_, a), (_, b)| a.total_cmp(b)).unwrap();
+			let outer_face_area = areas.iter().find(|(key, _)| **key == outer_face_key).map_or(0., |(_, area)| area.abs());
+			if outer_face_area < largest_area * (1. - EPS.linear) { *largest_face_key } else { outer_face_key }
+		} else {
+			outer_face_key
+		};
 		#[cfg(feature = "logging")]
 		dbg!(outer_face_key);
 
@@ -1263,73 +1319,74 @@ fn insert_component(trees: &mut Vec<NestingTree>, component: &DualGraphComponent
 	trees.push(new_tree);
 }
 
-fn get_flag(count: i32, fill_rule: FillRule) -> u8 {
+fn is_inside(count: i32, fill_rule: FillRule) -> bool {
 	match fill_rule {
-		FillRule::NonZero => {
-			if count == 0 {
-				0
-			} else {
-				1
-			}
-		}
-		FillRule::EvenOdd => (count % 2).unsigned_abs() as u8,
+		FillRule::NonZero => count != 0,
+		FillRule::EvenOdd => count % 2 != 0,
 	}
 }
 
 /// Determines which faces should be included in the result based on the boolean operation.
 ///
-/// This function applies the specified boolean operation and fill rules to decide
-/// which regions of the dual graph should be part of the resulting path.
+/// This function applies the fill rule of each input to decide which inputs each region
+/// of the dual graph is inside of, which the boolean operation then selects faces by.
 fn flag_faces(
 	nesting_trees: &[NestingTree],
-	a_fill_rule: FillRule,
-	b_fill_rule: FillRule,
+	fill_rules: &[FillRule],
 	edges: &SlotMap<DualEdgeKey, DualGraphHalfEdge>,
 	vertices: &SlotMap<DualVertexKey, DualGraphVertex>,
-	flags: &mut HashMap<DualVertexKey, u8>,
+	flags: &mut HashMap<DualVertexKey, InputSet>,
 ) {
+	let flag = |counts: &[i32]| {
+		counts
+			.iter()
+			.zip(fill_rules)
+			.enumerate()
+			.filter(|(_, (count, fill_rule))| is_inside(**count, **fill_rule))
+			.map(|(index, _)| index)
+			.collect()
+	};
+
 	for tree in nesting_trees.iter() {
-		let mut tree_stack = vec![(tree, 0, 0)];
+		let mut tree_stack = vec![(tree, vec![0; fill_rules.len()])];
 
-		while let Some((current_tree, a_running_count, b_running_count)) = tree_stack.pop() {
+		while let Some((current_tree, running_counts)) = tree_stack.pop() {
 			let mut visited_faces = HashSet::new();
 			let mut face_stack = VecDeque::new();
 
 			let outer_face_key = current_tree.component.outer_face.expect("Component doesn't have an outer face.");
-			face_stack.push_back((outer_face_key, a_running_count, b_running_count));
+			face_stack.push_back((outer_face_key, running_counts));
 
-			while let Some((face_key, a_count, b_count)) = face_stack.pop_front() {
+			while let Some((face_key, counts)) = face_stack.pop_front() {
 				if visited_faces.contains(&face_key) {
 					continue;
 				}
 				visited_faces.insert(face_key);
 
-				let a_flag = get_flag(a_count, a_fill_rule);
-				let b_flag = get_flag(b_count, b_fill_rule);
-				*flags.entry(face_key).or_default() = a_flag | (b_flag << 1);
+				flags.insert(face_key, flag(&counts));
 
 				for edge_key in &vertices[face_key].incident_edges {
 					let edge = &edges[*edge_key];
 					let twin_key = edge.twin.expect("Edge doesn't have a twin");
-					#[cfg(feature = "logging")]
-					eprintln!("Processing edge: {:?} to: {:?}", edge_key.0, edges[twin_key].incident_vertex.0);
-					let mut next_a_count = a_count;
-					if edge.parent & 1 != 0 {
-						next_a_count += if edge.direction_flag.forward() { 1 } else { -1 };
+					let next_face_key = edges[twin_key].incident_vertex;
+					if visited_faces.contains(&next_face_key) {
+						continue;
 					}
-					let mut next_b_count = b_count;
-					if edge.parent & 2 != 0 {
-						next_b_count += if edge.direction_flag.forward() { 1 } else { -1 };
+					#[cfg(feature = "logging")]
+					eprintln!("Processing edge: {:?} to: {:?}", edge_key.0, next_face_key.0);
+					let mut next_counts = counts.clone();
+					for parent in edge.parent.iter() {
+						next_counts[parent] += if edge.direction_flag.forward() { 1 } else { -1 };
 					}
 					#[cfg(feature = "logging")]
-					eprintln!("next_count a: {}, b:{}", next_a_count, next_b_count);
-					face_stack.push_back((edges[twin_key].incident_vertex, next_a_count, next_b_count));
+					eprintln!("next_counts: {:?}", next_counts);
+					face_stack.push_back((next_face_key, next_counts));
 				}
 
 				// Collect subtrees to be processed later
 				if let Some(subtrees) = current_tree.outgoing_edges.get(&face_key) {
 					for subtree in subtrees {
-						tree_stack.push((subtree, a_count, b_count));
+						tree_stack.push((subtree, counts.clone()));
 					}
 				}
 			}
@@ -1337,8 +1394,28 @@ fn flag_faces(
 	}
 }
 
-fn get_selected_faces<'a>(predicate: &'a impl Fn(u8) -> bool, flags: &'a HashMap<DualVertexKey, u8>) -> impl Iterator<Item = DualVertexKey> + 'a {
-	flags.iter().filter_map(|(key, &flag)| predicate(flag).then_some(*key))
+fn get_selected_faces<'a>(predicate: &'a impl Fn(&InputSet) -> bool, flags: &'a HashMap<DualVertexKey, InputSet>) -> impl Iterator<Item = DualVertexKey> + 'a {
+	flags.iter().filter_map(|(key, flag)| predicate(flag).then_some(*key))
+}
+
+/// Walks the boundary of all faces matching the predicate, producing a single fragment whose sources are those of all included faces.
+fn walk_selected_faces(
+	predicate: impl Fn(&InputSet) -> bool,
+	edges: &SlotMap<DualEdgeKey, DualGraphHalfEdge>,
+	vertices: &SlotMap<DualVertexKey, DualGraphVertex>,
+	flags: &HashMap<DualVertexKey, InputSet>,
+) -> PathFragment {
+	let mut selected_faces: Vec<DualVertexKey> = get_selected_faces(&predicate, flags).collect();
+	selected_faces.sort_unstable();
+	let mut sources = InputSet::default();
+	for face in &selected_faces {
+		sources.union_with(&flags[face]);
+	}
+
+	PathFragment {
+		path: walk_faces(&selected_faces, edges, vertices).collect(),
+		sources,
+	}
 }
 
 fn walk_faces<'a>(faces: &'a [DualVertexKey], edges: &SlotMap<DualEdgeKey, DualGraphHalfEdge>, vertices: &SlotMap<DualVertexKey, DualGraphVertex>) -> impl Iterator<Item = PathSegment> + use<'a> {
@@ -1388,6 +1465,31 @@ fn walk_faces<'a>(faces: &'a [DualVertexKey], edges: &SlotMap<DualEdgeKey, DualG
 	result.into_iter()
 }
 
+/// Appends the boundary of a face to the path, following the orientation of its edges.
+fn extend_with_face_boundary(path: &mut Path, face: &DualGraphVertex, edges: &SlotMap<DualEdgeKey, DualGraphHalfEdge>) {
+	for &edge_key in &face.incident_edges {
+		let edge = &edges[edge_key];
+		if edge.direction_flag.forward() {
+			path.extend(edge.segments.iter().cloned());
+		} else {
+			path.extend(edge.segments.iter().map(PathSegment::reverse));
+		}
+	}
+}
+
+/// The boundary of a face of the nesting tree's component, with holes poked in it by the outer boundaries of the components nested inside the face.
+fn face_with_holes(tree: &NestingTree, face_key: DualVertexKey, edges: &SlotMap<DualEdgeKey, DualGraphHalfEdge>, vertices: &SlotMap<DualVertexKey, DualGraphVertex>) -> Path {
+	let mut path = Vec::new();
+	extend_with_face_boundary(&mut path, &vertices[face_key], edges);
+
+	for subtree in tree.outgoing_edges.get(&face_key).into_iter().flatten() {
+		let outer_face_key = subtree.component.outer_face.unwrap();
+		extend_with_face_boundary(&mut path, &vertices[outer_face_key], edges);
+	}
+
+	path
+}
+
 /// Reconstructs the resulting path(s) from the selected faces of the dual graph.
 ///
 /// This function takes the faces that were flagged for inclusion and reconstructs
@@ -1395,55 +1497,31 @@ fn walk_faces<'a>(faces: &'a [DualVertexKey], edges: &SlotMap<DualEdgeKey, DualG
 /// final output of the boolean operation.
 fn dump_faces(
 	nesting_trees: &[NestingTree],
-	predicate: impl Fn(u8) -> bool + Copy,
+	predicate: impl Fn(&InputSet) -> bool + Copy,
 	edges: &SlotMap<DualEdgeKey, DualGraphHalfEdge>,
 	vertices: &SlotMap<DualVertexKey, DualGraphVertex>,
-	flags: &HashMap<DualVertexKey, u8>,
-) -> Vec<Path> {
+	flags: &HashMap<DualVertexKey, InputSet>,
+) -> Vec<PathFragment> {
 	let mut paths = Vec::new();
 
 	fn visit(
 		tree: &NestingTree,
-		predicate: impl Fn(u8) -> bool + Copy,
-		paths: &mut Vec<Path>,
+		predicate: impl Fn(&InputSet) -> bool + Copy,
+		paths: &mut Vec<PathFragment>,
 		edges: &SlotMap<DualEdgeKey, DualGraphHalfEdge>,
 		vertices: &SlotMap<DualVertexKey, DualGraphVertex>,
-		flags: &HashMap<DualVertexKey, u8>,
+		flags: &HashMap<DualVertexKey, InputSet>,
 	) {
 		for &face_key in tree.component.vertices.iter() {
-			let face = &vertices[face_key];
-			let flag = flags[&face_key];
+			let flag = &flags[&face_key];
 			if !predicate(flag) || Some(face_key) == tree.component.outer_face {
 				continue;
 			}
 
-			let mut path = Vec::new();
-
-			for &edge_key in &face.incident_edges {
-				let edge = &edges[edge_key];
-				if edge.direction_flag.forward() {
-					path.extend(edge.segments.iter().cloned());
-				} else {
-					path.extend(edge.segments.iter().map(PathSegment::reverse));
-				}
-			}
-
-			// Poke holes in the face
-			if let Some(subtrees) = tree.outgoing_edges.get(&face_key) {
-				for subtree in subtrees {
-					let outer_face_key = subtree.component.outer_face.unwrap();
-					for &edge_key in &vertices[outer_face_key].incident_edges {
-						let edge = &edges[edge_key];
-						if edge.direction_flag.forward() {
-							path.extend(edge.segments.iter().cloned());
-						} else {
-							path.extend(edge.segments.iter().map(PathSegment::reverse));
-						}
-					}
-				}
-			}
-
-			paths.push(path);
+			paths.push(PathFragment {
+				path: face_with_holes(tree, face_key, edges, vertices),
+				sources: flag.clone(),
+			});
 		}
 
 		for subtrees in tree.outgoing_edges.values() {
@@ -1460,17 +1538,21 @@ fn dump_faces(
 	paths
 }
 
-const OPERATION_PREDICATES: [fn(u8) -> bool; 6] = [
-	|flag: u8| flag > 0,               // Union
-	|flag: u8| flag == 1,              // Difference
-	|flag: u8| flag == 0b11,           // Intersection
-	|flag: u8| flag == 1 || flag == 2, // Exclusion
-	|flag: u8| (flag & 1) == 1,        // Division
-	|flag: u8| flag > 0,               // Fracture
-];
+/// Decides if a face, which is inside the inputs given by its flag, is part of the result of the operation on `input_count` inputs.
+fn is_selected_face(op: PathBooleanOperation, flag: &InputSet, input_count: usize) -> bool {
+	match op {
+		PathBooleanOperation::Union | PathBooleanOperation::Fracture => !flag.is_empty(),
+		PathBooleanOperation::Difference => flag.contains(0) && flag.len() == 1,
+		PathBooleanOperation::Intersection => flag.len() == input_count,
+		PathBooleanOperation::Exclusion => flag.len() % 2 == 1,
+		PathBooleanOperation::Division => flag.contains(0),
+		// Each input is walked separately, taking the faces where it is the topmost input
+		PathBooleanOperation::Trim => !flag.is_empty(),
+	}
+}
 
 /// Represents errors that can occur during boolean operations on paths.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BooleanError {
 	/// Indicates that multiple outer faces were found where only one was expected.
 	MultipleOuterFaces,
@@ -1523,8 +1605,10 @@ impl Display for BooleanError {
 /// - Exclusion
 /// - Division
 /// - Fracture
+/// - Trim
 ///
 /// See [`PathBooleanOperation`] for more details on each operation.
+/// Use [`path_boolean_fragments`] to also find out which input each resulting path originates from.
 ///
 /// # Algorithm
 ///
@@ -1545,7 +1629,74 @@ impl Display for BooleanError {
 /// - The operation encounters an unsolvable geometric configuration.
 /// - Issues arise in determining the nesting structure of the paths.
 pub fn path_boolean(a: &Path, a_fill_rule: FillRule, b: &Path, b_fill_rule: FillRule, op: PathBooleanOperation) -> Result<Vec<Path>, BooleanError> {
-	let mut unsplit_edges: Vec<MajorGraphEdgeStage1> = a.iter().map(segment_to_edge(1)).chain(b.iter().map(segment_to_edge(2))).flatten().collect();
+	Ok(path_boolean_fragments(a, a_fill_rule, b, b_fill_rule, op)?.into_iter().map(|fragment| fragment.path).collect())
+}
+
+/// Performs boolean operations on two paths, keeping track of which input(s) each resulting path originates from.
+///
+/// This behaves like [`path_boolean`], but each resulting path is returned as a [`PathFragment`] whose sources
+/// tell if it covers path A, path B or both. This allows callers to carry over the style of the originating input,
+/// for example to give each piece of a [`PathBooleanOperation::Fracture`] the fill of the topmost path it was cut from.
+///
+/// # Examples
+///
+/// ```
+/// use path_bool::{path_boolean_fragments, FillRule, PathBooleanOperation, path_from_path_data};
+///
+/// let path_a = path_from_path_data("M 0 0 L 20 0 L 20 20 L 0 20 Z").unwrap();
+/// let path_b = path_from_path_data("M 10 0 L 30 0 L 30 20 L 10 20 Z").unwrap();
+///
+/// let fragments = path_boolean_fragments(&path_a, FillRule::NonZero, &path_b, FillRule::NonZero, PathBooleanOperation::Fracture).unwrap();
+///
+/// assert_eq!(fragments.len(), 3);
+/// assert_eq!(fragments.iter().filter(|fragment| fragment.from_a() && fragment.from_b()).count(), 1);
+/// ```
+pub fn path_boolean_fragments(a: &Path, a_fill_rule: FillRule, b: &Path, b_fill_rule: FillRule, op: PathBooleanOperation) -> Result<Vec<PathFragment>, BooleanError> {
+	path_boolean_multiple_fragments(&[(a, a_fill_rule), (b, b_fill_rule)], op)
+}
+
+/// Performs a boolean operation on any number of paths at once, each with its own fill rule.
+///
+/// All paths are intersected and resolved in a single pass, which is both faster and more precise than folding
+/// [`path_boolean`] over the paths pairwise, since intermediate results don't need to be rebuilt and re-intersected.
+/// The first path plays the role of path A in the operations described by [`PathBooleanOperation`],
+/// while the paths are otherwise ordered from bottom to top.
+///
+/// # Examples
+///
+/// ```
+/// use path_bool::{path_boolean_multiple, FillRule, PathBooleanOperation, path_from_path_data};
+///
+/// let paths = [
+///     path_from_path_data("M 0 0 L 20 0 L 20 20 L 0 20 Z").unwrap(),
+///     path_from_path_data("M 10 0 L 30 0 L 30 20 L 10 20 Z").unwrap(),
+///     path_from_path_data("M 5 5 L 25 5 L 25 25 L 5 25 Z").unwrap(),
+/// ];
+/// let inputs: Vec<_> = paths.iter().map(|path| (path, FillRule::NonZero)).collect();
+///
+/// let result = path_boolean_multiple(&inputs, PathBooleanOperation::Intersection).unwrap();
+///
+/// assert_eq!(result.len(), 1);
+/// assert!(!result[0].is_empty());
+/// ```
+///
+/// # Errors
+///
+/// Returns a [`BooleanError`] under the same conditions as [`path_boolean`].
+pub fn path_boolean_multiple(paths: &[(&Path, FillRule)], op: PathBooleanOperation) -> Result<Vec<Path>, BooleanError> {
+	Ok(path_boolean_multiple_fragments(paths, op)?.into_iter().map(|fragment| fragment.path).collect())
+}
+
+/// Performs a boolean operation on any number of paths at once, keeping track of which input(s) each resulting path originates from.
+///
+/// See [`path_boolean_multiple`] and [`path_boolean_fragments`].
+pub fn path_boolean_multiple_fragments(paths: &[(&Path, FillRule)], op: PathBooleanOperation) -> Result<Vec<PathFragment>, BooleanError> {
+	boolean_fragments(paths, op, false)
+}
+
+/// Implements [`path_boolean_multiple_fragments`], where `robust` enables the handling of faces touching themselves used by [`crate::path_boolean_robust`].
+pub(crate) fn boolean_fragments(paths: &[(&Path, FillRule)], op: PathBooleanOperation, robust: bool) -> Result<Vec<PathFragment>, BooleanError> {
+	let mut unsplit_edges: Vec<MajorGraphEdgeStage1> = paths.iter().enumerate().flat_map(|(index, (path, _))| path.iter().filter_map(segment_to_edge(index))).collect();
 
 	split_at_self_intersections(&mut unsplit_edges);
 
@@ -1603,7 +1754,7 @@ pub fn path_boolean(a: &Path, a_fill_rule: FillRule, b: &Path, b_fill_rule: Fill
 		assert_eq!(twin.twin.unwrap(), edge_key, "Twin relationship should be symmetrical for edge {:?}", edge_key);
 	}
 
-	let dual_graph = compute_dual(&minor_graph)?;
+	let dual_graph = compute_dual(&minor_graph, robust)?;
 
 	let nesting_trees = compute_nesting_tree(&dual_graph);
 
@@ -1619,26 +1770,107 @@ pub fn path_boolean(a: &Path, a_fill_rule: FillRule, b: &Path, b_fill_rule: Fill
 	#[cfg(feature = "logging")]
 	eprintln!("{}", dual_graph_to_dot(&dual_graph.components, edges));
 
+	let fill_rules: Vec<FillRule> = paths.iter().map(|&(_, fill_rule)| fill_rule).collect();
 	let mut flags = HashMap::new();
-	flag_faces(&nesting_trees, a_fill_rule, b_fill_rule, edges, vertices, &mut flags);
+	flag_faces(&nesting_trees, &fill_rules, edges, vertices, &mut flags);
 
 	#[cfg(feature = "logging")]
 	for (face, flag) in &flags {
-		eprintln!("{:?}: {:b}", face.0, flag);
+		eprintln!("{:?}: {:?}", face.0, flag);
 	}
 
-	let predicate = OPERATION_PREDICATES[op as usize];
+	let predicate = |flag: &InputSet| is_selected_face(op, flag, paths.len());
 
 	match op {
 		PathBooleanOperation::Division | PathBooleanOperation::Fracture => Ok(dump_faces(&nesting_trees, predicate, edges, vertices, &flags)),
-		_ => {
-			let mut selected_faces: Vec<DualVertexKey> = get_selected_faces(&predicate, &flags).collect();
-			selected_faces.sort_unstable();
-			Ok(vec![walk_faces(&selected_faces, edges, vertices).collect()])
-		}
+		PathBooleanOperation::Trim => Ok((0..paths.len())
+			.map(|index| PathFragment {
+				// Every face belongs to the topmost input it is inside of
+				sources: InputSet::single(index),
+				..walk_selected_faces(|flag| flag.last() == Some(index), edges, vertices, &flags)
+			})
+			.collect()),
+		_ => Ok(vec![walk_selected_faces(predicate, edges, vertices, &flags)]),
 	}
 }
 
+/// Finds the face of the planar graph formed by the paths which contains the point, like the "live paint" of illustration software.
+///
+/// Unlike the boolean operations, the paths aren't treated as filled shapes. Instead, all of their segments are cut at every
+/// intersection and together divide the plane into faces, so open paths crossing each other can enclose a face just like closed ones.
+/// Segments which don't separate two faces, such as the loose ends of crossing lines, are ignored.
+/// The resulting path is the boundary of the face containing the point, with holes for any faces nested inside of it,
+/// or `None` if the point isn't enclosed by the paths.
+///
+/// # Examples
+///
+/// ```
+/// use path_bool::{enclosing_face, path_from_path_data};
+/// use glam::DVec2;
+///
+/// // A hash sign made of four open lines, which encloses a square in the middle
+/// let lines = [
+///     path_from_path_data("M 10 0 L 10 30").unwrap(),
+///     path_from_path_data("M 20 0 L 20 30").unwrap(),
+///     path_from_path_data("M 0 10 L 30 10").unwrap(),
+///     path_from_path_data("M 0 20 L 30 20").unwrap(),
+/// ];
+/// let paths: Vec<_> = lines.iter().collect();
+///
+/// let face = enclosing_face(&paths, DVec2::new(15., 15.)).unwrap().unwrap();
+/// assert_eq!(face.len(), 4);
+/// assert_eq!(enclosing_face(&paths, DVec2::new(5., 5.)).unwrap(), None);
+/// ```
+///
+/// # Errors
+///
+/// Returns a [`BooleanError`] if the graph formed by the paths can't be resolved into faces.
+pub fn enclosing_face(paths: &[&Path], point: DVec2) -> Result<Option<Path>, BooleanError> {
+	// All paths share one input, so the segments of different paths can enclose faces together
+	let mut unsplit_edges: Vec<MajorGraphEdgeStage1> = paths.iter().flat_map(|path| path.iter().filter_map(segment_to_edge(0))).collect();
+	split_at_self_intersections(&mut unsplit_edges);
+
+	let (split_edges, total_bounding_box) = split_at_intersections(&unsplit_edges);
+	let Some(total_bounding_box) = total_bounding_box else { return Ok(None) };
+
+	let major_graph = find_vertices(&split_edges, total_bounding_box);
+	let mut minor_graph = compute_minor(&major_graph);
+	remove_dangling_edges(&mut minor_graph);
+	sort_outgoing_edges_by_angle(&mut minor_graph);
+
+	let dual_graph = compute_dual(&minor_graph, false)?;
+	let nesting_trees = compute_nesting_tree(&dual_graph);
+	let DualGraph { edges, vertices, .. } = &dual_graph;
+
+	let contains_point = |face_key: DualVertexKey| {
+		let crossings: usize = vertices[face_key]
+			.incident_edges
+			.iter()
+			.flat_map(|&edge_key| &edges[edge_key].segments)
+			.map(|segment| path_segment_horizontal_ray_intersection_count(segment, point))
+			.sum();
+		crossings % 2 == 1
+	};
+
+	// Descend into the components nested inside the face containing the point, until no nested component has a face containing it
+	let mut trees = nesting_trees.as_slice();
+	let mut enclosing = None;
+	while let Some((tree, face_key)) = trees.iter().find_map(|tree| {
+		let component = &tree.component;
+		let face_key = component
+			.vertices
+			.iter()
+			.copied()
+			.find(|&face_key| Some(face_key) != component.outer_face && contains_point(face_key))?;
+		Some((tree, face_key))
+	}) {
+		enclosing = Some((tree, face_key));
+		trees = tree.outgoing_edges.get(&face_key).map(Vec::as_slice).unwrap_or_default();
+	}
+
+	Ok(enclosing.map(|(tree, face_key)| face_with_holes(tree, face_key, edges, vertices)))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -1666,16 +1898,16 @@ mod tests {
 		// of your split_at_intersections function
 	}
 
-	fn unsplit_edges() -> Vec<(PathSegment, u8)> {
+	fn unsplit_edges() -> Vec<(PathSegment, usize)> {
 		let unsplit_edges = vec![
-			(PathSegment::Arc(DVec2::new(39., 20.), 19., 19., 0., false, true, DVec2::new(20., 39.)), 1),
-			(PathSegment::Arc(DVec2::new(20., 39.), 19., 19., 0., false, true, DVec2::new(1., 20.)), 1),
-			(PathSegment::Arc(DVec2::new(1., 20.), 19., 19., 0., false, true, DVec2::new(20., 1.)), 1),
-			(PathSegment::Arc(DVec2::new(20., 1.), 19., 19., 0., false, true, DVec2::new(39., 20.)), 1),
-			(PathSegment::Arc(DVec2::new(47., 28.), 19., 19., 0., false, true, DVec2::new(28., 47.)), 2),
-			(PathSegment::Arc(DVec2::new(28., 47.), 19., 19., 0., false, true, DVec2::new(9., 28.)), 2),
-			(PathSegment::Arc(DVec2::new(9., 28.), 19., 19., 0., false, true, DVec2::new(28., 9.)), 2),
-			(PathSegment::Arc(DVec2::new(28., 9.), 19., 19., 0., false, true, DVec2::new(47., 28.)), 2),
+			(PathSegment::Arc(DVec2::new(39., 20.), 19., 19., 0., false, true, DVec2::new(20., 39.)), 0),
+			(PathSegment::Arc(DVec2::new(20., 39.), 19., 19., 0., false, true, DVec2::new(1., 20.)), 0),
+			(PathSegment::Arc(DVec2::new(1., 20.), 19., 19., 0., false, true, DVec2::new(20., 1.)), 0),
+			(PathSegment::Arc(DVec2::new(20., 1.), 19., 19., 0., false, true, DVec2::new(39., 20.)), 0),
+			(PathSegment::Arc(DVec2::new(47., 28.), 19., 19., 0., false, true, DVec2::new(28., 47.)), 1),
+			(PathSegment::Arc(DVec2::new(28., 47.), 19., 19., 0., false, true, DVec2::new(9., 28.)), 1),
+			(PathSegment::Arc(DVec2::new(9., 28.), 19., 19., 0., false, true, DVec2::new(28., 9.)), 1),
+			(PathSegment::Arc(DVec2::new(28., 9.), 19., 19., 0., false, true, DVec2::new(47., 28.)), 1),
 		];
 		unsplit_edges
 	}
@@ -1714,8 +1946,8 @@ mod tests {
 		}
 
 		// Check that parents are correctly assigned
-		assert_eq!(minor_graph.edges.values().filter(|e| e.parent == 1).count(), 4, "Expected 4 edges with parent 1");
-		assert_eq!(minor_graph.edges.values().filter(|e| e.parent == 2).count(), 4, "Expected 4 edges with parent 2");
+		assert_eq!(minor_graph.edges.values().filter(|e| e.parent == InputSet::single(0)).count(), 4, "Expected 4 edges with parent 0");
+		assert_eq!(minor_graph.edges.values().filter(|e| e.parent == InputSet::single(1)).count(), 4, "Expected 4 edges with parent 1");
 	}
 
 	fn print_minor_graph_state(graph: &MinorGraph) {
@@ -1729,7 +1961,7 @@ mod tests {
 
 		for (edge_key, edge) in &graph.edges {
 			eprintln!("    Edge {:?}:", edge_key);
-			eprintln!("      Parent: {}", edge.parent);
+			eprintln!("      Parent: {:?}", edge.parent);
 			eprintln!("      Twin: {:?}", edge.twin);
 			eprintln!("      Incident vertices: {:?}", edge.incident_vertices);
 		}
@@ -1833,4 +2065,31 @@ mod tests {
 		assert!(bounding_box_intersects_horizontal_ray(&bbox, DVec2::new(10., 20.)));
 		assert!(!bounding_box_intersects_horizontal_ray(&bbox, DVec2::new(30., 40.)));
 	}
+
+	#[test]
+	fn test_enclosing_face() {
+		let square = |min: f64, max: f64| {
+			let [a, b, c, d] = [DVec2::new(min, min), DVec2::new(max, min), DVec2::new(max, max), DVec2::new(min, max)];
+			vec![PathSegment::Line(a, b), PathSegment::Line(b, c), PathSegment::Line(c, d), PathSegment::Line(d, a)]
+		};
+		let outer = square(0., 30.);
+		let inner = square(10., 20.);
+		// An open line across the ring between the squares, above the inner square
+		let line = vec![PathSegment::Line(DVec2::new(-5., 25.), DVec2::new(35., 25.))];
+		let paths = [&outer, &inner, &line];
+
+		let area = |path: &Path| path.iter().map(|segment| segment.start().perp_dot(segment.end()) / 2.).sum::<f64>().abs();
+
+		// The inner square is a face of its own
+		let face = enclosing_face(&paths, DVec2::new(15., 15.)).unwrap().unwrap();
+		assert!((area(&face) - 100.).abs() < 1e-6, "{face:?}");
+
+		// Below the line, the ring has a hole where the inner square is
+		let face = enclosing_face(&paths, DVec2::new(25., 5.)).unwrap().unwrap();
+		assert!(face.iter().any(|segment| segment.start() == DVec2::new(10., 10.)), "{face:?}");
+		assert!(face.iter().all(|segment| segment.start().y <= 25. && segment.end().y <= 25.), "{face:?}");
+
+		// The ends of the line sticking out don't enclose anything
+		assert_eq!(enclosing_face(&paths, DVec2::new(-2., 20.)).unwrap(), None);
+	}
 }
@@ -0,0 +1,342 @@
+//! An opt-in robust mode for boolean operations which repairs degenerate input instead of giving up.
+//!
+//! Inputs drawn by hand or produced by earlier operations often contain vertices which are almost, but not exactly,
+//! coincident, vertices lying a hair away from another path's edge, or segments and subpaths which have collapsed.
+//! These configurations can make the regular [`path_boolean_multiple_fragments`](crate::path_boolean_multiple_fragments) fail with a [`BooleanError`].
+//!
+//! Near-coincidences don't always cause an error, they can also silently produce a result covering the wrong area.
+//! [`path_boolean_robust`] therefore always snaps the inputs together using [`EPS`] based tolerances before running
+//! the operation, and if it still fails, retries it a number of times on slightly perturbed copies of the snapped
+//! inputs. Every modification made along the way is recorded in a [`RepairReport`]. It also handles faces whose boundary
+//! touches itself, which the regular operation can cut short or mistake for the outer face.
+
+use crate::aabb::bounding_box_max_extent;
+use crate::path_boolean::{BooleanError, EPS, FillRule, PathBooleanOperation, PathFragment, boolean_fragments};
+use crate::{InputSet, Path, PathSegment};
+use glam::DVec2;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Configures how [`path_boolean_robust`] repairs its inputs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RobustOptions {
+	/// Vertices closer than this distance are merged, and lines closer than this to a vertex are split at it.
+	pub snap_tolerance: f64,
+	/// Segments with a bounding box smaller than this after snapping are removed.
+	pub degenerate_tolerance: f64,
+	/// The distance vertices are moved by on the first perturbation attempt, which grows linearly with every further attempt.
+	pub perturbation: f64,
+	/// How often the operation is retried with perturbed inputs after snapping alone didn't help.
+	pub max_perturbation_attempts: usize,
+}
+
+impl Default for RobustOptions {
+	fn default() -> Self {
+		Self {
+			snap_tolerance: EPS.linear,
+			degenerate_tolerance: EPS.point,
+			perturbation: EPS.linear,
+			max_perturbation_attempts: 4,
+		}
+	}
+}
+
+/// A modification made to one of the inputs of a boolean operation to get it to succeed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Repair {
+	/// Vertices of the input were merged with nearby vertices.
+	SnappedVertices { input: usize, count: usize },
+	/// Lines of the input were split at the given number of nearby vertices.
+	SplitEdges { input: usize, count: usize },
+	/// Segments of the input which had collapsed to a point were removed.
+	RemovedDegenerateSegments { input: usize, count: usize },
+	/// Subpaths of the input which had collapsed to a line or a point were removed.
+	RemovedDegenerateSubpaths { input: usize, count: usize },
+	/// All vertices of the input were moved by up to the given distance.
+	Perturbed { input: usize, magnitude: f64 },
+}
+
+impl Repair {
+	/// The index of the input which was modified.
+	pub fn input(&self) -> usize {
+		match *self {
+			Self::SnappedVertices { input, .. }
+			| Self::SplitEdges { input, .. }
+			| Self::RemovedDegenerateSegments { input, .. }
+			| Self::RemovedDegenerateSubpaths { input, .. }
+			| Self::Perturbed { input, .. } => input,
+		}
+	}
+}
+
+/// Describes how the inputs of [`path_boolean_robust`] were repaired.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepairReport {
+	/// The modifications made to the inputs of the attempt which succeeded.
+	pub repairs: Vec<Repair>,
+	/// How many times the operation was run.
+	pub attempts: usize,
+	/// The errors of the failed attempts, in order.
+	pub errors: Vec<BooleanError>,
+}
+
+impl RepairReport {
+	/// Whether the operation succeeded without modifying the inputs.
+	pub fn is_empty(&self) -> bool {
+		self.repairs.is_empty()
+	}
+
+	/// The indices of the inputs which had to be modified.
+	pub fn repaired_inputs(&self) -> InputSet {
+		self.repairs.iter().map(Repair::input).collect()
+	}
+}
+
+/// Performs a boolean operation on any number of paths, repairing degenerate inputs.
+///
+/// The result is the same as [`path_boolean_multiple_fragments`](crate::path_boolean_multiple_fragments) for inputs without near-coincident vertices or edges.
+/// Otherwise, the inputs are snapped and, if the operation still fails, perturbed until it succeeds or the attempts
+/// configured in `options` are exhausted.
+///
+/// # Examples
+///
+/// ```
+/// use path_bool::{path_boolean_robust, path_from_path_data, FillRule, PathBooleanOperation, RobustOptions};
+///
+/// let a = path_from_path_data("M 0 0 L 10 0 L 10 10 L 0 10 Z").unwrap();
+/// // The corner of this square almost, but not exactly, touches the other square's corner
+/// let b = path_from_path_data("M 10.00000001 10 L 20 10 L 20 20 L 10 20 Z").unwrap();
+///
+/// let (fragments, report) = path_boolean_robust(&[(&a, FillRule::NonZero), (&b, FillRule::NonZero)], PathBooleanOperation::Union, &RobustOptions::default()).unwrap();
+///
+/// assert_eq!(fragments.len(), 1);
+/// assert_eq!(report.repaired_inputs().iter().collect::<Vec<_>>(), [1]);
+/// ```
+///
+/// # Errors
+///
+/// Returns the [`BooleanError`] of the last attempt if none of the attempts succeeded.
+pub fn path_boolean_robust(paths: &[(&Path, FillRule)], op: PathBooleanOperation, options: &RobustOptions) -> Result<(Vec<PathFragment>, RepairReport), BooleanError> {
+	let mut report = RepairReport::default();
+	let (snapped, snap_repairs) = snap_inputs(paths, options);
+
+	for attempt in 0..=options.max_perturbation_attempts {
+		let mut repairs = snap_repairs.clone();
+		let inputs = if attempt == 0 {
+			snapped.clone()
+		} else {
+			let magnitude = options.perturbation * attempt as f64;
+			repairs.extend((0..snapped.len()).map(|input| Repair::Perturbed { input, magnitude }));
+			snapped.iter().enumerate().map(|(input, path)| perturb(path, input, attempt, magnitude)).collect()
+		};
+
+		let inputs: Vec<_> = inputs.iter().zip(paths).map(|(path, &(_, fill_rule))| (path, fill_rule)).collect();
+
+		report.attempts += 1;
+		match boolean_fragments(&inputs, op, true) {
+			Ok(fragments) => {
+				report.repairs = repairs;
+				return Ok((fragments, report));
+			}
+			Err(error) => report.errors.push(error),
+		}
+	}
+
+	Err(report.errors.pop().expect("At least one attempt was made"))
+}
+
+/// Merges nearby vertices across all inputs, splits lines at nearby vertices, and removes collapsed segments and subpaths.
+fn snap_inputs(paths: &[(&Path, FillRule)], options: &RobustOptions) -> (Vec<Path>, Vec<Repair>) {
+	let mut repairs = Vec::new();
+	let tolerance = options.snap_tolerance;
+	let is_collapsed = |segment: &PathSegment| segment.start() == segment.end() && bounding_box_max_extent(&segment.bounding_box()) < options.degenerate_tolerance;
+
+	// Merge each vertex with the first vertex within the tolerance, so vertices shared between inputs become identical
+	let mut grid = PointGrid::new(tolerance);
+	let mut snapped: Vec<Path> = Vec::with_capacity(paths.len());
+	for (input, (path, _)) in paths.iter().enumerate() {
+		let mut count = 0;
+		let mut collapsed = 0;
+		let mut snapped_path = Vec::with_capacity(path.len());
+		for segment in path.iter() {
+			let snapped_segment = map_endpoints(segment, |point| {
+				let snapped = grid.snap(point);
+				count += usize::from(snapped != point);
+				snapped
+			});
+
+			// Segments which were already collapsed, like the closing segment of most paths, are handled fine without counting as a repair
+			if is_collapsed(&snapped_segment) {
+				collapsed += usize::from(!is_collapsed(segment));
+				continue;
+			}
+			snapped_path.push(snapped_segment);
+		}
+
+		if count > 0 {
+			repairs.push(Repair::SnappedVertices { input, count });
+		}
+		if collapsed > 0 {
+			repairs.push(Repair::RemovedDegenerateSegments { input, count: collapsed });
+		}
+		snapped.push(snapped_path);
+	}
+
+	// Split lines at the vertices lying just beside them, so edges which almost overlap end up sharing vertices and become identical
+	// The vertices are sorted by their x coordinate, so only those within the horizontal extent of a line padded by the tolerance need to be checked
+	let mut vertices: Vec<DVec2> = snapped.iter().flatten().map(PathSegment::start).collect();
+	vertices.sort_by(|a, b| a.x.total_cmp(&b.x));
+	for (input, path) in snapped.iter_mut().enumerate() {
+		let mut count = 0;
+		*path = path
+			.iter()
+			.flat_map(|segment| {
+				let PathSegment::Line(start, end) = *segment else { return vec![*segment] };
+
+				let (min, max) = (start.min(end) - tolerance, start.max(end) + tolerance);
+				let first = vertices.partition_point(|vertex| vertex.x < min.x);
+				let nearby = vertices[first..].iter().take_while(|vertex| vertex.x <= max.x).filter(|vertex| (min.y..=max.y).contains(&vertex.y));
+
+				let mut splits: Vec<(f64, DVec2)> = nearby.filter_map(|&vertex| Some((line_parameter_beside(vertex, start, end, tolerance)?, vertex))).collect();
+				splits.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+				splits.dedup_by(|(_, a), (_, b)| a == b);
+				count += splits.len();
+
+				let points: Vec<_> = std::iter::once(start).chain(splits.into_iter().map(|(_, vertex)| vertex)).chain(std::iter::once(end)).collect();
+				points.windows(2).map(|pair| PathSegment::Line(pair[0], pair[1])).collect()
+			})
+			.collect();
+
+		if count > 0 {
+			repairs.push(Repair::SplitEdges { input, count });
+		}
+	}
+
+	// Remove subpaths which have collapsed into a line or a point and don't enclose any area
+	for (input, path) in snapped.iter_mut().enumerate() {
+		let mut count = 0;
+		*path = subpaths(path)
+			.filter(|subpath| {
+				let collapsed = is_subpath_collapsed(subpath, tolerance);
+				count += usize::from(collapsed);
+				!collapsed
+			})
+			.flatten()
+			.copied()
+			.collect();
+
+		if count > 0 {
+			repairs.push(Repair::RemovedDegenerateSubpaths { input, count });
+		}
+	}
+
+	(snapped, repairs)
+}
+
+/// Splits the path into its subpaths, which are the runs of segments each starting where the previous one ends.
+fn subpaths(path: &Path) -> impl Iterator<Item = &[PathSegment]> {
+	path.chunk_by(|previous, next| previous.end() == next.start())
+}
+
+/// Whether all points of the subpath lie within the tolerance of a single line.
+fn is_subpath_collapsed(subpath: &[PathSegment], tolerance: f64) -> bool {
+	// Arcs are approximated by cubic segments, since they have no control points to check
+	let points: Vec<DVec2> = subpath
+		.iter()
+		.flat_map(|segment| segment.arc_segment_to_cubics(PI / 16.))
+		.flat_map(|segment| segment.to_cubic())
+		.collect();
+	let Some(&first) = points.first() else { return true };
+	let farthest = points.iter().copied().max_by(|a, b| first.distance_squared(*a).total_cmp(&first.distance_squared(*b))).unwrap_or(first);
+
+	let direction = farthest - first;
+	if direction.length() < tolerance {
+		return true;
+	}
+	points.iter().all(|point| direction.perp_dot(*point - first).abs() / direction.length() < tolerance)
+}
+
+/// Moves every vertex of the path by a pseudo-random offset of up to `magnitude`, which is the same for identical vertices so the path stays closed.
+fn perturb(path: &Path, input: usize, attempt: usize, magnitude: f64) -> Path {
+	let seed = splitmix64((input as u64) << 32 | attempt as u64);
+	path.iter()
+		.map(|segment| {
+			map_endpoints(segment, |point| {
+				let x = splitmix64(seed ^ point.x.to_bits());
+				let y = splitmix64(x ^ point.y.to_bits());
+				let unit = |bits: u64| (bits >> 11) as f64 / (1u64 << 53) as f64 * 2. - 1.;
+				point + DVec2::new(unit(x), unit(y)) * magnitude
+			})
+		})
+		.collect()
+}
+
+/// Applies the function to the start and end point of the segment, moving the adjacent control points along with them.
+fn map_endpoints(segment: &PathSegment, mut f: impl FnMut(DVec2) -> DVec2) -> PathSegment {
+	let (start, end) = (segment.start(), segment.end());
+	let (new_start, new_end) = (f(start), f(end));
+	let (start_delta, end_delta) = (new_start - start, new_end - end);
+
+	match *segment {
+		PathSegment::Line(..) => PathSegment::Line(new_start, new_end),
+		PathSegment::Cubic(_, handle_start, handle_end, _) => PathSegment::Cubic(new_start, handle_start + start_delta, handle_end + end_delta, new_end),
+		PathSegment::Quadratic(_, handle, _) => PathSegment::Quadratic(new_start, handle + (start_delta + end_delta) / 2., new_end),
+		PathSegment::Arc(_, rx, ry, rotation, large_arc, sweep, _) => PathSegment::Arc(new_start, rx, ry, rotation, large_arc, sweep, new_end),
+	}
+}
+
+/// Returns the parameter along the line of the point if it lies within the tolerance of the line's interior, but not exactly on it.
+fn line_parameter_beside(point: DVec2, start: DVec2, end: DVec2, tolerance: f64) -> Option<f64> {
+	let direction = end - start;
+	let length_squared = direction.length_squared();
+	if length_squared < tolerance * tolerance {
+		return None;
+	}
+
+	let t = (point - start).dot(direction) / length_squared;
+	let distance = point.distance(start + direction * t);
+
+	// Vertices near the line's endpoints have already been merged with them
+	let margin = tolerance / length_squared.sqrt();
+	let interior = (margin..=1. - margin).contains(&t);
+
+	(interior && distance > 0. && distance < tolerance).then_some(t)
+}
+
+/// A spatial hash of vertices used to find an already seen vertex within a tolerance.
+struct PointGrid {
+	tolerance: f64,
+	cells: HashMap<(i64, i64), Vec<DVec2>>,
+}
+
+impl PointGrid {
+	fn new(tolerance: f64) -> Self {
+		Self { tolerance, cells: HashMap::new() }
+	}
+
+	fn cell(&self, point: DVec2) -> (i64, i64) {
+		let cell = (point / self.tolerance).floor();
+		(cell.x as i64, cell.y as i64)
+	}
+
+	/// Returns the first seen vertex within the tolerance of the point, or remembers the point if there is none.
+	fn snap(&mut self, point: DVec2) -> DVec2 {
+		let (x, y) = self.cell(point);
+		let neighbors = (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (x + dx, y + dy)));
+		for cell in neighbors {
+			if let Some(existing) = self.cells.get(&cell).and_then(|points| points.iter().find(|existing| existing.distance(point) < self.tolerance)) {
+				return *existing;
+			}
+		}
+
+		self.cells.entry((x, y)).or_default().push(point);
+		point
+	}
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+	x = x.wrapping_add(0x9E3779B97F4A7C15);
+	x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+	x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+	x ^ (x >> 31)
+}
@@ -0,0 +1,139 @@
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+
+/// A set of input path indices, used to track which of the inputs of a boolean operation an edge or a face belongs to.
+///
+/// The first 64 inputs are stored inline, so the common case of a handful of inputs never allocates.
+#[derive(Clone, Default, Eq)]
+pub struct InputSet {
+	first: u64,
+	rest: Vec<u64>,
+}
+
+impl InputSet {
+	/// Creates a set containing only the input with the given index.
+	pub fn single(index: usize) -> Self {
+		let mut set = Self::default();
+		set.insert(index);
+		set
+	}
+
+	pub fn insert(&mut self, index: usize) {
+		match index / 64 {
+			0 => self.first |= 1 << index,
+			word => {
+				if self.rest.len() < word {
+					self.rest.resize(word, 0);
+				}
+				self.rest[word - 1] |= 1 << (index % 64);
+			}
+		}
+	}
+
+	pub fn contains(&self, index: usize) -> bool {
+		match index / 64 {
+			0 => self.first & (1 << index) != 0,
+			word => self.rest.get(word - 1).is_some_and(|bits| bits & (1 << (index % 64)) != 0),
+		}
+	}
+
+	/// Adds all inputs of the other set to this set.
+	pub fn union_with(&mut self, other: &Self) {
+		self.first |= other.first;
+		if self.rest.len() < other.rest.len() {
+			self.rest.resize(other.rest.len(), 0);
+		}
+		for (bits, other_bits) in self.rest.iter_mut().zip(&other.rest) {
+			*bits |= other_bits;
+		}
+	}
+
+	/// Whether the sets have any input in common.
+	pub fn intersects(&self, other: &Self) -> bool {
+		self.first & other.first != 0 || self.rest.iter().zip(&other.rest).any(|(bits, other_bits)| bits & other_bits != 0)
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.first == 0 && self.rest.iter().all(|&bits| bits == 0)
+	}
+
+	/// The number of inputs in the set.
+	pub fn len(&self) -> usize {
+		self.first.count_ones() as usize + self.rest.iter().map(|bits| bits.count_ones() as usize).sum::<usize>()
+	}
+
+	/// Iterates over the indices of the inputs in the set in ascending order.
+	pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+		std::iter::once(self.first)
+			.chain(self.rest.iter().copied())
+			.enumerate()
+			.flat_map(|(word, bits)| (0..64).filter(move |bit| bits & (1 << bit) != 0).map(move |bit| word * 64 + bit))
+	}
+
+	/// The words after the first one, without the trailing empty words which don't change what the set contains.
+	fn rest_words(&self) -> &[u64] {
+		let length = self.rest.iter().rposition(|&bits| bits != 0).map_or(0, |index| index + 1);
+		&self.rest[..length]
+	}
+
+	/// The highest input index in the set, which is the topmost input if inputs are ordered from bottom to top.
+	pub fn last(&self) -> Option<usize> {
+		let (word, bits) = self
+			.rest
+			.iter()
+			.enumerate()
+			.rev()
+			.find(|(_, bits)| **bits != 0)
+			.map_or((0, self.first), |(word, &bits)| (word + 1, bits));
+		(bits != 0).then(|| word * 64 + 63 - bits.leading_zeros() as usize)
+	}
+}
+
+impl PartialEq for InputSet {
+	fn eq(&self, other: &Self) -> bool {
+		self.first == other.first && self.rest_words() == other.rest_words()
+	}
+}
+
+impl Hash for InputSet {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.first.hash(state);
+		self.rest_words().hash(state);
+	}
+}
+
+impl FromIterator<usize> for InputSet {
+	fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+		let mut set = Self::default();
+		for index in iter {
+			set.insert(index);
+		}
+		set
+	}
+}
+
+impl Debug for InputSet {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.debug_set().entries(self.iter()).finish()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::InputSet;
+	use std::hash::{BuildHasher, RandomState};
+
+	#[test]
+	fn trailing_empty_words_are_ignored() {
+		let set = InputSet::from_iter([3, 70]);
+		let padded = InputSet {
+			rest: vec![set.rest[0], 0, 0],
+			..set.clone()
+		};
+
+		assert_eq!(set, padded);
+		let hasher = RandomState::new();
+		assert_eq!(hasher.hash_one(&set), hasher.hash_one(&padded));
+		assert_ne!(set, InputSet::single(3));
+	}
+}
@@ -1,6 +1,9 @@
 use crate::path_boolean::{self, FillRule, PathBooleanOperation};
 use crate::path_data::{path_from_path_data, path_to_path_data};
+use crate::test::{SampleGrid, coverage};
+use crate::{Path, RobustOptions, path_boolean_robust};
 use core::panic;
+use glam::DVec2;
 use glob::glob;
 use image::{DynamicImage, GenericImageView, RgbaImage};
 use resvg::render;
@@ -192,3 +195,258 @@ fn compare_images(img1: &DynamicImage, img2: &DynamicImage, tolerance: u8) -> bo
 	}
 	false
 }
+
+/// How many random cases [`fuzz`] checks, which can be raised for a longer fuzzing session.
+const FUZZ_ITERATIONS_VARIABLE: &str = "PATH_BOOL_FUZZ_ITERATIONS";
+/// The seed of the first random case, so a fuzzing session can continue where a previous one stopped.
+const FUZZ_SEED_VARIABLE: &str = "PATH_BOOL_FUZZ_SEED";
+/// When set, failing cases found by [`fuzz`] are recorded as regression fixtures instead of being written to the target directory.
+const FUZZ_RECORD_VARIABLE: &str = "PATH_BOOL_FUZZ_RECORD";
+const FUZZ_REGRESSIONS_DIRECTORY: &str = "fuzz-regressions";
+/// The fraction of sampled points allowed to disagree with the expected result, covering points which lie within the snapping tolerance of an edge.
+const FUZZ_COVERAGE_TOLERANCE: f64 = 0.002;
+/// The grid sampled to check the area covered by the results, spanning the coarse grid which [`fuzz_case`] places its shapes on.
+const FUZZ_GRID: SampleGrid = SampleGrid {
+	origin: DVec2::new(0.137, 0.291),
+	spacing: 1.,
+	size: 100,
+};
+
+const FUZZ_OPS: [(&str, PathBooleanOperation); 5] = [
+	("union", PathBooleanOperation::Union),
+	("difference", PathBooleanOperation::Difference),
+	("intersection", PathBooleanOperation::Intersection),
+	("exclusion", PathBooleanOperation::Exclusion),
+	("division", PathBooleanOperation::Division),
+];
+
+/// Runs the boolean operations in robust mode on randomly generated degenerate shapes, storing every failing case next to the test binary in the
+/// target directory. With [`FUZZ_RECORD_VARIABLE`] set, they are stored in the regression fixtures directory so [`fuzz_regressions`] checks them from then on.
+#[test]
+fn fuzz() {
+	let iterations = std::env::var(FUZZ_ITERATIONS_VARIABLE).ok().and_then(|value| value.parse().ok()).unwrap_or(64);
+	let first_seed = std::env::var(FUZZ_SEED_VARIABLE).ok().and_then(|value| value.parse().ok()).unwrap_or(0);
+	let output_directory = if std::env::var_os(FUZZ_RECORD_VARIABLE).is_some() {
+		PathBuf::from(FUZZ_REGRESSIONS_DIRECTORY)
+	} else {
+		// The test binary lives in `target/<profile>/deps`
+		let executable = std::env::current_exe().expect("Failed to find the test executable");
+		executable
+			.ancestors()
+			.nth(2)
+			.expect("The test executable should be inside the target directory")
+			.join(FUZZ_REGRESSIONS_DIRECTORY)
+	};
+
+	let mut failures = Vec::new();
+	for seed in first_seed..first_seed + iterations {
+		let paths = fuzz_case(seed);
+		let errors = check_robust(&paths);
+		if !errors.is_empty() {
+			let fixture = output_directory.join(format!("seed-{seed}.svg"));
+			fs::create_dir_all(&output_directory).expect("Failed to create fuzz regressions directory");
+			fs::write(&fixture, fixture_svg(&paths, &errors)).expect("Failed to write fuzz regression fixture");
+			failures.push(format!("{}: {}", fixture.display(), errors.join(", ")));
+		}
+	}
+
+	assert!(failures.is_empty(), "Robust mode failed on {} fuzz case(s), stored as:\n{}", failures.len(), failures.join("\n"));
+}
+
+/// Checks all cases previously found by [`fuzz`].
+#[test]
+fn fuzz_regressions() {
+	let mut failures = Vec::new();
+	for entry in glob(&format!("{FUZZ_REGRESSIONS_DIRECTORY}/*.svg")).expect("Failed to read glob pattern") {
+		let fixture = entry.expect("Failed to get fixture entry");
+		let content = fs::read_to_string(&fixture).expect("Failed to read fixture");
+
+		let mut paths = Vec::new();
+		for event in svg::read(&content).expect("Failed to parse fixture") {
+			if let Event::Tag("path", _, attributes) = event {
+				let data = attributes.get("d").expect("Path data not found");
+				let fill_rule = attributes.get("fill-rule").map(|value| value.to_string()).unwrap_or_default();
+				paths.push((path_from_path_data(data).unwrap(), get_fill_rule(&fill_rule)));
+			}
+		}
+
+		let errors = check_robust(&paths);
+		if !errors.is_empty() {
+			failures.push(format!("{}: {}", fixture.display(), errors.join(", ")));
+		}
+	}
+
+	assert!(failures.is_empty(), "Robust mode failed on {} regression fixture(s):\n{}", failures.len(), failures.join("\n"));
+}
+
+/// Runs every operation in robust mode, returning a description of each operation which failed, panicked, or covers the wrong area.
+fn check_robust(paths: &[(Path, FillRule)]) -> Vec<String> {
+	let inputs: Vec<_> = paths.iter().map(|(path, fill_rule)| (path, *fill_rule)).collect();
+	let input_coverage: Vec<_> = paths.iter().map(|(path, _)| coverage(path, FUZZ_GRID)).collect();
+
+	let mut errors = Vec::new();
+	for (op_name, op) in FUZZ_OPS {
+		let result = std::panic::catch_unwind(|| path_boolean_robust(&inputs, op, &RobustOptions::default()));
+		let fragments = match result {
+			Ok(Ok((fragments, _))) => fragments,
+			Ok(Err(error)) => {
+				errors.push(format!("{op_name} failed with {error:?}"));
+				continue;
+			}
+			Err(_) => {
+				errors.push(format!("{op_name} panicked"));
+				continue;
+			}
+		};
+
+		let result: Path = fragments.into_iter().flat_map(|fragment| fragment.path).collect();
+		let actual = coverage(&result, FUZZ_GRID);
+		let mismatches = (0..actual.len())
+			.filter(|&sample| {
+				let a = input_coverage[0][sample];
+				let b = input_coverage[1][sample];
+				let expected = match op {
+					PathBooleanOperation::Union => a || b,
+					PathBooleanOperation::Difference => a && !b,
+					PathBooleanOperation::Intersection => a && b,
+					PathBooleanOperation::Exclusion => a != b,
+					_ => a,
+				};
+				actual[sample] != expected
+			})
+			.count();
+		if mismatches as f64 > actual.len() as f64 * FUZZ_COVERAGE_TOLERANCE {
+			errors.push(format!("{op_name} covers the wrong area at {mismatches} sample(s)"));
+		}
+	}
+	errors
+}
+
+/// Generates two shapes on a coarse grid so they often share vertices and edges, with some vertices nudged by less than the snapping tolerance.
+fn fuzz_case(seed: u64) -> Vec<(Path, FillRule)> {
+	let mut rng = FuzzRng(seed.wrapping_mul(0x9E3779B97F4A7C15) | 1);
+
+	(0..2)
+		.map(|_| {
+			let grid_point = |rng: &mut FuzzRng| DVec2::new(rng.below(11) as f64, rng.below(11) as f64) * 10.;
+			let nudge = |rng: &mut FuzzRng, point: DVec2| if rng.below(4) == 0 { point + DVec2::new(rng.signed(), rng.signed()) * 1e-5 } else { point };
+			let point = |rng: &mut FuzzRng| {
+				let point = grid_point(rng);
+				nudge(rng, point)
+			};
+
+			match rng.below(4) {
+				// An axis-aligned rectangle
+				0 => {
+					let (a, b) = (point(&mut rng), point(&mut rng));
+					let (min, max) = (a.min(b), a.max(b).max(a.min(b) + 10.));
+					let data = format!("M {} {} L {} {} L {} {} L {} {} Z", min.x, min.y, max.x, min.y, max.x, max.y, min.x, max.y);
+					(path_from_path_data(&data).unwrap(), FillRule::NonZero)
+				}
+				// A triangle, which never intersects itself
+				1 => {
+					let [a, b, c] = [point(&mut rng), point(&mut rng), point(&mut rng)];
+					let data = format!("M {} {} L {} {} L {} {} Z", a.x, a.y, b.x, b.y, c.x, c.y);
+					(path_from_path_data(&data).unwrap(), FillRule::NonZero)
+				}
+				// A circle made of cubic segments, centered on a grid point
+				2 => {
+					let center = point(&mut rng);
+					let radius = 10. * (1 + rng.below(4)) as f64;
+					let k = radius * 0.552284749831;
+					let (x, y) = (center.x, center.y);
+					let data = format!(
+						"M {} {y} C {} {} {} {} {x} {} C {} {} {} {} {} {y} C {} {} {} {} {x} {} C {} {} {} {} {} {y} Z",
+						x + radius,
+						x + radius,
+						y + k,
+						x + k,
+						y + radius,
+						y + radius,
+						x - k,
+						y + radius,
+						x - radius,
+						y + k,
+						x - radius,
+						x - radius,
+						y - k,
+						x - k,
+						y - radius,
+						y - radius,
+						x + k,
+						y - radius,
+						x + radius,
+						y - k,
+						x + radius,
+					);
+					(path_from_path_data(&data).unwrap(), FillRule::NonZero)
+				}
+				// A self-intersecting polygon, which the coverage check only supports with the even-odd fill rule
+				_ => {
+					// Edges of the same path which overlap each other are merged into one, losing how often they cover the area beside them,
+					// so points which would make the polygon double back along its own edges are skipped
+					let mut points: Vec<DVec2> = Vec::new();
+					for _ in 0..3 + rng.below(4) {
+						let candidate = grid_point(&mut rng);
+						let collinear = |a: DVec2, b: DVec2, c: DVec2| (b - a).perp_dot(c - a) == 0.;
+						let doubles_back = points.contains(&candidate)
+							|| points.len() >= 2 && collinear(points[points.len() - 2], points[points.len() - 1], candidate)
+							|| points.len() >= 2 && collinear(points[points.len() - 1], candidate, points[0])
+							|| points.len() >= 2 && collinear(candidate, points[0], points[1]);
+						if !doubles_back {
+							points.push(candidate);
+						}
+					}
+					if points.len() < 3 {
+						points = vec![DVec2::new(0., 0.), DVec2::new(50., 100.), DVec2::new(100., 20.)];
+					}
+					let points: Vec<_> = points.into_iter().map(|point| nudge(&mut rng, point)).collect();
+					let data = points
+						.iter()
+						.enumerate()
+						.map(|(index, p)| format!("{} {} {}", if index == 0 { "M" } else { "L" }, p.x, p.y))
+						.collect::<Vec<_>>()
+						.join(" ");
+					(path_from_path_data(&format!("{data} Z")).unwrap(), FillRule::EvenOdd)
+				}
+			}
+		})
+		.collect()
+}
+
+fn fixture_svg(paths: &[(Path, FillRule)], errors: &[String]) -> String {
+	let mut svg = format!(
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"100\" viewBox=\"0 0 100 100\">\n<!-- {} -->\n",
+		errors.join(", ")
+	);
+	for (path, fill_rule) in paths {
+		let fill_rule = match fill_rule {
+			FillRule::NonZero => "nonzero",
+			FillRule::EvenOdd => "evenodd",
+		};
+		svg.push_str(&format!("<path d=\"{}\" fill-rule=\"{fill_rule}\"/>\n", path_to_path_data(path, 1e-9)));
+	}
+	svg.push_str("</svg>\n");
+	svg
+}
+
+/// A small deterministic xorshift generator, so every fuzz case can be reproduced from its seed alone.
+struct FuzzRng(u64);
+
+impl FuzzRng {
+	fn next(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+
+	fn below(&mut self, bound: u64) -> u64 {
+		self.next() % bound
+	}
+
+	/// A value in the range -1 to 1.
+	fn signed(&mut self) -> f64 {
+		(self.next() >> 11) as f64 / (1u64 << 53) as f64 * 2. - 1.
+	}
+}
@@ -1,71 +1,125 @@
-use crate::tiff::Ifd;
 use crate::tiff::file::TiffRead;
-use crate::tiff::tags::SonyDataOffset;
-use crate::{RawImage, SubtractBlack, Transform};
+use crate::tiff::tags::{CfaPattern, ImageLength, ImageWidth, SonyDataOffset, StripOffsets, Tag};
+use crate::tiff::{Ifd, TiffError};
+use crate::{ColorFilterArray, DecoderError, RawImage, SubtractBlack, Transform};
 use bitstream_io::{BE, BitRead, BitReader, Endianness};
+use rawkit_proc_macros::Tag;
 use std::io::{Read, Seek};
 
-pub fn decode_a100<R: Read + Seek>(ifd: Ifd, file: &mut TiffRead<R>) -> RawImage {
-	let data_offset = ifd.get_value::<SonyDataOffset, _>(file).unwrap();
+#[allow(dead_code)]
+#[derive(Tag)]
+struct Arw1Ifd {
+	image_width: ImageWidth,
+	image_height: ImageLength,
+	cfa_pattern: Option<CfaPattern>,
+	strip_offsets: StripOffsets,
+}
+
+pub fn decode<R: Read + Seek>(ifd: Ifd, file: &mut TiffRead<R>) -> Result<RawImage, DecoderError> {
+	let ifd = ifd.get_value::<Arw1Ifd, _>(file)?;
+
+	let image_width: usize = ifd.image_width.try_into()?;
+	let image_height: usize = ifd.image_height.try_into()?;
+
+	let &[strip_offset] = ifd.strip_offsets.as_slice() else {
+		return Err(DecoderError::UnsupportedLayout("compressed ARW 1 file with more than one strip".to_string()));
+	};
+
+	let cfa_pattern = match ifd.cfa_pattern {
+		Some(cfa_pattern) if cfa_pattern.len() == 4 => cfa_pattern.try_into()?,
+		Some(_) => return Err(DecoderError::UnsupportedLayout("CFA pattern which is not 2x2".to_string())),
+		None => ColorFilterArray::Bayer([0, 1, 1, 2]),
+	};
+
+	// The compressed data contains 8 more rows than the image height, which are discarded
+	file.seek_from_start(strip_offset)?;
+	let image = sony_arw_load_raw(image_width, image_height, image_height + 8, &mut BitReader::<_, BE>::new(file))?;
+
+	// Same black level that libraw uses for these cameras
+	let black = if image_width > 3888 { 128 } else { 0 };
+
+	Ok(RawImage {
+		data: image,
+		width: image_width,
+		height: image_height,
+		cfa_pattern,
+		maximum: (1 << 12) - 1,
+		black: SubtractBlack::CfaGrid([black; 4]),
+		transform: Transform::Horizontal,
+		camera_model: None,
+		camera_white_balance: None,
+		white_balance: None,
+		daylight_white_balance: None,
+		camera_to_rgb: None,
+	})
+}
+
+pub fn decode_a100<R: Read + Seek>(ifd: Ifd, file: &mut TiffRead<R>) -> Result<RawImage, DecoderError> {
+	let data_offset = ifd.get_value::<SonyDataOffset, _>(file)?;
 
 	let image_width = 3881;
 	let image_height = 2608;
 
-	file.seek_from_start(data_offset).unwrap();
-	let mut image = sony_arw_load_raw(image_width, image_height, &mut BitReader::<_, BE>::new(file)).unwrap();
+	file.seek_from_start(data_offset)?;
+	let mut image = sony_arw_load_raw(image_width, image_height, image_height, &mut BitReader::<_, BE>::new(file))?;
 
 	let len = image.len();
 	image[len - image_width..].fill(0);
 
-	RawImage {
+	Ok(RawImage {
 		data: image,
 		width: image_width,
 		height: image_height,
-		cfa_pattern: todo!(),
-		#[allow(unreachable_code)]
+		cfa_pattern: ColorFilterArray::Bayer([1, 0, 2, 1]),
 		maximum: (1 << 12) - 1,
 		black: SubtractBlack::None,
 		transform: Transform::Horizontal,
 		camera_model: None,
 		camera_white_balance: None,
 		white_balance: None,
+		daylight_white_balance: None,
 		camera_to_rgb: None,
-	}
+	})
 }
 
-fn read_and_huffman_decode_file<R: Read + Seek, E: Endianness>(huff: &[u16], file: &mut BitReader<R, E>) -> u32 {
+fn read_and_huffman_decode_file<R: Read + Seek, E: Endianness>(huff: &[u16], file: &mut BitReader<R, E>) -> std::io::Result<u32> {
 	let number_of_bits = huff[0].into();
 	let huffman_table = &huff[1..];
 
 	// `number_of_bits` will be no more than 32, so the result is put into a u32
-	let bits: u32 = file.read(number_of_bits).unwrap();
+	let bits: u32 = file.read(number_of_bits)?;
 	let bits = bits as usize;
 
 	let bits_to_seek_from = huffman_table[bits].to_le_bytes()[1] as i64 - number_of_bits as i64;
-	file.seek_bits(std::io::SeekFrom::Current(bits_to_seek_from)).unwrap();
+	file.seek_bits(std::io::SeekFrom::Current(bits_to_seek_from))?;
 
-	huffman_table[bits].to_le_bytes()[0].into()
+	Ok(huffman_table[bits].to_le_bytes()[0].into())
 }
 
-fn read_n_bits_from_file<R: Read + Seek, E: Endianness>(number_of_bits: u32, file: &mut BitReader<R, E>) -> u32 {
+fn read_n_bits_from_file<R: Read + Seek, E: Endianness>(number_of_bits: u32, file: &mut BitReader<R, E>) -> std::io::Result<u32> {
 	// `number_of_bits` will be no more than 32, so the result is put into a u32
-	file.read(number_of_bits).unwrap()
+	file.read(number_of_bits)
 }
 
 /// ljpeg is a lossless variant of JPEG which gets used for decoding the embedded (thumbnail) preview images in raw files
-fn ljpeg_diff<R: Read + Seek, E: Endianness>(huff: &[u16], file: &mut BitReader<R, E>, dng_version: Option<u32>) -> i32 {
-	let length = read_and_huffman_decode_file(huff, file);
+fn ljpeg_diff<R: Read + Seek, E: Endianness>(huff: &[u16], file: &mut BitReader<R, E>, dng_version: Option<u32>) -> std::io::Result<i32> {
+	let length = read_and_huffman_decode_file(huff, file)?;
 
 	if length == 16 && dng_version.map(|x| x >= 0x1010000).unwrap_or(true) {
-		return -32768;
+		return Ok(-32768);
+	}
+
+	if length == 0 {
+		return Ok(0);
 	}
 
-	let diff = read_n_bits_from_file(length, file) as i32;
+	let diff = read_n_bits_from_file(length, file)? as i32;
 
-	if length == 0 || (diff & (1 << (length - 1))) == 0 { diff - (1 << length) - 1 } else { diff }
+	Ok(if (diff & (1 << (length - 1))) == 0 { diff - ((1 << length) - 1) } else { diff })
 }
 
-fn sony_arw_load_raw<R: Read + Seek>(width: usize, height: usize, file: &mut BitReader<R, BE>) -> Option<Vec<u16>> {
+/// Decodes the rows in the order they are stored (first the even rows and then the odd rows), keeping only the first `height` of the `raw_height` rows.
+fn sony_arw_load_raw<R: Read + Seek>(width: usize, height: usize, raw_height: usize, file: &mut BitReader<R, BE>) -> Result<Vec<u16>, DecoderError> {
 	const TABLE: [u16; 18] = [
 		0x0f11, 0x0f10, 0x0e0f, 0x0d0e, 0x0c0d, 0x0b0c, 0x0a0b, 0x090a, 0x0809, 0x0708, 0x0607, 0x0506, 0x0405, 0x0304, 0x0303, 0x0300, 0x0202, 0x0201,
 	];
@@ -87,11 +141,14 @@ fn sony_arw_load_raw<R: Read + Seek>(width: usize, height: usize, file: &mut Bit
 	let mut sum = 0;
 	let mut image = vec![0_u16; width * height];
 	for column in (0..width).rev() {
-		for row in (0..height).step_by(2).chain((1..height).step_by(2)) {
-			sum += ljpeg_diff(&huffman_table, file, None);
+		for row in (0..raw_height).step_by(2).chain((1..raw_height).step_by(2)) {
+			sum += ljpeg_diff(&huffman_table, file, None).map_err(|error| match error.kind() {
+				std::io::ErrorKind::UnexpectedEof => DecoderError::CorruptData("the compressed ARW data is truncated"),
+				_ => error.into(),
+			})?;
 
 			if (sum >> 12) != 0 {
-				return None;
+				return Err(DecoderError::CorruptData("the compressed ARW data decodes to values outside of 12 bits"));
 			}
 
 			if row < height {
@@ -100,5 +157,5 @@ fn sony_arw_load_raw<R: Read + Seek>(width: usize, height: usize, file: &mut Bit
 		}
 	}
 
-	Some(image)
+	Ok(image)
 }
@@ -2,7 +2,7 @@ use crate::tiff::file::{Endian, TiffRead};
 use crate::tiff::tags::{BitsPerSample, CfaPattern, CfaPatternDim, Compression, ImageLength, ImageWidth, SonyToneCurve, StripByteCounts, StripOffsets, Tag, WhiteBalanceRggbLevels};
 use crate::tiff::values::CurveLookupTable;
 use crate::tiff::{Ifd, TiffError};
-use crate::{RawImage, SubtractBlack, Transform};
+use crate::{DecoderError, RawImage, SubtractBlack, Transform};
 use rawkit_proc_macros::Tag;
 use std::io::{Read, Seek};
 
@@ -21,40 +21,54 @@ struct Arw2Ifd {
 	white_balance_levels: Option<WhiteBalanceRggbLevels>,
 }
 
-pub fn decode<R: Read + Seek>(ifd: Ifd, file: &mut TiffRead<R>) -> RawImage {
-	let ifd = ifd.get_value::<Arw2Ifd, _>(file).unwrap();
+pub fn decode<R: Read + Seek>(ifd: Ifd, file: &mut TiffRead<R>) -> Result<RawImage, DecoderError> {
+	let ifd = ifd.get_value::<Arw2Ifd, _>(file)?;
 
-	assert!(ifd.strip_offsets.len() == ifd.strip_byte_counts.len());
-	assert!(ifd.strip_offsets.len() == 1);
-	assert!(ifd.compression == 32767);
+	let &[strip_offset] = ifd.strip_offsets.as_slice() else {
+		return Err(DecoderError::UnsupportedLayout("ARW 2 file with more than one strip".to_string()));
+	};
 
-	let image_width: usize = ifd.image_width.try_into().unwrap();
-	let image_height: usize = ifd.image_height.try_into().unwrap();
+	let image_width: usize = ifd.image_width.try_into()?;
+	let image_height: usize = ifd.image_height.try_into()?;
 	let bits_per_sample: usize = ifd.bits_per_sample.into();
-	assert!(bits_per_sample == 12);
+	if bits_per_sample != 12 {
+		return Err(DecoderError::UnsupportedLayout(format!("ARW 2 file with {bits_per_sample} bits per sample")));
+	}
+	if image_width < 32 {
+		return Err(DecoderError::UnsupportedLayout(format!("ARW 2 file with a width of only {image_width} pixels")));
+	}
 
 	let [cfa_pattern_width, cfa_pattern_height] = ifd.cfa_pattern_dim;
-	assert!(cfa_pattern_width == 2 && cfa_pattern_height == 2);
+	if cfa_pattern_width != 2 || cfa_pattern_height != 2 {
+		return Err(DecoderError::UnsupportedLayout(format!("CFA pattern of size {cfa_pattern_width}x{cfa_pattern_height}")));
+	}
+	if ifd.cfa_pattern.len() != 4 {
+		return Err(DecoderError::CorruptData("the CFA pattern doesn't match its dimension"));
+	}
+	let cfa_pattern = ifd.cfa_pattern.try_into()?;
 
-	file.seek_from_start(ifd.strip_offsets[0]).unwrap();
-	let mut image = sony_arw2_load_raw(image_width, image_height, ifd.sony_tone_curve, file).unwrap();
+	file.seek_from_start(strip_offset)?;
+	let mut image = sony_arw2_load_raw(image_width, image_height, ifd.sony_tone_curve, file)?;
 
 	// Converting the bps from 12 to 14 so that ARW 2.3.1 and 2.3.5 have the same 14 bps.
 	image.iter_mut().for_each(|x| *x <<= 2);
 
-	RawImage {
+	Ok(RawImage {
 		data: image,
 		width: image_width,
 		height: image_height,
-		cfa_pattern: ifd.cfa_pattern.try_into().unwrap(),
+		cfa_pattern,
 		maximum: (1 << 14) - 1,
 		black: SubtractBlack::CfaGrid([512, 512, 512, 512]), // TODO: Find the correct way to do this
 		transform: Transform::Horizontal,
 		camera_model: None,
-		camera_white_balance: ifd.white_balance_levels.map(|arr| arr.map(|x| x as f64)),
+		camera_white_balance: ifd
+			.white_balance_levels
+			.map(|[red, green_1, green_2, blue]| [red as f64, (green_1 as f64 + green_2 as f64) / 2., blue as f64]),
 		white_balance: None,
+		daylight_white_balance: None,
 		camera_to_rgb: None,
-	}
+	})
 }
 
 fn as_u32(buffer: &[u8], endian: Endian) -> Option<u32> {
@@ -71,12 +85,12 @@ fn as_u16(buffer: &[u8], endian: Endian) -> Option<u16> {
 	})
 }
 
-fn sony_arw2_load_raw<R: Read + Seek>(width: usize, height: usize, curve: CurveLookupTable, file: &mut TiffRead<R>) -> Option<Vec<u16>> {
+fn sony_arw2_load_raw<R: Read + Seek>(width: usize, height: usize, curve: CurveLookupTable, file: &mut TiffRead<R>) -> std::io::Result<Vec<u16>> {
 	let mut image = vec![0_u16; height * width];
 	let mut data = vec![0_u8; width + 1];
 
 	for row in 0..height {
-		file.read_exact(&mut data[0..width]).unwrap();
+		file.read_exact(&mut data[0..width])?;
 
 		let mut column = 0;
 		let mut data_index = 0;
@@ -122,5 +136,5 @@ fn sony_arw2_load_raw<R: Read + Seek>(width: usize, height: usize, curve: CurveL
 		}
 	}
 
-	Some(image)
+	Ok(image)
 }
@@ -0,0 +1,283 @@
+use super::ljpeg;
+use crate::tiff::file::{Endian, TiffRead};
+use crate::tiff::tags::{
+	AsShotNeutral, BitsPerSample, BlackLevelRepeatDim, CfaPattern, CfaPatternDim, ColorMatrix1, ColorMatrix2, Compression, DngBlackLevel, ImageLength, ImageWidth, LinearizationTable, NewSubfileType,
+	PhotometricInterpretation, RowsPerStrip, SamplesPerPixel, StripByteCounts, StripOffsets, SubIfds, Tag, TileByteCounts, TileLength, TileOffsets, TileWidth, WhiteLevel,
+};
+use crate::tiff::values::ToFloat;
+use crate::tiff::{Ifd, TiffError};
+use crate::{ColorFilterArray, DecoderError, RawImage, SubtractBlack, Transform};
+use bitstream_io::{BE, BitRead, BitReader};
+use rawkit_proc_macros::Tag;
+use std::io::{Read, Seek};
+
+const PHOTOMETRIC_INTERPRETATION_CFA: u16 = 32803;
+const COMPRESSION_UNCOMPRESSED: u16 = 1;
+const COMPRESSION_LOSSLESS_JPEG: u16 = 7;
+
+#[allow(dead_code)]
+#[derive(Tag)]
+struct DngRawIfd {
+	image_width: ImageWidth,
+	image_height: ImageLength,
+	bits_per_sample: BitsPerSample,
+	compression: Compression,
+	photometric_interpretation: PhotometricInterpretation,
+	samples_per_pixel: Option<SamplesPerPixel>,
+	cfa_pattern_dim: CfaPatternDim,
+	cfa_pattern: CfaPattern,
+	linearization_table: Option<LinearizationTable>,
+	black_level_repeat_dim: Option<BlackLevelRepeatDim>,
+	black_level: Option<DngBlackLevel>,
+	white_level: Option<WhiteLevel>,
+}
+
+#[allow(dead_code)]
+#[derive(Tag)]
+struct DngTileIfd {
+	tile_width: TileWidth,
+	tile_length: TileLength,
+	tile_offsets: TileOffsets,
+	tile_byte_counts: TileByteCounts,
+}
+
+#[allow(dead_code)]
+#[derive(Tag)]
+struct DngStripIfd {
+	rows_per_strip: Option<RowsPerStrip>,
+	strip_offsets: StripOffsets,
+	strip_byte_counts: StripByteCounts,
+}
+
+#[allow(dead_code)]
+#[derive(Tag)]
+struct DngColorIfd {
+	color_matrix_1: Option<ColorMatrix1>,
+	color_matrix_2: Option<ColorMatrix2>,
+	as_shot_neutral: Option<AsShotNeutral>,
+}
+
+/// The raw data is divided into tiles which are stored separately. Strips are handled as tiles which span the width of the image.
+struct TileLayout {
+	tile_width: usize,
+	tile_length: usize,
+	offsets: Vec<u32>,
+	byte_counts: Vec<u32>,
+}
+
+pub fn decode<R: Read + Seek>(ifd: Ifd, file: &mut TiffRead<R>) -> Result<RawImage, DecoderError> {
+	let raw_ifd = find_raw_ifd(&ifd, file)?;
+	let raw = raw_ifd.get_value::<DngRawIfd, _>(file)?;
+
+	if raw.photometric_interpretation != PHOTOMETRIC_INTERPRETATION_CFA {
+		return Err(DecoderError::UnsupportedLayout(format!("DNG file with photometric interpretation {}", raw.photometric_interpretation)));
+	}
+	if raw.samples_per_pixel.unwrap_or(1) != 1 {
+		return Err(DecoderError::UnsupportedLayout("DNG file with more than one sample per pixel".to_string()));
+	}
+	if raw.compression != COMPRESSION_UNCOMPRESSED && raw.compression != COMPRESSION_LOSSLESS_JPEG {
+		return Err(DecoderError::UnsupportedLayout(format!("DNG file with compression {}", raw.compression)));
+	}
+	if raw.bits_per_sample == 0 || raw.bits_per_sample > 16 {
+		return Err(DecoderError::UnsupportedLayout(format!("DNG file with {} bits per sample", raw.bits_per_sample)));
+	}
+
+	let [cfa_pattern_width, cfa_pattern_height] = raw.cfa_pattern_dim;
+	if cfa_pattern_width != cfa_pattern_height || (cfa_pattern_width != 2 && cfa_pattern_width != 6) {
+		return Err(DecoderError::UnsupportedLayout(format!("CFA pattern of size {cfa_pattern_width}x{cfa_pattern_height}")));
+	}
+	if raw.cfa_pattern.len() != cfa_pattern_width as usize * cfa_pattern_height as usize {
+		return Err(DecoderError::CorruptData("the CFA pattern doesn't match its dimension"));
+	}
+	let cfa_pattern: ColorFilterArray = raw.cfa_pattern.try_into()?;
+
+	let image_width: usize = raw.image_width.try_into()?;
+	let image_height: usize = raw.image_height.try_into()?;
+	let layout = tile_layout(&raw_ifd, file, image_width, image_height)?;
+
+	// Every pixel needs at least one bit of data, which keeps corrupt image dimensions from allocating huge amounts of memory
+	let pixel_count = image_width.checked_mul(image_height).ok_or(DecoderError::CorruptData("the image dimensions are too large"))?;
+	if pixel_count as u64 > 8 * layout.byte_counts.iter().map(|&byte_count| byte_count as u64).sum::<u64>() {
+		return Err(DecoderError::CorruptData("the image data is too small for the image dimensions"));
+	}
+	let mut image = vec![0_u16; pixel_count];
+	let tiles_across = image_width.div_ceil(layout.tile_width);
+
+	for (index, (&offset, &byte_count)) in layout.offsets.iter().zip(&layout.byte_counts).enumerate() {
+		let top = (index / tiles_across) * layout.tile_length;
+		let left = (index % tiles_across) * layout.tile_width;
+		if top >= image_height {
+			break;
+		}
+
+		let mut data = Vec::new();
+		file.seek_from_start(offset)?;
+		file.by_ref().take(byte_count.into()).read_to_end(&mut data)?;
+		if data.len() != byte_count as usize {
+			return Err(DecoderError::CorruptData("the image data is truncated"));
+		}
+
+		let samples = if raw.compression == COMPRESSION_LOSSLESS_JPEG {
+			ljpeg::decode(&data)?.data
+		} else {
+			let rows = layout.tile_length.min(image_height - top);
+			unpack_samples(&data, raw.bits_per_sample.into(), file.endian(), layout.tile_width, rows)?
+		};
+
+		// Samples are written into the tile row by row, which also covers lossless JPEG data with a width that differs from the tile width
+		for (sample_index, &sample) in samples.iter().enumerate() {
+			let row = top + sample_index / layout.tile_width;
+			let column = left + sample_index % layout.tile_width;
+			if row >= image_height {
+				break;
+			}
+			if column < image_width {
+				image[row * image_width + column] = sample;
+			}
+		}
+	}
+
+	if let Some(table) = raw.linearization_table.filter(|table| !table.is_empty()) {
+		image.iter_mut().for_each(|value| *value = table[(*value as usize).min(table.len() - 1)]);
+	}
+
+	let black = black_level(raw.black_level_repeat_dim.unwrap_or([1, 1]), &raw.black_level.unwrap_or_default());
+	let maximum = match raw.white_level.as_deref() {
+		Some([white_level, ..]) => (*white_level).try_into().unwrap_or(u16::MAX),
+		_ if raw.bits_per_sample == 16 => u16::MAX,
+		_ => (1 << raw.bits_per_sample) - 1,
+	};
+
+	let color = ifd.get_value::<DngColorIfd, _>(file)?;
+	let camera_white_balance = color
+		.as_shot_neutral
+		.filter(|neutral| neutral.len() >= 3 && neutral.iter().all(|&value| value > 0.))
+		.map(|neutral| [1. / neutral[0], 1. / neutral[1], 1. / neutral[2]]);
+
+	let mut raw_image = RawImage {
+		data: image,
+		width: image_width,
+		height: image_height,
+		cfa_pattern,
+		maximum,
+		black: SubtractBlack::CfaGrid(black),
+		transform: Transform::Horizontal,
+		camera_model: None,
+		camera_white_balance,
+		white_balance: None,
+		daylight_white_balance: None,
+		camera_to_rgb: None,
+	};
+
+	// The second color matrix is usually the one for the D65 illuminant, which matches the color space conversion done afterwards
+	let xyz_to_camera = color.color_matrix_2.or(color.color_matrix_1).and_then(|matrix| {
+		let matrix: Vec<_> = matrix.iter().map(|value| value.to_float()).collect();
+		matrix.try_into().ok()
+	});
+	if let Some(xyz_to_camera) = xyz_to_camera {
+		raw_image.calculate_conversion_matrices_from_xyz_to_camera(xyz_to_camera);
+	}
+
+	Ok(raw_image)
+}
+
+/// Finds the IFD with the full resolution raw image, which is either the first IFD or one of its SubIFDs.
+fn find_raw_ifd<R: Read + Seek>(ifd: &Ifd, file: &mut TiffRead<R>) -> Result<Ifd, DecoderError> {
+	let mut candidates = vec![ifd.clone()];
+	candidates.extend(ifd.get_value::<Option<SubIfds>, _>(file)?.unwrap_or_default());
+
+	for candidate in candidates {
+		// A value of 0 means that this IFD contains the main image instead of a preview
+		if candidate.get_value::<Option<NewSubfileType>, _>(file)?.unwrap_or(0) == 0 {
+			return Ok(candidate);
+		}
+	}
+
+	Err(DecoderError::UnsupportedLayout("DNG file without a full resolution image".to_string()))
+}
+
+fn tile_layout<R: Read + Seek>(ifd: &Ifd, file: &mut TiffRead<R>, image_width: usize, image_height: usize) -> Result<TileLayout, DecoderError> {
+	let layout = if let Some(tiles) = ifd.get_value::<Option<DngTileIfd>, _>(file)? {
+		TileLayout {
+			tile_width: tiles.tile_width.try_into()?,
+			tile_length: tiles.tile_length.try_into()?,
+			offsets: tiles.tile_offsets,
+			byte_counts: tiles.tile_byte_counts,
+		}
+	} else {
+		let strips = ifd.get_value::<DngStripIfd, _>(file)?;
+		let rows_per_strip = strips.rows_per_strip.map(|rows| rows.try_into()).transpose()?.unwrap_or(image_height);
+
+		TileLayout {
+			tile_width: image_width,
+			tile_length: rows_per_strip.min(image_height),
+			offsets: strips.strip_offsets,
+			byte_counts: strips.strip_byte_counts,
+		}
+	};
+
+	if layout.tile_width == 0 || layout.tile_length == 0 {
+		return Err(DecoderError::CorruptData("the tiles of the image are empty"));
+	}
+	if layout.offsets.len() != layout.byte_counts.len() {
+		return Err(DecoderError::CorruptData("the number of tile offsets and tile byte counts are different"));
+	}
+
+	let tile_count = image_width.div_ceil(layout.tile_width) * image_height.div_ceil(layout.tile_length);
+	if layout.offsets.len() < tile_count {
+		return Err(DecoderError::CorruptData("the tiles don't cover the whole image"));
+	}
+
+	Ok(layout)
+}
+
+/// Reads `rows` rows of `columns` samples each, where samples of less than 16 bits are packed together and each row starts at a new byte.
+fn unpack_samples(data: &[u8], bits_per_sample: u32, endian: Endian, columns: usize, rows: usize) -> Result<Vec<u16>, DecoderError> {
+	let sample_count = columns * rows;
+
+	match bits_per_sample {
+		8 => Ok(data
+			.get(..sample_count)
+			.ok_or(DecoderError::CorruptData("the image data is truncated"))?
+			.iter()
+			.map(|&x| x.into())
+			.collect()),
+		16 => Ok(data
+			.get(..2 * sample_count)
+			.ok_or(DecoderError::CorruptData("the image data is truncated"))?
+			.chunks_exact(2)
+			.map(|bytes| match endian {
+				Endian::Little => u16::from_le_bytes([bytes[0], bytes[1]]),
+				Endian::Big => u16::from_be_bytes([bytes[0], bytes[1]]),
+			})
+			.collect()),
+		_ => {
+			let mut reader = BitReader::<_, BE>::new(data);
+			let mut samples = Vec::with_capacity(sample_count);
+			for _ in 0..rows {
+				for _ in 0..columns {
+					let sample = reader.read(bits_per_sample).map_err(|error| match error.kind() {
+						std::io::ErrorKind::UnexpectedEof => DecoderError::CorruptData("the image data is truncated"),
+						_ => error.into(),
+					})?;
+					samples.push(sample);
+				}
+				reader.byte_align();
+			}
+			Ok(samples)
+		}
+	}
+}
+
+fn black_level([repeat_rows, repeat_columns]: [u16; 2], black_level: &[f64]) -> [u16; 4] {
+	let to_level = |value: f64| value.round().clamp(0., u16::MAX as f64) as u16;
+
+	match (repeat_rows, repeat_columns) {
+		_ if black_level.is_empty() => [0; 4],
+		(2, 2) if black_level.len() >= 4 => [0, 1, 2, 3].map(|index| to_level(black_level[index])),
+		(1, 2) if black_level.len() >= 2 => [0, 1, 0, 1].map(|index| to_level(black_level[index])),
+		(2, 1) if black_level.len() >= 2 => [0, 0, 1, 1].map(|index| to_level(black_level[index])),
+		// Other repeating patterns don't line up with the 2x2 CFA grid, so their average is used instead
+		_ => [to_level(black_level.iter().sum::<f64>() / black_level.len() as f64); 4],
+	}
+}
@@ -0,0 +1,356 @@
+use crate::DecoderError;
+
+const MARKER_SOF3: u8 = 0xc3;
+const MARKER_DHT: u8 = 0xc4;
+const MARKER_SOI: u8 = 0xd8;
+const MARKER_EOI: u8 = 0xd9;
+const MARKER_SOS: u8 = 0xda;
+const MARKER_DRI: u8 = 0xdd;
+
+/// The samples of a lossless JPEG (ITU-T T.81 process 14) image, which is how DNG files compress their raw data.
+pub struct LosslessJpeg {
+	/// Number of samples per line for each component.
+	pub width: usize,
+
+	/// Number of lines.
+	pub height: usize,
+
+	/// Number of interleaved components.
+	pub components: usize,
+
+	/// Decoded samples stored in a linear fashion, with the components of each sample interleaved.
+	pub data: Vec<u16>,
+}
+
+struct Frame {
+	precision: u32,
+	width: usize,
+	height: usize,
+	component_ids: Vec<u8>,
+}
+
+struct HuffmanTable {
+	/// Maps the next 16 bits of the stream to the length of the code they start with (0 for invalid codes) and its symbol.
+	lookup: Vec<(u8, u8)>,
+}
+
+impl HuffmanTable {
+	fn new(code_counts: &[u8; 16], symbols: &[u8]) -> Result<Self, DecoderError> {
+		let mut lookup = vec![(0, 0); 1 << 16];
+		let mut symbols = symbols.iter();
+		let mut code = 0_usize;
+
+		for (length, &count) in (1..=16).zip(code_counts) {
+			for _ in 0..count {
+				let &symbol = symbols.next().ok_or(DecoderError::CorruptData("the Huffman table has less symbols than codes"))?;
+				if code >= 1 << length {
+					return Err(DecoderError::CorruptData("the Huffman table has too many codes"));
+				}
+
+				let shift = 16 - length;
+				lookup[code << shift..(code + 1) << shift].fill((length as u8, symbol));
+				code += 1;
+			}
+			code <<= 1;
+		}
+
+		Ok(HuffmanTable { lookup })
+	}
+}
+
+/// Reads the entropy coded data of a scan, removing the stuffed zero bytes after each `0xff`.
+struct BitPump<'a> {
+	data: &'a [u8],
+	position: usize,
+	buffer: u64,
+	bits: u32,
+	/// Number of zero bits at the end of the buffer which were fed in because the entropy coded data ended.
+	padding_bits: u32,
+}
+
+impl<'a> BitPump<'a> {
+	fn new(data: &'a [u8]) -> Self {
+		BitPump {
+			data,
+			position: 0,
+			buffer: 0,
+			bits: 0,
+			padding_bits: 0,
+		}
+	}
+
+	fn fill(&mut self) {
+		while self.bits <= 56 {
+			let byte = match self.data.get(self.position..self.position + 2) {
+				Some([0xff, 0x00]) => {
+					self.position += 2;
+					0xff
+				}
+				// Markers end the entropy coded data, so zeros are fed in until the marker is skipped by a restart
+				Some([0xff, _]) => {
+					self.padding_bits += 8;
+					0
+				}
+				_ => match self.data.get(self.position) {
+					Some(&byte) if byte != 0xff => {
+						self.position += 1;
+						byte
+					}
+					// A single 0xff at the end is the start of a marker or stuffed byte which was cut off
+					_ => {
+						self.padding_bits += 8;
+						0
+					}
+				},
+			};
+
+			self.buffer = (self.buffer << 8) | byte as u64;
+			self.bits += 8;
+		}
+	}
+
+	fn peek(&mut self, bits: u32) -> u32 {
+		self.fill();
+		((self.buffer >> (self.bits - bits)) & ((1 << bits) - 1)) as u32
+	}
+
+	fn consume(&mut self, bits: u32) {
+		self.bits -= bits;
+	}
+
+	fn read(&mut self, bits: u32) -> u32 {
+		if bits == 0 {
+			return 0;
+		}
+
+		let value = self.peek(bits);
+		self.consume(bits);
+		value
+	}
+
+	/// Whether some of the zeros fed in after the end of the entropy coded data were read, which happens when the data is truncated.
+	fn is_exhausted(&self) -> bool {
+		self.padding_bits > self.bits
+	}
+
+	/// Drops the buffered bits and continues after the next restart marker.
+	fn restart(&mut self) {
+		self.buffer = 0;
+		self.bits = 0;
+		self.padding_bits = 0;
+
+		while let Some(&[first, second]) = self.data.get(self.position..self.position + 2) {
+			self.position += 1;
+			if first == 0xff && (0xd0..=0xd7).contains(&second) {
+				self.position += 1;
+				return;
+			}
+		}
+	}
+
+	fn decode_difference(&mut self, table: &HuffmanTable) -> Result<i32, DecoderError> {
+		let (length, symbol) = table.lookup[self.peek(16) as usize];
+		if length == 0 {
+			return Err(DecoderError::CorruptData("the lossless JPEG data contains an invalid Huffman code"));
+		}
+		self.consume(length.into());
+
+		Ok(match symbol {
+			0 => 0,
+			1..=15 => {
+				let length: u32 = symbol.into();
+				let difference = self.read(length) as i32;
+				if difference & (1 << (length - 1)) == 0 { difference - (1 << length) + 1 } else { difference }
+			}
+			16 => 32768,
+			_ => return Err(DecoderError::CorruptData("the lossless JPEG data contains a difference which is too large")),
+		})
+	}
+}
+
+/// Decodes a lossless JPEG image which is completely contained in `data`.
+pub fn decode(data: &[u8]) -> Result<LosslessJpeg, DecoderError> {
+	if !data.starts_with(&[0xff, MARKER_SOI]) {
+		return Err(DecoderError::CorruptData("the lossless JPEG data doesn't start with a SOI marker"));
+	}
+
+	let mut position = 2;
+	let mut frame = None;
+	let mut tables: [Option<HuffmanTable>; 4] = Default::default();
+	let mut restart_interval = 0;
+
+	loop {
+		// Any number of 0xff fill bytes can precede a marker
+		while data.get(position) == Some(&0xff) && data.get(position + 1) == Some(&0xff) {
+			position += 1;
+		}
+		let (Some(0xff), Some(&marker)) = (data.get(position), data.get(position + 1)) else {
+			return Err(DecoderError::CorruptData("the lossless JPEG data is missing a marker"));
+		};
+		position += 2;
+
+		if marker == MARKER_EOI {
+			return Err(DecoderError::CorruptData("the lossless JPEG data ended before the start of a scan"));
+		}
+
+		let segment_length = data.get(position..position + 2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]) as usize).unwrap_or_default();
+		let segment = data
+			.get(position + 2..position + segment_length)
+			.ok_or(DecoderError::CorruptData("a lossless JPEG segment is truncated"))?;
+
+		match marker {
+			MARKER_SOF3 => frame = Some(read_frame(segment)?),
+			0xc0..=0xcf if marker != MARKER_DHT && marker != 0xc8 && marker != 0xcc => {
+				return Err(DecoderError::UnsupportedLayout("JPEG compression which isn't lossless".to_string()));
+			}
+			MARKER_DHT => read_huffman_tables(segment, &mut tables)?,
+			MARKER_DRI => restart_interval = segment.get(0..2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]) as usize).unwrap_or_default(),
+			MARKER_SOS => {
+				let frame = frame.ok_or(DecoderError::CorruptData("the lossless JPEG data has a scan before the frame header"))?;
+				return read_scan(segment, &data[position + segment_length..], &frame, &tables, restart_interval);
+			}
+			_ => {}
+		}
+
+		position += segment_length;
+	}
+}
+
+fn read_frame(segment: &[u8]) -> Result<Frame, DecoderError> {
+	let truncated = || DecoderError::CorruptData("the lossless JPEG frame header is truncated");
+
+	let &[precision, height_high, height_low, width_high, width_low, component_count, ref components @ ..] = segment else {
+		return Err(truncated());
+	};
+	let components = components.get(..3 * component_count as usize).ok_or_else(truncated)?;
+
+	if !(2..=16).contains(&precision) {
+		return Err(DecoderError::CorruptData("the lossless JPEG data has an invalid sample precision"));
+	}
+	if component_count == 0 || component_count > 4 {
+		return Err(DecoderError::UnsupportedLayout(format!("lossless JPEG data with {component_count} components")));
+	}
+	if components.chunks_exact(3).any(|component| component[1] != 0x11) {
+		return Err(DecoderError::UnsupportedLayout("subsampled lossless JPEG data".to_string()));
+	}
+
+	Ok(Frame {
+		precision: precision.into(),
+		height: u16::from_be_bytes([height_high, height_low]).into(),
+		width: u16::from_be_bytes([width_high, width_low]).into(),
+		component_ids: components.chunks_exact(3).map(|component| component[0]).collect(),
+	})
+}
+
+fn read_huffman_tables(mut segment: &[u8], tables: &mut [Option<HuffmanTable>; 4]) -> Result<(), DecoderError> {
+	while let Some((&class_and_id, rest)) = segment.split_first() {
+		let code_counts: &[u8; 16] = rest
+			.get(..16)
+			.and_then(|counts| counts.try_into().ok())
+			.ok_or(DecoderError::CorruptData("a lossless JPEG Huffman table is truncated"))?;
+		let symbol_count = code_counts.iter().map(|&count| count as usize).sum::<usize>();
+		let symbols = rest.get(16..16 + symbol_count).ok_or(DecoderError::CorruptData("a lossless JPEG Huffman table is truncated"))?;
+
+		let table = tables
+			.get_mut((class_and_id & 0x0f) as usize)
+			.ok_or(DecoderError::CorruptData("a lossless JPEG Huffman table has an invalid index"))?;
+		*table = Some(HuffmanTable::new(code_counts, symbols)?);
+
+		segment = &rest[16 + symbol_count..];
+	}
+
+	Ok(())
+}
+
+fn read_scan(segment: &[u8], entropy_coded_data: &[u8], frame: &Frame, tables: &[Option<HuffmanTable>; 4], restart_interval: usize) -> Result<LosslessJpeg, DecoderError> {
+	let truncated = || DecoderError::CorruptData("the lossless JPEG scan header is truncated");
+
+	let (&component_count, rest) = segment.split_first().ok_or_else(truncated)?;
+	let (components, rest) = rest.split_at_checked(2 * component_count as usize).ok_or_else(truncated)?;
+	let &[predictor, _, point_transform, ..] = rest else { return Err(truncated()) };
+	let point_transform = (point_transform & 0x0f) as u32;
+
+	if components.chunks_exact(2).map(|component| component[0]).ne(frame.component_ids.iter().copied()) {
+		return Err(DecoderError::UnsupportedLayout("lossless JPEG data with multiple scans".to_string()));
+	}
+	if !(1..=7).contains(&predictor) {
+		return Err(DecoderError::CorruptData("the lossless JPEG data has an invalid predictor"));
+	}
+	if point_transform >= frame.precision {
+		return Err(DecoderError::CorruptData("the lossless JPEG data has an invalid point transform"));
+	}
+
+	let tables = components
+		.chunks_exact(2)
+		.map(|component| tables[(component[1] >> 4) as usize & 3].as_ref())
+		.collect::<Option<Vec<_>>>()
+		.ok_or(DecoderError::CorruptData("the lossless JPEG data uses a missing Huffman table"))?;
+
+	let width = frame.width;
+	let components = tables.len();
+	let initial_prediction = 1 << (frame.precision - point_transform - 1);
+
+	// Every sample needs at least one bit of data, which keeps corrupt image dimensions from allocating huge amounts of memory
+	let sample_count = width * frame.height * components;
+	if sample_count > 8 * entropy_coded_data.len() {
+		return Err(DecoderError::CorruptData("the lossless JPEG data is too small for the image dimensions"));
+	}
+
+	let mut data = vec![0_u16; sample_count];
+	let mut bits = BitPump::new(entropy_coded_data);
+	let mut first_row_after_restart = 0;
+
+	for row in 0..frame.height {
+		if row > 0 && restart_interval > 0 && (row * width).is_multiple_of(restart_interval) {
+			bits.restart();
+			first_row_after_restart = row;
+		}
+
+		for column in 0..width {
+			for (component, table) in tables.iter().enumerate() {
+				let index = (row * width + column) * components + component;
+
+				let prediction = if column == 0 {
+					if row == first_row_after_restart {
+						initial_prediction
+					} else {
+						data[index - width * components] as i32
+					}
+				} else if row == first_row_after_restart {
+					data[index - components] as i32
+				} else {
+					let left = data[index - components] as i32;
+					let above = data[index - width * components] as i32;
+					let above_left = data[index - width * components - components] as i32;
+
+					match predictor {
+						1 => left,
+						2 => above,
+						3 => above_left,
+						4 => left + above - above_left,
+						5 => left + ((above - above_left) >> 1),
+						6 => above + ((left - above_left) >> 1),
+						_ => (left + above) >> 1,
+					}
+				};
+
+				data[index] = (prediction + bits.decode_difference(table)?) as u16;
+			}
+		}
+
+		if bits.is_exhausted() {
+			return Err(DecoderError::CorruptData("the lossless JPEG data is truncated"));
+		}
+	}
+
+	if point_transform > 0 {
+		data.iter_mut().for_each(|sample| *sample <<= point_transform);
+	}
+
+	Ok(LosslessJpeg {
+		width,
+		height: frame.height,
+		components,
+		data,
+	})
+}
@@ -1,3 +1,5 @@
 pub mod arw1;
 pub mod arw2;
+pub mod dng;
+pub mod ljpeg;
 pub mod uncompressed;
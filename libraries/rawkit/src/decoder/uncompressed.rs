@@ -1,7 +1,7 @@
 use crate::tiff::file::TiffRead;
 use crate::tiff::tags::{BitsPerSample, BlackLevel, CfaPattern, CfaPatternDim, Compression, ImageLength, ImageWidth, RowsPerStrip, StripByteCounts, StripOffsets, Tag, WhiteBalanceRggbLevels};
 use crate::tiff::{Ifd, TiffError};
-use crate::{RawImage, SubtractBlack, Transform};
+use crate::{DecoderError, RawImage, SubtractBlack, Transform};
 use rawkit_proc_macros::Tag;
 use std::io::{Read, Seek};
 
@@ -21,46 +21,62 @@ struct ArwUncompressedIfd {
 	white_balance_levels: Option<WhiteBalanceRggbLevels>,
 }
 
-pub fn decode<R: Read + Seek>(ifd: Ifd, file: &mut TiffRead<R>) -> RawImage {
-	let ifd = ifd.get_value::<ArwUncompressedIfd, _>(file).unwrap();
+pub fn decode<R: Read + Seek>(ifd: Ifd, file: &mut TiffRead<R>) -> Result<RawImage, DecoderError> {
+	let ifd = ifd.get_value::<ArwUncompressedIfd, _>(file)?;
 
-	assert!(ifd.strip_offsets.len() == ifd.strip_byte_counts.len());
-	assert!(ifd.strip_offsets.len() == 1);
-	assert!(ifd.compression == 1); // 1 is the value for uncompressed format
+	if ifd.strip_offsets.len() != ifd.strip_byte_counts.len() {
+		return Err(DecoderError::CorruptData("the number of strip offsets and strip byte counts are different"));
+	}
 
-	let image_width: usize = ifd.image_width.try_into().unwrap();
-	let image_height: usize = ifd.image_height.try_into().unwrap();
-	let rows_per_strip: usize = ifd.rows_per_strip.try_into().unwrap();
+	let image_width: usize = ifd.image_width.try_into()?;
+	let image_height: usize = ifd.image_height.try_into()?;
+	let rows_per_strip: usize = ifd.rows_per_strip.try_into()?;
 	let bits_per_sample: usize = ifd.bits_per_sample.into();
+	if bits_per_sample > 16 {
+		return Err(DecoderError::UnsupportedLayout(format!("uncompressed ARW file with {bits_per_sample} bits per sample")));
+	}
+
 	let [cfa_pattern_width, cfa_pattern_height] = ifd.cfa_pattern_dim;
-	assert!(cfa_pattern_width == 2 && cfa_pattern_height == 2);
+	if cfa_pattern_width != 2 || cfa_pattern_height != 2 {
+		return Err(DecoderError::UnsupportedLayout(format!("CFA pattern of size {cfa_pattern_width}x{cfa_pattern_height}")));
+	}
+	if ifd.cfa_pattern.len() != 4 {
+		return Err(DecoderError::CorruptData("the CFA pattern doesn't match its dimension"));
+	}
+	let cfa_pattern = ifd.cfa_pattern.try_into()?;
 
 	let mut image: Vec<u16> = Vec::with_capacity(image_height * image_width);
 
-	for i in 0..ifd.strip_offsets.len() {
-		file.seek_from_start(ifd.strip_offsets[i]).unwrap();
+	for (i, &strip_offset) in ifd.strip_offsets.iter().enumerate() {
+		file.seek_from_start(strip_offset)?;
 
-		let last = i == ifd.strip_offsets.len();
-		let rows = if last { image_height % rows_per_strip } else { rows_per_strip };
+		let rows = rows_per_strip.min(image_height.saturating_sub(i * rows_per_strip));
 
 		for _ in 0..rows {
 			for _ in 0..image_width {
-				image.push(file.read_u16().unwrap());
+				image.push(file.read_u16()?);
 			}
 		}
 	}
 
-	RawImage {
+	if image.len() != image_height * image_width {
+		return Err(DecoderError::CorruptData("the strips don't cover the whole image"));
+	}
+
+	Ok(RawImage {
 		data: image,
 		width: image_width,
 		height: image_height,
-		cfa_pattern: ifd.cfa_pattern.try_into().unwrap(),
+		cfa_pattern,
 		maximum: if bits_per_sample == 16 { u16::MAX } else { (1 << bits_per_sample) - 1 },
 		black: SubtractBlack::CfaGrid(ifd.black_level),
 		transform: Transform::Horizontal,
 		camera_model: None,
-		camera_white_balance: ifd.white_balance_levels.map(|arr| arr.map(|x| x as f64)),
+		camera_white_balance: ifd
+			.white_balance_levels
+			.map(|[red, green_1, green_2, blue]| [red as f64, (green_1 as f64 + green_2 as f64) / 2., blue as f64]),
 		white_balance: None,
+		daylight_white_balance: None,
 		camera_to_rgb: None,
-	}
+	})
 }
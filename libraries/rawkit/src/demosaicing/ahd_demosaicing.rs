@@ -0,0 +1,171 @@
+use crate::metadata::camera_data::RGB_TO_XYZ;
+use crate::{CHANNELS_IN_RGB, RawImage};
+
+/// Size of the square tiles which are demosaiced at once, which keeps the memory used by the intermediate buffers small.
+const TILE_SIZE: usize = 256;
+
+/// White point of the D65 illuminant in the XYZ color space.
+const D65_WHITE: [f64; 3] = [0.950456, 1., 1.088754];
+
+/// Converts from the color space of the camera to the CIELAB color space, which is where the homogeneity of the interpolated colors is measured.
+struct CielabConverter {
+	camera_to_xyz: [[f32; CHANNELS_IN_RGB]; 3],
+	cube_root: Vec<f32>,
+}
+
+impl CielabConverter {
+	fn new(camera_to_rgb: Option<[[f64; CHANNELS_IN_RGB]; 3]>) -> Self {
+		let camera_to_rgb = camera_to_rgb.unwrap_or([[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]);
+
+		let camera_to_xyz = std::array::from_fn(|i| std::array::from_fn(|j| ((0..3).map(|k| RGB_TO_XYZ[i][k] * camera_to_rgb[k][j]).sum::<f64>() / D65_WHITE[i]) as f32));
+		let cube_root = (0..=u16::MAX as usize)
+			.map(|value| {
+				let value = value as f32 / u16::MAX as f32;
+				if value > 0.008856 { value.cbrt() } else { 7.787 * value + 16. / 116. }
+			})
+			.collect();
+
+		CielabConverter { camera_to_xyz, cube_root }
+	}
+
+	fn convert(&self, color: [u16; CHANNELS_IN_RGB]) -> [f32; 3] {
+		let [x, y, z] = self.camera_to_xyz.map(|row| {
+			let value = row.iter().zip(color).map(|(&coefficient, value)| coefficient * value as f32).sum::<f32>();
+			self.cube_root[value.round().clamp(0., u16::MAX as f32) as usize]
+		});
+
+		[116. * y - 16., 500. * (x - y), 200. * (y - z)]
+	}
+}
+
+/// Limits `value` to the range between `a` and `b`, which can be in either order.
+fn limit(value: i32, a: i32, b: i32) -> i32 {
+	value.clamp(a.min(b), a.max(b))
+}
+
+fn clip(value: i32) -> u16 {
+	value.clamp(0, u16::MAX as i32) as u16
+}
+
+impl RawImage {
+	/// Demosaics the image with the Adaptive Homogeneity-Directed algorithm by Hirakawa and Parks, in the same way as dcraw.
+	///
+	/// The image is interpolated once horizontally and once vertically, and for each pixel the direction whose neighborhood is more homogeneous in the CIELAB color space is picked.
+	/// Pixels within 5 pixels of the border keep their linearly interpolated colors.
+	pub(crate) fn ahd_demosaic(&self) -> Vec<u16> {
+		let mut output = self.linear_demosaic();
+
+		let (width, height) = (self.width, self.height);
+		let cielab = CielabConverter::new(self.camera_to_rgb);
+		let color_at = |row: usize, column: usize| self.cfa_pattern.color_at(row, column);
+		let raw = |row: usize, column: usize| self.data[row * width + column] as i32;
+
+		// The interpolated colors and their CIELAB values for the horizontal and vertical directions, and how many neighbors of each pixel have a similar color
+		let mut rgb = [vec![[0_u16; CHANNELS_IN_RGB]; TILE_SIZE * TILE_SIZE], vec![[0_u16; CHANNELS_IN_RGB]; TILE_SIZE * TILE_SIZE]];
+		let mut lab = [vec![[0_f32; 3]; TILE_SIZE * TILE_SIZE], vec![[0_f32; 3]; TILE_SIZE * TILE_SIZE]];
+		let mut homogeneity = [vec![0_u8; TILE_SIZE * TILE_SIZE], vec![0_u8; TILE_SIZE * TILE_SIZE]];
+
+		// Neighboring tiles overlap by the 3 pixels on each side which are needed to interpolate the pixels at the edge of a tile
+		for top in (2..height.saturating_sub(5)).step_by(TILE_SIZE - 6) {
+			for left in (2..width.saturating_sub(5)).step_by(TILE_SIZE - 6) {
+				let bottom = (top + TILE_SIZE).min(height - 2);
+				let right = (left + TILE_SIZE).min(width - 2);
+				let tile_index = |row: usize, column: usize| (row - top) * TILE_SIZE + (column - left);
+
+				// Interpolates green horizontally and vertically at the red and blue pixels
+				for row in top..bottom {
+					let first_column = if color_at(row, left) == 1 { left + 1 } else { left };
+					for column in (first_column..right).step_by(2) {
+						let center = raw(row, column);
+						let (west, east) = (raw(row, column - 1), raw(row, column + 1));
+						let (north, south) = (raw(row - 1, column), raw(row + 1, column));
+
+						let horizontal = ((west + center + east) * 2 - raw(row, column - 2) - raw(row, column + 2)) >> 2;
+						rgb[0][tile_index(row, column)][1] = limit(horizontal, west, east) as u16;
+						let vertical = ((north + center + south) * 2 - raw(row - 2, column) - raw(row + 2, column)) >> 2;
+						rgb[1][tile_index(row, column)][1] = limit(vertical, north, south) as u16;
+					}
+				}
+
+				// Interpolates red and blue from the differences to the interpolated green, and converts the result to CIELAB
+				for (rgb, lab) in rgb.iter_mut().zip(lab.iter_mut()) {
+					for row in top + 1..(top + TILE_SIZE - 1).min(height - 3) {
+						for column in left + 1..(left + TILE_SIZE - 1).min(width - 3) {
+							let index = tile_index(row, column);
+							let green = |index: usize| rgb[index][1] as i32;
+							let own_color = color_at(row, column);
+
+							if own_color == 1 {
+								let vertical_color = color_at(row + 1, column);
+								let horizontal_color = 2 - vertical_color;
+								let horizontal = raw(row, column) + ((raw(row, column - 1) + raw(row, column + 1) - green(index - 1) - green(index + 1)) >> 1);
+								let vertical = raw(row, column) + ((raw(row - 1, column) + raw(row + 1, column) - green(index - TILE_SIZE) - green(index + TILE_SIZE)) >> 1);
+								rgb[index][horizontal_color] = clip(horizontal);
+								rgb[index][vertical_color] = clip(vertical);
+							} else {
+								let diagonals = raw(row - 1, column - 1) + raw(row - 1, column + 1) + raw(row + 1, column - 1) + raw(row + 1, column + 1);
+								let diagonal_greens = green(index - TILE_SIZE - 1) + green(index - TILE_SIZE + 1) + green(index + TILE_SIZE - 1) + green(index + TILE_SIZE + 1);
+								let value = green(index) + ((diagonals - diagonal_greens + 1) >> 2);
+								rgb[index][2 - own_color] = clip(value);
+							}
+							rgb[index][own_color] = raw(row, column) as u16;
+
+							lab[index] = cielab.convert(rgb[index]);
+						}
+					}
+				}
+
+				// Counts the neighbors of each pixel whose color is within the smaller of the horizontal and vertical variations
+				for row in top + 2..(top + TILE_SIZE - 2).min(height - 4) {
+					for column in left + 2..(left + TILE_SIZE - 2).min(width - 4) {
+						let index = tile_index(row, column);
+						let neighbors = [index - 1, index + 1, index - TILE_SIZE, index + TILE_SIZE];
+
+						let differences = lab.each_ref().map(|lab| {
+							neighbors.map(|neighbor| {
+								let [l, a, b] = lab[index];
+								let [neighbor_l, neighbor_a, neighbor_b] = lab[neighbor];
+								((l - neighbor_l).abs(), (a - neighbor_a).powi(2) + (b - neighbor_b).powi(2))
+							})
+						});
+
+						let luminance_epsilon = differences[0][0].0.max(differences[0][1].0).min(differences[1][2].0.max(differences[1][3].0));
+						let chrominance_epsilon = differences[0][0].1.max(differences[0][1].1).min(differences[1][2].1.max(differences[1][3].1));
+
+						for (homogeneity, differences) in homogeneity.iter_mut().zip(differences) {
+							homogeneity[index] = differences
+								.iter()
+								.filter(|&&(luminance, chrominance)| luminance <= luminance_epsilon && chrominance <= chrominance_epsilon)
+								.count() as u8;
+						}
+					}
+				}
+
+				// Picks the direction which is more homogeneous over the 3x3 neighborhood of each pixel
+				for row in top + 3..(top + TILE_SIZE - 3).min(height - 5) {
+					for column in left + 3..(left + TILE_SIZE - 3).min(width - 5) {
+						let index = tile_index(row, column);
+						let [horizontal, vertical] = homogeneity.each_ref().map(|homogeneity| {
+							[index - TILE_SIZE, index, index + TILE_SIZE]
+								.into_iter()
+								.flat_map(|center| [center - 1, center, center + 1])
+								.map(|neighbor| homogeneity[neighbor] as u32)
+								.sum::<u32>()
+						});
+
+						let color = match horizontal.cmp(&vertical) {
+							std::cmp::Ordering::Greater => rgb[0][index],
+							std::cmp::Ordering::Less => rgb[1][index],
+							std::cmp::Ordering::Equal => std::array::from_fn(|channel| ((rgb[0][index][channel] as u32 + rgb[1][index][channel] as u32) / 2) as u16),
+						};
+
+						let output_index = CHANNELS_IN_RGB * (row * width + column);
+						output[output_index..output_index + CHANNELS_IN_RGB].copy_from_slice(&color);
+					}
+				}
+			}
+		}
+
+		output
+	}
+}
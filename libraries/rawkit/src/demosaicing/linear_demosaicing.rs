@@ -1,81 +1,72 @@
-use crate::{Pixel, RawImage};
+use crate::{CHANNELS_IN_RGB, Pixel, RawImage};
 
-fn average(data: &[u16], indexes: impl Iterator<Item = i64>) -> u16 {
-	let mut sum = 0;
-	let mut count = 0;
-	for index in indexes {
-		if index >= 0 && (index as usize) < data.len() {
-			sum += data[index as usize] as u32;
-			count += 1;
-		}
-	}
+/// Offsets to the pixels of each color in the ring at the given distance around each position of the CFA pattern.
+fn ring_offsets(raw_image: &RawImage, distance: isize) -> Vec<[Vec<(isize, isize)>; CHANNELS_IN_RGB]> {
+	let size = raw_image.cfa_pattern.size();
+
+	(0..size * size)
+		.map(|position| {
+			let (row, column) = ((position / size) as isize, (position % size) as isize);
+			let mut offsets: [Vec<_>; CHANNELS_IN_RGB] = Default::default();
 
-	(sum / count) as u16
+			for row_offset in -distance..=distance {
+				for column_offset in -distance..=distance {
+					if row_offset.abs() != distance && column_offset.abs() != distance {
+						continue;
+					}
+
+					// Adding a multiple of the pattern size keeps the position positive without changing its color
+					let neighbor_row = (row + row_offset + 6 * size as isize) as usize;
+					let neighbor_column = (column + column_offset + 6 * size as isize) as usize;
+					offsets[raw_image.cfa_pattern.color_at(neighbor_row, neighbor_column)].push((row_offset, column_offset));
+				}
+			}
+
+			offsets
+		})
+		.collect()
 }
 
 impl RawImage {
 	pub fn linear_demosaic_iter(&self) -> impl Iterator<Item = Pixel> + use<'_> {
-		match self.cfa_pattern {
-			[0, 1, 1, 2] => self.linear_demosaic_rggb_iter(),
-			_ => todo!(),
-		}
-	}
+		let size = self.cfa_pattern.size();
+		let rings = [ring_offsets(self, 1), ring_offsets(self, 2)];
 
-	fn linear_demosaic_rggb_iter(&self) -> impl Iterator<Item = Pixel> + use<'_> {
-		let width = self.width as i64;
-		let height = self.height as i64;
+		(0..self.width * self.height).map(move |index| {
+			let row = index / self.width;
+			let column = index % self.width;
+			let position = (row % size) * size + column % size;
+			let own_color = self.cfa_pattern.color_at(row, column);
 
-		(0..height).flat_map(move |row| {
-			let row_by_width = row * width;
-
-			(0..width).map(move |column| {
-				let pixel_index = row_by_width + column;
+			let values = std::array::from_fn(|color| {
+				if color == own_color {
+					return self.data[index];
+				}
 
-				let vertical_indexes = [pixel_index + width, pixel_index - width];
-				let horizontal_indexes = [pixel_index + 1, pixel_index - 1];
-				let cross_indexes = [pixel_index + width, pixel_index - width, pixel_index + 1, pixel_index - 1];
-				let diagonal_indexes = [pixel_index + width + 1, pixel_index - width + 1, pixel_index + width - 1, pixel_index - width - 1];
+				// Averages the closest ring which contains pixels of this color, which can be further out at the edges of the image
+				for ring in &rings {
+					let (sum, count) = ring[position][color]
+						.iter()
+						.filter_map(|&(row_offset, column_offset)| {
+							let row = row.checked_add_signed(row_offset).filter(|&row| row < self.height)?;
+							let column = column.checked_add_signed(column_offset).filter(|&column| column < self.width)?;
+							Some(self.data[row * self.width + column] as u32)
+						})
+						.fold((0, 0), |(sum, count), value| (sum + value, count + 1));
 
-				let pixel_index = pixel_index as usize;
-				match (row % 2 == 0, column % 2 == 0) {
-					(true, true) => Pixel {
-						values: [
-							self.data[pixel_index],
-							average(&self.data, cross_indexes.into_iter()),
-							average(&self.data, diagonal_indexes.into_iter()),
-						],
-						row: row as usize,
-						column: column as usize,
-					},
-					(true, false) => Pixel {
-						values: [
-							average(&self.data, horizontal_indexes.into_iter()),
-							self.data[pixel_index],
-							average(&self.data, vertical_indexes.into_iter()),
-						],
-						row: row as usize,
-						column: column as usize,
-					},
-					(false, true) => Pixel {
-						values: [
-							average(&self.data, vertical_indexes.into_iter()),
-							self.data[pixel_index],
-							average(&self.data, horizontal_indexes.into_iter()),
-						],
-						row: row as usize,
-						column: column as usize,
-					},
-					(false, false) => Pixel {
-						values: [
-							average(&self.data, diagonal_indexes.into_iter()),
-							average(&self.data, cross_indexes.into_iter()),
-							self.data[pixel_index],
-						],
-						row: row as usize,
-						column: column as usize,
-					},
+					if let Some(average) = sum.checked_div(count) {
+						return average as u16;
+					}
 				}
-			})
+
+				0
+			});
+
+			Pixel { values, row, column }
 		})
 	}
+
+	pub(crate) fn linear_demosaic(&self) -> Vec<u16> {
+		self.linear_demosaic_iter().flat_map(|pixel| pixel.values).collect()
+	}
 }
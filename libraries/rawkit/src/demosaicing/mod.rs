@@ -1 +1,50 @@
+pub mod ahd_demosaicing;
 pub mod linear_demosaicing;
+pub mod vng_demosaicing;
+
+use crate::{ColorFilterArray, Image, RawImage};
+
+/// Algorithm used to interpolate the two colors of each pixel which aren't captured by the camera sensor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DemosaicAlgorithm {
+	/// Averages the nearest pixels of each color, which is fast but blurs edges and adds color fringes around them.
+	#[default]
+	Linear,
+
+	/// Variable Number of Gradients, which only interpolates along the directions with the smallest changes in brightness.
+	Vng,
+
+	/// Adaptive Homogeneity-Directed, which interpolates both horizontally and vertically and picks whichever gives the smoother result in the CIELAB color space.
+	///
+	/// It only supports Bayer patterns and falls back to [`DemosaicAlgorithm::Vng`] for other patterns.
+	Ahd,
+}
+
+impl ColorFilterArray {
+	/// Whether the pattern is a Bayer pattern with both green pixels on a diagonal, which is the case for the RGGB, BGGR, GRBG and GBRG patterns.
+	pub fn is_standard_bayer(&self) -> bool {
+		match self {
+			ColorFilterArray::Bayer([1, a, b, 1]) | ColorFilterArray::Bayer([a, 1, 1, b]) => (*a == 0 && *b == 2) || (*a == 2 && *b == 0),
+			_ => false,
+		}
+	}
+}
+
+impl RawImage {
+	/// Interpolates the missing colors of each pixel, giving an [`Image`] with three channels in the color space of the camera.
+	pub fn demosaic(&self, algorithm: DemosaicAlgorithm) -> Image<u16> {
+		let data = match algorithm {
+			DemosaicAlgorithm::Linear => self.linear_demosaic(),
+			DemosaicAlgorithm::Ahd if self.cfa_pattern.is_standard_bayer() => self.ahd_demosaic(),
+			DemosaicAlgorithm::Vng | DemosaicAlgorithm::Ahd => self.vng_demosaic(),
+		};
+
+		Image {
+			channels: 3,
+			data,
+			width: self.width,
+			height: self.height,
+			transform: self.transform,
+		}
+	}
+}
@@ -0,0 +1,119 @@
+use crate::{CHANNELS_IN_RGB, RawImage};
+
+type Offset = (isize, isize);
+
+/// Pairs of pixels which are compared to find the gradient towards the north, along with the weight of each pair.
+const NORTH_GRADIENT: [(Offset, Offset, f64); 6] = [
+	((-1, 0), (1, 0), 1.),
+	((-2, 0), (0, 0), 1.),
+	((-1, -1), (1, -1), 0.5),
+	((-1, 1), (1, 1), 0.5),
+	((-2, -1), (0, -1), 0.5),
+	((-2, 1), (0, 1), 0.5),
+];
+
+/// Pairs of pixels which are compared to find the gradient towards the north east, along with the weight of each pair.
+const NORTH_EAST_GRADIENT: [(Offset, Offset, f64); 6] = [
+	((-1, 1), (1, -1), 1.),
+	((-2, 2), (0, 0), 1.),
+	((-1, 0), (0, -1), 0.5),
+	((0, 1), (1, 0), 0.5),
+	((-2, 1), (-1, 0), 0.5),
+	((-1, 2), (0, 1), 0.5),
+];
+
+/// Rotates an offset by 90 degrees clockwise.
+fn rotate((row, column): Offset) -> Offset {
+	(column, -row)
+}
+
+impl RawImage {
+	/// Demosaics the image with the Variable Number of Gradients algorithm by Chang, Cheung and Pang.
+	///
+	/// Gradients are calculated in 8 directions from pairs of pixels with the same color, and the colors of the directions with a gradient below a threshold are averaged.
+	/// Pixels near the border, where not all gradients are available, keep their linearly interpolated colors.
+	pub(crate) fn vng_demosaic(&self) -> Vec<u16> {
+		let linear = self.linear_demosaic();
+		let mut output = linear.clone();
+
+		// The directions are ordered clockwise from north, with the gradients of the straight and diagonal directions rotated from the north and north east ones
+		let mut directions = Vec::with_capacity(8);
+		let (mut straight, mut diagonal) = ((-1, 0), (-1, 1));
+		let (mut straight_gradient, mut diagonal_gradient) = (NORTH_GRADIENT, NORTH_EAST_GRADIENT);
+		for _ in 0..4 {
+			directions.push((straight, straight_gradient));
+			directions.push((diagonal, diagonal_gradient));
+			straight = rotate(straight);
+			diagonal = rotate(diagonal);
+			straight_gradient = straight_gradient.map(|(first, second, weight)| (rotate(first), rotate(second), weight));
+			diagonal_gradient = diagonal_gradient.map(|(first, second, weight)| (rotate(first), rotate(second), weight));
+		}
+
+		let width = self.width;
+		let color_at = |row: usize, column: usize, (row_offset, column_offset): Offset| self.cfa_pattern.color_at(row.wrapping_add_signed(row_offset), column.wrapping_add_signed(column_offset));
+
+		for row in 2..self.height.saturating_sub(2) {
+			for column in 2..width.saturating_sub(2) {
+				let index_at = |(row_offset, column_offset): Offset| row.wrapping_add_signed(row_offset) * width + column.wrapping_add_signed(column_offset);
+				let raw_at = |offset: Offset| self.data[index_at(offset)] as f64;
+
+				// The pattern is periodic, so the colors can be looked up relative to a position which is never negative
+				let (pattern_row, pattern_column) = (row % 6 + 6, column % 6 + 6);
+
+				let gradients: [Option<f64>; 8] = std::array::from_fn(|direction| {
+					let (sum, weight) = directions[direction]
+						.1
+						.iter()
+						.filter(|(first, second, _)| color_at(pattern_row, pattern_column, *first) == color_at(pattern_row, pattern_column, *second))
+						.fold((0., 0.), |(sum, total_weight), &(first, second, weight)| {
+							(sum + weight * (raw_at(first) - raw_at(second)).abs(), total_weight + weight)
+						});
+
+					if weight > 0. { Some(sum / weight) } else { None }
+				});
+
+				let (minimum, maximum) = gradients
+					.iter()
+					.flatten()
+					.fold((f64::INFINITY, f64::NEG_INFINITY), |(minimum, maximum), &gradient| (minimum.min(gradient), maximum.max(gradient)));
+				if minimum > maximum {
+					continue;
+				}
+				let threshold = 1.5 * minimum + 0.5 * (maximum - minimum);
+
+				let own_color = self.cfa_pattern.color_at(row, column);
+				let center = self.data[row * width + column] as f64;
+				let mut sum = [0.; CHANNELS_IN_RGB];
+				let mut count = 0.;
+
+				for (&(offset, _), gradient) in directions.iter().zip(gradients) {
+					if gradient.is_none_or(|gradient| gradient > threshold) {
+						continue;
+					}
+
+					let neighbor = index_at(offset);
+					let further = (2 * offset.0, 2 * offset.1);
+					for (color, sum) in sum.iter_mut().enumerate() {
+						*sum += if color == own_color && color_at(pattern_row, pattern_column, offset) != own_color && color_at(pattern_row, pattern_column, further) == own_color {
+							// Estimates the color of the center pixel at the neighbor from the center and the next pixel of the same color in this direction
+							(center + raw_at(further)) / 2.
+						} else {
+							linear[CHANNELS_IN_RGB * neighbor + color] as f64
+						};
+					}
+					count += 1.;
+				}
+
+				let index = row * width + column;
+				for color in 0..CHANNELS_IN_RGB {
+					if color != own_color {
+						let value = center + (sum[color] - sum[own_color]) / count;
+						output[CHANNELS_IN_RGB * index + color] = value.round().clamp(0., u16::MAX as f64) as u16;
+					}
+				}
+			}
+		}
+
+		output
+	}
+}
@@ -0,0 +1,258 @@
+//! A configurable alternative to the fixed pipeline of [`RawImage::process_16bit`], for applications which let the user develop raw images.
+
+use crate::demosaicing::DemosaicAlgorithm;
+use crate::metadata::camera_data::{pseudoinverse, transpose};
+use crate::{CHANNELS_IN_RGB, Image, Pixel, RawImage, RawPixel, Transform};
+
+/// Converts from the XYZ color space to linear sRGB, which is the color space of [`RawImage::camera_to_rgb`].
+const XYZ_TO_RGB: [[f64; 3]; 3] = [
+	// Matrix:
+	[3.240479, -1.537150, -0.498535],
+	[-0.969256, 1.875992, 0.041556],
+	[0.055648, -0.204043, 1.057311],
+];
+
+/// Converts from linear sRGB to linear Adobe RGB (1998).
+const RGB_TO_ADOBE_RGB: [[f64; 3]; 3] = [
+	// Matrix:
+	[0.715146, 0.284856, 0.],
+	[0., 1., 0.],
+	[0., 0.041166, 0.958839],
+];
+
+/// Converts from linear sRGB to linear ProPhoto RGB.
+const RGB_TO_PROPHOTO_RGB: [[f64; 3]; 3] = [
+	// Matrix:
+	[0.529317, 0.330092, 0.140588],
+	[0.098368, 0.873465, 0.028169],
+	[0.016879, 0.117663, 0.865457],
+];
+
+/// How the multipliers which neutralize the color of the light in the scene are chosen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WhiteBalance {
+	/// Uses the white balance recorded by the camera, falling back to daylight if the raw file doesn't contain it.
+	AsShot,
+
+	/// Neutralizes the light of a black body with the given color temperature in kelvin.
+	///
+	/// The tint ranges from -100 to 100, where positive values shift the colors towards magenta and negative values towards green.
+	Temperature { temperature: f64, tint: f64 },
+
+	/// Multipliers for the Red, Green and Blue pixels.
+	Custom([f64; 3]),
+}
+
+/// How the parts of the image where at least one color of the sensor is saturated are handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HighlightRecovery {
+	/// Clips all colors at the saturation point, which turns highlights white.
+	#[default]
+	Clip,
+
+	/// Keeps the colors above the saturation point, which can be recovered by lowering the exposure but often gives highlights a color cast.
+	Unclip,
+
+	/// Blends the clipped and unclipped colors, which keeps the brightness of the unclipped colors with the hue of the clipped ones.
+	Blend,
+}
+
+/// The color space of the developed image.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OutputColorSpace {
+	#[default]
+	Srgb,
+	AdobeRgb,
+	ProPhotoRgb,
+	/// The color space of the camera sensor, without any color conversion.
+	Camera,
+}
+
+impl OutputColorSpace {
+	/// Applies the transfer function of the color space to a linear value between 0 and 1.
+	pub fn encode(&self, value: f64) -> f64 {
+		match self {
+			OutputColorSpace::Srgb | OutputColorSpace::Camera => {
+				if value <= 0.0031308 {
+					value * 12.92
+				} else {
+					1.055 * value.powf(1. / 2.4) - 0.055
+				}
+			}
+			OutputColorSpace::AdobeRgb => value.powf(256. / 563.),
+			OutputColorSpace::ProPhotoRgb => {
+				if value < 1. / 512. {
+					value * 16.
+				} else {
+					value.powf(1. / 1.8)
+				}
+			}
+		}
+	}
+}
+
+/// Parameters for [`RawImage::develop`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DevelopSettings {
+	pub demosaic_algorithm: DemosaicAlgorithm,
+	pub white_balance: WhiteBalance,
+
+	/// Exposure compensation in stops.
+	pub exposure: f64,
+
+	pub highlight_recovery: HighlightRecovery,
+	pub output_color_space: OutputColorSpace,
+
+	/// Whether the developed image stays in linear light instead of being encoded with the transfer function of the output color space.
+	pub linear: bool,
+}
+
+impl Default for DevelopSettings {
+	fn default() -> Self {
+		DevelopSettings {
+			demosaic_algorithm: DemosaicAlgorithm::default(),
+			white_balance: WhiteBalance::AsShot,
+			exposure: 0.,
+			highlight_recovery: HighlightRecovery::default(),
+			output_color_space: OutputColorSpace::default(),
+			linear: false,
+		}
+	}
+}
+
+/// Chromaticity of the light of a black body with the given color temperature in kelvin, using the approximation by Kim et al.
+fn black_body_chromaticity(temperature: f64) -> (f64, f64) {
+	let t = temperature.clamp(1667., 25000.);
+
+	let x = if t <= 4000. {
+		-0.2661239e9 / t.powi(3) - 0.2343589e6 / t.powi(2) + 0.8776956e3 / t + 0.179910
+	} else {
+		-3.0258469e9 / t.powi(3) + 2.1070379e6 / t.powi(2) + 0.2226347e3 / t + 0.240390
+	};
+
+	let y = if t <= 2222. {
+		-1.1063814 * x.powi(3) - 1.34811020 * x.powi(2) + 2.18555832 * x - 0.20219683
+	} else if t <= 4000. {
+		-0.9549476 * x.powi(3) - 1.37418593 * x.powi(2) + 2.09137015 * x - 0.16748867
+	} else {
+		3.0817580 * x.powi(3) - 5.87338670 * x.powi(2) + 3.75112997 * x - 0.37001483
+	};
+
+	(x, y)
+}
+
+fn multiply(matrix: &[[f64; 3]; 3], vector: [f64; 3]) -> [f64; 3] {
+	matrix.map(|row| row.iter().zip(vector).map(|(&coefficient, value)| coefficient * value).sum())
+}
+
+fn multiply_matrices(a: &[[f64; 3]; 3], b: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+	std::array::from_fn(|i| std::array::from_fn(|j| (0..3).map(|k| a[i][k] * b[k][j]).sum()))
+}
+
+/// Reconstructs the clipped colors of a pixel by scaling the chroma of the clipped color to match the chroma of the unclipped color, in the same way as dcraw.
+fn blend_highlights(color: [f64; CHANNELS_IN_RGB], clip: f64) -> [f64; CHANNELS_IN_RGB] {
+	const TRANSFORM: [[f64; 3]; 3] = [[1., 1., 1.], [1.7320508, -1.7320508, 0.], [-1., -1., 2.]];
+	const INVERSE_TRANSFORM: [[f64; 3]; 3] = [[1., 0.8660254, -0.5], [1., -0.8660254, -0.5], [1., 0., 1.]];
+
+	if color.iter().all(|&value| value <= clip) {
+		return color;
+	}
+
+	let unclipped = multiply(&TRANSFORM, color);
+	let clipped = multiply(&TRANSFORM, color.map(|value| value.min(clip)));
+
+	let unclipped_chroma = unclipped[1].powi(2) + unclipped[2].powi(2);
+	let clipped_chroma = clipped[1].powi(2) + clipped[2].powi(2);
+	let chroma_ratio = if unclipped_chroma > 0. { (clipped_chroma / unclipped_chroma).sqrt() } else { 0. };
+
+	let blended = [unclipped[0], unclipped[1] * chroma_ratio, unclipped[2] * chroma_ratio];
+	multiply(&INVERSE_TRANSFORM, blended).map(|value| value / 3.)
+}
+
+impl RawImage {
+	/// Calculates the multipliers of the Red, Green and Blue pixels for the given white balance.
+	pub fn white_balance_multipliers(&self, white_balance: WhiteBalance) -> [f64; 3] {
+		let multipliers = match white_balance {
+			WhiteBalance::AsShot => self.white_balance.or(self.camera_white_balance).or(self.daylight_white_balance).unwrap_or([1.; 3]),
+			WhiteBalance::Temperature { temperature, tint } => {
+				// `camera_to_rgb` expects colors which are balanced for daylight, so its inverse gives how the camera sees the light relative to daylight
+				let rgb_to_camera = self
+					.camera_to_rgb
+					.map(|camera_to_rgb| transpose(pseudoinverse(camera_to_rgb)))
+					.unwrap_or([[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]);
+				let daylight = self.daylight_white_balance.unwrap_or([1.; 3]);
+
+				let (x, y) = black_body_chromaticity(temperature);
+				let light = multiply(&rgb_to_camera, multiply(&XYZ_TO_RGB, [x / y, 1., (1. - x - y) / y]));
+
+				let mut multipliers = std::array::from_fn(|color| if light[color] > 0. { daylight[color] / light[color] } else { daylight[color] });
+				multipliers[1] *= 1. - tint.clamp(-100., 100.) / 200.;
+				multipliers
+			}
+			WhiteBalance::Custom(multipliers) => multipliers,
+		};
+
+		if multipliers.iter().all(|&multiplier| multiplier.is_finite() && multiplier > 0.) {
+			multipliers
+		} else {
+			[1.; 3]
+		}
+	}
+
+	/// Converts the [`RawImage`] to an [`Image`] with 16 bit resolution for each channel, using the given settings instead of the fixed choices of [`RawImage::process_16bit`].
+	pub fn develop(self, settings: &DevelopSettings) -> Image<u16> {
+		let white_balance = self.white_balance_multipliers(settings.white_balance);
+		let minimum_multiplier = white_balance.into_iter().fold(f64::INFINITY, f64::min);
+		let maximum_multiplier = white_balance.into_iter().fold(f64::NEG_INFINITY, f64::max);
+
+		// White balance is applied before demosaicing, scaled down so no color is clipped, and the scale is undone afterwards so highlights can be recovered
+		let headroom = maximum_multiplier / minimum_multiplier;
+		let cfa_pattern = self.cfa_pattern;
+		let scaled_white_balance = white_balance.map(|multiplier| multiplier / maximum_multiplier);
+		let scale_white_balance = move |pixel: RawPixel| (pixel.value as f64 * scaled_white_balance[cfa_pattern.color_at(pixel.row, pixel.column)]).round() as u16;
+
+		let subtract_black = self.subtract_black_fn();
+		let scale_to_16bit = self.scale_to_16bit_fn();
+		let raw_image = self.apply((subtract_black, scale_to_16bit, scale_white_balance));
+
+		let camera_to_output = match settings.output_color_space {
+			OutputColorSpace::Camera => [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]],
+			color_space => {
+				let camera_to_rgb = raw_image.camera_to_rgb.unwrap_or([[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]);
+				match color_space {
+					OutputColorSpace::AdobeRgb => multiply_matrices(&RGB_TO_ADOBE_RGB, &camera_to_rgb),
+					OutputColorSpace::ProPhotoRgb => multiply_matrices(&RGB_TO_PROPHOTO_RGB, &camera_to_rgb),
+					_ => camera_to_rgb,
+				}
+			}
+		};
+
+		let exposure = settings.exposure.exp2();
+		let highlight_recovery = settings.highlight_recovery;
+		let output_color_space = settings.output_color_space;
+		let linear = settings.linear;
+
+		let develop = move |pixel: Pixel| {
+			// The saturation point of the sensor is at 1 for the color with the smallest multiplier, and above 1 for the others
+			let color = pixel.values.map(|value| value as f64 / u16::MAX as f64 * headroom);
+			let color = match highlight_recovery {
+				HighlightRecovery::Clip => color.map(|value| value.min(1.)),
+				HighlightRecovery::Unclip => color,
+				HighlightRecovery::Blend => blend_highlights(color, 1.),
+			};
+
+			multiply(&camera_to_output, color).map(|value| {
+				let value = (value * exposure).clamp(0., 1.);
+				let value = if linear { value } else { output_color_space.encode(value) };
+				(value * u16::MAX as f64).round() as u16
+			})
+		};
+
+		let image = raw_image.demosaic(settings.demosaic_algorithm);
+		if image.transform == Transform::Horizontal {
+			image.apply(develop)
+		} else {
+			image.transform_and_apply(develop)
+		}
+	}
+}
@@ -1,18 +1,20 @@
 pub mod decoder;
 pub mod demosaicing;
+pub mod develop;
 pub mod metadata;
 pub mod postprocessing;
 pub mod preprocessing;
 pub mod processing;
 pub mod tiff;
 
+use crate::demosaicing::DemosaicAlgorithm;
 use crate::metadata::identify::CameraModel;
 use processing::{Pixel, PixelTransform, RawPixel, RawPixelTransform};
 use rawkit_proc_macros::Tag;
 use std::io::{Read, Seek};
 use thiserror::Error;
 use tiff::file::TiffRead;
-use tiff::tags::{Compression, ImageLength, ImageWidth, Orientation, StripByteCounts, SubIfd, Tag};
+use tiff::tags::{Compression, DngVersion, ImageLength, ImageWidth, Orientation, StripByteCounts, SubIfd, Tag};
 use tiff::values::Transform;
 use tiff::{Ifd, TiffError};
 
@@ -31,6 +33,63 @@ pub enum SubtractBlack {
 	CfaGrid([u16; 4]),
 }
 
+impl SubtractBlack {
+	/// The black level of each of the 4 pixels in the Bayer CFA Grid.
+	pub fn cfa_grid(&self) -> [u16; 4] {
+		match *self {
+			SubtractBlack::None => [0; 4],
+			SubtractBlack::Value(value) => [value; 4],
+			SubtractBlack::CfaGrid(values) => values,
+		}
+	}
+}
+
+/// The arrangement of the color filters over the pixels of the camera sensor, which repeats over the whole sensor.
+///
+/// It encodes Red, Green and Blue as 0, 1, and 2 respectively, with the colors stored in row major order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorFilterArray {
+	/// A 2x2 Bayer pattern, which is used by almost every camera.
+	Bayer([u8; 4]),
+
+	/// A 6x6 X-Trans pattern, which is used by many Fujifilm cameras.
+	XTrans([u8; 36]),
+}
+
+impl ColorFilterArray {
+	/// The width and height of the repeating pattern.
+	pub fn size(&self) -> usize {
+		match self {
+			ColorFilterArray::Bayer(_) => 2,
+			ColorFilterArray::XTrans(_) => 6,
+		}
+	}
+
+	/// The color of the filter over the pixel in the given row and column of the sensor.
+	pub fn color_at(&self, row: usize, column: usize) -> usize {
+		match self {
+			ColorFilterArray::Bayer(colors) => colors[2 * (row % 2) + column % 2] as usize,
+			ColorFilterArray::XTrans(colors) => colors[6 * (row % 6) + column % 6] as usize,
+		}
+	}
+}
+
+impl TryFrom<Vec<u8>> for ColorFilterArray {
+	type Error = DecoderError;
+
+	fn try_from(colors: Vec<u8>) -> Result<Self, Self::Error> {
+		if colors.iter().any(|&color| color > 2) {
+			return Err(DecoderError::UnsupportedLayout("CFA pattern with colors other than red, green and blue".to_string()));
+		}
+
+		match colors.len() {
+			4 => Ok(ColorFilterArray::Bayer(colors.try_into().unwrap())),
+			36 => Ok(ColorFilterArray::XTrans(colors.try_into().unwrap())),
+			len => Err(DecoderError::UnsupportedLayout(format!("CFA pattern with {len} colors"))),
+		}
+	}
+}
+
 /// Represents a Raw Image along with its metadata.
 pub struct RawImage {
 	/// Raw pixel data stored in linear fashion.
@@ -42,10 +101,8 @@ pub struct RawImage {
 	/// Height of the raw image.
 	pub height: usize,
 
-	/// Bayer CFA pattern used to arrange pixels in [`RawImage::data`].
-	///
-	/// It encodes Red, Blue and Green as 0, 1, and 2 respectively.
-	pub cfa_pattern: [u8; 4],
+	/// CFA pattern used to arrange pixels in [`RawImage::data`].
+	pub cfa_pattern: ColorFilterArray,
 
 	/// Transformation to be applied to negate the orientation of camera.
 	pub transform: Transform,
@@ -63,16 +120,21 @@ pub struct RawImage {
 
 	/// White balance specified in the metadata of the raw file.
 	///
-	/// It represents the 4 values of CFA Grid which follows the same pattern as [`RawImage::cfa_pattern`].
-	pub camera_white_balance: Option<[f64; 4]>,
+	/// It represents the multipliers of the Red, Green and Blue pixels.
+	pub camera_white_balance: Option<[f64; 3]>,
 
 	/// White balance of the raw image.
 	///
 	/// It is the same as [`RawImage::camera_white_balance`] if the raw file contains the metadata.
 	/// Otherwise it falls back to calculating the white balance from the color space conversion matrix.
 	///
-	/// It represents the 4 values of CFA Grid which follows the same pattern as [`RawImage::cfa_pattern`].
-	pub white_balance: Option<[f64; 4]>,
+	/// It represents the multipliers of the Red, Green and Blue pixels.
+	pub white_balance: Option<[f64; 3]>,
+
+	/// White balance which makes neutral surfaces lit by daylight appear neutral.
+	///
+	/// It is calculated from the color space conversion matrix, and represents the multipliers of the Red, Green and Blue pixels.
+	pub daylight_white_balance: Option<[f64; 3]>,
 
 	/// Color space conversion matrix to convert from camera's color space to sRGB.
 	pub camera_to_rgb: Option<[[f64; 3]; 3]>,
@@ -114,42 +176,67 @@ impl RawImage {
 	/// Create a [`RawImage`] from an input stream.
 	///
 	/// Decodes the contents of `reader` and extracts raw pixel data and metadata.
+	/// DNG files are supported for every camera, other raw formats only for the cameras which have a dedicated decoder.
 	pub fn decode<R: Read + Seek>(reader: &mut R) -> Result<RawImage, DecoderError> {
 		let mut file = TiffRead::new(reader)?;
 		let ifd = Ifd::new_first_ifd(&mut file)?;
 
-		let camera_model = metadata::identify::identify_camera_model(&ifd, &mut file).unwrap();
-		let transform = ifd.get_value::<Orientation, _>(&mut file)?;
+		let camera_model = metadata::identify::identify_camera_model(&ifd, &mut file);
+		let transform = ifd.get_value::<Option<Orientation>, _>(&mut file)?.unwrap_or(Transform::Horizontal);
 
-		let mut raw_image = if camera_model.model == "DSLR-A100" {
-			decoder::arw1::decode_a100(ifd, &mut file)
+		let mut raw_image = if ifd.get_value::<Option<DngVersion>, _>(&mut file)?.is_some() {
+			decoder::dng::decode(ifd, &mut file)?
 		} else {
-			let sub_ifd = ifd.get_value::<SubIfd, _>(&mut file)?;
-			let arw_ifd = sub_ifd.get_value::<ArwIfd, _>(&mut file)?;
-
-			if arw_ifd.compression == 1 {
-				decoder::uncompressed::decode(sub_ifd, &mut file)
-			} else if arw_ifd.strip_byte_counts[0] == arw_ifd.image_width * arw_ifd.image_height {
-				decoder::arw2::decode(sub_ifd, &mut file)
-			} else {
-				// TODO: implement for arw 1.
-				todo!()
+			match &camera_model {
+				Some(CameraModel { make, model }) if make == "Sony" => Self::decode_sony(model, ifd, &mut file)?,
+				Some(CameraModel { make, model }) => return Err(DecoderError::UnsupportedCamera(format!("{make} {model}"))),
+				None => return Err(DecoderError::UnsupportedCamera("Unknown".to_string())),
 			}
 		};
 
-		raw_image.camera_model = Some(camera_model);
+		raw_image.camera_model = camera_model;
 		raw_image.transform = transform;
 
-		raw_image.calculate_conversion_matrices();
+		if raw_image.camera_to_rgb.is_none() {
+			raw_image.calculate_conversion_matrices();
+		}
 
 		Ok(raw_image)
 	}
 
+	fn decode_sony<R: Read + Seek>(model: &str, ifd: Ifd, file: &mut TiffRead<R>) -> Result<RawImage, DecoderError> {
+		if model == "DSLR-A100" {
+			return decoder::arw1::decode_a100(ifd, file);
+		}
+
+		let sub_ifd = ifd.get_value::<SubIfd, _>(file)?;
+		let arw_ifd = sub_ifd.get_value::<ArwIfd, _>(file)?;
+
+		let pixel_count = u64::from(arw_ifd.image_width) * u64::from(arw_ifd.image_height);
+		let strip_byte_count = arw_ifd.strip_byte_counts.first().copied().map(u64::from).unwrap_or_default();
+
+		match arw_ifd.compression {
+			1 => decoder::uncompressed::decode(sub_ifd, file),
+			32767 if strip_byte_count == pixel_count => decoder::arw2::decode(sub_ifd, file),
+			// Some cameras store their uncompressed 14 bit data with the compression tag used by ARW files
+			32767 if strip_byte_count == 2 * pixel_count => decoder::uncompressed::decode(sub_ifd, file),
+			32767 => decoder::arw1::decode(sub_ifd, file),
+			compression => Err(DecoderError::UnsupportedLayout(format!("ARW file with compression {compression}"))),
+		}
+	}
+
 	/// Converts the [`RawImage`] to an [`Image`] with 8 bit resolution for each channel.
 	///
 	/// Applies all the processing steps to finally get RGB pixel data.
 	pub fn process_8bit(self) -> Image<u8> {
-		let image = self.process_16bit();
+		self.process_8bit_with_demosaic(DemosaicAlgorithm::default())
+	}
+
+	/// Converts the [`RawImage`] to an [`Image`] with 8 bit resolution for each channel, using the given demosaicing algorithm.
+	///
+	/// Applies all the processing steps to finally get RGB pixel data.
+	pub fn process_8bit_with_demosaic(self, algorithm: DemosaicAlgorithm) -> Image<u8> {
+		let image = self.process_16bit_with_demosaic(algorithm);
 
 		Image {
 			channels: image.channels,
@@ -164,6 +251,13 @@ impl RawImage {
 	///
 	/// Applies all the processing steps to finally get RGB pixel data.
 	pub fn process_16bit(self) -> Image<u16> {
+		self.process_16bit_with_demosaic(DemosaicAlgorithm::default())
+	}
+
+	/// Converts the [`RawImage`] to an [`Image`] with 16 bit resolution for each channel, using the given demosaicing algorithm.
+	///
+	/// Applies all the processing steps to finally get RGB pixel data.
+	pub fn process_16bit_with_demosaic(self, algorithm: DemosaicAlgorithm) -> Image<u16> {
 		let subtract_black = self.subtract_black_fn();
 		let scale_white_balance = self.scale_white_balance_fn();
 		let scale_to_16bit = self.scale_to_16bit_fn();
@@ -171,7 +265,7 @@ impl RawImage {
 
 		let convert_to_rgb = raw_image.convert_to_rgb_fn();
 		let mut record_histogram = raw_image.record_histogram_fn();
-		let image = raw_image.demosaic_and_apply((convert_to_rgb, &mut record_histogram));
+		let image = raw_image.demosaic_and_apply(algorithm, (convert_to_rgb, &mut record_histogram));
 
 		let gamma_correction = image.gamma_correction_fn(&record_histogram.histogram);
 		if image.transform == Transform::Horizontal {
@@ -196,23 +290,8 @@ impl RawImage {
 		self
 	}
 
-	pub fn demosaic_and_apply(self, mut transform: impl PixelTransform) -> Image<u16> {
-		let mut image = vec![0; self.width * self.height * 3];
-		for Pixel { values, row, column } in self.linear_demosaic_iter().map(|mut pixel| {
-			pixel.values = transform.apply(pixel);
-			pixel
-		}) {
-			let pixel_index = row * self.width + column;
-			image[3 * pixel_index..3 * (pixel_index + 1)].copy_from_slice(&values);
-		}
-
-		Image {
-			channels: 3,
-			data: image,
-			width: self.width,
-			height: self.height,
-			transform: self.transform,
-		}
+	pub fn demosaic_and_apply(self, algorithm: DemosaicAlgorithm, transform: impl PixelTransform) -> Image<u16> {
+		self.demosaic(algorithm).apply(transform)
 	}
 }
 
@@ -259,4 +338,10 @@ pub enum DecoderError {
 	ConversionError(#[from] std::num::TryFromIntError),
 	#[error("An IO Error ocurred")]
 	IoError(#[from] std::io::Error),
+	#[error("Raw files from the camera '{0}' are not supported")]
+	UnsupportedCamera(String),
+	#[error("The layout of the raw image is not supported: {0}")]
+	UnsupportedLayout(String),
+	#[error("The raw image data is corrupt: {0}")]
+	CorruptData(&'static str),
 }
@@ -17,7 +17,7 @@ impl CameraData {
 
 const CAMERA_DATA: [(&str, CameraData); 40] = build_camera_data!();
 
-const RGB_TO_XYZ: [[f64; 3]; 3] = [
+pub(crate) const RGB_TO_XYZ: [[f64; 3]; 3] = [
 	// Matrix:
 	[0.412453, 0.357580, 0.180423],
 	[0.212671, 0.715160, 0.072169],
@@ -35,6 +35,13 @@ impl RawImage {
 			.map(|(_, data)| data.xyz_to_camera.map(|x| (x as f64) / 10_000.));
 		let Some(xyz_to_camera) = xyz_to_camera else { return };
 
+		self.calculate_conversion_matrices_from_xyz_to_camera(xyz_to_camera);
+	}
+
+	/// Calculates [`RawImage::camera_to_rgb`], [`RawImage::white_balance`] and [`RawImage::daylight_white_balance`] from a matrix which converts from the XYZ color space to the camera's color space.
+	///
+	/// The matrix is stored in row major order, in the same way as the `ColorMatrix` tags of DNG files.
+	pub fn calculate_conversion_matrices_from_xyz_to_camera(&mut self, xyz_to_camera: [f64; 9]) {
 		let mut rgb_to_camera = [[0.; 3]; 3];
 		for i in 0..3 {
 			for j in 0..3 {
@@ -50,19 +57,14 @@ impl RawImage {
 		}
 		let camera_to_rgb = transpose(pseudoinverse(rgb_to_camera));
 
-		let cfa_white_balance_multiplier = if let Some(white_balance) = self.camera_white_balance {
-			white_balance
-		} else {
-			self.cfa_pattern.map(|index| white_balance_multiplier[index as usize])
-		};
-
-		self.white_balance = Some(cfa_white_balance_multiplier);
+		self.white_balance = Some(self.camera_white_balance.unwrap_or(white_balance_multiplier));
+		self.daylight_white_balance = Some(white_balance_multiplier);
 		self.camera_to_rgb = Some(camera_to_rgb);
 	}
 }
 
 #[allow(clippy::needless_range_loop)]
-fn pseudoinverse<const N: usize>(matrix: [[f64; 3]; N]) -> [[f64; 3]; N] {
+pub(crate) fn pseudoinverse<const N: usize>(matrix: [[f64; 3]; N]) -> [[f64; 3]; N] {
 	let mut output_matrix = [[0.; 3]; N];
 	let mut work = [[0.; 6]; 3];
 
@@ -105,7 +107,7 @@ fn pseudoinverse<const N: usize>(matrix: [[f64; 3]; N]) -> [[f64; 3]; N] {
 	output_matrix
 }
 
-fn transpose<const N: usize>(matrix: [[f64; 3]; N]) -> [[f64; N]; 3] {
+pub(crate) fn transpose<const N: usize>(matrix: [[f64; 3]; N]) -> [[f64; N]; 3] {
 	let mut output_matrix = [[0.; N]; 3];
 
 	for (i, row) in matrix.iter().enumerate() {
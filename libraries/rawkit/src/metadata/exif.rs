@@ -0,0 +1,265 @@
+use crate::tiff::Ifd;
+use crate::tiff::file::TiffRead;
+use crate::tiff::tags::{
+	DateTime, DateTimeOriginal, ExifIfd, ExposureBias, ExposureTime, FNumber, FocalLength, FocalLengthIn35mmFilm, GpsAltitude, GpsAltitudeRef, GpsIfd, GpsLatitude, GpsLatitudeRef, GpsLongitude,
+	GpsLongitudeRef, IsoSpeedRatings, LensMake, LensModel, Make, MakerNote, Model, OffsetTimeOriginal, Orientation, RecommendedExposureIndex, SonyLensSpec, SubSecTimeOriginal,
+};
+use crate::tiff::values::Transform;
+use crate::{DecoderError, RawImage};
+use std::fmt::Display;
+use std::io::{Read, Seek};
+
+/// The photographic metadata of a raw file, as recorded by the camera in its EXIF and maker note tags.
+///
+/// Every field is optional since cameras differ in what they record.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+	pub make: Option<String>,
+	pub model: Option<String>,
+	/// The lens model, or a description of its focal length and aperture range when the camera doesn't record the name.
+	pub lens: Option<String>,
+	pub iso: Option<u32>,
+	/// The shutter speed in seconds.
+	pub exposure_time: Option<f64>,
+	/// The aperture as an f-number.
+	pub f_number: Option<f64>,
+	/// The focal length in millimeters.
+	pub focal_length: Option<f64>,
+	/// The focal length in millimeters which gives the same field of view on a 35 mm camera.
+	pub focal_length_35mm: Option<u32>,
+	/// The exposure compensation in EV.
+	pub exposure_bias: Option<f64>,
+	pub capture_time: Option<CaptureTime>,
+	pub gps: Option<GpsPosition>,
+	pub orientation: Option<Transform>,
+}
+
+/// The local time when the photo was taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CaptureTime {
+	pub year: u16,
+	pub month: u8,
+	pub day: u8,
+	pub hour: u8,
+	pub minute: u8,
+	pub second: u8,
+	pub millisecond: Option<u16>,
+	/// The offset from UTC in minutes, when the camera records its time zone.
+	pub utc_offset: Option<i16>,
+}
+
+/// The location where the photo was taken, in degrees north and east and meters above sea level.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GpsPosition {
+	pub latitude: f64,
+	pub longitude: f64,
+	pub altitude: Option<f64>,
+}
+
+impl RawImage {
+	/// Reads the photographic metadata without decoding the image data, which is much faster than [`RawImage::decode`].
+	pub fn decode_metadata<R: Read + Seek>(reader: &mut R) -> Result<Metadata, DecoderError> {
+		let mut file = TiffRead::new(reader)?;
+		let ifd = Ifd::new_first_ifd(&mut file)?;
+
+		// Individual tags are read leniently so a single malformed tag doesn't hide the rest of the metadata
+		let make = ifd.get_value::<Make, _>(&mut file).ok().map(|make| make.trim().to_string()).filter(|make| !make.is_empty());
+		let model = ifd.get_value::<Model, _>(&mut file).ok().map(|model| model.trim().to_string()).filter(|model| !model.is_empty());
+		let orientation = ifd.get_value::<Orientation, _>(&mut file).ok();
+
+		let mut metadata = Metadata {
+			make,
+			model,
+			orientation,
+			..Default::default()
+		};
+
+		let exif_ifd = ifd.get_value::<ExifIfd, _>(&mut file).ok();
+		if let Some(exif_ifd) = &exif_ifd {
+			metadata.exposure_time = exif_ifd.get_value::<ExposureTime, _>(&mut file).ok().filter(|&x| x.is_finite() && x > 0.);
+			metadata.f_number = exif_ifd.get_value::<FNumber, _>(&mut file).ok().filter(|&x| x.is_finite() && x > 0.);
+			metadata.focal_length = exif_ifd.get_value::<FocalLength, _>(&mut file).ok().filter(|&x| x.is_finite() && x > 0.);
+			metadata.focal_length_35mm = exif_ifd.get_value::<FocalLengthIn35mmFilm, _>(&mut file).ok().filter(|&x| x > 0);
+			metadata.exposure_bias = exif_ifd.get_value::<ExposureBias, _>(&mut file).ok().filter(|x| x.is_finite());
+
+			// The ISO speed ratings tag only holds 16 bits, so cameras record higher values in the recommended exposure index instead
+			let iso = exif_ifd.get_value::<IsoSpeedRatings, _>(&mut file).ok().and_then(|ratings| ratings.first().copied());
+			metadata.iso = match iso {
+				Some(65535) | None => exif_ifd.get_value::<RecommendedExposureIndex, _>(&mut file).ok().or(iso),
+				iso => iso,
+			}
+			.filter(|&x| x > 0);
+
+			let lens_make = exif_ifd.get_value::<LensMake, _>(&mut file).ok().map(|make| make.trim().to_string()).filter(|make| !make.is_empty());
+			let lens_model = exif_ifd
+				.get_value::<LensModel, _>(&mut file)
+				.ok()
+				.map(|model| model.trim().to_string())
+				.filter(|model| !model.is_empty());
+			metadata.lens = match (lens_make, lens_model) {
+				(Some(make), Some(model)) if !model.starts_with(&make) => Some(format!("{make} {model}")),
+				(_, model) => model,
+			};
+
+			let date_time = exif_ifd.get_value::<DateTimeOriginal, _>(&mut file).ok();
+			let sub_second = exif_ifd.get_value::<SubSecTimeOriginal, _>(&mut file).ok();
+			let utc_offset = exif_ifd.get_value::<OffsetTimeOriginal, _>(&mut file).ok();
+			metadata.capture_time = date_time.and_then(|date_time| CaptureTime::parse(&date_time, sub_second.as_deref(), utc_offset.as_deref()));
+		}
+
+		if metadata.capture_time.is_none() {
+			metadata.capture_time = ifd.get_value::<DateTime, _>(&mut file).ok().and_then(|date_time| CaptureTime::parse(&date_time, None, None));
+		}
+
+		if metadata.lens.is_none() && metadata.make.as_deref().is_some_and(|make| make.eq_ignore_ascii_case("sony")) {
+			metadata.lens = exif_ifd.and_then(|exif_ifd| sony_lens_description(&exif_ifd, &mut file));
+		}
+
+		metadata.gps = ifd.get_value::<GpsIfd, _>(&mut file).ok().and_then(|gps_ifd| read_gps_position(&gps_ifd, &mut file));
+
+		Ok(metadata)
+	}
+}
+
+fn read_gps_position<R: Read + Seek>(ifd: &Ifd, file: &mut TiffRead<R>) -> Option<GpsPosition> {
+	let to_degrees = |[degrees, minutes, seconds]: [f64; 3]| degrees + minutes / 60. + seconds / 3600.;
+
+	let latitude = to_degrees(ifd.get_value::<GpsLatitude, _>(file).ok()?);
+	let longitude = to_degrees(ifd.get_value::<GpsLongitude, _>(file).ok()?);
+	if !latitude.is_finite() || !longitude.is_finite() {
+		return None;
+	}
+
+	let latitude = if ifd.get_value::<GpsLatitudeRef, _>(file).ok().is_some_and(|reference| reference.starts_with('S')) {
+		-latitude
+	} else {
+		latitude
+	};
+	let longitude = if ifd.get_value::<GpsLongitudeRef, _>(file).ok().is_some_and(|reference| reference.starts_with('W')) {
+		-longitude
+	} else {
+		longitude
+	};
+
+	let altitude = ifd.get_value::<GpsAltitude, _>(file).ok().filter(|x| x.is_finite());
+	let altitude = if ifd.get_value::<GpsAltitudeRef, _>(file).ok() == Some(1) {
+		altitude.map(|x| -x)
+	} else {
+		altitude
+	};
+
+	Some(GpsPosition { latitude, longitude, altitude })
+}
+
+/// Older Sony cameras don't record the lens model in the EXIF data, but their maker note has the focal length and aperture range of the lens.
+fn sony_lens_description<R: Read + Seek>(exif_ifd: &Ifd, file: &mut TiffRead<R>) -> Option<String> {
+	let maker_note_offset = exif_ifd.get_value::<MakerNote, _>(file).ok()?;
+
+	// Most Sony maker notes start with a 12 byte header like "SONY DSC \0\0\0" before the IFD, whose offsets are relative to the start of the file
+	file.seek_from_start(maker_note_offset).ok()?;
+	let header = file.read_n::<4>().ok()?;
+	let ifd_offset = if &header == b"SONY" { maker_note_offset.checked_add(12)? } else { maker_note_offset };
+
+	let maker_note = Ifd::new_from_offset(file, ifd_offset).ok()?;
+	let lens_spec = maker_note.get_value::<SonyLensSpec, _>(file).ok()?;
+
+	// The focal lengths and apertures are stored as binary-coded decimals, with flags for the lens series in the first and last bytes
+	let bcd = |byte: u8| u32::from(byte >> 4) * 10 + u32::from(byte & 0xf);
+	let short_focal_length = bcd(lens_spec[1]) * 100 + bcd(lens_spec[2]);
+	let long_focal_length = bcd(lens_spec[3]) * 100 + bcd(lens_spec[4]);
+	let short_aperture = bcd(lens_spec[5]) as f64 / 10.;
+	let long_aperture = bcd(lens_spec[6]) as f64 / 10.;
+
+	if short_focal_length == 0 {
+		return None;
+	}
+
+	let focal_length = if long_focal_length > short_focal_length {
+		format!("{short_focal_length}-{long_focal_length}mm")
+	} else {
+		format!("{short_focal_length}mm")
+	};
+	let aperture = match (short_aperture, long_aperture) {
+		(0., _) => String::new(),
+		(short, long) if long > short => format!(" F{short}-{long}"),
+		(short, _) => format!(" F{short}"),
+	};
+
+	Some(format!("{focal_length}{aperture}"))
+}
+
+impl CaptureTime {
+	/// Parses the EXIF date format `YYYY:MM:DD HH:MM:SS`, along with the optional sub-second digits and `±HH:MM` UTC offset which are stored in separate tags.
+	pub fn parse(date_time: &str, sub_second: Option<&str>, utc_offset: Option<&str>) -> Option<CaptureTime> {
+		let date_time = date_time.trim();
+		let number = |range: std::ops::Range<usize>| date_time.get(range)?.parse::<u16>().ok();
+
+		let separators = [(4, b':'), (7, b':'), (10, b' '), (13, b':'), (16, b':')];
+		if !separators.iter().all(|&(index, separator)| date_time.as_bytes().get(index) == Some(&separator)) {
+			return None;
+		}
+
+		let capture_time = CaptureTime {
+			year: number(0..4)?,
+			month: number(5..7)?.try_into().ok()?,
+			day: number(8..10)?.try_into().ok()?,
+			hour: number(11..13)?.try_into().ok()?,
+			minute: number(14..16)?.try_into().ok()?,
+			second: number(17..19)?.try_into().ok()?,
+			millisecond: sub_second.and_then(parse_milliseconds),
+			utc_offset: utc_offset.and_then(parse_utc_offset),
+		};
+
+		// Cameras without a clock set write zeros or blanks
+		let valid = (1..=12).contains(&capture_time.month) && (1..=31).contains(&capture_time.day) && capture_time.hour < 24 && capture_time.minute < 60 && capture_time.second < 61;
+		valid.then_some(capture_time)
+	}
+}
+
+fn parse_milliseconds(sub_second: &str) -> Option<u16> {
+	let digits = sub_second.trim();
+	if digits.is_empty() || !digits.bytes().all(|digit| digit.is_ascii_digit()) {
+		return None;
+	}
+
+	// The digits are a decimal fraction, so "5" means 500 ms
+	digits
+		.bytes()
+		.chain(std::iter::repeat(b'0'))
+		.take(3)
+		.try_fold(0, |milliseconds, digit| Some(milliseconds * 10 + u16::from(digit - b'0')))
+}
+
+fn parse_utc_offset(utc_offset: &str) -> Option<i16> {
+	let utc_offset = utc_offset.trim();
+	let sign = match utc_offset.get(0..1)? {
+		"+" => 1,
+		"-" => -1,
+		_ => return None,
+	};
+	if utc_offset.get(3..4)? != ":" {
+		return None;
+	}
+
+	let hours = utc_offset.get(1..3)?.parse::<i16>().ok()?;
+	let minutes = utc_offset.get(4..6)?.parse::<i16>().ok()?;
+	Some(sign * (hours * 60 + minutes))
+}
+
+impl Display for CaptureTime {
+	/// Formats the time according to ISO 8601.
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}", self.year, self.month, self.day, self.hour, self.minute, self.second)?;
+
+		if let Some(millisecond) = self.millisecond {
+			write!(f, ".{millisecond:03}")?;
+		}
+
+		if let Some(utc_offset) = self.utc_offset {
+			let sign = if utc_offset < 0 { '-' } else { '+' };
+			write!(f, "{sign}{:02}:{:02}", utc_offset.unsigned_abs() / 60, utc_offset.unsigned_abs() % 60)?;
+		}
+
+		Ok(())
+	}
+}
@@ -42,7 +42,7 @@ pub struct CameraModel {
 }
 
 pub fn identify_camera_model<R: Read + Seek>(ifd: &Ifd, file: &mut TiffRead<R>) -> Option<CameraModel> {
-	let mut ifd = ifd.get_value::<CameraModelIfd, _>(file).unwrap();
+	let mut ifd = ifd.get_value::<CameraModelIfd, _>(file).ok()?;
 
 	ifd.make.make_ascii_lowercase();
 	for company_name in COMPANY_NAMES {
@@ -1,2 +1,4 @@
 pub mod camera_data;
+pub mod exif;
 pub mod identify;
+pub mod preview;
@@ -0,0 +1,78 @@
+use crate::tiff::Ifd;
+use crate::tiff::file::TiffRead;
+use crate::tiff::tags::{Compression, JpegLength, JpegOffset, NewSubfileType, StripByteCounts, StripOffsets, SubIfds};
+use crate::{DecoderError, RawImage};
+use std::io::{Read, Seek, SeekFrom};
+
+/// Corrupt files could chain IFDs in a loop, so only this many are followed.
+const MAX_IFD_COUNT: usize = 16;
+
+impl RawImage {
+	/// Extracts the largest JPEG preview embedded by the camera, which can be shown as a thumbnail long before the raw data is decoded.
+	///
+	/// Returns `None` when the file has no embedded JPEG.
+	pub fn decode_preview<R: Read + Seek>(reader: &mut R) -> Result<Option<Vec<u8>>, DecoderError> {
+		let mut file = TiffRead::new(reader)?;
+		let file_length = file.seek(SeekFrom::End(0))?;
+
+		let mut ifds = Vec::new();
+		let mut next_ifd = Some(Ifd::new_first_ifd(&mut file)?);
+		while let Some(ifd) = next_ifd {
+			if ifds.len() >= MAX_IFD_COUNT {
+				break;
+			}
+
+			next_ifd = ifd.next_ifd(&mut file).ok().flatten();
+			let sub_ifds = ifd.get_value::<SubIfds, _>(&mut file).unwrap_or_default();
+			ifds.push(ifd);
+			ifds.extend(sub_ifds.into_iter().take(MAX_IFD_COUNT));
+		}
+
+		let mut largest_preview: Option<(u32, u32)> = None;
+		for ifd in &ifds {
+			let Some((offset, length)) = jpeg_location(ifd, &mut file) else { continue };
+			if u64::from(offset) + u64::from(length) > file_length || length < 4 {
+				continue;
+			}
+
+			// Only keep data which actually starts with a JPEG start of image marker
+			file.seek_from_start(offset)?;
+			if file.read_n::<2>()? != [0xff, 0xd8] {
+				continue;
+			}
+
+			if largest_preview.is_none_or(|(_, largest_length)| length > largest_length) {
+				largest_preview = Some((offset, length));
+			}
+		}
+
+		let Some((offset, length)) = largest_preview else { return Ok(None) };
+
+		let mut data = vec![0; length.try_into()?];
+		file.seek_from_start(offset)?;
+		file.read_exact(&mut data)?;
+
+		Ok(Some(data))
+	}
+}
+
+/// Finds where an IFD stores a JPEG image, either in the JPEG interchange format tags used by most cameras or as a single strip in a reduced resolution DNG preview.
+fn jpeg_location<R: Read + Seek>(ifd: &Ifd, file: &mut TiffRead<R>) -> Option<(u32, u32)> {
+	if let (Ok(offset), Ok(length)) = (ifd.get_value::<JpegOffset, _>(file), ifd.get_value::<JpegLength, _>(file)) {
+		return Some((offset, length));
+	}
+
+	// Both old style (6) and new style (7) JPEG compression are accepted, but full resolution images are skipped since those would be lossless JPEG raw data
+	let is_reduced_resolution = ifd.get_value::<NewSubfileType, _>(file).ok()? & 1 == 1;
+	let compression = ifd.get_value::<Compression, _>(file).ok()?;
+	if !is_reduced_resolution || !matches!(compression, 6 | 7) {
+		return None;
+	}
+
+	let strip_offsets = ifd.get_value::<StripOffsets, _>(file).ok()?;
+	let strip_byte_counts = ifd.get_value::<StripByteCounts, _>(file).ok()?;
+	match (strip_offsets.as_slice(), strip_byte_counts.as_slice()) {
+		([offset], [length]) => Some((*offset, *length)),
+		_ => None,
+	}
+}
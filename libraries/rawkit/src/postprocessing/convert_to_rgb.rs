@@ -2,7 +2,8 @@ use crate::{CHANNELS_IN_RGB, Pixel, RawImage};
 
 impl RawImage {
 	pub fn convert_to_rgb_fn(&self) -> impl Fn(Pixel) -> [u16; CHANNELS_IN_RGB] + use<> {
-		let Some(camera_to_rgb) = self.camera_to_rgb else { todo!() };
+		// Without color data for the camera, its colors are passed through unchanged
+		let camera_to_rgb = self.camera_to_rgb.unwrap_or([[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]);
 
 		move |pixel: Pixel| {
 			std::array::from_fn(|i| i)
@@ -2,7 +2,7 @@ use crate::{RawImage, RawPixel};
 
 impl RawImage {
 	pub fn scale_white_balance_fn(&self) -> impl Fn(RawPixel) -> u16 + use<> {
-		let Some(mut white_balance) = self.white_balance else { todo!() };
+		let mut white_balance = self.white_balance.or(self.camera_white_balance).unwrap_or([1.; 3]);
 
 		if white_balance[1] == 0. {
 			white_balance[1] = 1.;
@@ -20,12 +20,13 @@ impl RawImage {
 		let normalized_white_balance = if normalization_factor > 0.00001 {
 			white_balance.map(|x| x / normalization_factor)
 		} else {
-			[1., 1., 1., 1.]
+			[1., 1., 1.]
 		};
 
+		let cfa_pattern = self.cfa_pattern;
 		move |pixel: RawPixel| {
-			let cfa_index = 2 * (pixel.row % 2) + (pixel.column % 2);
-			((pixel.value as f64) * normalized_white_balance[cfa_index]).min(u16::MAX as f64).max(0.) as u16
+			let color = cfa_pattern.color_at(pixel.row, pixel.column);
+			((pixel.value as f64) * normalized_white_balance[color]).min(u16::MAX as f64).max(0.) as u16
 		}
 	}
 }
@@ -2,6 +2,7 @@ pub mod file;
 pub mod tags;
 mod types;
 pub mod values;
+pub mod writer;
 
 use file::TiffRead;
 use num_enum::{FromPrimitive, IntoPrimitive};
@@ -13,11 +14,18 @@ use thiserror::Error;
 #[derive(Copy, Clone, Debug, PartialEq, Eq, FromPrimitive, IntoPrimitive)]
 #[repr(u16)]
 pub enum TagId {
+	GpsLatitudeRef = 0x1,
+	GpsLatitude = 0x2,
+	GpsLongitudeRef = 0x3,
+	GpsLongitude = 0x4,
+	GpsAltitudeRef = 0x5,
+	GpsAltitude = 0x6,
+	NewSubfileType = 0xfe,
 	ImageWidth = 0x100,
 	ImageLength = 0x101,
 	BitsPerSample = 0x102,
 	Compression = 0x103,
-	PhotometricInterpretation = 0x104,
+	PhotometricInterpretation = 0x106,
 	Make = 0x10f,
 	Model = 0x110,
 	StripOffsets = 0x111,
@@ -25,7 +33,20 @@ pub enum TagId {
 	SamplesPerPixel = 0x115,
 	RowsPerStrip = 0x116,
 	StripByteCounts = 0x117,
+	XResolution = 0x11a,
+	YResolution = 0x11b,
+	PlanarConfiguration = 0x11c,
+	ResolutionUnit = 0x128,
+	DateTime = 0x132,
+	Artist = 0x13b,
+	Predictor = 0x13d,
+	TileWidth = 0x142,
+	TileLength = 0x143,
+	TileOffsets = 0x144,
+	TileByteCounts = 0x145,
 	SubIfd = 0x14a,
+	ExtraSamples = 0x152,
+	SampleFormat = 0x153,
 	JpegOffset = 0x201,
 	JpegLength = 0x202,
 	SonyToneCurve = 0x7010,
@@ -33,8 +54,32 @@ pub enum TagId {
 	WhiteBalanceRggbLevels = 0x7313,
 	CfaPatternDim = 0x828d,
 	CfaPattern = 0x828e,
+	Copyright = 0x8298,
+	ExposureTime = 0x829a,
+	FNumber = 0x829d,
+	ExifIfd = 0x8769,
+	IccProfile = 0x8773,
+	GpsIfd = 0x8825,
+	IsoSpeedRatings = 0x8827,
+	RecommendedExposureIndex = 0x8832,
+	DateTimeOriginal = 0x9003,
+	OffsetTimeOriginal = 0x9011,
+	ExposureBias = 0x9204,
+	FocalLength = 0x920a,
+	MakerNote = 0x927c,
+	SubSecTimeOriginal = 0x9291,
+	FocalLengthIn35mmFilm = 0xa405,
+	LensMake = 0xa433,
+	LensModel = 0xa434,
+	SonyLensSpec = 0xb02a,
+	DngVersion = 0xc612,
+	LinearizationTable = 0xc618,
+	BlackLevelRepeatDim = 0xc619,
+	DngBlackLevel = 0xc61a,
+	WhiteLevel = 0xc61d,
 	ColorMatrix1 = 0xc621,
 	ColorMatrix2 = 0xc622,
+	AsShotNeutral = 0xc628,
 
 	#[num_enum(catch_all)]
 	Unknown(u16),
@@ -55,6 +100,7 @@ pub enum IfdTagType {
 	SRational = 10,
 	Float = 11,
 	Double = 12,
+	Ifd = 13,
 
 	#[num_enum(catch_all)]
 	Unknown(u16),
@@ -110,8 +156,8 @@ impl Ifd {
 		})
 	}
 
-	fn _next_ifd<R: Read + Seek>(&self, file: &mut TiffRead<R>) -> Result<Self, TiffError> {
-		Ifd::new_from_offset(file, self.next_ifd_offset.unwrap_or(0))
+	pub fn next_ifd<R: Read + Seek>(&self, file: &mut TiffRead<R>) -> Result<Option<Self>, TiffError> {
+		self.next_ifd_offset.map(|offset| Ifd::new_from_offset(file, offset)).transpose()
 	}
 
 	pub fn ifd_entries(&self) -> &[IfdEntry] {
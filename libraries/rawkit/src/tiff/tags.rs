@@ -1,4 +1,7 @@
-use super::types::{Array, ConstArray, TagType, TypeByte, TypeIfd, TypeLong, TypeNumber, TypeOrientation, TypeSRational, TypeSShort, TypeShort, TypeSonyToneCurve, TypeString};
+use super::types::{
+	Array, ConstArray, TagType, TypeAnyNumber, TypeByte, TypeDataOffset, TypeIfd, TypeIfdList, TypeLong, TypeNumber, TypeOrientation, TypeSRational, TypeSShort, TypeShort, TypeSonyToneCurve,
+	TypeString, TypeUndefined,
+};
 use super::{Ifd, TagId, TiffError, TiffRead};
 use std::io::{Read, Seek};
 
@@ -9,6 +12,7 @@ pub trait SimpleTag {
 	const NAME: &'static str;
 }
 
+pub struct NewSubfileType;
 pub struct ImageWidth;
 pub struct ImageLength;
 pub struct BitsPerSample;
@@ -21,7 +25,12 @@ pub struct Orientation;
 pub struct SamplesPerPixel;
 pub struct RowsPerStrip;
 pub struct StripByteCounts;
+pub struct TileWidth;
+pub struct TileLength;
+pub struct TileOffsets;
+pub struct TileByteCounts;
 pub struct SubIfd;
+pub struct SubIfds;
 pub struct JpegOffset;
 pub struct JpegLength;
 pub struct SonyDataOffset;
@@ -30,8 +39,46 @@ pub struct BlackLevel;
 pub struct WhiteBalanceRggbLevels;
 pub struct CfaPatternDim;
 pub struct CfaPattern;
+pub struct DngVersion;
+pub struct LinearizationTable;
+pub struct BlackLevelRepeatDim;
+pub struct DngBlackLevel;
+pub struct WhiteLevel;
 pub struct ColorMatrix1;
 pub struct ColorMatrix2;
+pub struct AsShotNeutral;
+pub struct DateTime;
+pub struct Artist;
+pub struct Copyright;
+pub struct ExifIfd;
+pub struct GpsIfd;
+pub struct ExposureTime;
+pub struct FNumber;
+pub struct IsoSpeedRatings;
+pub struct RecommendedExposureIndex;
+pub struct DateTimeOriginal;
+pub struct OffsetTimeOriginal;
+pub struct SubSecTimeOriginal;
+pub struct ExposureBias;
+pub struct FocalLength;
+pub struct FocalLengthIn35mmFilm;
+pub struct MakerNote;
+pub struct LensMake;
+pub struct LensModel;
+pub struct GpsLatitudeRef;
+pub struct GpsLatitude;
+pub struct GpsLongitudeRef;
+pub struct GpsLongitude;
+pub struct GpsAltitudeRef;
+pub struct GpsAltitude;
+pub struct SonyLensSpec;
+
+impl SimpleTag for NewSubfileType {
+	type Type = TypeNumber;
+
+	const ID: TagId = TagId::NewSubfileType;
+	const NAME: &'static str = "New Subfile Type";
+}
 
 impl SimpleTag for ImageWidth {
 	type Type = TypeNumber;
@@ -117,6 +164,34 @@ impl SimpleTag for StripByteCounts {
 	const NAME: &'static str = "Strip Byte Counts";
 }
 
+impl SimpleTag for TileWidth {
+	type Type = TypeNumber;
+
+	const ID: TagId = TagId::TileWidth;
+	const NAME: &'static str = "Tile Width";
+}
+
+impl SimpleTag for TileLength {
+	type Type = TypeNumber;
+
+	const ID: TagId = TagId::TileLength;
+	const NAME: &'static str = "Tile Length";
+}
+
+impl SimpleTag for TileOffsets {
+	type Type = Array<TypeNumber>;
+
+	const ID: TagId = TagId::TileOffsets;
+	const NAME: &'static str = "Tile Offsets";
+}
+
+impl SimpleTag for TileByteCounts {
+	type Type = Array<TypeNumber>;
+
+	const ID: TagId = TagId::TileByteCounts;
+	const NAME: &'static str = "Tile Byte Counts";
+}
+
 impl SimpleTag for SubIfd {
 	type Type = TypeIfd;
 
@@ -124,6 +199,13 @@ impl SimpleTag for SubIfd {
 	const NAME: &'static str = "SubIFD";
 }
 
+impl SimpleTag for SubIfds {
+	type Type = TypeIfdList;
+
+	const ID: TagId = TagId::SubIfd;
+	const NAME: &'static str = "SubIFDs";
+}
+
 impl SimpleTag for JpegOffset {
 	type Type = TypeLong;
 
@@ -152,6 +234,41 @@ impl SimpleTag for CfaPattern {
 	const NAME: &'static str = "CFA Pattern";
 }
 
+impl SimpleTag for DngVersion {
+	type Type = ConstArray<TypeByte, 4>;
+
+	const ID: TagId = TagId::DngVersion;
+	const NAME: &'static str = "DNG Version";
+}
+
+impl SimpleTag for LinearizationTable {
+	type Type = Array<TypeShort>;
+
+	const ID: TagId = TagId::LinearizationTable;
+	const NAME: &'static str = "Linearization Table";
+}
+
+impl SimpleTag for BlackLevelRepeatDim {
+	type Type = ConstArray<TypeShort, 2>;
+
+	const ID: TagId = TagId::BlackLevelRepeatDim;
+	const NAME: &'static str = "Black Level Repeat Dimension";
+}
+
+impl SimpleTag for DngBlackLevel {
+	type Type = Array<TypeAnyNumber>;
+
+	const ID: TagId = TagId::DngBlackLevel;
+	const NAME: &'static str = "DNG Black Level";
+}
+
+impl SimpleTag for WhiteLevel {
+	type Type = Array<TypeNumber>;
+
+	const ID: TagId = TagId::WhiteLevel;
+	const NAME: &'static str = "White Level";
+}
+
 impl SimpleTag for ColorMatrix1 {
 	type Type = Array<TypeSRational>;
 
@@ -166,6 +283,13 @@ impl SimpleTag for ColorMatrix2 {
 	const NAME: &'static str = "Color Matrix 2";
 }
 
+impl SimpleTag for AsShotNeutral {
+	type Type = Array<TypeAnyNumber>;
+
+	const ID: TagId = TagId::AsShotNeutral;
+	const NAME: &'static str = "As Shot Neutral";
+}
+
 impl SimpleTag for SonyDataOffset {
 	type Type = TypeLong;
 
@@ -194,6 +318,181 @@ impl SimpleTag for WhiteBalanceRggbLevels {
 	const NAME: &'static str = "White Balance Levels (RGGB)";
 }
 
+impl SimpleTag for DateTime {
+	type Type = TypeString;
+
+	const ID: TagId = TagId::DateTime;
+	const NAME: &'static str = "Date Time";
+}
+
+impl SimpleTag for Artist {
+	type Type = TypeString;
+
+	const ID: TagId = TagId::Artist;
+	const NAME: &'static str = "Artist";
+}
+
+impl SimpleTag for Copyright {
+	type Type = TypeString;
+
+	const ID: TagId = TagId::Copyright;
+	const NAME: &'static str = "Copyright";
+}
+
+impl SimpleTag for ExifIfd {
+	type Type = TypeIfd;
+
+	const ID: TagId = TagId::ExifIfd;
+	const NAME: &'static str = "Exif IFD";
+}
+
+impl SimpleTag for GpsIfd {
+	type Type = TypeIfd;
+
+	const ID: TagId = TagId::GpsIfd;
+	const NAME: &'static str = "GPS IFD";
+}
+
+impl SimpleTag for ExposureTime {
+	type Type = TypeAnyNumber;
+
+	const ID: TagId = TagId::ExposureTime;
+	const NAME: &'static str = "Exposure Time";
+}
+
+impl SimpleTag for FNumber {
+	type Type = TypeAnyNumber;
+
+	const ID: TagId = TagId::FNumber;
+	const NAME: &'static str = "F Number";
+}
+
+impl SimpleTag for IsoSpeedRatings {
+	type Type = Array<TypeNumber>;
+
+	const ID: TagId = TagId::IsoSpeedRatings;
+	const NAME: &'static str = "ISO Speed Ratings";
+}
+
+impl SimpleTag for RecommendedExposureIndex {
+	type Type = TypeNumber;
+
+	const ID: TagId = TagId::RecommendedExposureIndex;
+	const NAME: &'static str = "Recommended Exposure Index";
+}
+
+impl SimpleTag for DateTimeOriginal {
+	type Type = TypeString;
+
+	const ID: TagId = TagId::DateTimeOriginal;
+	const NAME: &'static str = "Date Time Original";
+}
+
+impl SimpleTag for OffsetTimeOriginal {
+	type Type = TypeString;
+
+	const ID: TagId = TagId::OffsetTimeOriginal;
+	const NAME: &'static str = "Offset Time Original";
+}
+
+impl SimpleTag for SubSecTimeOriginal {
+	type Type = TypeString;
+
+	const ID: TagId = TagId::SubSecTimeOriginal;
+	const NAME: &'static str = "Sub-second Time Original";
+}
+
+impl SimpleTag for ExposureBias {
+	type Type = TypeAnyNumber;
+
+	const ID: TagId = TagId::ExposureBias;
+	const NAME: &'static str = "Exposure Bias";
+}
+
+impl SimpleTag for FocalLength {
+	type Type = TypeAnyNumber;
+
+	const ID: TagId = TagId::FocalLength;
+	const NAME: &'static str = "Focal Length";
+}
+
+impl SimpleTag for FocalLengthIn35mmFilm {
+	type Type = TypeNumber;
+
+	const ID: TagId = TagId::FocalLengthIn35mmFilm;
+	const NAME: &'static str = "Focal Length in 35mm Film";
+}
+
+impl SimpleTag for MakerNote {
+	type Type = TypeDataOffset;
+
+	const ID: TagId = TagId::MakerNote;
+	const NAME: &'static str = "Maker Note";
+}
+
+impl SimpleTag for LensMake {
+	type Type = TypeString;
+
+	const ID: TagId = TagId::LensMake;
+	const NAME: &'static str = "Lens Make";
+}
+
+impl SimpleTag for LensModel {
+	type Type = TypeString;
+
+	const ID: TagId = TagId::LensModel;
+	const NAME: &'static str = "Lens Model";
+}
+
+impl SimpleTag for GpsLatitudeRef {
+	type Type = TypeString;
+
+	const ID: TagId = TagId::GpsLatitudeRef;
+	const NAME: &'static str = "GPS Latitude Reference";
+}
+
+impl SimpleTag for GpsLatitude {
+	type Type = ConstArray<TypeAnyNumber, 3>;
+
+	const ID: TagId = TagId::GpsLatitude;
+	const NAME: &'static str = "GPS Latitude";
+}
+
+impl SimpleTag for GpsLongitudeRef {
+	type Type = TypeString;
+
+	const ID: TagId = TagId::GpsLongitudeRef;
+	const NAME: &'static str = "GPS Longitude Reference";
+}
+
+impl SimpleTag for GpsLongitude {
+	type Type = ConstArray<TypeAnyNumber, 3>;
+
+	const ID: TagId = TagId::GpsLongitude;
+	const NAME: &'static str = "GPS Longitude";
+}
+
+impl SimpleTag for GpsAltitudeRef {
+	type Type = TypeNumber;
+
+	const ID: TagId = TagId::GpsAltitudeRef;
+	const NAME: &'static str = "GPS Altitude Reference";
+}
+
+impl SimpleTag for GpsAltitude {
+	type Type = TypeAnyNumber;
+
+	const ID: TagId = TagId::GpsAltitude;
+	const NAME: &'static str = "GPS Altitude";
+}
+
+impl SimpleTag for SonyLensSpec {
+	type Type = ConstArray<TypeUndefined, 8>;
+
+	const ID: TagId = TagId::SonyLensSpec;
+	const NAME: &'static str = "Sony Lens Specification";
+}
+
 pub trait Tag {
 	type Output;
 
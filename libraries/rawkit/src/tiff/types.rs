@@ -1,5 +1,5 @@
 use super::file::TiffRead;
-use super::values::{CurveLookupTable, Rational, Transform};
+use super::values::{CurveLookupTable, Rational, ToFloat, Transform};
 use super::{Ifd, IfdTagType, TiffError};
 use std::io::{Read, Seek};
 
@@ -18,6 +18,8 @@ pub struct TypeUndefined;
 
 pub struct TypeNumber;
 pub struct TypeSNumber;
+pub struct TypeAnyNumber;
+pub struct TypeIfdOffset;
 pub struct TypeIfd;
 
 pub trait PrimitiveType {
@@ -201,14 +203,17 @@ impl PrimitiveType for TypeDouble {
 }
 
 impl PrimitiveType for TypeUndefined {
-	type Output = ();
+	type Output = u8;
 
-	fn get_size(_: IfdTagType) -> Option<u32> {
-		todo!()
+	fn get_size(the_type: IfdTagType) -> Option<u32> {
+		match the_type {
+			IfdTagType::Byte | IfdTagType::Undefined => Some(1),
+			_ => None,
+		}
 	}
 
-	fn read_primitive<R: Read + Seek>(_: IfdTagType, _: &mut TiffRead<R>) -> Result<Self::Output, TiffError> {
-		todo!()
+	fn read_primitive<R: Read + Seek>(_: IfdTagType, file: &mut TiffRead<R>) -> Result<Self::Output, TiffError> {
+		Ok(file.read_u8()?)
 	}
 }
 
@@ -256,15 +261,58 @@ impl PrimitiveType for TypeSNumber {
 	}
 }
 
+impl PrimitiveType for TypeAnyNumber {
+	type Output = f64;
+
+	fn get_size(the_type: IfdTagType) -> Option<u32> {
+		match the_type {
+			IfdTagType::Byte | IfdTagType::Short | IfdTagType::Long => TypeNumber::get_size(the_type),
+			IfdTagType::SByte | IfdTagType::SShort | IfdTagType::SLong => TypeSNumber::get_size(the_type),
+			IfdTagType::Rational => TypeRational::get_size(the_type),
+			IfdTagType::SRational => TypeSRational::get_size(the_type),
+			IfdTagType::Float => TypeFloat::get_size(the_type),
+			IfdTagType::Double => TypeDouble::get_size(the_type),
+			_ => None,
+		}
+	}
+
+	fn read_primitive<R: Read + Seek>(the_type: IfdTagType, file: &mut TiffRead<R>) -> Result<Self::Output, TiffError> {
+		Ok(match the_type {
+			IfdTagType::Byte | IfdTagType::Short | IfdTagType::Long => TypeNumber::read_primitive(the_type, file)?.into(),
+			IfdTagType::SByte | IfdTagType::SShort | IfdTagType::SLong => TypeSNumber::read_primitive(the_type, file)?.into(),
+			IfdTagType::Rational => TypeRational::read_primitive(the_type, file)?.to_float(),
+			IfdTagType::SRational => TypeSRational::read_primitive(the_type, file)?.to_float(),
+			IfdTagType::Float => TypeFloat::read_primitive(the_type, file)?.into(),
+			IfdTagType::Double => TypeDouble::read_primitive(the_type, file)?,
+			_ => unreachable!(),
+		})
+	}
+}
+
+impl PrimitiveType for TypeIfdOffset {
+	type Output = u32;
+
+	fn get_size(the_type: IfdTagType) -> Option<u32> {
+		match the_type {
+			IfdTagType::Long | IfdTagType::Ifd => Some(4),
+			_ => None,
+		}
+	}
+
+	fn read_primitive<R: Read + Seek>(_: IfdTagType, file: &mut TiffRead<R>) -> Result<Self::Output, TiffError> {
+		Ok(file.read_u32()?)
+	}
+}
+
 impl PrimitiveType for TypeIfd {
 	type Output = Ifd;
 
 	fn get_size(the_type: IfdTagType) -> Option<u32> {
-		TypeLong::get_size(the_type)
+		TypeIfdOffset::get_size(the_type)
 	}
 
 	fn read_primitive<R: Read + Seek>(the_type: IfdTagType, file: &mut TiffRead<R>) -> Result<Self::Output, TiffError> {
-		let offset = TypeLong::read_primitive(the_type, file)?;
+		let offset = TypeIfdOffset::read_primitive(the_type, file)?;
 		Ifd::new_from_offset(file, offset)
 	}
 }
@@ -312,12 +360,13 @@ impl<T: PrimitiveType> TagType for Array<T> {
 		let count = file.read_u32()?;
 
 		let size = T::get_size(the_type).ok_or(TiffError::InvalidType)?;
-		if count * size > 4 {
+		if count.checked_mul(size).ok_or(TiffError::InvalidCount)? > 4 {
 			let offset = file.read_u32()?;
 			file.seek_from_start(offset)?;
 		}
 
-		let mut ans = Vec::with_capacity(count.try_into()?);
+		// The count isn't used to reserve memory upfront since a corrupt file could make it arbitrarily large
+		let mut ans = Vec::new();
 		for _ in 0..count {
 			ans.push(T::read_primitive(the_type, file)?);
 		}
@@ -353,6 +402,8 @@ impl<T: PrimitiveType, const N: usize> TagType for ConstArray<T, N> {
 pub struct TypeString;
 pub struct TypeSonyToneCurve;
 pub struct TypeOrientation;
+pub struct TypeIfdList;
+pub struct TypeDataOffset;
 
 impl TagType for TypeString {
 	type Output = String;
@@ -362,7 +413,7 @@ impl TagType for TypeString {
 
 		// Skip the NUL character at the end
 		let len = string.len();
-		Ok(string.into_iter().take(len - 1).collect())
+		Ok(string.into_iter().take(len.saturating_sub(1)).collect())
 	}
 }
 
@@ -392,3 +443,30 @@ impl TagType for TypeOrientation {
 		})
 	}
 }
+
+impl TagType for TypeIfdList {
+	type Output = Vec<Ifd>;
+
+	fn read<R: Read + Seek>(file: &mut TiffRead<R>) -> Result<Self::Output, TiffError> {
+		// All the offsets need to be read before any of the IFDs, since reading an IFD moves the file cursor
+		let offsets = Array::<TypeIfdOffset>::read(file)?;
+		offsets.into_iter().map(|offset| Ifd::new_from_offset(file, offset)).collect()
+	}
+}
+
+impl TagType for TypeDataOffset {
+	type Output = u32;
+
+	fn read<R: Read + Seek>(file: &mut TiffRead<R>) -> Result<Self::Output, TiffError> {
+		// Only the location of the data is returned, which is needed for blobs like maker notes whose contents point to other parts of the file
+		let the_type = IfdTagType::from(file.read_u16()?);
+		let count = file.read_u32()?;
+
+		let size = TypeUndefined::get_size(the_type).or_else(|| TypeAnyNumber::get_size(the_type)).ok_or(TiffError::InvalidType)?;
+		if count.checked_mul(size).ok_or(TiffError::InvalidCount)? > 4 {
+			Ok(file.read_u32()?)
+		} else {
+			Ok(file.stream_position()?.try_into()?)
+		}
+	}
+}
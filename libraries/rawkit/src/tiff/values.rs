@@ -51,7 +51,7 @@ impl CurveLookupTable {
 	}
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Transform {
 	Horizontal,
 	MirrorHorizontal,
@@ -0,0 +1,370 @@
+use super::values::Transform;
+use super::{IfdTagType, TagId, TiffError};
+use crate::Image;
+use std::io::Write;
+
+/// When the number of rows per strip isn't given, strips are sized to hold about this many bytes before compression, as recommended by the TIFF specification.
+const TARGET_STRIP_SIZE: usize = 64 * 1024;
+
+/// The compression applied to each strip or tile.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TiffCompression {
+	#[default]
+	None,
+	/// Lempel-Ziv-Welch compression, which every TIFF reader supports.
+	Lzw,
+	/// Zlib compression, which usually gives smaller files than LZW but isn't supported by some older readers.
+	Deflate,
+}
+
+/// How the image data is split up in the file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TiffLayout {
+	/// Groups of whole rows. When `rows_per_strip` is `None`, each strip holds about 64 KiB of samples.
+	Strips { rows_per_strip: Option<u32> },
+	/// Rectangular tiles, whose width and height must be multiples of 16.
+	Tiles { width: u32, height: u32 },
+}
+
+impl Default for TiffLayout {
+	fn default() -> Self {
+		Self::Strips { rows_per_strip: None }
+	}
+}
+
+/// How the color channels relate to the alpha channel, for images with 2 or 4 channels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TiffAlpha {
+	#[default]
+	Unassociated,
+	/// The color channels are premultiplied by the alpha channel.
+	Associated,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiffWriteOptions {
+	pub compression: TiffCompression,
+	pub layout: TiffLayout,
+	/// The ICC profile which describes the color space of the samples, for readers to color manage the image.
+	pub icc_profile: Option<Vec<u8>>,
+	/// The resolution the image should be printed at.
+	pub pixels_per_inch: f64,
+}
+
+impl Default for TiffWriteOptions {
+	fn default() -> Self {
+		Self {
+			compression: TiffCompression::default(),
+			layout: TiffLayout::default(),
+			icc_profile: None,
+			pixels_per_inch: 72.,
+		}
+	}
+}
+
+/// The sample types which can be written, which are 8-bit and 16-bit unsigned integers and 32-bit floats.
+pub trait TiffSample: Copy + Default {
+	const BITS_PER_SAMPLE: u16;
+	const SAMPLE_FORMAT: u16;
+	const PREDICTOR: u16;
+
+	/// Appends the little endian bytes of a row of interleaved samples, transformed by the predictor when it's enabled.
+	fn encode_row(row: &[Self], channels: usize, predictor: bool, output: &mut Vec<u8>);
+}
+
+macro_rules! impl_integer_sample {
+	($type:ty) => {
+		impl TiffSample for $type {
+			const BITS_PER_SAMPLE: u16 = <$type>::BITS as u16;
+			const SAMPLE_FORMAT: u16 = 1;
+			const PREDICTOR: u16 = 2;
+
+			fn encode_row(row: &[Self], channels: usize, predictor: bool, output: &mut Vec<u8>) {
+				// The horizontal differencing predictor stores each sample as the difference to the same channel of the previous pixel
+				for (index, &sample) in row.iter().enumerate() {
+					let sample = if predictor && index >= channels {
+						sample.wrapping_sub(row[index - channels])
+					} else {
+						sample
+					};
+					output.extend(sample.to_le_bytes());
+				}
+			}
+		}
+	};
+}
+
+impl_integer_sample!(u8);
+impl_integer_sample!(u16);
+
+impl TiffSample for f32 {
+	const BITS_PER_SAMPLE: u16 = 32;
+	const SAMPLE_FORMAT: u16 = 3;
+	const PREDICTOR: u16 = 3;
+
+	fn encode_row(row: &[Self], channels: usize, predictor: bool, output: &mut Vec<u8>) {
+		if !predictor {
+			output.extend(row.iter().flat_map(|sample| sample.to_le_bytes()));
+			return;
+		}
+
+		// The floating point predictor splits the row into planes of bytes, most significant first, and then differences neighboring bytes
+		let start = output.len();
+		for plane in 0..4 {
+			output.extend(row.iter().map(|sample| sample.to_be_bytes()[plane]));
+		}
+
+		let bytes = &mut output[start..];
+		for index in (channels..bytes.len()).rev() {
+			bytes[index] = bytes[index].wrapping_sub(bytes[index - channels]);
+		}
+	}
+}
+
+/// An image to be written, with its channels interleaved.
+pub struct TiffImage<'a, T: TiffSample> {
+	pub data: &'a [T],
+	pub width: usize,
+	pub height: usize,
+	/// The number of channels, which are interpreted as gray, gray and alpha, RGB, or RGB and alpha.
+	pub channels: usize,
+	pub alpha: TiffAlpha,
+	/// The orientation which viewers should apply when displaying the image.
+	pub transform: Transform,
+}
+
+struct Entry {
+	tag: TagId,
+	the_type: IfdTagType,
+	count: u32,
+	data: Vec<u8>,
+}
+
+impl Entry {
+	fn shorts(tag: TagId, values: &[u16]) -> Result<Self, TiffError> {
+		Ok(Self {
+			tag,
+			the_type: IfdTagType::Short,
+			count: values.len().try_into()?,
+			data: values.iter().flat_map(|value| value.to_le_bytes()).collect(),
+		})
+	}
+
+	fn longs(tag: TagId, values: &[u32]) -> Result<Self, TiffError> {
+		Ok(Self {
+			tag,
+			the_type: IfdTagType::Long,
+			count: values.len().try_into()?,
+			data: values.iter().flat_map(|value| value.to_le_bytes()).collect(),
+		})
+	}
+
+	fn rational(tag: TagId, value: f64) -> Self {
+		let denominator = 1000;
+		let numerator = (value * denominator as f64).round().clamp(1., u32::MAX as f64) as u32;
+
+		Self {
+			tag,
+			the_type: IfdTagType::Rational,
+			count: 1,
+			data: [numerator.to_le_bytes(), u32::to_le_bytes(denominator)].concat(),
+		}
+	}
+
+	fn undefined(tag: TagId, data: &[u8]) -> Result<Self, TiffError> {
+		Ok(Self {
+			tag,
+			the_type: IfdTagType::Undefined,
+			count: data.len().try_into()?,
+			data: data.to_vec(),
+		})
+	}
+}
+
+/// Writes the image as a little endian TIFF file with a single IFD.
+pub fn write_tiff<T: TiffSample, W: Write>(writer: &mut W, image: &TiffImage<T>, options: &TiffWriteOptions) -> Result<(), TiffError> {
+	let &TiffImage { data, width, height, channels, .. } = image;
+
+	if !(1..=4).contains(&channels) || width == 0 || height == 0 {
+		return Err(TiffError::InvalidValue);
+	}
+	if width.checked_mul(height).and_then(|pixels| pixels.checked_mul(channels)) != Some(data.len()) {
+		return Err(TiffError::InvalidCount);
+	}
+
+	let (chunk_width, chunk_height, tiled) = match options.layout {
+		TiffLayout::Strips { rows_per_strip } => {
+			let row_size = width * channels * size_of::<T>();
+			let rows_per_strip = rows_per_strip.map(usize::try_from).transpose()?.unwrap_or(TARGET_STRIP_SIZE / row_size);
+			(width, rows_per_strip.clamp(1, height), false)
+		}
+		TiffLayout::Tiles { width, height } => {
+			if width == 0 || height == 0 || !width.is_multiple_of(16) || !height.is_multiple_of(16) {
+				return Err(TiffError::InvalidValue);
+			}
+			(width.try_into()?, height.try_into()?, true)
+		}
+	};
+
+	let predictor = options.compression != TiffCompression::None;
+	let mut chunks = Vec::new();
+	let mut row = vec![T::default(); chunk_width * channels];
+
+	for top in (0..height).step_by(chunk_height) {
+		for left in (0..width).step_by(chunk_width) {
+			// Strips stop at the bottom of the image, while tiles always have their full size and are padded at the edges
+			let rows = if tiled { chunk_height } else { chunk_height.min(height - top) };
+			let columns = chunk_width.min(width - left);
+
+			let mut bytes = Vec::with_capacity(rows * chunk_width * channels * size_of::<T>());
+			for y in top..top + rows {
+				row.fill(T::default());
+				if y < height {
+					let start = (y * width + left) * channels;
+					row[..columns * channels].copy_from_slice(&data[start..start + columns * channels]);
+				}
+				T::encode_row(&row, channels, predictor, &mut bytes);
+			}
+
+			chunks.push(compress(bytes, options.compression)?);
+		}
+	}
+
+	// The image data comes right after the header, followed by the IFD and the values which don't fit in its entries
+	let mut chunk_offsets = Vec::with_capacity(chunks.len());
+	let mut offset = 8_u32;
+	for chunk in &chunks {
+		chunk_offsets.push(offset);
+		offset = offset.checked_add(chunk.len().try_into()?).ok_or(TiffError::InvalidOffset)?;
+	}
+	let chunk_byte_counts = chunks.iter().map(|chunk| chunk.len().try_into()).collect::<Result<Vec<u32>, _>>()?;
+
+	let has_alpha = channels == 2 || channels == 4;
+	let photometric_interpretation = if channels < 3 { 1 } else { 2 };
+	let compression = match options.compression {
+		TiffCompression::None => 1,
+		TiffCompression::Lzw => 5,
+		TiffCompression::Deflate => 8,
+	};
+
+	let mut entries = vec![
+		Entry::longs(TagId::ImageWidth, &[width.try_into()?])?,
+		Entry::longs(TagId::ImageLength, &[height.try_into()?])?,
+		Entry::shorts(TagId::BitsPerSample, &vec![T::BITS_PER_SAMPLE; channels])?,
+		Entry::shorts(TagId::Compression, &[compression])?,
+		Entry::shorts(TagId::PhotometricInterpretation, &[photometric_interpretation])?,
+		Entry::shorts(TagId::Orientation, &[orientation(image.transform)])?,
+		Entry::shorts(TagId::SamplesPerPixel, &[channels.try_into()?])?,
+		Entry::rational(TagId::XResolution, options.pixels_per_inch),
+		Entry::rational(TagId::YResolution, options.pixels_per_inch),
+		Entry::shorts(TagId::PlanarConfiguration, &[1])?,
+		Entry::shorts(TagId::ResolutionUnit, &[2])?,
+		Entry::shorts(TagId::SampleFormat, &vec![T::SAMPLE_FORMAT; channels])?,
+	];
+
+	if tiled {
+		entries.push(Entry::longs(TagId::TileWidth, &[chunk_width.try_into()?])?);
+		entries.push(Entry::longs(TagId::TileLength, &[chunk_height.try_into()?])?);
+		entries.push(Entry::longs(TagId::TileOffsets, &chunk_offsets)?);
+		entries.push(Entry::longs(TagId::TileByteCounts, &chunk_byte_counts)?);
+	} else {
+		entries.push(Entry::longs(TagId::StripOffsets, &chunk_offsets)?);
+		entries.push(Entry::longs(TagId::RowsPerStrip, &[chunk_height.try_into()?])?);
+		entries.push(Entry::longs(TagId::StripByteCounts, &chunk_byte_counts)?);
+	}
+	if predictor {
+		entries.push(Entry::shorts(TagId::Predictor, &[T::PREDICTOR])?);
+	}
+	if has_alpha {
+		let extra_sample = match image.alpha {
+			TiffAlpha::Associated => 1,
+			TiffAlpha::Unassociated => 2,
+		};
+		entries.push(Entry::shorts(TagId::ExtraSamples, &[extra_sample])?);
+	}
+	if let Some(icc_profile) = &options.icc_profile {
+		entries.push(Entry::undefined(TagId::IccProfile, icc_profile)?);
+	}
+
+	entries.sort_by_key(|entry| u16::from(entry.tag));
+
+	// IFDs and values have to start on a word boundary
+	let ifd_offset = offset.checked_add(offset % 2).ok_or(TiffError::InvalidOffset)?;
+	let entry_count = u16::try_from(entries.len())?;
+	let values_offset = ifd_offset.checked_add(2 + 12 * u32::from(entry_count) + 4).ok_or(TiffError::InvalidOffset)?;
+
+	let mut ifd = Vec::new();
+	let mut values = Vec::new();
+	ifd.extend(entry_count.to_le_bytes());
+	for entry in &entries {
+		ifd.extend(u16::from(entry.tag).to_le_bytes());
+		ifd.extend(u16::from(entry.the_type).to_le_bytes());
+		ifd.extend(entry.count.to_le_bytes());
+
+		if entry.data.len() <= 4 {
+			ifd.extend(entry.data.iter().copied().chain([0; 4]).take(4));
+		} else {
+			let value_offset = values_offset.checked_add(values.len().try_into()?).ok_or(TiffError::InvalidOffset)?;
+			ifd.extend(value_offset.to_le_bytes());
+			values.extend(&entry.data);
+			if !values.len().is_multiple_of(2) {
+				values.push(0);
+			}
+		}
+	}
+	ifd.extend(0_u32.to_le_bytes());
+
+	writer.write_all(&[0x49, 0x49, 42, 0])?;
+	writer.write_all(&ifd_offset.to_le_bytes())?;
+	for chunk in &chunks {
+		writer.write_all(chunk)?;
+	}
+	if !offset.is_multiple_of(2) {
+		writer.write_all(&[0])?;
+	}
+	writer.write_all(&ifd)?;
+	writer.write_all(&values)?;
+
+	Ok(())
+}
+
+fn compress(data: Vec<u8>, compression: TiffCompression) -> Result<Vec<u8>, TiffError> {
+	Ok(match compression {
+		TiffCompression::None => data,
+		TiffCompression::Lzw => weezl::encode::Encoder::with_tiff_size_switch(weezl::BitOrder::Msb, 8)
+			.encode(&data)
+			.map_err(|_| TiffError::InvalidValue)?,
+		TiffCompression::Deflate => miniz_oxide::deflate::compress_to_vec_zlib(&data, 6),
+	})
+}
+
+fn orientation(transform: Transform) -> u16 {
+	match transform {
+		Transform::Horizontal => 1,
+		Transform::MirrorHorizontal => 2,
+		Transform::Rotate180 => 3,
+		Transform::MirrorVertical => 4,
+		Transform::MirrorHorizontalRotate270 => 5,
+		Transform::Rotate90 => 6,
+		Transform::MirrorHorizontalRotate90 => 7,
+		Transform::Rotate270 => 8,
+	}
+}
+
+impl<T: TiffSample> Image<T> {
+	/// Encodes the image as a TIFF file, keeping the full precision of its samples.
+	pub fn encode_tiff(&self, options: &TiffWriteOptions) -> Result<Vec<u8>, TiffError> {
+		let image = TiffImage {
+			data: &self.data,
+			width: self.width,
+			height: self.height,
+			channels: self.channels.into(),
+			alpha: TiffAlpha::default(),
+			transform: self.transform,
+		};
+
+		let mut data = Vec::new();
+		write_tiff(&mut data, &image, options)?;
+		Ok(data)
+	}
+}
@@ -0,0 +1,160 @@
+//! A TIFF file builder which is shared by the tests that decode hand assembled files.
+
+#![allow(dead_code)]
+
+pub const BYTE: u16 = 1;
+pub const ASCII: u16 = 2;
+pub const SHORT: u16 = 3;
+pub const LONG: u16 = 4;
+pub const RATIONAL: u16 = 5;
+pub const UNDEFINED: u16 = 7;
+pub const SRATIONAL: u16 = 10;
+
+pub enum Data {
+	Bytes(Vec<u8>),
+	/// Data which was already written to the file at the given offset.
+	At(u32),
+}
+
+pub struct Entry {
+	pub tag: u16,
+	pub the_type: u16,
+	pub count: u32,
+	pub data: Data,
+}
+
+pub fn ascii(tag: u16, value: &str) -> Entry {
+	let bytes: Vec<u8> = value.bytes().chain([0]).collect();
+	Entry {
+		tag,
+		the_type: ASCII,
+		count: bytes.len() as u32,
+		data: Data::Bytes(bytes),
+	}
+}
+
+pub fn short(tag: u16, value: u16) -> Entry {
+	Entry {
+		tag,
+		the_type: SHORT,
+		count: 1,
+		data: Data::Bytes(value.to_le_bytes().to_vec()),
+	}
+}
+
+pub fn long(tag: u16, value: u32) -> Entry {
+	Entry {
+		tag,
+		the_type: LONG,
+		count: 1,
+		data: Data::Bytes(value.to_le_bytes().to_vec()),
+	}
+}
+
+pub fn bytes(tag: u16, values: &[u8]) -> Entry {
+	Entry {
+		tag,
+		the_type: BYTE,
+		count: values.len() as u32,
+		data: Data::Bytes(values.to_vec()),
+	}
+}
+
+pub fn shorts(tag: u16, values: &[u16]) -> Entry {
+	Entry {
+		tag,
+		the_type: SHORT,
+		count: values.len() as u32,
+		data: Data::Bytes(values.iter().flat_map(|value| value.to_le_bytes()).collect()),
+	}
+}
+
+pub fn longs(tag: u16, values: &[u32]) -> Entry {
+	Entry {
+		tag,
+		the_type: LONG,
+		count: values.len() as u32,
+		data: Data::Bytes(values.iter().flat_map(|value| value.to_le_bytes()).collect()),
+	}
+}
+
+pub fn rationals(tag: u16, values: &[(u32, u32)]) -> Entry {
+	let bytes = values
+		.iter()
+		.flat_map(|(numerator, denominator)| [numerator.to_le_bytes(), denominator.to_le_bytes()].concat())
+		.collect();
+	Entry {
+		tag,
+		the_type: RATIONAL,
+		count: values.len() as u32,
+		data: Data::Bytes(bytes),
+	}
+}
+
+pub fn srational(tag: u16, numerator: i32, denominator: i32) -> Entry {
+	Entry {
+		tag,
+		the_type: SRATIONAL,
+		count: 1,
+		data: Data::Bytes([numerator.to_le_bytes(), denominator.to_le_bytes()].concat()),
+	}
+}
+
+/// A little endian TIFF file assembled one IFD at a time, with IFDs written before the IFDs which point to them.
+pub struct TiffBuilder {
+	data: Vec<u8>,
+}
+
+impl TiffBuilder {
+	pub fn new() -> Self {
+		Self {
+			data: vec![0x49, 0x49, 42, 0, 0, 0, 0, 0],
+		}
+	}
+
+	pub fn offset(&self) -> u32 {
+		self.data.len() as u32
+	}
+
+	pub fn write_bytes(&mut self, bytes: &[u8]) -> u32 {
+		if !self.data.len().is_multiple_of(2) {
+			self.data.push(0);
+		}
+		let offset = self.offset();
+		self.data.extend_from_slice(bytes);
+		offset
+	}
+
+	/// Writes an IFD at the current position, followed by the values which don't fit in their entries.
+	pub fn write_ifd(&mut self, mut entries: Vec<Entry>, next_ifd_offset: u32) -> u32 {
+		entries.sort_by_key(|entry| entry.tag);
+
+		let ifd_offset = self.write_bytes(&(entries.len() as u16).to_le_bytes());
+		let values_offset = ifd_offset + 2 + 12 * entries.len() as u32 + 4;
+		let mut values = Vec::new();
+
+		for entry in &entries {
+			self.data.extend(entry.tag.to_le_bytes());
+			self.data.extend(entry.the_type.to_le_bytes());
+			self.data.extend(entry.count.to_le_bytes());
+
+			match &entry.data {
+				Data::Bytes(bytes) if bytes.len() <= 4 => self.data.extend(bytes.iter().copied().chain([0; 4]).take(4)),
+				Data::Bytes(bytes) => {
+					self.data.extend((values_offset + values.len() as u32).to_le_bytes());
+					values.extend_from_slice(bytes);
+				}
+				Data::At(offset) => self.data.extend(offset.to_le_bytes()),
+			}
+		}
+
+		self.data.extend(next_ifd_offset.to_le_bytes());
+		self.data.extend(values);
+		ifd_offset
+	}
+
+	pub fn finish(mut self, first_ifd_offset: u32) -> Vec<u8> {
+		self.data[4..8].copy_from_slice(&first_ifd_offset.to_le_bytes());
+		self.data
+	}
+}
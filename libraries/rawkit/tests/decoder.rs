@@ -0,0 +1,460 @@
+mod common;
+
+use common::*;
+use rawkit::decoder::ljpeg;
+use rawkit::{ColorFilterArray, DecoderError, RawImage};
+use std::io::Cursor;
+
+/// Code lengths of the Huffman table used by the lossless JPEG encoder below, for each difference category from 0 to 16.
+const CODE_LENGTHS: [u8; 17] = [2, 2, 3, 3, 3, 5, 5, 5, 7, 7, 7, 9, 9, 9, 12, 12, 12];
+
+/// The Huffman table of compressed ARW files, with the code length in the high byte and the difference category in the low byte.
+const ARW_TABLE: [u16; 18] = [
+	0x0f11, 0x0f10, 0x0e0f, 0x0d0e, 0x0c0d, 0x0b0c, 0x0a0b, 0x090a, 0x0809, 0x0708, 0x0607, 0x0506, 0x0405, 0x0304, 0x0303, 0x0300, 0x0202, 0x0201,
+];
+
+/// Writes bits starting with the most significant one, optionally stuffing a zero byte after every `0xff` like JPEG data needs.
+struct BitWriter {
+	data: Vec<u8>,
+	byte: u8,
+	bits: u32,
+	stuff_bytes: bool,
+}
+
+impl BitWriter {
+	fn new(stuff_bytes: bool) -> Self {
+		Self {
+			data: Vec::new(),
+			byte: 0,
+			bits: 0,
+			stuff_bytes,
+		}
+	}
+
+	fn write(&mut self, value: u32, length: u32) {
+		for bit in (0..length).rev() {
+			self.byte = (self.byte << 1) | ((value >> bit) & 1) as u8;
+			self.bits += 1;
+
+			if self.bits == 8 {
+				self.data.push(self.byte);
+				if self.stuff_bytes && self.byte == 0xff {
+					self.data.push(0);
+				}
+				self.byte = 0;
+				self.bits = 0;
+			}
+		}
+	}
+
+	/// Fills the rest of the current byte with ones, which is how JPEG data is padded before a marker.
+	fn pad_with_ones(&mut self) {
+		while self.bits != 0 {
+			self.write(1, 1);
+		}
+	}
+
+	/// Fills the rest of the current byte with zeros, which is how rows of packed samples are padded.
+	fn pad_with_zeros(&mut self) {
+		while self.bits != 0 {
+			self.write(0, 1);
+		}
+	}
+
+	/// Writes a difference as its category's Huffman code followed by the category's number of extra bits.
+	fn write_difference(&mut self, difference: i32, code: impl Fn(u32) -> (u32, u32)) {
+		let category = match difference {
+			0 => 0,
+			32768 => 16,
+			_ => 32 - difference.unsigned_abs().leading_zeros(),
+		};
+
+		let (code, length) = code(category);
+		self.write(code, length);
+		if (1..16).contains(&category) {
+			let extra_bits = if difference > 0 { difference } else { difference + (1 << category) - 1 };
+			self.write(extra_bits as u32, category);
+		}
+	}
+}
+
+/// The canonical Huffman code and its length for each category, assigned in the same order as in a JPEG DHT segment.
+fn lossless_jpeg_codes() -> Vec<(u32, u32)> {
+	let mut codes = vec![(0, 0); CODE_LENGTHS.len()];
+	let mut code = 0;
+	for length in 1..=16 {
+		for (category, _) in CODE_LENGTHS.iter().enumerate().filter(|&(_, &code_length)| code_length == length) {
+			codes[category] = (code, length.into());
+			code += 1;
+		}
+		code <<= 1;
+	}
+	codes
+}
+
+/// Encodes interleaved samples as a lossless JPEG image with one Huffman table, following the prediction rules of ITU-T T.81 section H.1.2.
+fn encode_lossless_jpeg(samples: &[u16], width: usize, height: usize, components: usize, precision: u8, predictor: u8, restart_interval: usize) -> Vec<u8> {
+	let mut data = vec![0xff, 0xd8];
+
+	let mut segment = |marker: u8, contents: &[u8]| {
+		data.extend([0xff, marker]);
+		data.extend((contents.len() as u16 + 2).to_be_bytes());
+		data.extend(contents);
+	};
+
+	let mut frame = vec![precision];
+	frame.extend((height as u16).to_be_bytes());
+	frame.extend((width as u16).to_be_bytes());
+	frame.push(components as u8);
+	(0..components).for_each(|component| frame.extend([component as u8 + 1, 0x11, 0]));
+	segment(0xc3, &frame);
+
+	let mut huffman_table = vec![0x00];
+	huffman_table.extend((1..=16).map(|length| CODE_LENGTHS.iter().filter(|&&code_length| code_length == length).count() as u8));
+	huffman_table.extend(0..CODE_LENGTHS.len() as u8);
+	segment(0xc4, &huffman_table);
+
+	if restart_interval > 0 {
+		segment(0xdd, &(restart_interval as u16).to_be_bytes());
+	}
+
+	let mut scan = vec![components as u8];
+	(0..components).for_each(|component| scan.extend([component as u8 + 1, 0x00]));
+	scan.extend([predictor, 0, 0]);
+	segment(0xda, &scan);
+
+	let codes = lossless_jpeg_codes();
+	let mut bits = BitWriter::new(true);
+	let mut first_row_after_restart = 0;
+	let mut restarts = 0;
+
+	for row in 0..height {
+		if row > 0 && restart_interval > 0 && (row * width).is_multiple_of(restart_interval) {
+			bits.pad_with_ones();
+			bits.data.extend([0xff, 0xd0 + restarts % 8]);
+			restarts += 1;
+			first_row_after_restart = row;
+		}
+
+		for column in 0..width {
+			for component in 0..components {
+				let sample = |row: usize, column: usize| samples[(row * width + column) * components + component] as i32;
+
+				let prediction = match (row == first_row_after_restart, column == 0) {
+					(true, true) => 1 << (precision - 1),
+					(true, false) => sample(row, column - 1),
+					(false, true) => sample(row - 1, column),
+					(false, false) => {
+						let (a, b, c) = (sample(row, column - 1), sample(row - 1, column), sample(row - 1, column - 1));
+						match predictor {
+							1 => a,
+							2 => b,
+							3 => c,
+							4 => a + b - c,
+							5 => a + ((b - c) >> 1),
+							6 => b + ((a - c) >> 1),
+							_ => (a + b) >> 1,
+						}
+					}
+				};
+
+				// Differences are calculated modulo 2^16, in the range from -32767 to 32768
+				let difference = (sample(row, column) - prediction).rem_euclid(1 << 16);
+				let difference = if difference > 32768 { difference - (1 << 16) } else { difference };
+				bits.write_difference(difference, |category| codes[category as usize]);
+			}
+		}
+	}
+
+	bits.pad_with_ones();
+	data.extend(bits.data);
+	data.extend([0xff, 0xd9]);
+	data
+}
+
+/// 12 bit samples with a flat area, gradients and noise, so small and large differences are both encoded.
+fn test_samples(width: usize, height: usize, components: usize) -> Vec<u16> {
+	(0..width * height * components)
+		.map(|index| {
+			let (row, column, component) = (index / components / width, index / components % width, index % components);
+			if column < 2 {
+				return 2048;
+			}
+			(((column * 389 + row * 1021 + component * 1500) ^ (column * row * 7)) % 4096) as u16
+		})
+		.collect()
+}
+
+fn is_corrupt_data<T>(result: &Result<T, DecoderError>) -> bool {
+	matches!(result, Err(DecoderError::CorruptData(_)))
+}
+
+#[test]
+fn lossless_jpeg_predictors_and_restart_intervals() {
+	let (width, height, components) = (9, 6, 2);
+	let samples = test_samples(width, height, components);
+
+	for predictor in 1..=7 {
+		for restart_interval in [0, width, 2 * width] {
+			let data = encode_lossless_jpeg(&samples, width, height, components, 12, predictor, restart_interval);
+			let image = ljpeg::decode(&data).unwrap_or_else(|error| panic!("predictor {predictor}, restart interval {restart_interval}: {error}"));
+
+			assert_eq!((image.width, image.height, image.components), (width, height, components));
+			assert_eq!(image.data, samples, "predictor {predictor}, restart interval {restart_interval}");
+		}
+	}
+}
+
+#[test]
+fn lossless_jpeg_differences_wrap_around() {
+	// Starting from the initial prediction of 32768, these need differences of 32768 (which has no extra bits), 32767 and 1 after wrapping around
+	let samples = [0, 32768, 65535, 0];
+	let data = encode_lossless_jpeg(&samples, 4, 1, 1, 16, 1, 0);
+
+	assert_eq!(ljpeg::decode(&data).unwrap().data, samples);
+}
+
+#[test]
+fn truncated_lossless_jpeg() {
+	let (width, height, components) = (9, 6, 2);
+	let samples = test_samples(width, height, components);
+
+	for restart_interval in [0, 2 * width] {
+		let data = encode_lossless_jpeg(&samples, width, height, components, 12, 4, restart_interval);
+
+		// Everything except the end of image marker is needed
+		for length in 0..data.len() - 2 {
+			assert!(is_corrupt_data(&ljpeg::decode(&data[..length])), "restart interval {restart_interval}, length {length}");
+		}
+		assert_eq!(ljpeg::decode(&data[..data.len() - 2]).unwrap().data, samples);
+	}
+}
+
+#[test]
+fn invalid_lossless_jpeg() {
+	let data = encode_lossless_jpeg(&test_samples(4, 4, 1), 4, 4, 1, 12, 1, 0);
+	let scan_header = data.windows(2).position(|bytes| bytes == [0xff, 0xda]).unwrap() + 2;
+
+	assert!(is_corrupt_data(&ljpeg::decode(&data[2..])));
+
+	let mut invalid_predictor = data.clone();
+	invalid_predictor[scan_header + 5] = 8;
+	assert!(is_corrupt_data(&ljpeg::decode(&invalid_predictor)));
+
+	// The codes made of only ones aren't assigned to any category
+	let mut invalid_code = data[..scan_header + 8].to_vec();
+	invalid_code.extend([0xff, 0x00].repeat(8));
+	invalid_code.extend([0xff, 0xd9]);
+	assert!(is_corrupt_data(&ljpeg::decode(&invalid_code)));
+}
+
+/// Packs the samples of each row into `bits_per_sample` bits each, with every row starting at a new byte.
+fn pack_samples(samples: &[u16], width: usize, bits_per_sample: u32) -> Vec<u8> {
+	let mut bits = BitWriter::new(false);
+	for row in samples.chunks(width) {
+		row.iter().for_each(|&sample| bits.write(sample.into(), bits_per_sample));
+		bits.pad_with_zeros();
+	}
+	bits.data
+}
+
+/// The entries which every DNG raw IFD of these tests has, describing a 12 bit image with a GRBG Bayer pattern.
+fn dng_raw_entries(width: usize, height: usize, compression: u16) -> Vec<Entry> {
+	vec![
+		long(0x100, width as u32),
+		long(0x101, height as u32),
+		short(0x102, 12),
+		short(0x103, compression),
+		short(0x106, 32803),
+		shorts(0x828d, &[2, 2]),
+		bytes(0x828e, &[1, 0, 2, 1]),
+	]
+}
+
+/// An uncompressed DNG file with strips of two rows, where the last strip is missing `missing_bytes` bytes.
+fn uncompressed_dng_file(samples: &[u16], width: usize, height: usize, missing_bytes: u32) -> Vec<u8> {
+	let mut tiff = TiffBuilder::new();
+
+	let mut offsets = Vec::new();
+	let mut byte_counts = Vec::new();
+	for strip in samples.chunks(2 * width) {
+		let data = pack_samples(strip, width, 12);
+		offsets.push(tiff.write_bytes(&data));
+		byte_counts.push(data.len() as u32);
+	}
+	*byte_counts.last_mut().unwrap() -= missing_bytes;
+
+	let mut entries = dng_raw_entries(width, height, 1);
+	entries.extend([
+		bytes(0xc612, &[1, 4, 0, 0]),
+		long(0x116, 2),
+		longs(0x111, &offsets),
+		longs(0x117, &byte_counts),
+		shorts(0xc61a, &[64]),
+		shorts(0xc61d, &[4000]),
+	]);
+	let ifd = tiff.write_ifd(entries, 0);
+	tiff.finish(ifd)
+}
+
+/// A DNG file with a preview in the main IFD and lossless JPEG compressed tiles of 8x4 pixels in a SubIFD, where the last tile is missing `missing_bytes` bytes.
+///
+/// Each tile is encoded as a 4x4 image with two components, like most DNG writers do, so the samples of the components alternate along each row of the tile.
+fn lossless_jpeg_dng_file(samples: &[u16], width: usize, height: usize, missing_bytes: u32) -> Vec<u8> {
+	let (tile_width, tile_length) = (8, 4);
+	let mut tiff = TiffBuilder::new();
+
+	let mut offsets = Vec::new();
+	let mut byte_counts = Vec::new();
+	for top in (0..height).step_by(tile_length) {
+		for left in (0..width).step_by(tile_width) {
+			let tile: Vec<u16> = (0..tile_width * tile_length)
+				.map(|index| {
+					let (row, column) = (top + index / tile_width, left + index % tile_width);
+					if row < height && column < width { samples[row * width + column] } else { 0 }
+				})
+				.collect();
+
+			let data = encode_lossless_jpeg(&tile, tile_width / 2, tile_length, 2, 12, 1, 0);
+			offsets.push(tiff.write_bytes(&data));
+			byte_counts.push(data.len() as u32);
+		}
+	}
+	*byte_counts.last_mut().unwrap() -= missing_bytes;
+
+	let mut raw_entries = dng_raw_entries(width, height, 7);
+	raw_entries.extend([
+		long(0xfe, 0),
+		long(0x142, tile_width as u32),
+		long(0x143, tile_length as u32),
+		longs(0x144, &offsets),
+		longs(0x145, &byte_counts),
+	]);
+	let raw_ifd = tiff.write_ifd(raw_entries, 0);
+	let ifd = tiff.write_ifd(vec![bytes(0xc612, &[1, 4, 0, 0]), long(0xfe, 1), long(0x14a, raw_ifd)], 0);
+	tiff.finish(ifd)
+}
+
+#[test]
+fn uncompressed_dng() {
+	// An odd width, so the 12 bit rows end in the middle of a byte
+	let (width, height) = (7, 5);
+	let samples = test_samples(width, height, 1);
+
+	let raw_image = RawImage::decode(&mut Cursor::new(uncompressed_dng_file(&samples, width, height, 0))).unwrap();
+
+	assert_eq!((raw_image.width, raw_image.height), (width, height));
+	assert_eq!(raw_image.data, samples);
+	assert_eq!(raw_image.cfa_pattern, ColorFilterArray::Bayer([1, 0, 2, 1]));
+	assert_eq!(raw_image.maximum, 4000);
+	assert_eq!(raw_image.black.cfa_grid(), [64; 4]);
+
+	let result = RawImage::decode(&mut Cursor::new(uncompressed_dng_file(&samples, width, height, 1)));
+	assert!(is_corrupt_data(&result));
+}
+
+#[test]
+fn lossless_jpeg_dng() {
+	// The tiles in the right column and bottom row extend past the image
+	let (width, height) = (12, 6);
+	let samples = test_samples(width, height, 1);
+
+	let raw_image = RawImage::decode(&mut Cursor::new(lossless_jpeg_dng_file(&samples, width, height, 0))).unwrap();
+
+	assert_eq!((raw_image.width, raw_image.height), (width, height));
+	assert_eq!(raw_image.data, samples);
+	assert_eq!(raw_image.cfa_pattern, ColorFilterArray::Bayer([1, 0, 2, 1]));
+	assert_eq!(raw_image.maximum, 4095);
+	assert_eq!(raw_image.black.cfa_grid(), [0; 4]);
+
+	// The end of image marker isn't needed, but every byte before it is
+	assert!(RawImage::decode(&mut Cursor::new(lossless_jpeg_dng_file(&samples, width, height, 2))).is_ok());
+	for missing_bytes in 3..12 {
+		let result = RawImage::decode(&mut Cursor::new(lossless_jpeg_dng_file(&samples, width, height, missing_bytes)));
+		assert!(is_corrupt_data(&result), "{missing_bytes} missing bytes");
+	}
+}
+
+/// The Huffman code and its length for each difference category of compressed ARW files.
+fn arw_codes() -> Vec<(u32, u32)> {
+	let mut codes = vec![(0, 0); ARW_TABLE.len()];
+	let mut first_lookup_index = 0;
+	for entry in ARW_TABLE {
+		let length = (entry >> 8) as u32;
+		codes[(entry & 0xff) as usize] = (first_lookup_index >> (15 - length), length);
+		first_lookup_index += 0x8000 >> length;
+	}
+	codes
+}
+
+/// Encodes the samples as compressed ARW 1 data, which stores the differences column by column from right to left, with the even rows of each column before the odd rows.
+///
+/// Returns the compressed data and the number of bytes in it which are read by the decoder.
+fn encode_arw1(samples: &[u16], width: usize, height: usize) -> (Vec<u8>, usize) {
+	let codes = arw_codes();
+	let mut bits = BitWriter::new(false);
+	let mut previous = 0;
+
+	// The data contains 8 more rows than the image, which aren't part of the decoded image
+	let raw_height = height + 8;
+	for column in (0..width).rev() {
+		for row in (0..raw_height).step_by(2).chain((1..raw_height).step_by(2)) {
+			let sample = if row < height { samples[row * width + column].into() } else { 100 };
+			bits.write_difference(sample - previous, |category| codes[category as usize]);
+			previous = sample;
+		}
+	}
+	bits.pad_with_zeros();
+
+	// The decoder looks 15 bits ahead for each Huffman code, which needs some data after the last code
+	let length = bits.data.len();
+	bits.data.extend([0; 2]);
+	(bits.data, length)
+}
+
+/// A Sony file with compressed ARW 1 data, which is written after the IFDs so that truncating the file cuts it off.
+///
+/// Returns the file and the offset of the compressed data.
+fn compressed_arw_file(compressed: &[u8], width: usize, height: usize) -> (Vec<u8>, u32) {
+	let build = |strip_offset: u32| {
+		let mut tiff = TiffBuilder::new();
+		let arw_ifd = tiff.write_ifd(
+			vec![
+				long(0x100, width as u32),
+				long(0x101, height as u32),
+				short(0x103, 32767),
+				long(0x111, strip_offset),
+				long(0x117, compressed.len() as u32),
+			],
+			0,
+		);
+		let ifd = tiff.write_ifd(vec![ascii(0x10f, "SONY"), ascii(0x110, "DSLR-A700"), long(0x14a, arw_ifd)], 0);
+		let strip_offset = tiff.write_bytes(compressed);
+		(tiff.finish(ifd), strip_offset)
+	};
+
+	// The size of the IFDs doesn't depend on the offset, so the file is built once to find where the data ends up
+	let (_, strip_offset) = build(0);
+	(build(strip_offset).0, strip_offset)
+}
+
+#[test]
+fn compressed_arw1() {
+	let (width, height) = (6, 4);
+	let samples = test_samples(width, height, 1);
+	let (compressed, length) = encode_arw1(&samples, width, height);
+	let (file, strip_offset) = compressed_arw_file(&compressed, width, height);
+
+	let raw_image = RawImage::decode(&mut Cursor::new(&file)).unwrap();
+
+	assert_eq!((raw_image.width, raw_image.height), (width, height));
+	assert_eq!(raw_image.data, samples);
+	assert_eq!(raw_image.cfa_pattern, ColorFilterArray::Bayer([0, 1, 1, 2]));
+	assert_eq!(raw_image.maximum, 4095);
+	assert_eq!(raw_image.camera_model.unwrap().model, "DSLR-A700");
+
+	for length in strip_offset as usize..strip_offset as usize + length {
+		let result = RawImage::decode(&mut Cursor::new(&file[..length]));
+		assert!(is_corrupt_data(&result), "{length} of {} bytes", file.len());
+	}
+}
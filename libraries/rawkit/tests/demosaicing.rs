@@ -0,0 +1,170 @@
+use rawkit::demosaicing::DemosaicAlgorithm;
+use rawkit::tiff::values::Transform;
+use rawkit::{ColorFilterArray, RawImage, SubtractBlack};
+
+const WIDTH: usize = 128;
+const HEIGHT: usize = 96;
+
+/// Pixels this close to the border are left out when measuring the error, since every algorithm has less information there.
+const BORDER: usize = 8;
+
+const ALGORITHMS: [DemosaicAlgorithm; 3] = [DemosaicAlgorithm::Linear, DemosaicAlgorithm::Vng, DemosaicAlgorithm::Ahd];
+
+const BAYER_PATTERNS: [(&str, [u8; 4]); 4] = [("RGGB", [0, 1, 1, 2]), ("BGGR", [2, 1, 1, 0]), ("GRBG", [1, 0, 2, 1]), ("GBRG", [1, 2, 0, 1])];
+
+#[rustfmt::skip]
+const X_TRANS_PATTERN: [u8; 36] = [
+	1, 1, 0, 1, 1, 2,
+	1, 1, 2, 1, 1, 0,
+	2, 0, 1, 0, 2, 1,
+	1, 1, 2, 1, 1, 0,
+	1, 1, 0, 1, 1, 2,
+	0, 2, 1, 2, 0, 1,
+];
+
+/// The known output which the demosaiced images are compared against: smooth gradients, a disc with a sharp edge, diagonal stripes and fine horizontal lines.
+fn reference_image() -> Vec<[u16; 3]> {
+	(0..WIDTH * HEIGHT)
+		.map(|index| {
+			let (row, column) = ((index / WIDTH) as f64, (index % WIDTH) as f64);
+
+			let brightness = 0.35 + 0.25 * (column / 20.).sin() * (row / 30.).cos();
+			let mut color = [0.9 * brightness, 0.7 * brightness, 0.4 * brightness];
+
+			if (row - 48.).hypot(column - 40.) < 24. {
+				color = [0.2, 0.55, 0.85];
+			}
+			if column > 76. && (((row + column) / 8.).floor() as usize).is_multiple_of(2) {
+				color = color.map(|value| value * 0.3);
+			}
+			if row > 80. && (row as usize / 3).is_multiple_of(2) {
+				color = [0.8, 0.8, 0.75];
+			}
+
+			color.map(|value| (value * u16::MAX as f64).round() as u16)
+		})
+		.collect()
+}
+
+fn mosaic(reference: &[[u16; 3]], cfa_pattern: ColorFilterArray) -> RawImage {
+	RawImage {
+		data: reference.iter().enumerate().map(|(index, color)| color[cfa_pattern.color_at(index / WIDTH, index % WIDTH)]).collect(),
+		width: WIDTH,
+		height: HEIGHT,
+		cfa_pattern,
+		maximum: u16::MAX,
+		black: SubtractBlack::None,
+		transform: Transform::Horizontal,
+		camera_model: None,
+		camera_white_balance: None,
+		white_balance: None,
+		daylight_white_balance: None,
+		camera_to_rgb: None,
+	}
+}
+
+/// Peak signal-to-noise ratio in decibels between the demosaiced image and the reference, ignoring the border.
+fn psnr(demosaiced: &[u16], reference: &[[u16; 3]]) -> f64 {
+	let mut squared_error = 0.;
+	let mut count = 0.;
+
+	for row in BORDER..HEIGHT - BORDER {
+		for column in BORDER..WIDTH - BORDER {
+			let index = row * WIDTH + column;
+			for channel in 0..3 {
+				let difference = demosaiced[3 * index + channel] as f64 - reference[index][channel] as f64;
+				squared_error += difference * difference;
+				count += 1.;
+			}
+		}
+	}
+
+	let peak = u16::MAX as f64;
+	10. * (peak * peak / (squared_error / count)).log10()
+}
+
+fn demosaic_psnr(cfa_pattern: ColorFilterArray, algorithm: DemosaicAlgorithm) -> f64 {
+	let reference = reference_image();
+	let image = mosaic(&reference, cfa_pattern).demosaic(algorithm);
+
+	assert_eq!((image.width, image.height, image.channels), (WIDTH, HEIGHT, 3));
+	psnr(&image.data, &reference)
+}
+
+#[test]
+fn raw_samples_are_preserved() {
+	let reference = reference_image();
+	let cfa_patterns = BAYER_PATTERNS
+		.map(|(_, pattern)| ColorFilterArray::Bayer(pattern))
+		.into_iter()
+		.chain([ColorFilterArray::XTrans(X_TRANS_PATTERN)]);
+
+	for cfa_pattern in cfa_patterns {
+		let raw_image = mosaic(&reference, cfa_pattern);
+		for algorithm in ALGORITHMS {
+			let image = raw_image.demosaic(algorithm);
+			for (index, &value) in raw_image.data.iter().enumerate() {
+				let color = cfa_pattern.color_at(index / WIDTH, index % WIDTH);
+				assert_eq!(image.data[3 * index + color], value, "{algorithm:?} changed a raw sample of {cfa_pattern:?}");
+			}
+		}
+	}
+}
+
+#[test]
+fn flat_images_are_reconstructed_exactly() {
+	let reference = vec![[30000, 20000, 10000]; WIDTH * HEIGHT];
+	let cfa_patterns = BAYER_PATTERNS
+		.map(|(_, pattern)| ColorFilterArray::Bayer(pattern))
+		.into_iter()
+		.chain([ColorFilterArray::XTrans(X_TRANS_PATTERN)]);
+
+	for cfa_pattern in cfa_patterns {
+		for algorithm in ALGORITHMS {
+			let image = mosaic(&reference, cfa_pattern).demosaic(algorithm);
+			assert!(
+				image.data.chunks_exact(3).all(|pixel| pixel == [30000, 20000, 10000]),
+				"{algorithm:?} changed a flat image with {cfa_pattern:?}"
+			);
+		}
+	}
+}
+
+#[test]
+fn bayer_patterns_match_reference() {
+	for (name, pattern) in BAYER_PATTERNS {
+		let [linear, vng, ahd] = ALGORITHMS.map(|algorithm| demosaic_psnr(ColorFilterArray::Bayer(pattern), algorithm));
+
+		assert!(linear > 24., "linear demosaicing of {name} has a PSNR of {linear:.2} dB");
+		assert!(
+			vng > 29. && vng > linear + 3.,
+			"VNG demosaicing of {name} has a PSNR of {vng:.2} dB, compared to {linear:.2} dB for linear"
+		);
+		assert!(ahd > 30. && ahd > vng, "AHD demosaicing of {name} has a PSNR of {ahd:.2} dB, compared to {vng:.2} dB for VNG");
+	}
+}
+
+#[test]
+fn bayer_orientations_have_similar_error() {
+	for algorithm in ALGORITHMS {
+		let psnrs = BAYER_PATTERNS.map(|(_, pattern)| demosaic_psnr(ColorFilterArray::Bayer(pattern), algorithm));
+		let (minimum, maximum) = psnrs
+			.iter()
+			.fold((f64::INFINITY, f64::NEG_INFINITY), |(minimum, maximum), &psnr| (minimum.min(psnr), maximum.max(psnr)));
+
+		assert!(maximum - minimum < 1., "{algorithm:?} has PSNRs of {psnrs:.2?} dB for the Bayer orientations");
+	}
+}
+
+#[test]
+fn x_trans_pattern_matches_reference() {
+	let [linear, vng, ahd] = ALGORITHMS.map(|algorithm| demosaic_psnr(ColorFilterArray::XTrans(X_TRANS_PATTERN), algorithm));
+
+	assert!(linear > 23., "linear demosaicing of X-Trans has a PSNR of {linear:.2} dB");
+	assert!(
+		vng > 27. && vng > linear + 3.,
+		"VNG demosaicing of X-Trans has a PSNR of {vng:.2} dB, compared to {linear:.2} dB for linear"
+	);
+	// AHD only supports Bayer patterns, so it falls back to VNG
+	assert_eq!(ahd, vng);
+}
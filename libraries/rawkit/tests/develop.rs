@@ -0,0 +1,131 @@
+use rawkit::develop::{DevelopSettings, HighlightRecovery, OutputColorSpace, WhiteBalance};
+use rawkit::tiff::values::Transform;
+use rawkit::{ColorFilterArray, Image, RawImage, SubtractBlack};
+
+const SIZE: usize = 16;
+const MAXIMUM: u16 = 4095;
+
+/// Color matrix of the Sony ILCE-7M3.
+const XYZ_TO_CAMERA: [f64; 9] = [0.7374, -0.2389, -0.0551, -0.5435, 1.3162, 0.2519, -0.1006, 0.1795, 0.6552];
+
+/// A flat raw image where each color of the sensor records the given fraction of its saturation point.
+fn flat_raw_image(color: impl Fn(&RawImage) -> [f64; 3]) -> RawImage {
+	let mut raw_image = RawImage {
+		data: vec![0; SIZE * SIZE],
+		width: SIZE,
+		height: SIZE,
+		cfa_pattern: ColorFilterArray::Bayer([0, 1, 1, 2]),
+		maximum: MAXIMUM,
+		black: SubtractBlack::None,
+		transform: Transform::Horizontal,
+		camera_model: None,
+		camera_white_balance: None,
+		white_balance: None,
+		daylight_white_balance: None,
+		camera_to_rgb: None,
+	};
+	raw_image.calculate_conversion_matrices_from_xyz_to_camera(XYZ_TO_CAMERA);
+
+	let color = color(&raw_image);
+	for (index, value) in raw_image.data.iter_mut().enumerate() {
+		let color = color[raw_image.cfa_pattern.color_at(index / SIZE, index % SIZE)];
+		*value = (color.min(1.) * MAXIMUM as f64).round() as u16;
+	}
+
+	raw_image
+}
+
+/// How a gray surface lit by daylight is recorded by the camera.
+fn daylight_gray(brightness: f64) -> impl Fn(&RawImage) -> [f64; 3] {
+	move |raw_image| {
+		let daylight = raw_image.daylight_white_balance.unwrap();
+		let minimum = daylight.into_iter().fold(f64::INFINITY, f64::min);
+		daylight.map(|multiplier| brightness * minimum / multiplier)
+	}
+}
+
+fn center(image: &Image<u16>) -> [f64; 3] {
+	let index = 3 * (SIZE / 2 * SIZE + SIZE / 2);
+	std::array::from_fn(|channel| image.data[index + channel] as f64 / u16::MAX as f64)
+}
+
+fn linear_settings(white_balance: WhiteBalance) -> DevelopSettings {
+	DevelopSettings {
+		white_balance,
+		linear: true,
+		..Default::default()
+	}
+}
+
+#[test]
+fn daylight_white_balance_keeps_gray_neutral() {
+	let raw_image = flat_raw_image(daylight_gray(0.4));
+	let daylight = raw_image.daylight_white_balance.unwrap();
+
+	let [red, green, blue] = center(&raw_image.develop(&linear_settings(WhiteBalance::Custom(daylight))));
+	assert!((red - green).abs() < 0.005 && (blue - green).abs() < 0.005, "gray became {:?}", [red, green, blue]);
+	assert!((green - 0.4).abs() < 0.01, "gray has a brightness of {green}");
+
+	// The light of a black body at 6500 K is close to daylight
+	let raw_image = flat_raw_image(daylight_gray(0.4));
+	let [red, green, blue] = center(&raw_image.develop(&linear_settings(WhiteBalance::Temperature { temperature: 6500., tint: 0. })));
+	assert!((red - green).abs() < 0.03 && (blue - green).abs() < 0.03, "gray became {:?}", [red, green, blue]);
+}
+
+#[test]
+fn temperature_and_tint_shift_colors() {
+	// Neutralizing a warmer light than the one in the scene makes the image cooler
+	let [red, _, blue] = center(&flat_raw_image(daylight_gray(0.4)).develop(&linear_settings(WhiteBalance::Temperature { temperature: 3200., tint: 0. })));
+	assert!(blue > 1.5 * red, "red is {red} and blue is {blue}");
+
+	let [red, _, blue] = center(&flat_raw_image(daylight_gray(0.4)).develop(&linear_settings(WhiteBalance::Temperature { temperature: 12000., tint: 0. })));
+	assert!(red > 1.2 * blue, "red is {red} and blue is {blue}");
+
+	let [red, green, blue] = center(&flat_raw_image(daylight_gray(0.4)).develop(&linear_settings(WhiteBalance::Temperature { temperature: 6500., tint: 50. })));
+	assert!(green < red.min(blue), "a magenta tint gave {:?}", [red, green, blue]);
+}
+
+#[test]
+fn exposure_scales_linear_output() {
+	let develop = |exposure| {
+		let raw_image = flat_raw_image(daylight_gray(0.2));
+		let daylight = raw_image.daylight_white_balance.unwrap();
+		center(&raw_image.develop(&DevelopSettings {
+			exposure,
+			..linear_settings(WhiteBalance::Custom(daylight))
+		}))
+	};
+
+	let [normal, brighter] = [develop(0.), develop(1.)];
+	for (normal, brighter) in normal.into_iter().zip(brighter) {
+		assert!((brighter - 2. * normal).abs() < 0.005, "{normal} became {brighter}");
+	}
+}
+
+#[test]
+fn highlight_recovery_modes() {
+	// Every color of the sensor is saturated, but the white balance multipliers would push red and blue above green
+	let develop = |highlight_recovery, exposure| {
+		let raw_image = flat_raw_image(|_| [1.; 3]);
+		let daylight = raw_image.daylight_white_balance.unwrap();
+		center(&raw_image.develop(&DevelopSettings {
+			highlight_recovery,
+			exposure,
+			output_color_space: OutputColorSpace::Camera,
+			..linear_settings(WhiteBalance::Custom(daylight))
+		}))
+	};
+
+	assert_eq!(develop(HighlightRecovery::Clip, 0.), [1.; 3]);
+	assert_eq!(develop(HighlightRecovery::Clip, -1.).map(|value| (value * 1000.).round()), [500.; 3]);
+
+	let [red, green, blue] = develop(HighlightRecovery::Unclip, -2.);
+	assert!(red > green * 1.2 || blue > green * 1.2, "unclipped highlights have no color cast: {:?}", [red, green, blue]);
+
+	let [red, green, blue] = develop(HighlightRecovery::Blend, -2.);
+	assert!(
+		(red - green).abs() < 0.01 && (blue - green).abs() < 0.01,
+		"blended highlights have a color cast: {:?}",
+		[red, green, blue]
+	);
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use common::*;
+use rawkit::RawImage;
+use rawkit::metadata::exif::{CaptureTime, GpsPosition};
+use rawkit::tiff::values::Transform;
+use std::io::Cursor;
+
+fn jpeg(length: usize) -> Vec<u8> {
+	let mut data = vec![0x55; length];
+	data[..2].copy_from_slice(&[0xff, 0xd8]);
+	data[length - 2..].copy_from_slice(&[0xff, 0xd9]);
+	data
+}
+
+#[test]
+fn exif_and_gps_metadata() {
+	let mut tiff = TiffBuilder::new();
+
+	let exif_ifd = tiff.write_ifd(
+		vec![
+			rationals(0x829a, &[(1, 250)]),
+			rationals(0x829d, &[(28, 10)]),
+			short(0x8827, 800),
+			ascii(0x9003, "2024:05:03 14:22:10"),
+			ascii(0x9011, "+09:00"),
+			ascii(0x9291, "12"),
+			srational(0x9204, -2, 3),
+			rationals(0x920a, &[(35, 1)]),
+			short(0xa405, 52),
+			ascii(0xa434, "FE 35mm F1.8"),
+		],
+		0,
+	);
+	let gps_ifd = tiff.write_ifd(
+		vec![
+			ascii(0x1, "N"),
+			rationals(0x2, &[(48, 1), (51, 1), (295, 10)]),
+			ascii(0x3, "W"),
+			rationals(0x4, &[(2, 1), (17, 1), (40, 1)]),
+			Entry {
+				tag: 0x5,
+				the_type: BYTE,
+				count: 1,
+				data: Data::Bytes(vec![1]),
+			},
+			rationals(0x6, &[(10, 1)]),
+		],
+		0,
+	);
+	let ifd = tiff.write_ifd(vec![ascii(0x10f, "SONY "), ascii(0x110, "ILCE-7M3"), short(0x112, 6), long(0x8769, exif_ifd), long(0x8825, gps_ifd)], 0);
+	let data = tiff.finish(ifd);
+
+	let metadata = RawImage::decode_metadata(&mut Cursor::new(data)).unwrap();
+
+	assert_eq!(metadata.make.as_deref(), Some("SONY"));
+	assert_eq!(metadata.model.as_deref(), Some("ILCE-7M3"));
+	assert_eq!(metadata.orientation, Some(Transform::Rotate90));
+	assert_eq!(metadata.lens.as_deref(), Some("FE 35mm F1.8"));
+	assert_eq!(metadata.iso, Some(800));
+	assert_eq!(metadata.exposure_time, Some(1. / 250.));
+	assert_eq!(metadata.f_number, Some(2.8));
+	assert_eq!(metadata.focal_length, Some(35.));
+	assert_eq!(metadata.focal_length_35mm, Some(52));
+	assert_eq!(metadata.exposure_bias, Some(-2. / 3.));
+
+	let capture_time = metadata.capture_time.unwrap();
+	assert_eq!(capture_time.to_string(), "2024-05-03T14:22:10.120+09:00");
+
+	let GpsPosition { latitude, longitude, altitude } = metadata.gps.unwrap();
+	assert!((latitude - (48. + 51. / 60. + 29.5 / 3600.)).abs() < 1e-9);
+	assert!((longitude + (2. + 17. / 60. + 40. / 3600.)).abs() < 1e-9);
+	assert_eq!(altitude, Some(-10.));
+}
+
+#[test]
+fn sony_maker_note_lens() {
+	let mut tiff = TiffBuilder::new();
+
+	// A 24-70mm F2.8 lens, recorded in the maker note only
+	let maker_note_offset = tiff.write_bytes(b"SONY DSC \0\0\0");
+	tiff.write_ifd(
+		vec![Entry {
+			tag: 0xb02a,
+			the_type: BYTE,
+			count: 8,
+			data: Data::Bytes(vec![0x00, 0x00, 0x24, 0x00, 0x70, 0x28, 0x28, 0x00]),
+		}],
+		0,
+	);
+	let maker_note_length = tiff.offset() - maker_note_offset;
+
+	let exif_ifd = tiff.write_ifd(
+		vec![Entry {
+			tag: 0x927c,
+			the_type: UNDEFINED,
+			count: maker_note_length,
+			data: Data::At(maker_note_offset),
+		}],
+		0,
+	);
+	let ifd = tiff.write_ifd(vec![ascii(0x10f, "SONY"), ascii(0x110, "ILCE-7M3"), ascii(0x132, "2019:01:02 03:04:05"), long(0x8769, exif_ifd)], 0);
+	let data = tiff.finish(ifd);
+
+	let metadata = RawImage::decode_metadata(&mut Cursor::new(data)).unwrap();
+
+	assert_eq!(metadata.lens.as_deref(), Some("24-70mm F2.8"));
+	assert_eq!(metadata.capture_time.unwrap().to_string(), "2019-01-02T03:04:05");
+	assert_eq!(metadata.iso, None);
+	assert_eq!(metadata.gps, None);
+}
+
+#[test]
+fn largest_jpeg_preview() {
+	let mut tiff = TiffBuilder::new();
+
+	let thumbnail = tiff.write_bytes(&jpeg(40));
+	let preview = tiff.write_bytes(&jpeg(300));
+	// Lossless JPEG raw data also starts with a start of image marker, but it isn't a preview
+	let raw = tiff.write_bytes(&jpeg(1000));
+
+	let preview_ifd = tiff.write_ifd(vec![long(0xfe, 1), short(0x103, 7), long(0x111, preview), long(0x117, 300)], 0);
+	let raw_ifd = tiff.write_ifd(vec![long(0xfe, 0), short(0x103, 7), long(0x111, raw), long(0x117, 1000)], 0);
+	let sub_ifds = tiff.write_bytes(&[preview_ifd.to_le_bytes(), raw_ifd.to_le_bytes()].concat());
+
+	// The thumbnail is in the second IFD of the chain, like in most TIFF based raw formats
+	let thumbnail_ifd = tiff.write_ifd(vec![long(0x201, thumbnail), long(0x202, 40)], 0);
+	let ifd = tiff.write_ifd(
+		vec![Entry {
+			tag: 0x14a,
+			the_type: LONG,
+			count: 2,
+			data: Data::At(sub_ifds),
+		}],
+		thumbnail_ifd,
+	);
+	let data = tiff.finish(ifd);
+
+	let extracted = RawImage::decode_preview(&mut Cursor::new(data)).unwrap().unwrap();
+	assert_eq!(extracted, jpeg(300));
+}
+
+#[test]
+fn missing_metadata_and_preview() {
+	let mut tiff = TiffBuilder::new();
+
+	// The JPEG tags point past the end of the file, as they do in some truncated files
+	let ifd = tiff.write_ifd(vec![ascii(0x10f, "Unknown"), long(0x201, 1 << 20), long(0x202, 1000)], 0);
+	let data = tiff.finish(ifd);
+
+	let metadata = RawImage::decode_metadata(&mut Cursor::new(data.clone())).unwrap();
+	assert_eq!(metadata.make.as_deref(), Some("Unknown"));
+	assert_eq!(metadata.lens, None);
+	assert_eq!(metadata.capture_time, None);
+
+	assert_eq!(RawImage::decode_preview(&mut Cursor::new(data)).unwrap(), None);
+}
+
+#[test]
+fn capture_time_parsing() {
+	let capture_time = CaptureTime::parse("2021:12:31 23:59:58", Some("5"), Some("-03:30")).unwrap();
+	assert_eq!(capture_time.millisecond, Some(500));
+	assert_eq!(capture_time.utc_offset, Some(-210));
+	assert_eq!(capture_time.to_string(), "2021-12-31T23:59:58.500-03:30");
+
+	// Cameras without a clock set record zeros or blanks
+	assert_eq!(CaptureTime::parse("0000:00:00 00:00:00", None, None), None);
+	assert_eq!(CaptureTime::parse("    :  :     :  :  ", None, None), None);
+	assert_eq!(CaptureTime::parse("2021-12-31 23:59:58", None, None), None);
+}
@@ -0,0 +1,158 @@
+use rawkit::Image;
+use rawkit::tiff::TiffError;
+use rawkit::tiff::values::Transform;
+use rawkit::tiff::writer::{TiffAlpha, TiffCompression, TiffImage, TiffLayout, TiffSample, TiffWriteOptions, write_tiff};
+use std::io::Cursor;
+use tiff::ColorType;
+use tiff::decoder::ifd::Value;
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::tags::Tag;
+
+/// Sizes which aren't multiples of the strip or tile size, so the edges are exercised.
+const WIDTH: usize = 37;
+const HEIGHT: usize = 29;
+
+const COMPRESSIONS: [TiffCompression; 3] = [TiffCompression::None, TiffCompression::Lzw, TiffCompression::Deflate];
+const LAYOUTS: [TiffLayout; 3] = [
+	TiffLayout::Strips { rows_per_strip: None },
+	TiffLayout::Strips { rows_per_strip: Some(4) },
+	TiffLayout::Tiles { width: 16, height: 32 },
+];
+
+fn gradient<T>(channels: usize, sample: impl Fn(usize, usize, usize) -> T) -> Vec<T> {
+	(0..HEIGHT)
+		.flat_map(|y| (0..WIDTH).flat_map(move |x| (0..channels).map(move |channel| (x, y, channel))))
+		.map(|(x, y, channel)| sample(x, y, channel))
+		.collect()
+}
+
+fn encode<T: TiffSample>(data: &[T], channels: usize, options: &TiffWriteOptions) -> Vec<u8> {
+	let image = TiffImage {
+		data,
+		width: WIDTH,
+		height: HEIGHT,
+		channels,
+		alpha: TiffAlpha::Unassociated,
+		transform: Transform::Horizontal,
+	};
+
+	let mut output = Vec::new();
+	write_tiff(&mut output, &image, options).unwrap();
+	output
+}
+
+fn decode(data: Vec<u8>) -> (ColorType, DecodingResult) {
+	let mut decoder = Decoder::new(Cursor::new(data)).unwrap();
+	assert_eq!(decoder.dimensions().unwrap(), (WIDTH as u32, HEIGHT as u32));
+	(decoder.colortype().unwrap(), decoder.read_image().unwrap())
+}
+
+#[test]
+fn integer_samples_round_trip() {
+	let rgb_8bit = gradient(3, |x, y, channel| (x * 7 + y * 3 + channel * 50) as u8);
+	let rgba_16bit = gradient(4, |x, y, channel| (x * 1777 + y * 331 + channel * 9000) as u16);
+
+	for compression in COMPRESSIONS {
+		for layout in LAYOUTS {
+			let options = TiffWriteOptions {
+				compression,
+				layout,
+				..Default::default()
+			};
+
+			let (color_type, DecodingResult::U8(decoded)) = decode(encode(&rgb_8bit, 3, &options)) else {
+				panic!("Expected 8-bit samples")
+			};
+			assert_eq!(color_type, ColorType::RGB(8));
+			assert_eq!(decoded, rgb_8bit, "{compression:?} {layout:?}");
+
+			let (color_type, DecodingResult::U16(decoded)) = decode(encode(&rgba_16bit, 4, &options)) else {
+				panic!("Expected 16-bit samples")
+			};
+			assert_eq!(color_type, ColorType::RGBA(16));
+			assert_eq!(decoded, rgba_16bit, "{compression:?} {layout:?}");
+		}
+	}
+}
+
+#[test]
+fn float_samples_round_trip() {
+	// Negative and over range values are kept since these are common in linear light images
+	let gray = gradient(1, |x, y, _| (x as f32 - 10.) * 0.37 + (y as f32).sqrt());
+	let rgb = gradient(3, |x, y, channel| x as f32 / WIDTH as f32 * (channel + 1) as f32 - y as f32 * 0.01);
+
+	for compression in COMPRESSIONS {
+		for layout in LAYOUTS {
+			let options = TiffWriteOptions {
+				compression,
+				layout,
+				..Default::default()
+			};
+
+			let (color_type, DecodingResult::F32(decoded)) = decode(encode(&gray, 1, &options)) else {
+				panic!("Expected float samples")
+			};
+			assert_eq!(color_type, ColorType::Gray(32));
+			assert_eq!(decoded, gray, "{compression:?} {layout:?}");
+
+			let (color_type, DecodingResult::F32(decoded)) = decode(encode(&rgb, 3, &options)) else {
+				panic!("Expected float samples")
+			};
+			assert_eq!(color_type, ColorType::RGB(32));
+			assert_eq!(decoded, rgb, "{compression:?} {layout:?}");
+		}
+	}
+}
+
+#[test]
+fn icc_profile_resolution_and_orientation() {
+	// Odd length, so the values after it have to be realigned
+	let icc_profile: Vec<u8> = (0..=254).collect();
+
+	let image = Image {
+		data: gradient(3, |x, y, channel| (x * y + channel) as u16),
+		width: WIDTH,
+		height: HEIGHT,
+		channels: 3,
+		transform: Transform::Rotate90,
+	};
+	let options = TiffWriteOptions {
+		compression: TiffCompression::Deflate,
+		icc_profile: Some(icc_profile.clone()),
+		pixels_per_inch: 300.,
+		..Default::default()
+	};
+
+	let mut decoder = Decoder::new(Cursor::new(image.encode_tiff(&options).unwrap())).unwrap();
+	assert_eq!(decoder.get_tag_u8_vec(Tag::IccProfile).unwrap(), icc_profile);
+	assert_eq!(decoder.get_tag(Tag::XResolution).unwrap(), Value::Rational(300_000, 1000));
+	assert_eq!(decoder.get_tag(Tag::YResolution).unwrap(), Value::Rational(300_000, 1000));
+	assert_eq!(decoder.get_tag_u32(Tag::ResolutionUnit).unwrap(), 2);
+	assert_eq!(decoder.get_tag_u32(Tag::Unknown(0x112)).unwrap(), 6);
+	assert!(matches!(decoder.read_image().unwrap(), DecodingResult::U16(data) if data == image.data));
+}
+
+#[test]
+fn invalid_images_are_rejected() {
+	let data = gradient(3, |_, _, _| 0_u8);
+	let mut output = Vec::new();
+
+	let mut write = |data: &[u8], channels: usize, layout: TiffLayout| {
+		let image = TiffImage {
+			data,
+			width: WIDTH,
+			height: HEIGHT,
+			channels,
+			alpha: TiffAlpha::default(),
+			transform: Transform::Horizontal,
+		};
+		let options = TiffWriteOptions { layout, ..Default::default() };
+		write_tiff(&mut output, &image, &options)
+	};
+
+	assert!(matches!(write(&data[1..], 3, TiffLayout::default()), Err(TiffError::InvalidCount)));
+	assert!(matches!(write(&data, 4, TiffLayout::default()), Err(TiffError::InvalidCount)));
+	assert!(matches!(write(&[], 0, TiffLayout::default()), Err(TiffError::InvalidValue)));
+	assert!(matches!(write(&data, 3, TiffLayout::Tiles { width: 20, height: 16 }), Err(TiffError::InvalidValue)));
+	assert!(output.is_empty());
+}
@@ -248,6 +248,7 @@ pub enum ExportFormat {
 		transparent: bool,
 	},
 	Jpeg,
+	Pdf,
 	Canvas,
 }
 
@@ -1,5 +1,9 @@
+mod animated_svg;
+mod pdf;
 mod quad;
 mod rect;
+mod svg_optimizer;
+mod svg_tree;
 
 use crate::raster::image::ImageFrameTable;
 use crate::raster::{BlendMode, Image};
@@ -8,15 +12,18 @@ use crate::uuid::{NodeId, generate_uuid};
 use crate::vector::style::{Fill, Stroke, ViewMode};
 use crate::vector::{PointId, VectorDataTable};
 use crate::{Artboard, ArtboardGroupTable, Color, GraphicElement, GraphicGroupTable, RasterFrame};
+pub use animated_svg::SvgAnimationStyle;
 use base64::Engine;
 use bezier_rs::Subpath;
 use dyn_any::DynAny;
 use glam::{DAffine2, DMat2, DVec2};
 use num_traits::Zero;
+pub use pdf::{PdfFillRule, PdfPath, PdfRender};
 pub use quad::Quad;
 pub use rect::Rect;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
+pub use svg_optimizer::SvgExportOptions;
 #[cfg(feature = "vello")]
 use vello::*;
 
@@ -275,6 +282,7 @@ pub trait GraphicElementRendered {
 
 	#[cfg(feature = "vello")]
 	fn render_to_vello(&self, scene: &mut Scene, transform: DAffine2, context: &mut RenderContext, _render_params: &RenderParams);
+	fn render_pdf(&self, render: &mut PdfRender, transform: DAffine2, render_params: &RenderParams);
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]>;
 
 	// The upstream click targets for each layer are collected during the render so that they do not have to be calculated for each click detection
@@ -355,6 +363,25 @@ impl GraphicElementRendered for GraphicGroupTable {
 		}
 	}
 
+	fn render_pdf(&self, render: &mut PdfRender, transform: DAffine2, render_params: &RenderParams) {
+		for instance in self.instances() {
+			let alpha_blending = *instance.alpha_blending;
+			let blend_mode = match render_params.view_mode {
+				ViewMode::Outline => BlendMode::Normal,
+				_ => alpha_blending.blend_mode,
+			};
+
+			let layer = alpha_blending.opacity < 1. || blend_mode != BlendMode::default();
+			if layer {
+				render.push_group();
+			}
+			instance.instance.render_pdf(render, transform * *instance.transform, render_params);
+			if layer {
+				render.pop_group(alpha_blending.opacity, blend_mode);
+			}
+		}
+	}
+
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		self.instances()
 			.filter_map(|element| element.instance.bounding_box(transform * *element.transform))
@@ -613,6 +640,71 @@ impl GraphicElementRendered for VectorDataTable {
 		}
 	}
 
+	fn render_pdf(&self, render: &mut PdfRender, parent_transform: DAffine2, render_params: &RenderParams) {
+		use crate::consts::{LAYER_OUTLINE_STROKE_COLOR, LAYER_OUTLINE_STROKE_WEIGHT};
+
+		for instance in self.instances() {
+			let multiplied_transform = parent_transform * *instance.transform;
+			let has_real_stroke = instance.instance.style.stroke().filter(|stroke| stroke.weight() > 0.);
+			let set_stroke_transform = has_real_stroke.map(|stroke| stroke.transform).filter(|transform| transform.matrix2.determinant() != 0.);
+			let applied_stroke_transform = set_stroke_transform.unwrap_or(multiplied_transform);
+			let element_transform = set_stroke_transform.map(|stroke_transform| multiplied_transform * stroke_transform.inverse());
+			let element_transform = element_transform.unwrap_or(DAffine2::IDENTITY);
+
+			let mut path = PdfPath::default();
+			for subpath in instance.instance.stroke_bezier_paths() {
+				path.push_subpath(&subpath, applied_stroke_transform);
+			}
+
+			// Opacity and blend modes apply to the fill and stroke together, so they need a group
+			let blend_mode = match render_params.view_mode {
+				ViewMode::Outline => BlendMode::Normal,
+				_ => instance.alpha_blending.blend_mode,
+			};
+			let layer = instance.alpha_blending.opacity < 1. || blend_mode != BlendMode::default();
+			if layer {
+				render.push_group();
+			}
+
+			match render_params.view_mode {
+				ViewMode::Outline => {
+					let outline_stroke = Stroke::new(Some(LAYER_OUTLINE_STROKE_COLOR), LAYER_OUTLINE_STROKE_WEIGHT);
+					render.stroke(&path, element_transform, &outline_stroke);
+				}
+				_ => {
+					// Vector data is filled with the nonzero rule, like in the SVG and Vello renderers
+					match instance.instance.style.fill() {
+						Fill::Solid(color) => render.fill(&path, element_transform, *color, PdfFillRule::NonZero),
+						Fill::Gradient(gradient) => {
+							// The gradient points are placed the same way as in the Vello renderer, relative to the parent transform
+							let bounds = instance.instance.nonzero_bounding_box();
+							let bound_transform = DAffine2::from_scale_angle_translation(bounds[1] - bounds[0], 0., bounds[0]);
+
+							let inverse_parent_transform = if parent_transform.matrix2.determinant() != 0. {
+								parent_transform.inverse()
+							} else {
+								DAffine2::IDENTITY
+							};
+							let mod_points = inverse_parent_transform * multiplied_transform * bound_transform;
+
+							let points = [mod_points.transform_point2(gradient.start), mod_points.transform_point2(gradient.end)];
+							render.fill_gradient(&path, element_transform, gradient, points, parent_transform, PdfFillRule::NonZero);
+						}
+						Fill::None => {}
+					}
+
+					if let Some(stroke) = instance.instance.style.stroke() {
+						render.stroke(&path, element_transform, &stroke);
+					}
+				}
+			}
+
+			if layer {
+				render.pop_group(instance.alpha_blending.opacity, blend_mode);
+			}
+		}
+	}
+
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		self.instances()
 			.flat_map(|instance| {
@@ -761,6 +853,25 @@ impl GraphicElementRendered for Artboard {
 		}
 	}
 
+	fn render_pdf(&self, render: &mut PdfRender, transform: DAffine2, render_params: &RenderParams) {
+		let rect = Subpath::<PointId>::new_rect(self.location.as_dvec2(), self.location.as_dvec2() + self.dimensions.as_dvec2());
+		let mut path = PdfPath::default();
+		path.push_subpath(&rect, transform);
+
+		if !render_params.hide_artboards {
+			render.fill(&path, DAffine2::IDENTITY, self.background, PdfFillRule::NonZero);
+		}
+
+		if self.clip {
+			render.push_clip(&path);
+		}
+		let child_transform = transform * DAffine2::from_translation(self.location.as_dvec2());
+		self.graphic_group.render_pdf(render, child_transform, render_params);
+		if self.clip {
+			render.pop_clip();
+		}
+	}
+
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		let artboard_bounds = (transform * Quad::from_box([self.location.as_dvec2(), self.location.as_dvec2() + self.dimensions.as_dvec2()])).bounding_box();
 		if self.clip {
@@ -808,6 +919,27 @@ impl GraphicElementRendered for ArtboardGroupTable {
 		}
 	}
 
+	fn render_pdf(&self, render: &mut PdfRender, transform: DAffine2, render_params: &RenderParams) {
+		for instance in self.instances() {
+			let artboard = instance.instance;
+			if render.in_page() {
+				artboard.render_pdf(render, transform, render_params);
+				continue;
+			}
+
+			// Each artboard gets a page of its own size, unless it's outside of what's being exported
+			let corners = [artboard.location.as_dvec2(), artboard.location.as_dvec2() + artboard.dimensions.as_dvec2()];
+			let bounds = (transform * Quad::from_box(corners)).bounding_box();
+			if !render.in_export_bounds(bounds) {
+				continue;
+			}
+
+			render.begin_page(bounds[1] - bounds[0]);
+			artboard.render_pdf(render, DAffine2::from_translation(-bounds[0]) * transform, render_params);
+			render.end_page();
+		}
+	}
+
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		self.instances().filter_map(|instance| instance.instance.bounding_box(transform)).reduce(Quad::combine_bounds)
 	}
@@ -882,6 +1014,13 @@ impl GraphicElementRendered for ImageFrameTable<Color> {
 		}
 	}
 
+	fn render_pdf(&self, render: &mut PdfRender, transform: DAffine2, _render_params: &RenderParams) {
+		for instance in self.instances() {
+			let alpha_blending = *instance.alpha_blending;
+			render.draw_image(instance.instance, transform * *instance.transform, alpha_blending.opacity, alpha_blending.blend_mode);
+		}
+	}
+
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		self.instances()
 			.flat_map(|instance| {
@@ -964,6 +1103,14 @@ impl GraphicElementRendered for RasterFrame {
 		}
 	}
 
+	fn render_pdf(&self, render: &mut PdfRender, transform: DAffine2, render_params: &RenderParams) {
+		match self {
+			RasterFrame::ImageFrame(image) => image.render_pdf(render, transform, render_params),
+			// GPU textures would first need to be read back to the CPU
+			RasterFrame::TextureFrame(_) => warn!("Texture frames can't be exported to PDF"),
+		}
+	}
+
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		let transform = transform * self.transform();
 		(transform.matrix2.determinant() != 0.).then(|| (transform * Quad::from_box([DVec2::ZERO, DVec2::ONE])).bounding_box())
@@ -1002,6 +1149,14 @@ impl GraphicElementRendered for GraphicElement {
 		}
 	}
 
+	fn render_pdf(&self, render: &mut PdfRender, transform: DAffine2, render_params: &RenderParams) {
+		match self {
+			GraphicElement::VectorData(vector_data) => vector_data.render_pdf(render, transform, render_params),
+			GraphicElement::GraphicGroup(graphic_group) => graphic_group.render_pdf(render, transform, render_params),
+			GraphicElement::RasterFrame(raster) => raster.render_pdf(render, transform, render_params),
+		}
+	}
+
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		match self {
 			GraphicElement::VectorData(vector_data) => vector_data.bounding_box(transform),
@@ -1084,6 +1239,8 @@ impl<P: Primitive> GraphicElementRendered for P {
 
 	#[cfg(feature = "vello")]
 	fn render_to_vello(&self, _scene: &mut Scene, _transform: DAffine2, _context: &mut RenderContext, _render_params: &RenderParams) {}
+
+	fn render_pdf(&self, _render: &mut PdfRender, _transform: DAffine2, _render_params: &RenderParams) {}
 }
 
 impl GraphicElementRendered for Option<Color> {
@@ -1112,6 +1269,8 @@ impl GraphicElementRendered for Option<Color> {
 
 	#[cfg(feature = "vello")]
 	fn render_to_vello(&self, _scene: &mut Scene, _transform: DAffine2, _context: &mut RenderContext, _render_params: &RenderParams) {}
+
+	fn render_pdf(&self, _render: &mut PdfRender, _transform: DAffine2, _render_params: &RenderParams) {}
 }
 
 impl GraphicElementRendered for Vec<Color> {
@@ -1136,6 +1295,8 @@ impl GraphicElementRendered for Vec<Color> {
 
 	#[cfg(feature = "vello")]
 	fn render_to_vello(&self, _scene: &mut Scene, _transform: DAffine2, _context: &mut RenderContext, _render_params: &RenderParams) {}
+
+	fn render_pdf(&self, _render: &mut PdfRender, _transform: DAffine2, _render_params: &RenderParams) {}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -0,0 +1,513 @@
+use super::SvgRender;
+use super::svg_tree::{Element, Node, format_number, number_end, parse, parse_transform};
+use glam::{DAffine2, DVec2};
+use std::fmt::Write;
+
+/// How the changes between the sampled frames of an animated SVG are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SvgAnimationStyle {
+	/// `<animate>` and `<animateTransform>` elements, which can animate every attribute.
+	#[default]
+	Smil,
+	/// CSS keyframes in a `<style>` block for the presentation properties, leaving SMIL for the attributes CSS can't animate.
+	Css,
+}
+
+/// Presentation attributes which CSS can animate as properties of the same name.
+const CSS_PROPERTIES: [&str; 8] = ["opacity", "fill", "fill-opacity", "stroke", "stroke-opacity", "stroke-width", "stop-color", "stop-opacity"];
+
+/// The value an attribute takes when it's omitted, which `SvgRender` does for attributes at their defaults (such as an identity transform).
+fn default_value(attribute: &str) -> Option<&'static str> {
+	match attribute {
+		"transform" | "gradientTransform" => Some(""),
+		"opacity" | "fill-opacity" | "stroke-opacity" | "stop-opacity" | "stroke-width" => Some("1"),
+		"offset" | "stroke-dashoffset" => Some("0"),
+		_ => None,
+	}
+}
+
+/// Separates the numbers in a value from the text around them, which must match for the values to be interpolated.
+fn split_numbers(value: &str) -> (String, Vec<f64>) {
+	let bytes = value.as_bytes();
+	let mut text = String::new();
+	let mut numbers = Vec::new();
+	let mut index = 0;
+	while index < bytes.len() {
+		if let Some(end) = number_end(bytes, index) {
+			match value[index..end].parse() {
+				Ok(number) => {
+					numbers.push(number);
+					text.push('0');
+				}
+				Err(_) => text.push_str(&value[index..end]),
+			}
+			index = end;
+		} else {
+			let character = value[index..].chars().next().unwrap_or_default();
+			text.push(character);
+			index += character.len_utf8();
+		}
+	}
+	(text, numbers)
+}
+
+/// Whether the values can be smoothly interpolated rather than switched between on each frame.
+fn interpolatable(values: &[String]) -> bool {
+	let is_color = |value: &String| value.len() > 1 && value.starts_with('#') && value[1..].chars().all(|c| c.is_ascii_hexdigit());
+	if values.iter().all(is_color) {
+		return true;
+	}
+	// Paint server references can't be interpolated
+	if values.iter().any(|value| value.contains('#')) {
+		return false;
+	}
+
+	let (text, numbers) = split_numbers(&values[0]);
+	!numbers.is_empty() && values[1..].iter().all(|value| split_numbers(value).0 == text)
+}
+
+/// The sequence of commands in path data, which is its topology regardless of where the points are.
+fn path_commands(path: &str) -> impl Iterator<Item = char> + '_ {
+	path.chars().filter(|c| c.is_ascii_alphabetic() && !matches!(c, 'e' | 'E'))
+}
+
+/// Splits a transform into `[translate x, translate y, rotate, skew x, scale x, scale y]` (with angles in degrees), which compose it in that order.
+fn decompose(transform: DAffine2) -> [f64; 6] {
+	let x_axis = transform.matrix2.x_axis;
+	let scale_x = x_axis.length();
+	let angle = if scale_x == 0. { 0. } else { x_axis.y.atan2(x_axis.x) };
+
+	// With the rotation undone, the y axis is left sheared by the skew and scaled (or flipped) by the vertical scale
+	let y_axis = DVec2::from_angle(-angle).rotate(transform.matrix2.y_axis);
+	let skew = if y_axis.y == 0. { 0. } else { (y_axis.x / y_axis.y).atan() };
+
+	[transform.translation.x, transform.translation.y, angle.to_degrees(), skew.to_degrees(), scale_x, y_axis.y]
+}
+
+fn number(value: f64) -> String {
+	format_number(value, 6)
+}
+
+/// Merges the corresponding elements of each frame, collecting the animations of the attributes that change between them.
+struct Animator {
+	style: SvgAnimationStyle,
+	frame_count: usize,
+	duration: String,
+	css: String,
+	classes: usize,
+}
+
+impl Animator {
+	/// The times of each frame as a fraction of the loop, with an extra key at the end for interpolated animations to hold the last frame.
+	fn key_times(&self, linear: bool) -> Vec<String> {
+		let count = if linear { self.frame_count + 1 } else { self.frame_count };
+		(0..count).map(|frame| number(frame as f64 / self.frame_count as f64)).collect()
+	}
+
+	fn smil(&self, name: &str, attributes: &[(&str, &str)], mut values: Vec<String>, linear: bool) -> Node {
+		if linear {
+			values.push(values.last().cloned().unwrap_or_default());
+		}
+		let values = values.join(";");
+		let key_times = self.key_times(linear).join(";");
+
+		let mut animation = Element::new(name, attributes);
+		for (attribute, value) in [
+			("values", values.as_str()),
+			("keyTimes", &key_times),
+			("calcMode", if linear { "linear" } else { "discrete" }),
+			("dur", &self.duration),
+			("repeatCount", "indefinite"),
+		] {
+			animation.attributes.push((attribute.to_string(), value.to_string()));
+		}
+		Node::Element(animation)
+	}
+
+	fn keyframes(&mut self, name: &str, property: &str, mut values: Vec<String>, linear: bool) -> String {
+		values.push(values.last().cloned().unwrap_or_default());
+		let _ = write!(self.css, "@keyframes {name}{{");
+		for (value, time) in values.iter().zip(self.key_times(true)) {
+			let percentage = number(time.parse::<f64>().unwrap_or_default() * 100.);
+			let _ = write!(self.css, "{percentage}%{{{property}:{value}}}");
+		}
+		self.css.push('}');
+
+		let timing = if linear { "linear" } else { "steps(1,end)" };
+		format!("{name} {} {timing} infinite", self.duration)
+	}
+
+	fn merge(&mut self, elements: &[&Element]) -> Option<Element> {
+		let first = elements[0];
+		if elements.iter().any(|element| element.name != first.name || element.children.len() != first.children.len()) {
+			return None;
+		}
+
+		let mut names: Vec<&str> = Vec::new();
+		for (name, _) in elements.iter().flat_map(|element| &element.attributes) {
+			if !names.contains(&name.as_str()) {
+				names.push(name);
+			}
+		}
+
+		let mut merged = Element::new(&first.name, &[]);
+		let mut animations = Vec::new();
+		let mut css_animations = Vec::new();
+		let class = format!("a{}", self.classes);
+
+		for name in names {
+			let values = elements
+				.iter()
+				.map(|element| element.attribute(name).or_else(|| default_value(name)).map(str::to_string))
+				.collect::<Option<Vec<_>>>()?;
+			if let Some(value) = first.attribute(name) {
+				merged.attributes.push((name.to_string(), value.to_string()));
+			}
+			if values.iter().all(|value| *value == values[0]) {
+				continue;
+			}
+
+			match name {
+				"id" | "class" | "style" => return None,
+				"transform" => {
+					let mut components = values.iter().map(|value| parse_transform(value).map(decompose)).collect::<Option<Vec<_>>>()?;
+					// Keep each rotation within half a turn of the previous one so it doesn't spin the long way around
+					for frame in 1..components.len() {
+						let previous = components[frame - 1][2];
+						components[frame][2] -= ((components[frame][2] - previous) / 360.).round() * 360.;
+					}
+
+					if self.style == SvgAnimationStyle::Css {
+						let values = components
+							.iter()
+							.map(|[x, y, angle, skew, scale_x, scale_y]| {
+								let [x, y, angle, skew, scale_x, scale_y] = [x, y, angle, skew, scale_x, scale_y].map(|value| number(*value));
+								format!("translate({x}px,{y}px) rotate({angle}deg) skewX({skew}deg) scale({scale_x},{scale_y})")
+							})
+							.collect();
+						css_animations.push(self.keyframes(&format!("{class}-transform"), "transform", values, true));
+						continue;
+					}
+
+					// The first transform animation replaces the static transform and the others are applied after it
+					let mut additive = false;
+					for (kind, range, identity) in [("translate", 0..2, 0.), ("rotate", 2..3, 0.), ("skewX", 3..4, 0.), ("scale", 4..6, 1.)] {
+						if components.iter().all(|component| component[range.clone()].iter().all(|&value| value == identity)) {
+							continue;
+						}
+						let values = components
+							.iter()
+							.map(|component| component[range.clone()].iter().map(|&value| number(value)).collect::<Vec<_>>().join(" "))
+							.collect();
+						let mut attributes = vec![("attributeName", "transform"), ("type", kind)];
+						if additive {
+							attributes.push(("additive", "sum"));
+						}
+						additive = true;
+						animations.push(self.smil("animateTransform", &attributes, values, true));
+					}
+				}
+				_ => {
+					if name == "d" && values[1..].iter().any(|path| !path_commands(path).eq(path_commands(&values[0]))) {
+						return None;
+					}
+
+					let linear = interpolatable(&values);
+					if self.style == SvgAnimationStyle::Css && CSS_PROPERTIES.contains(&name) {
+						css_animations.push(self.keyframes(&format!("{class}-{name}"), name, values, linear));
+					} else {
+						animations.push(self.smil("animate", &[("attributeName", name)], values, linear));
+					}
+				}
+			}
+		}
+
+		if !css_animations.is_empty() {
+			self.classes += 1;
+			let _ = write!(self.css, ".{class}{{animation:{};transform-origin:0 0}}", css_animations.join(","));
+			merged.attributes.push(("class".to_string(), class));
+		}
+
+		merged.children = animations;
+		for index in 0..first.children.len() {
+			let child = match &first.children[index] {
+				Node::Element(_) => {
+					let children = elements
+						.iter()
+						.map(|element| match &element.children[index] {
+							Node::Element(child) => Some(child),
+							Node::Text(_) => None,
+						})
+						.collect::<Option<Vec<_>>>()?;
+					Node::Element(self.merge(&children)?)
+				}
+				text => {
+					if elements.iter().any(|element| element.children[index] != *text) {
+						return None;
+					}
+					text.clone()
+				}
+			};
+			merged.children.push(child);
+		}
+
+		Some(merged)
+	}
+
+	/// Stacks the content of every frame in its own group, each shown only for the duration of its frame.
+	fn flipbook(&mut self, frames: Vec<Element>) -> Element {
+		let mut root = Element {
+			children: Vec::new(),
+			..frames[0].clone()
+		};
+		let frame_count = self.frame_count;
+		let key_time = |frame: usize| number(frame as f64 / frame_count as f64);
+
+		if self.style == SvgAnimationStyle::Css {
+			let _ = write!(self.css, "@keyframes flip{{0%{{visibility:visible}}{}%{{visibility:hidden}}}}", number(100. / frame_count as f64));
+		}
+
+		for (frame, content) in frames.into_iter().enumerate() {
+			let mut group = Element::new("g", &[]);
+			match self.style {
+				SvgAnimationStyle::Smil => {
+					let (values, key_times) = match frame {
+						0 => ("inline;none".to_string(), format!("0;{}", key_time(1))),
+						_ if frame + 1 == frame_count => ("none;inline".to_string(), format!("0;{}", key_time(frame))),
+						_ => ("none;inline;none".to_string(), format!("0;{};{}", key_time(frame), key_time(frame + 1))),
+					};
+					if frame > 0 {
+						group.attributes.push(("display".to_string(), "none".to_string()));
+					}
+					let values = Node::Element(Element::new(
+						"animate",
+						&[
+							("attributeName", "display"),
+							("values", &values),
+							("keyTimes", &key_times),
+							("calcMode", "discrete"),
+							("dur", &self.duration),
+							("repeatCount", "indefinite"),
+						],
+					));
+					group.children.push(values);
+				}
+				SvgAnimationStyle::Css => {
+					// Starting each frame's animation partway through lines its visible part up with the frame's time in the loop
+					let delay = if frame == 0 {
+						"0s".to_string()
+					} else {
+						format!("-{}s", number(self.seconds() * (frame_count - frame) as f64 / frame_count as f64))
+					};
+					if frame > 0 {
+						group.attributes.push(("visibility".to_string(), "hidden".to_string()));
+					}
+					group.attributes.push(("style".to_string(), format!("animation:flip {} steps(1,end) {delay} infinite", self.duration)));
+				}
+			}
+			group.children.extend(content.children);
+			root.children.push(Node::Element(group));
+		}
+
+		root
+	}
+
+	fn seconds(&self) -> f64 {
+		self.duration.trim_end_matches('s').parse().unwrap_or_default()
+	}
+}
+
+impl SvgRender {
+	/// Combines the SVG renders of consecutive animation frames, sampled at the given frame rate, into a single SVG which loops through them.
+	///
+	/// Attributes which change between frames (like transforms, opacity and fills) are animated, interpolating between the frames where their values allow.
+	/// When the frames don't share the same elements or path topology, every frame is kept and shown in turn like a flipbook instead.
+	pub fn animate_frames(frames: &[String], frame_rate: f64, style: SvgAnimationStyle) -> Result<String, String> {
+		let frames = frames.iter().map(|frame| parse(frame)).collect::<Option<Vec<_>>>().ok_or("Unable to read the rendered frames")?;
+		if frames.is_empty() {
+			return Err("There are no frames to animate".to_string());
+		}
+
+		let mut animator = Animator {
+			style,
+			frame_count: frames.len(),
+			duration: format!("{}s", number(frames.len() as f64 / frame_rate)),
+			css: String::new(),
+			classes: 0,
+		};
+
+		// IDs such as those of gradients are generated anew for every render, so they're matched up by the order they appear in
+		let ids = frames
+			.iter()
+			.map(|frame| {
+				let mut ids = Vec::new();
+				frame.ids(&mut ids);
+				ids
+			})
+			.collect::<Vec<_>>();
+		let renamed_frames = |rename: &dyn Fn(usize, usize) -> String| {
+			let mut frames = frames.clone();
+			for (frame, element) in frames.iter_mut().enumerate().skip(1) {
+				let renamed = ids[frame].iter().enumerate().map(|(index, id)| (id.clone(), rename(frame, index))).collect();
+				element.rename_ids(&renamed);
+			}
+			frames
+		};
+
+		let merged = ids.iter().all(|frame_ids| frame_ids.len() == ids[0].len()).then(|| {
+			let frames = renamed_frames(&|_, index| ids[0][index].clone());
+			animator.merge(&frames.iter().collect::<Vec<_>>())
+		});
+		let mut root = match merged.flatten() {
+			Some(root) => root,
+			None => {
+				animator.css.clear();
+				animator.classes = 0;
+				// Every frame is kept in the flipbook, so their IDs need to be kept apart
+				animator.flipbook(renamed_frames(&|frame, index| format!("{}-{frame}", ids[frame][index])))
+			}
+		};
+
+		if !animator.css.is_empty() {
+			let mut style = Element::new("style", &[]);
+			style.children.push(Node::Text(animator.css));
+			root.children.insert(0, Node::Element(style));
+		}
+
+		let mut svg = String::new();
+		root.write(&mut svg);
+		Ok(svg)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn frames(frame: impl Fn(usize) -> String, count: usize) -> Vec<String> {
+		(0..count).map(frame).collect()
+	}
+
+	#[test]
+	fn parse_and_write() {
+		let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><defs></defs>
+	<g opacity="0.5">
+		<path d="M0,0 L10,10 Z" fill="#ff0000" />
+		<text>Text</text>
+	</g></svg>"##;
+		let animated = SvgRender::animate_frames(&[svg.to_string()], 24., SvgAnimationStyle::Smil).unwrap();
+		assert_eq!(
+			animated,
+			r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><defs/><g opacity="0.5"><path d="M0,0 L10,10 Z" fill="#ff0000"/><text>Text</text></g></svg>"##
+		);
+	}
+
+	#[test]
+	fn decompose_transform() {
+		let transform = DAffine2::from_translation(DVec2::new(3., -4.)) * DAffine2::from_angle(2.) * DAffine2::from_cols_array(&[1., 0., 0.5, 1., 0., 0.]) * DAffine2::from_scale(DVec2::new(2., -3.));
+		let [x, y, angle, skew, scale_x, scale_y] = decompose(transform);
+		let recomposed = parse_transform(&format!("translate({x},{y}) rotate({angle}) skewX({skew}) scale({scale_x},{scale_y})")).unwrap();
+		assert!(recomposed.abs_diff_eq(transform, 1e-9));
+		assert!((skew - 0.5_f64.atan().to_degrees()).abs() < 1e-9);
+	}
+
+	#[test]
+	fn animated_attributes() {
+		let frames = frames(
+			|frame| {
+				let transform = if frame == 0 { String::new() } else { format!(r#" transform="matrix(1,0,0,1,{},0)""#, frame * 5) };
+				let opacity = if frame == 2 { String::new() } else { format!(r#" opacity="0.{}""#, frame + 5) };
+				format!(
+					r##"<svg viewBox="0 0 10 10"><rect width="1" height="1"{transform}{opacity} fill="#{}"/></svg>"##,
+					["ff0000", "00ff00", "0000ff"][frame]
+				)
+			},
+			3,
+		);
+		let animated = SvgRender::animate_frames(&frames, 2., SvgAnimationStyle::Smil).unwrap();
+
+		assert!(animated.starts_with(r##"<svg viewBox="0 0 10 10"><rect width="1" height="1" opacity="0.5" fill="#ff0000">"##));
+		assert!(animated.contains(
+			r#"<animateTransform attributeName="transform" type="translate" values="0 0;5 0;10 0;10 0" keyTimes="0;0.333333;0.666667;1" calcMode="linear" dur="1.5s" repeatCount="indefinite"/>"#
+		));
+		assert!(!animated.contains(r#"type="rotate""#));
+		assert!(animated.contains(r#"<animate attributeName="opacity" values="0.5;0.6;1;1""#));
+		assert!(animated.contains(r##"<animate attributeName="fill" values="#ff0000;#00ff00;#0000ff;#0000ff" keyTimes="0;0.333333;0.666667;1" calcMode="linear""##));
+		assert!(!animated.contains("<g"));
+	}
+
+	#[test]
+	fn rotation_takes_the_short_way() {
+		let frames = frames(|frame| format!(r#"<svg><rect transform="rotate({})"/></svg>"#, [170., -170., -150.][frame]), 3);
+		let animated = SvgRender::animate_frames(&frames, 24., SvgAnimationStyle::Smil).unwrap();
+		assert!(animated.contains(r#"<animateTransform attributeName="transform" type="rotate" values="170;190;210;210""#));
+		assert!(!animated.contains(r#"type="translate""#));
+	}
+
+	#[test]
+	fn regenerated_ids_are_matched() {
+		let frames = frames(
+			|frame| {
+				let id = [1234, 98765][frame];
+				format!(r#"<svg><defs><linearGradient id="{id}" x1="{frame}"/></defs><path d="M0,0 L1,1" fill="url(#{id})"/></svg>"#)
+			},
+			2,
+		);
+		let animated = SvgRender::animate_frames(&frames, 24., SvgAnimationStyle::Smil).unwrap();
+		assert!(animated.contains(r#"<linearGradient id="1234" x1="0"><animate attributeName="x1" values="0;1;1""#));
+		assert!(animated.contains(r#"<path d="M0,0 L1,1" fill="url(#1234)"/>"#));
+		assert!(!animated.contains("98765"));
+	}
+
+	#[test]
+	fn discrete_paint_changes() {
+		let frames = frames(|frame| format!(r#"<svg><path d="M0,0 L1,1" fill="{}"/></svg>"#, ["#ff0000", "url(#1)"][frame]), 2);
+		let animated = SvgRender::animate_frames(&frames, 24., SvgAnimationStyle::Smil).unwrap();
+		assert!(animated.contains(r##"<animate attributeName="fill" values="#ff0000;url(#1)" keyTimes="0;0.5" calcMode="discrete""##));
+	}
+
+	#[test]
+	fn topology_change_flipbook() {
+		let frames = frames(
+			|frame| {
+				format!(
+					r#"<svg viewBox="0 0 2 2"><defs><linearGradient id="1"/></defs><path d="M0,0 L1,1{}" fill="url(#1)"/></svg>"#,
+					" L2,2".repeat(frame)
+				)
+			},
+			3,
+		);
+		let animated = SvgRender::animate_frames(&frames, 2., SvgAnimationStyle::Smil).unwrap();
+
+		assert!(animated.starts_with(r#"<svg viewBox="0 0 2 2"><g><animate attributeName="display" values="inline;none" keyTimes="0;0.333333" calcMode="discrete" dur="1.5s""#));
+		assert!(animated.contains(r#"<g display="none"><animate attributeName="display" values="none;inline;none" keyTimes="0;0.333333;0.666667""#));
+		assert!(animated.contains(r#"<g display="none"><animate attributeName="display" values="none;inline" keyTimes="0;0.666667""#));
+		assert!(animated.contains(r#"<linearGradient id="1-2"/></defs><path d="M0,0 L1,1 L2,2 L2,2" fill="url(#1-2)"/>"#));
+		assert_eq!(animated.matches("<path").count(), 3);
+	}
+
+	#[test]
+	fn css_keyframes() {
+		let frames = frames(
+			|frame| format!(r#"<svg><rect x="{frame}" opacity="{}" transform="translate({frame})"/></svg>"#, 1. - frame as f64 / 2.),
+			2,
+		);
+		let animated = SvgRender::animate_frames(&frames, 1., SvgAnimationStyle::Css).unwrap();
+
+		assert!(animated.contains("@keyframes a0-opacity{0%{opacity:1}50%{opacity:0.5}100%{opacity:0.5}}"));
+		assert!(animated.contains("@keyframes a0-transform{0%{transform:translate(0px,0px) rotate(0deg) skewX(0deg) scale(1,1)}50%{transform:translate(1px,0px)"));
+		assert!(animated.contains(".a0{animation:a0-opacity 2s linear infinite,a0-transform 2s linear infinite;transform-origin:0 0}"));
+		// CSS can't animate the position of a rectangle, so it's left to SMIL
+		assert!(animated.contains(r#"<rect x="0" opacity="1" transform="translate(0)" class="a0"><animate attributeName="x" values="0;1;1""#));
+	}
+
+	#[test]
+	fn css_flipbook() {
+		let frames = frames(|frame| format!(r#"<svg><path d="M0,0{}"/></svg>"#, " Z".repeat(frame)), 4);
+		let animated = SvgRender::animate_frames(&frames, 2., SvgAnimationStyle::Css).unwrap();
+
+		assert!(animated.starts_with("<svg><style>@keyframes flip{0%{visibility:visible}25%{visibility:hidden}}</style>"));
+		assert!(animated.contains(r#"<g style="animation:flip 2s steps(1,end) 0s infinite"><path d="M0,0"/></g>"#));
+		assert!(animated.contains(r#"<g visibility="hidden" style="animation:flip 2s steps(1,end) -0.5s infinite"><path d="M0,0 Z Z Z"/></g>"#));
+	}
+}
@@ -0,0 +1,601 @@
+use crate::Color;
+use crate::raster::BlendMode;
+use crate::raster::image::Image;
+use crate::vector::style::{Gradient, GradientType, LineCap, LineJoin, Stroke};
+use bezier_rs::{BezierHandles, Identifier, Subpath};
+use glam::{DAffine2, DVec2};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Write};
+
+/// The size of a CSS pixel in PDF points, so a document exported at a scale factor of 1 prints at the same physical size as its SVG export.
+const POINTS_PER_PIXEL: f64 = 0.75;
+
+/// The page tree and catalog objects are reserved up front since they can only be written once every page is known.
+const PAGES_OBJECT: usize = 1;
+const CATALOG_OBJECT: usize = 2;
+
+/// Formats a number the way PDF expects, which doesn't allow exponents and doesn't need trailing zeros.
+struct Number(f64);
+
+impl fmt::Display for Number {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let value = if self.0.is_finite() { (self.0 * 10_000.).round() / 10_000. } else { 0. };
+		if value == 0. {
+			return f.write_str("0");
+		}
+
+		let formatted = format!("{value:.4}");
+		f.write_str(formatted.trim_end_matches('0').trim_end_matches('.'))
+	}
+}
+
+fn write_matrix(stream: &mut String, transform: DAffine2) {
+	if transform == DAffine2::IDENTITY {
+		return;
+	}
+	let [a, b, c, d, e, f] = transform.to_cols_array().map(Number);
+	let _ = writeln!(stream, "{a} {b} {c} {d} {e} {f} cm");
+}
+
+/// The path construction operators of one or more subpaths, which are painted by the methods of [`PdfRender`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PdfPath(String);
+
+impl PdfPath {
+	/// Appends a subpath with its points transformed by `transform`.
+	pub fn push_subpath<PointId: Identifier>(&mut self, subpath: &Subpath<PointId>, transform: DAffine2) {
+		let Some(first) = subpath.manipulator_groups().first() else { return };
+		let point = |point: DVec2| {
+			let point = transform.transform_point2(point);
+			format!("{} {}", Number(point.x), Number(point.y))
+		};
+
+		let _ = writeln!(self.0, "{} m", point(first.anchor));
+		for segment in subpath.iter() {
+			let _ = match segment.handles {
+				BezierHandles::Linear => writeln!(self.0, "{} l", point(segment.end)),
+				BezierHandles::Quadratic { handle } => {
+					// PDF only has cubic curves, so the quadratic is degree elevated
+					let handle_start = segment.start + (handle - segment.start) * (2. / 3.);
+					let handle_end = segment.end + (handle - segment.end) * (2. / 3.);
+					writeln!(self.0, "{} {} {} c", point(handle_start), point(handle_end), point(segment.end))
+				}
+				BezierHandles::Cubic { handle_start, handle_end } => writeln!(self.0, "{} {} {} c", point(handle_start), point(handle_end), point(segment.end)),
+			};
+		}
+		if subpath.closed() {
+			self.0.push_str("h\n");
+		}
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+}
+
+/// How the inside of a path is found where its subpaths overlap or cross themselves, like the SVG `fill-rule` property.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PdfFillRule {
+	#[default]
+	NonZero,
+	EvenOdd,
+}
+
+impl PdfFillRule {
+	fn fill_operator(self) -> &'static str {
+		match self {
+			Self::NonZero => "f",
+			Self::EvenOdd => "f*",
+		}
+	}
+
+	fn clip_operator(self) -> &'static str {
+		match self {
+			Self::NonZero => "W",
+			Self::EvenOdd => "W*",
+		}
+	}
+}
+
+/// A content stream along with the resources it uses, named after their object numbers.
+#[derive(Debug, Default)]
+struct PdfContent {
+	stream: String,
+	graphics_states: BTreeSet<usize>,
+	x_objects: BTreeSet<usize>,
+	shadings: BTreeSet<usize>,
+}
+
+impl PdfContent {
+	fn resources(&self) -> String {
+		let mut resources = String::from("<<");
+		for (category, prefix, objects) in [("ExtGState", 'G', &self.graphics_states), ("XObject", 'X', &self.x_objects), ("Shading", 'S', &self.shadings)] {
+			if objects.is_empty() {
+				continue;
+			}
+			let _ = write!(resources, " /{category} <<");
+			for object in objects {
+				let _ = write!(resources, " /{prefix}{object} {object} 0 R");
+			}
+			resources.push_str(" >>");
+		}
+		resources.push_str(" >>");
+		resources
+	}
+
+	fn set_graphics_state(&mut self, graphics_state: Option<usize>) {
+		if let Some(graphics_state) = graphics_state {
+			self.graphics_states.insert(graphics_state);
+			let _ = writeln!(self.stream, "/G{graphics_state} gs");
+		}
+	}
+}
+
+/// Writes a PDF document with the artwork rendered by [`GraphicElementRendered::render_pdf`](super::GraphicElementRendered::render_pdf).
+///
+/// Coordinates are given in pixels with the y axis pointing down, like the other renderers, and every page has its own transparency group stack.
+/// Drawing outside of a page is ignored.
+#[derive(Debug)]
+pub struct PdfRender {
+	/// The bodies of the indirect objects written so far, where the object number of each is its index plus one.
+	objects: Vec<Vec<u8>>,
+	page_objects: Vec<usize>,
+	/// The size of the current page in pixels, if a page has been started.
+	page_size: Option<DVec2>,
+	/// The content of the current page, followed by the content of each transparency group that is being drawn into it.
+	contents: Vec<PdfContent>,
+	/// Graphics state dictionaries are shared, since most elements which use one have the same opacity and blend mode.
+	graphics_states: HashMap<String, usize>,
+	/// The region, in the coordinates given to [`PdfRender::begin_page`], outside of which artboards don't get a page of their own.
+	export_bounds: Option<[DVec2; 2]>,
+}
+
+impl Default for PdfRender {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl PdfRender {
+	pub fn new() -> Self {
+		Self {
+			objects: vec![Vec::new(); CATALOG_OBJECT],
+			page_objects: Vec::new(),
+			page_size: None,
+			contents: Vec::new(),
+			graphics_states: HashMap::new(),
+			export_bounds: None,
+		}
+	}
+
+	pub fn set_export_bounds(&mut self, bounds: [DVec2; 2]) {
+		self.export_bounds = Some(bounds);
+	}
+
+	/// Whether an artboard with these bounds overlaps the exported region, and so should get a page.
+	pub fn in_export_bounds(&self, bounds: [DVec2; 2]) -> bool {
+		self.export_bounds
+			.is_none_or(|export_bounds| bounds[0].cmplt(export_bounds[1]).all() && bounds[1].cmpgt(export_bounds[0]).all())
+	}
+
+	pub fn in_page(&self) -> bool {
+		self.page_size.is_some()
+	}
+
+	pub fn page_count(&self) -> usize {
+		self.page_objects.len() + self.in_page() as usize
+	}
+
+	/// Starts a new page of the given size in pixels, ending the current one.
+	pub fn begin_page(&mut self, size: DVec2) {
+		self.end_page();
+		self.page_size = Some(size.max(DVec2::ONE));
+		self.contents = vec![PdfContent::default()];
+	}
+
+	pub fn end_page(&mut self) {
+		let Some(size) = self.page_size else { return };
+		while self.contents.len() > 1 {
+			self.pop_group(1., BlendMode::Normal);
+		}
+		self.page_size = None;
+		let content = self.contents.pop().unwrap_or_default();
+
+		let page_points = size * POINTS_PER_PIXEL;
+		let mut stream = String::new();
+		write_matrix(&mut stream, DAffine2::from_cols_array(&[POINTS_PER_PIXEL, 0., 0., -POINTS_PER_PIXEL, 0., page_points.y]));
+		stream.push_str(&content.stream);
+		let contents = self.add_stream("", stream.as_bytes());
+
+		let page = format!(
+			"<< /Type /Page /Parent {PAGES_OBJECT} 0 R /MediaBox [0 0 {} {}] /Group << /S /Transparency /CS /DeviceRGB >> /Resources {} /Contents {contents} 0 R >>",
+			Number(page_points.x),
+			Number(page_points.y),
+			content.resources()
+		);
+		let page = self.add_object(page.into_bytes());
+		self.page_objects.push(page);
+	}
+
+	/// Starts a transparency group, so the elements drawn until [`PdfRender::pop_group`] are composited together.
+	pub fn push_group(&mut self) {
+		if self.in_page() {
+			self.contents.push(PdfContent::default());
+		}
+	}
+
+	pub fn pop_group(&mut self, opacity: f32, blend_mode: BlendMode) {
+		if self.contents.len() <= 1 {
+			return;
+		}
+		let Some(content) = self.contents.pop() else { return };
+
+		let form = self.add_form(&content, "DeviceRGB");
+		let graphics_state = self.graphics_state(opacity, opacity, blend_mode);
+		let Some(parent) = self.contents.last_mut() else { return };
+
+		parent.stream.push_str("q\n");
+		parent.set_graphics_state(graphics_state);
+		parent.x_objects.insert(form);
+		let _ = writeln!(parent.stream, "/X{form} Do\nQ");
+	}
+
+	/// Clips everything drawn until the matching [`PdfRender::pop_clip`] to the path, which is in page coordinates.
+	pub fn push_clip(&mut self, path: &PdfPath) {
+		let Some(content) = self.content() else { return };
+		let _ = write!(content.stream, "q\n{}W n\n", path.0);
+	}
+
+	pub fn pop_clip(&mut self) {
+		let Some(content) = self.content() else { return };
+		content.stream.push_str("Q\n");
+	}
+
+	/// Fills the path, with `transform` mapping its coordinates to the page.
+	pub fn fill(&mut self, path: &PdfPath, transform: DAffine2, color: Color, fill_rule: PdfFillRule) {
+		if path.is_empty() || color.a() == 0. {
+			return;
+		}
+		let graphics_state = self.graphics_state(color.a(), 1., BlendMode::Normal);
+		let Some(content) = self.content() else { return };
+
+		content.stream.push_str("q\n");
+		content.set_graphics_state(graphics_state);
+		write_matrix(&mut content.stream, transform);
+		let _ = write!(
+			content.stream,
+			"{} {} {} rg\n{}{}\nQ\n",
+			Number(color.r() as f64),
+			Number(color.g() as f64),
+			Number(color.b() as f64),
+			path.0,
+			fill_rule.fill_operator()
+		);
+	}
+
+	/// Fills the path with a gradient, where `transform` maps the path coordinates and `gradient_transform` maps the `start` and `end` points to the page.
+	///
+	/// The transparency of the gradient stops is applied with a soft mask, since shadings themselves are opaque.
+	pub fn fill_gradient(&mut self, path: &PdfPath, transform: DAffine2, gradient: &Gradient, [start, end]: [DVec2; 2], gradient_transform: DAffine2, fill_rule: PdfFillRule) {
+		if path.is_empty() || !self.in_page() || transform.matrix2.determinant() == 0. {
+			return;
+		}
+
+		let Some(shading) = self.add_shading(gradient, start, end, "DeviceRGB", |color| vec![color.r(), color.g(), color.b()]) else {
+			return;
+		};
+		let paint = |shading: usize| {
+			let mut paint = String::new();
+			write_matrix(&mut paint, transform);
+			let _ = writeln!(paint, "{}{} n", path.0, fill_rule.clip_operator());
+			write_matrix(&mut paint, transform.inverse() * gradient_transform);
+			let _ = writeln!(paint, "/S{shading} sh");
+			paint
+		};
+
+		let mut soft_mask = None;
+		if gradient.stops.iter().any(|(_, color)| color.a() < 1.) {
+			let Some(alpha_shading) = self.add_shading(gradient, start, end, "DeviceGray", |color| vec![color.a()]) else {
+				return;
+			};
+			let mask = PdfContent {
+				stream: paint(alpha_shading),
+				shadings: BTreeSet::from([alpha_shading]),
+				..Default::default()
+			};
+			let mask = self.add_form(&mask, "DeviceGray");
+			soft_mask = Some(self.add_object(format!("<< /Type /ExtGState /SMask << /Type /Mask /S /Luminosity /G {mask} 0 R >> >>").into_bytes()));
+		}
+
+		let Some(content) = self.content() else { return };
+		content.stream.push_str("q\n");
+		content.set_graphics_state(soft_mask);
+		content.shadings.insert(shading);
+		content.stream.push_str(&paint(shading));
+		content.stream.push_str("Q\n");
+	}
+
+	/// Strokes the path, with `transform` mapping its coordinates to the page and scaling the stroke along with them.
+	pub fn stroke(&mut self, path: &PdfPath, transform: DAffine2, stroke: &Stroke) {
+		let Some(color) = stroke.color else { return };
+		if path.is_empty() || stroke.weight <= 0. || color.a() == 0. {
+			return;
+		}
+		let graphics_state = self.graphics_state(1., color.a(), BlendMode::Normal);
+		let Some(content) = self.content() else { return };
+
+		content.stream.push_str("q\n");
+		content.set_graphics_state(graphics_state);
+		write_matrix(&mut content.stream, transform);
+
+		let line_cap = match stroke.line_cap {
+			LineCap::Butt => 0,
+			LineCap::Round => 1,
+			LineCap::Square => 2,
+		};
+		let line_join = match stroke.line_join {
+			LineJoin::Miter => 0,
+			LineJoin::Round => 1,
+			LineJoin::Bevel => 2,
+		};
+		let _ = writeln!(
+			content.stream,
+			"{} {} {} RG {} w {line_cap} J {line_join} j {} M",
+			Number(color.r() as f64),
+			Number(color.g() as f64),
+			Number(color.b() as f64),
+			Number(stroke.weight),
+			Number(stroke.line_join_miter_limit.max(1.)),
+		);
+
+		// A dash pattern without any length would never advance, which PDF readers reject
+		if stroke.dash_lengths.iter().all(|&length| length >= 0.) && stroke.dash_lengths.iter().sum::<f64>() > 0. {
+			let dash_lengths = stroke.dash_lengths.iter().map(|&length| Number(length).to_string()).collect::<Vec<_>>().join(" ");
+			let _ = writeln!(content.stream, "[{dash_lengths}] {} d", Number(stroke.dash_offset));
+		}
+
+		let _ = write!(content.stream, "{}S\nQ\n", path.0);
+	}
+
+	/// Draws an image into the unit square, which `transform` maps to the page.
+	pub fn draw_image(&mut self, image: &Image<Color>, transform: DAffine2, opacity: f32, blend_mode: BlendMode) {
+		if image.data.is_empty() || !self.in_page() {
+			return;
+		}
+
+		let (data, width, height) = image.to_flat_u8();
+		let color: Vec<u8> = data.chunks_exact(4).flat_map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect();
+		let alpha: Vec<u8> = data.chunks_exact(4).map(|pixel| pixel[3]).collect();
+
+		let mut dictionary = format!("/Type /XObject /Subtype /Image /Width {width} /Height {height} /BitsPerComponent 8");
+		if alpha.iter().any(|&alpha| alpha < u8::MAX) {
+			let soft_mask = self.add_stream(&format!("{dictionary} /ColorSpace /DeviceGray"), &alpha);
+			let _ = write!(dictionary, " /SMask {soft_mask} 0 R");
+		}
+		let x_object = self.add_stream(&format!("{dictionary} /ColorSpace /DeviceRGB"), &color);
+
+		let graphics_state = self.graphics_state(opacity, opacity, blend_mode);
+		let Some(content) = self.content() else { return };
+
+		content.stream.push_str("q\n");
+		content.set_graphics_state(graphics_state);
+		// PDF draws the first row of an image at the top of the unit square, where y is 1 rather than 0
+		write_matrix(&mut content.stream, transform * DAffine2::from_cols_array(&[1., 0., 0., -1., 0., 1.]));
+		content.x_objects.insert(x_object);
+		let _ = writeln!(content.stream, "/X{x_object} Do\nQ");
+	}
+
+	/// Completes the document, ending the current page. A page is added if none was drawn, since a PDF needs at least one.
+	pub fn finish(mut self) -> Vec<u8> {
+		self.end_page();
+		if self.page_objects.is_empty() {
+			self.begin_page(DVec2::ONE);
+			self.end_page();
+		}
+
+		let kids = self.page_objects.iter().map(|page| format!("{page} 0 R")).collect::<Vec<_>>().join(" ");
+		self.objects[PAGES_OBJECT - 1] = format!("<< /Type /Pages /Kids [{kids}] /Count {} >>", self.page_objects.len()).into_bytes();
+		self.objects[CATALOG_OBJECT - 1] = format!("<< /Type /Catalog /Pages {PAGES_OBJECT} 0 R >>").into_bytes();
+
+		// The comment with high bytes marks the file as binary for tools which would otherwise treat it as text
+		let mut output = b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n".to_vec();
+		let mut offsets = Vec::with_capacity(self.objects.len());
+		for (index, object) in self.objects.iter().enumerate() {
+			offsets.push(output.len());
+			output.extend(format!("{} 0 obj\n", index + 1).bytes());
+			output.extend(object);
+			output.extend(b"\nendobj\n");
+		}
+
+		let cross_reference_offset = output.len();
+		let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", self.objects.len() + 1);
+		for offset in offsets {
+			let _ = writeln!(trailer, "{offset:010} 00000 n ");
+		}
+		let _ = write!(
+			trailer,
+			"trailer\n<< /Size {} /Root {CATALOG_OBJECT} 0 R >>\nstartxref\n{cross_reference_offset}\n%%EOF\n",
+			self.objects.len() + 1
+		);
+		output.extend(trailer.bytes());
+		output
+	}
+
+	fn content(&mut self) -> Option<&mut PdfContent> {
+		self.page_size?;
+		self.contents.last_mut()
+	}
+
+	fn add_object(&mut self, object: Vec<u8>) -> usize {
+		self.objects.push(object);
+		self.objects.len()
+	}
+
+	fn add_stream(&mut self, dictionary: &str, data: &[u8]) -> usize {
+		let compressed = miniz_oxide::deflate::compress_to_vec_zlib(data, 6);
+		let separator = if dictionary.is_empty() { "" } else { " " };
+		let mut object = format!("<< {dictionary}{separator}/Filter /FlateDecode /Length {} >>\nstream\n", compressed.len()).into_bytes();
+		object.extend(compressed);
+		object.extend(b"\nendstream");
+		self.add_object(object)
+	}
+
+	/// Adds a form XObject covering the page, which is drawn as a transparency group since that's how it composites with what's below it.
+	fn add_form(&mut self, content: &PdfContent, color_space: &str) -> usize {
+		let size = self.page_size.unwrap_or(DVec2::ONE);
+		let dictionary = format!(
+			"/Type /XObject /Subtype /Form /BBox [0 0 {} {}] /Group << /S /Transparency /CS /{color_space} >> /Resources {}",
+			Number(size.x),
+			Number(size.y),
+			content.resources()
+		);
+		self.add_stream(&dictionary, content.stream.as_bytes())
+	}
+
+	fn graphics_state(&mut self, fill_alpha: f32, stroke_alpha: f32, blend_mode: BlendMode) -> Option<usize> {
+		if fill_alpha >= 1. && stroke_alpha >= 1. && blend_mode == BlendMode::Normal {
+			return None;
+		}
+
+		let blend_mode = blend_mode.to_pdf_name().unwrap_or_else(|| {
+			warn!("Unsupported blend mode {blend_mode:?}");
+			"Normal"
+		});
+		let dictionary = format!(
+			"<< /Type /ExtGState /ca {} /CA {} /BM /{blend_mode} >>",
+			Number(fill_alpha.clamp(0., 1.) as f64),
+			Number(stroke_alpha.clamp(0., 1.) as f64)
+		);
+
+		if let Some(&object) = self.graphics_states.get(&dictionary) {
+			return Some(object);
+		}
+		let object = self.add_object(dictionary.clone().into_bytes());
+		self.graphics_states.insert(dictionary, object);
+		Some(object)
+	}
+
+	/// Adds an axial or radial shading which pads the gradient beyond its ends, using the given components of each stop color.
+	fn add_shading(&mut self, gradient: &Gradient, start: DVec2, end: DVec2, color_space: &str, components: impl Fn(Color) -> Vec<f32>) -> Option<usize> {
+		let mut stops = Vec::new();
+		let mut last_position = 0_f64;
+		for &(position, color) in gradient.stops.iter() {
+			last_position = position.clamp(last_position, 1.);
+			stops.push((last_position, components(color)));
+		}
+		let first = stops.first()?.clone();
+		let last = stops.last()?.clone();
+		if first.0 > 0. {
+			stops.insert(0, (0., first.1));
+		}
+		if last.0 < 1. || stops.len() == 1 {
+			stops.push((1., last.1));
+		}
+
+		let components = |values: &[f32]| values.iter().map(|&value| Number(value as f64).to_string()).collect::<Vec<_>>().join(" ");
+		let mut functions = stops
+			.windows(2)
+			.map(|pair| format!("<< /FunctionType 2 /Domain [0 1] /C0 [{}] /C1 [{}] /N 1 >>", components(&pair[0].1), components(&pair[1].1)))
+			.collect::<Vec<_>>();
+		let function = if functions.len() == 1 {
+			functions.remove(0)
+		} else {
+			let bounds = stops[1..stops.len() - 1].iter().map(|(position, _)| Number(*position).to_string()).collect::<Vec<_>>().join(" ");
+			let encode = vec!["0 1"; functions.len()].join(" ");
+			format!("<< /FunctionType 3 /Domain [0 1] /Functions [{}] /Bounds [{bounds}] /Encode [{encode}] >>", functions.join(" "))
+		};
+
+		let coordinates = match gradient.gradient_type {
+			GradientType::Linear => format!("/ShadingType 2 /Coords [{} {} {} {}]", Number(start.x), Number(start.y), Number(end.x), Number(end.y)),
+			GradientType::Radial => format!("/ShadingType 3 /Coords [{x} {y} 0 {x} {y} {}]", Number(start.distance(end)), x = Number(start.x), y = Number(start.y)),
+		};
+		let shading = format!("<< {coordinates} /ColorSpace /{color_space} /Function {function} /Extend [true true] >>");
+		Some(self.add_object(shading.into_bytes()))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::vector::PointId;
+
+	/// Checks that every entry of the cross reference table points at the start of its object.
+	fn assert_valid_structure(pdf: &[u8]) -> usize {
+		// Everything after the last stream is text, but the streams themselves are compressed binary data
+		let cross_reference_offset = pdf.windows(6).rposition(|window| window == b"\nxref\n").unwrap() + 1;
+		let trailer = std::str::from_utf8(&pdf[cross_reference_offset..]).unwrap();
+		let start_cross_reference: usize = trailer.lines().skip_while(|line| *line != "startxref").nth(1).unwrap().parse().unwrap();
+		assert_eq!(start_cross_reference, cross_reference_offset);
+
+		let mut lines = trailer.lines().skip(1);
+		let count: usize = lines.next().unwrap().split(' ').nth(1).unwrap().parse().unwrap();
+		for (object, line) in lines.skip(1).take(count - 1).enumerate() {
+			let offset: usize = line[..10].parse().unwrap();
+			assert!(pdf[offset..].starts_with(format!("{} 0 obj\n", object + 1).as_bytes()), "Object {} is not at {offset}", object + 1);
+		}
+		count - 1
+	}
+
+	#[test]
+	fn numbers() {
+		assert_eq!(Number(1.).to_string(), "1");
+		assert_eq!(Number(-0.00001).to_string(), "0");
+		assert_eq!(Number(0.12345).to_string(), "0.1235");
+		assert_eq!(Number(-2.5).to_string(), "-2.5");
+		assert_eq!(Number(1e20).to_string(), "100000000000000000000");
+		assert_eq!(Number(f64::NAN).to_string(), "0");
+	}
+
+	#[test]
+	fn pages_and_objects() {
+		let mut render = PdfRender::new();
+		let mut path = PdfPath::default();
+		path.push_subpath(&Subpath::<PointId>::new_rect(DVec2::ZERO, DVec2::new(10., 20.)), DAffine2::from_translation(DVec2::new(5., 5.)));
+		assert_eq!(path.0, "5 5 m\n15 5 l\n15 25 l\n5 25 l\n5 5 l\nh\n");
+
+		// Drawing outside of a page is ignored
+		render.fill(&path, DAffine2::IDENTITY, Color::BLACK, PdfFillRule::NonZero);
+		assert_eq!(render.page_count(), 0);
+
+		render.begin_page(DVec2::new(200., 100.));
+		render.push_group();
+		render.fill(&path, DAffine2::IDENTITY, Color::from_rgbaf32_unchecked(1., 0., 0., 0.5), PdfFillRule::NonZero);
+		render.stroke(&path, DAffine2::from_scale(DVec2::splat(2.)), &Stroke::new(Some(Color::BLACK), 3.).with_dash_lengths("4 2").unwrap());
+		render.pop_group(0.5, BlendMode::Multiply);
+		render.begin_page(DVec2::new(50., 50.));
+		render.push_clip(&path);
+		render.draw_image(&Image::new(2, 2, Color::TRANSPARENT), DAffine2::from_scale(DVec2::splat(50.)), 1., BlendMode::Normal);
+		render.pop_clip();
+
+		let pdf = render.finish();
+		let text = String::from_utf8_lossy(&pdf);
+		assert!(text.starts_with("%PDF-1.7\n"));
+		assert!(text.contains("/Type /Pages /Kids [") && text.contains("/Count 2 >>"));
+		assert!(text.contains("/MediaBox [0 0 150 75]"));
+		assert!(text.contains("/MediaBox [0 0 37.5 37.5]"));
+		assert!(text.contains("/BM /Multiply"));
+		assert!(text.contains("/SMask"));
+		assert!(text.ends_with("%%EOF\n"));
+
+		// The page tree, the catalog, and for each page its contents and page object
+		assert!(assert_valid_structure(&pdf) >= 6);
+	}
+
+	#[test]
+	fn fill_rules() {
+		let mut path = PdfPath::default();
+		path.push_subpath(&Subpath::<PointId>::new_rect(DVec2::ZERO, DVec2::ONE), DAffine2::IDENTITY);
+		let gradient = Gradient::new(DVec2::ZERO, Color::BLACK, DVec2::ONE, Color::WHITE, DAffine2::IDENTITY, GradientType::Linear);
+
+		let mut render = PdfRender::new();
+		render.begin_page(DVec2::ONE);
+		render.fill(&path, DAffine2::IDENTITY, Color::BLACK, PdfFillRule::NonZero);
+		render.fill(&path, DAffine2::IDENTITY, Color::BLACK, PdfFillRule::EvenOdd);
+		render.fill_gradient(&path, DAffine2::IDENTITY, &gradient, [DVec2::ZERO, DVec2::ONE], DAffine2::IDENTITY, PdfFillRule::EvenOdd);
+
+		let stream = &render.contents[0].stream;
+		assert_eq!(stream.matches("h\nf\n").count(), 1);
+		assert_eq!(stream.matches("h\nf*\n").count(), 1);
+		assert_eq!(stream.matches("h\nW* n\n").count(), 1);
+	}
+}
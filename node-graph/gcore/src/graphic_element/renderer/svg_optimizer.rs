@@ -0,0 +1,530 @@
+use super::SvgRender;
+use super::svg_tree::{Element, Node, format_number, map_references, number_end, parse, parse_transform};
+use glam::{DAffine2, DVec2};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Optimizations for exported SVG files, which are all off by default so the export matches the document's render exactly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct SvgExportOptions {
+	/// The number of decimal places that coordinates and sizes are rounded to, or `None` to keep their full precision.
+	pub precision: Option<u32>,
+	/// Bake the transforms of paths into their coordinates, where that doesn't change how they are drawn.
+	pub flatten_transforms: bool,
+	/// Move the presentation attributes shared by several elements into classes in a `<style>` block.
+	pub merge_styles: bool,
+	/// Rename IDs to the shortest available names and remove those which nothing refers to.
+	pub minify_ids: bool,
+	/// Remove the markup only meaningful to the editor, like `data-*` attributes and `<metadata>`, along with empty definitions and groups.
+	pub strip_metadata: bool,
+}
+
+/// Attributes holding coordinates or sizes, which are rounded to the chosen precision.
+const GEOMETRY_ATTRIBUTES: [&str; 22] = [
+	"d",
+	"transform",
+	"gradientTransform",
+	"patternTransform",
+	"viewBox",
+	"points",
+	"x",
+	"y",
+	"x1",
+	"y1",
+	"x2",
+	"y2",
+	"cx",
+	"cy",
+	"fx",
+	"fy",
+	"r",
+	"rx",
+	"ry",
+	"width",
+	"height",
+	"stroke-width",
+];
+
+/// Attributes which can be moved into a CSS rule as properties of the same name.
+const PRESENTATION_ATTRIBUTES: [&str; 14] = [
+	"fill",
+	"fill-opacity",
+	"fill-rule",
+	"stroke",
+	"stroke-opacity",
+	"stroke-width",
+	"stroke-linecap",
+	"stroke-linejoin",
+	"stroke-miterlimit",
+	"stroke-dasharray",
+	"stroke-dashoffset",
+	"paint-order",
+	"vector-effect",
+	"opacity",
+];
+
+fn strip_metadata(element: &mut Element) {
+	element.attributes.retain(|(name, _)| {
+		let editor_namespace = name.contains(':') && !name.starts_with("xlink:") && !name.starts_with("xml:") && name != "xmlns:xlink";
+		!name.starts_with("data-") && !editor_namespace
+	});
+
+	let children = std::mem::take(&mut element.children);
+	for child in children {
+		let Node::Element(mut child) = child else {
+			element.children.push(child);
+			continue;
+		};
+		if child.name == "metadata" || child.name.contains(':') {
+			continue;
+		}
+
+		strip_metadata(&mut child);
+		match child.name.as_str() {
+			"defs" if child.children.is_empty() => {}
+			// Groups without any attributes have no effect on their content
+			"g" if child.attributes.is_empty() => element.children.append(&mut child.children),
+			_ => element.children.push(Node::Element(child)),
+		}
+	}
+}
+
+/// Transforms path data, made absolute and with horizontal and vertical lines turned into regular lines since they may no longer be either.
+/// Arcs aren't supported, since a transform can change their shape in ways their parameters can't always express.
+fn transform_path(path: &str, transform: DAffine2) -> Option<String> {
+	let bytes = path.as_bytes();
+	let mut commands: Vec<(u8, Vec<f64>)> = Vec::new();
+	let mut index = 0;
+	while index < bytes.len() {
+		let byte = bytes[index];
+		if byte.is_ascii_whitespace() || byte == b',' {
+			index += 1;
+		} else if byte.is_ascii_alphabetic() {
+			commands.push((byte, Vec::new()));
+			index += 1;
+		} else {
+			let end = number_end(bytes, index)?;
+			commands.last_mut()?.1.push(path[index..end].parse().ok()?);
+			index = end;
+		}
+	}
+
+	let mut transformed = String::with_capacity(path.len());
+	let mut current = DVec2::ZERO;
+	let mut subpath_start = DVec2::ZERO;
+	for (command, arguments) in commands {
+		let relative = command.is_ascii_lowercase();
+		let command = command.to_ascii_uppercase();
+		let count = match command {
+			b'Z' => {
+				if !arguments.is_empty() {
+					return None;
+				}
+				transformed.push_str("Z ");
+				current = subpath_start;
+				continue;
+			}
+			b'H' | b'V' => 1,
+			b'M' | b'L' | b'T' => 2,
+			b'S' | b'Q' => 4,
+			b'C' => 6,
+			_ => return None,
+		};
+		if arguments.is_empty() || arguments.len() % count != 0 {
+			return None;
+		}
+
+		for (repeat, arguments) in arguments.chunks(count).enumerate() {
+			let origin = if relative { current } else { DVec2::ZERO };
+			let points = match command {
+				b'H' => vec![DVec2::new(origin.x + arguments[0], current.y)],
+				b'V' => vec![DVec2::new(current.x, origin.y + arguments[0])],
+				_ => arguments.chunks(2).map(|point| origin + DVec2::new(point[0], point[1])).collect(),
+			};
+			// Coordinates after the first pair of a move are implicit lines
+			let letter = match command {
+				b'H' | b'V' => 'L',
+				b'M' if repeat > 0 => 'L',
+				_ => command as char,
+			};
+
+			transformed.push(letter);
+			for (point_index, point) in points.iter().enumerate() {
+				let point = transform.transform_point2(*point);
+				let separator = if point_index == 0 { "" } else { " " };
+				let _ = write!(transformed, "{separator}{},{}", format_number(point.x, 9), format_number(point.y, 9));
+			}
+			transformed.push(' ');
+
+			current = *points.last()?;
+			if letter == 'M' {
+				subpath_start = current;
+			}
+		}
+	}
+
+	Some(transformed.trim_end().to_string())
+}
+
+/// Which of the attributes affecting how a path's transform can be flattened are inherited from its ancestors.
+#[derive(Clone, Copy, Default)]
+struct Inherited {
+	stroked: bool,
+	non_scaling_stroke: bool,
+	references: bool,
+}
+
+fn flatten_transforms(element: &mut Element, mut inherited: Inherited) {
+	if let Some(stroke) = element.attribute("stroke") {
+		inherited.stroked = stroke != "none";
+	}
+	if let Some(vector_effect) = element.attribute("vector-effect") {
+		inherited.non_scaling_stroke = vector_effect == "non-scaling-stroke";
+	}
+	// Gradients, clipping paths, masks and filters are positioned in the coordinates of the element using them
+	inherited.references |= element.attributes.iter().any(|(_, value)| value.contains("url("));
+
+	if element.name == "path" && !inherited.references {
+		flatten_path(element, inherited);
+	}
+	for child in element.child_elements_mut() {
+		flatten_transforms(child, inherited);
+	}
+}
+
+fn flatten_path(path: &mut Element, inherited: Inherited) {
+	let Some(transform) = path.attribute("transform").and_then(parse_transform) else { return };
+
+	// Strokes are drawn in the transformed coordinates, so they can only keep their look if the transform scales them uniformly
+	let stroke_scale = if inherited.stroked && !inherited.non_scaling_stroke {
+		let (x_axis, y_axis) = (transform.matrix2.x_axis, transform.matrix2.y_axis);
+		let scale = x_axis.length();
+		if (y_axis.length() - scale).abs() > scale * 1e-9 || x_axis.dot(y_axis).abs() > scale * scale * 1e-9 {
+			return;
+		}
+		Some(scale)
+	} else {
+		None
+	};
+
+	let Some(data) = path.attribute("d").and_then(|data| transform_path(data, transform)) else {
+		return;
+	};
+	path.set_attribute("d", data);
+	path.remove_attribute("transform");
+
+	if let Some(scale) = stroke_scale.filter(|&scale| scale != 1.) {
+		let scale_numbers = |value: &str| round_numbers(value, |number| format_number(number * scale, 9));
+		let stroke_width = scale_numbers(path.attribute("stroke-width").unwrap_or("1"));
+		path.set_attribute("stroke-width", stroke_width);
+		for attribute in ["stroke-dasharray", "stroke-dashoffset"] {
+			if let Some(value) = path.attribute(attribute).map(scale_numbers) {
+				path.set_attribute(attribute, value);
+			}
+		}
+	}
+}
+
+/// Replaces every number in the value, keeping numbers apart which would otherwise run together once they're shortened.
+fn round_numbers(value: &str, format: impl Fn(f64) -> String) -> String {
+	let bytes = value.as_bytes();
+	let mut rounded = String::with_capacity(value.len());
+	let mut index = 0;
+	while index < bytes.len() {
+		let Some(end) = number_end(bytes, index) else {
+			let character = value[index..].chars().next().unwrap_or_default();
+			rounded.push(character);
+			index += character.len_utf8();
+			continue;
+		};
+
+		let number = value[index..end].parse().map(&format).unwrap_or_else(|_| value[index..end].to_string());
+		if rounded.ends_with(|c: char| c.is_ascii_digit() || c == '.') && !number.starts_with('-') {
+			rounded.push(' ');
+		}
+		rounded.push_str(&number);
+		index = end;
+	}
+	rounded
+}
+
+fn round_geometry(element: &mut Element, precision: u32) {
+	for (name, value) in &mut element.attributes {
+		if GEOMETRY_ATTRIBUTES.contains(&name.as_str()) {
+			*value = round_numbers(value, |number| format_number(number, precision));
+		}
+	}
+	for child in element.child_elements_mut() {
+		round_geometry(child, precision);
+	}
+}
+
+/// The shortest names, in order, starting with a letter as IDs must.
+fn short_name(mut index: usize) -> String {
+	const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+	let mut name = String::new();
+	loop {
+		name.push(LETTERS[index % LETTERS.len()] as char);
+		index /= LETTERS.len();
+		if index == 0 {
+			return name;
+		}
+		index -= 1;
+	}
+}
+
+fn collect_references(element: &Element, references: &mut HashSet<String>) {
+	let attributes = element.attributes.iter().filter(|(name, _)| name != "id").map(|(_, value)| value.as_str());
+	let style = element.children.iter().filter_map(|child| match child {
+		Node::Text(text) if element.name == "style" => Some(text.as_str()),
+		_ => None,
+	});
+	for value in attributes.chain(style) {
+		map_references(value, |id| {
+			references.insert(id.to_string());
+			None
+		});
+	}
+
+	for child in &element.children {
+		if let Node::Element(child) = child {
+			collect_references(child, references);
+		}
+	}
+}
+
+fn remove_unreferenced_ids(element: &mut Element, references: &HashSet<String>) {
+	element.attributes.retain(|(name, value)| name != "id" || references.contains(value));
+	for child in element.child_elements_mut() {
+		remove_unreferenced_ids(child, references);
+	}
+}
+
+fn minify_ids(root: &mut Element) {
+	let mut references = HashSet::new();
+	collect_references(root, &mut references);
+	remove_unreferenced_ids(root, &references);
+
+	let mut ids = Vec::new();
+	root.ids(&mut ids);
+	let renamed = ids.into_iter().enumerate().map(|(index, id)| (id, short_name(index))).collect();
+	root.rename_ids(&renamed);
+}
+
+/// The CSS declarations equivalent to the element's presentation attributes.
+fn declarations(element: &Element) -> String {
+	if element.name.starts_with("animate") || element.name == "set" {
+		return String::new();
+	}
+
+	let mut declarations = element
+		.attributes
+		.iter()
+		.filter(|(name, _)| PRESENTATION_ATTRIBUTES.contains(&name.as_str()))
+		.map(|(name, value)| format!("{name}:{}", value.trim()))
+		.collect::<Vec<_>>();
+	// The style attribute is only used for blend modes, so it's merged along with them
+	if let Some(style) = element.attribute("style") {
+		declarations.extend(
+			style
+				.split(';')
+				.map(str::trim)
+				.filter(|declaration| !declaration.is_empty())
+				.map(|declaration| declaration.replace(": ", ":")),
+		);
+	}
+	declarations.join(";")
+}
+
+fn count_declarations(element: &Element, counts: &mut Vec<(String, usize)>) {
+	let declarations = declarations(element);
+	if !declarations.is_empty() {
+		match counts.iter_mut().find(|(existing, _)| *existing == declarations) {
+			Some((_, count)) => *count += 1,
+			None => counts.push((declarations, 1)),
+		}
+	}
+	for child in &element.children {
+		if let Node::Element(child) = child {
+			count_declarations(child, counts);
+		}
+	}
+}
+
+fn apply_classes(element: &mut Element, classes: &HashMap<String, String>) {
+	if let Some(class) = classes.get(&declarations(element)) {
+		element.attributes.retain(|(name, _)| !PRESENTATION_ATTRIBUTES.contains(&name.as_str()) && name != "style");
+		let class = match element.attribute("class") {
+			Some(existing) => format!("{existing} {class}"),
+			None => class.clone(),
+		};
+		element.set_attribute("class", class);
+	}
+	for child in element.child_elements_mut() {
+		apply_classes(child, classes);
+	}
+}
+
+fn merge_styles(root: &mut Element) {
+	let mut counts = Vec::new();
+	count_declarations(root, &mut counts);
+
+	// Styles used only once are shorter left as attributes
+	let mut css = String::new();
+	let mut classes = HashMap::new();
+	for (declarations, _) in counts.into_iter().filter(|(_, count)| *count > 1) {
+		let class = format!("s{}", classes.len());
+		let _ = write!(css, ".{class}{{{declarations}}}");
+		classes.insert(declarations, class);
+	}
+	if classes.is_empty() {
+		return;
+	}
+	apply_classes(root, &classes);
+
+	let existing_style = root.child_elements_mut().find(|child| child.name == "style");
+	match existing_style {
+		Some(style) => style.children.push(Node::Text(css)),
+		None => {
+			let mut style = Element::new("style", &[]);
+			style.children.push(Node::Text(css));
+			root.children.insert(0, Node::Element(style));
+		}
+	}
+}
+
+impl SvgRender {
+	/// Applies the chosen optimizations to an SVG document written by `SvgRender`.
+	pub fn optimize(svg: &str, options: SvgExportOptions) -> Result<String, String> {
+		let mut root = parse(svg).ok_or("Unable to read the rendered SVG")?;
+
+		if options.strip_metadata {
+			strip_metadata(&mut root);
+		}
+		if options.flatten_transforms {
+			flatten_transforms(&mut root, Inherited::default());
+		}
+		if let Some(precision) = options.precision {
+			round_geometry(&mut root, precision);
+		}
+		if options.minify_ids {
+			minify_ids(&mut root);
+		}
+		if options.merge_styles {
+			merge_styles(&mut root);
+		}
+
+		let mut optimized = String::with_capacity(svg.len());
+		root.write(&mut optimized);
+		Ok(optimized)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn optimize(svg: &str, options: SvgExportOptions) -> String {
+		SvgRender::optimize(svg, options).unwrap()
+	}
+
+	#[test]
+	fn precision() {
+		let svg = r##"<svg viewBox="0 0 100.123456 50"><path d="M0.123456,-1.987654 C1.11111,2.22222 3.33333,4.44444 5.55555,6.66666 Z" transform="matrix(1,0,0,1,0.3333333,0.6666667)" fill="#123456" opacity="0.567"/></svg>"##;
+		let options = SvgExportOptions {
+			precision: Some(2),
+			..Default::default()
+		};
+		assert_eq!(
+			optimize(svg, options),
+			r##"<svg viewBox="0 0 100.12 50"><path d="M0.12,-1.99 C1.11,2.22 3.33,4.44 5.56,6.67 Z" transform="matrix(1,0,0,1,0.33,0.67)" fill="#123456" opacity="0.567"/></svg>"##
+		);
+
+		// Numbers which were only kept apart by their decimal points stay separate
+		let options = SvgExportOptions {
+			precision: Some(0),
+			..Default::default()
+		};
+		assert_eq!(optimize(r#"<svg><path d="M1.4.6-0.2 L0.00001,3e2"/></svg>"#, options), r#"<svg><path d="M1 1 0 L0,300"/></svg>"#);
+	}
+
+	#[test]
+	fn flatten_path_transforms() {
+		let options = SvgExportOptions {
+			flatten_transforms: true,
+			..Default::default()
+		};
+
+		let svg = r#"<svg><path d="M1,2 h3 v4 l-1,1 C5,5 6,6 7,7 Z m1,1 2,0" transform="matrix(2,0,0,3,10,20)" fill="red"/></svg>"#;
+		assert_eq!(
+			optimize(svg, options),
+			r#"<svg><path d="M12,26 L18,26 L18,38 L16,41 C20,35 22,38 24,41 Z M14,29 L18,29" fill="red"/></svg>"#
+		);
+
+		// Uniformly scaled strokes are scaled along with the path
+		let svg = r#"<svg><path d="M0,0 L1,0" transform="rotate(90) scale(2)" stroke="black" stroke-dasharray="1, 2"/></svg>"#;
+		assert_eq!(optimize(svg, options), r#"<svg><path d="M0,0 L0,2" stroke="black" stroke-dasharray="2, 4" stroke-width="2"/></svg>"#);
+
+		// Non-uniformly scaled strokes, gradients and arcs can't be flattened
+		for svg in [
+			r#"<svg><path d="M0,0 L1,0" transform="scale(2,1)" stroke="black"/></svg>"#,
+			r#"<svg><g stroke="black"><path d="M0,0 L1,0" transform="scale(2,1)"/></g></svg>"#,
+			r#"<svg><path d="M0,0 L1,0" transform="scale(2,1)" fill="url('#gradient')"/></svg>"#,
+			r#"<svg><path d="M0,0 A1,1 0 0 1 1,0" transform="scale(2,1)"/></svg>"#,
+		] {
+			assert_eq!(optimize(svg, options), svg.replace(" />", "/>"));
+		}
+
+		let svg = r#"<svg><path d="M0,0 L1,0" transform="scale(2,1)" stroke="black" vector-effect="non-scaling-stroke"/></svg>"#;
+		assert_eq!(optimize(svg, options), r#"<svg><path d="M0,0 L2,0" stroke="black" vector-effect="non-scaling-stroke"/></svg>"#);
+	}
+
+	#[test]
+	fn minify_and_remove_ids() {
+		let svg = r#"<svg><defs><linearGradient id="9876543210"/><clipPath id="artboard-123"><rect/></clipPath><rect id="unused"/></defs><g clip-path="url(#artboard-123)"><path fill="url('#9876543210')"/></g></svg>"#;
+		let options = SvgExportOptions {
+			minify_ids: true,
+			..Default::default()
+		};
+		assert_eq!(
+			optimize(svg, options),
+			r#"<svg><defs><linearGradient id="a"/><clipPath id="b"><rect/></clipPath><rect/></defs><g clip-path="url(#b)"><path fill="url('#a')"/></g></svg>"#
+		);
+
+		assert_eq!(short_name(0), "a");
+		assert_eq!(short_name(51), "Z");
+		assert_eq!(short_name(52), "aa");
+		assert_eq!(short_name(53), "ba");
+	}
+
+	#[test]
+	fn merged_styles() {
+		let svg = r##"<svg><path d="M0,0" fill="#ff0000" stroke="none"/><path d="M1,1" fill="#ff0000" stroke="none"/><path d="M2,2" fill="#00ff00" style=" mix-blend-mode: multiply;"/><rect fill="#00ff00" style=" mix-blend-mode: multiply;" class="existing"/><path d="M3,3" fill="#0000ff"/></svg>"##;
+		let options = SvgExportOptions {
+			merge_styles: true,
+			..Default::default()
+		};
+		assert_eq!(
+			optimize(svg, options),
+			r##"<svg><style>.s0{fill:#ff0000;stroke:none}.s1{fill:#00ff00;mix-blend-mode:multiply}</style><path d="M0,0" class="s0"/><path d="M1,1" class="s0"/><path d="M2,2" class="s1"/><rect class="existing s1"/><path d="M3,3" fill="#0000ff"/></svg>"##
+		);
+	}
+
+	#[test]
+	fn stripped_metadata() {
+		let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:graphite="https://graphite.rs">
+	<!-- Comment -->
+	<metadata>Editor state</metadata>
+	<defs></defs>
+	<g><g transform="translate(1)" data-layer="123" graphite:node="4"><path d="M0,0"/></g></g>
+</svg>"#;
+		let options = SvgExportOptions {
+			strip_metadata: true,
+			..Default::default()
+		};
+		assert_eq!(
+			optimize(svg, options),
+			r#"<svg xmlns="http://www.w3.org/2000/svg"><g transform="translate(1)"><path d="M0,0"/></g></svg>"#
+		);
+	}
+}
@@ -0,0 +1,252 @@
+use glam::{DAffine2, DVec2};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct Element {
+	pub name: String,
+	pub attributes: Vec<(String, String)>,
+	pub children: Vec<Node>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Node {
+	Element(Element),
+	Text(String),
+}
+
+impl Element {
+	pub fn new(name: &str, attributes: &[(&str, &str)]) -> Self {
+		Self {
+			name: name.to_string(),
+			attributes: attributes.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+			children: Vec::new(),
+		}
+	}
+
+	pub fn attribute(&self, name: &str) -> Option<&str> {
+		self.attributes.iter().find(|(attribute, _)| attribute == name).map(|(_, value)| value.as_str())
+	}
+
+	pub fn set_attribute(&mut self, name: &str, value: String) {
+		match self.attributes.iter_mut().find(|(attribute, _)| attribute == name) {
+			Some((_, existing)) => *existing = value,
+			None => self.attributes.push((name.to_string(), value)),
+		}
+	}
+
+	pub fn remove_attribute(&mut self, name: &str) {
+		self.attributes.retain(|(attribute, _)| attribute != name);
+	}
+
+	pub fn child_elements_mut(&mut self) -> impl Iterator<Item = &mut Element> {
+		self.children.iter_mut().filter_map(|child| match child {
+			Node::Element(element) => Some(element),
+			Node::Text(_) => None,
+		})
+	}
+
+	/// Collects the IDs of this element and its descendants, in document order.
+	pub fn ids(&self, ids: &mut Vec<String>) {
+		if let Some(id) = self.attribute("id") {
+			ids.push(id.to_string());
+		}
+		for child in &self.children {
+			if let Node::Element(child) = child {
+				child.ids(ids);
+			}
+		}
+	}
+
+	/// Renames the element IDs and every `#id` reference to them, in `url(#id)` paints, `href`s and `<style>` blocks.
+	pub fn rename_ids(&mut self, ids: &HashMap<String, String>) {
+		for (name, value) in &mut self.attributes {
+			if name == "id" {
+				if let Some(id) = ids.get(value.as_str()) {
+					*value = id.clone();
+				}
+			} else if value.contains('#') {
+				*value = rename_references(value, ids);
+			}
+		}
+		let style = self.name == "style";
+		for child in &mut self.children {
+			match child {
+				Node::Element(child) => child.rename_ids(ids),
+				Node::Text(text) if style => *text = rename_references(text, ids),
+				Node::Text(_) => {}
+			}
+		}
+	}
+
+	pub fn write(&self, svg: &mut String) {
+		svg.push('<');
+		svg.push_str(&self.name);
+		for (name, value) in &self.attributes {
+			let _ = write!(svg, r#" {name}="{}""#, value.replace('"', "&quot;"));
+		}
+		if self.children.is_empty() {
+			svg.push_str("/>");
+			return;
+		}
+		svg.push('>');
+		for child in &self.children {
+			match child {
+				Node::Element(element) => element.write(svg),
+				Node::Text(text) => svg.push_str(text),
+			}
+		}
+		let _ = write!(svg, "</{}>", self.name);
+	}
+}
+
+/// Parses the subset of XML written by `SvgRender`, returning the root element.
+pub(super) fn parse(svg: &str) -> Option<Element> {
+	// The bottom of the stack collects the root element
+	let mut stack = vec![Element::default()];
+	let mut rest = svg;
+
+	while !rest.is_empty() {
+		if let Some(comment) = rest.strip_prefix("<!--") {
+			rest = &comment[comment.find("-->")? + 3..];
+		} else if rest.starts_with("<?") || rest.starts_with("<!") {
+			rest = &rest[rest.find('>')? + 1..];
+		} else if let Some(closing) = rest.strip_prefix("</") {
+			let end = closing.find('>')?;
+			let element = stack.pop()?;
+			if element.name != closing[..end].trim() {
+				return None;
+			}
+			stack.last_mut()?.children.push(Node::Element(element));
+			rest = &closing[end + 1..];
+		} else if let Some(opening) = rest.strip_prefix('<') {
+			let name_end = opening.find(|c: char| c.is_whitespace() || c == '/' || c == '>')?;
+			let mut element = Element::new(&opening[..name_end], &[]);
+			let mut tag = &opening[name_end..];
+			loop {
+				tag = tag.trim_start();
+				if let Some(after) = tag.strip_prefix("/>") {
+					stack.last_mut()?.children.push(Node::Element(element));
+					rest = after;
+					break;
+				}
+				if let Some(after) = tag.strip_prefix('>') {
+					stack.push(element);
+					rest = after;
+					break;
+				}
+
+				let equals = tag.find('=')?;
+				let name = tag[..equals].trim().to_string();
+				let value = tag[equals + 1..].trim_start();
+				let quote = value.chars().next().filter(|&quote| quote == '"' || quote == '\'')?;
+				let value = &value[1..];
+				let end = value.find(quote)?;
+				element.attributes.push((name, value[..end].to_string()));
+				tag = &value[end + 1..];
+			}
+		} else {
+			let end = rest.find('<').unwrap_or(rest.len());
+			// The whitespace between tags is only indentation
+			if !rest[..end].trim().is_empty() {
+				stack.last_mut()?.children.push(Node::Text(rest[..end].to_string()));
+			}
+			rest = &rest[end..];
+		}
+	}
+
+	let document = stack.pop()?;
+	if !stack.is_empty() {
+		return None;
+	}
+	document.children.into_iter().rev().find_map(|node| match node {
+		Node::Element(element) => Some(element),
+		Node::Text(_) => None,
+	})
+}
+
+/// Calls `reference` with the name of every `#id` reference in the value, replacing it with the returned name.
+pub(super) fn map_references(value: &str, mut reference: impl FnMut(&str) -> Option<String>) -> String {
+	let mut mapped = String::with_capacity(value.len());
+	let mut rest = value;
+	while let Some(hash) = rest.find('#') {
+		mapped.push_str(&rest[..=hash]);
+		rest = &rest[hash + 1..];
+		let end = rest.find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))).unwrap_or(rest.len());
+		match reference(&rest[..end]) {
+			Some(name) => mapped.push_str(&name),
+			None => mapped.push_str(&rest[..end]),
+		}
+		rest = &rest[end..];
+	}
+	mapped.push_str(rest);
+	mapped
+}
+
+fn rename_references(value: &str, ids: &HashMap<String, String>) -> String {
+	map_references(value, |id| ids.get(id).cloned())
+}
+
+/// Finds the end of the number which starts at `start`, if one does.
+pub(super) fn number_end(bytes: &[u8], start: usize) -> Option<usize> {
+	let digits = |mut index: usize| {
+		while bytes.get(index).is_some_and(u8::is_ascii_digit) {
+			index += 1;
+		}
+		index
+	};
+
+	let integer_start = start + usize::from(matches!(bytes.get(start), Some(b'-' | b'+')));
+	let mut end = digits(integer_start);
+	// A second decimal point starts the next number, as in the compact path data `0.5.5`
+	if bytes.get(end) == Some(&b'.') && bytes.get(end + 1).is_some_and(u8::is_ascii_digit) {
+		end = digits(end + 1);
+	}
+	if end == integer_start {
+		return None;
+	}
+
+	if matches!(bytes.get(end), Some(b'e' | b'E')) {
+		let exponent_start = end + 1 + usize::from(matches!(bytes.get(end + 1), Some(b'-' | b'+')));
+		if bytes.get(exponent_start).is_some_and(u8::is_ascii_digit) {
+			end = digits(exponent_start);
+		}
+	}
+	Some(end)
+}
+
+/// Rounds to the given number of decimal places, without trailing zeros.
+pub(super) fn format_number(value: f64, precision: u32) -> String {
+	let factor = 10_f64.powi(precision as i32);
+	let value = (value * factor).round() / factor;
+	if value == 0. || !value.is_finite() { "0".to_string() } else { value.to_string() }
+}
+
+pub(super) fn parse_transform(value: &str) -> Option<DAffine2> {
+	let mut transform = DAffine2::IDENTITY;
+	let mut rest = value.trim();
+	while !rest.is_empty() {
+		let open = rest.find('(')?;
+		let close = rest.find(')')?;
+		let arguments = rest[open + 1..close]
+			.split(|c: char| c == ',' || c.is_whitespace())
+			.filter(|argument| !argument.is_empty())
+			.map(|argument| argument.parse().ok())
+			.collect::<Option<Vec<f64>>>()?;
+
+		transform *= match (rest[..open].trim(), arguments.as_slice()) {
+			("matrix", &[a, b, c, d, e, f]) => DAffine2::from_cols_array(&[a, b, c, d, e, f]),
+			("translate", &[x]) => DAffine2::from_translation(DVec2::new(x, 0.)),
+			("translate", &[x, y]) => DAffine2::from_translation(DVec2::new(x, y)),
+			("scale", &[scale]) => DAffine2::from_scale(DVec2::splat(scale)),
+			("scale", &[x, y]) => DAffine2::from_scale(DVec2::new(x, y)),
+			("rotate", &[angle]) => DAffine2::from_angle(angle.to_radians()),
+			("rotate", &[angle, x, y]) => DAffine2::from_translation(DVec2::new(x, y)) * DAffine2::from_angle(angle.to_radians()) * DAffine2::from_translation(-DVec2::new(x, y)),
+			("skewX", &[angle]) => DAffine2::from_cols_array(&[1., 0., angle.to_radians().tan(), 1., 0., 0.]),
+			("skewY", &[angle]) => DAffine2::from_cols_array(&[1., angle.to_radians().tan(), 0., 1., 0., 0.]),
+			_ => return None,
+		};
+		rest = rest[close + 1..].trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+	}
+	Some(transform)
+}
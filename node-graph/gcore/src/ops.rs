@@ -6,12 +6,15 @@ use crate::vector::style::GradientStops;
 use crate::{Color, Node};
 use core::marker::PhantomData;
 use core::ops::{Add, Div, Mul, Rem, Sub};
-use glam::DVec2;
+use glam::{DVec2, DVec4};
 use math_parser::ast;
+use math_parser::compiler::Program;
 use math_parser::context::{EvalContext, NothingMap, ValueProvider};
 use math_parser::value::{Number, Value};
 use num_traits::Pow;
 use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
 
 #[cfg(target_arch = "spirv")]
 use spirv_std::num_traits::float::Float;
@@ -19,65 +22,158 @@ use spirv_std::num_traits::float::Float;
 /// The struct that stores the context for the maths parser.
 /// This is currently just limited to supplying `a` and `b` until we add better node graph support and UI for variadic inputs.
 struct MathNodeContext {
-	a: f64,
-	b: f64,
+	a: Value,
+	b: Value,
 }
 
 impl ValueProvider for MathNodeContext {
 	fn get_value(&self, name: &str) -> Option<Value> {
 		if name.eq_ignore_ascii_case("a") {
-			Some(Value::from_f64(self.a))
+			Some(self.a.clone())
 		} else if name.eq_ignore_ascii_case("b") {
-			Some(Value::from_f64(self.b))
+			Some(self.b.clone())
 		} else {
 			None
 		}
 	}
 }
 
+/// The maximum number of compiled expressions kept around by the math node before the cache is cleared.
+const MAX_CACHED_PROGRAMS: usize = 256;
+
+/// Compiled math node expressions, so the expression is only parsed and compiled once rather than for every evaluation.
+static PROGRAMS: LazyLock<Mutex<HashMap<String, Arc<Program>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn compile_expression(expression: &str) -> Option<Arc<Program>> {
+	let mut programs = PROGRAMS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+	if let Some(program) = programs.get(expression) {
+		return Some(program.clone());
+	}
+
+	let node = match ast::Node::try_parse_from_str(expression) {
+		Ok(node) => node,
+		Err(e) => {
+			warn!("Invalid expression: `{expression}`\n{e:?}");
+			return None;
+		}
+	};
+	let program = match node.compile() {
+		Ok(program) => Arc::new(program),
+		Err(e) => {
+			warn!("Expression compilation error: `{expression}`\n{e:?}");
+			return None;
+		}
+	};
+
+	if programs.len() >= MAX_CACHED_PROGRAMS {
+		programs.clear();
+	}
+	programs.insert(expression.to_string(), program.clone());
+	Some(program)
+}
+
+/// Types which can be given to and returned from the math parser.
+pub trait MathValue: Sized {
+	fn to_value(&self) -> Value;
+	fn from_value(value: &Value) -> Option<Self>;
+}
+
+impl MathValue for f64 {
+	fn to_value(&self) -> Value {
+		Value::from_f64(*self)
+	}
+
+	fn from_value(value: &Value) -> Option<Self> {
+		match value {
+			Value::Number(Number::Real(val)) => Some(*val),
+			Value::Number(Number::Complex(c)) => Some(c.re),
+			Value::Bool(val) => Some(*val as u8 as f64),
+			Value::Quantity(_) => value.as_design_real(),
+			_ => None,
+		}
+	}
+}
+
+impl MathValue for f32 {
+	fn to_value(&self) -> Value {
+		Value::from_f64(*self as f64)
+	}
+
+	fn from_value(value: &Value) -> Option<Self> {
+		f64::from_value(value).map(|val| val as f32)
+	}
+}
+
+impl MathValue for DVec2 {
+	fn to_value(&self) -> Value {
+		Value::Vec2(*self)
+	}
+
+	fn from_value(value: &Value) -> Option<Self> {
+		match value {
+			Value::Vec2(vector) => Some(*vector),
+			_ => None,
+		}
+	}
+}
+
+impl MathValue for Color {
+	fn to_value(&self) -> Value {
+		Value::Color(DVec4::new(self.r() as f64, self.g() as f64, self.b() as f64, self.a() as f64))
+	}
+
+	fn from_value(value: &Value) -> Option<Self> {
+		match value {
+			Value::Color(color) => {
+				let color = color.as_vec4();
+				Some(Color::from_rgbaf32_unchecked(color.x, color.y, color.z, color.w))
+			}
+			_ => None,
+		}
+	}
+}
+
 /// Calculates a mathematical expression with input values "A" and "B"
 #[node_macro::node(category("General"), properties("math_properties"))]
-fn math<U: num_traits::float::Float>(
+fn math<U: MathValue + Default, T: MathValue>(
 	_: impl Ctx,
 	/// The value of "A" when calculating the expression
-	#[implementations(f64, f32)]
+	#[implementations(f64, f32, DVec2, DVec2, Color, Color)]
 	operand_a: U,
-	/// A math expression that may incorporate "A" and/or "B", such as "sqrt(A + B) - B^2"
+	/// A math expression that may incorporate "A" and/or "B", such as "sqrt(A + B) - B^2".
+	/// Statements separated by `;` can define variables with `let x = A * 2` and functions with `let f(x) = x^2`, followed by the resulting expression.
+	/// Units such as "mm", "in", "pt", "px" and "deg" are supported, with lengths given in pixels and angles in degrees unless converted like "A px to mm".
+	/// Vectors and colors support component access like "A.x" or "A.r" as well as functions such as "dot(A, B)" and "length(A)".
 	#[default(A + B)]
 	expression: String,
 	/// The value of "B" when calculating the expression
-	#[implementations(f64, f32)]
+	#[implementations(f64, f32, f64, DVec2, f64, Color)]
 	#[default(1.)]
-	operand_b: U,
+	operand_b: T,
 ) -> U {
-	let (node, _unit) = match ast::Node::try_parse_from_str(&expression) {
-		Ok(expr) => expr,
-		Err(e) => {
-			warn!("Invalid expression: `{expression}`\n{e:?}");
-			return U::from(0.).unwrap();
-		}
+	let Some(program) = compile_expression(&expression) else {
+		return U::default();
 	};
 	let context = EvalContext::new(
 		MathNodeContext {
-			a: operand_a.to_f64().unwrap(),
-			b: operand_b.to_f64().unwrap(),
+			a: operand_a.to_value(),
+			b: operand_b.to_value(),
 		},
 		NothingMap,
 	);
 
-	let value = match node.eval(&context) {
+	let value = match program.eval(&context) {
 		Ok(value) => value,
 		Err(e) => {
 			warn!("Expression evaluation error: {e:?}");
-			return U::from(0.).unwrap();
+			return U::default();
 		}
 	};
 
-	let Value::Number(num) = value;
-	match num {
-		Number::Real(val) => U::from(val).unwrap(),
-		Number::Complex(c) => U::from(c.re).unwrap(),
-	}
+	U::from_value(&value).unwrap_or_else(|| {
+		warn!("The result of the expression `{expression}` has the wrong type: {value}");
+		U::default()
+	})
 }
 
 /// The addition operation (+) calculates the sum of two numbers.
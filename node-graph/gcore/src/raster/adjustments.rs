@@ -195,6 +195,35 @@ impl BlendMode {
 		}
 	}
 
+	/// Convert the enum to the name of the PDF blend mode, which supports the same subset as SVG.
+	pub fn to_pdf_name(&self) -> Option<&'static str> {
+		match self {
+			// Normal group
+			BlendMode::Normal => Some("Normal"),
+			// Darken group
+			BlendMode::Darken => Some("Darken"),
+			BlendMode::Multiply => Some("Multiply"),
+			BlendMode::ColorBurn => Some("ColorBurn"),
+			// Lighten group
+			BlendMode::Lighten => Some("Lighten"),
+			BlendMode::Screen => Some("Screen"),
+			BlendMode::ColorDodge => Some("ColorDodge"),
+			// Contrast group
+			BlendMode::Overlay => Some("Overlay"),
+			BlendMode::SoftLight => Some("SoftLight"),
+			BlendMode::HardLight => Some("HardLight"),
+			// Inversion group
+			BlendMode::Difference => Some("Difference"),
+			BlendMode::Exclusion => Some("Exclusion"),
+			// Component group
+			BlendMode::Hue => Some("Hue"),
+			BlendMode::Saturation => Some("Saturation"),
+			BlendMode::Color => Some("Color"),
+			BlendMode::Luminosity => Some("Luminosity"),
+			_ => None,
+		}
+	}
+
 	/// Renders the blend mode CSS style declaration.
 	pub fn render(&self) -> String {
 		format!(
@@ -1070,6 +1099,116 @@ impl DomainWarpType {
 	}
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, DynAny)]
+pub enum RawDemosaicAlgorithm {
+	#[default]
+	Linear,
+	VariableNumberOfGradients,
+	AdaptiveHomogeneityDirected,
+}
+
+impl core::fmt::Display for RawDemosaicAlgorithm {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			RawDemosaicAlgorithm::Linear => write!(f, "Linear"),
+			RawDemosaicAlgorithm::VariableNumberOfGradients => write!(f, "Variable Number of Gradients (VNG)"),
+			RawDemosaicAlgorithm::AdaptiveHomogeneityDirected => write!(f, "Adaptive Homogeneity-Directed (AHD)"),
+		}
+	}
+}
+
+impl RawDemosaicAlgorithm {
+	pub fn list() -> &'static [RawDemosaicAlgorithm; 3] {
+		&[
+			RawDemosaicAlgorithm::Linear,
+			RawDemosaicAlgorithm::VariableNumberOfGradients,
+			RawDemosaicAlgorithm::AdaptiveHomogeneityDirected,
+		]
+	}
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, DynAny)]
+pub enum RawWhiteBalance {
+	#[default]
+	AsShot,
+	Temperature,
+	Custom,
+}
+
+impl core::fmt::Display for RawWhiteBalance {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			RawWhiteBalance::AsShot => write!(f, "As Shot"),
+			RawWhiteBalance::Temperature => write!(f, "Temperature and Tint"),
+			RawWhiteBalance::Custom => write!(f, "Custom Multipliers"),
+		}
+	}
+}
+
+impl RawWhiteBalance {
+	pub fn list() -> &'static [RawWhiteBalance; 3] {
+		&[RawWhiteBalance::AsShot, RawWhiteBalance::Temperature, RawWhiteBalance::Custom]
+	}
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, DynAny)]
+pub enum RawHighlightRecovery {
+	#[default]
+	Clip,
+	Unclip,
+	Blend,
+}
+
+impl core::fmt::Display for RawHighlightRecovery {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			RawHighlightRecovery::Clip => write!(f, "Clip"),
+			RawHighlightRecovery::Unclip => write!(f, "Unclip"),
+			RawHighlightRecovery::Blend => write!(f, "Blend"),
+		}
+	}
+}
+
+impl RawHighlightRecovery {
+	pub fn list() -> &'static [RawHighlightRecovery; 3] {
+		&[RawHighlightRecovery::Clip, RawHighlightRecovery::Unclip, RawHighlightRecovery::Blend]
+	}
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, DynAny)]
+pub enum RawColorSpace {
+	#[default]
+	Srgb,
+	AdobeRgb,
+	ProPhotoRgb,
+	Camera,
+}
+
+impl core::fmt::Display for RawColorSpace {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			RawColorSpace::Srgb => write!(f, "sRGB"),
+			RawColorSpace::AdobeRgb => write!(f, "Adobe RGB (1998)"),
+			RawColorSpace::ProPhotoRgb => write!(f, "ProPhoto RGB"),
+			RawColorSpace::Camera => write!(f, "Camera"),
+		}
+	}
+}
+
+impl RawColorSpace {
+	pub fn list() -> &'static [RawColorSpace; 4] {
+		&[RawColorSpace::Srgb, RawColorSpace::AdobeRgb, RawColorSpace::ProPhotoRgb, RawColorSpace::Camera]
+	}
+}
+
 // Aims for interoperable compatibility with:
 // https://www.adobe.com/devnet-apps/photoshop/fileformatashtml/#:~:text=%27mixr%27%20%3D%20Channel%20Mixer
 // https://www.adobe.com/devnet-apps/photoshop/fileformatashtml/#:~:text=Lab%20color%20only-,Channel%20Mixer,-Key%20is%20%27mixr
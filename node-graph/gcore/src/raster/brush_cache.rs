@@ -61,7 +61,7 @@ impl BrushCacheImpl {
 		let strokes = input[num_blended_strokes..].to_vec();
 		if !strokes.is_empty() && self.prev_input.len() > num_blended_strokes {
 			let last_stroke = &self.prev_input[num_blended_strokes];
-			let same_style = strokes[0].style == last_stroke.style;
+			let same_style = strokes[0].style == last_stroke.style && strokes[0].source == last_stroke.source;
 			let prev_points = last_stroke.compute_blit_points();
 			let new_points = strokes[0].compute_blit_points();
 			let is_point_prefix = new_points.get(..prev_points.len()) == Some(&prev_points);
@@ -70,12 +70,40 @@ impl Hash for BrushInputSample {
 	}
 }
 
+/// Where a brush stroke takes the pixels it paints from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BrushSource {
+	/// Paints with the color of the brush style.
+	#[default]
+	Color,
+	/// Paints with the image found at this offset from each blit point, in layer space.
+	Clone { offset: DVec2 },
+	/// Paints like `Clone`, but shifts the colors to match the luminance and color around each painted pixel.
+	Heal { offset: DVec2 },
+}
+
+impl Hash for BrushSource {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		core::mem::discriminant(self).hash(state);
+		match self {
+			BrushSource::Color => {}
+			BrushSource::Clone { offset } | BrushSource::Heal { offset } => {
+				offset.x.to_bits().hash(state);
+				offset.y.to_bits().hash(state);
+			}
+		}
+	}
+}
+
 /// The parameters for a single stroke brush.
 #[derive(Clone, Debug, PartialEq, Hash, Default, DynAny)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BrushStroke {
 	pub style: BrushStyle,
 	pub trace: Vec<BrushInputSample>,
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub source: BrushSource,
 }
 
 impl BrushStroke {
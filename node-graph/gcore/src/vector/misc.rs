@@ -18,21 +18,39 @@ pub enum BooleanOperation {
 	SubtractBack,
 	Intersect,
 	Difference,
+	/// Splits all shapes into their non-overlapping pieces, each keeping the style of the topmost shape covering it.
+	Divide,
+	/// Removes the hidden parts of every shape, so no shape overlaps another while the artwork looks unchanged.
+	Trim,
+	/// Removes the hidden parts of every shape like Trim does, then combines the shapes which share the same fill.
+	Merge,
 }
 
 impl BooleanOperation {
-	pub fn list() -> [BooleanOperation; 5] {
+	pub fn list() -> [BooleanOperation; 8] {
 		[
 			BooleanOperation::Union,
 			BooleanOperation::SubtractFront,
 			BooleanOperation::SubtractBack,
 			BooleanOperation::Intersect,
 			BooleanOperation::Difference,
+			BooleanOperation::Divide,
+			BooleanOperation::Trim,
+			BooleanOperation::Merge,
 		]
 	}
 
-	pub fn icons() -> [&'static str; 5] {
-		["BooleanUnion", "BooleanSubtractFront", "BooleanSubtractBack", "BooleanIntersect", "BooleanDifference"]
+	pub fn icons() -> [&'static str; 8] {
+		[
+			"BooleanUnion",
+			"BooleanSubtractFront",
+			"BooleanSubtractBack",
+			"BooleanIntersect",
+			"BooleanDifference",
+			"BooleanDivide",
+			"BooleanTrim",
+			"BooleanMerge",
+		]
 	}
 }
 
@@ -44,6 +62,9 @@ impl core::fmt::Display for BooleanOperation {
 			BooleanOperation::SubtractBack => write!(f, "Subtract Back"),
 			BooleanOperation::Intersect => write!(f, "Intersect"),
 			BooleanOperation::Difference => write!(f, "Difference"),
+			BooleanOperation::Divide => write!(f, "Divide"),
+			BooleanOperation::Trim => write!(f, "Trim"),
+			BooleanOperation::Merge => write!(f, "Merge"),
 		}
 	}
 }
@@ -1,7 +1,7 @@
 pub mod value;
 
 use crate::document::value::TaggedValue;
-use crate::proto::{ConstructionArgs, ProtoNetwork, ProtoNode, ProtoNodeInput};
+use crate::proto::{ConstructionArgs, GraphError, GraphErrorType, ProtoNetwork, ProtoNode, ProtoNodeInput};
 use dyn_any::DynAny;
 use glam::IVec2;
 use graphene_core::memo::MemoHashGuard;
@@ -764,6 +764,51 @@ impl NodeNetwork {
 		network
 	}
 
+	/// Replaces the hardcoded value of an input of the node at the given path, which may lead into nested networks.
+	/// The input must already be a [`NodeInput::Value`] and the new value must have the same type as the value it replaces.
+	pub fn set_input_value(&mut self, node_path: &[NodeId], input_index: usize, value: TaggedValue) -> Result<(), GraphError> {
+		let Some((node_id, network_path)) = node_path.split_last() else {
+			return Err(GraphError {
+				node_path: Vec::new(),
+				identifier: "".into(),
+				error: GraphErrorType::OverrideEmptyPath,
+			});
+		};
+		let node = self.nested_network_mut(network_path).and_then(|network| network.nodes.get_mut(node_id));
+		let Some(node) = node else {
+			return Err(GraphError {
+				node_path: node_path.to_vec(),
+				identifier: "".into(),
+				error: GraphErrorType::NodeNotFound(*node_id),
+			});
+		};
+
+		let identifier = match &node.implementation {
+			DocumentNodeImplementation::ProtoNode(identifier) => identifier.name.clone(),
+			_ => "".into(),
+		};
+		let error = |error| GraphError {
+			node_path: node_path.to_vec(),
+			identifier: identifier.clone(),
+			error,
+		};
+
+		let input = node.inputs.get_mut(input_index).ok_or_else(|| error(GraphErrorType::OverrideNotValue { index: input_index }))?;
+		replace_input_value(input, input_index, value).map_err(error)
+	}
+
+	/// Replaces the hardcoded value of one of this network's exports, following the same rules as [`NodeNetwork::set_input_value`].
+	pub fn set_export_value(&mut self, export_index: usize, value: TaggedValue) -> Result<(), GraphError> {
+		let error = |error| GraphError {
+			node_path: Vec::new(),
+			identifier: "Exports".into(),
+			error,
+		};
+
+		let export = self.exports.get_mut(export_index).ok_or_else(|| error(GraphErrorType::OverrideNotValue { index: export_index }))?;
+		replace_input_value(export, export_index, value).map_err(error)
+	}
+
 	/// Is the node being used directly as an output?
 	pub fn outputs_contain(&self, node_id_to_check: NodeId) -> bool {
 		self.exports
@@ -797,6 +842,20 @@ impl NodeNetwork {
 	}
 }
 
+/// Swaps the value of a [`NodeInput::Value`] for one of the same type.
+fn replace_input_value(input: &mut NodeInput, index: usize, value: TaggedValue) -> Result<(), GraphErrorType> {
+	let Some(mut tagged_value) = input.as_value_mut() else {
+		return Err(GraphErrorType::OverrideNotValue { index });
+	};
+	if tagged_value.ty() != value.ty() {
+		let (expected, found) = (tagged_value.ty(), value.ty().to_string());
+		return Err(GraphErrorType::OverrideTypeMismatch { index, expected, found });
+	}
+
+	*tagged_value = value;
+	Ok(())
+}
+
 /// Functions for compiling the network
 impl NodeNetwork {
 	/// Replace all references in the graph of a node ID with a new node ID defined by the function `f`.
@@ -1631,6 +1690,38 @@ mod test {
 		assert_eq!(ids, vec![NodeId(11), NodeId(10010)], "Should only contain identity and values");
 	}
 
+	#[test]
+	fn override_input_value() {
+		let mut network = NodeNetwork {
+			exports: vec![NodeInput::node(NodeId(1), 0)],
+			nodes: [(
+				NodeId(1),
+				DocumentNode {
+					inputs: vec![NodeInput::network(concrete!(u32), 0), NodeInput::value(TaggedValue::F64(1.), true)],
+					..Default::default()
+				},
+			)]
+			.into_iter()
+			.collect(),
+			..Default::default()
+		};
+
+		network.set_input_value(&[NodeId(1)], 1, TaggedValue::F64(2.)).expect("A value of the same type should be accepted");
+		assert_eq!(network.nodes[&NodeId(1)].inputs[1], NodeInput::value(TaggedValue::F64(2.), true));
+
+		let error = network.set_input_value(&[NodeId(1)], 1, TaggedValue::U32(2)).unwrap_err();
+		assert!(
+			matches!(error.error, GraphErrorType::OverrideTypeMismatch { index: 1, .. }),
+			"A value of a different type should be rejected"
+		);
+
+		let error = network.set_input_value(&[NodeId(1)], 0, TaggedValue::U32(2)).unwrap_err();
+		assert!(matches!(error.error, GraphErrorType::OverrideNotValue { index: 0 }), "An input which isn't a value should be rejected");
+
+		let error = network.set_input_value(&[], 1, TaggedValue::F64(2.)).unwrap_err();
+		assert!(matches!(error.error, GraphErrorType::OverrideEmptyPath), "An empty node path should be rejected");
+	}
+
 	// TODO: Write more tests
 	// #[test]
 	// fn out_of_order_duplicate() {
@@ -210,6 +210,10 @@ tagged_value! {
 	CellularDistanceFunction(graphene_core::raster::CellularDistanceFunction),
 	CellularReturnType(graphene_core::raster::CellularReturnType),
 	DomainWarpType(graphene_core::raster::DomainWarpType),
+	RawDemosaicAlgorithm(graphene_core::raster::RawDemosaicAlgorithm),
+	RawWhiteBalance(graphene_core::raster::RawWhiteBalance),
+	RawHighlightRecovery(graphene_core::raster::RawHighlightRecovery),
+	RawColorSpace(graphene_core::raster::RawColorSpace),
 	RelativeAbsolute(graphene_core::raster::RelativeAbsolute),
 	SelectiveColorChoice(graphene_core::raster::SelectiveColorChoice),
 	LineCap(graphene_core::vector::style::LineCap),
@@ -261,8 +265,8 @@ impl TaggedValue {
 		}
 
 		fn to_color(input: &str) -> Option<Color> {
-			// String syntax (e.g. "000000ff")
-			if input.starts_with('"') && input.ends_with('"') {
+			// String syntax (e.g. "000000ff" or #000000ff)
+			if (input.starts_with('"') && input.ends_with('"')) || input.starts_with('#') {
 				let color = input.trim().trim_matches('"').trim().trim_start_matches('#');
 				match color.len() {
 					6 => return Color::from_rgb_str(color),
@@ -392,6 +396,7 @@ pub enum RenderOutputType {
 	CanvasFrame(graphene_core::SurfaceFrame),
 	Svg(String),
 	Image(Vec<u8>),
+	Pdf(Vec<u8>),
 }
 
 impl Hash for RenderOutput {
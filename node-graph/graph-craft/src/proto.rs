@@ -541,6 +541,9 @@ pub enum GraphErrorType {
 	NoConstructor,
 	InvalidImplementations { inputs: String, error_inputs: Vec<Vec<(usize, (Type, Type))>> },
 	MultipleImplementations { inputs: String, valid: Vec<NodeIOTypes> },
+	OverrideEmptyPath,
+	OverrideNotValue { index: usize },
+	OverrideTypeMismatch { index: usize, expected: Type, found: String },
 }
 impl core::fmt::Debug for GraphErrorType {
 	// TODO: format with the document graph context so the input index is the same as in the graph UI.
@@ -568,6 +571,9 @@ impl core::fmt::Debug for GraphErrorType {
 				)
 			}
 			GraphErrorType::MultipleImplementations { inputs, valid } => write!(f, "Multiple implementations found ({inputs}):\n{valid:#?}"),
+			GraphErrorType::OverrideEmptyPath => write!(f, "No node was given whose input should be overridden"),
+			GraphErrorType::OverrideNotValue { index } => write!(f, "Input {} is not a hardcoded value and can't be overridden", index + 1),
+			GraphErrorType::OverrideTypeMismatch { index, expected, found } => write!(f, "Input {} expects a value of type {expected}, found: {found}", index + 1),
 		}
 	}
 }
@@ -1,12 +1,86 @@
-use crate::document::NodeNetwork;
+use crate::document::value::TaggedValue;
+use crate::document::{NodeId, NodeInput, NodeNetwork};
 use crate::graphene_compiler::Compiler;
-use crate::proto::ProtoNetwork;
+use crate::proto::{GraphError, ProtoNetwork};
+use std::collections::HashMap;
 
 pub fn load_network(document_string: &str) -> NodeNetwork {
 	let document: serde_json::Value = serde_json::from_str(document_string).expect("Failed to parse document");
 	serde_json::from_value::<NodeNetwork>(document["network_interface"]["network"].clone()).expect("Failed to parse document")
 }
 
+/// An input of the document's root network which can be given a new value before compilation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NetworkInputLocation {
+	Export(usize),
+	Node { node_id: NodeId, input_index: usize },
+}
+
+/// Collects the names under which the inputs of the document's root network can be addressed, in the form `Node Name.Input Name`.
+/// Nodes are named by their display name, falling back to the name of their definition (such as `Merge`), and exports are named `Exports.0`, `Exports.1`, etc.
+pub fn load_input_names(document_string: &str) -> HashMap<String, NetworkInputLocation> {
+	let document: serde_json::Value = serde_json::from_str(document_string).expect("Failed to parse document");
+	let interface = &document["network_interface"];
+
+	let export_count = interface["network"]["exports"].as_array().map_or(0, Vec::len);
+	let mut names: HashMap<_, _> = (0..export_count).map(|index| (format!("Exports.{index}"), NetworkInputLocation::Export(index))).collect();
+
+	let node_metadata = interface["network_metadata"]["persistent_metadata"]["node_metadata"].as_array().into_iter().flatten();
+	for entry in node_metadata {
+		let Some(node_id) = entry[0].as_u64().map(NodeId) else { continue };
+		let metadata = &entry[1]["persistent_metadata"];
+
+		let node_name = match metadata["display_name"].as_str() {
+			Some(display_name) if !display_name.is_empty() => display_name,
+			_ => metadata["reference"].as_str().unwrap_or_default(),
+		};
+		if node_name.is_empty() {
+			continue;
+		}
+
+		for (input_index, properties) in metadata["input_properties"].as_array().into_iter().flatten().enumerate() {
+			let Some(input_name) = properties["input_data"]["input_name"].as_str().filter(|name| !name.is_empty()) else {
+				continue;
+			};
+			names.insert(format!("{node_name}.{input_name}"), NetworkInputLocation::Node { node_id, input_index });
+		}
+	}
+
+	names
+}
+
+/// Resolves a name from [`load_input_names`], or a location given as `node_id.input_index`, to an input of the root network.
+pub fn find_input(names: &HashMap<String, NetworkInputLocation>, name: &str) -> Option<NetworkInputLocation> {
+	if let Some(location) = names.get(name) {
+		return Some(*location);
+	}
+
+	let (node_id, input_index) = name.split_once('.')?;
+	Some(NetworkInputLocation::Node {
+		node_id: NodeId(node_id.trim().parse().ok()?),
+		input_index: input_index.trim().parse().ok()?,
+	})
+}
+
+/// Parses the textual value as the type of the value it replaces and writes it into the root network.
+pub fn override_input(network: &mut NodeNetwork, location: NetworkInputLocation, value: &str) -> Result<(), GraphError> {
+	let current = match location {
+		NetworkInputLocation::Export(index) => network.exports.get(index),
+		NetworkInputLocation::Node { node_id, input_index } => network.nodes.get(&node_id).and_then(|node| node.inputs.get(input_index)),
+	};
+
+	// Text that can't be parsed as the expected type is kept as a string so the mismatch gets reported like any other
+	let ty = current.and_then(NodeInput::as_value).map(TaggedValue::ty);
+	let value = ty
+		.and_then(|ty| TaggedValue::from_primitive_string(value, &ty))
+		.unwrap_or_else(|| TaggedValue::String(value.to_string()));
+
+	match location {
+		NetworkInputLocation::Export(index) => network.set_export_value(index, value),
+		NetworkInputLocation::Node { node_id, input_index } => network.set_input_value(&[node_id], input_index, value),
+	}
+}
+
 pub fn compile(network: NodeNetwork) -> ProtoNetwork {
 	let compiler = Compiler {};
 	compiler.compile_single(network).unwrap()
@@ -0,0 +1,101 @@
+use glam::{DAffine2, DVec2};
+use graph_craft::document::value::{RenderOutput, TaggedValue};
+use graph_craft::graphene_compiler::Executor;
+use graphene_core::application_io::{ExportFormat, RenderConfig, TimingInformation};
+use graphene_core::transform::Footprint;
+use graphene_std::wasm_application_io::RenderOutputType;
+use interpreted_executor::dynamic_executor::DynamicExecutor;
+use std::error::Error;
+use std::path::Path;
+use std::time::Duration;
+
+/// The file formats which can be written by the `export` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFileType {
+	Svg,
+	Png,
+}
+
+impl ExportFileType {
+	/// Infers the file type from the extension of the output path.
+	pub fn from_path(path: &Path) -> Option<Self> {
+		match path.extension()?.to_str()?.to_lowercase().as_str() {
+			"svg" => Some(Self::Svg),
+			"png" => Some(Self::Png),
+			_ => None,
+		}
+	}
+}
+
+/// Settings describing the region and moment of the document which is rendered by the `export` command.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExportSettings {
+	pub file_type: ExportFileType,
+	/// Top left corner of the exported region in document space
+	pub origin: DVec2,
+	/// Size of the exported region in document space
+	pub size: DVec2,
+	pub scale_factor: f64,
+	pub transparent_background: bool,
+	/// Animation time in seconds
+	pub time: f64,
+}
+
+impl ExportSettings {
+	/// Builds the render config passed to the graph, mirroring the one the editor uses for its own exports.
+	pub fn render_config(&self) -> RenderConfig {
+		let transform = DAffine2::from_scale(DVec2::splat(self.scale_factor)) * DAffine2::from_translation(-self.origin);
+
+		RenderConfig {
+			viewport: Footprint {
+				transform,
+				resolution: (self.size * self.scale_factor).round().as_uvec2(),
+				..Default::default()
+			},
+			export_format: ExportFormat::Svg,
+			time: TimingInformation {
+				time: self.time,
+				animation_time: Duration::from_secs_f64(self.time.max(0.)),
+			},
+			view_mode: Default::default(),
+			hide_artboards: self.transparent_background,
+			for_export: true,
+		}
+	}
+}
+
+/// Evaluates the graph once and returns the rendered SVG document.
+pub async fn render_svg(executor: &DynamicExecutor, render_config: RenderConfig) -> Result<String, Box<dyn Error>> {
+	let result = executor.execute(render_config).await?;
+
+	let TaggedValue::RenderOutput(RenderOutput { data: RenderOutputType::Svg(svg), .. }) = result else {
+		return Err(format!("Incorrect render type for exporting (expected RenderOutput::Svg), got {result:?}").into());
+	};
+
+	Ok(svg)
+}
+
+/// Rasterizes an SVG document on the CPU into an encoded PNG file.
+pub fn rasterize_png(svg: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+	let tree = resvg::usvg::Tree::from_str(svg, &resvg::usvg::Options::default())?;
+
+	let size = tree.size().to_int_size();
+	let mut pixmap = resvg::tiny_skia::Pixmap::new(size.width(), size.height()).ok_or("The exported image must have a non-zero size")?;
+	resvg::render(&tree, resvg::tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+	Ok(pixmap.encode_png()?)
+}
+
+/// Renders the document with the given settings and writes the result to `output`.
+pub async fn export_document(executor: &DynamicExecutor, settings: &ExportSettings, output: &Path) -> Result<(), Box<dyn Error>> {
+	let svg = render_svg(executor, settings.render_config()).await?;
+
+	match settings.file_type {
+		ExportFileType::Svg => std::fs::write(output, svg)?,
+		ExportFileType::Png => std::fs::write(output, rasterize_png(&svg)?)?,
+	}
+
+	log::info!("exported {}", output.display());
+
+	Ok(())
+}
@@ -1,4 +1,8 @@
+mod export;
+mod overrides;
+
 use clap::{Args, Parser, Subcommand};
+use export::{ExportFileType, ExportSettings};
 use fern::colors::{Color, ColoredLevelConfig};
 use futures::executor::block_on;
 use graph_craft::document::*;
@@ -56,6 +60,47 @@ enum Command {
 		#[clap(long, short = 'l')]
 		run_loop: bool,
 	},
+	/// Render the document without a window or GPU and write the result to an SVG or PNG file.
+	#[clap(alias = "render")]
+	Export {
+		/// Path to the .graphite document
+		document: PathBuf,
+
+		/// Path of the file to write
+		output: PathBuf,
+
+		/// File type of the output, inferred from the output's extension if omitted
+		#[clap(long, short = 'f', value_enum)]
+		file_type: Option<ExportFileType>,
+
+		/// Width of the exported region in document units
+		#[clap(long, default_value_t = 1920.)]
+		width: f64,
+
+		/// Height of the exported region in document units
+		#[clap(long, default_value_t = 1080.)]
+		height: f64,
+
+		/// X coordinate of the top left corner of the exported region in document units
+		#[clap(long, default_value_t = 0., allow_negative_numbers = true)]
+		x: f64,
+
+		/// Y coordinate of the top left corner of the exported region in document units
+		#[clap(long, default_value_t = 0., allow_negative_numbers = true)]
+		y: f64,
+
+		/// Factor by which the exported region is scaled to obtain the output resolution
+		#[clap(long, short = 's', default_value_t = 1.)]
+		scale: f64,
+
+		/// Omit the artboard backgrounds so areas without artwork are left transparent
+		#[clap(long, short = 't')]
+		transparent: bool,
+
+		/// Animation time in seconds at which the document is rendered
+		#[clap(long, default_value_t = 0.)]
+		time: f64,
+	},
 }
 
 #[derive(Debug, Args)]
@@ -63,6 +108,14 @@ struct GlobalOpts {
 	/// Verbosity level (can be specified multiple times)
 	#[clap(long, short, global = true, action = clap::ArgAction::Count)]
 	verbose: u8,
+
+	/// Override an input of the document's root network, given as `Node Name.Input Name=value` (can be specified multiple times)
+	#[clap(long = "set", global = true, value_name = "NAME=VALUE")]
+	overrides: Vec<String>,
+
+	/// Path to a JSON file, or a TOML file with the `.toml` extension, mapping input names of the document's root network to the values overriding them
+	#[clap(long, global = true)]
+	overrides_file: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -76,20 +129,28 @@ async fn main() -> Result<(), Box<dyn Error>> {
 	let document_path = match app.command {
 		Command::Compile { ref document, .. } => document,
 		Command::Run { ref document, .. } => document,
+		Command::Export { ref document, .. } => document,
 	};
 
 	let document_string = std::fs::read_to_string(document_path).expect("Failed to read document");
 
-	log::info!("creating gpu context",);
-	let mut application_io = block_on(WasmApplicationIo::new());
+	// Exporting renders through the SVG pipeline, so it doesn't require a GPU
+	let headless = matches!(app.command, Command::Export { .. });
+
+	let mut application_io = if headless {
+		WasmApplicationIo::default()
+	} else {
+		log::info!("creating gpu context",);
+		block_on(WasmApplicationIo::new())
+	};
 
 	if let Command::Run { image: Some(ref image_path), .. } = app.command {
 		application_io.resources.insert("null".to_string(), Arc::from(std::fs::read(image_path).expect("Failed to read image")));
 	}
-	let device = application_io.gpu_executor().unwrap().context.device.clone();
+	let device = application_io.gpu_executor().map(|executor| executor.context.device.clone());
 
 	let preferences = EditorPreferences {
-		use_vello: true,
+		use_vello: !headless,
 		..Default::default()
 	};
 	let editor_api = Arc::new(WasmEditorApi {
@@ -99,7 +160,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
 		editor_preferences: Box::new(preferences),
 	});
 
-	let proto_graph = compile_graph(document_string, editor_api)?;
+	let overrides = overrides::collect_overrides(&app.global_opts.overrides, app.global_opts.overrides_file.as_deref())?;
+	let proto_graph = compile_graph(document_string, &overrides, editor_api)?;
 
 	match app.command {
 		Command::Compile { print_proto, .. } => {
@@ -108,6 +170,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 			}
 		}
 		Command::Run { run_loop, .. } => {
+			let device = device.ok_or("Running a document requires a GPU")?;
 			std::thread::spawn(move || {
 				loop {
 					std::thread::sleep(std::time::Duration::from_nanos(10));
@@ -126,6 +189,33 @@ async fn main() -> Result<(), Box<dyn Error>> {
 				std::thread::sleep(std::time::Duration::from_millis(16));
 			}
 		}
+		Command::Export {
+			output,
+			file_type,
+			width,
+			height,
+			x,
+			y,
+			scale,
+			transparent,
+			time,
+			..
+		} => {
+			let file_type = file_type
+				.or_else(|| ExportFileType::from_path(&output))
+				.ok_or("Could not infer the file type from the output path, specify it with --file-type")?;
+			let settings = ExportSettings {
+				file_type,
+				origin: glam::DVec2::new(x, y),
+				size: glam::DVec2::new(width, height),
+				scale_factor: scale,
+				transparent_background: transparent,
+				time,
+			};
+
+			let executor = create_executor(proto_graph)?;
+			export::export_document(&executor, &settings, &output).await?;
+		}
 	}
 
 	Ok(())
@@ -180,9 +270,10 @@ fn fix_nodes(network: &mut NodeNetwork) {
 		}
 	}
 }
-fn compile_graph(document_string: String, editor_api: Arc<WasmEditorApi>) -> Result<ProtoNetwork, Box<dyn Error>> {
+fn compile_graph(document_string: String, overrides: &[(String, String)], editor_api: Arc<WasmEditorApi>) -> Result<ProtoNetwork, Box<dyn Error>> {
 	let mut network = load_network(&document_string);
 	fix_nodes(&mut network);
+	overrides::apply_overrides(&mut network, &document_string, overrides)?;
 
 	let wrapped_network = wrap_network_in_scope(network.clone(), editor_api);
 	let compiler = Compiler {};
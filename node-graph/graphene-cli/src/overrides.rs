@@ -0,0 +1,73 @@
+use graph_craft::document::NodeNetwork;
+use graph_craft::proto::GraphErrors;
+use graph_craft::util::{find_input, load_input_names, override_input};
+use std::error::Error;
+use std::path::Path;
+
+/// Collects the input overrides given as `NAME=VALUE` pairs on the command line and as a table in a JSON or TOML file, told apart by the `.toml` extension.
+/// Pairs from the command line come last so they take precedence over the file.
+pub fn collect_overrides(pairs: &[String], file: Option<&Path>) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+	let mut overrides = Vec::new();
+
+	if let Some(file) = file {
+		let contents = std::fs::read_to_string(file)?;
+		let json: serde_json::Value = match file.extension().and_then(|extension| extension.to_str()) {
+			Some(extension) if extension.eq_ignore_ascii_case("toml") => toml::from_str(&contents)?,
+			_ => serde_json::from_str(&contents)?,
+		};
+		let object = json.as_object().ok_or("The overrides file should contain a table mapping input names to values")?;
+
+		for (name, value) in object {
+			let value = json_to_primitive_string(value).ok_or_else(|| format!("Unsupported value for override \"{name}\": {value}"))?;
+			overrides.push((name.clone(), value));
+		}
+	}
+
+	for pair in pairs {
+		let (name, value) = pair.split_once('=').ok_or_else(|| format!("Override \"{pair}\" should have the form NAME=VALUE"))?;
+		overrides.push((name.trim().to_string(), value.to_string()));
+	}
+
+	Ok(overrides)
+}
+
+/// Converts a JSON value into the textual form parsed by `TaggedValue::from_primitive_string`.
+fn json_to_primitive_string(value: &serde_json::Value) -> Option<String> {
+	match value {
+		serde_json::Value::Bool(value) => Some(value.to_string()),
+		serde_json::Value::Number(value) => Some(value.to_string()),
+		serde_json::Value::String(value) => Some(value.clone()),
+		// Vectors are written as arrays of their components
+		serde_json::Value::Array(components) => components
+			.iter()
+			.map(|component| component.as_f64().map(|x| x.to_string()))
+			.collect::<Option<Vec<_>>>()
+			.map(|components| components.join(",")),
+		serde_json::Value::Null | serde_json::Value::Object(_) => None,
+	}
+}
+
+/// Writes the overrides into the root network before it gets compiled, reporting every input that couldn't be overridden.
+pub fn apply_overrides(network: &mut NodeNetwork, document_string: &str, overrides: &[(String, String)]) -> Result<(), Box<dyn Error>> {
+	if overrides.is_empty() {
+		return Ok(());
+	}
+
+	let names = load_input_names(document_string);
+
+	let mut errors = GraphErrors::new();
+	for (name, value) in overrides {
+		let location = find_input(&names, name).ok_or_else(|| format!("The document has no input named \"{name}\""))?;
+
+		log::info!("overriding {name} with {value}");
+		if let Err(error) = override_input(network, location, value) {
+			errors.push(error);
+		}
+	}
+
+	if !errors.is_empty() {
+		return Err(errors.iter().map(|e| format!("{e:?}")).collect::<Vec<_>>().join("\n").into());
+	}
+
+	Ok(())
+}
@@ -1,5 +1,6 @@
+use crate::healing::{SummedAreaTable, heal_pixel, pixel_bounds};
 use crate::raster::{BlendImageTupleNode, ExtendImageToBoundsNode, blend_image_closure};
-use glam::{DAffine2, DVec2};
+use glam::{DAffine2, DVec2, IVec2};
 use graph_craft::generic::FnNode;
 use graph_craft::proto::FutureWrapperNode;
 use graphene_core::raster::adjustments::blend_colors;
@@ -10,7 +11,7 @@ use graphene_core::raster::{Alpha, Bitmap, BlendMode, Color, Pixel, Sample};
 use graphene_core::transform::{Transform, TransformMut};
 use graphene_core::value::{ClonedNode, CopiedNode, ValueNode};
 use graphene_core::vector::VectorDataTable;
-use graphene_core::vector::brush_stroke::{BrushStroke, BrushStyle};
+use graphene_core::vector::brush_stroke::{BrushSource, BrushStroke, BrushStyle};
 use graphene_core::{Ctx, GraphicElement, Node};
 
 #[node_macro::node(category("Debug"))]
@@ -136,6 +137,77 @@ where
 	target
 }
 
+/// Stamps the brush texture at each position like `blit`, but paints with the pixels of `source` found at `offset` from the stamp instead of the texture's color.
+/// With a healing radius (in layer space), each pixel is also shifted to match the mean color around its destination instead of around its source.
+fn blit_cloned(mut target: ImageFrameTable<Color>, texture: &Image<Color>, positions: &[DVec2], source: &ImageFrameTable<Color>, offset: DVec2, heal_radius: Option<f64>) -> ImageFrameTable<Color> {
+	let source_image = source.one_instance().instance;
+	if positions.is_empty() || source_image.width == 0 || source_image.height == 0 || source.transform().matrix2.determinant() == 0. {
+		return target;
+	}
+
+	let target_width = target.one_instance().instance.width;
+	let target_height = target.one_instance().instance.height;
+	let target_size = DVec2::new(target_width as f64, target_height as f64);
+
+	let texture_size = DVec2::new(texture.width as f64, texture.height as f64);
+	let source_size = DVec2::new(source_image.width as f64, source_image.height as f64);
+
+	let document_to_target = DAffine2::from_translation(-texture_size / 2.) * DAffine2::from_scale(target_size) * target.transform().inverse();
+	// Maps a target pixel to the source image pixel below it, and to the one found at the clone offset from it in layer space
+	let target_to_destination = DAffine2::from_scale(source_size) * source.transform().inverse() * target.transform() * DAffine2::from_scale(1. / target_size);
+	let target_to_source = DAffine2::from_scale(source_size) * source.transform().inverse() * DAffine2::from_translation(offset) * target.transform() * DAffine2::from_scale(1. / target_size);
+
+	let healing = heal_radius.map(|radius| {
+		let radius = (radius * source_size / source.transform().decompose_scale()).max_element().ceil() as i32;
+		let margin = IVec2::splat(radius + 1);
+		let (destination_min, destination_max) = pixel_bounds(target_to_destination * DAffine2::from_scale(target_size));
+		let (source_min, source_max) = pixel_bounds(target_to_source * DAffine2::from_scale(target_size));
+		let destination_table = SummedAreaTable::new(source_image, destination_min - margin, destination_max + margin);
+		let source_table = SummedAreaTable::new(source_image, source_min - margin, source_max + margin);
+		(radius, destination_table, source_table)
+	});
+
+	for &position in positions {
+		let start = document_to_target.transform_point2(position).round();
+		let stop = start + texture_size;
+
+		// Half-open integer ranges [start, stop).
+		let clamp_start = start.clamp(DVec2::ZERO, target_size).as_uvec2();
+		let clamp_stop = stop.clamp(DVec2::ZERO, target_size).as_uvec2();
+
+		let blit_area_offset = (clamp_start.as_dvec2() - start).as_uvec2().min(texture_size.as_uvec2());
+		let blit_area_dimensions = (clamp_stop - clamp_start).min(texture_size.as_uvec2() - blit_area_offset);
+
+		for y in blit_area_offset.y..blit_area_offset.y + blit_area_dimensions.y {
+			for x in blit_area_offset.x..blit_area_offset.x + blit_area_dimensions.x {
+				let coverage = texture.data[(y * texture.width + x) as usize].a();
+				if coverage == 0. {
+					continue;
+				}
+
+				let (target_x, target_y) = (x + clamp_start.x, y + clamp_start.y);
+				let target_center = DVec2::new(target_x as f64, target_y as f64) + 0.5;
+				let source_position = target_to_source.transform_point2(target_center).floor();
+				if source_position.x < 0. || source_position.y < 0. || source_position.x >= source_size.x || source_position.y >= source_size.y {
+					continue;
+				}
+				let Some(mut src_pixel) = source_image.get_pixel(source_position.x as u32, source_position.y as u32) else {
+					continue;
+				};
+				if let Some((radius, destination_table, source_table)) = &healing {
+					let destination = target_to_destination.transform_point2(target_center).floor().as_ivec2();
+					src_pixel = heal_pixel(src_pixel, destination, source_position.as_ivec2(), *radius, destination_table, source_table);
+				}
+
+				let dst_pixel = &mut target.one_instance_mut().instance.data[(target_y * target_width + target_x) as usize];
+				*dst_pixel = blend_colors(src_pixel.multiplied_alpha(coverage), *dst_pixel, BlendMode::Normal, 1.);
+			}
+		}
+	}
+
+	target
+}
+
 pub async fn create_brush_texture(brush_style: &BrushStyle) -> Image<Color> {
 	let stamp = brush_stamp_generator(brush_style.diameter, brush_style.color, brush_style.hardness, brush_style.flow);
 	let transform = DAffine2::from_scale_angle_translation(DVec2::splat(brush_style.diameter), 0., -DVec2::splat(brush_style.diameter / 2.));
@@ -146,6 +218,38 @@ pub async fn create_brush_texture(brush_style: &BrushStyle) -> Image<Color> {
 	image.one_instance().instance.clone()
 }
 
+/// Renders the coverage of the strokes' stamps onto the pixel grid of an image of the given size and transform, for the nodes which use strokes as masks.
+pub fn brush_stroke_mask(strokes: &[BrushStroke], width: u32, height: u32, transform: DAffine2) -> Vec<f32> {
+	let mut mask = vec![0.; width as usize * height as usize];
+	if width == 0 || height == 0 || transform.matrix2.determinant() == 0. {
+		return mask;
+	}
+
+	let pixel_to_layer = transform * DAffine2::from_scale(DVec2::new(1. / width as f64, 1. / height as f64));
+	let layer_to_pixel = pixel_to_layer.inverse();
+	let area = pixel_to_layer.transform_vector2(DVec2::ONE);
+
+	for stroke in strokes {
+		let stamp = brush_stamp_generator(stroke.style.diameter, Color::WHITE, stroke.style.hardness, stroke.style.flow);
+		for position in stroke.compute_blit_points() {
+			let (min, max) = pixel_bounds(layer_to_pixel * DAffine2::from_translation(position) * stamp.transform());
+			let (min, max) = (min.max(IVec2::ZERO), max.min(IVec2::new(width as i32, height as i32)));
+
+			for y in min.y..max.y {
+				for x in min.x..max.x {
+					let layer_position = pixel_to_layer.transform_point2(DVec2::new(x as f64, y as f64));
+					let Some(coverage) = stamp.sample(layer_position - position, area) else { continue };
+
+					let value = &mut mask[y as usize * width as usize + x as usize];
+					*value += coverage.a() * (1. - *value);
+				}
+			}
+		}
+	}
+
+	mask
+}
+
 macro_rules! inline_blend_funcs {
 	($bg:ident, $fg:ident, $blend_mode:ident, $opacity:ident, [$($mode:path,)*]) => {
 		match std::hint::black_box($blend_mode) {
@@ -253,13 +357,6 @@ async fn brush(_: impl Ctx, image_frame_table: ImageFrameTable<Color>, bounds: I
 			let stroke_origin_in_layer = bbox.start - snap_offset - DVec2::splat(stroke.style.diameter / 2.);
 			let stroke_to_layer = DAffine2::from_translation(stroke_origin_in_layer) * DAffine2::from_scale(stroke_size);
 
-			// let normal_blend = BlendColorPairNode::new(ValueNode::new(CopiedNode::new(BlendMode::Normal)), ValueNode::new(CopiedNode::new(100.)));
-			let normal_blend = FnNode::new(|(a, b)| blend_colors(a, b, BlendMode::Normal, 1.));
-			let blit_node = BlitNode::new(
-				FutureWrapperNode::new(ClonedNode::new(brush_texture)),
-				FutureWrapperNode::new(ClonedNode::new(positions)),
-				FutureWrapperNode::new(ClonedNode::new(normal_blend)),
-			);
 			let blit_target = if idx == 0 {
 				let target = core::mem::take(&mut brush_plan.first_stroke_texture);
 				ExtendImageToBoundsNode::new(CopiedNode::new(stroke_to_layer)).eval(target)
@@ -269,7 +366,22 @@ async fn brush(_: impl Ctx, image_frame_table: ImageFrameTable<Color>, bounds: I
 				// EmptyImageNode::new(CopiedNode::new(stroke_to_layer), CopiedNode::new(Color::TRANSPARENT)).eval(())
 			};
 
-			blit_node.eval(blit_target).await
+			match stroke.source {
+				BrushSource::Color => {
+					// let normal_blend = BlendColorPairNode::new(ValueNode::new(CopiedNode::new(BlendMode::Normal)), ValueNode::new(CopiedNode::new(100.)));
+					let normal_blend = FnNode::new(|(a, b)| blend_colors(a, b, BlendMode::Normal, 1.));
+					let blit_node = BlitNode::new(
+						FutureWrapperNode::new(ClonedNode::new(brush_texture)),
+						FutureWrapperNode::new(ClonedNode::new(positions)),
+						FutureWrapperNode::new(ClonedNode::new(normal_blend)),
+					);
+					blit_node.eval(blit_target).await
+				}
+				// Cloned strokes sample the layer as painted so far, so they can pick up earlier strokes
+				BrushSource::Clone { offset } => blit_cloned(blit_target, &brush_texture, &positions, &actual_image, offset, None),
+				// Healing matches the shading of an area twice the brush size, so the blemish being covered only weakly affects it
+				BrushSource::Heal { offset } => blit_cloned(blit_target, &brush_texture, &positions, &actual_image, offset, Some(stroke.style.diameter)),
+			}
 		};
 
 		// Cache image before doing final blend, and store final stroke texture.
@@ -362,10 +474,85 @@ mod test {
 					spacing: 20.,
 					blend_mode: BlendMode::Normal,
 				},
+				source: BrushSource::Color,
 			}],
 			BrushCache::new_proto(),
 		)
 		.await;
 		assert_eq!(image.width(), 20);
 	}
+
+	#[tokio::test]
+	async fn test_brush_clone_source() {
+		// A gradient from black on the left to white on the right
+		let mut gradient = Image::new(20, 20, Color::TRANSPARENT);
+		for (index, pixel) in gradient.data.iter_mut().enumerate() {
+			let value = (index % 20) as f32 / 19.;
+			*pixel = Color::from_rgbaf32_unchecked(value, value, value, 1.);
+		}
+		let mut image_frame_table = ImageFrameTable::new(gradient.clone());
+		*image_frame_table.transform_mut() = DAffine2::from_scale(DVec2::splat(20.));
+
+		let image = brush(
+			(),
+			image_frame_table,
+			ImageFrameTable::<Color>::default(),
+			vec![BrushStroke {
+				trace: vec![crate::vector::brush_stroke::BrushInputSample { position: DVec2::new(5., 10.) }],
+				style: BrushStyle {
+					color: Color::WHITE,
+					diameter: 4.,
+					hardness: 100.,
+					flow: 100.,
+					spacing: 20.,
+					blend_mode: BlendMode::Normal,
+				},
+				source: BrushSource::Clone { offset: DVec2::new(10., 0.) },
+			}],
+			BrushCache::new_proto(),
+		)
+		.await;
+
+		// The stamp repaints its pixels with those 10 pixels to the right
+		assert_eq!(image.get_pixel(5, 10), gradient.get_pixel(15, 10));
+		// Pixels outside the stamp are left untouched
+		assert_eq!(image.get_pixel(15, 10), gradient.get_pixel(15, 10));
+	}
+
+	#[tokio::test]
+	async fn test_brush_heal_source() {
+		// Darker gray on the left, lighter gray on the right
+		let mut halves = Image::new(40, 20, Color::from_rgbaf32_unchecked(0.5, 0.5, 0.5, 1.));
+		for (index, pixel) in halves.data.iter_mut().enumerate() {
+			if index % 40 >= 20 {
+				*pixel = Color::from_rgbaf32_unchecked(0.7, 0.7, 0.7, 1.);
+			}
+		}
+		let mut image_frame_table = ImageFrameTable::new(halves);
+		*image_frame_table.transform_mut() = DAffine2::from_scale(DVec2::new(40., 20.));
+
+		let image = brush(
+			(),
+			image_frame_table,
+			ImageFrameTable::<Color>::default(),
+			vec![BrushStroke {
+				trace: vec![crate::vector::brush_stroke::BrushInputSample { position: DVec2::new(8., 10.) }],
+				style: BrushStyle {
+					color: Color::WHITE,
+					diameter: 4.,
+					hardness: 100.,
+					flow: 100.,
+					spacing: 20.,
+					blend_mode: BlendMode::Normal,
+				},
+				source: BrushSource::Heal { offset: DVec2::new(20., 0.) },
+			}],
+			BrushCache::new_proto(),
+		)
+		.await;
+
+		// The lighter pixels are shifted to match the shading around the destination
+		let healed = image.get_pixel(8, 10).unwrap();
+		assert!((healed.r() - 0.5).abs() < 1e-5, "{healed:?}");
+	}
 }
@@ -0,0 +1,217 @@
+use glam::{DAffine2, DVec2, IVec2};
+use graph_craft::proto::types::Percentage;
+use graphene_core::raster::image::{Image, ImageFrameTable};
+use graphene_core::transform::Transform;
+use graphene_core::{Color, Ctx, GraphicElement, GraphicGroupTable, RasterFrame};
+
+/// Which pixels of the image a flood fill starting at `seed` reaches, as the coverage of each pixel from zero to one.
+/// Pixels match when none of their premultiplied channels differ from the seed pixel's by more than `tolerance`.
+pub fn flood_fill_mask(image: &Image<Color>, seed: IVec2, tolerance: f32, contiguous: bool, anti_aliasing: bool) -> Vec<f32> {
+	let (width, height) = (image.width as i32, image.height as i32);
+	let mut mask = vec![0.; image.data.len()];
+	if seed.cmplt(IVec2::ZERO).any() || seed.x >= width || seed.y >= height {
+		return mask;
+	}
+
+	let index_of = |pixel: IVec2| pixel.y as usize * width as usize + pixel.x as usize;
+	let seed_color = image.data[index_of(seed)];
+	let matches = |color: Color| {
+		let differences = [color.r() - seed_color.r(), color.g() - seed_color.g(), color.b() - seed_color.b(), color.a() - seed_color.a()];
+		differences.into_iter().all(|difference| difference.abs() <= tolerance)
+	};
+
+	if contiguous {
+		let mut stack = vec![seed];
+		mask[index_of(seed)] = 1.;
+		while let Some(pixel) = stack.pop() {
+			for neighbor in [pixel - IVec2::X, pixel + IVec2::X, pixel - IVec2::Y, pixel + IVec2::Y] {
+				if neighbor.cmplt(IVec2::ZERO).any() || neighbor.x >= width || neighbor.y >= height {
+					continue;
+				}
+				let index = index_of(neighbor);
+				if mask[index] == 0. && matches(image.data[index]) {
+					mask[index] = 1.;
+					stack.push(neighbor);
+				}
+			}
+		}
+	} else {
+		for (coverage, &color) in mask.iter_mut().zip(&image.data) {
+			if matches(color) {
+				*coverage = 1.;
+			}
+		}
+	}
+
+	if anti_aliasing {
+		mask = smooth_mask(&mask, width as usize, height as usize);
+	}
+	mask
+}
+
+/// Softens the stair-stepped edges of a mask by averaging each pixel with its neighbors inside the image.
+fn smooth_mask(mask: &[f32], width: usize, height: usize) -> Vec<f32> {
+	let mut smoothed = vec![0.; mask.len()];
+	for y in 0..height {
+		for x in 0..width {
+			let (mut sum, mut count) = (0., 0.);
+			for neighbor_y in y.saturating_sub(1)..(y + 2).min(height) {
+				for neighbor_x in x.saturating_sub(1)..(x + 2).min(width) {
+					sum += mask[neighbor_y * width + neighbor_x];
+					count += 1.;
+				}
+			}
+			smoothed[y * width + x] = sum / count;
+		}
+	}
+	smoothed
+}
+
+/// The raster images inside a group, each with the transform from the space of the group to its pixel space and its opacity, from bottom to top.
+fn raster_layers<'a>(group: &'a GraphicGroupTable, transform: DAffine2, opacity: f32, images: &mut Vec<(&'a Image<Color>, DAffine2, f32)>) {
+	for instance in group.instances() {
+		let transform = transform * *instance.transform;
+		let opacity = opacity * instance.alpha_blending.opacity;
+		match instance.instance {
+			GraphicElement::GraphicGroup(group) => raster_layers(group, transform, opacity, images),
+			GraphicElement::RasterFrame(RasterFrame::ImageFrame(image_frame)) => {
+				for image in image_frame.instances() {
+					let layer_to_pixel = DAffine2::from_scale(DVec2::new(image.instance.width as f64, image.instance.height as f64)) * (transform * *image.transform).inverse();
+					images.push((image.instance, layer_to_pixel, opacity * image.alpha_blending.opacity));
+				}
+			}
+			_ => {}
+		}
+	}
+}
+
+/// Composites the image over the raster layers beneath it, sampling them at the centers of its pixels.
+/// Vector content has no pixels to sample and is skipped.
+fn composite_over_layers(image: &Image<Color>, pixel_to_layer: DAffine2, layers_below: &GraphicGroupTable) -> Image<Color> {
+	let mut images = Vec::new();
+	raster_layers(layers_below, DAffine2::IDENTITY, 1., &mut images);
+	if images.is_empty() {
+		return image.clone();
+	}
+
+	let mut composite = image.clone();
+	for (index, pixel) in composite.data.iter_mut().enumerate() {
+		let center = DVec2::new((index % image.width as usize) as f64, (index / image.width as usize) as f64) + 0.5;
+		let position = pixel_to_layer.transform_point2(center);
+
+		let mut color = Color::TRANSPARENT;
+		for &(below, layer_to_pixel, opacity) in &images {
+			let sample_position = layer_to_pixel.transform_point2(position).floor();
+			if sample_position.cmplt(DVec2::ZERO).any() || sample_position.x >= below.width as f64 || sample_position.y >= below.height as f64 {
+				continue;
+			}
+			let sample = below.data[sample_position.y as usize * below.width as usize + sample_position.x as usize];
+			color = color.alpha_blend(sample.apply_opacity(opacity));
+		}
+		*pixel = color.alpha_blend(*pixel);
+	}
+	composite
+}
+
+/// Fills the area around the seed point whose colors are within the tolerance of the color there, like a paint bucket.
+/// The area is found again whenever the image changes, so the fill follows edits made upstream. The seed point is in layer space.
+#[node_macro::node(category("Raster"))]
+fn flood_fill(
+	_: impl Ctx,
+	mut image_frame: ImageFrameTable<Color>,
+	seed_point: DVec2,
+	#[default(Color::BLACK)] color: Color,
+	#[default(12.5)] tolerance: Percentage,
+	#[default(true)] contiguous: bool,
+	#[default(true)] anti_aliasing: bool,
+	sample_layers: GraphicGroupTable,
+) -> ImageFrameTable<Color> {
+	let transform = image_frame.transform();
+	if transform.matrix2.determinant() == 0. {
+		return image_frame;
+	}
+
+	let image = image_frame.one_instance_mut().instance;
+	let pixel_to_layer = transform * DAffine2::from_scale(DVec2::new(1. / image.width as f64, 1. / image.height as f64));
+	let seed = pixel_to_layer.inverse().transform_point2(seed_point).floor().as_ivec2();
+
+	let sampled = composite_over_layers(image, pixel_to_layer, &sample_layers);
+	let mask = flood_fill_mask(&sampled, seed, (tolerance / 100.) as f32, contiguous, anti_aliasing);
+
+	let color = Color::from_unassociated_alpha(color.r(), color.g(), color.b(), color.a());
+	for (pixel, coverage) in image.data.iter_mut().zip(mask) {
+		if coverage > 0. {
+			*pixel = pixel.alpha_blend(color.apply_opacity(coverage));
+		}
+	}
+
+	image_frame
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graphene_core::transform::TransformMut;
+
+	/// A black image split in two by a white column.
+	fn divided_image() -> Image<Color> {
+		let mut image = Image::new(5, 3, Color::BLACK);
+		for y in 0..3 {
+			image.data[y * 5 + 2] = Color::WHITE;
+		}
+		image
+	}
+
+	#[test]
+	fn contiguous_fill_stops_at_edges() {
+		let mask = flood_fill_mask(&divided_image(), IVec2::ZERO, 0.1, true, false);
+		let filled: Vec<_> = mask.iter().map(|&coverage| coverage == 1.).collect();
+
+		assert!(filled[0] && filled[1] && filled[5 + 1]);
+		assert!(!filled[2] && !filled[3] && !filled[2 * 5 + 4]);
+	}
+
+	#[test]
+	fn global_fill_and_tolerance() {
+		let mut image = divided_image();
+		image.data[4] = Color::from_rgbaf32_unchecked(0.2, 0.2, 0.2, 1.);
+
+		let global = flood_fill_mask(&image, IVec2::ZERO, 0.1, false, false);
+		assert_eq!(global[3], 1.);
+		assert_eq!(global[2], 0.);
+		assert_eq!(global[4], 0.);
+
+		let tolerant = flood_fill_mask(&image, IVec2::ZERO, 0.25, false, false);
+		assert_eq!(tolerant[4], 1.);
+	}
+
+	#[test]
+	fn anti_aliasing_softens_the_boundary() {
+		let mask = flood_fill_mask(&divided_image(), IVec2::ZERO, 0.1, true, true);
+
+		assert_eq!(mask[5], 1.);
+		assert!(mask[5 + 1] > 0. && mask[5 + 1] < 1., "{}", mask[5 + 1]);
+		assert!(mask[5 + 2] > 0. && mask[5 + 2] < 1., "{}", mask[5 + 2]);
+		assert_eq!(mask[5 + 3], 0.);
+	}
+
+	#[test]
+	fn fill_samples_layers_below() {
+		// A transparent image above the divided image only sees the edge when sampling the layer beneath it
+		let transform = DAffine2::from_scale(DVec2::new(5., 3.));
+		let mut below = ImageFrameTable::new(divided_image());
+		*below.transform_mut() = transform;
+		let layers_below = GraphicGroupTable::new(GraphicElement::RasterFrame(RasterFrame::ImageFrame(below)));
+
+		let mut image_frame = ImageFrameTable::new(Image::new(5, 3, Color::TRANSPARENT));
+		*image_frame.transform_mut() = transform;
+
+		let alone = flood_fill((), image_frame.clone(), DVec2::new(0.5, 0.5), Color::RED, 10., true, false, GraphicGroupTable::default());
+		assert!(alone.one_instance().instance.data.iter().all(|&pixel| pixel == Color::RED));
+
+		let sampled = flood_fill((), image_frame, DVec2::new(0.5, 0.5), Color::RED, 10., true, false, layers_below);
+		let data = &sampled.one_instance().instance.data;
+		assert_eq!(data[1], Color::RED);
+		assert_eq!(data[3], Color::TRANSPARENT);
+	}
+}
@@ -0,0 +1,260 @@
+use glam::{DAffine2, DVec2, IVec2};
+use graphene_core::raster::image::{Image, ImageFrameTable};
+use graphene_core::transform::Transform;
+use graphene_core::{Color, Ctx};
+
+/// Running sums of the premultiplied channels over a region of an image, for finding the mean color of any box inside it in constant time.
+pub struct SummedAreaTable {
+	origin: IVec2,
+	size: IVec2,
+	sums: Vec<[f64; 4]>,
+}
+
+impl SummedAreaTable {
+	/// Sums the pixels of the half-open box `[min, max)` of the image, clamped to its bounds.
+	pub fn new(image: &Image<Color>, min: IVec2, max: IVec2) -> Self {
+		let image_size = IVec2::new(image.width as i32, image.height as i32);
+		let origin = min.clamp(IVec2::ZERO, image_size);
+		let size = max.clamp(origin, image_size) - origin;
+
+		let stride = size.x as usize + 1;
+		let mut sums = vec![[0.; 4]; stride * (size.y as usize + 1)];
+		for y in 0..size.y as usize {
+			let mut row = [0.; 4];
+			for x in 0..size.x as usize {
+				let pixel = image.data[(origin.y as usize + y) * image.width as usize + origin.x as usize + x];
+				let channels = [pixel.r(), pixel.g(), pixel.b(), pixel.a()];
+				for (channel, value) in channels.into_iter().enumerate() {
+					row[channel] += value as f64;
+					sums[(y + 1) * stride + x + 1][channel] = sums[y * stride + x + 1][channel] + row[channel];
+				}
+			}
+		}
+
+		Self { origin, size, sums }
+	}
+
+	/// The mean color of the part of the half-open box `[min, max)` covered by the table, if there is any.
+	pub fn mean(&self, min: IVec2, max: IVec2) -> Option<[f64; 4]> {
+		let min = (min - self.origin).clamp(IVec2::ZERO, self.size);
+		let max = (max - self.origin).clamp(min, self.size);
+		let area = (max - min).element_product();
+		if area == 0 {
+			return None;
+		}
+
+		let stride = self.size.x as usize + 1;
+		let sum = |x: i32, y: i32| self.sums[y as usize * stride + x as usize];
+		let (bottom_right, bottom_left, top_right, top_left) = (sum(max.x, max.y), sum(min.x, max.y), sum(max.x, min.y), sum(min.x, min.y));
+		Some(std::array::from_fn(|channel| {
+			(bottom_right[channel] - bottom_left[channel] - top_right[channel] + top_left[channel]) / area as f64
+		}))
+	}
+}
+
+/// Adds a premultiplied color difference to a pixel, keeping the result a valid premultiplied color.
+pub fn shift_color(pixel: Color, difference: [f64; 4]) -> Color {
+	let alpha = (pixel.a() as f64 + difference[3]).clamp(0., 1.);
+	let channel = |value: f32, channel: usize| (value as f64 + difference[channel]).clamp(0., alpha) as f32;
+	Color::from_rgbaf32_unchecked(channel(pixel.r(), 0), channel(pixel.g(), 1), channel(pixel.b(), 2), alpha as f32)
+}
+
+/// Takes the texture of a pixel copied from `source` but the local luminance and color of `destination`,
+/// by shifting it with the difference between the mean colors of the boxes of the given radius around both.
+pub fn heal_pixel(pixel: Color, destination: IVec2, source: IVec2, radius: i32, destination_table: &SummedAreaTable, source_table: &SummedAreaTable) -> Color {
+	let radius = IVec2::splat(radius);
+	let (Some(destination_mean), Some(source_mean)) = (
+		destination_table.mean(destination - radius, destination + radius + 1),
+		source_table.mean(source - radius, source + radius + 1),
+	) else {
+		return pixel;
+	};
+
+	shift_color(pixel, std::array::from_fn(|channel| destination_mean[channel] - source_mean[channel]))
+}
+
+/// The integer pixel bounds, as a half-open box, covering the unit square mapped by `transform` into pixel space.
+pub fn pixel_bounds(transform: DAffine2) -> (IVec2, IVec2) {
+	let corners = [DVec2::ZERO, DVec2::X, DVec2::Y, DVec2::ONE].map(|corner| transform.transform_point2(corner));
+	let min = corners.into_iter().reduce(DVec2::min).unwrap_or_default();
+	let max = corners.into_iter().reduce(DVec2::max).unwrap_or_default();
+	(min.floor().as_ivec2(), max.ceil().as_ivec2())
+}
+
+/// Whether the point is inside the polygon, by the even-odd rule.
+pub fn polygon_contains(polygon: &[DVec2], point: DVec2) -> bool {
+	let mut inside = false;
+	for (index, &start) in polygon.iter().enumerate() {
+		let end = polygon[(index + 1) % polygon.len()];
+		if (start.y > point.y) != (end.y > point.y) && point.x < start.x + (point.y - start.y) / (end.y - start.y) * (end.x - start.x) {
+			inside = !inside;
+		}
+	}
+	inside
+}
+
+/// Replaces the pixels inside the region polygon with those found at the offset from them, blended seamlessly into the pixels around the region.
+///
+/// The color difference between the pixels bordering the region and those bordering the source region is smoothly interpolated across the region
+/// (by solving Laplace's equation) and added to the copied pixels, so the patch keeps the texture of the source but the shading of the destination.
+pub fn patch_image(image: &mut Image<Color>, region: &[DVec2], offset: DVec2) {
+	let image_size = IVec2::new(image.width as i32, image.height as i32);
+	if region.len() < 3 || image_size.min_element() == 0 {
+		return;
+	}
+
+	// The region with a one pixel border to hold the fixed differences surrounding it
+	let min = region.iter().copied().reduce(DVec2::min).unwrap_or_default().floor().as_ivec2() - 1;
+	let max = region.iter().copied().reduce(DVec2::max).unwrap_or_default().ceil().as_ivec2() + 2;
+	let (min, max) = (min.clamp(IVec2::ZERO, image_size), max.clamp(IVec2::ZERO, image_size));
+	let size = (max - min).max(IVec2::ZERO);
+	let (width, height) = (size.x as usize, size.y as usize);
+	if width == 0 || height == 0 {
+		return;
+	}
+
+	let pixel = |position: IVec2| image.data[position.y as usize * image.width as usize + position.x as usize];
+	let source_of = |position: IVec2| {
+		let source = (position.as_dvec2() + 0.5 + offset).floor().as_ivec2();
+		(source.cmpge(IVec2::ZERO).all() && source.cmplt(image_size).all()).then_some(source)
+	};
+
+	// Find the pixels to replace, and the color differences around them
+	let mut inside = vec![false; width * height];
+	let mut difference = vec![[0_f64; 4]; width * height];
+	for y in 0..height {
+		for x in 0..width {
+			let position = min + IVec2::new(x as i32, y as i32);
+			let Some(source) = source_of(position) else { continue };
+			if polygon_contains(region, position.as_dvec2() + 0.5) {
+				inside[y * width + x] = true;
+			} else {
+				let (destination, source) = (pixel(position), pixel(source));
+				difference[y * width + x] = [
+					(destination.r() - source.r()) as f64,
+					(destination.g() - source.g()) as f64,
+					(destination.b() - source.b()) as f64,
+					(destination.a() - source.a()) as f64,
+				];
+			}
+		}
+	}
+
+	if !inside.contains(&true) {
+		return;
+	}
+
+	// Start from the mean of the border differences so the result is close even before the relaxation converges
+	let neighbors = |index: usize| {
+		let (x, y) = (index % width, index / width);
+		[
+			(x > 0).then(|| index - 1),
+			(x + 1 < width).then(|| index + 1),
+			(y > 0).then(|| index - width),
+			(y + 1 < height).then(|| index + width),
+		]
+	};
+	let border: Vec<_> = (0..width * height)
+		.filter(|&index| !inside[index] && neighbors(index).into_iter().flatten().any(|neighbor| inside[neighbor]))
+		.collect();
+	let mut initial = [0.; 4];
+	for &index in &border {
+		for channel in 0..4 {
+			initial[channel] += difference[index][channel] / border.len().max(1) as f64;
+		}
+	}
+	for index in (0..width * height).filter(|&index| inside[index]) {
+		difference[index] = initial;
+	}
+
+	// Successive over-relaxation of the membrane spanning the border differences
+	const OVER_RELAXATION: f64 = 1.8;
+	let iterations = width.max(height).clamp(16, 256);
+	for _ in 0..iterations {
+		for index in 0..width * height {
+			if !inside[index] {
+				continue;
+			}
+			let mut sum = [0.; 4];
+			let mut count = 0.;
+			for neighbor in neighbors(index).into_iter().flatten() {
+				for channel in 0..4 {
+					sum[channel] += difference[neighbor][channel];
+				}
+				count += 1.;
+			}
+			for channel in 0..4 {
+				difference[index][channel] += OVER_RELAXATION * (sum[channel] / count - difference[index][channel]);
+			}
+		}
+	}
+
+	let patched: Vec<_> = (0..width * height)
+		.filter(|&index| inside[index])
+		.filter_map(|index| {
+			let position = min + IVec2::new((index % width) as i32, (index / width) as i32);
+			Some((position, shift_color(pixel(source_of(position)?), difference[index])))
+		})
+		.collect();
+	for (position, color) in patched {
+		image.data[position.y as usize * image.width as usize + position.x as usize] = color;
+	}
+}
+
+/// Fills a region of the image from elsewhere in it, matching the shading around the region like the Patch tool. The region polygon and offset are in layer space.
+#[node_macro::node(category("Raster"))]
+fn patch(_: impl Ctx, mut image_frame: ImageFrameTable<Color>, region: Vec<DVec2>, offset: DVec2) -> ImageFrameTable<Color> {
+	let transform = image_frame.transform();
+	if transform.matrix2.determinant() == 0. {
+		return image_frame;
+	}
+
+	let image = image_frame.one_instance_mut().instance;
+	let layer_to_pixel = DAffine2::from_scale(DVec2::new(image.width as f64, image.height as f64)) * transform.inverse();
+	let pixel_region: Vec<_> = region.iter().map(|&point| layer_to_pixel.transform_point2(point)).collect();
+	let pixel_offset = layer_to_pixel.transform_vector2(offset);
+
+	patch_image(image, &pixel_region, pixel_offset);
+	image_frame
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn summed_area_table_mean() {
+		let mut image = Image::new(4, 4, Color::BLACK);
+		image.data[5] = Color::WHITE;
+		let table = SummedAreaTable::new(&image, IVec2::ZERO, IVec2::splat(4));
+
+		assert_eq!(table.mean(IVec2::ONE, IVec2::splat(2)), Some([1.; 4]));
+		assert_eq!(table.mean(IVec2::ZERO, IVec2::splat(2)), Some([0.25, 0.25, 0.25, 1.]));
+		// Boxes are clipped to the image
+		assert_eq!(table.mean(IVec2::splat(-2), IVec2::splat(2)), Some([0.25, 0.25, 0.25, 1.]));
+		assert_eq!(table.mean(IVec2::splat(5), IVec2::splat(6)), None);
+	}
+
+	#[test]
+	fn patch_matches_surrounding_shading() {
+		// A flat gray image with a dark blemish, and a brighter copy of its texture to the right
+		let gray = Color::from_rgbaf32_unchecked(0.5, 0.5, 0.5, 1.);
+		let mut image = Image::new(40, 20, gray);
+		for y in 0..20 {
+			for x in 20..40 {
+				image.data[y * 40 + x] = Color::from_rgbaf32_unchecked(0.7, 0.7, 0.7, 1.);
+			}
+		}
+		image.data[10 * 40 + 10] = Color::BLACK;
+
+		let region = [DVec2::new(6., 6.), DVec2::new(14., 6.), DVec2::new(14., 14.), DVec2::new(6., 14.)];
+		patch_image(&mut image, &region, DVec2::new(20., 0.));
+
+		// The blemish takes the texture of the source but keeps the shading around it
+		let patched = image.data[10 * 40 + 10];
+		assert!((patched.r() - 0.5).abs() < 1e-3, "{patched:?}");
+		assert_eq!(patched.a(), 1.);
+		// Pixels outside the region are left untouched
+		assert_eq!(image.data[2 * 40 + 2], gray);
+	}
+}
@@ -3,11 +3,16 @@ pub mod any;
 pub mod gpu_nodes;
 pub mod http;
 pub mod raster;
+pub mod raw;
 pub mod text;
+pub mod tiff;
 pub mod vector;
 pub use graphene_core::*;
 pub mod brush;
 pub mod dehaze;
+pub mod flood_fill;
+pub mod healing;
 pub mod image_color_palette;
+pub mod retouch;
 #[cfg(feature = "wasm")]
 pub mod wasm_application_io;
@@ -0,0 +1,76 @@
+use graph_craft::proto::types::SignedPercentage;
+use graphene_core::raster::image::{Image, ImageFrameTable};
+use graphene_core::raster::{RawColorSpace, RawDemosaicAlgorithm, RawHighlightRecovery, RawWhiteBalance};
+use graphene_core::{Color, Ctx};
+use rawkit::RawImage;
+use rawkit::demosaicing::DemosaicAlgorithm;
+use rawkit::develop::{DevelopSettings, HighlightRecovery, OutputColorSpace, WhiteBalance};
+use std::io::Cursor;
+use std::sync::Arc;
+
+/// Decodes a camera raw file and develops it into an image with 16 bits of precision per channel.
+///
+/// The image is kept in linear light, which is what the other raster nodes expect, unless "Linear Output" is disabled to encode it with the transfer function of the output color space.
+#[node_macro::node(category("Raster"))]
+fn develop_raw(
+	_: impl Ctx,
+	data: Arc<[u8]>,
+	demosaic_algorithm: RawDemosaicAlgorithm,
+	white_balance: RawWhiteBalance,
+	#[default(5500.)]
+	#[range((2000., 12000.))]
+	temperature: f64,
+	tint: SignedPercentage,
+	#[default(2.)] red_multiplier: f64,
+	#[default(1.)] green_multiplier: f64,
+	#[default(1.5)] blue_multiplier: f64,
+	#[range((-5., 5.))] exposure: f64,
+	highlight_recovery: RawHighlightRecovery,
+	output_color_space: RawColorSpace,
+	#[default(true)] linear_output: bool,
+) -> ImageFrameTable<Color> {
+	let Ok(raw_image) = RawImage::decode(&mut Cursor::new(data.as_ref())) else {
+		return ImageFrameTable::one_empty_image();
+	};
+
+	let settings = DevelopSettings {
+		demosaic_algorithm: match demosaic_algorithm {
+			RawDemosaicAlgorithm::Linear => DemosaicAlgorithm::Linear,
+			RawDemosaicAlgorithm::VariableNumberOfGradients => DemosaicAlgorithm::Vng,
+			RawDemosaicAlgorithm::AdaptiveHomogeneityDirected => DemosaicAlgorithm::Ahd,
+		},
+		white_balance: match white_balance {
+			RawWhiteBalance::AsShot => WhiteBalance::AsShot,
+			RawWhiteBalance::Temperature => WhiteBalance::Temperature { temperature, tint },
+			RawWhiteBalance::Custom => WhiteBalance::Custom([red_multiplier, green_multiplier, blue_multiplier]),
+		},
+		exposure,
+		highlight_recovery: match highlight_recovery {
+			RawHighlightRecovery::Clip => HighlightRecovery::Clip,
+			RawHighlightRecovery::Unclip => HighlightRecovery::Unclip,
+			RawHighlightRecovery::Blend => HighlightRecovery::Blend,
+		},
+		output_color_space: match output_color_space {
+			RawColorSpace::Srgb => OutputColorSpace::Srgb,
+			RawColorSpace::AdobeRgb => OutputColorSpace::AdobeRgb,
+			RawColorSpace::ProPhotoRgb => OutputColorSpace::ProPhotoRgb,
+			RawColorSpace::Camera => OutputColorSpace::Camera,
+		},
+		linear: linear_output,
+	};
+
+	let developed = raw_image.develop(&settings);
+	let to_channel = |value: u16| value as f32 / u16::MAX as f32;
+	let image = Image {
+		data: developed
+			.data
+			.chunks_exact(3)
+			.map(|pixel| Color::from_rgbaf32_unchecked(to_channel(pixel[0]), to_channel(pixel[1]), to_channel(pixel[2]), 1.))
+			.collect(),
+		width: developed.width as u32,
+		height: developed.height as u32,
+		..Default::default()
+	};
+
+	ImageFrameTable::new(image)
+}
@@ -0,0 +1,252 @@
+use crate::brush::{brush_stamp_generator, brush_stroke_mask};
+use crate::healing::pixel_bounds;
+use glam::{DAffine2, DVec2, IVec2};
+use graph_craft::proto::types::{Percentage, PixelLength, SignedPercentage};
+use graphene_core::raster::Sample;
+use graphene_core::raster::image::{Image, ImageFrameTable};
+use graphene_core::transform::Transform;
+use graphene_core::vector::brush_stroke::BrushStroke;
+use graphene_core::{Color, Ctx};
+
+/// Approximates a Gaussian blur of the given pixel radius with three passes of a box blur in each direction, clamping at the image edges.
+pub fn blur_image(image: &Image<Color>, radius: usize) -> Image<Color> {
+	let mut blurred = image.clone();
+	let (width, height) = (image.width as usize, image.height as usize);
+	if radius == 0 || width == 0 || height == 0 {
+		return blurred;
+	}
+
+	let mut line = Vec::new();
+	let mut blur_line = |pixels: &mut [Color], stride: usize, length: usize| {
+		line.clear();
+		line.extend((0..length).map(|index| pixels[index * stride]));
+
+		let at = |index: isize| line[index.clamp(0, length as isize - 1) as usize];
+		let mut sum = [0.; 4];
+		for index in -(radius as isize)..=radius as isize {
+			let color = at(index);
+			for (channel, value) in [color.r(), color.g(), color.b(), color.a()].into_iter().enumerate() {
+				sum[channel] += value;
+			}
+		}
+
+		let count = (2 * radius + 1) as f32;
+		for index in 0..length {
+			pixels[index * stride] = Color::from_rgbaf32_unchecked(sum[0] / count, sum[1] / count, sum[2] / count, sum[3] / count);
+
+			let (entering, leaving) = (at((index + radius + 1) as isize), at(index as isize - radius as isize));
+			sum[0] += entering.r() - leaving.r();
+			sum[1] += entering.g() - leaving.g();
+			sum[2] += entering.b() - leaving.b();
+			sum[3] += entering.a() - leaving.a();
+		}
+	};
+
+	for _ in 0..3 {
+		for row in blurred.data.chunks_mut(width) {
+			blur_line(row, 1, width);
+		}
+		for column in 0..width {
+			blur_line(&mut blurred.data[column..], width, height);
+		}
+	}
+
+	blurred
+}
+
+/// Drags the colors under each stroke along its path, picking up the colors passed over as it goes.
+pub fn smudge_image(image: &mut Image<Color>, strokes: &[BrushStroke], transform: DAffine2, strength: f32) {
+	let (width, height) = (image.width as usize, image.height as usize);
+	if width == 0 || height == 0 || transform.matrix2.determinant() == 0. {
+		return;
+	}
+
+	let pixel_to_layer = transform * DAffine2::from_scale(DVec2::new(1. / width as f64, 1. / height as f64));
+	let layer_to_pixel = pixel_to_layer.inverse();
+	let area = pixel_to_layer.transform_vector2(DVec2::ONE);
+	let image_size = IVec2::new(width as i32, height as i32);
+
+	for stroke in strokes {
+		let stamp = brush_stamp_generator(stroke.style.diameter, Color::WHITE, stroke.style.hardness, stroke.style.flow);
+
+		// The pixels around the stamp, relative to the pixel its center falls in
+		let (min, max) = pixel_bounds(layer_to_pixel * stamp.transform());
+		let (min, max) = (min - 1, max + 1);
+		let window_width = (max.x - min.x) as usize;
+		let mut carried_colors: Option<Vec<Option<Color>>> = None;
+
+		for position in stroke.compute_blit_points() {
+			let center = layer_to_pixel.transform_point2(position).floor().as_ivec2();
+			let window = (min.y..max.y).flat_map(|y| (min.x..max.x).map(move |x| IVec2::new(x, y)));
+			let index_of = |offset: IVec2| {
+				let pixel = center + offset;
+				(pixel.cmpge(IVec2::ZERO).all() && pixel.cmplt(image_size).all()).then(|| pixel.y as usize * width + pixel.x as usize)
+			};
+
+			// The first stamp only picks up the colors to smudge
+			let Some(carried) = &mut carried_colors else {
+				carried_colors = Some(window.map(|offset| index_of(offset).map(|index| image.data[index])).collect());
+				continue;
+			};
+
+			for offset in window {
+				let Some(index) = index_of(offset) else { continue };
+				let carried_color = &mut carried[(offset.y - min.y) as usize * window_width + (offset.x - min.x) as usize];
+
+				let pixel = center + offset;
+				let layer_position = pixel_to_layer.transform_point2(pixel.as_dvec2());
+				let weight = stamp.sample(layer_position - position, area).map_or(0., |coverage| coverage.a() * strength);
+
+				let color = match *carried_color {
+					Some(carried_color) => image.data[index].lerp(&carried_color, weight.clamp(0., 1.)),
+					None => image.data[index],
+				};
+				image.data[index] = color;
+				*carried_color = Some(color);
+			}
+		}
+	}
+}
+
+/// Blurs the image by the given radius in pixels, approximating a Gaussian blur.
+#[node_macro::node(category("Raster"))]
+fn blur(_: impl Ctx, mut image_frame: ImageFrameTable<Color>, #[default(4.)] radius: PixelLength) -> ImageFrameTable<Color> {
+	let image = image_frame.one_instance_mut().instance;
+	*image = blur_image(image, radius.max(0.).round() as usize);
+
+	image_frame
+}
+
+/// Blends from the image to the adjusted version of it where the brush strokes have painted, so an adjustment like a blur or an exposure change only applies to the painted areas.
+///
+/// Negative amounts push the pixels away from the adjusted image instead, which sharpens them when the adjusted image is blurred.
+#[node_macro::node(category("Raster"))]
+fn masked_blend(
+	_: impl Ctx,
+	mut image_frame: ImageFrameTable<Color>,
+	adjusted: ImageFrameTable<Color>,
+	strokes: Vec<BrushStroke>,
+	#[default(100.)] amount: SignedPercentage,
+) -> ImageFrameTable<Color> {
+	let transform = image_frame.transform();
+	let amount = (amount / 100.) as f32;
+	let image = image_frame.one_instance_mut().instance;
+	let adjusted = adjusted.one_instance().instance;
+	if (adjusted.width, adjusted.height) != (image.width, image.height) {
+		return image_frame;
+	}
+
+	let mask = brush_stroke_mask(&strokes, image.width, image.height, transform);
+	for ((pixel, adjusted), coverage) in image.data.iter_mut().zip(&adjusted.data).zip(mask) {
+		let weight = coverage * amount;
+		if weight == 0. {
+			continue;
+		}
+
+		// Pushing away from the adjusted pixel can leave the valid range, which for premultiplied channels ends at the alpha
+		let blend = |original: f32, adjusted: f32| original + (adjusted - original) * weight;
+		let alpha = blend(pixel.a(), adjusted.a()).clamp(0., 1.);
+		let channel = |original: f32, adjusted: f32| blend(original, adjusted).clamp(0., alpha);
+		*pixel = Color::from_rgbaf32_unchecked(channel(pixel.r(), adjusted.r()), channel(pixel.g(), adjusted.g()), channel(pixel.b(), adjusted.b()), alpha);
+	}
+
+	image_frame
+}
+
+/// Drags the colors of the image along the brush strokes, leaving the original pixels untouched so the strokes stay editable.
+#[node_macro::node(category("Raster"))]
+fn smudge(_: impl Ctx, mut image_frame: ImageFrameTable<Color>, strokes: Vec<BrushStroke>, #[default(50.)] strength: Percentage) -> ImageFrameTable<Color> {
+	let transform = image_frame.transform();
+	let image = image_frame.one_instance_mut().instance;
+	smudge_image(image, &strokes, transform, (strength / 100.) as f32);
+
+	image_frame
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graphene_core::transform::TransformMut;
+	use graphene_core::vector::brush_stroke::{BrushInputSample, BrushStyle};
+
+	fn stroke(trace: &[DVec2], diameter: f64) -> BrushStroke {
+		BrushStroke {
+			style: BrushStyle {
+				diameter,
+				hardness: 0.,
+				flow: 100.,
+				spacing: 10.,
+				..Default::default()
+			},
+			trace: trace.iter().map(|&position| BrushInputSample { position }).collect(),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn blur_softens_edges() {
+		let mut image = Image::new(8, 1, Color::BLACK);
+		for pixel in &mut image.data[4..] {
+			*pixel = Color::WHITE;
+		}
+
+		let blurred = blur_image(&image, 1);
+		let values: Vec<_> = blurred.data.iter().map(|pixel| pixel.r()).collect();
+		assert!(values.windows(2).all(|pair| pair[0] <= pair[1]), "{values:?}");
+		assert!(values[3] > 0. && values[4] < 1., "{values:?}");
+		// Flat areas stay as they were
+		assert!(values[0] < 1e-6 && values[7] > 1. - 1e-6, "{values:?}");
+	}
+
+	#[test]
+	fn stroke_mask_covers_stamps() {
+		// One pixel per layer space unit
+		let transform = DAffine2::from_scale(DVec2::splat(20.));
+		let mask = brush_stroke_mask(&[stroke(&[DVec2::splat(10.)], 8.)], 20, 20, transform);
+
+		// The soft stamp fades out towards its edge
+		assert!(mask[10 * 20 + 10] > 0.8, "{}", mask[10 * 20 + 10]);
+		assert!(mask[10 * 20 + 12] < mask[10 * 20 + 10], "{}", mask[10 * 20 + 12]);
+		assert_eq!(mask[0], 0.);
+		assert_eq!(mask[10 * 20 + 15], 0.);
+	}
+
+	#[test]
+	fn masked_blend_only_changes_painted_pixels() {
+		let gray = Color::from_rgbaf32_unchecked(0.5, 0.5, 0.5, 1.);
+		let mut image_frame = ImageFrameTable::new(Image::new(20, 20, gray));
+		*image_frame.transform_mut() = DAffine2::from_scale(DVec2::splat(20.));
+		let adjusted = ImageFrameTable::new(Image::new(20, 20, Color::WHITE));
+		let strokes = [stroke(&[DVec2::splat(10.)], 8.)];
+
+		let blended = masked_blend((), image_frame.clone(), adjusted.clone(), strokes.to_vec(), 100.);
+		let blended = &blended.one_instance().instance.data;
+		assert!(blended[10 * 20 + 10].r() > 0.9, "{:?}", blended[10 * 20 + 10]);
+		assert_eq!(blended[0], gray);
+
+		// A negative amount pushes the pixels the other way, without leaving the valid range
+		let pushed = masked_blend((), image_frame, adjusted, strokes.to_vec(), -100.);
+		let pushed = &pushed.one_instance().instance.data;
+		assert!(pushed[10 * 20 + 10].r() < 0.1 && pushed[10 * 20 + 10].r() >= 0., "{:?}", pushed[10 * 20 + 10]);
+		assert_eq!(pushed[10 * 20 + 10].a(), 1.);
+		assert_eq!(pushed[0], gray);
+	}
+
+	#[test]
+	fn smudge_drags_colors_along_stroke() {
+		let mut image = Image::new(20, 5, Color::BLACK);
+		for y in 0..5 {
+			for pixel in &mut image.data[y * 20 + 10..(y + 1) * 20] {
+				*pixel = Color::WHITE;
+			}
+		}
+
+		let transform = DAffine2::from_scale(DVec2::new(20., 5.));
+		smudge_image(&mut image, &[stroke(&[DVec2::new(5., 2.5), DVec2::new(15., 2.5)], 4.)], transform, 1.);
+
+		// The black pixels got dragged into the white half, but not outside the stroke
+		assert!(image.data[2 * 20 + 12].r() < 0.9, "{:?}", image.data[2 * 20 + 12]);
+		assert_eq!(image.data[2 * 20 + 19], Color::WHITE);
+		assert_eq!(image.data[4 * 20 + 12], Color::WHITE);
+	}
+}
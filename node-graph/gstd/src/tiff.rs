@@ -0,0 +1,63 @@
+use graphene_core::Color;
+use graphene_core::raster::image::Image;
+use rawkit::tiff::TiffError;
+use rawkit::tiff::values::Transform;
+use rawkit::tiff::writer::{TiffAlpha, TiffImage, TiffSample, write_tiff};
+
+pub use rawkit::tiff::writer::{TiffCompression, TiffLayout, TiffWriteOptions};
+
+/// The precision of the samples in an exported TIFF file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TiffBitDepth {
+	/// 8-bit sRGB samples.
+	Eight,
+	/// 16-bit sRGB samples, which avoid the banding of 8-bit samples in smooth gradients.
+	#[default]
+	Sixteen,
+	/// 32-bit float samples in linear light, which also keep the values outside of the 0 to 1 range.
+	Float,
+}
+
+/// Encodes an image as an RGBA TIFF file, keeping more precision than the 8-bit PNG export.
+///
+/// Integer samples are gamma encoded with straight alpha, as most applications expect, while float samples stay linear and premultiplied like the colors in Graphite.
+pub fn encode_tiff(image: &Image<Color>, bit_depth: TiffBitDepth, options: &TiffWriteOptions) -> Result<Vec<u8>, TiffError> {
+	let channels = |color: Color| [color.r(), color.g(), color.b(), color.a()];
+	let gamma_channels = || {
+		image
+			.data
+			.iter()
+			.flat_map(|color| channels(color.to_unassociated_alpha().to_gamma_srgb()))
+			.map(|channel| channel.clamp(0., 1.))
+	};
+
+	match bit_depth {
+		TiffBitDepth::Eight => {
+			let data: Vec<u8> = gamma_channels().map(|channel| (channel * u8::MAX as f32).round() as u8).collect();
+			encode(image, &data, TiffAlpha::Unassociated, options)
+		}
+		TiffBitDepth::Sixteen => {
+			let data: Vec<u16> = gamma_channels().map(|channel| (channel * u16::MAX as f32).round() as u16).collect();
+			encode(image, &data, TiffAlpha::Unassociated, options)
+		}
+		TiffBitDepth::Float => {
+			let data: Vec<f32> = image.data.iter().flat_map(|&color| channels(color)).collect();
+			encode(image, &data, TiffAlpha::Associated, options)
+		}
+	}
+}
+
+fn encode<T: TiffSample>(image: &Image<Color>, data: &[T], alpha: TiffAlpha, options: &TiffWriteOptions) -> Result<Vec<u8>, TiffError> {
+	let tiff_image = TiffImage {
+		data,
+		width: image.width as usize,
+		height: image.height as usize,
+		channels: 4,
+		alpha,
+		transform: Transform::Horizontal,
+	};
+
+	let mut output = Vec::new();
+	write_tiff(&mut output, &tiff_image, options)?;
+	Ok(output)
+}
@@ -1,13 +1,12 @@
 use bezier_rs::{ManipulatorGroup, Subpath};
 use glam::{DAffine2, DVec2};
 use graphene_core::transform::Transform;
-use graphene_core::transform::TransformMut;
 use graphene_core::vector::misc::BooleanOperation;
 use graphene_core::vector::style::Fill;
 pub use graphene_core::vector::*;
 use graphene_core::{Color, Ctx, GraphicElement, GraphicGroupTable};
 pub use path_bool as path_bool_lib;
-use path_bool::{FillRule, PathBooleanOperation};
+use path_bool::{FillRule, PathBooleanOperation, PathFragment};
 use std::ops::Mul;
 
 #[node_macro::node(category(""))]
@@ -61,152 +60,135 @@ async fn boolean_operation(_: impl Ctx, group_of_paths: GraphicGroupTable, opera
 			.collect()
 	}
 
-	fn subtract<'a>(vector_data: impl Iterator<Item = &'a VectorDataTable>) -> VectorDataTable {
-		let mut vector_data = vector_data.into_iter();
-		let mut result = vector_data.next().cloned().unwrap_or_default();
-		let mut next_vector_data = vector_data.next();
-
-		while let Some(lower_vector_data) = next_vector_data {
-			let transform_of_lower_into_space_of_upper = result.transform().inverse() * lower_vector_data.transform();
-
-			let result = result.one_instance_mut().instance;
-
-			let upper_path_string = to_path(result, DAffine2::IDENTITY);
-			let lower_path_string = to_path(lower_vector_data.one_instance().instance, transform_of_lower_into_space_of_upper);
-
-			#[allow(unused_unsafe)]
-			let boolean_operation_string = unsafe { boolean_subtract(upper_path_string, lower_path_string) };
-			let boolean_operation_result = from_path(&boolean_operation_string);
-
-			result.colinear_manipulators = boolean_operation_result.colinear_manipulators;
-			result.point_domain = boolean_operation_result.point_domain;
-			result.segment_domain = boolean_operation_result.segment_domain;
-			result.region_domain = boolean_operation_result.region_domain;
+	fn boolean_operation_on_vector_data(vector_data_table: &[VectorDataTable], boolean_operation: BooleanOperation) -> VectorDataTable {
+		let Some(topmost) = vector_data_table.last() else { return VectorDataTable::default() };
 
-			next_vector_data = vector_data.next();
+		match boolean_operation {
+			// The result takes the style of the topmost vector data
+			BooleanOperation::Union => paths_to_vector_data(boolean_operation_on_paths(vector_data_table, PathBooleanOperation::Union), topmost),
+			BooleanOperation::Intersect => paths_to_vector_data(boolean_operation_on_paths(vector_data_table, PathBooleanOperation::Intersection), topmost),
+			// Everything above the bottommost vector data is subtracted from it
+			BooleanOperation::SubtractFront => paths_to_vector_data(boolean_operation_on_paths(vector_data_table, PathBooleanOperation::Difference), &vector_data_table[0]),
+			// Everything below the topmost vector data is subtracted from it
+			BooleanOperation::SubtractBack => {
+				let reversed: Vec<_> = vector_data_table.iter().rev().cloned().collect();
+				paths_to_vector_data(boolean_operation_on_paths(&reversed, PathBooleanOperation::Difference), topmost)
+			}
+			// Keep the areas covered by exactly one of the vector data
+			BooleanOperation::Difference => {
+				let fragments = boolean_fragments_on_vector_data(vector_data_table, PathBooleanOperation::Fracture);
+				let paths = fragments.into_iter().filter(|fragment| fragment.sources.len() == 1).map(|fragment| fragment.path).collect();
+				paths_to_vector_data(paths, topmost)
+			}
+			BooleanOperation::Divide => divide(vector_data_table),
+			BooleanOperation::Trim => trim(vector_data_table, false),
+			BooleanOperation::Merge => trim(vector_data_table, true),
 		}
-
-		result
 	}
 
-	fn boolean_operation_on_vector_data(vector_data_table: &[VectorDataTable], boolean_operation: BooleanOperation) -> VectorDataTable {
-		match boolean_operation {
-			BooleanOperation::Union => {
-				// Reverse vector data so that the result style is the style of the first vector data
-				let mut vector_data_table = vector_data_table.iter().rev();
-				let mut result_vector_data_table = vector_data_table.next().cloned().unwrap_or_default();
-
-				// Loop over all vector data and union it with the result
-				let default = VectorDataTable::default();
-				let mut second_vector_data = Some(vector_data_table.next().unwrap_or(&default));
-				while let Some(lower_vector_data) = second_vector_data {
-					let transform_of_lower_into_space_of_upper = result_vector_data_table.transform().inverse() * lower_vector_data.transform();
-
-					let result_vector_data = result_vector_data_table.one_instance_mut().instance;
-
-					let upper_path_string = to_path(result_vector_data, DAffine2::IDENTITY);
-					let lower_path_string = to_path(lower_vector_data.one_instance().instance, transform_of_lower_into_space_of_upper);
+	/// Performs the boolean operation on the paths of all of the vector data in a single pass, in document space.
+	fn boolean_fragments_on_vector_data(vector_data_table: &[VectorDataTable], operation: PathBooleanOperation) -> Vec<PathFragment> {
+		let paths: Vec<Path> = vector_data_table
+			.iter()
+			.map(|vector_data| to_path(vector_data.one_instance().instance, vector_data.transform()))
+			.collect();
+		path_bool_multiple_fragments(&paths, operation)
+	}
 
-					#[allow(unused_unsafe)]
-					let boolean_operation_string = unsafe { boolean_union(upper_path_string, lower_path_string) };
-					let boolean_operation_result = from_path(&boolean_operation_string);
+	fn boolean_operation_on_paths(vector_data_table: &[VectorDataTable], operation: PathBooleanOperation) -> Vec<Path> {
+		boolean_fragments_on_vector_data(vector_data_table, operation).into_iter().map(|fragment| fragment.path).collect()
+	}
 
-					result_vector_data.colinear_manipulators = boolean_operation_result.colinear_manipulators;
-					result_vector_data.point_domain = boolean_operation_result.point_domain;
-					result_vector_data.segment_domain = boolean_operation_result.segment_domain;
-					result_vector_data.region_domain = boolean_operation_result.region_domain;
+	/// Splits the vector data into all of its non-overlapping pieces, each styled like the topmost vector data covering it.
+	fn divide(vector_data_table: &[VectorDataTable]) -> VectorDataTable {
+		let fragments = boolean_fragments_on_vector_data(vector_data_table, PathBooleanOperation::Fracture)
+			.into_iter()
+			.filter_map(|fragment| Some((fragment.path, fragment.sources.last()?)))
+			.collect();
 
-					second_vector_data = vector_data_table.next();
-				}
+		fragments_to_vector_data(fragments, vector_data_table)
+	}
 
-				result_vector_data_table
-			}
-			BooleanOperation::SubtractFront => subtract(vector_data_table.iter()),
-			BooleanOperation::SubtractBack => subtract(vector_data_table.iter().rev()),
-			BooleanOperation::Intersect => {
-				let mut vector_data = vector_data_table.iter().rev();
-				let mut result = vector_data.next().cloned().unwrap_or_default();
-				let default = VectorDataTable::default();
-				let mut second_vector_data = Some(vector_data.next().unwrap_or(&default));
-
-				// For each vector data, set the result to the intersection of that data and the result
-				while let Some(lower_vector_data) = second_vector_data {
-					let transform_of_lower_into_space_of_upper = result.transform().inverse() * lower_vector_data.transform();
-
-					let result = result.one_instance_mut().instance;
-
-					let upper_path_string = to_path(result, DAffine2::IDENTITY);
-					let lower_path_string = to_path(lower_vector_data.one_instance().instance, transform_of_lower_into_space_of_upper);
-
-					#[allow(unused_unsafe)]
-					let boolean_operation_string = unsafe { boolean_intersect(upper_path_string, lower_path_string) };
-					let boolean_operation_result = from_path(&boolean_operation_string);
-
-					result.colinear_manipulators = boolean_operation_result.colinear_manipulators;
-					result.point_domain = boolean_operation_result.point_domain;
-					result.segment_domain = boolean_operation_result.segment_domain;
-					result.region_domain = boolean_operation_result.region_domain;
-					second_vector_data = vector_data.next();
+	/// Removes the parts of the vector data hidden behind the vector data above it, optionally merging the remaining parts which share a fill.
+	fn trim(vector_data_table: &[VectorDataTable], merge: bool) -> VectorDataTable {
+		// Each trimmed fragment only originates from the one vector data which is visible there
+		let mut fragments: Vec<(Path, usize)> = boolean_fragments_on_vector_data(vector_data_table, PathBooleanOperation::Trim)
+			.into_iter()
+			.filter_map(|fragment| Some((fragment.path, fragment.sources.last()?)))
+			.collect();
+
+		if merge {
+			let fill = |index: usize| vector_data_table[index].one_instance().instance.style.fill().clone();
+
+			// Group the fragments by fill, keeping the index of the topmost vector data in each group for its style
+			let mut groups: Vec<(Vec<Path>, usize)> = Vec::new();
+			for (path, index) in fragments {
+				match groups.iter_mut().find(|(_, group_index)| fill(*group_index) == fill(index)) {
+					Some((paths, group_index)) => {
+						paths.push(path);
+						*group_index = index.max(*group_index);
+					}
+					None => groups.push((vec![path], index)),
 				}
-
-				result
 			}
-			BooleanOperation::Difference => {
-				let mut vector_data_iter = vector_data_table.iter().rev();
-				let mut any_intersection = VectorDataTable::default();
-				let default = VectorDataTable::default();
-				let mut second_vector_data = Some(vector_data_iter.next().unwrap_or(&default));
 
-				// Find where all vector data intersect at least once
-				while let Some(lower_vector_data) = second_vector_data {
-					let all_other_vector_data = boolean_operation_on_vector_data(&vector_data_table.iter().filter(|v| v != &lower_vector_data).cloned().collect::<Vec<_>>(), BooleanOperation::Union);
-					let all_other_vector_data_instance = all_other_vector_data.one_instance();
+			fragments = groups
+				.into_iter()
+				.flat_map(|(paths, index)| {
+					path_bool_multiple_fragments(&paths, PathBooleanOperation::Union)
+						.into_iter()
+						.map(move |fragment| (fragment.path, index))
+				})
+				.collect();
+		}
 
-					let transform_of_lower_into_space_of_upper = all_other_vector_data.transform().inverse() * lower_vector_data.transform();
+		fragments_to_vector_data(fragments, vector_data_table)
+	}
 
-					let upper_path_string = to_path(all_other_vector_data_instance.instance, DAffine2::IDENTITY);
-					let lower_path_string = to_path(lower_vector_data.one_instance().instance, transform_of_lower_into_space_of_upper);
+	/// Creates a table with a single instance made of all the paths, styled like the given source vector data.
+	fn paths_to_vector_data(paths: Vec<Path>, source: &VectorDataTable) -> VectorDataTable {
+		let source = source.one_instance();
 
-					#[allow(unused_unsafe)]
-					let boolean_intersection_string = unsafe { boolean_intersect(upper_path_string, lower_path_string) };
-					let mut boolean_intersection_result = VectorDataTable::new(from_path(&boolean_intersection_string));
-					*boolean_intersection_result.transform_mut() = *all_other_vector_data_instance.transform;
+		let mut vector_data = from_path(&paths);
+		vector_data.style = source.instance.style.clone();
 
-					boolean_intersection_result.one_instance_mut().instance.style = all_other_vector_data_instance.instance.style.clone();
-					*boolean_intersection_result.one_instance_mut().alpha_blending = *all_other_vector_data_instance.alpha_blending;
+		let mut result = VectorDataTable::new(vector_data);
+		*result.one_instance_mut().alpha_blending = *source.alpha_blending;
+		result
+	}
 
-					let transform_of_lower_into_space_of_upper = boolean_intersection_result.one_instance_mut().transform.inverse() * any_intersection.transform();
+	/// Creates a table with an instance for each non-empty fragment, styled like the vector data it originates from.
+	fn fragments_to_vector_data(fragments: Vec<(Path, usize)>, vector_data_table: &[VectorDataTable]) -> VectorDataTable {
+		let mut result = VectorDataTable::empty();
 
-					let upper_path_string = to_path(boolean_intersection_result.one_instance_mut().instance, DAffine2::IDENTITY);
-					let lower_path_string = to_path(any_intersection.one_instance_mut().instance, transform_of_lower_into_space_of_upper);
+		for (path, index) in fragments.into_iter().filter(|(path, _)| !path.is_empty()) {
+			let source = vector_data_table[index].one_instance();
 
-					#[allow(unused_unsafe)]
-					let union_result = from_path(&unsafe { boolean_union(upper_path_string, lower_path_string) });
-					*any_intersection.one_instance_mut().instance = union_result;
+			let mut vector_data = from_path(&[path]);
+			vector_data.style = source.instance.style.clone();
 
-					*any_intersection.transform_mut() = boolean_intersection_result.transform();
-					any_intersection.one_instance_mut().instance.style = boolean_intersection_result.one_instance_mut().instance.style.clone();
-					any_intersection.one_instance_mut().alpha_blending = boolean_intersection_result.one_instance_mut().alpha_blending;
+			let instance = result.push(vector_data);
+			*instance.alpha_blending = *source.alpha_blending;
+		}
 
-					second_vector_data = vector_data_iter.next();
-				}
-				// Subtract the area where they intersect at least once from the union of all vector data
-				let union = boolean_operation_on_vector_data(vector_data_table, BooleanOperation::Union);
-				boolean_operation_on_vector_data(&[union, any_intersection], BooleanOperation::SubtractFront)
-			}
+		if result.is_empty() {
+			result.push(VectorData::empty());
 		}
+
+		result
 	}
 
 	// The first index is the bottom of the stack
 	let mut result_vector_data_table = boolean_operation_on_vector_data(&flatten_vector_data(&group_of_paths), operation);
 
 	// Replace the transformation matrix with a mutation of the vector points themselves
-	let result_vector_data_table_transform = result_vector_data_table.transform();
-	*result_vector_data_table.transform_mut() = DAffine2::IDENTITY;
-	let result_vector_data = result_vector_data_table.one_instance_mut().instance;
-	VectorData::transform(result_vector_data, result_vector_data_table_transform);
-	result_vector_data.style.set_stroke_transform(DAffine2::IDENTITY);
-	result_vector_data.upstream_graphic_group = Some(group_of_paths.clone());
+	for result_instance in result_vector_data_table.instances_mut() {
+		let result_vector_data_transform = *result_instance.transform;
+		*result_instance.transform = DAffine2::IDENTITY;
+		VectorData::transform(result_instance.instance, result_vector_data_transform);
+		result_instance.instance.style.set_stroke_transform(DAffine2::IDENTITY);
+	}
+	result_vector_data_table.one_instance_mut().instance.upstream_graphic_group = Some(group_of_paths.clone());
 
 	result_vector_data_table
 }
@@ -214,12 +196,34 @@ async fn boolean_operation(_: impl Ctx, group_of_paths: GraphicGroupTable, opera
 fn to_path(vector: &VectorData, transform: DAffine2) -> Vec<path_bool::PathSegment> {
 	let mut path = Vec::new();
 	for subpath in vector.stroke_bezier_paths() {
-		to_path_segments(&mut path, &subpath, transform);
+		to_path_segments(&mut path, &subpath, transform, true);
 	}
 	path
 }
 
-fn to_path_segments(path: &mut Vec<path_bool::PathSegment>, subpath: &bezier_rs::Subpath<PointId>, transform: DAffine2) {
+/// Finds the region enclosed by the outlines which contains the point, for filling line art like the "live paint" of illustration software.
+/// Open outlines are left open, so crossing lines can enclose a region as well as closed shapes. The outlines are given with their transforms to the space of the point.
+pub fn enclosed_region<'a>(outlines: impl IntoIterator<Item = (&'a Subpath<PointId>, DAffine2)>, point: DVec2) -> Option<Vec<Subpath<PointId>>> {
+	let paths: Vec<Path> = outlines
+		.into_iter()
+		.map(|(subpath, transform)| {
+			let mut path = Vec::new();
+			to_path_segments(&mut path, subpath, transform, subpath.closed());
+			path
+		})
+		.collect();
+	let paths: Vec<_> = paths.iter().collect();
+
+	match path_bool::enclosing_face(&paths, point) {
+		Ok(face) => Some(from_path(&[face?]).stroke_bezier_paths().collect()),
+		Err(err) => {
+			log::error!("Finding the enclosed region failed with: {err}");
+			None
+		}
+	}
+}
+
+fn to_path_segments(path: &mut Vec<path_bool::PathSegment>, subpath: &bezier_rs::Subpath<PointId>, transform: DAffine2, close: bool) {
 	use path_bool::PathSegment;
 	let mut global_start = None;
 	let mut global_end = DVec2::ZERO;
@@ -239,7 +243,7 @@ fn to_path_segments(path: &mut Vec<path_bool::PathSegment>, subpath: &bezier_rs:
 		};
 		path.push(segment);
 	}
-	if let Some(start) = global_start {
+	if let Some(start) = global_start.filter(|_| close) {
 		path.push(PathSegment::Line(global_end, start));
 	}
 }
@@ -337,26 +341,31 @@ pub fn convert_usvg_path(path: &usvg::Path) -> Vec<Subpath<PointId>> {
 
 type Path = Vec<path_bool::PathSegment>;
 
-fn boolean_union(a: Path, b: Path) -> Vec<Path> {
-	path_bool(a, b, PathBooleanOperation::Union)
+fn path_bool(a: Path, b: Path, op: PathBooleanOperation) -> Vec<Path> {
+	path_bool_multiple_fragments(&[a, b], op).into_iter().map(|fragment| fragment.path).collect()
 }
 
-fn path_bool(a: Path, b: Path, op: PathBooleanOperation) -> Vec<Path> {
-	match path_bool::path_boolean(&a, FillRule::NonZero, &b, FillRule::NonZero, op) {
-		Ok(results) => results,
+fn path_bool_multiple_fragments(paths: &[Path], op: PathBooleanOperation) -> Vec<PathFragment> {
+	let inputs: Vec<_> = paths.iter().map(|path| (path, FillRule::NonZero)).collect();
+	let error = match path_bool::path_boolean_multiple_fragments(&inputs, op) {
+		Ok(results) => return results,
+		Err(e) => e,
+	};
+
+	// Degenerate input is repaired by the robust mode, which is only used as a fallback since it modifies the inputs
+	match path_bool::path_boolean_robust(&inputs, op, &path_bool::RobustOptions::default()) {
+		Ok((results, report)) => {
+			log::debug!("Repaired the inputs of the boolean operation {op:?} after {error:?}: {:?}", report.repairs);
+			results
+		}
 		Err(e) => {
-			let a_path = path_bool::path_to_path_data(&a, 0.001);
-			let b_path = path_bool::path_to_path_data(&b, 0.001);
-			log::error!("Boolean error {e:?} encountered while processing {a_path}\n {op:?}\n {b_path}");
+			let paths = paths.iter().map(|path| path_bool::path_to_path_data(path, 0.001)).collect::<Vec<_>>().join("\n ");
+			log::error!("Boolean error {e:?} encountered while processing {op:?} on\n {paths}");
 			Vec::new()
 		}
 	}
 }
 
-fn boolean_subtract(a: Path, b: Path) -> Vec<Path> {
-	path_bool(a, b, PathBooleanOperation::Difference)
-}
-
 pub fn boolean_intersect(a: Path, b: Path) -> Vec<Path> {
 	path_bool(a, b, PathBooleanOperation::Intersection)
 }
@@ -10,7 +10,7 @@ use graphene_core::instances::Instances;
 use graphene_core::raster::bbox::Bbox;
 use graphene_core::raster::image::{Image, ImageFrameTable};
 use graphene_core::renderer::RenderMetadata;
-use graphene_core::renderer::{GraphicElementRendered, RenderParams, RenderSvgSegmentList, SvgRender, format_transform_matrix};
+use graphene_core::renderer::{GraphicElementRendered, PdfFillRule, PdfPath, PdfRender, RenderParams, RenderSvgSegmentList, SvgRender, format_transform_matrix};
 use graphene_core::transform::Footprint;
 #[cfg(target_arch = "wasm32")]
 use graphene_core::transform::TransformMut;
@@ -114,6 +114,31 @@ fn render_svg(data: impl GraphicElementRendered, mut render: SvgRender, render_p
 	RenderOutputType::Svg(render.svg.to_svg_string())
 }
 
+fn render_pdf(data: impl GraphicElementRendered, render_params: RenderParams, footprint: Footprint) -> RenderOutputType {
+	let mut render = PdfRender::new();
+	let size = footprint.resolution.as_dvec2();
+
+	if data.contains_artboard() {
+		// Every artboard in the exported region becomes a page
+		render.set_export_bounds([glam::DVec2::ZERO, size]);
+		data.render_pdf(&mut render, footprint.transform, &render_params);
+	}
+
+	// Without artboards, or if none of them are in the exported region, the whole region becomes a single page like in the SVG export
+	if render.page_count() == 0 {
+		render.begin_page(size);
+		if !data.contains_artboard() && !render_params.hide_artboards {
+			let mut background = PdfPath::default();
+			background.push_subpath(&bezier_rs::Subpath::<graphene_core::vector::PointId>::new_rect(glam::DVec2::ZERO, size), glam::DAffine2::IDENTITY);
+			render.fill(&background, glam::DAffine2::IDENTITY, Color::WHITE, PdfFillRule::NonZero);
+		}
+		data.render_pdf(&mut render, footprint.transform, &render_params);
+		render.end_page();
+	}
+
+	RenderOutputType::Pdf(render.finish())
+}
+
 #[cfg(feature = "vello")]
 #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
 async fn render_canvas(
@@ -272,6 +297,7 @@ async fn render<'a: 'n, T: 'n + GraphicElementRendered + WasmNotSend>(
 	let output_format = render_config.export_format;
 	let data = match output_format {
 		ExportFormat::Svg => render_svg(data, SvgRender::new(), render_params, footprint),
+		ExportFormat::Pdf => render_pdf(data, render_params, footprint),
 		ExportFormat::Canvas => {
 			if use_vello && editor_api.application_io.as_ref().unwrap().gpu_executor().is_some() {
 				#[cfg(all(feature = "vello", not(test)))]